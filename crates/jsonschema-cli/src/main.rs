@@ -1,4 +1,4 @@
-#![allow(clippy::print_stdout)]
+#![allow(clippy::print_stdout, clippy::print_stderr)]
 use std::{
     fs::File,
     io::BufReader,
@@ -6,11 +6,20 @@ use std::{
     process::ExitCode,
 };
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+mod validate;
 
 #[derive(Parser)]
-#[command(name = "jsonschema")]
+#[command(
+    name = "jsonschema",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// A path to a JSON instance (i.e. filename.json) to validate (may be specified multiple times).
     #[arg(short = 'i', long = "instance")]
     instances: Option<Vec<PathBuf>>,
@@ -24,6 +33,12 @@ struct Cli {
     version: bool,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Validate JSON files or newline-delimited JSON from stdin against a schema.
+    Validate(validate::ValidateArgs),
+}
+
 fn read_json(
     path: &Path,
 ) -> Result<serde_json::Result<serde_json::Value>, Box<dyn std::error::Error>> {
@@ -68,6 +83,17 @@ fn validate_instances(
 fn main() -> ExitCode {
     let config = Cli::parse();
 
+    if let Some(Command::Validate(args)) = config.command {
+        return match validate::run(&args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(error) => {
+                eprintln!("error: {error}");
+                ExitCode::from(2)
+            }
+        };
+    }
+
     if config.version {
         println!(concat!("Version: ", env!("CARGO_PKG_VERSION")));
         return ExitCode::SUCCESS;
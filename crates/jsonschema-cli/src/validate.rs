@@ -0,0 +1,318 @@
+//! The `validate` subcommand.
+use std::{
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::{Args, ValueEnum};
+use jsonschema::{diagnostics::Report, BasicOutput, Draft, Resource, Validator};
+use serde_json::Value;
+
+/// Root of the URIs that documents from `--registry` directories are available under.
+///
+/// Relative references in a schema without `$id` are resolved against the same root, so a
+/// schema can refer to `definitions/user.json` in a registry directory as is.
+const REGISTRY_ROOT: &str = "json-schema:///";
+
+#[derive(Args)]
+pub(crate) struct ValidateArgs {
+    /// The JSON Schema to validate with (i.e. schema.json).
+    #[arg(short, long)]
+    schema: PathBuf,
+
+    /// JSON instances to validate. Without instances, or for `-`, newline-delimited JSON is read
+    /// from stdin and every line is validated separately.
+    instances: Vec<PathBuf>,
+
+    /// The draft to validate with, instead of the one declared via `$schema`.
+    #[arg(short, long, value_enum)]
+    draft: Option<DraftArg>,
+
+    /// The output format.
+    #[arg(short, long, value_enum, default_value_t = Format::Detailed)]
+    format: Format,
+
+    /// The maximum number of errors to report per instance. Defaults to 1 for stdin and to
+    /// all errors for files.
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// A directory with JSON documents that references may point to (may be specified multiple
+    /// times). Documents are available by their `$id` and by their path relative to the
+    /// directory.
+    #[arg(long, value_name = "DIR")]
+    registry: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DraftArg {
+    #[value(name = "4")]
+    Draft4,
+    #[value(name = "6")]
+    Draft6,
+    #[value(name = "7")]
+    Draft7,
+    #[value(name = "2019-09")]
+    Draft201909,
+    #[value(name = "2020-12")]
+    Draft202012,
+}
+
+impl From<DraftArg> for Draft {
+    fn from(draft: DraftArg) -> Self {
+        match draft {
+            DraftArg::Draft4 => Draft::Draft4,
+            DraftArg::Draft6 => Draft::Draft6,
+            DraftArg::Draft7 => Draft::Draft7,
+            DraftArg::Draft201909 => Draft::Draft201909,
+            DraftArg::Draft202012 => Draft::Draft202012,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    /// Only whether the instance is valid, as JSON.
+    Flag,
+    /// The "basic" output format from the JSON Schema specification, as JSON.
+    Basic,
+    /// One line per error with its instance and schema locations.
+    Detailed,
+    /// Errors rendered against the instance source, like compiler diagnostics.
+    Pretty,
+}
+
+/// Where an instance comes from.
+#[derive(Clone, Copy)]
+enum Origin<'a> {
+    File(&'a str),
+    Line(usize),
+}
+
+struct Printer<W> {
+    out: W,
+    format: Format,
+    max_errors: usize,
+    colors: bool,
+}
+
+impl<W: Write> Printer<W> {
+    /// Print the result of validating `instance`, parsed from `source`. Valid instances from
+    /// stdin are not printed. Returns whether the instance is valid.
+    fn print(
+        &mut self,
+        validator: &Validator,
+        origin: Origin<'_>,
+        source: &str,
+        instance: &Value,
+    ) -> io::Result<bool> {
+        // Most instances in a stream are expected to be valid, building an output is much slower
+        let is_valid = validator.is_valid(instance);
+        if is_valid && matches!(origin, Origin::Line(_)) {
+            return Ok(true);
+        }
+        match self.format {
+            Format::Flag => {
+                let output = validator.apply(instance).flag();
+                self.print_json(origin, serde_json::json!({ "valid": output }))?;
+            }
+            Format::Basic => {
+                let mut output = validator.apply(instance).basic();
+                if let BasicOutput::Invalid(errors) = &mut output {
+                    errors.truncate(self.max_errors);
+                }
+                let output = serde_json::to_value(&output).map_err(io::Error::from)?;
+                self.print_json(origin, output)?;
+            }
+            Format::Detailed => match validator.apply(instance).basic() {
+                BasicOutput::Valid(_) => {
+                    if let Origin::File(name) = origin {
+                        writeln!(self.out, "{name} - VALID")?;
+                    }
+                }
+                BasicOutput::Invalid(errors) => {
+                    if let Origin::File(name) = origin {
+                        writeln!(self.out, "{name} - INVALID. Errors:")?;
+                    }
+                    for (idx, error) in errors.iter().take(self.max_errors).enumerate() {
+                        match origin {
+                            Origin::File(_) => write!(self.out, "{}. ", idx + 1)?,
+                            Origin::Line(line) => write!(self.out, "line {line}: ")?,
+                        }
+                        let instance_location = error.instance_location().as_str();
+                        writeln!(
+                            self.out,
+                            "{} at {} (schema: {})",
+                            error.error_description(),
+                            if instance_location.is_empty() {
+                                "(root)"
+                            } else {
+                                instance_location
+                            },
+                            error.keyword_location()
+                        )?;
+                    }
+                }
+            },
+            Format::Pretty => {
+                let errors: Vec<_> = validator
+                    .iter_errors(instance)
+                    .take(self.max_errors)
+                    .collect();
+                let name = match origin {
+                    Origin::File(name) => name.to_string(),
+                    Origin::Line(line) => format!("line {line}"),
+                };
+                let report = Report::new(source, &errors)
+                    .with_name(&name)
+                    .with_colors(self.colors);
+                write!(self.out, "{report}")?;
+            }
+        }
+        Ok(is_valid)
+    }
+
+    fn print_json(&mut self, origin: Origin<'_>, output: Value) -> io::Result<()> {
+        let mut object = serde_json::Map::new();
+        match origin {
+            Origin::File(name) => object.insert("instance".into(), name.into()),
+            Origin::Line(line) => object.insert("line".into(), line.into()),
+        };
+        if let Value::Object(output) = output {
+            object.extend(output);
+        }
+        serde_json::to_writer(&mut self.out, &object)?;
+        writeln!(self.out)
+    }
+}
+
+fn read_json(path: &Path) -> Result<(String, Value), Box<dyn std::error::Error>> {
+    let source = fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read {}: {error}", path.display()))?;
+    let value = serde_json::from_str(&source)
+        .map_err(|error| format!("Failed to parse {}: {error}", path.display()))?;
+    Ok((source, value))
+}
+
+/// Collect all `.json` files under `dir`, sorted for a stable registration order.
+fn collect_documents(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_documents(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn load_registry(
+    dir: &Path,
+    draft: Draft,
+) -> Result<Vec<(String, Resource)>, Box<dyn std::error::Error>> {
+    let mut files = Vec::new();
+    collect_documents(dir, &mut files)
+        .map_err(|error| format!("Failed to read {}: {error}", dir.display()))?;
+    let mut resources = Vec::with_capacity(files.len());
+    for path in files {
+        let (_, contents) = read_json(&path)?;
+        let relative = path
+            .strip_prefix(dir)
+            .expect("Collected from this directory")
+            .iter()
+            .map(|segment| segment.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let resource = draft.detect(&contents)?.create_resource(contents);
+        resources.push((format!("{REGISTRY_ROOT}{relative}"), resource));
+    }
+    Ok(resources)
+}
+
+/// Run the subcommand. Returns whether all instances are valid.
+pub(crate) fn run(args: &ValidateArgs) -> Result<bool, Box<dyn std::error::Error>> {
+    let (_, schema) = read_json(&args.schema)?;
+    let draft = args.draft.map(Draft::from);
+
+    let mut options = jsonschema::options();
+    if let Some(draft) = draft {
+        options.with_draft(draft);
+    }
+    for dir in &args.registry {
+        options.with_resources(load_registry(dir, draft.unwrap_or_default())?.into_iter());
+    }
+    let validator = options
+        .build(&schema)
+        .map_err(|error| format!("Schema is invalid: {error}"))?;
+
+    let stdout = io::stdout();
+    let colors = stdout.is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let mut printer = Printer {
+        out: io::BufWriter::new(stdout.lock()),
+        format: args.format,
+        max_errors: usize::MAX,
+        colors,
+    };
+
+    let mut success = true;
+    if args.instances.is_empty() {
+        printer.max_errors = args.max_errors.unwrap_or(1);
+        success = validate_lines(&validator, &mut printer, io::stdin().lock())?;
+    } else {
+        for path in &args.instances {
+            if path.as_os_str() == "-" {
+                printer.max_errors = args.max_errors.unwrap_or(1);
+                success &= validate_lines(&validator, &mut printer, io::stdin().lock())?;
+            } else {
+                printer.max_errors = args.max_errors.unwrap_or(usize::MAX);
+                let (source, instance) = read_json(path)?;
+                let name = path.to_string_lossy();
+                success &= printer.print(&validator, Origin::File(&name), &source, &instance)?;
+            }
+        }
+    }
+    printer.out.flush()?;
+    Ok(success)
+}
+
+/// Validate every non-empty line of `input` as a separate instance.
+fn validate_lines<W: Write>(
+    validator: &Validator,
+    printer: &mut Printer<W>,
+    mut input: impl BufRead,
+) -> io::Result<bool> {
+    let mut success = true;
+    let mut buffer = String::new();
+    let mut line = 0;
+    loop {
+        buffer.clear();
+        if input.read_line(&mut buffer)? == 0 {
+            break;
+        }
+        line += 1;
+        let source = buffer.trim_end_matches(['\n', '\r']);
+        if source.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(source) {
+            Ok(instance) => {
+                success &= printer.print(validator, Origin::Line(line), source, &instance)?;
+            }
+            Err(error) => {
+                // Keep going, a malformed line should not hide failures in the rest of the stream
+                printer.out.flush()?;
+                eprintln!("error: line {line}: {error}");
+                success = false;
+            }
+        }
+    }
+    Ok(success)
+}
@@ -129,3 +129,202 @@ fn test_no_instances() {
     assert!(output.status.success());
     assert_snapshot!(String::from_utf8_lossy(&output.stdout));
 }
+
+fn validate_output(output: &std::process::Output, file_names: &[&str]) -> String {
+    sanitize_output(
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        file_names,
+    )
+}
+
+const PERSON_SCHEMA: &str = r#"{
+    "type": "object",
+    "properties": {"name": {"type": "string"}, "age": {"type": "integer", "minimum": 0}},
+    "required": ["name"]
+}"#;
+
+#[test]
+fn test_validate_files() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", PERSON_SCHEMA);
+    let valid = create_temp_file(&dir, "valid.json", r#"{"name": "Jane"}"#);
+    let invalid = create_temp_file(&dir, "invalid.json", r#"{"age": -1}"#);
+
+    let output = cli()
+        .args(["validate", "--schema", &schema, &valid, &invalid])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_snapshot!(validate_output(&output, &[&valid, &invalid]));
+}
+
+#[test]
+fn test_validate_max_errors() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", PERSON_SCHEMA);
+    let instance = create_temp_file(&dir, "instance.json", r#"{"age": -1}"#);
+
+    let output = cli()
+        .args([
+            "validate",
+            "--max-errors",
+            "1",
+            "--schema",
+            &schema,
+            &instance,
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        validate_output(&output, &[&instance]),
+        "{FILE_1} - INVALID. Errors:\n1. -1 is less than the minimum of 0 at /age (schema: /properties/age/minimum)\n"
+    );
+}
+
+#[test]
+fn test_validate_stdin() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", PERSON_SCHEMA);
+
+    let output = cli()
+        .args(["validate", "--schema", &schema])
+        .write_stdin("{\"name\": \"Jane\"}\n{\"age\": -1}\n\n{\"name\": 1}\n{\"name\":\n{\"name\": \"John\"}\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_snapshot!(validate_output(&output, &[]));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "error: line 5: EOF while parsing a value at line 1 column 8\n"
+    );
+}
+
+#[test]
+fn test_validate_stdin_valid() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", PERSON_SCHEMA);
+
+    let output = cli()
+        .args(["validate", "--schema", &schema, "-"])
+        .write_stdin("{\"name\": \"Jane\"}\r\n{\"name\": \"John\"}")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_validate_format_flag() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", PERSON_SCHEMA);
+    let valid = create_temp_file(&dir, "valid.json", r#"{"name": "Jane"}"#);
+
+    let output = cli()
+        .args([
+            "validate", "--format", "flag", "--schema", &schema, &valid, "-",
+        ])
+        .write_stdin("{}\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        validate_output(&output, &[&valid]),
+        "{\"instance\":\"{FILE_1}\",\"valid\":true}\n{\"line\":1,\"valid\":false}\n"
+    );
+}
+
+#[test]
+fn test_validate_format_basic() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", PERSON_SCHEMA);
+
+    let output = cli()
+        .args(["validate", "--format", "basic", "--schema", &schema])
+        .write_stdin("{\"name\": \"Jane\"}\n{\"name\": 1, \"age\": 1.5}\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_snapshot!(validate_output(&output, &[]));
+}
+
+#[test]
+fn test_validate_format_pretty() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", PERSON_SCHEMA);
+    let instance = create_temp_file(
+        &dir,
+        "instance.json",
+        "{\n  \"name\": \"Jane\",\n  \"age\": -1\n}",
+    );
+
+    let output = cli()
+        .args([
+            "validate", "--format", "pretty", "--schema", &schema, &instance,
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_snapshot!(validate_output(&output, &[&instance]));
+}
+
+#[test]
+fn test_validate_registry() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("registry/definitions")).unwrap();
+    create_temp_file(
+        &dir,
+        "registry/definitions/name.json",
+        r#"{"type": "string", "minLength": 1}"#,
+    );
+    create_temp_file(
+        &dir,
+        "registry/age.json",
+        r#"{"$id": "https://example.com/age.json", "type": "integer"}"#,
+    );
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"name": {"$ref": "definitions/name.json"}, "age": {"$ref": "https://example.com/age.json"}}}"#,
+    );
+    let registry = dir.path().join("registry");
+
+    let output = cli()
+        .args(["validate", "--schema", &schema, "--registry"])
+        .arg(&registry)
+        .write_stdin("{\"name\": \"Jane\", \"age\": 42}\n{\"name\": \"\"}\n{\"age\": \"42\"}\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_snapshot!(validate_output(&output, &[]));
+}
+
+#[test]
+fn test_validate_draft() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"minimum": 5, "exclusiveMinimum": true}"#,
+    );
+
+    let output = cli()
+        .args(["validate", "--draft", "4", "--schema", &schema])
+        .write_stdin("6\n5\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "line 2: 5 is less than or equal to the minimum of 5 at (root) (schema: /minimum)\n"
+    );
+
+    // `exclusiveMinimum` must be a number in newer drafts
+    let output = cli()
+        .args(["validate", "--schema", &schema])
+        .write_stdin("6\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error: Schema is invalid: "));
+}
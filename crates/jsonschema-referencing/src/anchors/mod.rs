@@ -4,7 +4,7 @@ use serde_json::Value;
 
 mod keys;
 
-use crate::{Draft, Error, Resolved, Resolver, Resource};
+use crate::{specification::ids, Draft, Error, Resolved, Resolver, Resource};
 pub(crate) use keys::{AnchorKey, AnchorKeyRef};
 
 /// An anchor within a resource.
@@ -108,7 +108,7 @@ pub(crate) fn legacy_anchor_in_dollar_id(
         contents
             .as_object()
             .and_then(|schema| schema.get("$id").and_then(Value::as_str))
-            .and_then(|id| id.strip_prefix('#'))
+            .and_then(ids::fragment)
             .map(move |id| Anchor::Default {
                 draft,
                 name: id.to_string(),
@@ -126,7 +126,7 @@ pub(crate) fn legacy_anchor_in_id<'a>(
         contents
             .as_object()
             .and_then(|schema| schema.get("id").and_then(Value::as_str))
-            .and_then(|id| id.strip_prefix('#'))
+            .and_then(ids::fragment)
             .map(move |id| Anchor::Default {
                 draft,
                 name: id.to_string(),
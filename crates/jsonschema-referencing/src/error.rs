@@ -15,7 +15,21 @@ pub enum Error {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
     /// A JSON Pointer leads to a part of a document that does not exist.
-    PointerToNowhere { pointer: String },
+    PointerNotFound {
+        /// Document the pointer was evaluated in.
+        uri: String,
+        pointer: String,
+        /// The last segment of the pointer that exists, if any.
+        last_valid_segment: Option<String>,
+    },
+    /// A document is neither in a registry nor can it be retrieved, e.g. a URN.
+    DocumentNotRegistered {
+        uri: String,
+        /// Registered URIs that differ only in case, scheme, a trailing slash or an extension.
+        known_similar: Vec<String>,
+    },
+    /// A document can not be retrieved because its URI scheme is not supported.
+    UnsupportedScheme { scheme: String },
     /// JSON Pointer contains invalid percent-encoded data.
     InvalidPercentEncoding { pointer: String, source: Utf8Error },
     /// Failed to parse array index in JSON Pointer.
@@ -28,16 +42,39 @@ pub enum Error {
     NoSuchAnchor { anchor: String },
     /// An anchor which could never exist in a resource was dereferenced.
     InvalidAnchor { anchor: String },
+    /// A resource identifier has a fragment, which is not allowed since Draft 2019-09.
+    InvalidId { id: String },
+    /// Two documents that must be distinct define the same identifier.
+    DuplicateId {
+        id: String,
+        first: String,
+        second: String,
+    },
     /// An error occurred while parsing or manipulating a URI.
-    InvalidUri(UriError),
+    InvalidUri { input: String, reason: UriError },
     /// An unknown JSON Schema specification was encountered.
     UnknownSpecification { specification: String },
 }
 
 impl Error {
-    pub(crate) fn pointer_to_nowhere(pointer: impl Into<String>) -> Error {
-        Error::PointerToNowhere {
+    pub(crate) fn pointer_not_found(
+        uri: impl Into<String>,
+        pointer: impl Into<String>,
+        last_valid_segment: Option<String>,
+    ) -> Error {
+        Error::PointerNotFound {
+            uri: uri.into(),
             pointer: pointer.into(),
+            last_valid_segment,
+        }
+    }
+    pub(crate) fn document_not_registered(
+        uri: impl Into<String>,
+        known_similar: Vec<String>,
+    ) -> Error {
+        Error::DocumentNotRegistered {
+            uri: uri.into(),
+            known_similar,
         }
     }
     pub(crate) fn invalid_percent_encoding(pointer: impl Into<String>, source: Utf8Error) -> Error {
@@ -62,6 +99,20 @@ impl Error {
             anchor: anchor.into(),
         }
     }
+    pub(crate) fn invalid_id(id: impl Into<String>) -> Error {
+        Error::InvalidId { id: id.into() }
+    }
+    pub(crate) fn duplicate_id(
+        id: impl Into<String>,
+        first: impl Into<String>,
+        second: impl Into<String>,
+    ) -> Error {
+        Error::DuplicateId {
+            id: id.into(),
+            first: first.into(),
+            second: second.into(),
+        }
+    }
     pub(crate) fn no_such_anchor(anchor: impl Into<String>) -> Error {
         Error::NoSuchAnchor {
             anchor: anchor.into(),
@@ -84,19 +135,23 @@ impl Error {
     }
 
     pub(crate) fn uri_parsing_error(uri: impl Into<String>, error: ParseError) -> Error {
-        Error::InvalidUri(UriError::Parse {
-            uri: uri.into(),
-            is_reference: false,
-            error,
-        })
+        Error::InvalidUri {
+            input: uri.into(),
+            reason: UriError::Parse {
+                is_reference: false,
+                error,
+            },
+        }
     }
 
     pub(crate) fn uri_reference_parsing_error(uri: impl Into<String>, error: ParseError) -> Error {
-        Error::InvalidUri(UriError::Parse {
-            uri: uri.into(),
-            is_reference: true,
-            error,
-        })
+        Error::InvalidUri {
+            input: uri.into(),
+            reason: UriError::Parse {
+                is_reference: true,
+                error,
+            },
+        }
     }
 
     pub(crate) fn uri_resolving_error(
@@ -104,11 +159,13 @@ impl Error {
         base: Uri<&str>,
         error: ResolveError,
     ) -> Error {
-        Error::InvalidUri(UriError::Resolve {
-            uri: uri.into(),
-            base: base.to_owned(),
-            error,
-        })
+        Error::InvalidUri {
+            input: uri.into(),
+            reason: UriError::Resolve {
+                base: base.to_owned(),
+                error,
+            },
+        }
     }
 }
 
@@ -118,8 +175,33 @@ impl fmt::Display for Error {
             Error::Unretrievable { uri, source } => {
                 f.write_fmt(format_args!("Resource '{uri}' is not present in a registry and retrieving it failed: {source}"))
             },
-            Error::PointerToNowhere { pointer } => {
-                f.write_fmt(format_args!("Pointer '{pointer}' does not exist"))
+            Error::PointerNotFound {
+                uri,
+                pointer,
+                last_valid_segment,
+            } => {
+                f.write_fmt(format_args!("Pointer '{pointer}' does not exist in '{uri}'"))?;
+                match last_valid_segment {
+                    Some(segment) => f.write_fmt(format_args!(", the last segment that exists is '{segment}'")),
+                    None => Ok(()),
+                }
+            }
+            Error::DocumentNotRegistered { uri, known_similar } => {
+                f.write_fmt(format_args!("Resource '{uri}' is not registered"))?;
+                match known_similar.as_slice() {
+                    [] => Ok(()),
+                    [similar] => f.write_fmt(format_args!(". Did you mean '{similar}'?")),
+                    [similar @ .., last] => {
+                        f.write_str(". Did you mean ")?;
+                        for uri in similar {
+                            f.write_fmt(format_args!("'{uri}', "))?;
+                        }
+                        f.write_fmt(format_args!("or '{last}'?"))
+                    }
+                }
+            }
+            Error::UnsupportedScheme { scheme } => {
+                f.write_fmt(format_args!("Retrieving resources with the '{scheme}' URI scheme is not supported"))
             }
             Error::InvalidPercentEncoding { pointer, .. } => {
                 f.write_fmt(format_args!("Invalid percent encoding in pointer '{pointer}': the decoded bytes do not represent valid UTF-8"))
@@ -133,7 +215,25 @@ impl fmt::Display for Error {
             Error::InvalidAnchor { anchor } => {
                 f.write_fmt(format_args!("Anchor '{anchor}' is invalid"))
             }
-            Error::InvalidUri(error) => error.fmt(f),
+            Error::InvalidId { id } => {
+                f.write_fmt(format_args!("Identifier '{id}' must not contain a non-empty fragment"))
+            }
+            Error::DuplicateId { id, first, second } => {
+                f.write_fmt(format_args!("Identifier '{id}' is defined by both '{first}' and '{second}'"))
+            }
+            Error::InvalidUri { input, reason } => match reason {
+                UriError::Parse {
+                    is_reference: true,
+                    error,
+                } => f.write_fmt(format_args!("Invalid URI reference '{input}': {error}")),
+                UriError::Parse {
+                    is_reference: false,
+                    error,
+                } => f.write_fmt(format_args!("Invalid URI '{input}': {error}")),
+                UriError::Resolve { base, error } => f.write_fmt(format_args!(
+                    "Failed to resolve '{input}' against '{base}': {error}"
+                )),
+            },
             Error::UnknownSpecification { specification } => {
                 f.write_fmt(format_args!("Unknown specification: {specification}"))
             }
@@ -145,7 +245,7 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::Unretrievable { source, .. } => Some(&**source),
-            Error::InvalidUri(error) => Some(error),
+            Error::InvalidUri { reason, .. } => Some(reason),
             Error::InvalidPercentEncoding { source, .. } => Some(source),
             Error::InvalidArrayIndex { source, .. } => Some(source),
             _ => None,
@@ -153,15 +253,14 @@ impl std::error::Error for Error {
     }
 }
 
+/// Why a URI is invalid.
 #[derive(Debug)]
 pub enum UriError {
     Parse {
-        uri: String,
         is_reference: bool,
         error: ParseError,
     },
     Resolve {
-        uri: String,
         base: Uri<String>,
         error: ResolveError,
     },
@@ -170,20 +269,10 @@ pub enum UriError {
 impl fmt::Display for UriError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            UriError::Parse {
-                uri,
-                is_reference,
-                error,
-            } => {
-                if *is_reference {
-                    f.write_fmt(format_args!("Invalid URI reference '{uri}': {error}"))
-                } else {
-                    f.write_fmt(format_args!("Invalid URI '{uri}': {error}"))
-                }
+            UriError::Parse { error, .. } => error.fmt(f),
+            UriError::Resolve { base, error } => {
+                f.write_fmt(format_args!("can not resolve against '{base}': {error}"))
             }
-            UriError::Resolve { uri, base, error } => f.write_fmt(format_args!(
-                "Failed to resolve '{uri}' against '{base}': {error}"
-            )),
         }
     }
 }
@@ -5,6 +5,7 @@ mod anchors;
 mod error;
 mod list;
 pub mod meta;
+mod references;
 mod registry;
 mod resolver;
 mod resource;
@@ -18,6 +19,7 @@ pub(crate) use anchors::Anchor;
 pub use error::{Error, UriError};
 pub use fluent_uri::{Iri, IriRef, Uri, UriRef};
 pub use list::List;
+pub use references::{collect_references, ReferenceInfo};
 pub use registry::{Registry, RegistryOptions, SPECIFICATIONS};
 pub use resolver::{Resolved, Resolver};
 pub use resource::{Resource, ResourceRef};
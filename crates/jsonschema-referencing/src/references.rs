@@ -0,0 +1,304 @@
+//! Discovering references in raw schemas without building a registry.
+use ahash::{AHashMap, AHashSet};
+use fluent_uri::Uri;
+use serde_json::Value;
+
+use crate::{uri, Draft, Error};
+
+/// A reference found in a schema by [`collect_references`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceInfo<'a> {
+    keyword: &'static str,
+    reference: &'a str,
+    uri: Uri<String>,
+    location: String,
+    is_external: bool,
+}
+
+impl<'a> ReferenceInfo<'a> {
+    /// Keyword holding the reference, e.g. `$ref` or `$dynamicRef`.
+    #[must_use]
+    pub fn keyword(&self) -> &'static str {
+        self.keyword
+    }
+    /// Reference as written in the schema.
+    #[must_use]
+    pub fn reference(&self) -> &'a str {
+        self.reference
+    }
+    /// Reference resolved against the base URI in effect where it appears.
+    #[must_use]
+    pub fn uri(&self) -> &Uri<String> {
+        &self.uri
+    }
+    /// JSON Pointer to the schema object containing the reference.
+    #[must_use]
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+    /// Whether the reference points outside of the collected schema, i.e. to a document that
+    /// has to be retrieved or registered separately.
+    #[must_use]
+    pub fn is_external(&self) -> bool {
+        self.is_external
+    }
+}
+
+/// Collect all references in `schema`, ordered by their location.
+///
+/// Only subschemas are traversed, so `$ref`-like values inside `enum`, `const`, `examples`,
+/// etc. are not reported. Base URIs change with `$id` exactly as during resolution, and a
+/// reference is local if it resolves to the root document or to any resource embedded in it.
+/// Draft changes via `$schema` in embedded resources are respected.
+///
+/// # Errors
+///
+/// Returns an error if `$id` or a reference is not a valid URI reference.
+pub fn collect_references(schema: &Value, draft: Draft) -> Result<Vec<ReferenceInfo<'_>>, Error> {
+    let draft = draft.detect(schema)?;
+    let mut references = Vec::new();
+    let mut documents = AHashSet::new();
+    let base = uri::DEFAULT_ROOT_URI.clone();
+    collect(
+        schema,
+        draft,
+        &base,
+        &mut String::new(),
+        &mut references,
+        &mut documents,
+    )?;
+    for reference in &mut references {
+        let mut document = reference.uri.clone();
+        document.set_fragment(None);
+        reference.is_external = !documents.contains(&document);
+    }
+    references.sort_by(|left, right| left.location.cmp(&right.location));
+    Ok(references)
+}
+
+fn collect<'a>(
+    contents: &'a Value,
+    draft: Draft,
+    base: &Uri<String>,
+    location: &mut String,
+    references: &mut Vec<ReferenceInfo<'a>>,
+    documents: &mut AHashSet<Uri<String>>,
+) -> Result<(), Error> {
+    let mut base = match draft.id_of(contents) {
+        Some(id) => uri::resolve_against(&base.borrow(), id)?,
+        None => base.clone(),
+    };
+    base.set_fragment(None);
+    documents.insert(base.clone());
+    for keyword in reference_keywords(draft) {
+        if let Some(reference) = contents.get(*keyword).and_then(Value::as_str) {
+            references.push(ReferenceInfo {
+                keyword,
+                reference,
+                uri: uri::resolve_against(&base.borrow(), reference)?,
+                location: location.clone(),
+                is_external: false,
+            });
+        }
+    }
+    let mut children = None;
+    for subresource in draft.subresources_of(contents) {
+        let children = children.get_or_insert_with(|| children_of(contents));
+        let Some(child) = children.get(&(subresource as *const Value)) else {
+            continue;
+        };
+        let length = location.len();
+        child.push_to(location);
+        let draft = draft.detect(subresource)?;
+        collect(subresource, draft, &base, location, references, documents)?;
+        location.truncate(length);
+    }
+    Ok(())
+}
+
+fn reference_keywords(draft: Draft) -> &'static [&'static str] {
+    match draft {
+        Draft::Draft4 | Draft::Draft6 | Draft::Draft7 => &["$ref"],
+        Draft::Draft201909 => &["$ref", "$recursiveRef"],
+        Draft::Draft202012 => &["$ref", "$dynamicRef"],
+    }
+}
+
+/// Where a value is found within its parent schema.
+enum Child<'a> {
+    /// `/key`
+    Value(&'a str),
+    /// `/key/idx`
+    Item(&'a str, usize),
+    /// `/key/name`
+    Property(&'a str, &'a str),
+}
+
+impl Child<'_> {
+    fn push_to(&self, location: &mut String) {
+        let (Child::Value(key) | Child::Item(key, _) | Child::Property(key, _)) = self;
+        location.push('/');
+        location.push_str(&escape(key));
+        match self {
+            Child::Value(_) => {}
+            Child::Item(_, idx) => {
+                location.push('/');
+                location.push_str(&idx.to_string());
+            }
+            Child::Property(_, name) => {
+                location.push('/');
+                location.push_str(&escape(name));
+            }
+        }
+    }
+}
+
+/// Values directly or once nested in a schema, by address.
+///
+/// Subresources are at most two levels deep (e.g. `properties/foo` or `allOf/0`), so this is
+/// enough to find the location of each of them without scanning the schema again.
+fn children_of(parent: &Value) -> AHashMap<*const Value, Child<'_>> {
+    let mut children = AHashMap::new();
+    let Some(object) = parent.as_object() else {
+        return children;
+    };
+    for (key, value) in object {
+        children.insert(value as *const Value, Child::Value(key));
+        match value {
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    children.insert(item as *const Value, Child::Item(key, idx));
+                }
+            }
+            Value::Object(values) => {
+                for (name, item) in values {
+                    children.insert(item as *const Value, Child::Property(key, name));
+                }
+            }
+            _ => {}
+        }
+    }
+    children
+}
+
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_references;
+    use crate::Draft;
+    use serde_json::json;
+
+    fn collect(
+        schema: &serde_json::Value,
+        draft: Draft,
+    ) -> Vec<(&'static str, String, String, bool)> {
+        collect_references(schema, draft)
+            .expect("Invalid schema")
+            .into_iter()
+            .map(|info| {
+                (
+                    info.keyword(),
+                    info.location().to_string(),
+                    info.uri().to_string(),
+                    info.is_external(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn local_and_external() {
+        let schema = json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "a/b": {"$ref": "#/$defs/name"},
+                "remote": {"$ref": "other.json#/foo"}
+            },
+            "$defs": {
+                "name": {"type": "string"},
+                "nested": {
+                    "$id": "nested/",
+                    "items": {"$ref": "item.json"}
+                },
+                "embedded": {"$id": "https://example.com/nested/item.json"}
+            }
+        });
+        assert_eq!(
+            collect(&schema, Draft::Draft202012),
+            [
+                (
+                    "$ref",
+                    "/$defs/nested/items".to_string(),
+                    "https://example.com/nested/item.json".to_string(),
+                    false
+                ),
+                (
+                    "$ref",
+                    "/properties/a~1b".to_string(),
+                    "https://example.com/root.json#/$defs/name".to_string(),
+                    false
+                ),
+                (
+                    "$ref",
+                    "/properties/remote".to_string(),
+                    "https://example.com/other.json#/foo".to_string(),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_non_schema_values() {
+        let schema = json!({
+            "enum": [{"$ref": "enum.json"}],
+            "const": {"$ref": "const.json"},
+            "examples": [{"$ref": "examples.json"}],
+            "properties": {"$ref": {"$ref": "https://example.com/real.json"}}
+        });
+        assert_eq!(
+            collect(&schema, Draft::Draft7),
+            [(
+                "$ref",
+                "/properties/$ref".to_string(),
+                "https://example.com/real.json".to_string(),
+                true
+            )]
+        );
+    }
+
+    #[test]
+    fn array_subresources() {
+        let schema = json!({
+            "allOf": [{"type": "string"}, {"$ref": "a.json"}],
+            "items": [{"$ref": "b.json"}],
+            "properties": {"c": {"$ref": "#"}}
+        });
+        let locations: Vec<_> = collect(&schema, Draft::Draft7)
+            .into_iter()
+            .map(|(_, location, _, _)| location)
+            .collect();
+        assert_eq!(locations, ["/allOf/1", "/items/0", "/properties/c"]);
+    }
+
+    #[test]
+    fn dynamic_references() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2019-09/schema",
+            "$recursiveRef": "#",
+            "$dynamicRef": "#ignored"
+        });
+        assert_eq!(
+            collect(&schema, Draft::Draft202012),
+            [(
+                "$recursiveRef",
+                String::new(),
+                "json-schema:///#".to_string(),
+                false
+            )]
+        );
+    }
+}
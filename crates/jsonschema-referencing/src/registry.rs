@@ -1,5 +1,5 @@
 use std::{
-    collections::VecDeque,
+    collections::{hash_map::Entry, VecDeque},
     fmt::Debug,
     hash::{Hash, Hasher},
     sync::{Arc, RwLock},
@@ -13,7 +13,9 @@ use serde_json::Value;
 use crate::{
     anchors::{AnchorKey, AnchorKeyRef},
     list::List,
-    meta, uri,
+    meta,
+    specification::ids,
+    uri,
     vocabularies::{self, VocabularySet},
     Anchor, DefaultRetriever, Draft, Error, Resolver, Resource, Retrieve,
 };
@@ -36,6 +38,7 @@ pub static SPECIFICATIONS: Lazy<Registry> = Lazy::new(|| {
         &mut resources,
         &mut anchors,
         Draft::default(),
+        false,
     )
     .expect("Failed to process meta schemas");
     Registry {
@@ -170,7 +173,7 @@ impl Registry {
     ) -> Result<Self, Error> {
         let mut resources = ResourceMap::new();
         let mut anchors = AHashMap::new();
-        process_resources(pairs, retriever, &mut resources, &mut anchors, draft)?;
+        process_resources(pairs, retriever, &mut resources, &mut anchors, draft, false)?;
         Ok(Registry {
             resources,
             anchors,
@@ -229,7 +232,31 @@ impl Registry {
     ) -> Result<Registry, Error> {
         let mut resources = self.resources;
         let mut anchors = self.anchors;
-        process_resources(pairs, retriever, &mut resources, &mut anchors, draft)?;
+        process_resources(pairs, retriever, &mut resources, &mut anchors, draft, false)?;
+        Ok(Registry {
+            resources,
+            anchors,
+            resolving_cache: RwLock::new(AHashMap::new()),
+        })
+    }
+    /// Create a new registry with new documents and using the given retriever.
+    ///
+    /// Unlike [`Registry::try_with_resources_and_retriever`], an identifier defined by more than
+    /// one of the documents is an error instead of the last definition silently winning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any URI is invalid, if two documents define the same identifier or if
+    /// there's an issue processing the resources.
+    pub fn try_with_documents_and_retriever(
+        self,
+        pairs: impl Iterator<Item = (impl Into<String>, Resource)>,
+        retriever: &dyn Retrieve,
+        draft: Draft,
+    ) -> Result<Registry, Error> {
+        let mut resources = self.resources;
+        let mut anchors = self.anchors;
+        process_resources(pairs, retriever, &mut resources, &mut anchors, draft, true)?;
         Ok(Registry {
             resources,
             anchors,
@@ -262,10 +289,9 @@ impl Registry {
         if let Some(resource) = self.resources.get(uri) {
             Ok(resource)
         } else {
-            Err(Error::unretrievable(
+            Err(Error::document_not_registered(
                 uri.as_str(),
-                "Retrieving external resources is not supported once the registry is populated"
-                    .into(),
+                similar_uris(uri.as_str(), self.resources.keys()),
             ))
         }
     }
@@ -342,27 +368,37 @@ fn process_resources(
     resources: &mut ResourceMap,
     anchors: &mut AHashMap<AnchorKey, Anchor>,
     default_draft: Draft,
+    distinct: bool,
 ) -> Result<(), Error> {
     let mut queue = VecDeque::with_capacity(32);
     let mut seen = AHashSet::new();
     let mut external = AHashSet::new();
+    // Meta-schemas of unknown dialects are only used to find their vocabularies if available
+    let mut meta_schemas = AHashSet::new();
+    // Input document each identifier came from, only tracked if they must be distinct
+    let mut sources = Sources::default();
 
     // Populate the resources & queue from the input
     for (uri, resource) in pairs {
         let uri = uri::from_str(uri.into().trim_end_matches('#'))?;
+        let source = distinct.then(|| sources.add(&uri));
+        sources.claim(&uri, source)?;
         let resource = Arc::new(resource);
         resources.insert(uri.clone(), Arc::clone(&resource));
-        queue.push_back((uri, resource));
+        queue.push_back((uri, resource, source));
     }
 
     loop {
-        if queue.is_empty() && external.is_empty() {
+        if queue.is_empty() && external.is_empty() && meta_schemas.is_empty() {
             break;
         }
 
         // Process current queue and collect references to external resources
-        while let Some((mut base, resource)) = queue.pop_front() {
+        while let Some((mut base, resource, source)) = queue.pop_front() {
             if let Some(id) = resource.id() {
+                if resource.draft() >= Draft::Draft201909 && ids::fragment(id).is_some() {
+                    return Err(Error::invalid_id(id));
+                }
                 base = uri::resolve_against(&base.borrow(), id)?;
             }
 
@@ -375,7 +411,13 @@ fn process_resources(
             }
 
             // Collect references to external resources in this resource
-            collect_external_resources(&base, resource.contents(), &mut external, &mut seen)?;
+            collect_external_resources(
+                &base,
+                resource.contents(),
+                &mut external,
+                &mut meta_schemas,
+                &mut seen,
+            )?;
 
             // Process subresources
             for subresource in resource.subresources() {
@@ -387,6 +429,7 @@ fn process_resources(
                         &base,
                         subresource.contents(),
                         &mut external,
+                        &mut meta_schemas,
                         &mut seen,
                     )?;
                 } else {
@@ -394,12 +437,14 @@ fn process_resources(
                         &base,
                         subresource.contents(),
                         &mut external,
+                        &mut meta_schemas,
                         &mut seen,
                     )?;
                 };
-                queue.push_back((base.clone(), subresource));
+                queue.push_back((base.clone(), subresource, source));
             }
             if resource.id().is_some() {
+                sources.claim(&base, source)?;
                 resources.insert(base, resource);
             }
         }
@@ -408,9 +453,19 @@ fn process_resources(
             let mut fragmentless = uri.clone();
             fragmentless.set_fragment(None);
             if !resources.contains_key(&fragmentless) {
-                let retrieved = retriever
-                    .retrieve(&fragmentless.borrow())
-                    .map_err(|err| Error::unretrievable(fragmentless.as_str(), err))?;
+                let retrieved = retriever.retrieve(&fragmentless.borrow()).map_err(|err| {
+                    // Retrievers may explain why nothing could be retrieved with `Error`
+                    match err.downcast::<Error>() {
+                        Ok(error) => match *error {
+                            Error::DocumentNotRegistered { uri, .. } => {
+                                let known_similar = similar_uris(&uri, resources.keys());
+                                Error::document_not_registered(uri, known_similar)
+                            }
+                            error => error,
+                        },
+                        Err(err) => Error::unretrievable(fragmentless.as_str(), err),
+                    }
+                })?;
                 let resource = Arc::new(Resource::from_contents_and_specification(
                     retrieved,
                     default_draft,
@@ -427,10 +482,26 @@ fn process_resources(
                                 resolved.clone(),
                                 default_draft,
                             )?),
+                            None,
                         ));
                     }
                 }
-                queue.push_back((fragmentless, resource));
+                queue.push_back((fragmentless, resource, None));
+            }
+        }
+        for uri in meta_schemas.drain() {
+            if resources.contains_key(&uri) {
+                continue;
+            }
+            let Ok(retrieved) = retriever.retrieve(&uri.borrow()) else {
+                continue;
+            };
+            if let Ok(resource) =
+                Resource::from_contents_and_specification(retrieved, default_draft)
+            {
+                let resource = Arc::new(resource);
+                resources.insert(uri.clone(), Arc::clone(&resource));
+                queue.push_back((uri, resource, None));
             }
         }
     }
@@ -438,45 +509,124 @@ fn process_resources(
     Ok(())
 }
 
+/// Input documents of a registry and the identifiers each of them defines.
+#[derive(Default)]
+struct Sources {
+    uris: Vec<String>,
+    owners: AHashMap<Uri<String>, usize>,
+}
+
+impl Sources {
+    fn add(&mut self, uri: &Uri<String>) -> usize {
+        self.uris.push(uri.as_str().to_string());
+        self.uris.len() - 1
+    }
+
+    /// Record that `source` defines `id`, failing if another input document already does.
+    fn claim(&mut self, id: &Uri<String>, source: Option<usize>) -> Result<(), Error> {
+        let Some(source) = source else {
+            return Ok(());
+        };
+        match self.owners.entry(id.clone()) {
+            Entry::Occupied(entry) if *entry.get() != source => Err(Error::duplicate_id(
+                id.as_str(),
+                &self.uris[*entry.get()],
+                &self.uris[source],
+            )),
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(source);
+                Ok(())
+            }
+        }
+    }
+}
+
 fn collect_external_resources(
     base: &Uri<String>,
     contents: &Value,
     collected: &mut AHashSet<Uri<String>>,
+    meta_schemas: &mut AHashSet<Uri<String>>,
     seen: &mut AHashSet<u64>,
 ) -> Result<(), Error> {
     if base.scheme().as_str() == "urn" {
         return Ok(());
     }
-    for key in ["$ref", "$schema"] {
-        if let Some(reference) = contents.get(key).and_then(Value::as_str) {
-            if reference.starts_with('#')
-                || reference.starts_with("https://json-schema.org/draft/2020-12/")
-                || reference.starts_with("https://json-schema.org/draft/2019-09/")
-                || reference.starts_with("http://json-schema.org/draft-07/")
-                || reference.starts_with("http://json-schema.org/draft-06/")
-                || reference.starts_with("http://json-schema.org/draft-04/")
-            {
-                // Not an external resource
-                return Ok(());
+    if let Some(reference) = contents.get("$ref").and_then(Value::as_str) {
+        // Local references and references to the built-in meta-schemas are not external
+        let is_external = !(reference.starts_with('#')
+            || reference.starts_with("https://json-schema.org/draft/2020-12/")
+            || reference.starts_with("https://json-schema.org/draft/2019-09/")
+            || reference.starts_with("http://json-schema.org/draft-07/")
+            || reference.starts_with("http://json-schema.org/draft-06/")
+            || reference.starts_with("http://json-schema.org/draft-04/"));
+        if is_external {
+            if let Some(resolved) = resolve_unseen(base, reference, seen)? {
+                collected.insert(resolved);
             }
-            let mut hasher = AHasher::default();
-            (base.as_str(), reference).hash(&mut hasher);
-            let hash = hasher.finish();
-            if !seen.insert(hash) {
-                // Reference has already been seen
-                return Ok(());
+        }
+    }
+    if let Some(specification) = contents.get("$schema").and_then(Value::as_str) {
+        // Meta-schemas of known drafts are built in
+        if Draft::from_schema(contents).is_none() {
+            if let Some(mut resolved) = resolve_unseen(base, specification, seen)? {
+                resolved.set_fragment(None);
+                meta_schemas.insert(resolved);
             }
-            let resolved = if reference.contains('#') && base.has_fragment() {
-                uri::resolve_against(&uri::DEFAULT_ROOT_URI.borrow(), reference)?
-            } else {
-                uri::resolve_against(&base.borrow(), reference)?
-            };
-            collected.insert(resolved);
         }
     }
     Ok(())
 }
 
+/// Known URIs that `uri` was likely meant to be, i.e. that differ from it only in case, in `http`
+/// and `https`, in a trailing slash or in the extension of the last path segment.
+fn similar_uris<'a>(uri: &str, known: impl Iterator<Item = &'a Uri<String>>) -> Vec<String> {
+    let key = similarity_key(uri);
+    let mut similar: Vec<String> = known
+        .map(Uri::as_str)
+        .filter(|known| *known != uri && similarity_key(known) == key)
+        .map(str::to_string)
+        .collect();
+    similar.sort_unstable();
+    similar
+}
+
+fn similarity_key(uri: &str) -> String {
+    let uri = uri
+        .strip_prefix("https:")
+        .or_else(|| uri.strip_prefix("http:"))
+        .unwrap_or(uri)
+        .trim_end_matches('/');
+    let uri = match uri.rsplit_once('/') {
+        // Not the host in `//example.com`
+        Some((head, last)) if head.len() > 1 => match last.rsplit_once('.') {
+            Some((stem, _)) => &uri[..=head.len() + stem.len()],
+            None => uri,
+        },
+        _ => uri,
+    };
+    uri.to_lowercase()
+}
+
+/// Resolve `reference` against `base`, or return `None` if it was already resolved against it.
+fn resolve_unseen(
+    base: &Uri<String>,
+    reference: &str,
+    seen: &mut AHashSet<u64>,
+) -> Result<Option<Uri<String>>, Error> {
+    let mut hasher = AHasher::default();
+    (base.as_str(), reference).hash(&mut hasher);
+    if !seen.insert(hasher.finish()) {
+        return Ok(None);
+    }
+    let resolved = if reference.contains('#') && base.has_fragment() {
+        uri::resolve_against(&uri::DEFAULT_ROOT_URI.borrow(), reference)?
+    } else {
+        uri::resolve_against(&base.borrow(), reference)?
+    };
+    Ok(Some(resolved))
+}
+
 #[cfg(test)]
 mod tests {
     use std::error::Error as _;
@@ -486,7 +636,7 @@ mod tests {
     use serde_json::{json, Value};
     use test_case::test_case;
 
-    use crate::{uri::from_str, Draft, Registry, Resource, Retrieve};
+    use crate::{uri::from_str, DefaultRetriever, Draft, Error, Registry, Resource, Retrieve};
 
     use super::{RegistryOptions, SPECIFICATIONS};
 
@@ -526,10 +676,215 @@ mod tests {
 
         assert_eq!(
             result.unwrap_err().to_string(),
-            "Resource 'http://example.com/non_existent_schema' is not present in a registry and retrieving it failed: Retrieving external resources is not supported once the registry is populated"
+            "Resource 'http://example.com/non_existent_schema' is not registered"
+        );
+    }
+
+    #[test_case("http://example.com/schema1", "https://example.com/schema1", &["http://example.com/schema1"]; "scheme")]
+    #[test_case("http://example.com/Schema.json", "http://example.com/schema.json", &["http://example.com/Schema.json"]; "case")]
+    #[test_case("http://example.com/schema/", "http://example.com/schema", &["http://example.com/schema/"]; "trailing slash")]
+    #[test_case("http://example.com/schema.json", "http://example.com/schema", &["http://example.com/schema.json"]; "extension")]
+    #[test_case("http://example.com/schema.json", "http://example.com/other", &[]; "different")]
+    #[test_case("http://example.com/schema.json", "http://example.org/schema.json", &[]; "different host")]
+    fn test_lookup_similar_url(registered: &str, requested: &str, expected: &[&str]) {
+        let schema = Draft::Draft202012.create_resource(json!({}));
+        let registry = Registry::try_new(registered, schema).expect("Invalid resources");
+        let resolver = registry.try_resolver(requested).expect("Invalid base URI");
+        match resolver.lookup("").expect_err("Should fail") {
+            Error::DocumentNotRegistered { uri, known_similar } => {
+                assert_eq!(uri, requested);
+                assert_eq!(known_similar, expected);
+            }
+            error => panic!("Unexpected error: {error}"),
+        }
+    }
+
+    #[test]
+    fn test_lookup_similar_url_message() {
+        let registry = Registry::try_from_resources(
+            [
+                "http://example.com/schema.json",
+                "http://example.com/Schema",
+                "https://example.com/schema",
+            ]
+            .into_iter()
+            .map(|uri| (uri, Draft::Draft202012.create_resource(json!({})))),
+        )
+        .expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/schema")
+            .expect("Invalid base URI");
+        assert_eq!(
+            resolver.lookup("").expect_err("Should fail").to_string(),
+            "Resource 'http://example.com/schema' is not registered. Did you mean 'http://example.com/Schema', 'http://example.com/schema.json', or 'https://example.com/schema'?"
+        );
+    }
+
+    #[test]
+    fn test_retrieve_similar_url() {
+        let error = Registry::try_from_resources(
+            [
+                (
+                    "http://example.com/root",
+                    Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/item"})),
+                ),
+                (
+                    "http://example.com/item.json",
+                    Draft::Draft202012.create_resource(json!({})),
+                ),
+            ]
+            .into_iter(),
+        )
+        .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Resource 'http://example.com/item' is not registered. Did you mean 'http://example.com/item.json'?"
         );
     }
 
+    #[test_case(Draft::Draft4, json!({"definitions": {"A": {"id": "#foo", "type": "integer"}}}), "http://example.com/root.json#foo"; "draft 4 plain-name id")]
+    #[test_case(Draft::Draft4, json!({"definitions": {"A": {"id": "item.json#foo", "type": "integer"}}}), "http://example.com/item.json#foo"; "draft 4 id with base and fragment")]
+    #[test_case(Draft::Draft4, json!({"definitions": {"A": {"id": "item.json#foo", "type": "integer"}}}), "http://example.com/item.json"; "draft 4 base of id with fragment")]
+    #[test_case(Draft::Draft6, json!({"definitions": {"A": {"$id": "item.json#foo", "type": "integer"}}}), "http://example.com/item.json#foo"; "draft 6 id with base and fragment")]
+    #[test_case(Draft::Draft7, json!({"definitions": {"A": {"$id": "#foo", "type": "integer"}}}), "http://example.com/root.json#foo"; "draft 7 plain-name id")]
+    fn test_lookup_legacy_id(draft: Draft, schema: Value, reference: &str) {
+        let registry = Registry::try_new(
+            "http://example.com/root.json",
+            draft.create_resource(schema),
+        )
+        .expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/root.json")
+            .expect("Invalid base URI");
+        let resolved = resolver.lookup(reference).expect("Lookup failed");
+        assert_eq!(resolved.contents()["type"], json!("integer"));
+    }
+
+    #[test_case(Draft::Draft4, json!({"definitions": {"A": {"$id": "#foo"}}}); "draft 4 ignores $id")]
+    #[test_case(Draft::Draft6, json!({"definitions": {"A": {"id": "#foo"}}}); "draft 6 ignores id")]
+    #[test_case(Draft::Draft4, json!({"definitions": {"A": {"id": "#"}}}); "draft 4 empty fragment")]
+    fn test_lookup_legacy_id_not_an_anchor(draft: Draft, schema: Value) {
+        let registry = Registry::try_new(
+            "http://example.com/root.json",
+            draft.create_resource(schema),
+        )
+        .expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/root.json")
+            .expect("Invalid base URI");
+        assert!(resolver.lookup("#foo").is_err());
+    }
+
+    #[test_case(Draft::Draft201909, json!({"$defs": {"A": {"$id": "#foo"}}}), "#foo")]
+    #[test_case(Draft::Draft202012, json!({"$defs": {"A": {"$id": "item.json#foo"}}}), "item.json#foo")]
+    #[test_case(Draft::Draft202012, json!({"$id": "http://example.com/root.json#foo"}), "http://example.com/root.json#foo")]
+    fn test_fragment_in_id(draft: Draft, schema: Value, id: &str) {
+        let error = Registry::try_new(
+            "http://example.com/root.json",
+            draft.create_resource(schema),
+        )
+        .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            format!("Identifier '{id}' must not contain a non-empty fragment")
+        );
+    }
+
+    #[test]
+    fn test_empty_fragment_in_id() {
+        let schema = Draft::Draft202012.create_resource(json!({
+            "$defs": {"A": {"$id": "item.json#", "type": "integer"}}
+        }));
+        let registry =
+            Registry::try_new("http://example.com/root.json", schema).expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/root.json")
+            .expect("Invalid base URI");
+        let resolved = resolver.lookup("item.json").expect("Lookup failed");
+        assert_eq!(resolved.contents()["type"], json!("integer"));
+    }
+
+    #[test_case("urn:example:person", ""; "whole document")]
+    #[test_case("urn:example:person#/definitions/name", "/definitions/name"; "pointer")]
+    #[test_case("urn:example:person#name", "/definitions/name"; "legacy anchor")]
+    #[test_case("urn:example:person#/definitions/address/properties/city", "/definitions/address/properties/city"; "pointer into embedded resource")]
+    #[test_case("urn:example:address#/properties/city", "/definitions/address/properties/city"; "embedded resource")]
+    #[test_case("urn:example:address#city", "/definitions/address/properties/city"; "anchor in embedded resource")]
+    fn test_lookup_urn(reference: &str, pointer: &str) {
+        let contents = json!({
+            "$id": "urn:example:person",
+            "definitions": {
+                "name": {"$id": "#name", "type": "string"},
+                "address": {
+                    "$id": "urn:example:address",
+                    "properties": {"city": {"$id": "#city", "type": "string"}}
+                }
+            },
+            "properties": {"name": {"$ref": "#/definitions/name"}}
+        });
+        let registry = Registry::try_new(
+            "urn:example:person",
+            Draft::Draft7.create_resource(contents.clone()),
+        )
+        .expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("urn:example:person")
+            .expect("Invalid base URI");
+        let resolved = resolver.lookup(reference).expect("Lookup failed");
+        assert_eq!(Some(resolved.contents()), contents.pointer(pointer));
+    }
+
+    #[test]
+    fn test_urn_is_not_normalized() {
+        let schema = Draft::Draft202012.create_resource(json!({"type": "string"}));
+        let registry = Registry::try_new("urn:example:a/../b", schema).expect("Invalid resources");
+        let resolver = registry.try_resolver("").expect("Invalid base URI");
+        assert!(resolver.lookup("urn:example:a/../b").is_ok());
+        assert!(resolver.lookup("urn:example:b").is_err());
+    }
+
+    #[test_case("other.json")]
+    #[test_case("../other")]
+    #[test_case("/other")]
+    fn test_relative_reference_against_urn(reference: &str) {
+        let schema = Draft::Draft202012.create_resource(json!({
+            "$id": "urn:example:root",
+            "$defs": {"a": {"$ref": reference}}
+        }));
+        let registry = Registry::try_new("urn:example:root", schema).expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("urn:example:root")
+            .expect("Invalid base URI");
+        let error = resolver.lookup(reference).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            format!("Failed to resolve '{reference}' against 'urn:example:root': relative reference must be empty or start with '#' when resolved against authority-less base URI/IRI with rootless path")
+        );
+    }
+
+    #[test_case("HTTP://Example.COM/schemas/person.json", "http://example.com/schemas/person.json"; "case in id")]
+    #[test_case("http://example.com/schemas/person.json", "HTTP://EXAMPLE.COM/schemas/person.json"; "case in reference")]
+    #[test_case("http://example.com/schemas/./tmp/../person.json", "http://example.com/schemas/person.json"; "dot segments in id")]
+    #[test_case("http://example.com/schemas/person.json", "http://example.com/other/../schemas/person.json"; "dot segments in reference")]
+    #[test_case("http://example.com:80/schemas/person.json", "http://example.com/schemas/person.json"; "default port in id")]
+    #[test_case("http://example.com/schemas/person.json", "http://example.com:80/schemas/%70erson.json"; "default port and percent encoding in reference")]
+    fn test_lookup_normalized(id: &str, reference: &str) {
+        let schema = Draft::Draft202012.create_resource(json!({
+            "$defs": {"person": {"$id": id, "type": "object"}}
+        }));
+        let registry =
+            Registry::try_new("http://example.com/root.json", schema).expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/root.json")
+            .expect("Invalid base URI");
+        let resolved = resolver.lookup(reference).expect("Lookup failed");
+        assert_eq!(resolved.contents()["type"], json!("object"));
+        let resolved = resolver
+            .lookup(&format!("{reference}#/type"))
+            .expect("Lookup failed");
+        assert_eq!(resolved.contents(), &json!("object"));
+    }
+
     struct TestRetriever {
         schemas: AHashMap<String, Value>,
     }
@@ -772,8 +1127,11 @@ mod tests {
             .into_iter(),
         );
         let error = result.expect_err("Should fail");
-        assert_eq!(error.to_string(), "Resource 'http://example.com/schema2' is not present in a registry and retrieving it failed: Default retriever does not fetch resources");
-        assert!(error.source().is_some());
+        assert_eq!(
+            error.to_string(),
+            "Resource 'http://example.com/schema2' is not registered"
+        );
+        assert!(matches!(error, Error::DocumentNotRegistered { .. }));
     }
 
     #[test]
@@ -864,6 +1222,85 @@ mod tests {
         );
     }
 
+    #[test_case(
+        &[
+            ("http://example.com/a", json!({"$id": "http://example.com/shared"})),
+            ("http://example.com/b", json!({"$id": "http://example.com/shared"})),
+        ],
+        "http://example.com/shared",
+        "http://example.com/a",
+        "http://example.com/b";
+        "root identifiers"
+    )]
+    #[test_case(
+        &[
+            ("http://example.com/a", json!({"$defs": {"x": {"$id": "http://example.com/b"}}})),
+            ("http://example.com/b", json!({})),
+        ],
+        "http://example.com/b",
+        "http://example.com/b",
+        "http://example.com/a";
+        "embedded identifier"
+    )]
+    #[test_case(
+        &[
+            ("http://example.com/a", json!({})),
+            ("http://example.com/a#", json!({})),
+        ],
+        "http://example.com/a",
+        "http://example.com/a",
+        "http://example.com/a";
+        "input URIs"
+    )]
+    fn test_duplicate_documents(
+        documents: &[(&str, Value)],
+        expected_id: &str,
+        expected_first: &str,
+        expected_second: &str,
+    ) {
+        let error = SPECIFICATIONS
+            .clone()
+            .try_with_documents_and_retriever(
+                documents
+                    .iter()
+                    .map(|(uri, value)| (*uri, Draft::Draft202012.create_resource(value.clone()))),
+                &DefaultRetriever,
+                Draft::Draft202012,
+            )
+            .expect_err("Duplicate identifiers should be rejected");
+        let Error::DuplicateId { id, first, second } = &error else {
+            panic!("Unexpected error: {error}");
+        };
+        assert_eq!(id, expected_id);
+        assert_eq!(first, expected_first);
+        assert_eq!(second, expected_second);
+    }
+
+    #[test]
+    fn test_distinct_documents() {
+        let registry = SPECIFICATIONS
+            .clone()
+            .try_with_documents_and_retriever(
+                [
+                    (
+                        "http://example.com/a",
+                        json!({"$id": "http://example.com/a", "$defs": {"x": {"$id": "x"}}}),
+                    ),
+                    ("http://example.com/b", json!({"$ref": "a"})),
+                ]
+                .into_iter()
+                .map(|(uri, value)| (uri, Draft::Draft202012.create_resource(value))),
+                &DefaultRetriever,
+                Draft::Draft202012,
+            )
+            .expect("Identifiers are distinct");
+        let resolver = registry
+            .try_resolver("http://example.com/b")
+            .expect("Invalid base URI");
+        let resolved = resolver.lookup("x").expect("Lookup failed");
+        assert_eq!(resolved.contents(), &json!({"$id": "x"}));
+    }
+
     #[test]
     fn test_resolver_debug() {
         let registry = SPECIFICATIONS
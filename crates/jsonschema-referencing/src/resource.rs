@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use serde_json::Value;
 
-use crate::{Anchor, Draft, Error, Resolved, Resolver, Segments};
+use crate::{uri, Anchor, Draft, Error, Resolved, Resolver, Segments};
 
 /// A document with a concrete interpretation under a JSON Schema specification.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -64,10 +64,18 @@ impl Resource {
         let mut contents = &self.contents;
         let mut segments = Segments::new();
         let original_pointer = pointer;
-        let pointer = percent_encoding::percent_decode_str(&pointer[1..])
-            .decode_utf8()
+        let pointer = uri::decode_pointer(pointer)
             .map_err(|err| Error::invalid_percent_encoding(original_pointer, err))?;
-        for segment in pointer.split('/') {
+        let base_uri = resolver.base_uri();
+        let not_found = |last_valid_segment: Option<&str>| {
+            Error::pointer_not_found(
+                base_uri.as_str(),
+                original_pointer,
+                last_valid_segment.map(str::to_string),
+            )
+        };
+        let mut last_valid_segment = None;
+        for segment in pointer.split('/').skip(1) {
             if let Some(array) = contents.as_array() {
                 let idx = segment
                     .parse::<usize>()
@@ -75,7 +83,7 @@ impl Resource {
                 if let Some(next) = array.get(idx) {
                     contents = next;
                 } else {
-                    return Err(Error::pointer_to_nowhere(original_pointer));
+                    return Err(not_found(last_valid_segment));
                 }
                 segments.push(idx);
             } else {
@@ -83,10 +91,11 @@ impl Resource {
                 if let Some(next) = contents.get(segment.as_ref()) {
                     contents = next;
                 } else {
-                    return Err(Error::pointer_to_nowhere(original_pointer));
+                    return Err(not_found(last_valid_segment));
                 }
                 segments.push(segment);
             }
+            last_valid_segment = Some(segment);
             let last = &resolver;
             let new_resolver = self.draft.maybe_in_subresource(
                 &segments,
@@ -299,7 +308,7 @@ mod tests {
         let result = resolver.lookup("#/properties/bar/items/2");
         assert_eq!(
             result.expect_err("Should fail").to_string(),
-            "Pointer '/properties/bar/items/2' does not exist"
+            "Pointer '/properties/bar/items/2' does not exist in 'http://example.com', the last segment that exists is 'items'"
         );
     }
 
@@ -313,7 +322,48 @@ mod tests {
         let result = resolver.lookup("#/properties/baz");
         assert_eq!(
             result.expect_err("Should fail").to_string(),
-            "Pointer '/properties/baz' does not exist"
+            "Pointer '/properties/baz' does not exist in 'http://example.com', the last segment that exists is 'properties'"
         );
     }
+
+    #[test]
+    fn test_unknown_first_segment() {
+        let registry = create_test_registry();
+        let resolver = registry
+            .try_resolver("http://example.com")
+            .expect("Invalid base URI");
+
+        match resolver
+            .lookup("#/definitions/foo")
+            .expect_err("Should fail")
+        {
+            crate::Error::PointerNotFound {
+                uri,
+                pointer,
+                last_valid_segment,
+            } => {
+                assert_eq!(uri, "http://example.com");
+                assert_eq!(pointer, "/definitions/foo");
+                assert_eq!(last_valid_segment, None);
+            }
+            error => panic!("Unexpected error: {error}"),
+        }
+    }
+
+    #[test_case("#/$defs/caf%C3%A9", "café"; "non-ascii")]
+    #[test_case("#/$defs/a~1b", "a/b"; "escaped slash")]
+    #[test_case("#/$defs/a%2Fb", "a/b"; "encoded slash")]
+    #[test_case("#/$defs/a~0b", "a~b"; "escaped tilde")]
+    #[test_case("#/$defs/a%7E0b", "a~b"; "encoded tilde")]
+    #[test_case("#/$defs/a%25b", "a%b"; "percent")]
+    fn test_encoded_segments(reference: &str, key: &str) {
+        let schema = Draft::Draft202012.create_resource(json!({"$defs": {key: {"const": key}}}));
+        let registry = Registry::try_new("http://example.com", schema).expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com")
+            .expect("Invalid base URI");
+
+        let resolved = resolver.lookup(reference).expect("Lookup failed");
+        assert_eq!(resolved.contents(), &json!({"const": key}));
+    }
 }
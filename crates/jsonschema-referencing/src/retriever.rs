@@ -1,8 +1,8 @@
-use core::fmt;
-
 use fluent_uri::Uri;
 use serde_json::Value;
 
+use crate::Error;
+
 /// Trait for retrieving resources from external sources.
 ///
 /// Implementors of this trait can be used to fetch resources that are not
@@ -20,22 +20,14 @@ pub trait Retrieve: Send + Sync {
     fn retrieve(&self, uri: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>>;
 }
 
-#[derive(Debug, Clone)]
-struct DefaultRetrieverError;
-
-impl fmt::Display for DefaultRetrieverError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("Default retriever does not fetch resources")
-    }
-}
-
-impl std::error::Error for DefaultRetrieverError {}
-
 #[derive(Debug, PartialEq, Eq)]
 pub struct DefaultRetriever;
 
 impl Retrieve for DefaultRetriever {
-    fn retrieve(&self, _: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        Err(Box::new(DefaultRetrieverError))
+    fn retrieve(&self, uri: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        Err(Box::new(Error::document_not_registered(
+            uri.as_str(),
+            Vec::new(),
+        )))
     }
 }
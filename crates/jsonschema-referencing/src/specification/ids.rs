@@ -9,27 +9,56 @@ pub(crate) fn dollar_id(contents: &Value) -> Option<&str> {
 }
 
 pub(crate) fn legacy_dollar_id(contents: &Value) -> Option<&str> {
+    legacy(contents, "$id")
+}
+
+pub(crate) fn legacy_id(contents: &Value) -> Option<&str> {
+    legacy(contents, "id")
+}
+
+/// Before Draft 2019-09 an identifier may carry a plain-name fragment, e.g. `item.json#foo`.
+/// The fragment names an anchor, so only the part before it changes the base URI.
+fn legacy<'a>(contents: &'a Value, keyword: &str) -> Option<&'a str> {
     let object = contents.as_object()?;
     if object.contains_key("$ref") {
         return None;
     }
-    if let Some(id) = contents.get("$id").and_then(|id| id.as_str()) {
-        if !id.starts_with('#') {
-            return Some(id);
-        }
+    let id = object.get(keyword)?.as_str()?;
+    let base = id.split_once('#').map_or(id, |(base, _)| base);
+    if base.is_empty() {
+        None
+    } else {
+        Some(base)
     }
-    None
 }
 
-pub(crate) fn legacy_id(contents: &Value) -> Option<&str> {
-    let object = contents.as_object()?;
-    if object.contains_key("$ref") {
-        return None;
+/// Fragment of an identifier, if it is present and non-empty.
+pub(crate) fn fragment(id: &str) -> Option<&str> {
+    id.split_once('#')
+        .map(|(_, fragment)| fragment)
+        .filter(|fragment| !fragment.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(&json!({"id": "http://example.com/schema"}), Some("http://example.com/schema"))]
+    #[test_case(&json!({"id": "item.json#foo"}), Some("item.json"))]
+    #[test_case(&json!({"id": "item.json#"}), Some("item.json"))]
+    #[test_case(&json!({"id": "#foo"}), None)]
+    #[test_case(&json!({"id": "item.json", "$ref": "#/definitions/a"}), None)]
+    #[test_case(&json!({"$id": "item.json"}), None)]
+    fn legacy_id(contents: &Value, expected: Option<&str>) {
+        assert_eq!(super::legacy_id(contents), expected);
     }
-    if let Some(id) = object.get("id").and_then(|id| id.as_str()) {
-        if !id.starts_with('#') {
-            return Some(id);
-        }
+
+    #[test_case("item.json#foo", Some("foo"))]
+    #[test_case("#foo", Some("foo"))]
+    #[test_case("item.json#", None)]
+    #[test_case("item.json", None)]
+    fn fragment(id: &str, expected: Option<&str>) {
+        assert_eq!(super::fragment(id), expected);
     }
-    None
 }
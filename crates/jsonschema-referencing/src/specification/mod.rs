@@ -4,7 +4,7 @@ mod draft201909;
 mod draft4;
 mod draft6;
 mod draft7;
-mod ids;
+pub(crate) mod ids;
 mod subresources;
 
 use crate::{
@@ -45,23 +45,35 @@ impl Draft {
     ///
     /// On unknown `$schema` value it returns [`Error::UnknownSpecification`]
     pub fn detect(self, contents: &Value) -> Result<Draft, Error> {
-        if let Some(schema) = contents
-            .as_object()
-            .and_then(|contents| contents.get("$schema"))
-            .and_then(|schema| schema.as_str())
-        {
-            Ok(match schema.trim_end_matches('#') {
-                "https://json-schema.org/draft/2020-12/schema" => Draft::Draft202012,
-                "https://json-schema.org/draft/2019-09/schema" => Draft::Draft201909,
-                "http://json-schema.org/draft-07/schema" => Draft::Draft7,
-                "http://json-schema.org/draft-06/schema" => Draft::Draft6,
-                "http://json-schema.org/draft-04/schema" => Draft::Draft4,
-                value => return Err(Error::unknown_specification(value)),
-            })
+        if let Some(schema) = meta_schema_of(contents) {
+            Draft::from_meta_schema(schema).ok_or_else(|| Error::unknown_specification(schema))
         } else {
             Ok(self)
         }
     }
+    /// Get the specification declared by the `$schema` keyword of the given contents.
+    ///
+    /// Returns `None` if `$schema` is missing or is not a known meta-schema URI.
+    #[must_use]
+    pub fn from_schema(contents: &Value) -> Option<Draft> {
+        meta_schema_of(contents).and_then(Draft::from_meta_schema)
+    }
+    /// Draft of a meta-schema URI, with or without an empty fragment and over either `http` or
+    /// `https`.
+    fn from_meta_schema(uri: &str) -> Option<Draft> {
+        let uri = uri.trim_end_matches('#');
+        let location = uri
+            .strip_prefix("https://")
+            .or_else(|| uri.strip_prefix("http://"))?;
+        match location {
+            "json-schema.org/draft/2020-12/schema" => Some(Draft::Draft202012),
+            "json-schema.org/draft/2019-09/schema" => Some(Draft::Draft201909),
+            "json-schema.org/draft-07/schema" => Some(Draft::Draft7),
+            "json-schema.org/draft-06/schema" => Some(Draft::Draft6),
+            "json-schema.org/draft-04/schema" => Some(Draft::Draft4),
+            _ => None,
+        }
+    }
     pub(crate) fn id_of(self, contents: &Value) -> Option<&str> {
         match self {
             Draft::Draft4 => ids::legacy_id(contents),
@@ -185,6 +197,13 @@ impl Draft {
     }
 }
 
+fn meta_schema_of(contents: &Value) -> Option<&str> {
+    contents
+        .as_object()
+        .and_then(|contents| contents.get("$schema"))
+        .and_then(Value::as_str)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Draft;
@@ -197,6 +216,9 @@ mod tests {
     #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema"}), Draft::Draft7; "detect Draft 7")]
     #[test_case(&json!({"$schema": "http://json-schema.org/draft-06/schema"}), Draft::Draft6; "detect Draft 6")]
     #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema"}), Draft::Draft4; "detect Draft 4")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft/2020-12/schema"}), Draft::Draft202012; "detect Draft 2020-12 over http")]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft-07/schema#"}), Draft::Draft7; "detect Draft 7 over https")]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft-04/schema"}), Draft::Draft4; "detect Draft 4 over https")]
     #[test_case(&json!({}), Draft::Draft7; "default to Draft 7 when no $schema")]
     fn test_detect(contents: &serde_json::Value, expected: Draft) {
         let result = Draft::Draft7
@@ -213,6 +235,16 @@ mod tests {
         assert_eq!(error.to_string(), "Unknown specification: invalid");
     }
 
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#"}), Some(Draft::Draft7); "known")]
+    #[test_case(&json!({"$schema": "invalid"}), None; "unknown")]
+    #[test_case(&json!({"$schema": "ftp://json-schema.org/draft-07/schema"}), None; "unknown scheme")]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft-07/schema#/definitions"}), None; "non-empty fragment")]
+    #[test_case(&json!({}), None; "missing")]
+    #[test_case(&json!(true), None; "boolean schema")]
+    fn test_from_schema(contents: &serde_json::Value, expected: Option<Draft>) {
+        assert_eq!(Draft::from_schema(contents), expected);
+    }
+
     #[test_case(Draft::Draft4; "Draft 4 stays Draft 4")]
     #[test_case(Draft::Draft6; "Draft 6 stays Draft 6")]
     #[test_case(Draft::Draft7; "Draft 7 stays Draft 7")]
@@ -1,3 +1,5 @@
+use std::{borrow::Cow, str::Utf8Error};
+
 use fluent_uri::{
     encoding::{encoder::Fragment, EStr, Encoder},
     Uri, UriRef,
@@ -11,13 +13,17 @@ pub use fluent_uri::encoding::encoder::Path;
 ///
 /// # Errors
 ///
-/// Returns an error if base has not schema or there is a fragment.
+/// Returns an error if base has not schema or there is a fragment. URNs and other bases without
+/// an authority and with a rootless path are opaque: only empty and fragment-only references can
+/// be resolved against them.
 pub fn resolve_against(base: &Uri<&str>, uri: &str) -> Result<Uri<String>, Error> {
-    Ok(UriRef::parse(uri)
-        .map_err(|error| Error::uri_reference_parsing_error(uri, error))?
-        .resolve_against(base)
-        .map_err(|error| Error::uri_resolving_error(uri, *base, error))?
-        .normalize())
+    Ok(normalize_scheme_based(
+        UriRef::parse(uri)
+            .map_err(|error| Error::uri_reference_parsing_error(uri, error))?
+            .resolve_against(base)
+            .map_err(|error| Error::uri_resolving_error(uri, *base, error))?
+            .normalize(),
+    ))
 }
 
 /// Parses a URI reference from a string into a [`crate::Uri`].
@@ -30,9 +36,11 @@ pub fn from_str(uri: &str) -> Result<Uri<String>, Error> {
         .map_err(|error| Error::uri_reference_parsing_error(uri, error))?
         .normalize();
     if uriref.has_scheme() {
-        Ok(Uri::try_from(uriref.as_str())
-            .map_err(|error| Error::uri_parsing_error(uriref.as_str(), error))?
-            .into())
+        Ok(normalize_scheme_based(
+            Uri::try_from(uriref.as_str())
+                .map_err(|error| Error::uri_parsing_error(uriref.as_str(), error))?
+                .into(),
+        ))
     } else {
         Ok(uriref
             .resolve_against(&DEFAULT_ROOT_URI.borrow())
@@ -40,6 +48,31 @@ pub fn from_str(uri: &str) -> Result<Uri<String>, Error> {
     }
 }
 
+/// Scheme-based normalization from RFC 3986, section 6.2.3, on top of the syntax-based one done
+/// by `normalize`: the default port is removed, so that e.g. `http://example.com:80/` and
+/// `http://example.com/` identify the same resource.
+fn normalize_scheme_based(uri: Uri<String>) -> Uri<String> {
+    let default_port = match uri.scheme().as_str() {
+        "http" | "ws" => 80,
+        "https" | "wss" => 443,
+        _ => return uri,
+    };
+    let Some(authority) = uri.authority() else {
+        return uri;
+    };
+    if authority.port_to_u16() != Ok(Some(default_port)) {
+        return uri;
+    }
+    // The authority directly follows `scheme://`
+    let start = uri.scheme().as_str().len() + 3;
+    let port_start = start + authority.as_str().rfind(':').expect("Port is present");
+    let end = start + authority.as_str().len();
+    let mut normalized = String::with_capacity(uri.as_str().len());
+    normalized.push_str(&uri.as_str()[..port_start]);
+    normalized.push_str(&uri.as_str()[end..]);
+    Uri::parse(normalized).expect("Removing the port keeps the URI valid")
+}
+
 pub(crate) static DEFAULT_ROOT_URI: Lazy<Uri<String>> =
     Lazy::new(|| Uri::parse("json-schema:///".to_string()).expect("Invalid URI"));
 
@@ -72,3 +105,80 @@ pub fn encode_to(input: &str, buffer: &mut String) {
         }
     }
 }
+
+/// Decodes a JSON Pointer written as a percent-encoded URI fragment, e.g. `/$defs/caf%C3%A9`.
+///
+/// Segments are decoded one by one, so an encoded slash (`%2F`) is a part of a key, like `~1`,
+/// rather than a separator. The result is still an escaped JSON Pointer.
+///
+/// # Errors
+///
+/// Returns an error if the decoded bytes are not valid UTF-8.
+pub fn decode_pointer(fragment: &str) -> Result<Cow<'_, str>, Utf8Error> {
+    if !fragment.contains('%') {
+        return Ok(Cow::Borrowed(fragment));
+    }
+    let mut pointer = String::with_capacity(fragment.len());
+    for (idx, segment) in fragment.split('/').enumerate() {
+        if idx > 0 {
+            pointer.push('/');
+        }
+        for ch in percent_encoding::percent_decode_str(segment)
+            .decode_utf8()?
+            .chars()
+        {
+            if ch == '/' {
+                pointer.push_str("~1");
+            } else {
+                pointer.push(ch);
+            }
+        }
+    }
+    Ok(Cow::Owned(pointer))
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    #[test_case("/$defs/a", "/$defs/a"; "plain")]
+    #[test_case("/$defs/caf%C3%A9", "/$defs/café"; "non-ascii")]
+    #[test_case("/$defs/a%2Fb", "/$defs/a~1b"; "encoded slash")]
+    #[test_case("/$defs/a~1b/c~0d", "/$defs/a~1b/c~0d"; "escapes")]
+    #[test_case("/$defs/a%25b%20c", "/$defs/a%b c"; "percent and space")]
+    fn decode_pointer(fragment: &str, expected: &str) {
+        assert_eq!(
+            super::decode_pointer(fragment).expect("Valid UTF-8"),
+            expected
+        );
+    }
+
+    #[test_case("HTTP://Example.COM/schema.json", "http://example.com/schema.json"; "case")]
+    #[test_case("http://example.com/a/./b/../schema.json", "http://example.com/a/schema.json"; "dot segments")]
+    #[test_case("http://example.com/%7euser/%3a", "http://example.com/~user/%3A"; "percent encoding")]
+    #[test_case("http://example.com:80/schema.json", "http://example.com/schema.json"; "default http port")]
+    #[test_case("https://example.com:443/schema.json?q#/a", "https://example.com/schema.json?q#/a"; "default https port")]
+    #[test_case("http://user@example.com:080", "http://user@example.com"; "default port with leading zero")]
+    #[test_case("https://example.com:80/schema.json", "https://example.com:80/schema.json"; "other port")]
+    #[test_case("file:///schema.json", "file:///schema.json"; "other scheme")]
+    #[test_case("urn:example:a/../b", "urn:example:a/../b"; "urn")]
+    fn from_str(uri: &str, expected: &str) {
+        assert_eq!(
+            super::from_str(uri).expect("Invalid URI").as_str(),
+            expected
+        );
+    }
+
+    #[test_case("http://example.com/a/b.json", "../c.json", "http://example.com/c.json"; "dot segments")]
+    #[test_case("http://example.com/a/b.json", "HTTP://EXAMPLE.COM:80/c.json", "http://example.com/c.json"; "absolute")]
+    #[test_case("http://example.com/", "//Example.com:80/", "http://example.com/"; "network path")]
+    fn resolve_against(base: &str, uri: &str, expected: &str) {
+        let base = super::from_str(base).expect("Invalid URI");
+        assert_eq!(
+            super::resolve_against(&base.borrow(), uri)
+                .expect("Invalid URI")
+                .as_str(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use jsonschema::ValidationScratch;
+use serde_json::{json, Value};
+
+/// Counts allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn schema_and_instances() -> (Value, Vec<(&'static str, Value)>) {
+    let schema = json!({
+        "$defs": {
+            "address": {
+                "type": "object",
+                "properties": {
+                    "street": {"type": "string", "minLength": 1},
+                    "zip": {"type": "string", "maxLength": 10}
+                },
+                "required": ["street"]
+            }
+        },
+        "type": "object",
+        "properties": {
+            "id": {"type": "integer", "minimum": 1},
+            "name": {"type": "string", "minLength": 1, "maxLength": 64},
+            "kind": {"enum": ["person", "company"]},
+            "version": {"const": 1},
+            "score": {"anyOf": [{"type": "number", "maximum": 100}, {"type": "null"}]},
+            "contact": {
+                "oneOf": [
+                    {"required": ["email"]},
+                    {"required": ["phone"]}
+                ]
+            },
+            "home": {"$ref": "#/$defs/address"},
+            "tags": {
+                "type": "array",
+                "items": {"type": "string"},
+                "maxItems": 10,
+                "uniqueItems": true
+            },
+            "extra": {
+                "type": "object",
+                "propertyNames": {"maxLength": 8},
+                "patternProperties": {"^x_": {"type": "boolean"}},
+                "additionalProperties": {"type": "integer"}
+            }
+        },
+        "allOf": [{"required": ["id"]}, {"required": ["name"]}]
+    });
+    let valid = json!({
+        "id": 7,
+        "name": "Jane",
+        "kind": "person",
+        "version": 1,
+        "score": 42.5,
+        "contact": {"email": "jane@example.com"},
+        "home": {"street": "Main St", "zip": "12345"},
+        "tags": ["a", "b", "c"],
+        "extra": {"x_flag": true, "count": 3}
+    });
+    let invalid = json!({
+        "id": 0,
+        "name": "",
+        "kind": "animal",
+        "version": 2,
+        "score": "high",
+        "contact": {"email": "jane@example.com", "phone": "555"},
+        "home": {"zip": "12345678901"},
+        "tags": ["a", "a", 1],
+        "extra": {"x_flag": 1, "long_property": "nope"}
+    });
+    (schema, vec![("valid", valid), ("invalid", invalid)])
+}
+
+fn run_benchmarks(c: &mut Criterion) {
+    let (schema, instances) = schema_and_instances();
+    let validator = jsonschema::validator_for(&schema).expect("Valid schema");
+    for (name, instance) in &instances {
+        // Warm up lazily initialized state before counting
+        let _ = validator.is_valid(instance);
+        let count = allocations(|| {
+            let _ = validator.is_valid(instance);
+        });
+        assert_eq!(
+            count, 0,
+            "`is_valid` allocated {count} times for the {name} instance"
+        );
+        c.bench_function(&format!("allocations/{name}/is_valid"), |b| {
+            b.iter(|| validator.is_valid(instance))
+        });
+
+        let iter_errors = allocations(|| {
+            let _ = validator.iter_errors(instance).collect::<Vec<_>>();
+        });
+        let mut errors = Vec::new();
+        let mut scratch = ValidationScratch::new();
+        validator.validate_into_with(instance, &mut errors, &mut scratch);
+        let validate_into = allocations(|| {
+            validator.validate_into_with(instance, &mut errors, &mut scratch);
+        });
+        assert!(
+            validate_into < iter_errors,
+            "`validate_into_with` allocated {validate_into} times and `iter_errors` {iter_errors} \
+             times for the {name} instance"
+        );
+        c.bench_function(&format!("allocations/{name}/iter_errors"), |b| {
+            b.iter(|| validator.iter_errors(instance).collect::<Vec<_>>())
+        });
+        c.bench_function(&format!("allocations/{name}/validate_into_with"), |b| {
+            b.iter(|| validator.validate_into_with(instance, &mut errors, &mut scratch))
+        });
+    }
+}
+
+criterion_group!(allocations_group, run_benchmarks);
+criterion_main!(allocations_group);
@@ -0,0 +1,50 @@
+use codspeed_criterion_compat::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jsonschema::{BatchMode, BatchOptions};
+use serde_json::{json, Value};
+
+fn run_benchmarks(c: &mut Criterion) {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "integer", "minimum": 0},
+            "name": {"type": "string", "maxLength": 32},
+            "tags": {"type": "array", "items": {"type": "string"}}
+        },
+        "required": ["id", "name"]
+    });
+    let validator = jsonschema::validator_for(&schema).expect("Valid schema");
+    // Every hundredth record has a negative id
+    let instances: Vec<Value> = (0..10_000)
+        .map(|idx| {
+            let id = if idx % 100 == 0 { -idx } else { idx };
+            json!({"id": id, "name": format!("item-{idx}"), "tags": ["a", "b"]})
+        })
+        .collect();
+
+    c.bench_function("batch/iter_errors", |b| {
+        b.iter(|| {
+            instances
+                .iter()
+                .map(|instance| validator.iter_errors(instance).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        });
+    });
+    for (name, mode) in [
+        ("validity", BatchMode::Validity),
+        ("first_error", BatchMode::FirstError),
+        ("all_errors", BatchMode::AllErrors),
+    ] {
+        let options = BatchOptions {
+            mode,
+            max_invalid: None,
+        };
+        c.bench_with_input(
+            BenchmarkId::new("batch/validate_batch", name),
+            &options,
+            |b, options| b.iter(|| validator.validate_batch_with(&instances, *options)),
+        );
+    }
+}
+
+criterion_group!(batch, run_benchmarks);
+criterion_main!(batch);
@@ -1,6 +1,6 @@
 use benchmark::run_error_formatting_benchmarks;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use serde_json::Value;
+use serde_json::{json, Value};
 
 fn bench_error_formatting(c: &mut Criterion, name: &str, schema: &Value, instance: &Value) {
     let validator = jsonschema::validator_for(schema).expect("Valid schema");
@@ -13,10 +13,24 @@ fn bench_error_formatting(c: &mut Criterion, name: &str, schema: &Value, instanc
     );
 }
 
+/// Every item fails two keywords, producing thousands of errors that share their schema path.
+fn bench_many_errors(c: &mut Criterion) {
+    let schema = json!({"items": {"type": "string", "minimum": 10}});
+    let validator = jsonschema::validator_for(&schema).expect("Valid schema");
+    let instance = Value::Array((0..5000).map(|idx| json!(idx % 10)).collect());
+
+    c.bench_with_input(
+        BenchmarkId::new("iter_errors", "5000 items"),
+        &instance,
+        |b, instance| b.iter(|| validator.iter_errors(instance).count()),
+    );
+}
+
 fn run_benchmarks(c: &mut Criterion) {
     run_error_formatting_benchmarks(&mut |name, schema, instance| {
         bench_error_formatting(c, name, schema, instance);
     });
+    bench_many_errors(c);
 }
 
 criterion_group!(error_formatting, run_benchmarks);
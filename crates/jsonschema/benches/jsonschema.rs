@@ -1,6 +1,6 @@
 use benchmark::Benchmark;
 use codspeed_criterion_compat::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 
 fn bench_build(c: &mut Criterion, name: &str, schema: &Value) {
     c.bench_with_input(BenchmarkId::new("build", name), schema, |b, schema| {
@@ -34,6 +34,19 @@ fn bench_validate(c: &mut Criterion, name: &str, schema: &Value, instance: &Valu
     );
 }
 
+fn bench_validate_first(c: &mut Criterion, name: &str, schema: &Value, instance: &Value) {
+    let validator = jsonschema::validator_for(schema).expect("Valid schema");
+    c.bench_with_input(
+        BenchmarkId::new("validate_first", name),
+        instance,
+        |b, instance| {
+            b.iter(|| {
+                let _ = validator.validate_first(instance);
+            })
+        },
+    );
+}
+
 fn bench_apply(c: &mut Criterion, name: &str, schema: &Value, instance: &Value) {
     let validator = jsonschema::validator_for(schema).expect("Valid schema");
     c.bench_with_input(BenchmarkId::new("apply", name), instance, |b, instance| {
@@ -43,7 +56,75 @@ fn bench_apply(c: &mut Criterion, name: &str, schema: &Value, instance: &Value)
     });
 }
 
+/// A schema where many properties point to a few shared definitions.
+fn shared_references() -> (Value, Value) {
+    let mut properties = Map::new();
+    let mut instance = Map::new();
+    for idx in 0..100 {
+        properties.insert(format!("address{idx}"), json!({"$ref": "#/$defs/address"}));
+        properties.insert(format!("person{idx}"), json!({"$ref": "#/$defs/person"}));
+        instance.insert(
+            format!("address{idx}"),
+            json!({"street": "Main", "city": "Springfield", "zip": "12345"}),
+        );
+        instance.insert(
+            format!("person{idx}"),
+            json!({"name": "Homer", "home": {"street": "Main", "city": "Springfield"}}),
+        );
+    }
+    let schema = json!({
+        "type": "object",
+        "properties": properties,
+        "$defs": {
+            "address": {
+                "type": "object",
+                "properties": {
+                    "street": {"type": "string"},
+                    "city": {"type": "string"},
+                    "zip": {"type": "string", "pattern": "^[0-9]{5}$"}
+                },
+                "required": ["street", "city"]
+            },
+            "person": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "home": {"$ref": "#/$defs/address"},
+                    "work": {"$ref": "#/$defs/address"}
+                },
+                "required": ["name"]
+            }
+        }
+    });
+    (schema, Value::Object(instance))
+}
+
+/// A small object where most keywords are cheap checks without subschemas.
+fn small_object() -> (Value, Value) {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "name": {"type": "string", "minLength": 1, "maxLength": 64},
+            "age": {"type": "integer", "minimum": 0, "maximum": 150},
+            "kind": {"const": "person"},
+            "score": {"type": "number", "minimum": 0.5}
+        },
+        "required": ["name", "age"]
+    });
+    let instance = json!({"name": "Alice", "age": 30, "kind": "person", "score": 3.5});
+    (schema, instance)
+}
+
 fn run_benchmarks(c: &mut Criterion) {
+    let (schema, instance) = shared_references();
+    bench_build(c, "Shared references", &schema);
+    bench_is_valid(c, "Shared references", &schema, &instance);
+
+    let (schema, instance) = small_object();
+    bench_is_valid(c, "Small object", &schema, &instance);
+    bench_validate(c, "Small object", &schema, &instance);
+    bench_validate_first(c, "Small object", &schema, &instance);
+
     for benchmark in Benchmark::iter() {
         benchmark.run(&mut |name, schema, instances| {
             bench_build(c, name, schema);
@@ -51,6 +132,7 @@ fn run_benchmarks(c: &mut Criterion) {
                 let name = format!("{}/{}", name, instance.name);
                 bench_is_valid(c, &name, schema, &instance.data);
                 bench_validate(c, &name, schema, &instance.data);
+                bench_validate_first(c, &name, schema, &instance.data);
                 bench_apply(c, &name, schema, &instance.data);
             }
         });
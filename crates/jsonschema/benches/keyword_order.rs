@@ -0,0 +1,34 @@
+use codspeed_criterion_compat::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jsonschema::KeywordOrder;
+use serde_json::{json, Value};
+
+/// A schema where `type` rejects any string, but ten patterns would be checked against it
+/// first in schema order.
+fn schema() -> Value {
+    let patterns: Vec<_> = (0..10)
+        .map(|idx| json!({"pattern": format!("^(item-{idx}|[a-z]+[0-9]*)+$")}))
+        .collect();
+    json!({"allOf": patterns, "type": "integer"})
+}
+
+fn run_benchmarks(c: &mut Criterion) {
+    let schema = schema();
+    let instance = json!("abcdefghij".repeat(10));
+    for (name, order) in [
+        ("preserve", KeywordOrder::Preserve),
+        ("optimized", KeywordOrder::Optimized),
+    ] {
+        let validator = jsonschema::options()
+            .with_keyword_order(order)
+            .build(&schema)
+            .expect("Valid schema");
+        c.bench_with_input(
+            BenchmarkId::new("is_valid", name),
+            &instance,
+            |b, instance| b.iter(|| validator.is_valid(instance)),
+        );
+    }
+}
+
+criterion_group!(keyword_order, run_benchmarks);
+criterion_main!(keyword_order);
@@ -0,0 +1,60 @@
+use codspeed_criterion_compat::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jsonschema::OptimizationLevel;
+use serde_json::{json, Value};
+
+/// A schema composed from nested `allOf` layers, as produced by schema generators that model
+/// inheritance: every layer adds a property and repeats the shared constraints.
+fn layered_schema(depth: usize) -> Value {
+    let mut schema = json!({"type": "object", "required": ["id"]});
+    for layer in 0..depth {
+        let name = format!("field{layer}");
+        schema = json!({
+            "allOf": [
+                schema,
+                {
+                    "type": "object",
+                    "required": [name],
+                    "properties": {name: {"type": "integer", "minimum": 0}}
+                }
+            ]
+        });
+    }
+    schema
+}
+
+fn layered_instance(depth: usize) -> Value {
+    let mut instance = serde_json::Map::new();
+    instance.insert("id".into(), json!(1));
+    for layer in 0..depth {
+        instance.insert(format!("field{layer}"), json!(layer));
+    }
+    Value::Object(instance)
+}
+
+fn run_benchmarks(c: &mut Criterion) {
+    let schema = layered_schema(8);
+    let instance = layered_instance(8);
+    for (name, level) in [
+        ("none", OptimizationLevel::None),
+        ("flatten", OptimizationLevel::Flatten),
+        ("merge", OptimizationLevel::Merge),
+    ] {
+        let validator = jsonschema::options()
+            .with_optimization_level(level)
+            .build(&schema)
+            .expect("Valid schema");
+        c.bench_with_input(
+            BenchmarkId::new("is_valid", name),
+            &instance,
+            |b, instance| b.iter(|| validator.is_valid(instance)),
+        );
+        c.bench_with_input(
+            BenchmarkId::new("validate", name),
+            &instance,
+            |b, instance| b.iter(|| validator.validate(instance)),
+        );
+    }
+}
+
+criterion_group!(optimize, run_benchmarks);
+criterion_main!(optimize);
@@ -0,0 +1,47 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Value};
+
+fn run_benchmarks(c: &mut Criterion) {
+    let schema = json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "minimum": 0},
+                "name": {"type": "string", "maxLength": 32},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["id", "name"]
+        }
+    });
+    let instance = Value::Array(
+        (0..1_000_000)
+            .map(|idx| json!({"id": idx, "name": format!("item-{idx}"), "tags": ["a", "b"]}))
+            .collect(),
+    );
+    let validator = jsonschema::validator_for(&schema).expect("Valid schema");
+
+    let mut group = c.benchmark_group("parallel");
+    group.sample_size(10);
+    group.bench_function("is_valid", |b| {
+        b.iter(|| validator.is_valid(&instance));
+    });
+    let max_threads = std::thread::available_parallelism().map_or(1, usize::from);
+    let mut threads = 1;
+    while threads <= max_threads {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("Failed to build a thread pool");
+        group.bench_with_input(
+            BenchmarkId::new("par_is_valid", threads),
+            &threads,
+            |b, _| b.iter(|| pool.install(|| validator.par_is_valid(&instance))),
+        );
+        threads *= 2;
+    }
+    group.finish();
+}
+
+criterion_group!(parallel, run_benchmarks);
+criterion_main!(parallel);
@@ -0,0 +1,96 @@
+use codspeed_criterion_compat::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jsonschema::KeywordOrder;
+use serde_json::{json, Map, Value};
+
+fn names(count: usize) -> Vec<String> {
+    (0..count).map(|idx| format!("property_{idx}")).collect()
+}
+
+fn object(names: &[String]) -> Value {
+    let mut object = Map::new();
+    for name in names {
+        object.insert(name.clone(), json!(1));
+    }
+    Value::Object(object)
+}
+
+fn run_benchmarks(c: &mut Criterion) {
+    for count in [3, 300] {
+        let required = names(count);
+        let schema = json!({"required": required});
+        let validator = jsonschema::validator_for(&schema).expect("Valid schema");
+        let valid = object(&required);
+        // Missing a property in the middle of the list, with an extra one in its place
+        let mut invalid = required.clone();
+        invalid[count / 2] = "extra".to_string();
+        let invalid = object(&invalid);
+        for (name, instance) in [("valid", &valid), ("invalid", &invalid)] {
+            c.bench_with_input(
+                BenchmarkId::new(format!("required/{count}/is_valid"), name),
+                instance,
+                |b, instance| b.iter(|| validator.is_valid(instance)),
+            );
+            c.bench_with_input(
+                BenchmarkId::new(format!("required/{count}/iter_errors"), name),
+                instance,
+                |b, instance| b.iter(|| validator.iter_errors(instance).count()),
+            );
+        }
+    }
+}
+
+/// Objects with `required` names that have expensive subschemas, where 30 out of 100
+/// instances miss one of them.
+fn objects() -> (Value, Vec<Value>) {
+    let required = names(6);
+    let mut properties = Map::new();
+    for name in &required {
+        properties.insert(
+            name.clone(),
+            json!({"type": "string", "pattern": "^[a-z]+(-[a-z]+)*$"}),
+        );
+    }
+    let schema = json!({"type": "object", "properties": properties, "required": required});
+    let instances = (0..100)
+        .map(|idx| {
+            let mut names = required.clone();
+            if idx % 10 < 3 {
+                names.remove(idx % required.len());
+            }
+            let mut object = Map::new();
+            for name in names {
+                object.insert(name, json!("some-words-here"));
+            }
+            Value::Object(object)
+        })
+        .collect();
+    (schema, instances)
+}
+
+fn run_object_benchmarks(c: &mut Criterion) {
+    let (schema, instances) = objects();
+    for (name, order) in [
+        ("preserve", KeywordOrder::Preserve),
+        ("optimized", KeywordOrder::Optimized),
+    ] {
+        let validator = jsonschema::options()
+            .with_keyword_order(order)
+            .build(&schema)
+            .expect("Valid schema");
+        c.bench_with_input(
+            BenchmarkId::new("required/objects/is_valid", name),
+            &instances,
+            |b, instances| {
+                b.iter(|| {
+                    instances
+                        .iter()
+                        .filter(|instance| validator.is_valid(*instance))
+                        .count()
+                })
+            },
+        );
+    }
+}
+
+criterion_group!(required, run_benchmarks, run_object_benchmarks);
+criterion_main!(required);
@@ -0,0 +1,87 @@
+use codspeed_criterion_compat::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Serialize)]
+struct Order {
+    id: u64,
+    customer: String,
+    items: Vec<LineItem>,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LineItem {
+    sku: String,
+    quantity: u32,
+    price: f64,
+}
+
+fn orders(count: usize) -> Vec<Order> {
+    (0..count)
+        .map(|idx| Order {
+            id: idx as u64,
+            customer: format!("customer-{idx}"),
+            items: (0..5)
+                .map(|item| LineItem {
+                    sku: format!("SKU-{idx}-{item}"),
+                    quantity: item + 1,
+                    price: 9.99,
+                })
+                .collect(),
+            notes: None,
+        })
+        .collect()
+}
+
+fn run_benchmarks(c: &mut Criterion) {
+    let schema = json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["id", "customer", "items"],
+            "properties": {
+                "id": {"type": "integer", "minimum": 0},
+                "customer": {"type": "string", "minLength": 1},
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "sku": {"type": "string", "pattern": "^SKU-"},
+                            "quantity": {"type": "integer", "minimum": 1},
+                            "price": {"type": "number", "exclusiveMinimum": 0}
+                        }
+                    }
+                },
+                "notes": {"type": ["string", "null"]}
+            }
+        }
+    });
+    let validator = jsonschema::validator_for(&schema).expect("Valid schema");
+    for count in [10, 1000] {
+        let instance = orders(count);
+        c.bench_with_input(
+            BenchmarkId::new("validate_serialize", count),
+            &instance,
+            |b, instance| {
+                b.iter(|| {
+                    let _ = validator.validate_serialize(instance);
+                })
+            },
+        );
+        c.bench_with_input(
+            BenchmarkId::new("to_value_and_validate", count),
+            &instance,
+            |b, instance| {
+                b.iter(|| {
+                    let value = serde_json::to_value(instance).expect("Serializable");
+                    let _ = validator.validate(&value);
+                })
+            },
+        );
+    }
+}
+
+criterion_group!(serialize, run_benchmarks);
+criterion_main!(serialize);
@@ -0,0 +1,392 @@
+//! Validation of many instances against the same validator.
+use serde_json::Value;
+
+use crate::{
+    paths::LazyLocation,
+    validator::{Validate, ValidationScratch},
+    ValidationError, Validator,
+};
+
+/// How much [`Validator::validate_batch_with`] reports about each invalid instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BatchMode {
+    /// Only whether the instance is valid. Invalid instances have no errors.
+    Validity,
+    /// The error [`Validator::validate`] reports.
+    #[default]
+    FirstError,
+    /// All errors, in the order [`Validator::iter_errors`] reports them.
+    AllErrors,
+}
+
+/// Options for [`Validator::validate_batch_with`].
+///
+/// ```rust
+/// use jsonschema::{BatchMode, BatchOptions};
+///
+/// let options = BatchOptions {
+///     mode: BatchMode::AllErrors,
+///     max_invalid: Some(100),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// What to report about invalid instances.
+    pub mode: BatchMode,
+    /// Stop once this many instances are invalid. The rest of the batch is not validated.
+    pub max_invalid: Option<usize>,
+}
+
+/// Results of [`Validator::validate_batch`].
+#[derive(Debug)]
+pub struct BatchReport<'a> {
+    /// The result of every validated instance, by its position in the batch.
+    ///
+    /// If validation stopped early, the last result is the invalid instance that reached
+    /// [`BatchOptions::max_invalid`].
+    pub results: Vec<Result<(), Vec<ValidationError<'a>>>>,
+    /// Whether validation stopped before the end of the batch.
+    pub stopped: bool,
+}
+
+impl<'a> BatchReport<'a> {
+    /// Whether all validated instances are valid.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.results.iter().all(Result::is_ok)
+    }
+    /// Number of invalid instances.
+    #[must_use]
+    pub fn invalid_count(&self) -> usize {
+        self.results.iter().filter(|result| result.is_err()).count()
+    }
+    /// Positions of invalid instances in the batch, with their errors.
+    pub fn invalid(&self) -> impl Iterator<Item = (usize, &[ValidationError<'a>])> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, result)| result.as_ref().err().map(|errors| (idx, &errors[..])))
+    }
+}
+
+impl Validator {
+    /// Validate every instance of `instances` with the default [`BatchOptions`], reporting the
+    /// first error of each invalid instance.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"type": "integer"}))
+    ///     .expect("Invalid schema");
+    /// let instances = [json!(1), json!("2"), json!(3)];
+    /// let report = validator.validate_batch(&instances);
+    /// let invalid: Vec<_> = report.invalid().map(|(idx, _)| idx).collect();
+    /// assert_eq!(invalid, [1]);
+    /// ```
+    pub fn validate_batch<'a>(
+        &self,
+        instances: impl IntoIterator<Item = &'a Value>,
+    ) -> BatchReport<'a> {
+        self.validate_batch_with(instances, BatchOptions::default())
+    }
+    /// Validate every instance of `instances`, as configured by `options`.
+    ///
+    /// Temporary buffers are shared by all instances, and valid instances are only checked with
+    /// [`Validator::is_valid`], so the overhead per instance is lower than calling
+    /// [`Validator::iter_errors`] for each of them.
+    ///
+    /// ```rust
+    /// use jsonschema::{BatchMode, BatchOptions};
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"type": "integer", "minimum": 5}))
+    ///     .expect("Invalid schema");
+    /// let instances = [json!(7), json!("a"), json!(3), json!(1)];
+    /// let report = validator.validate_batch_with(
+    ///     &instances,
+    ///     BatchOptions {
+    ///         mode: BatchMode::AllErrors,
+    ///         max_invalid: Some(2),
+    ///     },
+    /// );
+    /// assert!(report.stopped);
+    /// assert_eq!(report.results.len(), 3);
+    /// assert_eq!(report.invalid_count(), 2);
+    /// ```
+    pub fn validate_batch_with<'a>(
+        &self,
+        instances: impl IntoIterator<Item = &'a Value>,
+        options: BatchOptions,
+    ) -> BatchReport<'a> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validate_batch").entered();
+        let mut batch = Batch::new(self, options);
+        let mut instances = instances.into_iter();
+        let stopped =
+            instances.by_ref().any(|instance| !batch.push(instance)) && instances.next().is_some();
+        BatchReport {
+            results: batch.results,
+            stopped,
+        }
+    }
+    /// The same as [`Validator::validate_batch`], but instances are validated on the `rayon`
+    /// thread pool.
+    #[cfg(feature = "parallel")]
+    pub fn par_validate_batch<'a>(
+        &self,
+        instances: impl IntoIterator<Item = &'a Value>,
+    ) -> BatchReport<'a> {
+        self.par_validate_batch_with(instances, BatchOptions::default())
+    }
+    /// The same as [`Validator::validate_batch_with`], but instances are split into shards that
+    /// are validated on the `rayon` thread pool.
+    ///
+    /// The report is the same as the one [`Validator::validate_batch_with`] returns. With
+    /// [`BatchOptions::max_invalid`], each shard stops on its own, so instances after the last
+    /// reported one may be validated as well.
+    #[cfg(feature = "parallel")]
+    pub fn par_validate_batch_with<'a>(
+        &self,
+        instances: impl IntoIterator<Item = &'a Value>,
+        options: BatchOptions,
+    ) -> BatchReport<'a> {
+        use rayon::prelude::*;
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("par_validate_batch").entered();
+        let instances: Vec<&'a Value> = instances.into_iter().collect();
+        let shards = rayon::current_num_threads() * 4;
+        let shard_size = ((instances.len() + shards - 1) / shards).max(1);
+        let shards: Vec<_> = instances
+            .par_chunks(shard_size)
+            .map(|shard| {
+                let mut batch = Batch::new(self, options);
+                for instance in shard {
+                    if !batch.push(instance) {
+                        break;
+                    }
+                }
+                batch.results
+            })
+            .collect();
+        // A shard only stops after `max_invalid` invalid instances of its own, so all shards
+        // before the one containing the last reported instance are complete
+        let mut results = Vec::with_capacity(instances.len());
+        let mut invalid = 0;
+        for shard in shards {
+            for result in shard {
+                invalid += usize::from(result.is_err());
+                results.push(result);
+                if options.max_invalid.is_some_and(|max| invalid >= max) {
+                    let stopped = results.len() < instances.len();
+                    return BatchReport { results, stopped };
+                }
+            }
+        }
+        BatchReport {
+            results,
+            stopped: false,
+        }
+    }
+}
+
+/// Results of a batch being validated, with the buffers shared by its instances.
+struct Batch<'v, 'a> {
+    validator: &'v Validator,
+    options: BatchOptions,
+    results: Vec<Result<(), Vec<ValidationError<'a>>>>,
+    invalid: usize,
+    errors: Vec<ValidationError<'a>>,
+    scratch: ValidationScratch<'static>,
+}
+
+impl<'v, 'a> Batch<'v, 'a> {
+    fn new(validator: &'v Validator, options: BatchOptions) -> Self {
+        Batch {
+            validator,
+            options,
+            results: Vec::new(),
+            invalid: 0,
+            errors: Vec::new(),
+            scratch: ValidationScratch::new(),
+        }
+    }
+    /// Validate the next instance. Returns `false` once the batch should stop.
+    fn push(&mut self, instance: &'a Value) -> bool {
+        let root = &self.validator.root;
+        let result = match self.options.mode {
+            BatchMode::Validity => {
+                if root.is_valid(instance) {
+                    Ok(())
+                } else {
+                    Err(Vec::new())
+                }
+            }
+            BatchMode::FirstError => {
+                if root.is_valid(instance) {
+                    Ok(())
+                } else {
+                    root.validate(instance, &LazyLocation::new())
+                        .map_err(|error| vec![error])
+                }
+            }
+            BatchMode::AllErrors => {
+                root.collect_errors(
+                    instance,
+                    &LazyLocation::new(),
+                    &mut self.errors,
+                    &mut self.scratch,
+                );
+                if self.errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(std::mem::take(&mut self.errors))
+                }
+            }
+        };
+        self.invalid += usize::from(result.is_err());
+        self.results.push(result);
+        !self
+            .options
+            .max_invalid
+            .is_some_and(|max| self.invalid >= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{BatchMode, BatchOptions};
+
+    fn instances() -> Vec<Value> {
+        vec![
+            json!(1),
+            json!("a"),
+            json!(2),
+            json!(-1.5),
+            json!(3),
+            json!(null),
+        ]
+    }
+
+    #[test_case(BatchMode::Validity, &[0, 0, 0]; "validity")]
+    #[test_case(BatchMode::FirstError, &[1, 1, 1]; "first error")]
+    #[test_case(BatchMode::AllErrors, &[1, 2, 1]; "all errors")]
+    fn modes(mode: BatchMode, expected: &[usize]) {
+        let validator =
+            crate::validator_for(&json!({"type": "integer", "minimum": 0})).expect("Valid schema");
+        let instances = instances();
+        let report = validator.validate_batch_with(
+            &instances,
+            BatchOptions {
+                mode,
+                max_invalid: None,
+            },
+        );
+        assert!(!report.stopped);
+        assert_eq!(report.results.len(), instances.len());
+        let invalid: Vec<_> = report.invalid().map(|(idx, _)| idx).collect();
+        assert_eq!(invalid, [1, 3, 5]);
+        let counts: Vec<_> = report.invalid().map(|(_, errors)| errors.len()).collect();
+        assert_eq!(counts, expected);
+    }
+
+    #[test]
+    fn same_errors_as_iter_errors() {
+        let validator = crate::validator_for(&json!({
+            "properties": {"id": {"type": "integer"}, "name": {"maxLength": 2}},
+            "required": ["id"]
+        }))
+        .expect("Valid schema");
+        let instances = vec![
+            json!({"id": 1}),
+            json!({"id": "1", "name": "long"}),
+            json!({"name": "ab"}),
+        ];
+        let report = validator.validate_batch_with(
+            &instances,
+            BatchOptions {
+                mode: BatchMode::AllErrors,
+                max_invalid: None,
+            },
+        );
+        for (instance, result) in instances.iter().zip(&report.results) {
+            let expected: Vec<_> = validator
+                .iter_errors(instance)
+                .map(|error| error.to_string())
+                .collect();
+            let actual: Vec<_> = match result {
+                Ok(()) => Vec::new(),
+                Err(errors) => errors.iter().map(ToString::to_string).collect(),
+            };
+            assert_eq!(actual, expected);
+        }
+        let report = validator.validate_batch(&instances);
+        for (instance, result) in instances.iter().zip(&report.results) {
+            let expected = validator
+                .validate(instance)
+                .map_err(|error| error.to_string());
+            let actual = result
+                .as_ref()
+                .map_err(|errors| errors[0].to_string())
+                .map(|_| ());
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test_case(Some(1), 2, true; "first invalid")]
+    #[test_case(Some(2), 4, true; "second invalid")]
+    #[test_case(Some(3), 6, false; "last instance")]
+    #[test_case(Some(4), 6, false; "not reached")]
+    #[test_case(None, 6, false; "unlimited")]
+    fn max_invalid(max_invalid: Option<usize>, validated: usize, stopped: bool) {
+        let validator = crate::validator_for(&json!({"type": "integer"})).expect("Valid schema");
+        let instances = instances();
+        let report = validator.validate_batch_with(
+            &instances,
+            BatchOptions {
+                mode: BatchMode::FirstError,
+                max_invalid,
+            },
+        );
+        assert_eq!(report.results.len(), validated);
+        assert_eq!(report.stopped, stopped);
+    }
+
+    #[test]
+    fn empty() {
+        let validator = crate::validator_for(&json!({"type": "integer"})).expect("Valid schema");
+        let report = validator.validate_batch([]);
+        assert!(report.is_valid());
+        assert!(report.results.is_empty());
+        assert!(!report.stopped);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test_case(None; "unlimited")]
+    #[test_case(Some(1); "first invalid")]
+    #[test_case(Some(250); "stops in the middle")]
+    #[test_case(Some(10_000); "not reached")]
+    fn parallel(max_invalid: Option<usize>) {
+        let validator = crate::validator_for(&json!({"type": "integer", "multipleOf": 3}))
+            .expect("Valid schema");
+        let instances: Vec<_> = (0..1000).map(|idx| json!(idx)).collect();
+        for mode in [
+            BatchMode::Validity,
+            BatchMode::FirstError,
+            BatchMode::AllErrors,
+        ] {
+            let options = BatchOptions { mode, max_invalid };
+            let expected = validator.validate_batch_with(&instances, options);
+            let actual = validator.par_validate_batch_with(&instances, options);
+            assert_eq!(actual.stopped, expected.stopped);
+            assert_eq!(
+                format!("{:?}", actual.results),
+                format!("{:?}", expected.results)
+            );
+        }
+    }
+}
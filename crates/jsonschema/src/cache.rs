@@ -0,0 +1,164 @@
+//! A small LRU cache of compiled validators used by the one-shot `is_valid` / `validate` functions.
+//!
+//! Entries are looked up by a hash of the schema contents, so calling the one-shot functions in a
+//! loop with the same schema compiles it only once. A hit is only used if the stored schema is
+//! equal to the given one, so colliding hashes never share a validator.
+use crate::{Draft, ValidationOptions, Validator};
+use ahash::AHasher;
+use serde_json::Value;
+use std::{
+    collections::VecDeque,
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+const CAPACITY: usize = 16;
+
+struct Entry {
+    hash: u64,
+    draft: Option<Draft>,
+    validator: Arc<Validator>,
+}
+
+static CACHE: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+
+/// Get a validator for `schema`, compiling it with the given draft (or auto-detection) on a miss.
+///
+/// # Panics
+///
+/// This function panics if an invalid schema is passed.
+pub(crate) fn get(schema: &Value, draft: Option<Draft>) -> Arc<Validator> {
+    let hash = hash(schema);
+    {
+        let mut cache = CACHE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(idx) = cache.iter().position(|entry| {
+            entry.hash == hash && entry.draft == draft && **entry.validator.schema() == *schema
+        }) {
+            let entry = cache.remove(idx).expect("Index is valid");
+            let validator = Arc::clone(&entry.validator);
+            cache.push_front(entry);
+            return validator;
+        }
+    }
+    // Compile without holding the lock, as it may take a while (e.g. to fetch remote documents)
+    let options = draft.map_or_else(crate::options, ValidationOptions::for_draft);
+    let validator = Arc::new(options.build(schema).expect("Invalid schema"));
+    let mut cache = CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    cache.push_front(Entry {
+        hash,
+        draft,
+        validator: Arc::clone(&validator),
+    });
+    cache.truncate(CAPACITY);
+    validator
+}
+
+fn hash(schema: &Value) -> u64 {
+    let mut hasher = AHasher::default();
+    hash_value(schema, &mut hasher);
+    hasher.finish()
+}
+
+/// Hash the structure of `value`, so that different schemas rarely get the same key.
+///
+/// Every value is prefixed with a tag and containers with their length, so e.g. `["a"]` and `"a"`
+/// or `[[], []]` and `[[[]]]` do not collide.
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+    match value {
+        Value::Null => state.write_u8(0),
+        Value::Bool(item) => {
+            state.write_u8(1);
+            item.hash(state);
+        }
+        Value::Number(item) => {
+            state.write_u8(2);
+            item.hash(state);
+        }
+        Value::String(item) => {
+            state.write_u8(3);
+            item.hash(state);
+        }
+        Value::Array(items) => {
+            state.write_u8(4);
+            state.write_usize(items.len());
+            for item in items {
+                hash_value(item, state);
+            }
+        }
+        Value::Object(items) => {
+            state.write_u8(5);
+            state.write_usize(items.len());
+            for (key, item) in items {
+                key.hash(state);
+                hash_value(item, state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get, hash, Entry, CACHE};
+    use crate::Draft;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use test_case::test_case;
+
+    #[test]
+    fn reuses_validator() {
+        let schema = json!({"minimum": 5, "$comment": "cache::reuses_validator"});
+        let first = get(&schema, None);
+        let second = get(&schema, None);
+        assert!(Arc::ptr_eq(&first, &second));
+        // Different draft is a different entry
+        let other = get(&schema, Some(Draft::Draft4));
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(other.draft(), Draft::Draft4);
+    }
+
+    #[test]
+    fn shares_validator_between_equal_schemas() {
+        let schema = json!({"minimum": 5, "$comment": "cache::shares_validator"});
+        let first = get(&schema, None);
+        let second = get(&schema.clone(), None);
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test_case(&json!({"enum": ["a"]}), &json!({"enum": [["a"]]}))]
+    #[test_case(&json!({"enum": [[], []]}), &json!({"enum": [[[]]]}))]
+    #[test_case(&json!({"enum": [1]}), &json!({"enum": [1.0]}))]
+    #[test_case(&json!({"enum": [null]}), &json!({"enum": [false]}))]
+    fn distinguishes_schemas(left: &Value, right: &Value) {
+        assert!(!Arc::ptr_eq(&get(left, None), &get(right, None)));
+    }
+
+    #[test]
+    fn detects_changed_schema() {
+        let mut schema = json!({"minimum": 5, "$comment": "cache::detects_changed_schema"});
+        let first = get(&schema, None);
+        assert!(!first.is_valid(&json!(3)));
+        schema["minimum"] = json!(1);
+        let second = get(&schema, None);
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(second.is_valid(&json!(3)));
+    }
+
+    #[test]
+    fn ignores_colliding_hash() {
+        let schema = json!({"minimum": 5, "$comment": "cache::ignores_colliding_hash"});
+        let other = Arc::new(crate::validator_for(&json!({"minimum": 1})).expect("Valid schema"));
+        // Pretend that another schema has the same hash
+        CACHE.lock().expect("Not poisoned").push_front(Entry {
+            hash: hash(&schema),
+            draft: None,
+            validator: Arc::clone(&other),
+        });
+        let validator = get(&schema, None);
+        assert!(!Arc::ptr_eq(&validator, &other));
+        assert!(!validator.is_valid(&json!(3)));
+    }
+}
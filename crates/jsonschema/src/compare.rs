@@ -0,0 +1,271 @@
+//! Comparing which instances two schemas accept.
+use serde_json::Value;
+
+use crate::{paths::Location, ValidationError, Validator};
+
+/// One of the two compared schemas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    /// The first schema.
+    Left,
+    /// The second schema.
+    Right,
+}
+
+/// The result of validating a corpus against two schemas, as returned by [`compare`].
+///
+/// Serializes to JSON, e.g. to be kept as a CI artifact:
+///
+/// ```json
+/// {
+///   "instances": 3,
+///   "disagreements": [
+///     {
+///       "index": 1,
+///       "instance": 7,
+///       "acceptedBy": "right",
+///       "rejection": {
+///         "keywordLocation": "/maximum",
+///         "instanceLocation": "",
+///         "error": "7 is greater than the maximum of 5"
+///       }
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct CompareReport {
+    /// Number of validated instances.
+    pub instances: usize,
+    /// Instances accepted by exactly one of the schemas, in corpus order.
+    pub disagreements: Vec<Disagreement>,
+}
+
+/// An instance that only one of the compared schemas accepts.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Disagreement {
+    /// Position of the instance in the corpus.
+    pub index: usize,
+    /// The instance itself.
+    pub instance: Value,
+    /// The schema that accepts the instance.
+    pub accepted_by: Side,
+    /// The first error reported by the other schema.
+    pub rejection: Rejection,
+}
+
+/// A validation error in a [`CompareReport`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rejection {
+    /// Location of the failed keyword.
+    pub keyword_location: Location,
+    /// Location of the invalid value within the instance.
+    pub instance_location: Location,
+    /// Error message.
+    pub error: String,
+}
+
+impl From<ValidationError<'_>> for Rejection {
+    fn from(error: ValidationError<'_>) -> Self {
+        Rejection {
+            keyword_location: error.schema_path.clone(),
+            instance_location: error.instance_path.clone(),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl CompareReport {
+    /// Whether both schemas accept the same instances of the corpus.
+    #[must_use]
+    pub fn is_equivalent(&self) -> bool {
+        self.disagreements.is_empty()
+    }
+}
+
+/// Validate every instance of `corpus` against both `left` and `right` and report the instances
+/// that only one of them accepts, e.g. to check that a refactored schema still accepts the same
+/// instances.
+///
+/// Whether two schemas accept the same instances in general can not be decided, so the result
+/// is only as good as the corpus.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let before = json!({"allOf": [{"type": "integer"}, {"minimum": 0}]});
+/// let after = json!({"type": "integer", "minimum": 0, "maximum": 5});
+/// let report = jsonschema::compare(&before, &after, [json!(1), json!(7), json!(-1)])
+///     .expect("Invalid schema");
+/// assert_eq!(report.instances, 3);
+/// assert_eq!(report.disagreements.len(), 1);
+/// assert_eq!(report.disagreements[0].index, 1);
+/// assert_eq!(report.disagreements[0].accepted_by, jsonschema::Side::Left);
+/// assert_eq!(report.disagreements[0].rejection.keyword_location.as_str(), "/maximum");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if either schema is invalid.
+pub fn compare(
+    left: &Value,
+    right: &Value,
+    corpus: impl IntoIterator<Item = Value>,
+) -> Result<CompareReport, ValidationError<'static>> {
+    let left = crate::validator_for(left)?;
+    let right = crate::validator_for(right)?;
+    Ok(left.compare(&right, corpus))
+}
+
+/// Like [`compare`], with a corpus of `samples` instances generated from both schemas: valid
+/// instances and instances that violate exactly one constraint, taking turns between the
+/// schemas. Instances that can not be generated, e.g. for unsatisfiable schemas, are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use rand::SeedableRng;
+/// use serde_json::json;
+///
+/// let before = json!({"type": "string", "enum": ["a", "b"]});
+/// let after = json!({"enum": ["a", "b"]});
+/// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(42);
+/// let report = jsonschema::compare_generated(&before, &after, 100, &mut rng)?;
+/// assert!(report.is_equivalent());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if either schema is invalid.
+#[cfg(feature = "generate")]
+pub fn compare_generated<R: rand::Rng + ?Sized>(
+    left: &Value,
+    right: &Value,
+    samples: usize,
+    rng: &mut R,
+) -> Result<CompareReport, crate::GenerateError> {
+    let generators = [crate::Generator::new(left)?, crate::Generator::new(right)?];
+    let corpus: Vec<Value> = (0..samples)
+        .filter_map(|idx| {
+            let generator = &generators[idx % 2];
+            if idx % 4 < 2 {
+                generator.generate(rng).ok()
+            } else {
+                generator
+                    .generate_invalid(rng)
+                    .ok()
+                    .map(|invalid| invalid.instance)
+            }
+        })
+        .collect();
+    Ok(generators[0]
+        .validator
+        .compare(&generators[1].validator, corpus))
+}
+
+impl Validator {
+    /// Validate every instance of `corpus` against this and the `other` validator and report the
+    /// instances that only one of them accepts. See [`compare`].
+    pub fn compare(
+        &self,
+        other: &Validator,
+        corpus: impl IntoIterator<Item = Value>,
+    ) -> CompareReport {
+        let mut instances = 0;
+        let mut disagreements = Vec::new();
+        for (index, instance) in corpus.into_iter().enumerate() {
+            instances += 1;
+            let (accepted_by, error) = match (self.validate(&instance), other.validate(&instance)) {
+                (Ok(()), Err(error)) => (Side::Left, error),
+                (Err(error), Ok(())) => (Side::Right, error),
+                _ => continue,
+            };
+            let rejection = Rejection::from(error);
+            disagreements.push(Disagreement {
+                index,
+                instance,
+                accepted_by,
+                rejection,
+            });
+        }
+        CompareReport {
+            instances,
+            disagreements,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{compare, Side};
+
+    #[test_case(&json!({"minimum": 1}), &json!({"exclusiveMinimum": 0}), &[json!(0.5), json!(1), json!("a")], &[(0, Side::Right)]; "numbers")]
+    #[test_case(&json!({"type": "object"}), &json!({"type": ["object", "null"]}), &[json!({}), json!(null)], &[(1, Side::Right)]; "types")]
+    #[test_case(&json!({"maxItems": 1}), &json!({"items": false}), &[json!([]), json!([1])], &[(1, Side::Left)]; "arrays")]
+    #[test_case(&json!({"const": 1}), &json!({"enum": [1]}), &[json!(1), json!(1.0), json!(2)], &[]; "equivalent")]
+    fn disagreements(left: &Value, right: &Value, corpus: &[Value], expected: &[(usize, Side)]) {
+        let report = compare(left, right, corpus.to_vec()).expect("Valid schemas");
+        assert_eq!(report.instances, corpus.len());
+        assert_eq!(report.is_equivalent(), expected.is_empty());
+        let disagreements: Vec<_> = report
+            .disagreements
+            .iter()
+            .map(|disagreement| (disagreement.index, disagreement.accepted_by))
+            .collect();
+        assert_eq!(disagreements, expected);
+    }
+
+    #[test]
+    fn serialize() {
+        let report = compare(
+            &json!({"properties": {"a": {"type": "string"}}}),
+            &json!({}),
+            [json!({"a": 1})],
+        )
+        .expect("Valid schemas");
+        assert_eq!(
+            serde_json::to_value(report).expect("Serializable report"),
+            json!({
+                "instances": 1,
+                "disagreements": [{
+                    "index": 0,
+                    "instance": {"a": 1},
+                    "acceptedBy": "right",
+                    "rejection": {
+                        "keywordLocation": "/properties/a/type",
+                        "instanceLocation": "/a",
+                        "error": r#"1 is not of type "string""#
+                    }
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_schema() {
+        assert!(compare(&json!({}), &json!({"type": 1}), []).is_err());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test_case(&json!({"type": "integer", "minimum": 0}), &json!({"type": "integer", "minimum": 1}), false; "different")]
+    #[test_case(&json!({"type": "string", "enum": ["a", "b"]}), &json!({"enum": ["b", "a"]}), true; "equivalent")]
+    fn generated(left: &Value, right: &Value, equivalent: bool) {
+        use rand::SeedableRng;
+
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(42);
+        let report = super::compare_generated(left, right, 200, &mut rng).expect("Valid schemas");
+        assert!(report.instances > 0);
+        assert_eq!(report.is_equivalent(), equivalent);
+    }
+}
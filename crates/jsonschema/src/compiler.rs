@@ -3,29 +3,233 @@ use crate::{
     content_media_type::ContentMediaTypeCheckType,
     keywords::{
         self,
-        custom::{CustomKeyword, KeywordFactory},
+        custom::{self, CustomKeyword, KeywordFactory},
         format::Format,
+        ref_::PendingReferences,
         BoxedValidator, BuiltinKeyword, Keyword,
     },
+    lint::{self, Lint},
     node::SchemaNode,
+    optimize,
     options::ValidationOptions,
-    paths::{Location, LocationSegment},
+    output::AbsoluteLocation,
+    paths::{LazyLocation, Location, LocationSegment},
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
-    ValidationError, Validator,
+    projection::ConstrainedPaths,
+    validator::Validate,
+    CompiledRegistry, ValidationError, Validator,
 };
-use ahash::{AHashMap, AHashSet};
+use ahash::{AHashMap, AHashSet, AHasher};
 use once_cell::sync::Lazy;
 use referencing::{
-    uri, Draft, List, Registry, Resolved, Resolver, Resource, ResourceRef, Uri, Vocabulary,
-    VocabularySet, SPECIFICATIONS,
+    uri, Draft, List, Registry, Resolved, Resolver, Resource, ResourceRef, Retrieve, Uri,
+    Vocabulary, VocabularySet, SPECIFICATIONS,
+};
+use serde_json::{Map, Value};
+use std::{
+    cell::{Cell, RefCell},
+    hash::{Hash, Hasher},
+    ops::Range,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
-use serde_json::Value;
-use std::{cell::RefCell, rc::Rc, sync::Arc};
 
-const DEFAULT_SCHEME: &str = "json-schema";
+pub(crate) const DEFAULT_SCHEME: &str = "json-schema";
 pub(crate) const DEFAULT_ROOT_URL: &str = "json-schema:///";
 type BaseUri = Uri<String>;
-type ResolverComponents = (Arc<BaseUri>, List<BaseUri>, Resource);
+pub(crate) type ResolverComponents = (Arc<BaseUri>, List<BaseUri>, Resource, Location);
+
+/// Keywords, documents and patterns encountered while compiling a schema.
+#[derive(Debug, Default)]
+struct Inventory {
+    keywords: Vec<(Location, String)>,
+    documents: Vec<String>,
+    /// Sources of compiled regular expressions
+    patterns: Vec<String>,
+    /// Parts of the schema removed by optimizations
+    warnings: Vec<Lint>,
+}
+
+impl Inventory {
+    /// Current end of the inventory.
+    fn mark(&self) -> Mark {
+        Mark {
+            keywords: self.keywords.len(),
+            documents: self.documents.len(),
+            patterns: self.patterns.len(),
+        }
+    }
+    /// Everything recorded since `mark` was taken.
+    fn since(&self, mark: Mark) -> Recorded {
+        Recorded {
+            keywords: mark.keywords..self.keywords.len(),
+            documents: mark.documents..self.documents.len(),
+            patterns: mark.patterns..self.patterns.len(),
+        }
+    }
+}
+
+/// Position in the inventory, kept small as it lives on the stack during recursive compilation.
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    keywords: usize,
+    documents: usize,
+    patterns: usize,
+}
+
+/// Keywords, documents and patterns recorded in the inventory while compiling a node.
+#[derive(Debug, Clone)]
+struct Recorded {
+    keywords: Range<usize>,
+    documents: Range<usize>,
+    patterns: Range<usize>,
+}
+
+/// Already compiled subschemas, keyed by a hash of their contents.
+///
+/// Identical subschemas compiled under the same draft, base URI and vocabularies share their
+/// validators instead of being compiled again.
+#[derive(Debug, Default)]
+struct Interner {
+    enabled: bool,
+    nodes: AHashMap<u64, Vec<InternedNode>>,
+    /// Hashes of already visited values, keyed by their address. A stale entry only leads to a
+    /// missed reuse, as reused subschemas are compared by value.
+    hashes: AHashMap<(usize, Draft), Option<u64>>,
+}
+
+/// Custom keywords already initialized, keyed by the address of their value and their location.
+///
+/// Keywords like `unevaluatedProperties` share them with their siblings instead of running the
+/// factories again.
+#[derive(Default)]
+struct CustomKeywords(AHashMap<(usize, Location), Arc<dyn custom::Keyword>>);
+
+impl std::fmt::Debug for CustomKeywords {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomKeywords")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+impl Interner {
+    /// Hash a subschema, or return `None` if it contains keywords whose meaning depends on where
+    /// the subschema is located.
+    fn hash(&mut self, value: &Value, draft: Draft) -> Option<u64> {
+        let key = (value as *const Value as usize, draft);
+        if let Some(hash) = self.hashes.get(&key) {
+            return *hash;
+        }
+        let mut hasher = AHasher::default();
+        let hash = match value {
+            Value::Array(items) => {
+                items.len().hash(&mut hasher);
+                items
+                    .iter()
+                    .try_for_each(|item| self.hash_into(item, draft, &mut hasher))
+                    .map(|()| hasher.finish())
+            }
+            Value::Object(object) => {
+                object.len().hash(&mut hasher);
+                object
+                    .iter()
+                    .try_for_each(|(key, value)| {
+                        if matches!(
+                            key.as_str(),
+                            "$id" | "$anchor" | "$dynamicAnchor" | "$recursiveAnchor"
+                        ) || (key == "id" && draft == Draft::Draft4)
+                        {
+                            return None;
+                        }
+                        key.hash(&mut hasher);
+                        self.hash_into(value, draft, &mut hasher)
+                    })
+                    .map(|()| hasher.finish())
+            }
+            _ => {
+                self.hash_into(value, draft, &mut hasher)?;
+                Some(hasher.finish())
+            }
+        };
+        self.hashes.insert(key, hash);
+        hash
+    }
+
+    fn hash_into(&mut self, value: &Value, draft: Draft, hasher: &mut AHasher) -> Option<()> {
+        match value {
+            Value::Null => 0_u8.hash(hasher),
+            Value::Bool(value) => value.hash(hasher),
+            Value::Number(value) => value.hash(hasher),
+            Value::String(value) => value.hash(hasher),
+            Value::Array(_) | Value::Object(_) => self.hash(value, draft)?.hash(hasher),
+        }
+        Some(())
+    }
+}
+
+#[derive(Debug)]
+struct InternedNode {
+    draft: Draft,
+    base_uri: Option<Uri<String>>,
+    vocabularies: VocabularySet,
+    /// Copy of the subschema to confirm a hash match. Only subschemas whose hash was seen before
+    /// are copied, as most subschemas are unique.
+    schema: Option<Value>,
+    node: SchemaNode,
+    recorded: Recorded,
+}
+
+/// Compiled targets of `$ref`, keyed by their absolute location.
+///
+/// An entry is inserted before its target is compiled, so references reaching it again while it
+/// is still being compiled are recognized as recursive.
+#[derive(Debug, Default)]
+struct References {
+    enabled: bool,
+    /// `None` while the target is being compiled
+    nodes: AHashMap<Arc<Uri<String>>, Option<CompiledReference>>,
+}
+
+#[derive(Debug)]
+struct CompiledReference {
+    node: SchemaNode,
+    recorded: Recorded,
+}
+
+/// Retriever that remembers the URIs of all documents it was asked for.
+struct RecordingRetriever<'r> {
+    inner: &'r dyn Retrieve,
+    /// Retrieved documents nested deeper than this are rejected
+    max_depth: usize,
+    retrieved: Mutex<AHashSet<String>>,
+    has_dynamic_references: AtomicBool,
+}
+
+impl Retrieve for RecordingRetriever<'_> {
+    fn retrieve(&self, uri: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("retrieve", uri = %uri).entered();
+        let value = self.inner.retrieve(uri);
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &value {
+            tracing::debug!(%error, "Failed to retrieve document");
+        }
+        let value = value?;
+        check_schema_depth(&value, self.max_depth)?;
+        if has_dynamic_references(&value) {
+            self.has_dynamic_references.store(true, Ordering::Relaxed);
+        }
+        self.retrieved
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(uri.as_str().to_string());
+        Ok(value)
+    }
+}
 
 /// Container for information required to build a tree.
 ///
@@ -37,8 +241,20 @@ pub(crate) struct Context<'a> {
     resolver: Rc<Resolver<'a>>,
     vocabularies: VocabularySet,
     location: Location,
+    /// Location of the current schema within the resource it belongs to
+    resource_pointer: Location,
     pub(crate) draft: Draft,
     seen: Rc<RefCell<AHashSet<Arc<Uri<String>>>>>,
+    inventory: Rc<RefCell<Inventory>>,
+    interner: Rc<RefCell<Interner>>,
+    references: Rc<RefCell<References>>,
+    /// Number of schema nodes compiled so far
+    compiled_nodes: Rc<Cell<usize>>,
+    custom_keywords: Rc<RefCell<CustomKeywords>>,
+    /// Depth of the location where compilation started
+    base_depth: usize,
+    pending: PendingReferences,
+    lazy_references: bool,
 }
 
 impl<'a> Context<'a> {
@@ -50,16 +266,43 @@ impl<'a> Context<'a> {
         draft: Draft,
         location: Location,
     ) -> Self {
+        let lazy_references = config.are_references_lazy();
+        let base_depth = location.depth();
         Context {
             config,
             registry,
             resolver,
             location,
+            resource_pointer: Location::new(),
             vocabularies,
             draft,
             seen: Rc::new(RefCell::new(AHashSet::new())),
+            inventory: Rc::new(RefCell::new(Inventory::default())),
+            interner: Rc::new(RefCell::new(Interner::default())),
+            references: Rc::new(RefCell::new(References::default())),
+            compiled_nodes: Rc::new(Cell::new(0)),
+            custom_keywords: Rc::new(RefCell::new(CustomKeywords::default())),
+            base_depth,
+            pending: PendingReferences::default(),
+            lazy_references,
         }
     }
+    /// Share pending lazy references with the context that created them.
+    pub(crate) fn with_pending_references(
+        mut self,
+        pending: PendingReferences,
+        lazy_references: bool,
+    ) -> Self {
+        self.references.borrow_mut().enabled = pending.share_targets();
+        self.pending = pending;
+        self.lazy_references = lazy_references;
+        self
+    }
+    /// Set the location of the schema being compiled within its resource.
+    pub(crate) fn with_resource_pointer(mut self, resource_pointer: Location) -> Self {
+        self.resource_pointer = resource_pointer;
+        self
+    }
     pub(crate) fn draft(&self) -> Draft {
         self.draft
     }
@@ -73,6 +316,12 @@ impl<'a> Context<'a> {
         resource: ResourceRef,
     ) -> Result<Context<'a>, referencing::Error> {
         let resolver = self.resolver.in_subresource(resource)?;
+        // Subresources with their own base URI start a new document
+        let resource_pointer = if resolver.base_uri() == self.resolver.base_uri() {
+            self.resource_pointer.clone()
+        } else {
+            Location::new()
+        };
         Ok(Context {
             config: Arc::clone(&self.config),
             registry: Arc::clone(&self.registry),
@@ -80,7 +329,16 @@ impl<'a> Context<'a> {
             vocabularies: self.vocabularies.clone(),
             draft: resource.draft(),
             location: self.location.clone(),
+            resource_pointer,
             seen: Rc::clone(&self.seen),
+            inventory: Rc::clone(&self.inventory),
+            interner: Rc::clone(&self.interner),
+            references: Rc::clone(&self.references),
+            compiled_nodes: Rc::clone(&self.compiled_nodes),
+            custom_keywords: Rc::clone(&self.custom_keywords),
+            base_depth: self.base_depth,
+            pending: self.pending.clone(),
+            lazy_references: self.lazy_references,
         })
     }
     pub(crate) fn as_resource_ref<'r>(&'a self, contents: &'r Value) -> ResourceRef<'r> {
@@ -92,20 +350,199 @@ impl<'a> Context<'a> {
 
     #[inline]
     pub(crate) fn new_at_location(&'a self, chunk: impl Into<LocationSegment<'a>>) -> Self {
+        let chunk = chunk.into();
         let location = self.location.join(chunk);
+        let resource_pointer = if self.resource_pointer == self.location {
+            location.clone()
+        } else {
+            self.resource_pointer.join(chunk)
+        };
+        self.with_locations(location, resource_pointer)
+    }
+
+    /// Create a context for a subschema at `location` within the same resource.
+    pub(crate) fn at_location(&self, location: Location) -> Context<'a> {
+        let resource_pointer = location
+            .rebase(&self.location, &self.resource_pointer)
+            .unwrap_or_else(|| location.clone());
+        self.with_locations(location, resource_pointer)
+    }
+
+    fn with_locations(&self, location: Location, resource_pointer: Location) -> Context<'a> {
         Context {
             config: Arc::clone(&self.config),
             registry: Arc::clone(&self.registry),
             resolver: Rc::clone(&self.resolver),
             vocabularies: self.vocabularies.clone(),
             location,
+            resource_pointer,
             draft: self.draft,
             seen: Rc::clone(&self.seen),
+            inventory: Rc::clone(&self.inventory),
+            interner: Rc::clone(&self.interner),
+            references: Rc::clone(&self.references),
+            compiled_nodes: Rc::clone(&self.compiled_nodes),
+            custom_keywords: Rc::clone(&self.custom_keywords),
+            base_depth: self.base_depth,
+            pending: self.pending.clone(),
+            lazy_references: self.lazy_references,
         }
     }
 
     pub(crate) fn lookup(&'a self, reference: &str) -> Result<Resolved<'a>, referencing::Error> {
-        self.resolver.lookup(reference)
+        let resolved = self.resolver.lookup(reference)?;
+        let mut document = (*self
+            .resolver
+            .resolve_against(&self.resolver.base_uri().borrow(), reference)?)
+        .clone();
+        document.set_fragment(None);
+        self.inventory
+            .borrow_mut()
+            .documents
+            .push(document.as_str().to_string());
+        Ok(resolved)
+    }
+
+    /// Hash of `contents` if it could be shared with other identical subschemas.
+    fn interning_hash(&self, contents: &Value) -> Option<u64> {
+        let mut interner = self.interner.borrow_mut();
+        if !interner.enabled || !contents.is_object() {
+            return None;
+        }
+        interner.hash(contents, self.draft)
+    }
+
+    /// Reuse an identical subschema compiled under the same conditions, if there is one.
+    fn find_interned(&self, hash: u64, contents: &Value) -> Option<SchemaNode> {
+        let interner = self.interner.borrow();
+        let base_uri = self.base_uri();
+        let interned = interner.nodes.get(&hash)?.iter().find(|interned| {
+            interned.draft == self.draft
+                && interned.base_uri == base_uri
+                && interned.vocabularies == self.vocabularies
+                && interned.schema.as_ref() == Some(contents)
+        })?;
+        self.record_shared(&interned.node, interned.recorded.clone());
+        Some(interned.node.share(self))
+    }
+
+    // Kept out of line, as they are called around recursive compilation
+    #[inline(never)]
+    fn inventory_mark(&self) -> Mark {
+        self.inventory.borrow().mark()
+    }
+    #[inline(never)]
+    fn recorded_since(&self, mark: Mark) -> Recorded {
+        self.inventory.borrow().since(mark)
+    }
+
+    /// Record keywords of a shared node once more at the current location, along with the
+    /// documents and patterns it depends on.
+    fn record_shared(&self, node: &SchemaNode, recorded: Recorded) {
+        let mut inventory = self.inventory.borrow_mut();
+        inventory.documents.extend_from_within(recorded.documents);
+        inventory.patterns.extend_from_within(recorded.patterns);
+        for idx in recorded.keywords {
+            let (location, keyword) = &inventory.keywords[idx];
+            if let Some(location) = location.rebase(node.location(), &self.location) {
+                let keyword = keyword.clone();
+                inventory.keywords.push((location, keyword));
+            }
+        }
+    }
+
+    fn intern(&self, hash: u64, contents: &Value, node: &SchemaNode, recorded: Recorded) {
+        let mut interner = self.interner.borrow_mut();
+        let nodes = interner.nodes.entry(hash).or_default();
+        let schema = (!nodes.is_empty()).then(|| contents.clone());
+        nodes.push(InternedNode {
+            draft: self.draft,
+            base_uri: self.base_uri(),
+            vocabularies: self.vocabularies.clone(),
+            schema,
+            node: node.share(self),
+            recorded,
+        });
+    }
+
+    /// Absolute location of a `$ref` target if compiled targets can be shared.
+    pub(crate) fn reference_key(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Arc<Uri<String>>>, referencing::Error> {
+        if !self.references.borrow().enabled {
+            return Ok(None);
+        }
+        self.resolver
+            .resolve_against(&self.resolver.base_uri().borrow(), reference)
+            .map(Some)
+    }
+
+    /// Reuse an already compiled `$ref` target at the current location.
+    ///
+    /// Returns `None` if the target was not compiled yet or is still being compiled.
+    pub(crate) fn find_compiled_reference(&self, key: &Arc<Uri<String>>) -> Option<SchemaNode> {
+        let references = self.references.borrow();
+        let compiled = references.nodes.get(key)?.as_ref()?;
+        self.record_shared(&compiled.node, compiled.recorded.clone());
+        Some(compiled.node.share_at(&self.location))
+    }
+
+    /// Compile a `$ref` target and remember it for other references to the same location.
+    pub(crate) fn compile_reference<'r>(
+        &self,
+        key: Option<Arc<Uri<String>>>,
+        resource: ResourceRef<'r>,
+    ) -> Result<SchemaNode, ValidationError<'r>> {
+        let Some(key) = key else {
+            return compile_with(self, resource);
+        };
+        self.references
+            .borrow_mut()
+            .nodes
+            .insert(Arc::clone(&key), None);
+        let mark = self.inventory_mark();
+        let node = compile_with(self, resource)?;
+        let recorded = self.recorded_since(mark);
+        self.references.borrow_mut().nodes.insert(
+            key,
+            Some(CompiledReference {
+                node: node.share(self),
+                recorded,
+            }),
+        );
+        Ok(node)
+    }
+
+    /// Record a keyword that was compiled at the current location.
+    pub(crate) fn record_keyword(&self, keyword: &str) {
+        self.inventory
+            .borrow_mut()
+            .keywords
+            .push((self.location.join(keyword), keyword.to_string()));
+    }
+
+    /// Record the source of a regular expression compiled at the current location.
+    pub(crate) fn record_pattern(&self, pattern: &str) {
+        self.inventory
+            .borrow_mut()
+            .patterns
+            .push(pattern.to_string());
+    }
+
+    /// Record a part of the schema that never affects validation and is therefore not compiled.
+    pub(crate) fn record_warning(&self, warning: Lint) {
+        self.inventory.borrow_mut().warnings.push(warning);
+    }
+
+    pub(crate) fn are_references_lazy(&self) -> bool {
+        self.lazy_references
+    }
+    pub(crate) fn pending_references(&self) -> &PendingReferences {
+        &self.pending
+    }
+    pub(crate) fn resolver_base_uri(&self) -> Arc<Uri<String>> {
+        self.resolver.base_uri()
     }
 
     pub(crate) fn scopes(&self) -> List<Uri<String>> {
@@ -120,7 +557,18 @@ impl<'a> Context<'a> {
             Some((*base_uri).clone())
         }
     }
-    fn is_known_keyword(&self, keyword: &str) -> bool {
+    /// Absolute location of the current schema.
+    ///
+    /// Resources without a base URI are located under the default `json-schema:///` URI. Such
+    /// locations tell schemas apart, e.g. to merge identical errors, but they are not reported.
+    pub(crate) fn absolute_location(&self) -> Option<AbsoluteLocation> {
+        Some(AbsoluteLocation::new(
+            &self.resolver.base_uri(),
+            &self.resource_pointer,
+            &self.location,
+        ))
+    }
+    pub(crate) fn is_known_keyword(&self, keyword: &str) -> bool {
         self.draft.is_known_keyword(keyword)
     }
     pub(crate) fn supports_adjacent_validation(&self) -> bool {
@@ -130,9 +578,7 @@ impl<'a> Context<'a> {
         !matches!(self.draft, Draft::Draft4)
     }
     pub(crate) fn validates_formats_by_default(&self) -> bool {
-        self.config
-            .validate_formats()
-            .unwrap_or_else(|| matches!(self.draft, Draft::Draft4 | Draft::Draft6 | Draft::Draft7))
+        self.config.validates_formats_for(self.draft)
     }
     pub(crate) fn are_unknown_formats_ignored(&self) -> bool {
         self.config.are_unknown_formats_ignored()
@@ -143,6 +589,7 @@ impl<'a> Context<'a> {
         draft: Draft,
         vocabularies: VocabularySet,
         location: Location,
+        resource_pointer: Location,
     ) -> Context<'a> {
         Context {
             config: Arc::clone(&self.config),
@@ -151,7 +598,16 @@ impl<'a> Context<'a> {
             draft,
             vocabularies,
             location,
+            resource_pointer,
             seen: Rc::clone(&self.seen),
+            inventory: Rc::clone(&self.inventory),
+            interner: Rc::clone(&self.interner),
+            references: Rc::clone(&self.references),
+            compiled_nodes: Rc::clone(&self.compiled_nodes),
+            custom_keywords: Rc::clone(&self.custom_keywords),
+            base_depth: self.base_depth,
+            pending: self.pending.clone(),
+            lazy_references: self.lazy_references,
         }
     }
     pub(crate) fn get_content_media_type_check(
@@ -173,8 +629,32 @@ impl<'a> Context<'a> {
     ) -> Option<ContentEncodingConverterType> {
         self.config.get_content_encoding_convert(content_encoding)
     }
+    /// The factory of the custom keyword `name`, if it is active in the draft of this schema.
     pub(crate) fn get_keyword_factory(&self, name: &str) -> Option<&Arc<dyn KeywordFactory>> {
-        self.config.get_keyword_factory(name)
+        self.config
+            .get_keyword_factory(name)
+            .filter(|factory| match factory.drafts() {
+                Some(drafts) => drafts.contains(&self.draft),
+                None => true,
+            })
+    }
+    /// The custom keyword initialized from `value` at `location`, if any.
+    pub(crate) fn get_custom_keyword(
+        &self,
+        value: &Value,
+        location: &Location,
+    ) -> Option<Arc<dyn custom::Keyword>> {
+        let key = (value as *const Value as usize, location.clone());
+        self.custom_keywords.borrow().0.get(&key).cloned()
+    }
+    pub(crate) fn store_custom_keyword(
+        &self,
+        value: &Value,
+        location: Location,
+        keyword: Arc<dyn custom::Keyword>,
+    ) {
+        let key = (value as *const Value as usize, location);
+        self.custom_keywords.borrow_mut().0.insert(key, keyword);
     }
     pub(crate) fn get_format(&self, format: &str) -> Option<(&String, &Arc<dyn Format>)> {
         self.config.get_format(format)
@@ -219,10 +699,12 @@ impl<'a> Context<'a> {
         let resource = self.draft().create_resource(resolved.contents().clone());
         let mut base_uri = resolved.resolver().base_uri().to_owned();
         let scopes = resolved.resolver().dynamic_scope();
+        let mut pointer = resource_pointer(resolved.resolver(), resolved.contents(), reference);
         if let Some(id) = resource.id() {
             base_uri = Arc::new(uri::resolve_against(&base_uri.borrow(), id)?);
+            pointer = Location::new();
         };
-        Ok(Some((base_uri, scopes, resource)))
+        Ok(Some((base_uri, scopes, resource, pointer)))
     }
 
     pub(crate) fn location(&self) -> &Location {
@@ -282,57 +764,538 @@ static META_SCHEMA_VALIDATORS: Lazy<AHashMap<Draft, Validator>> = Lazy::new(|| {
 });
 
 pub(crate) fn build_validator(
-    mut config: ValidationOptions,
+    config: ValidationOptions,
     schema: &Value,
 ) -> Result<Validator, ValidationError<'static>> {
+    // Cloning recurses into the schema too
+    check_schema_depth(schema, config.max_schema_depth())?;
     let draft = config.draft_for(schema)?;
-    let resource_ref = draft.create_resource_ref(schema);
-    let resource = draft.create_resource(schema.clone());
-    let base_uri = resource.id().unwrap_or(DEFAULT_ROOT_URL).to_string();
+    let base_uri = draft
+        .create_resource_ref(schema)
+        .id()
+        .unwrap_or(DEFAULT_ROOT_URL)
+        .to_string();
+    let mut validators = compile_documents(config, vec![(base_uri, schema.clone())], false)?;
+    Ok(validators.pop().expect("One validator per document"))
+}
+
+/// Compile documents that may reference each other into validators sharing their subschemas.
+pub(crate) fn build_registry(
+    config: ValidationOptions,
+    documents: Vec<(String, Value)>,
+) -> Result<CompiledRegistry, ValidationError<'static>> {
+    let mut names = Vec::with_capacity(documents.len());
+    for (uri, schema) in &documents {
+        names.push(document_uris(uri, schema, config.draft_for(schema)?)?);
+    }
+    let validators = compile_documents(config, documents, true)?;
+    let mut index = AHashMap::with_capacity(names.len());
+    for (idx, uris) in names.into_iter().enumerate() {
+        for uri in uris {
+            index.insert(uri, idx);
+        }
+    }
+    Ok(CompiledRegistry { validators, index })
+}
+
+/// Compile each `(URI, schema)` document into its own validator.
+///
+/// All documents share one registry and compilation context, so subschemas referenced from several
+/// documents are compiled once. If `distinct` is set, identifiers defined by more than one
+/// document are rejected and the roots themselves are shared with references to them.
+fn compile_documents(
+    mut config: ValidationOptions,
+    documents: Vec<(String, Value)>,
+    distinct: bool,
+) -> Result<Vec<Validator>, ValidationError<'static>> {
+    config.check_disabled_keywords()?;
+    if documents.is_empty() {
+        return Ok(Vec::new());
+    }
+    #[cfg(feature = "tracing")]
+    let span = tracing::info_span!(
+        "build",
+        draft = tracing::field::Empty,
+        nodes = tracing::field::Empty,
+        external_references = tracing::field::Empty,
+    )
+    .entered();
+    // Everything below recurses into the schema
+    for (_, schema) in &documents {
+        check_schema_depth(schema, config.max_schema_depth())?;
+    }
+    for resource in config.resources.values() {
+        check_schema_depth(resource.contents(), config.max_schema_depth())?;
+    }
+    let mut drafts = Vec::with_capacity(documents.len());
+    for (_, schema) in &documents {
+        drafts.push(config.draft_for(schema)?);
+    }
+    #[cfg(feature = "tracing")]
+    span.record("draft", tracing::field::debug(drafts[0]));
 
     // Prepare additional resources to use in resolving
-    let mut resources = Vec::with_capacity(1 + config.resources.len());
-    resources.push((base_uri.clone(), resource));
+    let mut resources = Vec::with_capacity(documents.len() + config.resources.len());
+    let mut provided = AHashSet::with_capacity(documents.len() + config.resources.len());
+    let mut names = Vec::with_capacity(documents.len());
+    for ((uri, schema), draft) in documents.iter().zip(&drafts) {
+        let uris = document_uris(uri, schema, *draft).unwrap_or_default();
+        provided.extend(uris.iter().cloned());
+        names.push(uris);
+        resources.push((uri.clone(), draft.create_resource(schema.clone())));
+    }
     for (uri, resource) in config.resources.drain() {
+        if let Ok(normalized) = uri::from_str(uri.trim_end_matches('#')) {
+            provided.insert(normalized.as_str().to_string());
+        }
         resources.push((uri, resource));
     }
 
     // Get retriever for external resources
-    let retriever = Arc::clone(&config.retriever);
+    let inner = Arc::clone(&config.retriever);
+    let retriever = RecordingRetriever {
+        inner: &*inner,
+        max_depth: config.max_schema_depth(),
+        retrieved: Mutex::new(AHashSet::new()),
+        has_dynamic_references: AtomicBool::new(false),
+    };
+    // Sharing subschemas is not possible if their meaning depends on the dynamic scope
+    let mut is_static = !resources
+        .iter()
+        .any(|(_, resource)| has_dynamic_references(resource.contents()));
 
-    // Build a registry & resolver needed for validator compilation
-    let registry = Arc::new(SPECIFICATIONS.clone().try_with_resources_and_retriever(
-        resources.into_iter(),
-        &*retriever,
-        draft,
-    )?);
-    let vocabularies = registry.find_vocabularies(draft, schema);
-    let resolver = Rc::new(registry.try_resolver(&base_uri)?);
+    // Build a registry needed for validator compilation
+    let draft = drafts[0];
+    let registry = if distinct {
+        SPECIFICATIONS.clone().try_with_documents_and_retriever(
+            resources.into_iter(),
+            &retriever,
+            draft,
+        )
+    } else {
+        SPECIFICATIONS.clone().try_with_resources_and_retriever(
+            resources.into_iter(),
+            &retriever,
+            draft,
+        )
+    };
+    let registry =
+        Arc::new(registry.map_err(|error| locate_registry_error(error, &documents, &drafts))?);
+    is_static &= !retriever.has_dynamic_references.load(Ordering::Relaxed);
 
     let config = Arc::new(config);
     let ctx = Context::new(
         Arc::clone(&config),
         Arc::clone(&registry),
-        resolver,
-        vocabularies,
+        Rc::new(registry.try_resolver(DEFAULT_ROOT_URL)?),
+        registry.find_vocabularies(draft, &documents[0].1),
         draft,
         Location::new(),
     );
+    ctx.interner.borrow_mut().enabled = is_static && !config.has_custom_keywords();
+    ctx.references.borrow_mut().enabled = is_static;
+    ctx.pending.set_share_targets(is_static);
 
-    // Validate the schema itself
+    // Validate the schemas themselves
     if config.validate_schema {
-        if let Err(error) = META_SCHEMA_VALIDATORS
-            .get(&draft)
-            .expect("Existing draft")
-            .validate(schema)
+        for ((_, schema), draft) in documents.iter().zip(&drafts) {
+            if let Err(error) = META_SCHEMA_VALIDATORS
+                .get(draft)
+                .expect("Existing draft")
+                .validate(schema)
+            {
+                return Err(error.into_owned());
+            }
+        }
+    }
+
+    // Finally, compile the validators
+    let mut compiled = Vec::with_capacity(documents.len());
+    for ((uri, schema), draft) in documents.iter().zip(&drafts) {
+        let mark = ctx.inventory_mark();
+        let warnings_start = ctx.inventory.borrow().warnings.len();
+        let document_ctx = ctx.with_resolver_and_draft(
+            registry.try_resolver(uri.trim_end_matches('#'))?,
+            *draft,
+            registry.find_vocabularies(*draft, schema),
+            Location::new(),
+            Location::new(),
+        );
+        let resource_ref = draft.create_resource_ref(schema);
+        let root = if distinct {
+            compile_shared_root(&document_ctx, resource_ref)
+        } else {
+            compile(&document_ctx, resource_ref)
+        }
+        .map_err(|err| err.into_owned())?;
+        let recorded = ctx.recorded_since(mark);
+        let warnings = warnings_start..ctx.inventory.borrow().warnings.len();
+        if config.validates_defaults() || config.validates_examples() {
+            check_defaults(&document_ctx, schema, *draft)?;
+        }
+        compiled.push((root, recorded, warnings));
+    }
+
+    // Referenced documents that did not come from the schemas themselves
+    let inventory = ctx.inventory.take();
+    let retrieved = retriever
+        .retrieved
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    #[cfg(feature = "tracing")]
+    span.record("nodes", ctx.compiled_nodes.get())
+        .record("external_references", retrieved.len());
+    let mut validators = Vec::with_capacity(compiled.len());
+    for (((root, recorded, warnings), own), ((_, schema), draft)) in compiled
+        .into_iter()
+        .zip(names)
+        .zip(documents.into_iter().zip(drafts))
+    {
+        let mut external_references: Vec<String> = retrieved
+            .iter()
+            .cloned()
+            .chain(
+                inventory.documents[recorded.documents]
+                    .iter()
+                    .filter(|document| provided.contains(*document) && !own.contains(*document))
+                    .cloned(),
+            )
+            .collect();
+        external_references.sort_unstable();
+        external_references.dedup();
+        let mut keyword_locations = inventory.keywords[recorded.keywords].to_vec();
+        keyword_locations.sort_by(|(left, _), (right, _)| left.as_str().cmp(right.as_str()));
+        keyword_locations.dedup();
+        let constrained_paths = Arc::new(ConstrainedPaths::new(&keyword_locations));
+        let mut patterns = inventory.patterns[recorded.patterns].to_vec();
+        patterns.sort_unstable();
+        patterns.dedup();
+        let mut warnings = inventory.warnings[warnings].to_vec();
+        warnings.sort_by(|left, right| left.location.as_str().cmp(right.location.as_str()));
+        warnings.dedup();
+
+        if !config.denied_lints().is_empty() {
+            // Removed keywords are not compiled, so they are only known from the warnings
+            if let Some(lint) = lint::check(&schema, draft, &keyword_locations)
+                .into_iter()
+                .chain(warnings.iter().cloned())
+                .find(|lint| config.denied_lints().contains(&lint.code))
+            {
+                let value = schema.pointer(lint.location.as_str()).unwrap_or(&schema);
+                return Err(ValidationError::lint(value, lint).into_owned());
+            }
+        }
+
+        validators.push(Validator {
+            root,
+            config: Arc::clone(&config),
+            draft,
+            schema: Arc::new(schema),
+            pending: ctx.pending.clone(),
+            keyword_locations,
+            constrained_paths,
+            patterns,
+            external_references,
+            warnings,
+        });
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(validators = validators.len(), "Compiled schema");
+    Ok(validators)
+}
+
+/// Normalized URI of a document, followed by its root identifier resolved against it if present.
+fn document_uris(
+    uri: &str,
+    schema: &Value,
+    draft: Draft,
+) -> Result<Vec<String>, referencing::Error> {
+    let uri = uri::from_str(uri.trim_end_matches('#'))?;
+    let mut uris = vec![uri.as_str().to_string()];
+    if let Some(id) = draft.create_resource_ref(schema).id() {
+        let id = uri::resolve_against(&uri.borrow(), id.trim_end_matches('#'))?;
+        uris.push(id.as_str().to_string());
+    }
+    Ok(uris)
+}
+
+/// Compile a document root so that references to it from other documents reuse it.
+#[inline(never)]
+fn compile_shared_root<'a>(
+    ctx: &Context,
+    resource: ResourceRef<'a>,
+) -> Result<SchemaNode, ValidationError<'a>> {
+    let ctx = ctx.in_subresource(resource)?;
+    let base_uri = ctx.resolver_base_uri();
+    let Some(key) = ctx.reference_key(base_uri.as_str())? else {
+        return compile_with(&ctx, resource);
+    };
+    if let Some(node) = ctx.find_compiled_reference(&key) {
+        return Ok(node);
+    }
+    ctx.compile_reference(Some(key), resource)
+}
+
+/// Check `default` and `examples` values in all subschemas of a document against the
+/// subschemas they are in, as far as enabled in the options.
+#[inline(never)]
+fn check_defaults(
+    ctx: &Context,
+    schema: &Value,
+    draft: Draft,
+) -> Result<(), ValidationError<'static>> {
+    for (location, subschema) in crate::walk(schema, draft) {
+        let Value::Object(subschema) = subschema else {
+            continue;
+        };
+        let mut values = Vec::new();
+        if ctx.config().validates_defaults() {
+            if let Some(value) = subschema.get("default") {
+                values.push(("default", location.join("default"), value));
+            }
+        }
+        if ctx.config().validates_examples() {
+            if let Some(Value::Array(examples)) = subschema.get("examples") {
+                for (idx, value) in examples.iter().enumerate() {
+                    values.push(("examples", location.join("examples").join(idx), value));
+                }
+            }
+        }
+        if values.is_empty() {
+            continue;
+        }
+        let node = compile_at(ctx, &location)?;
+        for (keyword, value_location, value) in values {
+            let errors: Vec<_> = node
+                .iter_errors(value, &LazyLocation::new())
+                .map(ValidationError::into_owned)
+                .collect();
+            if !errors.is_empty() {
+                return Err(ValidationError::invalid_default(
+                    value_location,
+                    value,
+                    keyword,
+                    errors,
+                )
+                .into_owned());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compile the subschema at `location` in the document of `ctx`, within the resources it is
+/// embedded in.
+fn compile_at(ctx: &Context, location: &Location) -> Result<SchemaNode, ValidationError<'static>> {
+    // Fragments are percent-decoded when they are resolved
+    let reference = format!("#{}", location.as_str().replace('%', "%25"));
+    let (contents, resolver, draft) = ctx.lookup(&reference)?.into_inner();
+    let vocabularies = ctx.registry.find_vocabularies(draft, contents);
+    let resource_ref = draft.create_resource_ref(contents);
+    let resource_pointer = resource_pointer(&resolver, contents, &reference);
+    let ctx = ctx.with_resolver_and_draft(
+        resolver,
+        resource_ref.draft(),
+        vocabularies,
+        location.clone(),
+        resource_pointer,
+    );
+    compile_with(&ctx, resource_ref).map_err(ValidationError::into_owned)
+}
+
+/// Warn about keywords that would be compiled if they were not next to `$ref`.
+// Kept out of `compile` to keep its stack frame small for deeply nested schemas
+#[inline(never)]
+fn record_ignored_ref_siblings(ctx: &Context, schema: &Map<String, Value>) {
+    for keyword in schema.keys() {
+        if keyword != "$ref"
+            && !ctx.config().is_keyword_disabled(keyword)
+            && (ctx.get_keyword_factory(keyword).is_some()
+                || keywords::get_for_draft(ctx, keyword).is_some())
         {
-            return Err(error.into_owned());
+            ctx.record_warning(lint::ignored_ref_sibling(ctx.location(), keyword));
         }
     }
+}
 
-    // Finally, compile the validator
-    let root = compile(&ctx, resource_ref).map_err(|err| err.into_owned())?;
-    Ok(Validator { root, config })
+fn has_dynamic_references(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.iter().any(has_dynamic_references),
+        Value::Object(object) => object.iter().any(|(key, value)| {
+            key == "$dynamicRef" || key == "$recursiveRef" || has_dynamic_references(value)
+        }),
+        _ => false,
+    }
+}
+
+/// Fail if `value` is nested deeper than `limit`.
+///
+/// The value is walked without recursion, as it might be too deeply nested to recurse into.
+fn check_schema_depth(value: &Value, limit: usize) -> Result<(), ValidationError<'static>> {
+    let mut path = Vec::new();
+    let mut stack: Vec<Children<'_>> = Children::of(value).into_iter().collect();
+    while let Some(children) = stack.last_mut() {
+        let Some((segment, child)) = children.next() else {
+            stack.pop();
+            path.pop();
+            continue;
+        };
+        path.push(segment);
+        if stack.len() > limit {
+            let location = path
+                .into_iter()
+                .fold(Location::new(), |location, segment| location.join(segment));
+            return Err(ValidationError::max_schema_depth(location, limit));
+        }
+        match Children::of(child) {
+            Some(grandchildren) => stack.push(grandchildren),
+            None => {
+                path.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Members of an array or an object together with their location segments.
+enum Children<'a> {
+    Array(std::iter::Enumerate<std::slice::Iter<'a, Value>>),
+    Object(serde_json::map::Iter<'a>),
+}
+
+impl<'a> Children<'a> {
+    fn of(value: &'a Value) -> Option<Self> {
+        match value {
+            Value::Array(items) => Some(Children::Array(items.iter().enumerate())),
+            Value::Object(object) => Some(Children::Object(object.iter())),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = (LocationSegment<'a>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Children::Array(items) => items
+                .next()
+                .map(|(idx, item)| (LocationSegment::Index(idx), item)),
+            Children::Object(object) => object
+                .next()
+                .map(|(key, value)| (LocationSegment::Property(key), value)),
+        }
+    }
+}
+
+/// Location of `contents`, resolved from `reference`, within the resource `resolver` is at.
+#[inline(never)]
+pub(crate) fn resource_pointer(
+    resolver: &Resolver<'_>,
+    contents: &Value,
+    reference: &str,
+) -> Location {
+    let Ok(root) = resolver.lookup("#") else {
+        return Location::new();
+    };
+    let root = root.contents();
+    let fragment = reference
+        .rsplit_once('#')
+        .map_or("", |(_, fragment)| fragment);
+    if fragment.starts_with('/') {
+        if let Ok(pointer) = uri::decode_pointer(fragment) {
+            if root
+                .pointer(&pointer)
+                .is_some_and(|target| target == contents)
+            {
+                return Location::from_escaped(&pointer, root);
+            }
+        }
+    }
+    // Anchors and pointers crossing into other resources. Anchors may resolve to a copy of the
+    // subschema
+    find_within(root, &Location::new(), &|value| {
+        std::ptr::eq(value, contents)
+    })
+    .or_else(|| find_within(root, &Location::new(), &|value| value == contents))
+    .unwrap_or_default()
+}
+
+/// Locate an error raised while building the registry at the reference that caused it.
+///
+/// The registry does not track where references come from, so the documents are searched for a
+/// reference matching the error. Errors in retrieved documents are left without a location.
+fn locate_registry_error(
+    error: referencing::Error,
+    documents: &[(String, Value)],
+    drafts: &[Draft],
+) -> ValidationError<'static> {
+    let location = documents
+        .iter()
+        .zip(drafts)
+        .find_map(|((_, schema), draft)| find_reference(&error, schema, *draft));
+    let mut error = ValidationError::from(error);
+    if let Some(location) = location {
+        error.instance_path = location;
+    }
+    error
+}
+
+fn find_reference(error: &referencing::Error, schema: &Value, draft: Draft) -> Option<Location> {
+    let document = match error {
+        referencing::Error::Unretrievable { uri, .. }
+        | referencing::Error::DocumentNotRegistered { uri, .. } => uri,
+        referencing::Error::UnsupportedScheme { scheme } => {
+            return find_raw_reference(schema, &|reference| {
+                reference
+                    .split_once(':')
+                    .is_some_and(|(prefix, _)| prefix.eq_ignore_ascii_case(scheme))
+            });
+        }
+        referencing::Error::InvalidUri { input, .. } => {
+            return find_raw_reference(schema, &|reference| reference == input);
+        }
+        _ => return None,
+    };
+    let reference = referencing::collect_references(schema, draft)
+        .ok()?
+        .into_iter()
+        .find(|reference| {
+            let mut uri = reference.uri().clone();
+            uri.set_fragment(None);
+            uri.as_str() == document
+        })?;
+    Some(Location::from_escaped(reference.location(), schema).join(reference.keyword()))
+}
+
+/// Find a reference or identifier as written in `schema`, e.g. one that is not a valid URI.
+fn find_raw_reference(schema: &Value, matches: &dyn Fn(&str) -> bool) -> Option<Location> {
+    const KEYWORDS: [&str; 5] = ["$ref", "$dynamicRef", "$recursiveRef", "$id", "id"];
+    let keyword_of = |value: &Value| {
+        KEYWORDS.into_iter().find(|keyword| {
+            value
+                .get(keyword)
+                .and_then(Value::as_str)
+                .is_some_and(matches)
+        })
+    };
+    let location = find_within(schema, &Location::new(), &|value| {
+        keyword_of(value).is_some()
+    })?;
+    let keyword = keyword_of(schema.pointer(location.as_str())?)?;
+    Some(location.join(keyword))
+}
+
+fn find_within(
+    value: &Value,
+    location: &Location,
+    matches: &dyn Fn(&Value) -> bool,
+) -> Option<Location> {
+    if matches(value) {
+        return Some(location.clone());
+    }
+    Children::of(value)?
+        .find_map(|(segment, child)| find_within(child, &location.join(segment), matches))
 }
 
 /// Compile a JSON Schema instance to a tree of nodes.
@@ -348,69 +1311,83 @@ pub(crate) fn compile_with<'a>(
     ctx: &Context,
     resource: ResourceRef<'a>,
 ) -> Result<SchemaNode, ValidationError<'a>> {
-    let location = ctx.location().clone();
+    if is_trivially_true(resource.contents()) {
+        return Ok(SchemaNode::trivially_true(ctx));
+    }
+    let Some(hash) = ctx.interning_hash(resource.contents()) else {
+        return compile_uninterned(ctx, resource);
+    };
+    if let Some(node) = ctx.find_interned(hash, resource.contents()) {
+        return Ok(node);
+    }
+    let mark = ctx.inventory_mark();
+    let node = compile_uninterned(ctx, resource)?;
+    let recorded = ctx.recorded_since(mark);
+    ctx.intern(hash, resource.contents(), &node, recorded);
+    Ok(node)
+}
+
+/// Whether `schema` accepts every instance without producing annotations. Such subschemas are
+/// not counted as compiled nodes.
+fn is_trivially_true(schema: &Value) -> bool {
+    match schema {
+        Value::Bool(value) => *value,
+        Value::Object(schema) => schema.is_empty(),
+        _ => false,
+    }
+}
+
+fn compile_uninterned<'a>(
+    ctx: &Context,
+    resource: ResourceRef<'a>,
+) -> Result<SchemaNode, ValidationError<'a>> {
+    check_limits(ctx, resource)?;
+    ctx.compiled_nodes.set(ctx.compiled_nodes.get() + 1);
     match resource.contents() {
-        Value::Bool(value) => match value {
-            true => Ok(SchemaNode::from_boolean(ctx, None)),
-            false => Ok(SchemaNode::from_boolean(
-                ctx,
-                Some(
-                    keywords::boolean::FalseValidator::compile(location)
-                        .expect("Should always compile"),
-                ),
-            )),
-        },
         Value::Object(schema) => {
             // A schema could contain validation keywords along with annotations and we need to
             // collect annotations separately
             if !ctx.supports_adjacent_validation() {
                 // Older drafts ignore all other keywords if `$ref` is present
                 if let Some(reference) = schema.get("$ref") {
-                    // Treat all keywords other than `$ref` as annotations
-                    let annotations = schema
-                        .iter()
-                        .filter_map(|(k, v)| {
-                            if k.as_str() == "$ref" {
-                                None
-                            } else {
-                                Some((k.clone(), v.clone()))
-                            }
-                        })
-                        .collect();
-                    return if let Some(validator) =
-                        keywords::ref_::compile_ref(ctx, schema, reference)
-                    {
-                        let validators = vec![(BuiltinKeyword::Ref.into(), validator?)];
-                        Ok(SchemaNode::from_keywords(
-                            ctx,
-                            validators,
-                            Some(annotations),
-                        ))
-                    } else {
-                        // Infinite reference to the same location
-                        Ok(SchemaNode::from_boolean(ctx, None))
-                    };
+                    return compile_legacy_ref(ctx, schema, reference);
                 }
             }
 
+            if optimize::applies(ctx, schema) {
+                return optimize::compile(ctx, schema);
+            }
             let mut validators = Vec::with_capacity(schema.len());
             let mut annotations = AHashMap::new();
+            // Keywords are compiled inline, as every level of a deeply nested schema
+            // recurses through here
             for (keyword, value) in schema {
+                if ctx.config().is_keyword_disabled(keyword) {
+                    continue;
+                }
                 // Check if this keyword is overridden, then check the standard definitions
                 if let Some(factory) = ctx.get_keyword_factory(keyword) {
-                    let path = ctx.location().join(keyword);
-                    let validator = CustomKeyword::new(factory.init(schema, value, path)?);
-                    let validator: BoxedValidator = Box::new(validator);
-                    validators.push((Keyword::custom(keyword), validator));
-                } else if let Some((keyword, validator)) = keywords::get_for_draft(ctx, keyword)
+                    validators.push(compile_custom(
+                        ctx,
+                        factory.as_ref(),
+                        schema,
+                        keyword,
+                        value,
+                    )?);
+                    ctx.record_keyword(keyword);
+                } else if let Some((compiled, validator)) = keywords::get_for_draft(ctx, keyword)
                     .and_then(|(keyword, f)| f(ctx, schema, value).map(|v| (keyword, v)))
                 {
-                    validators.push((keyword, validator.map_err(|err| err.into_owned())?));
+                    validators.push((compiled, validator.map_err(|err| err.into_owned())?));
+                    ctx.record_keyword(keyword);
                 } else if !ctx.is_known_keyword(keyword) {
                     // Treat all non-validation keywords as annotations
                     annotations.insert(keyword.to_string(), value.clone());
                 }
             }
+            if validators.len() > 1 {
+                keywords::ranges::fuse(ctx, schema, &mut validators);
+            }
             let annotations = if annotations.is_empty() {
                 None
             } else {
@@ -418,13 +1395,131 @@ pub(crate) fn compile_with<'a>(
             };
             Ok(SchemaNode::from_keywords(ctx, validators, annotations))
         }
+        _ => compile_non_object(ctx, resource.contents()),
+    }
+}
+
+/// Reject schemas nested too deep and validators with too many nodes.
+// Kept out of line, as `compile_uninterned` is on the stack for every level of nesting
+#[inline(never)]
+fn check_limits<'a>(ctx: &Context, resource: ResourceRef<'a>) -> Result<(), ValidationError<'a>> {
+    // Deep nesting is rejected upfront, but chains of references may still lead deep
+    let max_depth = ctx.config.max_schema_depth();
+    if ctx.location().depth() - ctx.base_depth > max_depth {
+        return Err(ValidationError::max_schema_depth(
+            ctx.location().clone(),
+            max_depth,
+        ));
+    }
+    let max_nodes = ctx.config.max_compiled_nodes();
+    if ctx.compiled_nodes.get() >= max_nodes {
+        return Err(ValidationError::max_compiled_nodes(
+            ctx.location().clone(),
+            resource.contents(),
+            max_nodes,
+        ));
+    }
+    Ok(())
+}
+
+/// Compile a boolean schema, or reject a schema that is neither a boolean nor an object.
+// Kept out of line for the same reason as `check_limits`
+#[inline(never)]
+fn compile_non_object<'a>(
+    ctx: &Context,
+    schema: &'a Value,
+) -> Result<SchemaNode, ValidationError<'a>> {
+    let location = ctx.location().clone();
+    match schema {
+        Value::Bool(true) => Ok(SchemaNode::from_boolean(ctx, None)),
+        Value::Bool(false) => Ok(SchemaNode::from_boolean(
+            ctx,
+            Some(
+                keywords::boolean::FalseValidator::compile(location)
+                    .expect("Should always compile"),
+            ),
+        )),
         _ => Err(ValidationError::multiple_type_error(
             Location::new(),
             location,
-            resource.contents(),
+            schema,
             PrimitiveTypesBitMap::new()
                 .add_type(PrimitiveType::Boolean)
                 .add_type(PrimitiveType::Object),
         )),
     }
 }
+
+/// Compile a schema with `$ref` in drafts that ignore all other keywords next to it.
+// Kept out of line for the same reason as `check_limits`
+#[inline(never)]
+fn compile_legacy_ref<'a>(
+    ctx: &Context,
+    schema: &'a Map<String, Value>,
+    reference: &'a Value,
+) -> Result<SchemaNode, ValidationError<'a>> {
+    record_ignored_ref_siblings(ctx, schema);
+    // Treat all keywords other than `$ref` as annotations
+    let annotations = schema
+        .iter()
+        .filter_map(|(k, v)| {
+            if k.as_str() == "$ref" {
+                None
+            } else {
+                Some((k.clone(), v.clone()))
+            }
+        })
+        .collect();
+    if let Some(validator) = keywords::ref_::compile_ref(ctx, schema, reference) {
+        let validators = vec![(BuiltinKeyword::Ref.into(), validator?)];
+        ctx.record_keyword("$ref");
+        Ok(SchemaNode::from_keywords(
+            ctx,
+            validators,
+            Some(annotations),
+        ))
+    } else {
+        // Infinite reference to the same location
+        Ok(SchemaNode::from_boolean(ctx, None))
+    }
+}
+
+/// Compile a custom keyword of `schema`.
+// Kept out of line for the same reason as `check_limits`
+#[inline(never)]
+fn compile_custom<'a>(
+    ctx: &Context,
+    factory: &dyn KeywordFactory,
+    schema: &'a Map<String, Value>,
+    keyword: &'a str,
+    value: &'a Value,
+) -> Result<(Keyword, BoxedValidator), ValidationError<'a>> {
+    let validator = CustomKeyword::new(custom::compile(ctx, factory, schema, keyword, value)?);
+    Ok((Keyword::custom(keyword), Box::new(validator)))
+}
+
+/// Compile a single keyword of `schema`. Returns `None` if the keyword does not validate anything.
+pub(crate) fn compile_keyword<'a>(
+    ctx: &Context,
+    schema: &'a Map<String, Value>,
+    keyword: &'a str,
+    value: &'a Value,
+) -> Result<Option<(Keyword, BoxedValidator)>, ValidationError<'a>> {
+    if ctx.config().is_keyword_disabled(keyword) {
+        return Ok(None);
+    }
+    // Check if this keyword is overridden, then check the standard definitions
+    if let Some(factory) = ctx.get_keyword_factory(keyword) {
+        let compiled = compile_custom(ctx, factory.as_ref(), schema, keyword, value)?;
+        ctx.record_keyword(keyword);
+        return Ok(Some(compiled));
+    }
+    if let Some((compiled, validator)) = keywords::get_for_draft(ctx, keyword)
+        .and_then(|(keyword, f)| f(ctx, schema, value).map(|v| (keyword, v)))
+    {
+        let validator = validator.map_err(|err| err.into_owned())?;
+        ctx.record_keyword(keyword);
+        return Ok(Some((compiled, validator)));
+    }
+    Ok(None)
+}
@@ -1,5 +1,6 @@
 use crate::error::ValidationError;
 use ahash::AHashMap;
+#[cfg(feature = "content")]
 use base64::{engine::general_purpose, Engine as _};
 use once_cell::sync::Lazy;
 
@@ -7,10 +8,12 @@ pub(crate) type ContentEncodingCheckType = fn(&str) -> bool;
 pub(crate) type ContentEncodingConverterType =
     fn(&str) -> Result<Option<String>, ValidationError<'static>>;
 
+#[cfg(feature = "content")]
 pub(crate) fn is_base64(instance_string: &str) -> bool {
     general_purpose::STANDARD.decode(instance_string).is_ok()
 }
 
+#[cfg(feature = "content")]
 pub(crate) fn from_base64(
     instance_string: &str,
 ) -> Result<Option<String>, ValidationError<'static>> {
@@ -23,8 +26,12 @@ pub(crate) fn from_base64(
 pub(crate) static DEFAULT_CONTENT_ENCODING_CHECKS_AND_CONVERTERS: Lazy<
     AHashMap<&'static str, (ContentEncodingCheckType, ContentEncodingConverterType)>,
 > = Lazy::new(|| {
-    let mut map: AHashMap<&'static str, (ContentEncodingCheckType, ContentEncodingConverterType)> =
-        AHashMap::with_capacity(1);
+    #[cfg_attr(not(feature = "content"), allow(unused_mut))]
+    let mut map: AHashMap<
+        &'static str,
+        (ContentEncodingCheckType, ContentEncodingConverterType),
+    > = AHashMap::with_capacity(1);
+    #[cfg(feature = "content")]
     map.insert("base64", (is_base64, from_base64));
     map
 });
@@ -1,17 +1,40 @@
 use ahash::AHashMap;
 use once_cell::sync::Lazy;
-use serde_json::{from_str, Value};
+#[cfg(feature = "content")]
+use serde::{de::IgnoredAny, Deserialize};
 
 pub(crate) type ContentMediaTypeCheckType = fn(&str) -> bool;
 
+/// Check that the input is well-formed JSON without building a `Value` from it.
+#[cfg(feature = "content")]
 pub(crate) fn is_json(instance_string: &str) -> bool {
-    from_str::<Value>(instance_string).is_ok()
+    let mut deserializer = serde_json::Deserializer::from_str(instance_string);
+    IgnoredAny::deserialize(&mut deserializer).is_ok() && deserializer.end().is_ok()
 }
 
 pub(crate) static DEFAULT_CONTENT_MEDIA_TYPE_CHECKS: Lazy<
     AHashMap<&'static str, ContentMediaTypeCheckType>,
 > = Lazy::new(|| {
+    #[cfg_attr(not(feature = "content"), allow(unused_mut))]
     let mut map: AHashMap<&'static str, ContentMediaTypeCheckType> = AHashMap::with_capacity(1);
+    #[cfg(feature = "content")]
     map.insert("application/json", is_json);
     map
 });
+
+#[cfg(all(test, feature = "content"))]
+mod tests {
+    use test_case::test_case;
+
+    #[test_case("{}", true)]
+    #[test_case(r#"{"a": [1, 2.5, "x", null, true]}"#, true)]
+    #[test_case(" 42 ", true)]
+    #[test_case("", false)]
+    #[test_case("{", false)]
+    #[test_case("{} {}", false)]
+    #[test_case("[1,]", false)]
+    #[test_case(r#"{"a": 1} x"#, false)]
+    fn is_json(input: &str, expected: bool) {
+        assert_eq!(super::is_json(input), expected);
+    }
+}
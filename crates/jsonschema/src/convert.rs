@@ -0,0 +1,429 @@
+//! Conversion of YAML and CBOR documents into JSON instances.
+use std::{error, fmt};
+
+use serde_json::{Map, Number, Value};
+
+use crate::paths::Location;
+
+/// An error that occurred while converting a YAML or CBOR document into JSON.
+#[derive(Debug)]
+pub struct ConversionError {
+    /// Path to the value that could not be converted.
+    pub location: Location,
+    /// Type of conversion error.
+    pub kind: ConversionErrorKind,
+}
+
+/// Kinds of errors that may happen during conversion into JSON.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConversionErrorKind {
+    /// The input is not a well-formed document.
+    Parse(Box<dyn error::Error + Send + Sync>),
+    /// A mapping key can not be represented as a JSON object key.
+    UnsupportedKey { key: String },
+    /// Two mapping keys have the same string representation.
+    DuplicateKey { key: String },
+    /// A number that can not be represented in JSON.
+    UnsupportedNumber { number: String },
+    /// A tagged value.
+    Tag { tag: String },
+    /// A value without a JSON counterpart, like a CBOR byte string.
+    UnsupportedValue { value: String },
+}
+
+impl ConversionError {
+    fn new(location: &Location, kind: ConversionErrorKind) -> ConversionError {
+        ConversionError {
+            location: location.clone(),
+            kind,
+        }
+    }
+    fn parse(error: impl error::Error + Send + Sync + 'static) -> ConversionError {
+        ConversionError {
+            location: Location::new(),
+            kind: ConversionErrorKind::Parse(Box::new(error)),
+        }
+    }
+}
+
+impl error::Error for ConversionError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            ConversionErrorKind::Parse(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ConversionErrorKind::Parse(error) => return error.fmt(f),
+            ConversionErrorKind::UnsupportedKey { key } => {
+                write!(f, "{key} can not be used as an object key")?;
+            }
+            ConversionErrorKind::DuplicateKey { key } => write!(f, r#"Duplicate key "{key}""#)?,
+            ConversionErrorKind::UnsupportedNumber { number } => {
+                write!(f, "{number} can not be represented as a JSON number")?;
+            }
+            ConversionErrorKind::Tag { tag } => {
+                write!(f, "Tagged values are not supported: {tag}")?
+            }
+            ConversionErrorKind::UnsupportedValue { value } => {
+                write!(f, "{value} can not be represented in JSON")?;
+            }
+        }
+        write!(f, r#" at "{}""#, self.location)
+    }
+}
+
+fn insert_key(
+    object: &mut Map<String, Value>,
+    location: &Location,
+    key: String,
+    value: Value,
+) -> Result<(), ConversionError> {
+    if object.contains_key(&key) {
+        return Err(ConversionError::new(
+            location,
+            ConversionErrorKind::DuplicateKey { key },
+        ));
+    }
+    object.insert(key, value);
+    Ok(())
+}
+
+fn float(location: &Location, value: f64) -> Result<Value, ConversionError> {
+    Number::from_f64(value).map(Value::Number).ok_or_else(|| {
+        ConversionError::new(
+            location,
+            ConversionErrorKind::UnsupportedNumber {
+                number: value.to_string(),
+            },
+        )
+    })
+}
+
+/// Parse a YAML document into a JSON instance.
+///
+/// - Anchors and aliases are resolved and `<<` merge keys are applied.
+/// - Mapping keys that are numbers or booleans are converted into strings. Other non-string
+///   keys, like `null`, sequences or mappings, are rejected.
+/// - Keys that collide after conversion, e.g. `1` and `"1"`, are rejected.
+/// - Tagged values and non-finite floats (`.nan`, `.inf`) are rejected.
+/// - Only single-document streams are accepted.
+///
+/// # Errors
+///
+/// Returns [`ConversionError`] if the input is not valid YAML or contains values without a JSON
+/// counterpart.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let instance = jsonschema::from_yaml_str("name: Jane\nage: 42\n").expect("Valid YAML");
+/// assert_eq!(instance, json!({"name": "Jane", "age": 42}));
+///
+/// let error = jsonschema::from_yaml_str("items:\n  ? [1, 2]\n  : pair\n").expect_err("Invalid key");
+/// assert_eq!(error.location.as_str(), "/items");
+/// ```
+#[cfg(feature = "yaml")]
+pub fn from_yaml_str(input: &str) -> Result<Value, ConversionError> {
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(input).map_err(ConversionError::parse)?;
+    value.apply_merge().map_err(ConversionError::parse)?;
+    yaml::convert(value, &Location::new())
+}
+
+#[cfg(feature = "yaml")]
+mod yaml {
+    use serde_json::{Map, Value};
+    use serde_yaml::Value as Yaml;
+
+    use super::{float, insert_key, ConversionError, ConversionErrorKind};
+    use crate::paths::Location;
+
+    pub(super) fn convert(value: Yaml, location: &Location) -> Result<Value, ConversionError> {
+        Ok(match value {
+            Yaml::Null => Value::Null,
+            Yaml::Bool(value) => Value::Bool(value),
+            Yaml::Number(number) => {
+                if let Some(value) = number.as_u64() {
+                    Value::from(value)
+                } else if let Some(value) = number.as_i64() {
+                    Value::from(value)
+                } else {
+                    float(location, number.as_f64().unwrap_or(f64::NAN))?
+                }
+            }
+            Yaml::String(value) => Value::String(value),
+            Yaml::Sequence(items) => Value::Array(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, item)| convert(item, &location.join(idx)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Yaml::Mapping(mapping) => {
+                let mut object = Map::with_capacity(mapping.len());
+                for (key, value) in mapping {
+                    let key = match key {
+                        Yaml::String(key) => key,
+                        Yaml::Bool(key) => key.to_string(),
+                        Yaml::Number(key) => key.to_string(),
+                        other => {
+                            return Err(ConversionError::new(
+                                location,
+                                ConversionErrorKind::UnsupportedKey {
+                                    key: describe(&other),
+                                },
+                            ))
+                        }
+                    };
+                    let value = convert(value, &location.join(&key))?;
+                    insert_key(&mut object, location, key, value)?;
+                }
+                Value::Object(object)
+            }
+            Yaml::Tagged(tagged) => {
+                return Err(ConversionError::new(
+                    location,
+                    ConversionErrorKind::Tag {
+                        tag: tagged.tag.to_string(),
+                    },
+                ))
+            }
+        })
+    }
+
+    fn describe(value: &Yaml) -> String {
+        match value {
+            Yaml::Sequence(_) => "A sequence".to_string(),
+            Yaml::Mapping(_) => "A mapping".to_string(),
+            Yaml::Tagged(tagged) => format!("A value tagged {}", tagged.tag),
+            other => format!("{other:?}"),
+        }
+    }
+}
+
+/// Decode a CBOR item into a JSON instance.
+///
+/// - Map keys that are integers, floats or booleans are converted into strings. Other non-text
+///   keys are rejected.
+/// - Keys that collide after conversion, e.g. `1` and `"1"`, are rejected.
+/// - Tags, byte strings, non-finite floats and integers outside of the `i64` / `u64` range are
+///   rejected.
+///
+/// # Errors
+///
+/// Returns [`ConversionError`] if the input is not valid CBOR or contains values without a JSON
+/// counterpart.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// // {"a": [1, true]}
+/// let bytes = [0xa1, 0x61, b'a', 0x82, 0x01, 0xf5];
+/// let instance = jsonschema::from_cbor_slice(&bytes).expect("Valid CBOR");
+/// assert_eq!(instance, json!({"a": [1, true]}));
+///
+/// // {"a": h'00'}
+/// let bytes = [0xa1, 0x61, b'a', 0x41, 0x00];
+/// let error = jsonschema::from_cbor_slice(&bytes).expect_err("Byte string");
+/// assert_eq!(error.location.as_str(), "/a");
+/// ```
+#[cfg(feature = "cbor")]
+pub fn from_cbor_slice(input: &[u8]) -> Result<Value, ConversionError> {
+    let value: ciborium::Value = ciborium::from_reader(input).map_err(ConversionError::parse)?;
+    cbor::convert(value, &Location::new())
+}
+
+#[cfg(feature = "cbor")]
+mod cbor {
+    use ciborium::Value as Cbor;
+    use serde_json::{Map, Value};
+
+    use super::{float, insert_key, ConversionError, ConversionErrorKind};
+    use crate::paths::Location;
+
+    pub(super) fn convert(value: Cbor, location: &Location) -> Result<Value, ConversionError> {
+        Ok(match value {
+            Cbor::Null => Value::Null,
+            Cbor::Bool(value) => Value::Bool(value),
+            Cbor::Integer(integer) => {
+                let value = i128::from(integer);
+                if let Ok(value) = u64::try_from(value) {
+                    Value::from(value)
+                } else if let Ok(value) = i64::try_from(value) {
+                    Value::from(value)
+                } else {
+                    return Err(ConversionError::new(
+                        location,
+                        ConversionErrorKind::UnsupportedNumber {
+                            number: value.to_string(),
+                        },
+                    ));
+                }
+            }
+            Cbor::Float(value) => float(location, value)?,
+            Cbor::Text(value) => Value::String(value),
+            Cbor::Bytes(_) => {
+                return Err(ConversionError::new(
+                    location,
+                    ConversionErrorKind::UnsupportedValue {
+                        value: "A byte string".to_string(),
+                    },
+                ))
+            }
+            Cbor::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, item)| convert(item, &location.join(idx)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Cbor::Map(entries) => {
+                let mut object = Map::with_capacity(entries.len());
+                for (key, value) in entries {
+                    let key = match key {
+                        Cbor::Text(key) => key,
+                        Cbor::Bool(key) => key.to_string(),
+                        Cbor::Integer(key) => i128::from(key).to_string(),
+                        Cbor::Float(key) => key.to_string(),
+                        other => {
+                            return Err(ConversionError::new(
+                                location,
+                                ConversionErrorKind::UnsupportedKey {
+                                    key: format!("{other:?}"),
+                                },
+                            ))
+                        }
+                    };
+                    let value = convert(value, &location.join(&key))?;
+                    insert_key(&mut object, location, key, value)?;
+                }
+                Value::Object(object)
+            }
+            Cbor::Tag(tag, _) => {
+                return Err(ConversionError::new(
+                    location,
+                    ConversionErrorKind::Tag {
+                        tag: tag.to_string(),
+                    },
+                ))
+            }
+            other => {
+                return Err(ConversionError::new(
+                    location,
+                    ConversionErrorKind::UnsupportedValue {
+                        value: format!("{other:?}"),
+                    },
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[cfg(feature = "yaml")]
+    #[test_case("a: &x [1, 2]\nb: *x\n", &json!({"a": [1, 2], "b": [1, 2]}); "aliases")]
+    #[test_case("base: &b {x: 1}\nderived:\n  <<: *b\n  y: 2\n", &json!({"base": {"x": 1}, "derived": {"x": 1, "y": 2}}); "merge keys")]
+    #[test_case("1: a\ntrue: b\n1.5: c\n", &json!({"1": "a", "true": "b", "1.5": "c"}); "scalar keys")]
+    #[test_case("- -1\n- 18446744073709551615\n- 0.5\n- null\n", &json!([-1, 18_446_744_073_709_551_615_u64, 0.5, null]); "numbers")]
+    fn yaml(input: &str, expected: &Value) {
+        assert_eq!(crate::from_yaml_str(input).expect("Valid YAML"), *expected);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test_case("a:\n  - {? [1]: x}\n", "/a/0", "A sequence can not be used as an object key at \"/a/0\""; "sequence key")]
+    #[test_case("a:\n  ~: x\n", "/a", "Null can not be used as an object key at \"/a\""; "null key")]
+    #[test_case("a:\n  1: x\n  \"1\": y\n", "/a", "Duplicate key \"1\" at \"/a\""; "duplicate key")]
+    #[test_case("a: [!custom 1]\n", "/a/0", "Tagged values are not supported: !custom at \"/a/0\""; "tag")]
+    #[test_case("a/b: {c: .nan}\n", "/a~1b/c", "NaN can not be represented as a JSON number at \"/a~1b/c\""; "nan")]
+    fn yaml_errors(input: &str, location: &str, message: &str) {
+        let error = crate::from_yaml_str(input).expect_err("Should fail");
+        assert_eq!(error.location.as_str(), location);
+        assert_eq!(error.to_string(), message);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_parse_error() {
+        let error = crate::from_yaml_str("a: [").expect_err("Should fail");
+        assert!(matches!(error.kind, crate::ConversionErrorKind::Parse(_)));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[cfg(feature = "cbor")]
+    fn cbor(value: &ciborium::Value) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes).expect("Valid CBOR");
+        bytes
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_values() {
+        use ciborium::Value as Cbor;
+
+        let value = Cbor::Map(vec![
+            (
+                Cbor::Text("a".into()),
+                Cbor::Array(vec![
+                    Cbor::Integer((-1).into()),
+                    Cbor::Float(0.5),
+                    Cbor::Null,
+                ]),
+            ),
+            (Cbor::Integer(1.into()), Cbor::Bool(true)),
+        ]);
+        let instance = crate::from_cbor_slice(&cbor(&value)).expect("Valid CBOR");
+        assert_eq!(instance, json!({"a": [-1, 0.5, null], "1": true}));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test_case(ciborium::Value::Bytes(vec![0]), "A byte string can not be represented in JSON at \"/a/0\""; "bytes")]
+    #[test_case(ciborium::Value::Tag(1, Box::new(ciborium::Value::Integer(0.into()))), "Tagged values are not supported: 1 at \"/a/0\""; "tag")]
+    #[test_case(ciborium::Value::Integer(ciborium::value::Integer::try_from(-(1_i128 << 64)).unwrap()), "-18446744073709551616 can not be represented as a JSON number at \"/a/0\""; "big integer")]
+    #[test_case(ciborium::Value::Float(f64::INFINITY), "inf can not be represented as a JSON number at \"/a/0\""; "infinity")]
+    #[test_case(ciborium::Value::Map(vec![(ciborium::Value::Null, ciborium::Value::Null)]), "Null can not be used as an object key at \"/a/0\""; "null key")]
+    fn cbor_errors(item: ciborium::Value, message: &str) {
+        let value = ciborium::Value::Map(vec![(
+            ciborium::Value::Text("a".into()),
+            ciborium::Value::Array(vec![item]),
+        )]);
+        let error = crate::from_cbor_slice(&cbor(&value)).expect_err("Should fail");
+        assert_eq!(error.location.as_str(), "/a/0");
+        assert_eq!(error.to_string(), message);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_parse_error() {
+        let error = crate::from_cbor_slice(&[0xa1]).expect_err("Should fail");
+        assert!(matches!(error.kind, crate::ConversionErrorKind::Parse(_)));
+    }
+
+    #[cfg(all(feature = "yaml", feature = "cbor"))]
+    #[test]
+    fn validator_wrappers() {
+        let validator = crate::validator_for(&json!({"required": ["name"]})).expect("Valid schema");
+        assert!(validator.is_valid_yaml("name: Jane").expect("Valid YAML"));
+        assert!(!validator.is_valid_yaml("age: 42").expect("Valid YAML"));
+        assert!(validator.is_valid_yaml("? [name]\n: Jane").is_err());
+        let bytes = cbor(&ciborium::Value::Map(vec![(
+            ciborium::Value::Text("name".into()),
+            ciborium::Value::Text("Jane".into()),
+        )]));
+        assert!(validator.is_valid_cbor(&bytes).expect("Valid CBOR"));
+    }
+}
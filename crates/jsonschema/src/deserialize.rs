@@ -0,0 +1,139 @@
+//! Validation as part of deserialization.
+use crate::Validator;
+use serde::de::{Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, Error as _};
+use serde_json::Value;
+use std::{fmt, marker::PhantomData};
+
+/// A [`DeserializeSeed`] that validates the input against a schema before producing `T`.
+///
+/// This is a convenience for the usual two passes, not streaming validation: the whole input is
+/// first deserialized into a [`serde_json::Value`], validated, and only then converted into `T`.
+/// Invalid input never reaches `T`'s `Deserialize` implementation, but it is fully parsed and
+/// buffered before it is rejected, so this is not a way to bound memory use on untrusted input.
+///
+/// Only the first validation error is reported, through the deserializer's error type, as a
+/// message that includes the instance path of the failing value. The structured
+/// [`crate::ValidationError`] is not available this way; deserialize into a
+/// [`serde_json::Value`] and call [`Validator::validate`] or [`Validator::iter_errors`] instead
+/// when the error details are needed.
+///
+/// ```rust
+/// use jsonschema::ValidatingDeserializer;
+/// use serde::de::DeserializeSeed;
+/// use serde::Deserialize;
+/// use serde_json::json;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// let validator = jsonschema::validator_for(&json!({
+///     "properties": {"port": {"minimum": 1024}}
+/// })).expect("Invalid schema");
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"port": 8080}"#);
+/// let config: Config = ValidatingDeserializer::new(&validator)
+///     .deserialize(&mut deserializer)
+///     .expect("Valid config");
+/// assert_eq!(config.port, 8080);
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"port": 80}"#);
+/// let error = ValidatingDeserializer::<Config>::new(&validator)
+///     .deserialize(&mut deserializer)
+///     .err()
+///     .expect("Should fail");
+/// assert_eq!(
+///     error.to_string(),
+///     "80 is less than the minimum of 1024 at \"/port\""
+/// );
+/// ```
+pub struct ValidatingDeserializer<'a, T> {
+    validator: &'a Validator,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> ValidatingDeserializer<'a, T> {
+    /// Create a seed that validates against `validator`.
+    #[must_use]
+    pub fn new(validator: &'a Validator) -> Self {
+        Self {
+            validator,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ValidatingDeserializer<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidatingDeserializer")
+            .field("validator", &self.validator)
+            .finish()
+    }
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for ValidatingDeserializer<'_, T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        let instance = Value::deserialize(deserializer)?;
+        if let Err(error) = self.validator.validate(&instance) {
+            return Err(D::Error::custom(format_args!(
+                "{} at \"{}\"",
+                error, error.instance_path
+            )));
+        }
+        T::deserialize(instance).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidatingDeserializer;
+    use serde::{de::DeserializeSeed, Deserialize};
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    fn deserialize(input: &str) -> Result<Vec<Point>, serde_json::Error> {
+        let validator = crate::validator_for(&json!({
+            "items": {"properties": {"x": {"minimum": 0}}}
+        }))
+        .expect("Invalid schema");
+        let mut deserializer = serde_json::Deserializer::from_str(input);
+        ValidatingDeserializer::new(&validator).deserialize(&mut deserializer)
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(
+            deserialize(r#"[{"x": 1, "y": -1}]"#).expect("Valid"),
+            vec![Point { x: 1, y: -1 }]
+        );
+    }
+
+    #[test]
+    fn invalid_instance() {
+        let error = deserialize(r#"[{"x": 1, "y": 2}, {"x": -1, "y": 2}]"#).expect_err("Invalid");
+        assert_eq!(
+            error.to_string(),
+            "-1 is less than the minimum of 0 at \"/1/x\""
+        );
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(deserialize(r#"[{"x": 1"#).is_err());
+    }
+
+    #[test]
+    fn valid_instance_wrong_type() {
+        // Passes the schema, but can't be represented as `Point`
+        let error = deserialize(r#"[{"x": 1}]"#).expect_err("Invalid");
+        assert_eq!(error.to_string(), "missing field `y`");
+    }
+}
@@ -0,0 +1,270 @@
+//! Rendering of validation errors against the source text of an instance.
+//!
+//! A [`Report`] needs only the errors and the source text, so it can also render errors that
+//! were produced elsewhere, e.g. deserialized from another process, as long as the text is the
+//! one that was validated.
+use std::fmt;
+
+use crate::{source, SourceSpan, ValidationError};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Validation errors for one document, rendered like compiler diagnostics.
+///
+/// Each error shows the offending line with the value underlined and labelled with the failing
+/// keyword, followed by the instance and schema locations. Errors whose value can not be found in
+/// the text, or all errors if the text is not valid JSON, are rendered without the snippet.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use jsonschema::diagnostics::Report;
+/// use serde_json::json;
+///
+/// let schema = json!({"properties": {"port": {"type": "integer"}}});
+/// let validator = jsonschema::validator_for(&schema)?;
+/// let source = "{\n  \"port\": \"80\"\n}";
+/// let instance = serde_json::from_str(source)?;
+/// let errors: Vec<_> = validator.iter_errors(&instance).collect();
+///
+/// let report = Report::new(source, &errors).with_name("config.json");
+/// assert_eq!(
+///     report.to_string(),
+///     r#"error: "80" is not of type "integer"
+///  --> config.json:2:11
+///   |
+/// 2 |   "port": "80"
+///   |           ^^^^ type
+///   = instance: /port
+///   = schema: /properties/port/type
+/// "#
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Report<'a> {
+    source: &'a str,
+    name: Option<&'a str>,
+    colors: bool,
+    errors: Vec<(&'a ValidationError<'a>, Option<SourceSpan>)>,
+}
+
+impl<'a> Report<'a> {
+    /// Create a report for `errors` that were produced by validating `source`.
+    pub fn new<I>(source: &'a str, errors: I) -> Report<'a>
+    where
+        I: IntoIterator<Item = &'a ValidationError<'a>>,
+    {
+        let errors: Vec<_> = errors.into_iter().collect();
+        let spans = if serde_json::from_str::<serde::de::IgnoredAny>(source).is_ok() {
+            source::spans(
+                source,
+                errors.iter().map(|error| error.instance_path.as_str()),
+            )
+        } else {
+            vec![None; errors.len()]
+        };
+        Report {
+            source,
+            name: None,
+            colors: false,
+            errors: errors.into_iter().zip(spans).collect(),
+        }
+    }
+    /// Set the document name shown next to positions, e.g. a file path.
+    #[must_use]
+    pub fn with_name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+    /// Render with ANSI colors for terminals. Disabled by default.
+    #[must_use]
+    pub fn with_colors(mut self, yes: bool) -> Self {
+        self.colors = yes;
+        self
+    }
+    /// Number of errors in the report.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+    /// Whether the report has no errors.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    fn style(&self, style: &'static str) -> &'static str {
+        if self.colors {
+            style
+        } else {
+            ""
+        }
+    }
+
+    fn render(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        error: &ValidationError<'_>,
+        span: Option<&SourceSpan>,
+    ) -> fmt::Result {
+        let (red, blue, bold, reset) = (
+            self.style(RED),
+            self.style(BLUE),
+            self.style(BOLD),
+            self.style(RESET),
+        );
+        writeln!(f, "{red}error{reset}{bold}: {error}{reset}")?;
+        let name = self.name.unwrap_or("<instance>");
+        let width = span.map_or(0, |span| span.line.to_string().len());
+        let gutter = " ".repeat(width);
+        if let Some(span) = span {
+            let line_start = self.source[..span.range.start]
+                .rfind('\n')
+                .map_or(0, |idx| idx + 1);
+            let line_end = self.source[span.range.start..]
+                .find('\n')
+                .map_or(self.source.len(), |idx| span.range.start + idx);
+            let line = self.source[line_start..line_end].trim_end_matches('\r');
+            // Keep tabs so the underline stays aligned with the line above
+            let padding: String = self.source[line_start..span.range.start]
+                .chars()
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let underline = self.source[span.range.start..span.range.end.min(line_end)]
+                .chars()
+                .count()
+                .max(1);
+            let keyword = keyword(error);
+            writeln!(
+                f,
+                "{gutter}{blue}-->{reset} {name}:{}:{}",
+                span.line, span.column
+            )?;
+            writeln!(f, "{gutter} {blue}|{reset}")?;
+            writeln!(f, "{blue}{}{reset} {blue}|{reset} {line}", span.line)?;
+            write!(
+                f,
+                "{gutter} {blue}|{reset} {padding}{red}{}",
+                "^".repeat(underline)
+            )?;
+            if keyword.is_empty() {
+                writeln!(f, "{reset}")?;
+            } else {
+                writeln!(f, " {keyword}{reset}")?;
+            }
+        } else {
+            writeln!(f, "{gutter}{blue}-->{reset} {name}")?;
+        }
+        if error.instance_path.as_str().is_empty() {
+            writeln!(f, "{gutter} {blue}={reset} instance: (root)")?;
+        } else {
+            writeln!(
+                f,
+                "{gutter} {blue}={reset} instance: {}",
+                error.instance_path
+            )?;
+        }
+        writeln!(f, "{gutter} {blue}={reset} schema: {}", error.schema_path)
+    }
+}
+
+/// The keyword that failed, i.e. the last segment of the schema path.
+fn keyword(error: &ValidationError<'_>) -> String {
+    error
+        .schema_path
+        .as_str()
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .replace("~1", "/")
+        .replace("~0", "~")
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, (error, span)) in self.errors.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            self.render(f, error, span.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::Report;
+
+    fn render(schema: &Value, source: &str, colors: bool) -> String {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        let instance: Value = serde_json::from_str(source).expect("Valid JSON");
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        Report::new(source, &errors)
+            .with_name("doc.json")
+            .with_colors(colors)
+            .to_string()
+    }
+
+    #[test]
+    fn multiple_errors() {
+        let schema = json!({
+            "properties": {"name": {"minLength": 3}},
+            "required": ["id"]
+        });
+        let source = "{\n\t\"name\": \"ab\",\n\t\"tags\": []\n}\n";
+        assert_eq!(
+            render(&schema, source, false),
+            r#"error: "ab" is shorter than 3 characters
+ --> doc.json:2:10
+  |
+2 | 	"name": "ab",
+  | 	        ^^^^ minLength
+  = instance: /name
+  = schema: /properties/name/minLength
+
+error: "id" is a required property
+ --> doc.json:1:1
+  |
+1 | {
+  | ^ required
+  = instance: (root)
+  = schema: /required
+"#
+        );
+    }
+
+    #[test]
+    fn colors() {
+        let rendered = render(&json!({"type": "string"}), "42", true);
+        assert_eq!(
+            rendered,
+            "\x1b[1;31merror\x1b[0m\x1b[1m: 42 is not of type \"string\"\x1b[0m\n \x1b[1;34m-->\x1b[0m doc.json:1:1\n  \x1b[1;34m|\x1b[0m\n\x1b[1;34m1\x1b[0m \x1b[1;34m|\x1b[0m 42\n  \x1b[1;34m|\x1b[0m \x1b[1;31m^^ type\x1b[0m\n  \x1b[1;34m=\x1b[0m instance: (root)\n  \x1b[1;34m=\x1b[0m schema: /type\n"
+        );
+    }
+
+    #[test]
+    fn without_span() {
+        // Errors from another source, the text does not match
+        let schema = json!({"items": {"type": "string"}});
+        let validator = crate::validator_for(&schema).expect("Valid schema");
+        let instance = json!(["a", 1]);
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        for source in ["[\"a\"]", "not json"] {
+            let report = Report::new(source, &errors);
+            assert_eq!(report.len(), 1);
+            assert_eq!(
+                report.to_string(),
+                "error: 1 is not of type \"string\"\n--> <instance>\n = instance: /1\n = schema: /items/type\n"
+            );
+        }
+    }
+}
@@ -2,40 +2,85 @@ use std::borrow::Cow;
 
 use regex_syntax::ast::{self, parse::Parser, *};
 
-/// Convert ECMA Script 262 regex to Rust regex on the best effort basiso.
-///
-/// NOTE: Patterns with look arounds and backreferecnes are not supported.
-pub(crate) fn to_rust_regex(pattern: &str) -> Result<Cow<'_, str>, ()> {
-    let mut pattern = Cow::Borrowed(pattern);
-    let mut ast = loop {
-        match Parser::new().parse(&pattern) {
-            Ok(ast) => break ast,
+/// Why a pattern is not a valid ECMA 262 regular expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RegexError {
+    pub(crate) message: String,
+    /// Byte offset in the pattern where the problem was found.
+    pub(crate) offset: usize,
+}
+
+impl RegexError {
+    fn new(message: impl Into<String>, offset: usize) -> RegexError {
+        RegexError {
+            message: message.into(),
+            offset,
+        }
+    }
+}
+
+impl From<&ast::Error> for RegexError {
+    fn from(error: &ast::Error) -> RegexError {
+        RegexError::new(error.kind().to_string(), error.span().start.offset)
+    }
+}
+
+/// A pattern parsed with the Rust regex syntax.
+struct Parsed<'a> {
+    /// The pattern with control escapes like `\cA` replaced by the characters they stand for.
+    pattern: Cow<'a, str>,
+    /// `None` if the pattern has look arounds or backreferences, which the Rust syntax lacks.
+    ast: Option<Ast>,
+    /// Offsets of the replaced control escapes in `pattern`.
+    controls: Vec<usize>,
+}
+
+impl Parsed<'_> {
+    /// Move an offset in `pattern` to the original pattern.
+    fn original_offset(&self, offset: usize) -> usize {
+        // Every control escape is two bytes longer than its replacement
+        offset
+            + 2 * self
+                .controls
+                .iter()
+                .filter(|&&control| control < offset)
+                .count()
+    }
+}
+
+/// Parse `pattern`. With `mask`, look arounds and backreferences are replaced by constructs of
+/// the same length, so that the rest of the pattern is still parsed.
+fn parse(pattern: &str, mask: bool) -> Result<Parsed<'_>, RegexError> {
+    let mut parsed = Parsed {
+        pattern: Cow::Borrowed(pattern),
+        ast: None,
+        controls: Vec::new(),
+    };
+    loop {
+        match Parser::new().parse(&parsed.pattern) {
+            Ok(ast) => {
+                parsed.ast = Some(ast);
+                return Ok(parsed);
+            }
             Err(error) if *error.kind() == ErrorKind::EscapeUnrecognized => {
                 let Span { start, end } = error.span();
                 let source = error.pattern();
                 if &source[start.offset..end.offset] == r"\c" {
                     if let Some(letter) = &source[end.offset..].chars().next() {
                         if letter.is_ascii_alphabetic() {
-                            let start = start.offset;
-                            let end = end.offset + 1;
                             let replacement = ((*letter as u8) % 32) as char;
-                            match pattern {
-                                Cow::Borrowed(_) => {
-                                    let prefix = &source[..start];
-                                    let suffix = &source[end..];
-                                    pattern = Cow::Owned(format!("{prefix}{replacement}{suffix}"));
-                                }
-                                Cow::Owned(ref mut buffer) => {
-                                    let mut char_buffer = [0; 4];
-                                    let replacement = replacement.encode_utf8(&mut char_buffer);
-                                    buffer.replace_range(start..end, replacement);
-                                }
-                            }
+                            let mut buffer = [0; 4];
+                            parsed.pattern.to_mut().replace_range(
+                                start.offset..end.offset + 1,
+                                replacement.encode_utf8(&mut buffer),
+                            );
+                            parsed.controls.push(start.offset);
                             continue;
                         }
                     }
                 }
-                return Err(());
+                let offset = parsed.original_offset(start.offset);
+                return Err(RegexError::new(error.kind().to_string(), offset));
             }
             Err(error)
                 if matches!(
@@ -43,29 +88,133 @@ pub(crate) fn to_rust_regex(pattern: &str) -> Result<Cow<'_, str>, ()> {
                     ErrorKind::UnsupportedLookAround | ErrorKind::UnsupportedBackreference
                 ) =>
             {
-                // Can't translate patterns with look arounds & backreferences
-                return Ok(pattern);
+                if !mask {
+                    return Ok(parsed);
+                }
+                let Span { start, end } = error.span();
+                // `(?=` and `(?!` become `(?:`, `(?<=` and `(?<!` become `(?:a` and
+                // backreferences like `\1` become `aa`
+                let replacement = match end.offset - start.offset {
+                    3 => "(?:",
+                    4 => "(?:a",
+                    _ => "aa",
+                };
+                parsed
+                    .pattern
+                    .to_mut()
+                    .replace_range(start.offset..end.offset, replacement);
             }
-            Err(_) => {
-                return Err(());
+            Err(error) => {
+                let offset = parsed.original_offset(error.span().start.offset);
+                return Err(RegexError::new(error.kind().to_string(), offset));
             }
         };
+    }
+}
+
+/// Convert ECMA Script 262 regex to Rust regex on the best effort basiso.
+///
+/// NOTE: Patterns with look arounds and backreferecnes are not supported.
+pub(crate) fn to_rust_regex(pattern: &str) -> Result<Cow<'_, str>, RegexError> {
+    let Parsed {
+        mut pattern, ast, ..
+    } = parse(pattern, false)?;
+    let Some(mut ast) = ast else {
+        // Can't translate patterns with look arounds & backreferences
+        return Ok(pattern);
     };
     let mut has_changes;
     loop {
         let translator = Ecma262Translator::new(pattern);
-        (pattern, has_changes) = ast::visit(&ast, translator).map_err(|_| ())?;
+        (pattern, has_changes) =
+            ast::visit(&ast, translator).map_err(|error| RegexError::from(&error))?;
         if !has_changes {
             return Ok(pattern);
         }
-        match Parser::new().parse(&pattern) {
-            Ok(updated_ast) => {
-                ast = updated_ast;
-            }
-            Err(_) => {
-                return Err(());
+        ast = Parser::new()
+            .parse(&pattern)
+            .map_err(|error| RegexError::from(&error))?;
+    }
+}
+
+/// Check that `pattern` is a valid ECMA 262 regular expression.
+///
+/// Unlike [`to_rust_regex`], this rejects syntax that only Rust regexes accept, like
+/// `(?P<name>...)` groups, and checks patterns with look arounds and backreferences as well.
+#[cfg(feature = "formats")]
+pub(crate) fn check(pattern: &str) -> Result<(), RegexError> {
+    let parsed = parse(pattern, true)?;
+    if let Some(ast) = &parsed.ast {
+        ast::visit(ast, EcmaSyntax).map_err(|error| RegexError {
+            offset: parsed.original_offset(error.offset),
+            ..error
+        })?;
+    }
+    to_rust_regex(pattern).map(|_| ())
+}
+
+/// Rejects syntax that is valid in Rust regexes only.
+#[cfg(feature = "formats")]
+struct EcmaSyntax;
+
+#[cfg(feature = "formats")]
+impl EcmaSyntax {
+    fn check_literal(literal: &Literal) -> Result<(), RegexError> {
+        if literal.kind == LiteralKind::Special(SpecialLiteralKind::Bell) {
+            Err(RegexError::new(
+                r"\a is not an escape sequence",
+                literal.span.start.offset,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "formats")]
+impl Visitor for EcmaSyntax {
+    type Output = ();
+    type Err = RegexError;
+
+    fn finish(self) -> Result<(), RegexError> {
+        Ok(())
+    }
+
+    fn visit_pre(&mut self, ast: &Ast) -> Result<(), RegexError> {
+        let (message, span) = match ast {
+            Ast::Group(group) => match &group.kind {
+                GroupKind::CaptureName {
+                    starts_with_p: true,
+                    ..
+                } => ("named groups are written as (?<name>...)", &group.span),
+                GroupKind::NonCapturing(flags) if !flags.items.is_empty() => {
+                    ("inline flags are not supported", &group.span)
+                }
+                _ => return Ok(()),
+            },
+            Ast::Flags(flags) => ("inline flags are not supported", &flags.span),
+            Ast::Assertion(assertion)
+                if matches!(
+                    assertion.kind,
+                    AssertionKind::StartText | AssertionKind::EndText
+                ) =>
+            {
+                (
+                    r"\A and \z are not assertions, use ^ and $",
+                    &assertion.span,
+                )
             }
+            Ast::Literal(literal) => return EcmaSyntax::check_literal(literal),
+            _ => return Ok(()),
         };
+        Err(RegexError::new(message, span.start.offset))
+    }
+
+    fn visit_class_set_item_pre(&mut self, item: &ClassSetItem) -> Result<(), RegexError> {
+        match item {
+            ClassSetItem::Literal(literal) => EcmaSyntax::check_literal(literal),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -207,4 +356,29 @@ mod tests {
         let result = to_rust_regex(input);
         assert!(result.is_err(), "Expected error for input: {input}");
     }
+
+    #[cfg(feature = "formats")]
+    #[test_case(r"(?=a)b"; "lookahead")]
+    #[test_case(r"(?<!a)b"; "negative lookbehind")]
+    #[test_case(r"(a)\1"; "backreference")]
+    #[test_case(r"(?<name>a)"; "named group")]
+    #[test_case(r"^\cA\d$"; "control character")]
+    fn check_valid(pattern: &str) {
+        assert_eq!(check(pattern), Ok(()));
+    }
+
+    #[cfg(feature = "formats")]
+    #[test_case(r"(?P<name>a)", "named groups are written as (?<name>...)", 0)]
+    #[test_case(r"a(?i)b", "inline flags are not supported", 1)]
+    #[test_case(r"(?i:a)", "inline flags are not supported", 0)]
+    #[test_case(r"a\z", r"\A and \z are not assertions, use ^ and $", 1)]
+    #[test_case(r"[\a]", r"\a is not an escape sequence", 1)]
+    #[test_case(r"(?=a", "unclosed group", 0)]
+    #[test_case(r"(?<=a)(?P<b>c)", "named groups are written as (?<name>...)", 6)]
+    #[test_case(r"\cA\cB[", "unclosed character class", 6)]
+    fn check_invalid(pattern: &str, message: &str, offset: usize) {
+        let error = check(pattern).expect_err("Invalid pattern");
+        assert_eq!(error.message, message);
+        assert_eq!(error.offset, offset);
+    }
 }
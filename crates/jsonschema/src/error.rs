@@ -1,8 +1,12 @@
 //! Error types
 use crate::{
+    lint::LintCode,
+    output::{is_reported, resolve_relative, AbsoluteLocation},
     paths::Location,
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
+    source::SourceSpan,
 };
+use referencing::Uri;
 use serde_json::{Map, Number, Value};
 use std::{
     borrow::Cow,
@@ -10,6 +14,8 @@ use std::{
     fmt::{self, Formatter, Write},
     iter::{empty, once},
     string::FromUtf8Error,
+    sync::Arc,
+    time::Duration,
 };
 
 /// An error that can occur during validation.
@@ -20,9 +26,25 @@ pub struct ValidationError<'a> {
     /// Type of validation error.
     pub kind: ValidationErrorKind,
     /// Path to the value that failed validation.
+    ///
+    /// [`Location::typed_segments`] tells array indices from object keys, and
+    /// [`Location::resolve`] finds the value in the validated instance.
     pub instance_path: Location,
     /// Path to the JSON Schema keyword that failed validation.
     pub schema_path: Location,
+    /// The keyword that failed, recorded by the schema node holding it.
+    pub(crate) keyword_origin: Option<Box<KeywordOrigin>>,
+}
+
+/// Where the keyword that produced an error is written.
+#[derive(Debug, Clone)]
+pub(crate) struct KeywordOrigin {
+    /// Absolute location of the schema holding the keyword
+    schema: Option<Arc<Uri<String>>>,
+    /// Length of the part of `schema_path` within that schema
+    relative: usize,
+    /// Bounds of the keyword in `schema_path`, counted from its end
+    keyword: (usize, usize),
 }
 
 /// An iterator over instances of [`ValidationError`] that represent validation error for the
@@ -58,23 +80,72 @@ pub(crate) fn error(instance: ValidationError) -> ErrorIterator {
 #[allow(missing_docs)]
 pub enum ValidationErrorKind {
     /// The input array contain more items than expected.
-    AdditionalItems { limit: usize },
+    AdditionalItems {
+        /// Number of items allowed by `items`.
+        limit: usize,
+        /// Number of items in the array. The unexpected items are at indexes `limit..length`.
+        length: usize,
+    },
+    /// Several items after the first `limit` are not valid under the `additionalItems` schema.
+    ///
+    /// Reported after the errors of these items.
+    AdditionalItemsFailed { limit: usize, count: usize },
     /// Unexpected properties.
     AdditionalProperties { unexpected: Vec<String> },
     /// The input value is not valid under any of the schemas listed in the 'anyOf' keyword.
     AnyOf,
     /// Results from a [`fancy_regex::RuntimeError::BacktrackLimitExceeded`] variant when matching
+    #[cfg(feature = "fancy-regex")]
     BacktrackLimitExceeded { error: fancy_regex::Error },
     /// The input value doesn't match expected constant.
     Constant { expected_value: Value },
-    /// The input array doesn't contain items conforming to the specified schema.
-    Contains,
+    /// The input array doesn't contain enough, or contains too many, items conforming to the
+    /// specified schema.
+    Contains {
+        /// Number of items valid under the schema. When there are more than `max_contains`,
+        /// counting stops at `max_contains + 1`.
+        matches: u64,
+        /// `minContains` of the schema, if any.
+        min_contains: Option<u64>,
+        /// `maxContains` of the schema, if any.
+        max_contains: Option<u64>,
+        /// The item that came closest to being valid when there are too few matches. Only
+        /// present with
+        /// [`ValidationOptions::with_verbose_errors`](crate::ValidationOptions::with_verbose_errors).
+        nearest_miss: Option<Box<ContainsMiss>>,
+    },
     /// The input value does not respect the defined contentEncoding
     ContentEncoding { content_encoding: String },
     /// The input value does not respect the defined contentMediaType
     ContentMediaType { content_media_type: String },
+    /// The content is longer than allowed by [`crate::ValidationOptions::with_content_size_limit`].
+    ContentSizeLimit { limit: usize },
     /// Custom error message for user-defined validation.
-    Custom { message: String },
+    ///
+    /// Created by [`ValidationError::custom`], with the optional `code` and `payload` set by
+    /// [`ValidationError::with_code`] and [`ValidationError::with_payload`].
+    Custom {
+        message: String,
+        code: Option<String>,
+        payload: Option<Value>,
+    },
+    /// The instance is not valid under the subschema that `dependencies` applies when the
+    /// `trigger` property is present.
+    DependencySchema {
+        trigger: String,
+        error: Box<ValidationError<'static>>,
+    },
+    /// A key appears more than once in the same object of the instance text, found by
+    /// [`crate::Validator::validate_str`] with
+    /// [`crate::ValidationOptions::with_reject_duplicate_keys`]. The error is located at the
+    /// object.
+    DuplicateKey {
+        key: String,
+        /// Position of the first occurrence of the key.
+        first: SourceSpan,
+        /// Position of the repeated key.
+        second: SourceSpan,
+    },
     /// The input value doesn't match any of specified options.
     Enum { options: Value },
     /// Value is too large.
@@ -87,6 +158,59 @@ pub enum ValidationErrorKind {
     Format { format: String },
     /// May happen in `contentEncoding` validation if `base64` encoded data is invalid.
     FromUtf8 { error: FromUtf8Error },
+    /// An `exclusiveMaximum` or `exclusiveMinimum` in the schema has a form the draft does not accept.
+    InvalidExclusiveLimit {
+        keyword: String,
+        limit: Value,
+        reason: InvalidExclusiveLimitReason,
+    },
+    /// A string with `"format": "regex"` is not a valid ECMA 262 regular expression.
+    InvalidRegex {
+        /// Why the regular expression is invalid.
+        message: String,
+        /// Byte offset in the string where the problem was found.
+        offset: usize,
+    },
+    /// A `default` or `examples` value in the schema is not valid under the subschema it is in,
+    /// found with [`crate::ValidationOptions::with_validate_defaults`] or
+    /// [`crate::ValidationOptions::with_validate_examples`].
+    InvalidDefault {
+        keyword: String,
+        /// Why the value is not valid, located within the value and the schema.
+        errors: Vec<ValidationError<'static>>,
+    },
+    /// A string is not a valid `ipv4` or `ipv6`.
+    InvalidIpAddress {
+        format: String,
+        reason: InvalidIpAddressReason,
+    },
+    /// A string is not a valid `email` or `idn-email`.
+    InvalidEmail {
+        format: String,
+        reason: InvalidEmailReason,
+    },
+    /// A string is not a valid `hostname` or `idn-hostname`.
+    InvalidHostname {
+        format: String,
+        reason: InvalidHostnameReason,
+    },
+    /// A limit keyword like `maxItems` in the schema is not a non-negative integer.
+    InvalidLimit {
+        keyword: String,
+        limit: Value,
+        reason: InvalidLimitReason,
+    },
+    /// The schema has a lint denied by [`crate::ValidationOptions::with_denied_lints`].
+    Lint { code: LintCode, message: String },
+    /// Validation took more than the [`crate::Budget`] given to
+    /// [`crate::Validator::validate_with_budget`]. The error is located where it stopped.
+    BudgetExceeded { visited: usize, elapsed: Duration },
+    /// The instance is nested deeper than allowed by [`crate::ValidationOptions::with_max_instance_depth`].
+    MaxInstanceDepth { limit: usize },
+    /// The schema is nested deeper than allowed by [`crate::ValidationOptions::with_max_schema_depth`].
+    MaxSchemaDepth { limit: usize },
+    /// The schema has more nodes than allowed by [`crate::ValidationOptions::with_max_compiled_nodes`].
+    MaxCompiledNodes { limit: usize },
     /// Too many items in an array.
     MaxItems { limit: u64 },
     /// Value is too large.
@@ -94,7 +218,7 @@ pub enum ValidationErrorKind {
     /// String is too long.
     MaxLength { limit: u64 },
     /// Too many properties in an object.
-    MaxProperties { limit: u64 },
+    MaxProperties { limit: u64, count: u64 },
     /// Too few items in an array.
     MinItems { limit: u64 },
     /// Value is too small.
@@ -102,11 +226,16 @@ pub enum ValidationErrorKind {
     /// String is too short.
     MinLength { limit: u64 },
     /// Not enough properties in an object.
-    MinProperties { limit: u64 },
+    MinProperties { limit: u64, count: u64 },
     /// When some number is not a multiple of another number.
     MultipleOf { multiple_of: f64 },
     /// Negated schema failed validation.
-    Not { schema: Value },
+    Not {
+        schema: Value,
+        /// Why the instance matched the negated schema. Only present with
+        /// [`ValidationOptions::with_verbose_errors`](crate::ValidationOptions::with_verbose_errors).
+        matched: Option<Box<NotMatch>>,
+    },
     /// The given schema is valid under more than one of the schemas listed in the 'oneOf' keyword.
     OneOfMultipleValid,
     /// The given schema is not valid under any of the schemas listed in the 'oneOf' keyword.
@@ -119,6 +248,11 @@ pub enum ValidationErrorKind {
     },
     /// When a required property is missing.
     Required { property: Value },
+    /// When a property that `dependencies` requires if the `trigger` property is present is
+    /// missing.
+    MissingDependency { trigger: String, property: Value },
+    /// When several properties of a long `required` list are missing.
+    RequiredProperties { properties: Vec<Value> },
     /// When the input value doesn't match one or multiple required types.
     Type { kind: TypeKind },
     /// Unexpected items.
@@ -127,10 +261,258 @@ pub enum ValidationErrorKind {
     UnevaluatedProperties { unexpected: Vec<String> },
     /// When the input array has non-unique elements.
     UniqueItems,
+    /// The value of the OpenAPI `discriminator` property does not select a subschema, and the
+    /// instance is not valid under the subschemas of `oneOf` or `anyOf` either.
+    UnknownDiscriminator { property: String, value: String },
     /// Error during schema ref resolution.
     Referencing(referencing::Error),
 }
 
+impl ValidationErrorKind {
+    /// Names of the missing properties for `required` and `dependencies` errors, empty for
+    /// other kinds.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"required": ["a", "b"]});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let instance = json!({});
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(error.kind.missing_properties(), [json!("a"), json!("b")]);
+    /// ```
+    pub fn missing_properties(&self) -> &[Value] {
+        match self {
+            ValidationErrorKind::Required { property }
+            | ValidationErrorKind::MissingDependency { property, .. } => {
+                std::slice::from_ref(property)
+            }
+            ValidationErrorKind::RequiredProperties { properties } => properties,
+            _ => &[],
+        }
+    }
+}
+
+/// Why an instance matched the schema negated by `not`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use jsonschema::error::ValidationErrorKind;
+///
+/// let schema = json!({"not": {"anyOf": [{"type": "string"}, {"type": "integer"}]}});
+/// let validator = jsonschema::options()
+///     .with_verbose_errors(true)
+///     .build(&schema)
+///     .expect("Invalid schema");
+/// let instance = json!(42);
+/// let error = validator.validate(&instance).expect_err("Should fail");
+/// let ValidationErrorKind::Not { matched: Some(matched), .. } = &error.kind else {
+///     panic!("Expected details on the match")
+/// };
+/// assert_eq!(matched.location.as_str(), "/not");
+/// assert_eq!(matched.any_of_branch, Some(1));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotMatch {
+    /// Location of the negated schema.
+    pub location: Location,
+    /// Index of the first matching `anyOf` subschema if the negated schema has `anyOf`.
+    pub any_of_branch: Option<usize>,
+    /// Annotations collected while evaluating the negated schema, by keyword location.
+    pub annotations: Vec<(Location, Value)>,
+}
+
+impl NotMatch {
+    /// Location of the first matching `anyOf` subschema, or of the negated schema itself.
+    #[must_use]
+    pub fn matched_location(&self) -> Location {
+        match self.any_of_branch {
+            Some(idx) => self.location.join("anyOf").join(idx),
+            None => self.location.clone(),
+        }
+    }
+}
+
+/// The array item that came closest to being valid under the schema of `contains`.
+///
+/// That is the item whose deepest error is the furthest into the schema, then the one with the
+/// fewest errors, then the first one.
+///
+/// ```rust
+/// use serde_json::json;
+/// use jsonschema::error::ValidationErrorKind;
+///
+/// let schema = json!({
+///     "contains": {"properties": {"a": {"type": "object", "properties": {"b": {"const": 1}}}}}
+/// });
+/// let validator = jsonschema::options()
+///     .with_verbose_errors(true)
+///     .build(&schema)
+///     .expect("Invalid schema");
+/// let instance = json!([{"a": 1}, {"a": {"b": 2}}]);
+/// let error = validator.validate(&instance).expect_err("Should fail");
+/// let ValidationErrorKind::Contains { matches: 0, nearest_miss: Some(miss), .. } = &error.kind else {
+///     panic!("Expected details on the nearest miss")
+/// };
+/// assert_eq!(miss.index, 1);
+/// assert_eq!(miss.errors[0].instance_path.as_str(), "/1/a/b");
+/// ```
+#[derive(Debug)]
+pub struct ContainsMiss {
+    /// Index of the item in the array.
+    pub index: usize,
+    /// Why the item is not valid under the schema.
+    pub errors: Vec<ValidationError<'static>>,
+}
+
+/// Why a limit keyword like `maxItems` or `minContains` is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidLimitReason {
+    /// The limit is below zero, like `-1`.
+    Negative,
+    /// The limit has a fractional part, like `2.5`.
+    Fractional,
+    /// The limit is an integer-valued number, like `2.0`, which Draft 4 does not accept.
+    IntegerValuedNumber,
+    /// The limit is not a number.
+    NotANumber,
+}
+
+impl fmt::Display for InvalidLimitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InvalidLimitReason::Negative => "must not be negative",
+            InvalidLimitReason::Fractional => "must not have a fractional part",
+            InvalidLimitReason::IntegerValuedNumber => {
+                "must be written without a decimal point in Draft 4"
+            }
+            InvalidLimitReason::NotANumber => "must be a number",
+        })
+    }
+}
+
+/// Which rule an invalid `ipv4` or `ipv6` breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidIpAddressReason {
+    /// A character other than digits and dots in IPv4 addresses, or other than hexadecimal
+    /// digits, colons and an IPv4 address at the end in IPv6 addresses, like in `fe80::/64`.
+    InvalidCharacter,
+    /// An IPv4 address, or the IPv4 address at the end of an IPv6 address, does not have four
+    /// octets.
+    OctetCount,
+    /// An octet is greater than 255.
+    OctetOutOfRange,
+    /// An octet has a leading zero, like in `010.1.1.1`, which some parsers read as octal.
+    LeadingZero,
+    /// An IPv6 address has a zone identifier, like in `fe80::1%eth0`.
+    ZoneIdentifier,
+    /// An IPv6 address does not have eight groups, or more than seven with `::`.
+    GroupCount,
+    /// A group of an IPv6 address is empty, like in `:1::` or `1:::2`.
+    EmptyGroup,
+    /// A group of an IPv6 address has more than four hexadecimal digits.
+    GroupTooLong,
+    /// An IPv6 address has more than one `::`.
+    MultipleDoubleColons,
+}
+
+impl fmt::Display for InvalidIpAddressReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InvalidIpAddressReason::InvalidCharacter => "it has a character that is not allowed",
+            InvalidIpAddressReason::OctetCount => "an IPv4 address must have four octets",
+            InvalidIpAddressReason::OctetOutOfRange => "an octet is greater than 255",
+            InvalidIpAddressReason::LeadingZero => "an octet has a leading zero",
+            InvalidIpAddressReason::ZoneIdentifier => "zone identifiers are not allowed",
+            InvalidIpAddressReason::GroupCount => "it does not have eight groups",
+            InvalidIpAddressReason::EmptyGroup => "a group is empty",
+            InvalidIpAddressReason::GroupTooLong => "a group has more than four hexadecimal digits",
+            InvalidIpAddressReason::MultipleDoubleColons => "it has more than one ::",
+        })
+    }
+}
+
+/// Which part of an invalid `email` or `idn-email` is wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidEmailReason {
+    /// There is no `@` between the local part and the domain.
+    MissingAt,
+    /// The local part, before the `@`, is not valid. With
+    /// [`EmailStrictness::Deliverable`](crate::EmailStrictness::Deliverable), also a quoted local
+    /// part or a display name like in `John <john@example.com>`.
+    LocalPart,
+    /// The local part is longer than 64 characters.
+    LocalPartTooLong,
+    /// The domain, after the `@`, is not a valid hostname or address literal. With
+    /// [`EmailStrictness::Deliverable`](crate::EmailStrictness::Deliverable), also an address
+    /// literal or a domain without a dot.
+    Domain,
+    /// The domain is longer than 253 characters, or one of its labels longer than 63.
+    DomainTooLong,
+    /// With [`EmailStrictness::Deliverable`](crate::EmailStrictness::Deliverable), the address is
+    /// longer than 254 characters.
+    TooLong,
+}
+
+impl fmt::Display for InvalidEmailReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InvalidEmailReason::MissingAt => "it has no @",
+            InvalidEmailReason::LocalPart => "the local part is not valid",
+            InvalidEmailReason::LocalPartTooLong => "the local part is longer than 64 characters",
+            InvalidEmailReason::Domain => "the domain is not valid",
+            InvalidEmailReason::DomainTooLong => "the domain is too long",
+            InvalidEmailReason::TooLong => "it is longer than 254 characters",
+        })
+    }
+}
+
+/// Which rule an invalid `hostname` or `idn-hostname` breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidHostnameReason {
+    /// The hostname is longer than 253 characters. For `idn-hostname`, in its ASCII form.
+    TooLong,
+    /// A label is empty, like in `a..b`, `a.` or an empty hostname.
+    EmptyLabel,
+    /// A label is longer than 63 characters. For `idn-hostname`, in its ASCII form.
+    LabelTooLong,
+    /// A label starts or ends with a hyphen. For `idn-hostname`, also a label with hyphens in the
+    /// third and fourth position, like `ab--c`.
+    LabelHyphen,
+    /// A character is not allowed. For `hostname`, anything but ASCII letters, digits and
+    /// hyphens.
+    InvalidCharacter,
+    /// For `idn-hostname`, a character is not allowed next to the characters around it, like a
+    /// `MIDDLE DOT` outside of `l·l` (RFC 5892).
+    InvalidContext,
+}
+
+impl fmt::Display for InvalidHostnameReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InvalidHostnameReason::TooLong => "it is longer than 253 characters",
+            InvalidHostnameReason::EmptyLabel => "it has an empty label",
+            InvalidHostnameReason::LabelTooLong => "a label is longer than 63 characters",
+            InvalidHostnameReason::LabelHyphen => "a label has a hyphen where it is not allowed",
+            InvalidHostnameReason::InvalidCharacter => "it has a character that is not allowed",
+            InvalidHostnameReason::InvalidContext => {
+                "it has a character that is not allowed in its context"
+            }
+        })
+    }
+}
+
+/// Why an `exclusiveMaximum` or `exclusiveMinimum` keyword is invalid for the schema's draft.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidExclusiveLimitReason {
+    /// Draft 4: the boolean form has no sibling `maximum` or `minimum` to apply to.
+    MissingLimit,
+    /// Draft 4: the numeric form is only valid in Draft 6 and later.
+    NumberInDraft4,
+    /// Draft 6 and later: the boolean form is only valid in Draft 4.
+    BooleanAfterDraft4,
+}
+
 #[derive(Debug)]
 #[allow(missing_docs)]
 pub enum TypeKind {
@@ -138,6 +520,108 @@ pub enum TypeKind {
     Multiple(PrimitiveTypesBitMap),
 }
 
+impl ValidationError<'_> {
+    /// Name of the keyword that failed, as written in `schema_path`.
+    ///
+    /// Errors from the `false` boolean schema have an empty keyword.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"a": {"minLength": 3}, "b": false}});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let instance = json!({"a": "x", "b": 1});
+    /// let keywords: Vec<_> = validator
+    ///     .iter_errors(&instance)
+    ///     .map(|error| error.keyword().to_string())
+    ///     .collect();
+    /// assert_eq!(keywords, ["minLength", ""]);
+    /// ```
+    #[must_use]
+    pub fn keyword(&self) -> &str {
+        let path = self.schema_path.as_str();
+        match &self.keyword_origin {
+            Some(origin) => &path[path.len() - origin.keyword.0..path.len() - origin.keyword.1],
+            None => path.rsplit('/').next().unwrap_or_default(),
+        }
+    }
+    /// Absolute location of the keyword that failed, within the document it is written in.
+    ///
+    /// Unlike `schema_path`, it points into the referenced document for errors found through
+    /// `$ref`. It is `None` if the schema has no base URI.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "$id": "https://example.com/schema",
+    ///     "properties": {"a": {"$ref": "#/$defs/name"}},
+    ///     "$defs": {"name": {"type": "string"}}
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let instance = json!({"a": 1});
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(error.schema_path.as_str(), "/properties/a/$ref/type");
+    /// assert_eq!(
+    ///     error.absolute_keyword_location().expect("Has a base URI").as_str(),
+    ///     "https://example.com/schema#/$defs/name/type"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn absolute_keyword_location(&self) -> Option<Uri<String>> {
+        let origin = self.keyword_origin.as_ref()?;
+        let schema = origin
+            .schema
+            .as_ref()
+            .filter(|schema| is_reported(schema))?;
+        let path = self.schema_path.as_str();
+        Some(resolve_relative(
+            schema,
+            &path[path.len() - origin.relative..],
+        ))
+    }
+    /// Record that the keyword after `parent` bytes of `schema_path` produced this error, unless
+    /// a nested schema already did.
+    pub(crate) fn attribute(
+        &mut self,
+        schema: Option<&AbsoluteLocation>,
+        location: &Location,
+        parent: usize,
+    ) {
+        if self.keyword_origin.is_some() {
+            return;
+        }
+        let path = self.schema_path.as_str();
+        let Some(rest) = path.get(parent..) else {
+            return;
+        };
+        let len = path.len();
+        let keyword = match rest.strip_prefix('/') {
+            Some(rest) => {
+                let end = rest.find('/').map_or(len, |idx| parent + 1 + idx);
+                (len - parent - 1, len - end)
+            }
+            None => (0, 0),
+        };
+        self.keyword_origin = Some(Box::new(KeywordOrigin {
+            schema: schema.map(|schema| Arc::clone(schema.uri())),
+            relative: len.saturating_sub(location.as_str().len()),
+            keyword,
+        }));
+    }
+    /// Move the recorded keyword from the schema at `from` to the same place in `to`.
+    pub(crate) fn rebase_keyword_origin(&mut self, from: &AbsoluteLocation, to: &AbsoluteLocation) {
+        let Some(origin) = &mut self.keyword_origin else {
+            return;
+        };
+        if let Some(schema) = &mut origin.schema {
+            if let Some(rebased) = to.rebase(schema, from) {
+                *schema = Arc::new(rebased);
+            }
+        }
+    }
+}
+
 /// Shortcuts for creation of specific error kinds.
 impl<'a> ValidationError<'a> {
     pub(crate) fn into_owned(self) -> ValidationError<'static> {
@@ -146,6 +630,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Owned(self.instance.into_owned()),
             kind: self.kind,
             schema_path: self.schema_path,
+            keyword_origin: self.keyword_origin,
         }
     }
 
@@ -154,12 +639,29 @@ impl<'a> ValidationError<'a> {
         instance_path: Location,
         instance: &'a Value,
         limit: usize,
+        length: usize,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::AdditionalItems { limit, length },
+            schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    pub(crate) const fn additional_items_failed(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        limit: usize,
+        count: usize,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::AdditionalItems { limit },
+            kind: ValidationErrorKind::AdditionalItemsFailed { limit, count },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn additional_properties(
@@ -173,6 +675,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::AdditionalProperties { unexpected },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn any_of(
@@ -185,8 +688,10 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::AnyOf,
             schema_path: location,
+            keyword_origin: None,
         }
     }
+    #[cfg(feature = "fancy-regex")]
     pub(crate) const fn backtrack_limit(
         location: Location,
         instance_path: Location,
@@ -198,8 +703,19 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::BacktrackLimitExceeded { error },
             schema_path: location,
+            keyword_origin: None,
         }
     }
+    /// Matching without `fancy-regex` can not hit a backtrack limit.
+    #[cfg(not(feature = "fancy-regex"))]
+    pub(crate) fn backtrack_limit(
+        _: Location,
+        _: Location,
+        _: &'a Value,
+        error: std::convert::Infallible,
+    ) -> ValidationError<'a> {
+        match error {}
+    }
     pub(crate) fn constant_array(
         location: Location,
         instance_path: Location,
@@ -213,6 +729,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Array(expected_value.to_vec()),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn constant_boolean(
@@ -228,6 +745,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Bool(expected_value),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn constant_null(
@@ -242,6 +760,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Null,
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) fn constant_number(
@@ -257,6 +776,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Number(expected_value.clone()),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) fn constant_object(
@@ -272,6 +792,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Object(expected_value.clone()),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) fn constant_string(
@@ -287,18 +808,28 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::String(expected_value.to_string()),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn contains(
         location: Location,
         instance_path: Location,
         instance: &'a Value,
+        matches: u64,
+        (min_contains, max_contains): (Option<u64>, Option<u64>),
+        nearest_miss: Option<Box<ContainsMiss>>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::Contains,
+            kind: ValidationErrorKind::Contains {
+                matches,
+                min_contains,
+                max_contains,
+                nearest_miss,
+            },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) fn content_encoding(
@@ -314,6 +845,7 @@ impl<'a> ValidationError<'a> {
                 content_encoding: encoding.to_string(),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) fn content_media_type(
@@ -329,6 +861,21 @@ impl<'a> ValidationError<'a> {
                 content_media_type: media_type.to_string(),
             },
             schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    pub(crate) const fn content_size_limit(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        limit: usize,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::ContentSizeLimit { limit },
+            schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) fn enumeration(
@@ -344,6 +891,7 @@ impl<'a> ValidationError<'a> {
                 options: options.clone(),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn exclusive_maximum(
@@ -357,6 +905,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::ExclusiveMaximum { limit },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn exclusive_minimum(
@@ -370,6 +919,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::ExclusiveMinimum { limit },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn false_schema(
@@ -382,6 +932,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::FalseSchema,
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) fn format(
@@ -397,6 +948,94 @@ impl<'a> ValidationError<'a> {
                 format: format.into(),
             },
             schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    #[cfg(feature = "formats")]
+    pub(crate) fn invalid_regex(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        error: crate::ecma::RegexError,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::InvalidRegex {
+                message: error.message,
+                offset: error.offset,
+            },
+            schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    #[cfg(feature = "formats")]
+    pub(crate) fn invalid_ip_address(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        format: &str,
+        reason: InvalidIpAddressReason,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::InvalidIpAddress {
+                format: format.to_string(),
+                reason,
+            },
+            schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    #[cfg(feature = "formats")]
+    pub(crate) fn invalid_email(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        format: &str,
+        reason: InvalidEmailReason,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::InvalidEmail {
+                format: format.to_string(),
+                reason,
+            },
+            schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    #[cfg(feature = "formats")]
+    pub(crate) fn invalid_hostname(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        format: &str,
+        reason: InvalidHostnameReason,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::InvalidHostname {
+                format: format.to_string(),
+                reason,
+            },
+            schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    /// A pattern in `patternProperties` that is not a valid regular expression.
+    pub(crate) fn invalid_pattern(instance_path: Location, pattern: &str) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Owned(Value::String(pattern.to_string())),
+            kind: ValidationErrorKind::Format {
+                format: "regex".to_string(),
+            },
+            schema_path: Location::new(),
+            keyword_origin: None,
         }
     }
     pub(crate) fn from_utf8(error: FromUtf8Error) -> ValidationError<'a> {
@@ -405,6 +1044,139 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Owned(Value::Null),
             kind: ValidationErrorKind::FromUtf8 { error },
             schema_path: Location::new(),
+            keyword_origin: None,
+        }
+    }
+    pub(crate) fn invalid_limit(
+        instance_path: Location,
+        instance: &'a Value,
+        keyword: &str,
+        reason: InvalidLimitReason,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::InvalidLimit {
+                keyword: keyword.to_string(),
+                limit: instance.clone(),
+                reason,
+            },
+            schema_path: Location::new(),
+            keyword_origin: None,
+        }
+    }
+    pub(crate) fn invalid_exclusive_limit(
+        instance_path: Location,
+        instance: &'a Value,
+        keyword: &str,
+        reason: InvalidExclusiveLimitReason,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::InvalidExclusiveLimit {
+                keyword: keyword.to_string(),
+                limit: instance.clone(),
+                reason,
+            },
+            schema_path: Location::new(),
+            keyword_origin: None,
+        }
+    }
+    pub(crate) fn invalid_default(
+        instance_path: Location,
+        instance: &'a Value,
+        keyword: &str,
+        errors: Vec<ValidationError<'static>>,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::InvalidDefault {
+                keyword: keyword.to_string(),
+                errors,
+            },
+            schema_path: Location::new(),
+            keyword_origin: None,
+        }
+    }
+    pub(crate) fn duplicate_key(
+        instance_path: Location,
+        instance: Value,
+        key: String,
+        first: SourceSpan,
+        second: SourceSpan,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Owned(instance),
+            kind: ValidationErrorKind::DuplicateKey { key, first, second },
+            schema_path: Location::new(),
+            keyword_origin: None,
+        }
+    }
+    pub(crate) fn lint(schema: &'a Value, lint: crate::Lint) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: lint.location,
+            instance: Cow::Borrowed(schema),
+            kind: ValidationErrorKind::Lint {
+                code: lint.code,
+                message: lint.message,
+            },
+            schema_path: Location::new(),
+            keyword_origin: None,
+        }
+    }
+    pub(crate) const fn budget_exceeded(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        visited: usize,
+        elapsed: Duration,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::BudgetExceeded { visited, elapsed },
+            schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    pub(crate) const fn max_instance_depth(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        limit: usize,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::MaxInstanceDepth { limit },
+            schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    pub(crate) fn max_schema_depth(location: Location, limit: usize) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: location,
+            // The schema is not included, as it could be too deeply nested to copy
+            instance: Cow::Owned(Value::Null),
+            kind: ValidationErrorKind::MaxSchemaDepth { limit },
+            schema_path: Location::new(),
+            keyword_origin: None,
+        }
+    }
+    pub(crate) fn max_compiled_nodes(
+        location: Location,
+        schema: &'a Value,
+        limit: usize,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: location,
+            instance: Cow::Borrowed(schema),
+            kind: ValidationErrorKind::MaxCompiledNodes { limit },
+            schema_path: Location::new(),
+            keyword_origin: None,
         }
     }
     pub(crate) const fn max_items(
@@ -418,6 +1190,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MaxItems { limit },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn maximum(
@@ -431,6 +1204,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Maximum { limit },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn max_length(
@@ -444,6 +1218,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MaxLength { limit },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn max_properties(
@@ -451,12 +1226,14 @@ impl<'a> ValidationError<'a> {
         instance_path: Location,
         instance: &'a Value,
         limit: u64,
+        count: u64,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::MaxProperties { limit },
+            kind: ValidationErrorKind::MaxProperties { limit, count },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn min_items(
@@ -470,6 +1247,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MinItems { limit },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn minimum(
@@ -483,6 +1261,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Minimum { limit },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn min_length(
@@ -496,6 +1275,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MinLength { limit },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn min_properties(
@@ -503,12 +1283,14 @@ impl<'a> ValidationError<'a> {
         instance_path: Location,
         instance: &'a Value,
         limit: u64,
+        count: u64,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::MinProperties { limit },
+            kind: ValidationErrorKind::MinProperties { limit, count },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn multiple_of(
@@ -522,6 +1304,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MultipleOf { multiple_of },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn not(
@@ -529,12 +1312,14 @@ impl<'a> ValidationError<'a> {
         instance_path: Location,
         instance: &'a Value,
         schema: Value,
+        matched: Option<Box<NotMatch>>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::Not { schema },
+            kind: ValidationErrorKind::Not { schema, matched },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn one_of_multiple_valid(
@@ -547,6 +1332,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::OneOfMultipleValid,
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn one_of_not_valid(
@@ -559,6 +1345,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::OneOfNotValid,
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn pattern(
@@ -572,6 +1359,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Pattern { pattern },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) fn property_names(
@@ -587,6 +1375,39 @@ impl<'a> ValidationError<'a> {
                 error: Box::new(error.into_owned()),
             },
             schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    pub(crate) const fn missing_dependency(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        trigger: String,
+        property: Value,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::MissingDependency { trigger, property },
+            schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    /// Wrap an error of the subschema `dependencies` applies when `trigger` is present, keeping
+    /// its locations.
+    pub(crate) fn dependency_schema(
+        trigger: &str,
+        error: ValidationError<'a>,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: error.instance_path.clone(),
+            instance: error.instance.clone(),
+            schema_path: error.schema_path.clone(),
+            keyword_origin: error.keyword_origin.clone(),
+            kind: ValidationErrorKind::DependencySchema {
+                trigger: trigger.to_string(),
+                error: Box::new(error.into_owned()),
+            },
         }
     }
     pub(crate) const fn required(
@@ -600,6 +1421,22 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Required { property },
             schema_path: location,
+            keyword_origin: None,
+        }
+    }
+
+    pub(crate) fn required_properties(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        properties: Vec<Value>,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::RequiredProperties { properties },
+            schema_path: location,
+            keyword_origin: None,
         }
     }
 
@@ -616,6 +1453,7 @@ impl<'a> ValidationError<'a> {
                 kind: TypeKind::Single(type_name),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn multiple_type_error(
@@ -631,6 +1469,7 @@ impl<'a> ValidationError<'a> {
                 kind: TypeKind::Multiple(types),
             },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn unevaluated_items(
@@ -644,6 +1483,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::UnevaluatedItems { unexpected },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn unevaluated_properties(
@@ -657,6 +1497,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::UnevaluatedProperties { unexpected },
             schema_path: location,
+            keyword_origin: None,
         }
     }
     pub(crate) const fn unique_items(
@@ -669,9 +1510,50 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::UniqueItems,
             schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    pub(crate) const fn unknown_discriminator(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        property: String,
+        value: String,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::UnknownDiscriminator { property, value },
+            schema_path: location,
+            keyword_origin: None,
         }
     }
     /// Create a new custom validation error.
+    ///
+    /// Custom keywords pass the location they were compiled at as `location` and the location
+    /// given to [`Keyword::validate`](crate::Keyword::validate) as `instance_path`, so the error
+    /// points to the same places as errors of built-in keywords. It is reported with `message`
+    /// as its description.
+    ///
+    /// ```rust
+    /// use jsonschema::{error::ValidationErrorKind, paths::Location, ValidationError};
+    /// use serde_json::json;
+    ///
+    /// let instance = json!(3);
+    /// let error = ValidationError::custom(
+    ///     Location::new().join("even"),
+    ///     Location::new(),
+    ///     &instance,
+    ///     "3 is not even",
+    /// )
+    /// .with_code("not-even")
+    /// .with_payload(json!({"remainder": 1}));
+    /// assert_eq!(error.to_string(), "3 is not even");
+    /// assert!(matches!(
+    ///     error.kind,
+    ///     ValidationErrorKind::Custom { code: Some(ref code), .. } if code == "not-even"
+    /// ));
+    /// ```
     pub fn custom(
         location: Location,
         instance_path: Location,
@@ -683,9 +1565,61 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Custom {
                 message: message.into(),
+                code: None,
+                payload: None,
             },
             schema_path: location,
+            keyword_origin: None,
+        }
+    }
+    /// An error of any kind, for code that used to build `ValidationError` with a struct literal.
+    ///
+    /// ```rust
+    /// use jsonschema::{error::ValidationErrorKind, paths::Location, ValidationError};
+    /// use serde_json::json;
+    ///
+    /// let instance = json!(5);
+    /// let error = ValidationError::new(
+    ///     ValidationErrorKind::Maximum { limit: json!(3) },
+    ///     &instance,
+    ///     Location::new(),
+    ///     Location::new().join("maximum"),
+    /// );
+    /// assert_eq!(error.to_string(), "5 is greater than the maximum of 3");
+    /// ```
+    pub fn new(
+        kind: ValidationErrorKind,
+        instance: &'a Value,
+        instance_path: Location,
+        schema_path: Location,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance: Cow::Borrowed(instance),
+            kind,
+            instance_path,
+            schema_path,
+            keyword_origin: None,
+        }
+    }
+    /// Set a machine-readable code for an error created by [`ValidationError::custom`].
+    ///
+    /// It has no effect on errors of other kinds.
+    #[must_use]
+    pub fn with_code(mut self, code: &str) -> ValidationError<'a> {
+        if let ValidationErrorKind::Custom { code: slot, .. } = &mut self.kind {
+            *slot = Some(code.to_string());
         }
+        self
+    }
+    /// Attach arbitrary data to an error created by [`ValidationError::custom`].
+    ///
+    /// It has no effect on errors of other kinds.
+    #[must_use]
+    pub fn with_payload(mut self, payload: Value) -> ValidationError<'a> {
+        if let ValidationErrorKind::Custom { payload: slot, .. } = &mut self.kind {
+            *slot = Some(payload);
+        }
+        self
     }
 }
 
@@ -698,6 +1632,7 @@ impl From<referencing::Error> for ValidationError<'_> {
             instance: Cow::Owned(Value::Null),
             kind: ValidationErrorKind::Referencing(err),
             schema_path: Location::new(),
+            keyword_origin: None,
         }
     }
 }
@@ -714,11 +1649,12 @@ impl fmt::Display for ValidationError<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match &self.kind {
             ValidationErrorKind::Referencing(error) => error.fmt(f),
+            #[cfg(feature = "fancy-regex")]
             ValidationErrorKind::BacktrackLimitExceeded { error } => error.fmt(f),
             ValidationErrorKind::Format { format } => {
                 write!(f, r#"{} is not a "{}""#, self.instance, format)
             }
-            ValidationErrorKind::AdditionalItems { limit } => {
+            ValidationErrorKind::AdditionalItems { limit, length } => {
                 f.write_str("Additional items are not allowed (")?;
                 let array = self.instance.as_array().expect("Always valid");
                 let mut iter = array.iter().skip(*limit);
@@ -731,12 +1667,17 @@ impl fmt::Display for ValidationError<'_> {
                     write!(f, "{}", item)?;
                 }
 
-                let items_count = array.len() - limit;
-                f.write_str(if items_count == 1 {
-                    " was unexpected)"
+                if length - limit == 1 {
+                    write!(f, " was unexpected at index {limit})")
                 } else {
-                    " were unexpected)"
-                })
+                    write!(f, " were unexpected at indexes {limit}-{})", length - 1)
+                }
+            }
+            ValidationErrorKind::AdditionalItemsFailed { limit, count } => {
+                write!(
+                    f,
+                    "{count} items after the first {limit} are not valid under the additional items schema"
+                )
             }
             ValidationErrorKind::AdditionalProperties { unexpected } => {
                 f.write_str("Additional properties are not allowed (")?;
@@ -768,11 +1709,39 @@ impl fmt::Display for ValidationError<'_> {
                 "{} is not valid under any of the schemas listed in the 'oneOf' keyword",
                 self.instance
             ),
-            ValidationErrorKind::Contains => write!(
-                f,
-                "None of {} are valid under the given schema",
-                self.instance
-            ),
+            ValidationErrorKind::Contains {
+                matches,
+                min_contains,
+                max_contains,
+                nearest_miss,
+            } => {
+                match (min_contains, max_contains) {
+                    (_, Some(max)) if matches > max => write!(
+                        f,
+                        "{} has more than {} item{} valid under the given schema",
+                        self.instance,
+                        max,
+                        if *max == 1 { "" } else { "s" }
+                    )?,
+                    (Some(min), _) if *matches > 0 => write!(
+                        f,
+                        "{} has less than {} item{} valid under the given schema ({} found)",
+                        self.instance,
+                        min,
+                        if *min == 1 { "" } else { "s" },
+                        matches
+                    )?,
+                    _ => write!(
+                        f,
+                        "None of {} are valid under the given schema",
+                        self.instance
+                    )?,
+                }
+                if let Some(miss) = nearest_miss {
+                    write!(f, " (item {} is the nearest miss)", miss.index)?;
+                }
+                Ok(())
+            }
             ValidationErrorKind::Constant { expected_value } => {
                 write!(f, "{} was expected", expected_value)
             }
@@ -790,7 +1759,69 @@ impl fmt::Display for ValidationError<'_> {
                     self.instance, content_media_type
                 )
             }
+            // The instance is not included, as it could be too large to format
+            ValidationErrorKind::ContentSizeLimit { limit } => write!(
+                f,
+                "Content is longer than {} byte{}",
+                limit,
+                if *limit == 1 { "" } else { "s" }
+            ),
             ValidationErrorKind::FromUtf8 { error } => error.fmt(f),
+            ValidationErrorKind::InvalidExclusiveLimit {
+                keyword,
+                limit,
+                reason,
+            } => {
+                let sibling = if keyword == "exclusiveMaximum" {
+                    "maximum"
+                } else {
+                    "minimum"
+                };
+                match reason {
+                    InvalidExclusiveLimitReason::MissingLimit => write!(
+                        f,
+                        r#""{keyword}": {limit} requires a sibling "{sibling}" in Draft 4"#
+                    ),
+                    InvalidExclusiveLimitReason::NumberInDraft4 => write!(
+                        f,
+                        r#""{keyword}" must be a boolean in Draft 4, not {limit}. Use "{sibling}": {limit} with "{keyword}": true instead"#
+                    ),
+                    InvalidExclusiveLimitReason::BooleanAfterDraft4 => write!(
+                        f,
+                        r#""{keyword}" must be a number in Draft 6 and later, not {limit}. Move the "{sibling}" value to "{keyword}" instead"#
+                    ),
+                }
+            }
+            ValidationErrorKind::InvalidLimit {
+                keyword,
+                limit,
+                reason,
+            } => write!(f, r#"Invalid "{keyword}" limit {limit}: {reason}"#),
+            ValidationErrorKind::InvalidDefault { keyword, errors } => {
+                write!(f, r#"Invalid "{keyword}" value {}: "#, self.instance)?;
+                for (idx, error) in errors.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{error}")?;
+                }
+                Ok(())
+            }
+            ValidationErrorKind::InvalidIpAddress { format, reason } => {
+                write!(f, r#"{} is not a "{format}": {reason}"#, self.instance)
+            }
+            ValidationErrorKind::InvalidEmail { format, reason } => {
+                write!(f, r#"{} is not a "{format}": {reason}"#, self.instance)
+            }
+            ValidationErrorKind::InvalidHostname { format, reason } => {
+                write!(f, r#"{} is not a "{format}": {reason}"#, self.instance)
+            }
+            ValidationErrorKind::InvalidRegex { message, offset } => write!(
+                f,
+                r#"{} is not a "regex": {message} at offset {offset}"#,
+                self.instance
+            ),
+            ValidationErrorKind::Lint { code, message } => write!(f, "{message} ({code})"),
             ValidationErrorKind::Enum { options } => {
                 write!(f, "{} is not one of {}", self.instance, options)
             }
@@ -829,6 +1860,21 @@ impl fmt::Display for ValidationError<'_> {
                 limit,
                 if *limit == 1 { "" } else { "s" }
             ),
+            // The instance is not included, as it could be too large to format
+            ValidationErrorKind::BudgetExceeded { visited, elapsed } => write!(
+                f,
+                "Validation budget exceeded after {visited} nodes and {elapsed:?}"
+            ),
+            // The instance is not included, as it could be too deeply nested to format
+            ValidationErrorKind::MaxInstanceDepth { limit } => {
+                write!(f, "Instance is nested deeper than {} levels", limit)
+            }
+            ValidationErrorKind::MaxSchemaDepth { limit } => {
+                write!(f, "Schema is nested deeper than {} levels", limit)
+            }
+            ValidationErrorKind::MaxCompiledNodes { limit } => {
+                write!(f, "Schema has more than {} nodes", limit)
+            }
             ValidationErrorKind::MaxItems { limit } => write!(
                 f,
                 "{} has more than {} item{}",
@@ -843,22 +1889,26 @@ impl fmt::Display for ValidationError<'_> {
                 limit,
                 if *limit == 1 { "" } else { "s" }
             ),
-            ValidationErrorKind::MaxProperties { limit } => write!(
+            ValidationErrorKind::MaxProperties { limit, .. } => write!(
                 f,
                 "{} has more than {} propert{}",
                 self.instance,
                 limit,
                 if *limit == 1 { "y" } else { "ies" }
             ),
-            ValidationErrorKind::MinProperties { limit } => write!(
+            ValidationErrorKind::MinProperties { limit, .. } => write!(
                 f,
                 "{} has less than {} propert{}",
                 self.instance,
                 limit,
                 if *limit == 1 { "y" } else { "ies" }
             ),
-            ValidationErrorKind::Not { schema } => {
-                write!(f, "{} is not allowed for {}", schema, self.instance)
+            ValidationErrorKind::Not { schema, matched } => {
+                write!(f, "{} is not allowed for {}", schema, self.instance)?;
+                if let Some(matched) = matched {
+                    write!(f, " (matches {})", matched.matched_location())?;
+                }
+                Ok(())
             }
             ValidationErrorKind::OneOfMultipleValid => write!(
                 f,
@@ -868,10 +1918,33 @@ impl fmt::Display for ValidationError<'_> {
             ValidationErrorKind::Pattern { pattern } => {
                 write!(f, r#"{} does not match "{}""#, self.instance, pattern)
             }
-            ValidationErrorKind::PropertyNames { error } => error.fmt(f),
+            ValidationErrorKind::PropertyNames { error }
+            | ValidationErrorKind::DependencySchema { error, .. } => error.fmt(f),
+            ValidationErrorKind::DuplicateKey { key, first, .. } => write!(
+                f,
+                "Duplicate key {}, first at line {}, column {}",
+                Value::String(key.clone()),
+                first.line,
+                first.column
+            ),
+            ValidationErrorKind::MissingDependency { trigger, property } => write!(
+                f,
+                r#"{} is a required property when "{}" is present"#,
+                property, trigger
+            ),
             ValidationErrorKind::Required { property } => {
                 write!(f, "{} is a required property", property)
             }
+            ValidationErrorKind::RequiredProperties { properties } => {
+                let mut iter = properties.iter();
+                if let Some(property) = iter.next() {
+                    write!(f, "{}", property)?;
+                }
+                for property in iter {
+                    write!(f, ", {}", property)?;
+                }
+                f.write_str(" are required properties")
+            }
             ValidationErrorKind::MultipleOf { multiple_of } => {
                 write!(f, "{} is not a multiple of {}", self.instance, multiple_of)
             }
@@ -918,6 +1991,11 @@ impl fmt::Display for ValidationError<'_> {
             ValidationErrorKind::UniqueItems => {
                 write!(f, "{} has non-unique elements", self.instance)
             }
+            ValidationErrorKind::UnknownDiscriminator { property, value } => write!(
+                f,
+                r#""{}" is not a known value of the "{}" discriminator"#,
+                value, property
+            ),
             ValidationErrorKind::Type {
                 kind: TypeKind::Single(type_),
             } => write!(f, r#"{} is not of type "{}""#, self.instance, type_),
@@ -939,7 +2017,7 @@ impl fmt::Display for ValidationError<'_> {
                 }
                 Ok(())
             }
-            ValidationErrorKind::Custom { message } => f.write_str(message),
+            ValidationErrorKind::Custom { message, .. } => f.write_str(message),
         }
     }
 }
@@ -1090,4 +2168,95 @@ mod tests {
         assert!(result.next().is_none());
         assert_eq!(error.instance_path.as_str(), expected);
     }
+
+    #[test_case(&json!({"type": "string"}), &json!(1), "type")]
+    #[test_case(&json!({"properties": {"a": false}}), &json!({"a": 1}), "")]
+    #[test_case(&json!({"items": {"minimum": 0, "maximum": 5}}), &json!([6]), "maximum")]
+    #[test_case(&json!({"allOf": [{"minLength": 2}]}), &json!("a"), "minLength")]
+    #[test_case(&json!({"type": "string", "allOf": [{"type": "string"}]}), &json!(1), "type")]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/$defs/a"}}, "$defs": {"a": {"required": ["b"]}}}), &json!({"a": {}}), "required")]
+    #[test_case(&json!({"dependencies": {"a": ["b"]}}), &json!({"a": 1}), "dependencies")]
+    fn keyword(schema: &Value, instance: &Value, expected: &str) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert_eq!(error.keyword(), expected);
+        for error in validator.iter_errors(instance) {
+            assert_eq!(error.keyword(), expected);
+        }
+    }
+
+    #[test_case(&json!({"$id": "https://example.com/s", "type": "string"}), &json!(1), "https://example.com/s#/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "items": false}), &json!([1]), "https://example.com/s#/items")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "#/$defs/a"}}, "$defs": {"a": {"items": {"type": "string"}}}}), &json!({"a": [1]}), "https://example.com/s#/$defs/a/items/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "#/$defs/a"}, "b": {"$ref": "#/$defs/a"}}, "$defs": {"a": {"type": "string"}}}), &json!({"b": 1}), "https://example.com/s#/$defs/a/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "#name"}}, "$defs": {"a": {"$anchor": "name", "type": "string"}}}), &json!({"a": 1}), "https://example.com/s#/$defs/a/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "other"}}, "$defs": {"a": {"$id": "other", "properties": {"b": {"type": "string"}}}}}), &json!({"a": {"b": 1}}), "https://example.com/other#/properties/b/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"items": {"type": "string"}}, "b": {"items": {"type": "string"}}}}), &json!({"b": [1]}), "https://example.com/s#/properties/b/items/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "type": "object", "properties": {"next": {"$ref": "#"}}}), &json!({"next": {"next": 1}}), "https://example.com/s#/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "#/$defs/a"}}, "$defs": {"a": false}}), &json!({"a": 1}), "https://example.com/s#/$defs/a")]
+    #[test_case(&json!({"$id": "https://example.com/s", "allOf": [{"items": {"$ref": "#/$defs/a"}}], "$defs": {"a": false}}), &json!([1]), "https://example.com/s#/$defs/a")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "other"}}, "$defs": {"a": {"$id": "other", "$ref": "#/$defs/b", "$defs": {"b": false}}}}), &json!({"a": 1}), "https://example.com/other#/$defs/b")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "#/$defs/caf%C3%A9"}}, "$defs": {"café": {"type": "string"}}}), &json!({"a": 1}), "https://example.com/s#/$defs/caf%C3%A9/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "#/$defs/a%2Fb"}}, "$defs": {"a/b": {"type": "string"}}}), &json!({"a": 1}), "https://example.com/s#/$defs/a~1b/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "#/$defs/a~0b"}}, "$defs": {"a~b": {"type": "string"}}}), &json!({"a": 1}), "https://example.com/s#/$defs/a~0b/type")]
+    #[test_case(&json!({"$id": "https://example.com/s", "properties": {"a": {"$ref": "#/$defs/a%25b%20c"}}, "$defs": {"a%b c": {"type": "string"}}}), &json!({"a": 1}), "https://example.com/s#/$defs/a%25b%20c/type")]
+    fn absolute_keyword_location(schema: &Value, instance: &Value, expected: &str) {
+        for lazy in [false, true] {
+            let validator = crate::options()
+                .with_lazy_references(lazy)
+                .build(schema)
+                .expect("Invalid schema");
+            assert_absolute_keyword_location(&validator, instance, expected);
+        }
+    }
+
+    fn assert_absolute_keyword_location(
+        validator: &crate::Validator,
+        instance: &Value,
+        expected: &str,
+    ) {
+        let error = validator.validate(instance).expect_err("Should fail");
+        let location = error.absolute_keyword_location().expect("Has a base URI");
+        assert_eq!(location.as_str(), expected);
+        let output = validator.apply(instance).basic();
+        let crate::BasicOutput::Invalid(units) = output else {
+            panic!("Should be invalid");
+        };
+        let unit = units
+            .iter()
+            .find(|unit| unit.instance_location() == &error.instance_path)
+            .expect("Same error in the basic output");
+        assert_eq!(
+            unit.absolute_keyword_location().map(|uri| uri.as_str()),
+            Some(expected)
+        );
+    }
+
+    #[test_case("café", "#/$defs/caf%C3%A9")]
+    #[test_case("a/b", "#/$defs/a%2Fb")]
+    #[test_case("a~b", "#/$defs/a~0b")]
+    #[test_case("a%b c", "#/$defs/a%25b%20c")]
+    fn absolute_keyword_location_round_trip(key: &str, reference: &str) {
+        let location = |reference: &str| {
+            let schema = json!({"$id": "https://example.com/s", "$ref": reference, "$defs": {key: {"type": "string"}}});
+            let validator = crate::validator_for(&schema).expect("Invalid schema");
+            let instance = json!(1);
+            let error = validator.validate(&instance).expect_err("Should fail");
+            error
+                .absolute_keyword_location()
+                .expect("Has a base URI")
+                .to_string()
+        };
+        let first = location(reference);
+        let target = first.strip_suffix("/type").expect("Points to `type`");
+        assert_eq!(location(target), first);
+    }
+
+    #[test]
+    fn absolute_keyword_location_without_base_uri() {
+        let validator = crate::validator_for(&json!({"type": "string"})).expect("Invalid schema");
+        let instance = json!(1);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert!(error.absolute_keyword_location().is_none());
+    }
 }
@@ -0,0 +1,447 @@
+//! Suggestions of JSON Patch (RFC 6902) operations that repair invalid instances.
+//!
+//! Only errors with an unambiguous, safe fix get a suggestion:
+//!
+//! - `additionalProperties: false`: remove the unexpected properties.
+//! - `required`: add a missing property if its subschema in the sibling `properties` has a
+//!   `default`.
+//! - `maxItems`: remove the items beyond the limit.
+//! - `minimum` / `maximum`: replace the number with the limit. Off by default, see
+//!   [`Fixer::with_clamping`].
+use std::{error, fmt};
+
+use serde_json::Value;
+
+use crate::{error::ValidationErrorKind, paths::Location, ValidationError};
+
+/// A suggested fix for a single validation error.
+#[derive(Debug)]
+pub struct Fix<'e, 'i> {
+    /// The error this fix resolves.
+    pub error: &'e ValidationError<'i>,
+    /// Operations to apply to the validated instance.
+    pub patch: Patch,
+}
+
+/// A JSON Patch document: operations applied in order.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(transparent)]
+pub struct Patch(Vec<PatchOperation>);
+
+/// A single JSON Patch operation.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Add a property to an object or insert an item into an array.
+    Add {
+        /// Location of the new value.
+        path: Location,
+        /// The new value.
+        value: Value,
+    },
+    /// Remove a property or an array item.
+    Remove {
+        /// Location of the removed value.
+        path: Location,
+    },
+    /// Replace an existing value.
+    Replace {
+        /// Location of the replaced value.
+        path: Location,
+        /// The new value.
+        value: Value,
+    },
+}
+
+/// An error that occurred while applying a [`Patch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    /// Location that does not exist in the instance.
+    pub path: Location,
+}
+
+impl error::Error for PatchError {}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, r#"Location "{}" does not exist"#, self.path)
+    }
+}
+
+impl Patch {
+    /// Operations of this patch.
+    #[must_use]
+    pub fn operations(&self) -> &[PatchOperation] {
+        &self.0
+    }
+    /// Apply all operations to `instance`. The instance is left unchanged if any of them fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operation points to a location that does not exist.
+    pub fn apply(&self, instance: &mut Value) -> Result<(), PatchError> {
+        let mut patched = instance.clone();
+        for operation in &self.0 {
+            operation.apply(&mut patched)?;
+        }
+        *instance = patched;
+        Ok(())
+    }
+}
+
+impl PatchOperation {
+    fn path(&self) -> &Location {
+        match self {
+            PatchOperation::Add { path, .. }
+            | PatchOperation::Remove { path }
+            | PatchOperation::Replace { path, .. } => path,
+        }
+    }
+
+    fn apply(&self, instance: &mut Value) -> Result<(), PatchError> {
+        let missing = || PatchError {
+            path: self.path().clone(),
+        };
+        if let PatchOperation::Replace { path, value } = self {
+            *instance.pointer_mut(path.as_str()).ok_or_else(missing)? = value.clone();
+            return Ok(());
+        }
+        let (parent, token) = self.path().as_str().rsplit_once('/').ok_or_else(missing)?;
+        let token = token.replace("~1", "/").replace("~0", "~");
+        match (self, instance.pointer_mut(parent).ok_or_else(missing)?) {
+            (PatchOperation::Add { value, .. }, Value::Object(object)) => {
+                object.insert(token, value.clone());
+            }
+            (PatchOperation::Add { value, .. }, Value::Array(items)) => {
+                let idx = if token == "-" {
+                    items.len()
+                } else {
+                    token.parse().map_err(|_| missing())?
+                };
+                if idx > items.len() {
+                    return Err(missing());
+                }
+                items.insert(idx, value.clone());
+            }
+            (PatchOperation::Remove { .. }, Value::Object(object)) => {
+                object.remove(&token).ok_or_else(missing)?;
+            }
+            (PatchOperation::Remove { .. }, Value::Array(items)) => {
+                let idx: usize = token.parse().map_err(|_| missing())?;
+                if idx >= items.len() {
+                    return Err(missing());
+                }
+                items.remove(idx);
+            }
+            _ => return Err(missing()),
+        }
+        Ok(())
+    }
+}
+
+/// Suggest fixes for `errors` produced by validating `instance` against `schema`.
+///
+/// See [`Fixer`] for details and configuration.
+#[must_use]
+pub fn suggest_fixes<'e, 'i>(
+    schema: &Value,
+    instance: &Value,
+    errors: &'e [ValidationError<'i>],
+) -> Vec<Fix<'e, 'i>> {
+    Fixer::new(schema).suggest(instance, errors)
+}
+
+/// Suggests fixes for validation errors.
+///
+/// The schema is needed to look up `default` values of missing properties. Each fix applies to
+/// the instance the errors were reported for, independently of the other fixes. Errors without
+/// a safe fix are skipped.
+///
+/// ```rust
+/// use jsonschema::fix::Fixer;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "properties": {"tags": {"maxItems": 2}, "retries": {"default": 3, "maximum": 10}},
+///     "required": ["retries"],
+///     "additionalProperties": false
+/// });
+/// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+/// let mut instance = json!({"tags": ["a", "b", "c"], "debug": true});
+/// let errors: Vec<_> = validator.iter_errors(&instance).collect();
+/// let fixes = Fixer::new(&schema).suggest(&instance, &errors);
+/// assert_eq!(fixes.len(), 3);
+/// let patches: Vec<_> = fixes.into_iter().map(|fix| fix.patch).collect();
+/// drop(errors);
+/// for patch in patches {
+///     patch.apply(&mut instance).expect("Fixes apply to the original instance");
+/// }
+/// assert_eq!(instance, json!({"tags": ["a", "b"], "retries": 3}));
+/// ```
+#[derive(Debug)]
+pub struct Fixer<'a> {
+    schema: &'a Value,
+    clamp_numbers: bool,
+}
+
+impl<'a> Fixer<'a> {
+    /// Create a fixer for errors reported by validating against `schema`.
+    #[must_use]
+    pub fn new(schema: &'a Value) -> Fixer<'a> {
+        Fixer {
+            schema,
+            clamp_numbers: false,
+        }
+    }
+    /// Replace numbers above `maximum` or below `minimum` with the limit. This changes the
+    /// meaning of the instance, so it is off by default.
+    #[must_use]
+    pub fn with_clamping(mut self, yes: bool) -> Self {
+        self.clamp_numbers = yes;
+        self
+    }
+    /// Suggest fixes for `errors` produced by validating `instance`, in the same order.
+    #[must_use]
+    pub fn suggest<'e, 'i>(
+        &self,
+        instance: &Value,
+        errors: &'e [ValidationError<'i>],
+    ) -> Vec<Fix<'e, 'i>> {
+        let mut fixes = Vec::new();
+        for error in errors {
+            for operations in self.operations(instance, error) {
+                fixes.push(Fix {
+                    error,
+                    patch: Patch(operations),
+                });
+            }
+        }
+        fixes
+    }
+
+    fn operations(
+        &self,
+        instance: &Value,
+        error: &ValidationError<'_>,
+    ) -> Vec<Vec<PatchOperation>> {
+        let keyword = error
+            .schema_path
+            .as_str()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        match (&error.kind, keyword) {
+            (ValidationErrorKind::AdditionalProperties { unexpected }, "additionalProperties") => {
+                vec![unexpected
+                    .iter()
+                    .map(|property| PatchOperation::Remove {
+                        path: error.instance_path.join(property.as_str()),
+                    })
+                    .collect()]
+            }
+            // Without `properties` and `patternProperties` all properties are additional, and
+            // the error only points to the object
+            (ValidationErrorKind::FalseSchema, "additionalProperties") => {
+                let parent = error
+                    .schema_path
+                    .as_str()
+                    .strip_suffix("/additionalProperties")
+                    .and_then(|parent| self.subschema(parent));
+                let Some(Value::Object(parent)) = parent else {
+                    return Vec::new();
+                };
+                if parent.contains_key("properties") || parent.contains_key("patternProperties") {
+                    return Vec::new();
+                }
+                let Some(Value::Object(object)) = instance.pointer(error.instance_path.as_str())
+                else {
+                    return Vec::new();
+                };
+                vec![object
+                    .keys()
+                    .map(|property| PatchOperation::Remove {
+                        path: error.instance_path.join(property),
+                    })
+                    .collect()]
+            }
+            (ValidationErrorKind::Required { property }, "required") => {
+                self.add_defaults(error, std::slice::from_ref(property))
+            }
+            (ValidationErrorKind::RequiredProperties { properties }, "required") => {
+                self.add_defaults(error, properties)
+            }
+            (ValidationErrorKind::MaxItems { limit }, "maxItems") => {
+                let (Value::Array(items), Ok(limit)) =
+                    (error.instance.as_ref(), usize::try_from(*limit))
+                else {
+                    return Vec::new();
+                };
+                // From the end, so the indices of the remaining items do not shift
+                vec![(limit..items.len())
+                    .rev()
+                    .map(|idx| PatchOperation::Remove {
+                        path: error.instance_path.join(idx),
+                    })
+                    .collect()]
+            }
+            (ValidationErrorKind::Maximum { limit }, "maximum")
+            | (ValidationErrorKind::Minimum { limit }, "minimum")
+                if self.clamp_numbers =>
+            {
+                vec![vec![PatchOperation::Replace {
+                    path: error.instance_path.clone(),
+                    value: limit.clone(),
+                }]]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// One fix per missing property that has a default value.
+    fn add_defaults(
+        &self,
+        error: &ValidationError<'_>,
+        properties: &[Value],
+    ) -> Vec<Vec<PatchOperation>> {
+        let Some(parent) = error
+            .schema_path
+            .as_str()
+            .strip_suffix("/required")
+            .and_then(|parent| self.subschema(parent))
+        else {
+            return Vec::new();
+        };
+        properties
+            .iter()
+            .filter_map(|property| {
+                let property = property.as_str()?;
+                let value = parent
+                    .get("properties")?
+                    .get(property)?
+                    .get("default")?
+                    .clone();
+                Some(vec![PatchOperation::Add {
+                    path: error.instance_path.join(property),
+                    value,
+                }])
+            })
+            .collect()
+    }
+
+    /// Subschema at a schema path of an error, following references within the document.
+    fn subschema(&self, location: &str) -> Option<&'a Value> {
+        let mut current = self.schema;
+        for token in location.split('/').skip(1) {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            current = match current {
+                Value::Object(object) if token == "$ref" => {
+                    let fragment = object.get("$ref")?.as_str()?.strip_prefix('#')?;
+                    let fragment = referencing::uri::decode_pointer(fragment).ok()?;
+                    self.schema.pointer(&fragment)?
+                }
+                Value::Object(object) => object.get(&token)?,
+                Value::Array(items) => items.get(token.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{suggest_fixes, Fixer};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn fixed(fixer: &Fixer<'_>, instance: &Value) -> Vec<Value> {
+        let validator = crate::validator_for(fixer.schema).expect("Invalid schema");
+        let errors: Vec<_> = validator.iter_errors(instance).collect();
+        fixer
+            .suggest(instance, &errors)
+            .into_iter()
+            .map(|fix| {
+                let mut instance = instance.clone();
+                fix.patch.apply(&mut instance).expect("Failed to apply");
+                instance
+            })
+            .collect()
+    }
+
+    #[test_case(&json!({"additionalProperties": false, "properties": {"a": true}}), &json!({"a": 1, "b": 2, "c~/": 3}), &[json!({"a": 1})]; "additional properties")]
+    #[test_case(&json!({"properties": {"a": {"additionalProperties": false}}}), &json!({"a": {"b": 1}}), &[json!({"a": {}})]; "nested additional properties")]
+    #[test_case(&json!({"required": ["a", "b"], "properties": {"a": {"default": 1}}}), &json!({}), &[json!({"a": 1})]; "required with default")]
+    #[test_case(&json!({"items": {"$ref": "#/$defs/item"}, "$defs": {"item": {"required": ["a"], "properties": {"a": {"default": "x"}}}}}), &json!([{}]), &[json!([{"a": "x"}])]; "required through reference")]
+    #[test_case(&json!({"maxItems": 2}), &json!([1, 2, 3, 4]), &[json!([1, 2])]; "max items")]
+    #[test_case(&json!({"maximum": 5}), &json!(10), &[]; "clamping disabled")]
+    #[test_case(&json!({"dependentRequired": {"a": ["b"]}, "properties": {"b": {"default": 1}}}), &json!({"a": 1}), &[]; "dependent required")]
+    #[test_case(&json!({"propertyNames": {"maxLength": 1}, "unevaluatedProperties": false}), &json!({"ab": 1}), &[]; "unsupported")]
+    fn suggestions(schema: &Value, instance: &Value, expected: &[Value]) {
+        assert_eq!(fixed(&Fixer::new(schema), instance), expected);
+    }
+
+    #[test]
+    fn long_required_list() {
+        let names: Vec<_> = (0..20).map(|idx| format!("p{idx}")).collect();
+        let schema = json!({
+            "required": names,
+            "properties": {"p3": {"default": 3}, "p7": {"default": 7}}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let instance = json!({});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        let errors = [error];
+        let fixes = suggest_fixes(&schema, &instance, &errors);
+        assert_eq!(fixes.len(), 2);
+        assert!(fixes.iter().all(|fix| std::ptr::eq(fix.error, &errors[0])));
+        let paths: Vec<_> = fixes
+            .iter()
+            .map(|fix| serde_json::to_value(&fix.patch).expect("Serializable"))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                json!([{"op": "add", "path": "/p3", "value": 3}]),
+                json!([{"op": "add", "path": "/p7", "value": 7}])
+            ]
+        );
+    }
+
+    #[test_case(&json!({"maximum": 5}), &json!({"a": 10}), &json!({"a": 5}))]
+    #[test_case(&json!({"minimum": 0}), &json!({"a": -1.5}), &json!({"a": 0}))]
+    fn clamping(schema: &Value, instance: &Value, expected: &Value) {
+        let schema = json!({"properties": {"a": schema}});
+        let fixer = Fixer::new(&schema).with_clamping(true);
+        assert_eq!(fixed(&fixer, instance), [expected.clone()]);
+    }
+
+    #[test]
+    fn serialize() {
+        let schema = json!({"properties": {"a": {"maxItems": 1}}});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let instance = json!({"a": [1, 2, 3]});
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        let fixes = suggest_fixes(&schema, &instance, &errors);
+        assert_eq!(
+            serde_json::to_value(&fixes[0].patch).expect("Serializable"),
+            json!([{"op": "remove", "path": "/a/2"}, {"op": "remove", "path": "/a/1"}])
+        );
+    }
+
+    #[test]
+    fn apply_is_atomic() {
+        let schema = json!({"additionalProperties": false});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let instance = json!({"a": 1, "b": 2});
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        let fixes = suggest_fixes(&schema, &instance, &errors);
+        let mut other = json!({"a": 1});
+        let error = fixes[0]
+            .patch
+            .apply(&mut other)
+            .expect_err("`b` does not exist");
+        assert_eq!(error.path.as_str(), "/b");
+        assert_eq!(error.to_string(), r#"Location "/b" does not exist"#);
+        assert_eq!(other, json!({"a": 1}));
+    }
+}
@@ -0,0 +1,1870 @@
+//! Generation of instances that are valid or invalid under a schema.
+use std::{error, fmt};
+
+use rand::Rng;
+use regex_syntax::hir::{Class, Hir, HirKind};
+use serde_json::{Map, Number, Value};
+
+use crate::{paths::Location, ValidationError, Validator};
+
+/// Keywords that imply the type of the instance when `type` is missing.
+const OBJECT_KEYWORDS: &[&str] = &[
+    "properties",
+    "required",
+    "additionalProperties",
+    "patternProperties",
+    "minProperties",
+    "maxProperties",
+    "propertyNames",
+    "dependentRequired",
+    "dependencies",
+];
+const ARRAY_KEYWORDS: &[&str] = &[
+    "items",
+    "prefixItems",
+    "additionalItems",
+    "minItems",
+    "maxItems",
+    "contains",
+    "uniqueItems",
+];
+const STRING_KEYWORDS: &[&str] = &["minLength", "maxLength", "pattern", "format"];
+const NUMBER_KEYWORDS: &[&str] = &[
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+];
+const ALL_TYPES: &[&str] = &[
+    "null", "boolean", "integer", "number", "string", "array", "object",
+];
+const SCALAR_TYPES: &[&str] = &["null", "boolean", "integer", "number", "string"];
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+];
+/// How far generated numbers, strings and collections go beyond their lower bounds.
+const SPREAD: usize = 8;
+
+/// An error that occurred while generating an instance.
+#[derive(Debug)]
+pub struct GenerateError {
+    /// Path to the schema keyword that could not be satisfied.
+    pub location: Location,
+    /// Type of generation error.
+    pub kind: GenerateErrorKind,
+}
+
+/// Kinds of errors that may happen during instance generation.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GenerateErrorKind {
+    /// The schema is not valid.
+    InvalidSchema(Box<ValidationError<'static>>),
+    /// No value satisfies the schema, e.g. `false` or `minimum` above `maximum`.
+    Unsatisfiable { reason: String },
+    /// No valid value was found within the configured number of attempts.
+    Exhausted { attempts: usize },
+    /// A reference that does not point into the schema document.
+    UnsupportedReference { reference: String },
+    /// A pattern that strings can not be generated from.
+    UnsupportedPattern { pattern: String },
+    /// References are nested deeper than the configured limit.
+    MaxDepth { depth: usize },
+    /// The schema has no constraint that an instance can violate on its own, e.g. `true` or `{}`.
+    CannotViolate,
+}
+
+impl GenerateError {
+    fn new(location: &Location, kind: GenerateErrorKind) -> GenerateError {
+        GenerateError {
+            location: location.clone(),
+            kind,
+        }
+    }
+    fn unsatisfiable(location: &Location, reason: impl Into<String>) -> GenerateError {
+        GenerateError::new(
+            location,
+            GenerateErrorKind::Unsatisfiable {
+                reason: reason.into(),
+            },
+        )
+    }
+}
+
+impl error::Error for GenerateError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            GenerateErrorKind::InvalidSchema(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            GenerateErrorKind::InvalidSchema(error) => return error.fmt(f),
+            GenerateErrorKind::Unsatisfiable { reason } => f.write_str(reason)?,
+            GenerateErrorKind::Exhausted { attempts } => {
+                write!(f, "No valid value found within {attempts} attempts")?;
+            }
+            GenerateErrorKind::UnsupportedReference { reference } => {
+                write!(
+                    f,
+                    r#"Reference "{reference}" does not point into the schema"#
+                )?;
+            }
+            GenerateErrorKind::UnsupportedPattern { pattern } => {
+                write!(f, r#"Can not generate strings matching "{pattern}""#)?;
+            }
+            GenerateErrorKind::MaxDepth { depth } => {
+                write!(f, "Schema is nested deeper than {depth} levels")?;
+            }
+            GenerateErrorKind::CannotViolate => f.write_str("No constraint can be violated")?,
+        }
+        write!(f, r#" at "{}""#, self.location)
+    }
+}
+
+/// Generate an instance that is valid under `schema`.
+///
+/// The same `rng` state always produces the same instance. See [`Generator`] for details and
+/// configuration.
+///
+/// # Errors
+///
+/// Returns an error if the schema is invalid or can not be satisfied.
+pub fn generate<R: Rng + ?Sized>(schema: &Value, rng: &mut R) -> Result<Value, GenerateError> {
+    Generator::new(schema)?.generate(rng)
+}
+
+/// Generate an instance that violates exactly one constraint of `schema`.
+///
+/// The same `rng` state always produces the same instance. See [`Generator::generate_invalid`]
+/// for details.
+///
+/// # Errors
+///
+/// Returns an error if the schema is invalid, can not be satisfied, or has no constraint to
+/// violate.
+pub fn generate_invalid<R: Rng + ?Sized>(
+    schema: &Value,
+    rng: &mut R,
+) -> Result<InvalidInstance, GenerateError> {
+    Generator::new(schema)?.generate_invalid(rng)
+}
+
+/// An instance that violates exactly one constraint of a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidInstance {
+    /// The invalid instance.
+    pub instance: Value,
+    /// Location of the violated keyword, as reported by [`ValidationError::schema_path`].
+    pub location: Location,
+    /// Location of the offending value, as reported by [`ValidationError::instance_path`].
+    pub instance_path: Location,
+}
+
+/// Generates instances that are valid under a schema, e.g. examples for documentation.
+///
+/// Values are built from the keywords that constrain them: `type`, `enum` / `const`, numeric
+/// bounds, string lengths, patterns and common formats, `properties` / `required`, `items` /
+/// `prefixItems` with their length bounds, and one branch of `anyOf` / `oneOf`. Keywords that
+/// are not used for building values, like `not` or `if`, are honored by validating every
+/// generated instance and starting over if it is invalid.
+///
+/// References are resolved within the schema document only.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use rand::SeedableRng;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {
+///         "id": {"type": "string", "pattern": "^[A-Z]{3}-[0-9]{4}$"},
+///         "tags": {"type": "array", "items": {"enum": ["new", "sale"]}, "minItems": 1}
+///     },
+///     "required": ["id", "tags"]
+/// });
+/// let generator = jsonschema::Generator::new(&schema)?.with_max_attempts(10);
+/// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(42);
+/// let instance = generator.generate(&mut rng)?;
+/// assert!(jsonschema::is_valid(&schema, &instance));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Generator<'a> {
+    schema: &'a Value,
+    pub(crate) validator: Validator,
+    max_attempts: usize,
+    max_depth: usize,
+}
+
+impl<'a> Generator<'a> {
+    /// Create a generator for `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema is invalid.
+    pub fn new(schema: &'a Value) -> Result<Generator<'a>, GenerateError> {
+        let validator = crate::validator_for(schema).map_err(|error| {
+            GenerateError::new(
+                &error.schema_path.clone(),
+                GenerateErrorKind::InvalidSchema(Box::new(error)),
+            )
+        })?;
+        Ok(Generator {
+            schema,
+            validator,
+            max_attempts: 100,
+            max_depth: 32,
+        })
+    }
+    /// Set how many times a value is generated before giving up on constraints that are only
+    /// checked after generation, like `pattern` combined with `maxLength`, `uniqueItems` or
+    /// `not`. Applies to single values and to the whole instance. Defaults to 100.
+    #[must_use]
+    pub fn with_max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+    /// Set how deeply subschemas may be nested, including through references. Optional
+    /// properties and array items are left out halfway to this limit, so recursive schemas
+    /// produce finite instances. Defaults to 32.
+    #[must_use]
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+    /// Generate an instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error pointing to the schema keyword that could not be satisfied.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Value, GenerateError> {
+        let mut state = State {
+            root: self.schema,
+            rng,
+            max_attempts: self.max_attempts,
+            max_depth: self.max_depth,
+        };
+        let mut failed = Location::new();
+        for _ in 0..self.max_attempts {
+            let instance = state.value(self.schema, &Location::new(), 0)?;
+            let error = self
+                .validator
+                .iter_errors(&instance)
+                .next()
+                .map(|error| error.schema_path);
+            match error {
+                Some(location) => failed = location,
+                None => return Ok(instance),
+            }
+        }
+        Err(GenerateError::new(
+            &failed,
+            GenerateErrorKind::Exhausted {
+                attempts: self.max_attempts,
+            },
+        ))
+    }
+    /// Generate an instance that violates exactly one constraint, for checking that invalid
+    /// input is rejected with the right error.
+    ///
+    /// A valid instance is generated first, then one of its values is changed so that it breaks
+    /// `type`, `enum` / `const`, a numeric bound, a length or size bound, `uniqueItems`,
+    /// `required` or `additionalProperties: false`, including within `properties`, `items`,
+    /// `allOf` and references. Only changes that make the validator report exactly one error,
+    /// at the chosen keyword, are kept. Values that are not needed for the violation are then
+    /// removed where possible, so the instance is as small as the schema allows.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GenerateErrorKind::CannotViolate`] if the schema has no such constraint, e.g.
+    /// `true` or `{}`, and the same errors as [`Generator::generate`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rand::SeedableRng;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "type": "object",
+    ///     "properties": {"name": {"type": "string", "maxLength": 8}},
+    ///     "required": ["name"]
+    /// });
+    /// let generator = jsonschema::Generator::new(&schema)?;
+    /// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(42);
+    /// let invalid = generator.generate_invalid(&mut rng)?;
+    /// let validator = jsonschema::validator_for(&schema)?;
+    /// let error = validator.validate(&invalid.instance).expect_err("Invalid instance");
+    /// assert_eq!(error.schema_path, invalid.location);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn generate_invalid<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> Result<InvalidInstance, GenerateError> {
+        let mut found = false;
+        for _ in 0..self.max_attempts {
+            // Optional values are left out at random, so constraints on them may only show up in
+            // some of the valid instances
+            let valid = self.generate(rng)?;
+            let state = State {
+                root: self.schema,
+                rng: &mut *rng,
+                max_attempts: self.max_attempts,
+                max_depth: self.max_depth,
+            };
+            let mut violations = Vec::new();
+            state.violations(
+                self.schema,
+                &valid,
+                &Location::new(),
+                &mut Vec::new(),
+                0,
+                &mut violations,
+            );
+            if violations.is_empty() {
+                continue;
+            }
+            found = true;
+            let start = rng.random_range(0..violations.len());
+            for offset in 0..violations.len() {
+                let violation = &violations[(start + offset) % violations.len()];
+                let mut instance = valid.clone();
+                if !replace(&mut instance, &violation.path, violation.value.clone()) {
+                    continue;
+                }
+                if self.violates_only(&instance, &violation.location, &violation.path) {
+                    let path = self.shrink(&mut instance, violation);
+                    return Ok(InvalidInstance {
+                        instance,
+                        location: violation.location.clone(),
+                        instance_path: instance_path(&path),
+                    });
+                }
+            }
+        }
+        let kind = if found {
+            GenerateErrorKind::Exhausted {
+                attempts: self.max_attempts,
+            }
+        } else {
+            GenerateErrorKind::CannotViolate
+        };
+        Err(GenerateError::new(&Location::new(), kind))
+    }
+
+    /// Whether `instance` has a single error, at `location` for the value at `path`.
+    fn violates_only(&self, instance: &Value, location: &Location, path: &[Step]) -> bool {
+        let mut errors = self.validator.iter_errors(instance);
+        match (errors.next(), errors.next()) {
+            (Some(error), None) => {
+                &error.schema_path == location && error.instance_path == instance_path(path)
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove object members and array items that the violation does not depend on, returning
+    /// the path to the violating value afterwards.
+    fn shrink(&self, instance: &mut Value, violation: &Violation) -> Vec<Step> {
+        let mut path = violation.path.clone();
+        let mut candidates = Vec::new();
+        members(instance, &mut Vec::new(), &mut candidates);
+        // Later items first, so removing them does not shift the remaining candidates
+        for candidate in candidates.into_iter().rev() {
+            if path.starts_with(&candidate) {
+                continue;
+            }
+            // Removing an earlier item of an array on the path shifts the violating value
+            let (last, parent) = candidate.split_last().expect("Members are not the root");
+            let mut shifted = path.clone();
+            if let (Step::Index(idx), Some(Step::Index(on_path))) =
+                (last, shifted.get_mut(parent.len()))
+            {
+                if path.starts_with(parent) && idx < on_path {
+                    *on_path -= 1;
+                }
+            }
+            let mut shrunk = instance.clone();
+            if remove(&mut shrunk, &candidate)
+                && self.violates_only(&shrunk, &violation.location, &shifted)
+            {
+                *instance = shrunk;
+                path = shifted;
+            }
+        }
+        path
+    }
+}
+
+struct State<'a, 'r, R: ?Sized> {
+    root: &'a Value,
+    rng: &'r mut R,
+    max_attempts: usize,
+    max_depth: usize,
+}
+
+impl<R: Rng + ?Sized> State<'_, '_, R> {
+    fn pick<'v, T>(&mut self, items: &'v [T]) -> &'v T {
+        &items[self.rng.random_range(0..items.len())]
+    }
+
+    /// Whether optional parts should be left out to keep recursive instances finite.
+    fn minimal(&self, depth: usize) -> bool {
+        depth >= self.max_depth / 2
+    }
+
+    fn value(
+        &mut self,
+        schema: &Value,
+        location: &Location,
+        depth: usize,
+    ) -> Result<Value, GenerateError> {
+        if depth > self.max_depth {
+            return Err(GenerateError::new(
+                location,
+                GenerateErrorKind::MaxDepth {
+                    depth: self.max_depth,
+                },
+            ));
+        }
+        let schema = match schema {
+            Value::Bool(true) => return self.typed(&Map::new(), location, depth),
+            Value::Object(schema) => schema,
+            _ => return Err(GenerateError::unsatisfiable(location, "No value is valid")),
+        };
+        for keyword in ["$ref", "$recursiveRef", "$dynamicRef"] {
+            if let Some(Value::String(reference)) = schema.get(keyword) {
+                let location = location.join(keyword);
+                let target = self.resolve(reference, &location)?;
+                let mut rest = schema.clone();
+                rest.remove(keyword);
+                let merged = merge(rest, target, &location)?;
+                return self.value(&Value::Object(merged), &location, depth + 1);
+            }
+        }
+        if let Some(Value::Array(subschemas)) = schema.get("allOf") {
+            let mut merged = schema.clone();
+            merged.remove("allOf");
+            for (idx, subschema) in subschemas.iter().enumerate() {
+                merged = merge(merged, subschema, &location.join("allOf").join(idx))?;
+            }
+            return self.value(&Value::Object(merged), location, depth + 1);
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(branches)) = schema.get(keyword) {
+                let location = location.join(keyword);
+                if branches.is_empty() {
+                    return Err(GenerateError::unsatisfiable(&location, "No branches"));
+                }
+                let mut rest = schema.clone();
+                rest.remove(keyword);
+                // Fall back to other branches if the chosen one can not be satisfied
+                let start = self.rng.random_range(0..branches.len());
+                let mut first_error = None;
+                for offset in 0..branches.len() {
+                    let idx = (start + offset) % branches.len();
+                    let location = location.join(idx);
+                    let result =
+                        merge(rest.clone(), &branches[idx], &location).and_then(|merged| {
+                            self.value(&Value::Object(merged), &location, depth + 1)
+                        });
+                    match result {
+                        Ok(value) => return Ok(value),
+                        Err(error) => {
+                            first_error.get_or_insert(error);
+                        }
+                    }
+                }
+                return Err(first_error.expect("At least one branch"));
+            }
+        }
+        self.typed(schema, location, depth)
+    }
+
+    /// Find the subschema a reference points to within the root document.
+    fn resolve(&self, reference: &str, location: &Location) -> Result<&Value, GenerateError> {
+        let unsupported = || {
+            GenerateError::new(
+                location,
+                GenerateErrorKind::UnsupportedReference {
+                    reference: reference.to_string(),
+                },
+            )
+        };
+        let (base, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let document = if base.is_empty() {
+            self.root
+        } else {
+            // Embedded resources, matched by their `$id` as written
+            find_subschema(self.root, &|object| {
+                ["$id", "id"].iter().any(|keyword| {
+                    object
+                        .get(*keyword)
+                        .and_then(Value::as_str)
+                        .is_some_and(|id| id.trim_end_matches('#') == base)
+                })
+            })
+            .ok_or_else(unsupported)?
+        };
+        let fragment = referencing::uri::decode_pointer(fragment).map_err(|_| unsupported())?;
+        if fragment.is_empty() || fragment.starts_with('/') {
+            document.pointer(&fragment).ok_or_else(unsupported)
+        } else {
+            find_subschema(document, &|object| {
+                ["$anchor", "$dynamicAnchor", "$id", "id"]
+                    .iter()
+                    .any(|keyword| {
+                        object
+                            .get(*keyword)
+                            .and_then(Value::as_str)
+                            .is_some_and(|value| {
+                                value == fragment || value.strip_prefix('#') == Some(&fragment)
+                            })
+                    })
+            })
+            .ok_or_else(unsupported)
+        }
+    }
+
+    /// Whether `schema` is `false`, directly or through references.
+    fn forbids<'s>(&'s self, mut schema: &'s Value) -> bool {
+        // Bounded, as references may form a cycle
+        for _ in 0..=self.max_depth {
+            let Value::Object(object) = schema else {
+                return schema == &Value::Bool(false);
+            };
+            let Some(reference) = ["$ref", "$recursiveRef", "$dynamicRef"]
+                .iter()
+                .find_map(|keyword| object.get(*keyword).and_then(Value::as_str))
+            else {
+                return false;
+            };
+            match self.resolve(reference, &Location::new()) {
+                Ok(target) => schema = target,
+                Err(_) => return false,
+            }
+        }
+        false
+    }
+
+    /// Generate a value from a schema without references or applicators.
+    fn typed(
+        &mut self,
+        schema: &Map<String, Value>,
+        location: &Location,
+        depth: usize,
+    ) -> Result<Value, GenerateError> {
+        if let Some(value) = schema.get("const") {
+            return Ok(value.clone());
+        }
+        if let Some(Value::Array(values)) = schema.get("enum") {
+            if values.is_empty() {
+                return Err(GenerateError::unsatisfiable(
+                    &location.join("enum"),
+                    "No values are allowed",
+                ));
+            }
+            return Ok(self.pick(values).clone());
+        }
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => {
+                let mut implied = Vec::new();
+                for (keywords, ty) in [
+                    (OBJECT_KEYWORDS, "object"),
+                    (ARRAY_KEYWORDS, "array"),
+                    (STRING_KEYWORDS, "string"),
+                    (NUMBER_KEYWORDS, "number"),
+                ] {
+                    if keywords.iter().any(|keyword| schema.contains_key(*keyword)) {
+                        implied.push(ty);
+                    }
+                }
+                if implied.is_empty() {
+                    if self.minimal(depth) {
+                        SCALAR_TYPES.to_vec()
+                    } else {
+                        ALL_TYPES.to_vec()
+                    }
+                } else {
+                    implied
+                }
+            }
+        };
+        if types.is_empty() {
+            return Err(GenerateError::unsatisfiable(
+                &location.join("type"),
+                "No types are allowed",
+            ));
+        }
+        match *self.pick(&types) {
+            "null" => Ok(Value::Null),
+            "boolean" => Ok(Value::Bool(self.rng.random_bool(0.5))),
+            "integer" => self.integer(schema, location),
+            "number" => self.number(schema, location),
+            "string" => self.string(schema, location),
+            "array" => self.array(schema, location, depth),
+            _ => self.object(schema, location, depth),
+        }
+    }
+
+    fn integer(
+        &mut self,
+        schema: &Map<String, Value>,
+        location: &Location,
+    ) -> Result<Value, GenerateError> {
+        let bounds = Bounds::of(schema);
+        let low = if bounds.low.is_finite() {
+            let low = if bounds.low_exclusive {
+                bounds.low.floor() + 1.0
+            } else {
+                bounds.low.ceil()
+            };
+            Some(low as i64)
+        } else {
+            None
+        };
+        let high = if bounds.high.is_finite() {
+            let high = if bounds.high_exclusive {
+                bounds.high.ceil() - 1.0
+            } else {
+                bounds.high.floor()
+            };
+            Some(high as i64)
+        } else {
+            None
+        };
+        let (low, high) = window(low, high, 100);
+        if low > high {
+            return Err(GenerateError::unsatisfiable(
+                location,
+                "No integers between the bounds",
+            ));
+        }
+        match schema.get("multipleOf").and_then(Value::as_f64) {
+            Some(factor) if factor.fract() == 0.0 && factor >= 1.0 => {
+                let factor = factor as i64;
+                let (first, last) = (div_ceil(low, factor), high.div_euclid(factor));
+                if first > last {
+                    return Err(GenerateError::unsatisfiable(
+                        &location.join("multipleOf"),
+                        "No multiples between the bounds",
+                    ));
+                }
+                let (first, last) = window(Some(first), Some(last), 100);
+                Ok(Value::from(
+                    self.rng.random_range(first..=last).saturating_mul(factor),
+                ))
+            }
+            // Other factors are checked with the whole instance
+            _ => Ok(Value::from(self.rng.random_range(low..=high))),
+        }
+    }
+
+    fn number(
+        &mut self,
+        schema: &Map<String, Value>,
+        location: &Location,
+    ) -> Result<Value, GenerateError> {
+        let bounds = Bounds::of(schema);
+        let (low, high) = match (bounds.low.is_finite(), bounds.high.is_finite()) {
+            (true, true) => (bounds.low, bounds.high),
+            (true, false) => (bounds.low, bounds.low + 100.0),
+            (false, true) => (bounds.high - 100.0, bounds.high),
+            (false, false) => (0.0, 100.0),
+        };
+        if let Some(factor) = schema.get("multipleOf").and_then(Value::as_f64) {
+            let mut first = (low / factor).ceil();
+            if bounds.low_exclusive && first * factor <= low {
+                first += 1.0;
+            }
+            let mut last = (high / factor).floor();
+            if bounds.high_exclusive && last * factor >= high {
+                last -= 1.0;
+            }
+            if first > last {
+                return Err(GenerateError::unsatisfiable(
+                    &location.join("multipleOf"),
+                    "No multiples between the bounds",
+                ));
+            }
+            let (first, last) = window(Some(first as i64), Some(last as i64), 100);
+            let value = self.rng.random_range(first..=last) as f64 * factor;
+            return Ok(number(round(value, decimals(factor))));
+        }
+        for _ in 0..self.max_attempts {
+            let value = round(low + self.rng.random::<f64>() * (high - low), Some(2));
+            if bounds.contains(value) {
+                return Ok(number(value));
+            }
+        }
+        let middle = low + (high - low) / 2.0;
+        if bounds.contains(middle) {
+            Ok(number(middle))
+        } else {
+            Err(GenerateError::unsatisfiable(
+                location,
+                "No numbers between the bounds",
+            ))
+        }
+    }
+
+    fn string(
+        &mut self,
+        schema: &Map<String, Value>,
+        location: &Location,
+    ) -> Result<Value, GenerateError> {
+        let min_length = schema
+            .get("minLength")
+            .and_then(Value::as_u64)
+            .map_or(0, |length| length as usize);
+        let max_length = schema
+            .get("maxLength")
+            .and_then(Value::as_u64)
+            .map(|length| length as usize);
+        if max_length.is_some_and(|max_length| max_length < min_length) {
+            return Err(GenerateError::unsatisfiable(
+                &location.join("maxLength"),
+                "maxLength is less than minLength",
+            ));
+        }
+        if let Some(value) = schema
+            .get("format")
+            .and_then(Value::as_str)
+            .and_then(|format| self.format(format))
+        {
+            return Ok(Value::String(value));
+        }
+        if let Some(Value::String(pattern)) = schema.get("pattern") {
+            let location = location.join("pattern");
+            let hir = parse_pattern(pattern).ok_or_else(|| {
+                GenerateError::new(
+                    &location,
+                    GenerateErrorKind::UnsupportedPattern {
+                        pattern: pattern.clone(),
+                    },
+                )
+            })?;
+            let mut spread = SPREAD;
+            for _ in 0..self.max_attempts {
+                let mut value = String::new();
+                self.matching(&hir, spread, &mut value);
+                let length = value.chars().count();
+                // Adjust how much repetitions may vary until the length fits
+                if length < min_length {
+                    spread = spread.saturating_mul(2) + 1;
+                } else if max_length.is_some_and(|max| length > max) {
+                    spread /= 2;
+                } else {
+                    return Ok(Value::String(value));
+                }
+            }
+            return Err(GenerateError::new(
+                &location,
+                GenerateErrorKind::Exhausted {
+                    attempts: self.max_attempts,
+                },
+            ));
+        }
+        let max_length = max_length.unwrap_or(usize::MAX).min(min_length + SPREAD);
+        let length = self.rng.random_range(min_length..=max_length);
+        Ok(Value::String(
+            (0..length)
+                .map(|_| char::from(self.rng.random_range(b'a'..=b'z')))
+                .collect(),
+        ))
+    }
+
+    fn format(&mut self, format: &str) -> Option<String> {
+        let word = *self.pick(WORDS);
+        let (year, month, day) = (
+            self.rng.random_range(2000..=2030),
+            self.rng.random_range(1..=12),
+            self.rng.random_range(1..=28),
+        );
+        let (hour, minute, second) = (
+            self.rng.random_range(0..24),
+            self.rng.random_range(0..60),
+            self.rng.random_range(0..60),
+        );
+        let value = match format {
+            "date" => format!("{year:04}-{month:02}-{day:02}"),
+            "time" => format!("{hour:02}:{minute:02}:{second:02}Z"),
+            "date-time" => {
+                format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+            }
+            "duration" => format!("P{day}DT{hour}H"),
+            "email" | "idn-email" => format!("{word}@example.com"),
+            "hostname" | "idn-hostname" => format!("{word}.example.com"),
+            "ipv4" => format!(
+                "{}.{}.{}.{}",
+                self.rng.random_range(1..=223),
+                self.rng.random::<u8>(),
+                self.rng.random::<u8>(),
+                self.rng.random_range(1..=254)
+            ),
+            "ipv6" => (0..8)
+                .map(|_| format!("{:x}", self.rng.random::<u16>()))
+                .collect::<Vec<_>>()
+                .join(":"),
+            "uri" | "iri" | "uri-reference" | "iri-reference" | "uri-template" => {
+                format!("https://example.com/{word}")
+            }
+            "json-pointer" => format!("/{word}"),
+            "relative-json-pointer" => format!("0/{word}"),
+            "regex" => format!("^{word}$"),
+            "uuid" => {
+                let bits: u128 = self.rng.random();
+                // Version 4, RFC 4122 variant
+                let bits = (bits & !(0xF << 76) & !(0x3 << 62)) | (0x4 << 76) | (0x2 << 62);
+                let hex = format!("{bits:032x}");
+                format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..]
+                )
+            }
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Append a string that matches `hir` to `out`.
+    fn matching(&mut self, hir: &Hir, spread: usize, out: &mut String) {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => {}
+            HirKind::Literal(literal) => out.push_str(&String::from_utf8_lossy(&literal.0)),
+            HirKind::Class(Class::Unicode(class)) => {
+                let ranges: Vec<_> = class
+                    .ranges()
+                    .iter()
+                    .map(|range| (u32::from(range.start()), u32::from(range.end())))
+                    .collect();
+                if let Some(c) = self.char_from(&ranges) {
+                    out.push(c);
+                }
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                let ranges: Vec<_> = class
+                    .ranges()
+                    .iter()
+                    .filter(|range| range.start().is_ascii())
+                    .map(|range| (u32::from(range.start()), u32::from(range.end().min(0x7F))))
+                    .collect();
+                if let Some(c) = self.char_from(&ranges) {
+                    out.push(c);
+                }
+            }
+            HirKind::Repetition(repetition) => {
+                let min = repetition.min as usize;
+                let max = repetition
+                    .max
+                    .map_or(usize::MAX, |max| max as usize)
+                    .min(min + spread);
+                for _ in 0..self.rng.random_range(min..=max) {
+                    self.matching(&repetition.sub, spread, out);
+                }
+            }
+            HirKind::Capture(capture) => self.matching(&capture.sub, spread, out),
+            HirKind::Concat(hirs) => {
+                for hir in hirs {
+                    self.matching(hir, spread, out);
+                }
+            }
+            HirKind::Alternation(hirs) => {
+                let hir = self.pick(hirs);
+                self.matching(hir, spread, out);
+            }
+        }
+    }
+
+    /// Pick a character from inclusive code point ranges, preferring printable ASCII.
+    fn char_from(&mut self, ranges: &[(u32, u32)]) -> Option<char> {
+        let printable: Vec<_> = ranges
+            .iter()
+            .filter_map(|&(start, end)| {
+                let (start, end) = (start.max(0x20), end.min(0x7E));
+                (start <= end).then_some((start, end))
+            })
+            .collect();
+        let ranges = if printable.is_empty() {
+            ranges
+        } else {
+            &printable
+        };
+        let total: u32 = ranges.iter().map(|(start, end)| end - start + 1).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut idx = self.rng.random_range(0..total);
+        for (start, end) in ranges {
+            let size = end - start + 1;
+            if idx < size {
+                return char::from_u32(start + idx);
+            }
+            idx -= size;
+        }
+        None
+    }
+
+    fn array(
+        &mut self,
+        schema: &Map<String, Value>,
+        location: &Location,
+        depth: usize,
+    ) -> Result<Value, GenerateError> {
+        let (prefix, prefix_keyword, rest, rest_keyword) =
+            match (schema.get("prefixItems"), schema.get("items")) {
+                (Some(Value::Array(prefix)), items) => {
+                    (prefix.as_slice(), "prefixItems", items, "items")
+                }
+                (_, Some(Value::Array(prefix))) => (
+                    prefix.as_slice(),
+                    "items",
+                    schema.get("additionalItems"),
+                    "additionalItems",
+                ),
+                (_, items) => (&[][..], "prefixItems", items, "items"),
+            };
+        let min_items = schema
+            .get("minItems")
+            .and_then(Value::as_u64)
+            .map_or(0, |count| count as usize);
+        let max_items = schema
+            .get("maxItems")
+            .and_then(Value::as_u64)
+            .map_or(usize::MAX, |count| count as usize);
+        let contains = schema.get("contains");
+        let min_contains = match contains {
+            Some(_) => schema
+                .get("minContains")
+                .and_then(Value::as_u64)
+                .map_or(1, |count| count as usize),
+            None => 0,
+        };
+        let max_items = if rest.is_some_and(|rest| self.forbids(rest)) {
+            max_items.min(prefix.len())
+        } else {
+            max_items
+        };
+        let min_items = min_items.max(min_contains);
+        if min_items > max_items {
+            return Err(GenerateError::unsatisfiable(
+                &location.join("minItems"),
+                "Not enough items are allowed",
+            ));
+        }
+        let length = if self.minimal(depth) {
+            min_items
+        } else {
+            self.rng
+                .random_range(min_items..=max_items.min(min_items.max(1) + SPREAD / 2))
+        };
+        let unique = schema.get("uniqueItems") == Some(&Value::Bool(true));
+        let mut items = Vec::with_capacity(length);
+        for idx in 0..length {
+            let (subschema, location) = match prefix.get(idx) {
+                Some(subschema) => (subschema, location.join(prefix_keyword).join(idx)),
+                None => (
+                    rest.unwrap_or(&Value::Bool(true)),
+                    location.join(rest_keyword),
+                ),
+            };
+            let merged;
+            let subschema = match contains {
+                Some(contains) if idx < min_contains => {
+                    merged = Value::Object(merge(
+                        Map::from_iter([(
+                            "allOf".to_string(),
+                            Value::Array(vec![subschema.clone()]),
+                        )]),
+                        contains,
+                        &location,
+                    )?);
+                    &merged
+                }
+                _ => subschema,
+            };
+            let mut item = self.value(subschema, &location, depth + 1)?;
+            if unique {
+                let mut attempts = 1;
+                while items.contains(&item) {
+                    if attempts == self.max_attempts {
+                        // Shorter arrays are fine as long as they are long enough
+                        if items.len() >= min_items {
+                            return Ok(Value::Array(items));
+                        }
+                        return Err(GenerateError::new(
+                            &location.join("uniqueItems"),
+                            GenerateErrorKind::Exhausted {
+                                attempts: self.max_attempts,
+                            },
+                        ));
+                    }
+                    item = self.value(subschema, &location, depth + 1)?;
+                    attempts += 1;
+                }
+            }
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn object(
+        &mut self,
+        schema: &Map<String, Value>,
+        location: &Location,
+        depth: usize,
+    ) -> Result<Value, GenerateError> {
+        let empty = Map::new();
+        let properties = match schema.get("properties") {
+            Some(Value::Object(properties)) => properties,
+            _ => &empty,
+        };
+        let additional = schema.get("additionalProperties");
+        let no_additional = additional.is_some_and(|additional| self.forbids(additional));
+        let closed = no_additional && !schema.contains_key("patternProperties");
+        let max_properties = schema
+            .get("maxProperties")
+            .and_then(Value::as_u64)
+            .map_or(usize::MAX, |count| count as usize);
+        let min_properties = schema
+            .get("minProperties")
+            .and_then(Value::as_u64)
+            .map_or(0, |count| count as usize);
+
+        let mut names: Vec<String> = Vec::new();
+        let add = |name: &str, names: &mut Vec<String>| {
+            if !names.iter().any(|existing| existing == name) {
+                names.push(name.to_string());
+            }
+        };
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if closed && !properties.contains_key(name) {
+                    return Err(GenerateError::unsatisfiable(
+                        &location.join("required"),
+                        format!(r#""{name}" is required, but not allowed"#),
+                    ));
+                }
+                add(name, &mut names);
+            }
+        }
+        if !self.minimal(depth) {
+            for (name, subschema) in properties {
+                if !self.forbids(subschema) && self.rng.random_bool(0.5) {
+                    add(name, &mut names);
+                }
+            }
+        }
+        // Fill up to `minProperties` with declared properties first, then with made up names
+        for (name, subschema) in properties {
+            if names.len() >= min_properties {
+                break;
+            }
+            if !self.forbids(subschema) {
+                add(name, &mut names);
+            }
+        }
+        let mut counter = 0;
+        while names.len() < min_properties {
+            if no_additional {
+                return Err(GenerateError::unsatisfiable(
+                    &location.join("minProperties"),
+                    "Not enough properties are allowed",
+                ));
+            }
+            counter += 1;
+            add(&format!("property{counter}"), &mut names);
+        }
+        // Properties required by the presence of others
+        let mut idx = 0;
+        while idx < names.len() {
+            for keyword in ["dependentRequired", "dependencies"] {
+                if let Some(Value::Array(dependencies)) = schema
+                    .get(keyword)
+                    .and_then(|dependencies| dependencies.get(&names[idx]))
+                {
+                    for name in dependencies.iter().filter_map(Value::as_str) {
+                        add(name, &mut names);
+                    }
+                }
+            }
+            idx += 1;
+        }
+        if names.len() > max_properties {
+            return Err(GenerateError::unsatisfiable(
+                &location.join("maxProperties"),
+                "Too many properties are required",
+            ));
+        }
+
+        let mut object = Map::new();
+        for name in names {
+            let (subschema, location) = match properties.get(&name) {
+                Some(subschema) => (subschema, location.join("properties").join(name.as_str())),
+                None => (
+                    additional.unwrap_or(&Value::Bool(true)),
+                    location.join("additionalProperties"),
+                ),
+            };
+            let value = self.value(subschema, &location, depth + 1)?;
+            object.insert(name, value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    /// Collect changes to `instance` that break one keyword of `schema` each.
+    ///
+    /// Changes may break other keywords as well, they are checked by validating the result.
+    fn violations(
+        &self,
+        schema: &Value,
+        instance: &Value,
+        location: &Location,
+        path: &mut Vec<Step>,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        let Value::Object(schema) = schema else {
+            return;
+        };
+        if depth > self.max_depth {
+            return;
+        }
+        let mut push = |keyword: &str, value: Value| {
+            out.push(Violation {
+                location: location.join(keyword),
+                path: path.clone(),
+                value,
+            });
+        };
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::String(ty) => vec![ty.as_str()],
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            for sample in samples() {
+                if !types.iter().any(|ty| has_type(&sample, ty)) {
+                    push("type", sample);
+                }
+            }
+        }
+        if let Some(Value::Array(values)) = schema.get("enum") {
+            for sample in samples() {
+                if !values.contains(&sample) {
+                    push("enum", sample);
+                }
+            }
+        }
+        if let Some(value) = schema.get("const") {
+            for sample in samples() {
+                if &sample != value {
+                    push("const", sample);
+                }
+            }
+        }
+        match instance {
+            Value::Number(current) => {
+                let is_integer = current.is_i64() || current.is_u64();
+                let at = |bound: f64| {
+                    if is_integer && bound.fract() == 0.0 {
+                        Value::from(bound as i64)
+                    } else {
+                        number(bound)
+                    }
+                };
+                if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+                    push("minimum", at(minimum.floor() - 1.0));
+                }
+                if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+                    push("maximum", at(maximum.ceil() + 1.0));
+                }
+                for keyword in ["exclusiveMinimum", "exclusiveMaximum"] {
+                    if let Some(bound) = schema.get(keyword).and_then(Value::as_f64) {
+                        push(keyword, at(bound));
+                    }
+                }
+                if let (Some(factor), Some(current)) = (
+                    schema.get("multipleOf").and_then(Value::as_f64),
+                    current.as_f64(),
+                ) {
+                    let value = if is_integer && factor.fract() == 0.0 && factor >= 2.0 {
+                        at(current + 1.0)
+                    } else {
+                        number(current + factor / 2.0)
+                    };
+                    push("multipleOf", value);
+                }
+            }
+            Value::String(current) => {
+                let length = current.chars().count();
+                if let Some(min_length) = schema.get("minLength").and_then(Value::as_u64) {
+                    if min_length > 0 {
+                        let shorter = current.chars().take(min_length as usize - 1).collect();
+                        push("minLength", Value::String(shorter));
+                    }
+                }
+                if let Some(max_length) = schema.get("maxLength").and_then(Value::as_u64) {
+                    let mut longer = current.clone();
+                    longer.extend(std::iter::repeat('a').take(max_length as usize + 1 - length));
+                    push("maxLength", Value::String(longer));
+                }
+            }
+            Value::Array(items) => {
+                if let Some(min_items) = schema.get("minItems").and_then(Value::as_u64) {
+                    if min_items > 0 {
+                        let fewer = items[..min_items as usize - 1].to_vec();
+                        push("minItems", Value::Array(fewer));
+                    }
+                }
+                if let Some(max_items) = schema.get("maxItems").and_then(Value::as_u64) {
+                    let filler = items.last().cloned().unwrap_or(Value::Null);
+                    let mut more = items.clone();
+                    more.resize(max_items as usize + 1, filler);
+                    push("maxItems", Value::Array(more));
+                }
+                if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+                    if let Some(first) = items.first() {
+                        let mut duplicated = items.clone();
+                        duplicated.push(first.clone());
+                        push("uniqueItems", Value::Array(duplicated));
+                    }
+                }
+            }
+            Value::Object(object) => {
+                if let Some(Value::Array(required)) = schema.get("required") {
+                    for name in required.iter().filter_map(Value::as_str) {
+                        let mut fewer = object.clone();
+                        if fewer.remove(name).is_some() {
+                            push("required", Value::Object(fewer));
+                        }
+                    }
+                }
+                if let Some(max_properties) = schema.get("maxProperties").and_then(Value::as_u64) {
+                    let mut more = object.clone();
+                    let mut counter = 0;
+                    while more.len() <= max_properties as usize {
+                        counter += 1;
+                        more.entry(format!("property{counter}"))
+                            .or_insert(Value::Null);
+                    }
+                    push("maxProperties", Value::Object(more));
+                }
+                if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                    let mut name = "additional".to_string();
+                    while object.contains_key(&name) {
+                        name.push('_');
+                    }
+                    let mut more = object.clone();
+                    more.insert(name, Value::Null);
+                    push("additionalProperties", Value::Object(more));
+                }
+            }
+            _ => {}
+        }
+        // Subschemas that apply to the same value
+        for keyword in ["$ref", "$recursiveRef", "$dynamicRef"] {
+            if let Some(Value::String(reference)) = schema.get(keyword) {
+                let location = location.join(keyword);
+                if let Ok(target) = self.resolve(reference, &location) {
+                    self.violations(target, instance, &location, path, depth + 1, out);
+                }
+            }
+        }
+        if let Some(Value::Array(subschemas)) = schema.get("allOf") {
+            for (idx, subschema) in subschemas.iter().enumerate() {
+                let location = location.join("allOf").join(idx);
+                self.violations(subschema, instance, &location, path, depth + 1, out);
+            }
+        }
+        // Subschemas that apply to the values within
+        match instance {
+            Value::Object(object) => {
+                let properties = match schema.get("properties") {
+                    Some(Value::Object(properties)) => Some(properties),
+                    _ => None,
+                };
+                let additional = schema
+                    .get("additionalProperties")
+                    .filter(|_| !schema.contains_key("patternProperties"));
+                for (name, value) in object {
+                    let (subschema, location) = match properties
+                        .and_then(|properties| properties.get(name))
+                    {
+                        Some(subschema) => (subschema, location.join("properties").join(name)),
+                        None => match additional {
+                            Some(subschema) => (subschema, location.join("additionalProperties")),
+                            None => continue,
+                        },
+                    };
+                    path.push(Step::Key(name.clone()));
+                    self.violations(subschema, value, &location, path, depth + 1, out);
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                let (prefix, prefix_keyword, rest, rest_keyword) =
+                    match (schema.get("prefixItems"), schema.get("items")) {
+                        (Some(Value::Array(prefix)), items) => {
+                            (prefix.as_slice(), "prefixItems", items, "items")
+                        }
+                        (_, Some(Value::Array(prefix))) => (
+                            prefix.as_slice(),
+                            "items",
+                            schema.get("additionalItems"),
+                            "additionalItems",
+                        ),
+                        (_, items) => (&[][..], "prefixItems", items, "items"),
+                    };
+                for (idx, item) in items.iter().enumerate() {
+                    let (subschema, location) = match prefix.get(idx) {
+                        Some(subschema) => (subschema, location.join(prefix_keyword).join(idx)),
+                        None => match rest {
+                            Some(subschema) => (subschema, location.join(rest_keyword)),
+                            None => continue,
+                        },
+                    };
+                    path.push(Step::Index(idx));
+                    self.violations(subschema, item, &location, path, depth + 1, out);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A step from a value to one of the values within.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// A change of the value at `path` that breaks the keyword at `location`.
+struct Violation {
+    location: Location,
+    path: Vec<Step>,
+    value: Value,
+}
+
+fn instance_path(path: &[Step]) -> Location {
+    path.iter()
+        .fold(Location::new(), |location, step| match step {
+            Step::Key(key) => location.join(key),
+            Step::Index(idx) => location.join(*idx),
+        })
+}
+
+/// Values of every type, used to break `type`, `enum` and `const`.
+fn samples() -> [Value; 7] {
+    [
+        Value::Null,
+        Value::Bool(false),
+        Value::from(0),
+        Value::from(0.5),
+        Value::String(String::new()),
+        Value::Array(Vec::new()),
+        Value::Object(Map::new()),
+    ]
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match (ty, value) {
+        ("null", Value::Null)
+        | ("boolean", Value::Bool(_))
+        | ("number", Value::Number(_))
+        | ("string", Value::String(_))
+        | ("array", Value::Array(_))
+        | ("object", Value::Object(_)) => true,
+        ("integer", Value::Number(number)) => number.as_f64().is_some_and(|n| n.fract() == 0.0),
+        _ => false,
+    }
+}
+
+fn get_mut<'v>(value: &'v mut Value, path: &[Step]) -> Option<&'v mut Value> {
+    path.iter()
+        .try_fold(value, |value, step| match (step, value) {
+            (Step::Key(key), Value::Object(object)) => object.get_mut(key),
+            (Step::Index(idx), Value::Array(items)) => items.get_mut(*idx),
+            _ => None,
+        })
+}
+
+/// Replace the value at `path`, returning whether it exists.
+fn replace(instance: &mut Value, path: &[Step], value: Value) -> bool {
+    match get_mut(instance, path) {
+        Some(current) => {
+            *current = value;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Remove the value at a non-empty `path`, returning whether it existed.
+fn remove(instance: &mut Value, path: &[Step]) -> bool {
+    let Some((last, parent)) = path.split_last() else {
+        return false;
+    };
+    match (last, get_mut(instance, parent)) {
+        (Step::Key(key), Some(Value::Object(object))) => object.remove(key).is_some(),
+        (Step::Index(idx), Some(Value::Array(items))) if *idx < items.len() => {
+            items.remove(*idx);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Paths of all object members and array items within `value`, parents first.
+fn members(value: &Value, path: &mut Vec<Step>, out: &mut Vec<Vec<Step>>) {
+    let children: Vec<(Step, &Value)> = match value {
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| (Step::Key(key.clone()), value))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| (Step::Index(idx), item))
+            .collect(),
+        _ => return,
+    };
+    for (step, child) in children {
+        path.push(step);
+        out.push(path.clone());
+        members(child, path, out);
+        path.pop();
+    }
+}
+
+/// Numeric bounds of a schema, infinite if missing.
+struct Bounds {
+    low: f64,
+    low_exclusive: bool,
+    high: f64,
+    high_exclusive: bool,
+}
+
+impl Bounds {
+    fn of(schema: &Map<String, Value>) -> Bounds {
+        let mut bounds = Bounds {
+            low: f64::NEG_INFINITY,
+            low_exclusive: false,
+            high: f64::INFINITY,
+            high_exclusive: false,
+        };
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            bounds.low = minimum;
+            // Boolean form from Draft 4
+            bounds.low_exclusive = schema.get("exclusiveMinimum") == Some(&Value::Bool(true));
+        }
+        if let Some(minimum) = schema.get("exclusiveMinimum").and_then(Value::as_f64) {
+            if minimum >= bounds.low {
+                bounds.low = minimum;
+                bounds.low_exclusive = true;
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            bounds.high = maximum;
+            bounds.high_exclusive = schema.get("exclusiveMaximum") == Some(&Value::Bool(true));
+        }
+        if let Some(maximum) = schema.get("exclusiveMaximum").and_then(Value::as_f64) {
+            if maximum <= bounds.high {
+                bounds.high = maximum;
+                bounds.high_exclusive = true;
+            }
+        }
+        bounds
+    }
+
+    fn contains(&self, value: f64) -> bool {
+        let above = if self.low_exclusive {
+            value > self.low
+        } else {
+            value >= self.low
+        };
+        let below = if self.high_exclusive {
+            value < self.high
+        } else {
+            value <= self.high
+        };
+        above && below
+    }
+}
+
+/// Narrow possibly open bounds to at most `size` values next to the given ones.
+fn window(low: Option<i64>, high: Option<i64>, size: i64) -> (i64, i64) {
+    match (low, high) {
+        (Some(low), Some(high)) => (low, high.min(low.saturating_add(size))),
+        (Some(low), None) => (low, low.saturating_add(size)),
+        (None, Some(high)) => (high.saturating_sub(size), high),
+        (None, None) => (0, size),
+    }
+}
+
+fn div_ceil(value: i64, divisor: i64) -> i64 {
+    let quotient = value.div_euclid(divisor);
+    if value.rem_euclid(divisor) == 0 {
+        quotient
+    } else {
+        quotient + 1
+    }
+}
+
+/// Number of decimal places in `value`, `None` for values in exponent notation.
+fn decimals(value: f64) -> Option<i32> {
+    let repr = value.to_string();
+    if repr.contains(['e', 'E']) {
+        return None;
+    }
+    Some(
+        repr.split_once('.')
+            .map_or(0, |(_, fraction)| fraction.len() as i32),
+    )
+}
+
+fn round(value: f64, decimals: Option<i32>) -> f64 {
+    match decimals {
+        Some(decimals) => {
+            let scale = 10_f64.powi(decimals);
+            (value * scale).round() / scale
+        }
+        None => value,
+    }
+}
+
+fn number(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+fn parse_pattern(pattern: &str) -> Option<Hir> {
+    let pattern = crate::ecma::to_rust_regex(pattern).ok()?;
+    regex_syntax::Parser::new().parse(&pattern).ok()
+}
+
+/// Find the first subschema that satisfies `predicate`.
+fn find_subschema<'a>(
+    schema: &'a Value,
+    predicate: &dyn Fn(&Map<String, Value>) -> bool,
+) -> Option<&'a Value> {
+    match schema {
+        Value::Object(object) => {
+            if predicate(object) {
+                return Some(schema);
+            }
+            object
+                .iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "enum" | "const"))
+                .find_map(|(_, value)| find_subschema(value, predicate))
+        }
+        Value::Array(items) => items
+            .iter()
+            .find_map(|item| find_subschema(item, predicate)),
+        _ => None,
+    }
+}
+
+/// Combine `other` into `schema`, so values generated from the result are likely valid under
+/// both.
+fn merge(
+    mut schema: Map<String, Value>,
+    other: &Value,
+    location: &Location,
+) -> Result<Map<String, Value>, GenerateError> {
+    let other = match other {
+        Value::Bool(true) => return Ok(schema),
+        Value::Object(other) => other,
+        _ => return Err(GenerateError::unsatisfiable(location, "No value is valid")),
+    };
+    for (keyword, value) in other {
+        let Some(existing) = schema.get_mut(keyword) else {
+            schema.insert(keyword.clone(), value.clone());
+            continue;
+        };
+        match (keyword.as_str(), existing, value) {
+            ("properties", Value::Object(existing), Value::Object(properties)) => {
+                for (name, subschema) in properties {
+                    match existing.get_mut(name) {
+                        Some(current) => {
+                            *current =
+                                serde_json::json!({"allOf": [current.take(), subschema.clone()]});
+                        }
+                        None => {
+                            existing.insert(name.clone(), subschema.clone());
+                        }
+                    }
+                }
+            }
+            ("required", Value::Array(existing), Value::Array(required)) => {
+                for name in required {
+                    if !existing.contains(name) {
+                        existing.push(name.clone());
+                    }
+                }
+            }
+            ("type", existing, value) => {
+                let types = |value: &Value| -> Vec<String> {
+                    match value {
+                        Value::String(ty) => vec![ty.clone()],
+                        Value::Array(types) => types
+                            .iter()
+                            .filter_map(|ty| ty.as_str().map(String::from))
+                            .collect(),
+                        _ => Vec::new(),
+                    }
+                };
+                let (left, right) = (types(existing), types(value));
+                let allows = |types: &[String], ty: &str| {
+                    types.iter().any(|allowed| allowed == ty)
+                        || (ty == "integer" && types.iter().any(|allowed| allowed == "number"))
+                };
+                let common: Vec<Value> = left
+                    .iter()
+                    .chain(&right)
+                    .filter(|ty| allows(&left, ty) && allows(&right, ty))
+                    .fold(Vec::new(), |mut common, ty| {
+                        if !common.contains(ty) {
+                            common.push(ty.clone());
+                        }
+                        common
+                    })
+                    .into_iter()
+                    .map(Value::String)
+                    .collect();
+                *existing = Value::Array(common);
+            }
+            (
+                "minimum" | "exclusiveMinimum" | "minLength" | "minItems" | "minProperties"
+                | "minContains",
+                Value::Number(existing),
+                Value::Number(value),
+            ) if value.as_f64() > existing.as_f64() => {
+                *existing = value.clone();
+            }
+            (
+                "maximum" | "exclusiveMaximum" | "maxLength" | "maxItems" | "maxProperties"
+                | "maxContains",
+                Value::Number(existing),
+                Value::Number(value),
+            ) if value.as_f64() < existing.as_f64() => {
+                *existing = value.clone();
+            }
+            ("allOf", Value::Array(existing), Value::Array(subschemas)) => {
+                existing.extend(subschemas.iter().cloned());
+            }
+            // Keep both, the applicator is resolved in a later step
+            ("$ref" | "$recursiveRef" | "$dynamicRef" | "anyOf" | "oneOf", _, value) => {
+                let wrapped = serde_json::json!({ keyword.clone(): value.clone() });
+                match schema.get_mut("allOf") {
+                    Some(Value::Array(subschemas)) => subschemas.push(wrapped),
+                    _ => {
+                        schema.insert("allOf".to_string(), Value::Array(vec![wrapped]));
+                    }
+                }
+            }
+            // Other conflicts are resolved by validating the whole instance
+            _ => {}
+        }
+    }
+    Ok(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{generate, generate_invalid, GenerateErrorKind, Generator};
+
+    #[test_case(&json!(true); "true schema")]
+    #[test_case(&json!({"type": "integer", "minimum": 10, "exclusiveMaximum": 13}); "integer bounds")]
+    #[test_case(&json!({"type": "integer", "multipleOf": 7, "minimum": -20, "maximum": 0}); "integer multiple")]
+    #[test_case(&json!({"type": "number", "exclusiveMinimum": 0.5, "maximum": 0.6}); "number bounds")]
+    #[test_case(&json!({"type": "number", "multipleOf": 0.25}); "number multiple")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#", "type": "integer", "minimum": 5, "exclusiveMinimum": true, "maximum": 6}); "draft 4 exclusive minimum")]
+    #[test_case(&json!({"type": "string", "minLength": 3, "maxLength": 5}); "string length")]
+    #[test_case(&json!({"type": "string", "pattern": "^[A-Z]{2}-\\d{3,}(x|yz)?$", "maxLength": 8}); "string pattern")]
+    #[test_case(&json!({"pattern": "[^\"]+@[a-z]+\\.(com|org)"}); "unanchored pattern")]
+    #[test_case(&json!({"format": "date-time"}); "format date-time")]
+    #[test_case(&json!({"format": "uuid"}); "format uuid")]
+    #[test_case(&json!({"format": "ipv6"}); "format ipv6")]
+    #[test_case(&json!({"enum": [1, "a", null]}); "enum")]
+    #[test_case(&json!({"const": {"a": [1]}}); "const")]
+    #[test_case(&json!({"type": ["string", "null"], "minLength": 1}); "type list")]
+    #[test_case(&json!({"properties": {"a": {"type": "boolean"}, "b": false}, "required": ["a", "c"], "additionalProperties": {"type": "integer"}}); "object")]
+    #[test_case(&json!({"type": "object", "minProperties": 3, "maxProperties": 3, "propertyNames": {"pattern": "^property"}}); "min properties")]
+    #[test_case(&json!({"required": ["a"], "dependentRequired": {"a": ["b"]}, "properties": {"b": {"const": 1}}}); "dependent required")]
+    #[test_case(&json!({"prefixItems": [{"type": "string"}, {"type": "integer"}], "items": false, "minItems": 2}); "prefix items")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "items": [{"const": 1}], "additionalItems": {"type": "string"}, "minItems": 3}); "legacy tuple")]
+    #[test_case(&json!({"items": {"enum": [1, 2, 3]}, "minItems": 3, "uniqueItems": true}); "unique items")]
+    #[test_case(&json!({"items": {"type": "integer"}, "contains": {"const": 42}, "minContains": 2}); "contains")]
+    #[test_case(&json!({"anyOf": [false, {"type": "string"}], "minLength": 2}); "any of")]
+    #[test_case(&json!({"oneOf": [{"type": "integer", "minimum": 5}, {"type": "integer", "maximum": 5}]}); "one of")]
+    #[test_case(&json!({"allOf": [{"properties": {"a": {"minimum": 1}}}, {"properties": {"a": {"maximum": 2}}, "required": ["a"]}]}); "all of")]
+    #[test_case(&json!({"type": "integer", "not": {"enum": [0, 1, 2]}, "minimum": 0, "maximum": 3}); "not")]
+    #[test_case(&json!({"$defs": {"node": {"type": "object", "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/node"}}}, "required": ["children"]}}, "$ref": "#/$defs/node"}); "recursive")]
+    #[test_case(&json!({"$defs": {"id": {"$anchor": "id", "type": "integer"}}, "properties": {"id": {"$ref": "#id"}}, "required": ["id"]}); "anchor")]
+    #[test_case(&json!({"$id": "https://example.com/root.json", "$defs": {"a": {"$id": "a.json", "$defs": {"b": {"type": "boolean"}}}}, "items": {"$ref": "a.json#/$defs/b"}, "minItems": 1}); "embedded resource")]
+    #[test_case(&json!({"$defs": {"yes": true, "no": false}, "properties": {"a": {"$ref": "#/$defs/yes"}, "b": {"$ref": "#/$defs/no"}}, "required": ["a"], "additionalProperties": {"$ref": "#/$defs/no"}, "minProperties": 1}); "boolean reference targets")]
+    #[test_case(&json!({"$defs": {"no": false}, "prefixItems": [{"type": "integer"}], "items": {"$ref": "#/$defs/no"}, "minItems": 1}); "reference to false items")]
+    fn valid(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        for seed in 0..20 {
+            let instance =
+                generate(schema, &mut ChaCha8Rng::seed_from_u64(seed)).expect("Should generate");
+            assert!(
+                validator.is_valid(&instance),
+                "{instance} is not valid under {schema}"
+            );
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "pattern": "^[a-z]+$"},
+                "tags": {"items": {"type": ["integer", "string"]}},
+                "score": {"type": "number"}
+            }
+        });
+        let generator = Generator::new(&schema).expect("Valid schema");
+        let first = generator
+            .generate(&mut ChaCha8Rng::seed_from_u64(7))
+            .expect("Should generate");
+        let second = generator
+            .generate(&mut ChaCha8Rng::seed_from_u64(7))
+            .expect("Should generate");
+        assert_eq!(first, second);
+    }
+
+    #[test_case(&json!(false), "", "No value is valid at \"\""; "false schema")]
+    #[test_case(&json!({"properties": {"a": {"minimum": 5, "maximum": 1, "type": "integer"}}, "required": ["a"]}), "/properties/a", "No integers between the bounds at \"/properties/a\""; "integer bounds")]
+    #[test_case(&json!({"items": {"enum": []}, "minItems": 1}), "/items/enum", "No values are allowed at \"/items/enum\""; "empty enum")]
+    #[test_case(&json!({"type": "string", "minLength": 3, "maxLength": 2}), "/maxLength", "maxLength is less than minLength at \"/maxLength\""; "string length")]
+    #[test_case(&json!({"properties": {"a": true}, "required": ["b"], "additionalProperties": false}), "/required", "\"b\" is required, but not allowed at \"/required\""; "closed object")]
+    #[test_case(&json!({"$ref": "https://json-schema.org/draft/2020-12/schema"}), "/$ref", "Reference \"https://json-schema.org/draft/2020-12/schema\" does not point into the schema at \"/$ref\""; "external reference")]
+    #[test_case(&json!({"$defs": {"a": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"}), "/$ref/$ref/$ref/$ref/$ref/$ref/$ref/$ref/$ref", "Schema is nested deeper than 8 levels at \"/$ref/$ref/$ref/$ref/$ref/$ref/$ref/$ref/$ref\""; "max depth")]
+    #[test_case(&json!({"type": "string", "pattern": "^a+$", "not": {"pattern": "a"}}), "/not", "No valid value found within 5 attempts at \"/not\""; "exhausted")]
+    fn error(schema: &Value, location: &str, message: &str) {
+        let error = Generator::new(schema)
+            .expect("Valid schema")
+            .with_max_attempts(5)
+            .with_max_depth(8)
+            .generate(&mut ChaCha8Rng::seed_from_u64(1))
+            .expect_err("Should fail");
+        assert_eq!(error.location.as_str(), location);
+        assert_eq!(error.to_string(), message);
+    }
+
+    #[test_case(&json!({"type": "integer"}); "type")]
+    #[test_case(&json!({"type": ["string", "null"]}); "type list")]
+    #[test_case(&json!({"enum": [1, "a"]}); "enum")]
+    #[test_case(&json!({"const": null}); "const")]
+    #[test_case(&json!({"type": "integer", "minimum": 3, "exclusiveMaximum": 10}); "integer bounds")]
+    #[test_case(&json!({"type": "number", "multipleOf": 0.5}); "multiple of")]
+    #[test_case(&json!({"type": "string", "minLength": 2, "maxLength": 4}); "string length")]
+    #[test_case(&json!({"type": "array", "items": {"type": "boolean"}, "minItems": 1, "maxItems": 3}); "array length")]
+    #[test_case(&json!({"items": {"enum": [1, 2, 3]}, "minItems": 1, "uniqueItems": true}); "unique items")]
+    #[test_case(&json!({"prefixItems": [{"type": "string"}], "items": {"type": "integer"}, "minItems": 2}); "prefix items")]
+    #[test_case(&json!({"type": "object", "properties": {"a": {"type": "string", "maxLength": 3}}, "required": ["a"], "additionalProperties": false}); "object")]
+    #[test_case(&json!({"type": "object", "maxProperties": 1}); "max properties")]
+    #[test_case(&json!({"allOf": [{"type": "object"}, {"required": ["a"]}]}); "all of")]
+    #[test_case(&json!({"$defs": {"age": {"type": "integer", "minimum": 0}}, "properties": {"age": {"$ref": "#/$defs/age"}}, "required": ["age"]}); "reference")]
+    fn invalid(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        for seed in 0..20 {
+            let invalid = generate_invalid(schema, &mut ChaCha8Rng::seed_from_u64(seed))
+                .expect("Should generate");
+            let errors: Vec<_> = validator.iter_errors(&invalid.instance).collect();
+            assert_eq!(errors.len(), 1, "{} under {schema}", invalid.instance);
+            assert_eq!(errors[0].schema_path, invalid.location);
+            assert_eq!(errors[0].instance_path, invalid.instance_path);
+        }
+    }
+
+    #[test]
+    fn invalid_is_shrunk() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "a": {"type": "integer"},
+                "b": {"type": "array", "items": {"type": "string"}},
+                "c": {"type": "boolean"}
+            }
+        });
+        for seed in 0..20 {
+            let invalid = generate_invalid(&schema, &mut ChaCha8Rng::seed_from_u64(seed))
+                .expect("Should generate");
+            // Only the violated value and its containers are left
+            let expected = match invalid.instance_path.as_str() {
+                "" => 0,
+                _ => 1,
+            };
+            if let Value::Object(object) = &invalid.instance {
+                assert_eq!(object.len(), expected, "{}", invalid.instance);
+                if let Some(Value::Array(items)) = object.get("b") {
+                    assert!(items.len() <= 1, "{}", invalid.instance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_deterministic() {
+        let schema =
+            json!({"properties": {"a": {"type": "integer", "maximum": 5}}, "required": ["a"]});
+        let generator = Generator::new(&schema).expect("Valid schema");
+        let first = generator
+            .generate_invalid(&mut ChaCha8Rng::seed_from_u64(7))
+            .expect("Should generate");
+        let second = generator
+            .generate_invalid(&mut ChaCha8Rng::seed_from_u64(7))
+            .expect("Should generate");
+        assert_eq!(first, second);
+    }
+
+    #[test_case(&json!(true); "true schema")]
+    #[test_case(&json!({}); "empty schema")]
+    #[test_case(&json!({"title": "Anything", "properties": {"a": true}}); "no constraints")]
+    fn cannot_violate(schema: &Value) {
+        let error = Generator::new(schema)
+            .expect("Valid schema")
+            .with_max_attempts(5)
+            .generate_invalid(&mut ChaCha8Rng::seed_from_u64(1))
+            .expect_err("Should fail");
+        assert!(matches!(error.kind, GenerateErrorKind::CannotViolate));
+        assert_eq!(error.to_string(), "No constraint can be violated at \"\"");
+    }
+
+    #[test]
+    fn invalid_schema() {
+        let error = generate(&json!({"type": 42}), &mut ChaCha8Rng::seed_from_u64(1))
+            .expect_err("Should fail");
+        assert!(matches!(error.kind, GenerateErrorKind::InvalidSchema(_)));
+    }
+}
@@ -0,0 +1,480 @@
+//! Revalidation of instances after a change at a single location.
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::{
+    node::{SchemaNode, WeakSchemaNode},
+    paths::{LazyLocation, Location},
+    validator::ValidationScratch,
+    ValidationError, Validator,
+};
+
+/// Result of validating an instance, kept to revalidate it after a change with
+/// [`Validator::revalidate_at`].
+///
+/// Besides the errors, it remembers which subschemas were valid at which locations of the
+/// instance. When only a part of the instance changes, subschemas that were applied to other
+/// parts are valid without running them again.
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let validator = jsonschema::validator_for(&json!({
+///     "properties": {"name": {"type": "string"}},
+///     "additionalProperties": {"type": "integer"}
+/// })).expect("Invalid schema");
+/// let mut instance = json!({"name": "config", "retries": 3});
+/// let state = validator.validation_state(&instance);
+/// assert!(state.is_valid());
+///
+/// instance["name"] = json!(42);
+/// let state = validator.revalidate_at(&instance, "/name", &state);
+/// assert_eq!(state.errors()[0].instance_path.as_str(), "/name");
+/// ```
+#[derive(Debug)]
+pub struct ValidationState {
+    /// Root of the validator this state was created by.
+    validator: WeakSchemaNode,
+    errors: Vec<ValidationError<'static>>,
+    /// Subschemas found to be valid, by the location of the instance they were applied to.
+    valid: BTreeMap<String, Vec<Visit>>,
+}
+
+/// A subschema applied to a value of the instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Visit {
+    /// Identifies the validators of the subschema, see [`SchemaNode::id`].
+    node: usize,
+    schema: Location,
+}
+
+impl ValidationState {
+    /// Whether the instance is valid.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Errors found in the instance, in the same order as
+    /// [`Validator::validate_into`](crate::Validator::validate_into) reports them.
+    #[must_use]
+    pub fn errors(&self) -> &[ValidationError<'static>] {
+        &self.errors
+    }
+}
+
+/// Records the subschemas that are valid during a validation, and skips the ones that were
+/// valid at an unchanged location before.
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    /// Location of the change, or `None` when validating from scratch.
+    changed: Option<String>,
+    valid: BTreeMap<String, Vec<Visit>>,
+    /// Depth of the location the innermost running subschema is applied to.
+    depth: usize,
+}
+
+/// How to proceed with applying a subschema.
+pub(crate) enum Enter {
+    /// The subschema was valid at this unchanged location.
+    Skip,
+    /// Apply the subschema and record its result with [`Recorder::exit`], if there is a visit.
+    Run(Option<PendingVisit>),
+}
+
+pub(crate) struct PendingVisit {
+    location: String,
+    visit: Visit,
+    parent_depth: usize,
+}
+
+impl Recorder {
+    /// Decide whether `node` has to be applied to the value at `location`.
+    ///
+    /// Only subschemas applied to a deeper location than their parent are recorded: others are
+    /// applied to the same value as their parent, and skipped along with it.
+    pub(crate) fn enter(&mut self, node: &SchemaNode, location: &LazyLocation) -> Enter {
+        let depth = location.depth();
+        if depth <= self.depth {
+            return Enter::Run(None);
+        }
+        let instance_location = Location::from(location);
+        let visit = Visit {
+            node: node.id(),
+            schema: node.location().clone(),
+        };
+        if let Some(changed) = &self.changed {
+            if !is_related(instance_location.as_str(), changed)
+                && self
+                    .valid
+                    .get(instance_location.as_str())
+                    .is_some_and(|visits| visits.contains(&visit))
+            {
+                return Enter::Skip;
+            }
+        }
+        let parent_depth = std::mem::replace(&mut self.depth, depth);
+        Enter::Run(Some(PendingVisit {
+            location: instance_location.as_str().to_string(),
+            visit,
+            parent_depth,
+        }))
+    }
+    /// Record the result of a subschema started with [`Recorder::enter`].
+    pub(crate) fn exit(&mut self, pending: PendingVisit, is_valid: bool) {
+        self.depth = pending.parent_depth;
+        if is_valid {
+            let visits = self.valid.entry(pending.location).or_default();
+            if !visits.contains(&pending.visit) {
+                visits.push(pending.visit);
+            }
+        }
+    }
+}
+
+/// Whether the value at `location` may be different after a change of the value at `changed`,
+/// i.e. it contains the changed value or is contained by it.
+fn is_related(location: &str, changed: &str) -> bool {
+    let (shorter, longer) = if location.len() <= changed.len() {
+        (location, changed)
+    } else {
+        (changed, location)
+    };
+    longer.starts_with(shorter)
+        && (longer.len() == shorter.len() || longer.as_bytes()[shorter.len()] == b'/')
+}
+
+/// The location whose value may have changed after a change at `pointer` of `instance`, or
+/// `None` if `pointer` can not be the location of a change.
+///
+/// Inserting or removing array items shifts the items after them, so a change of an item
+/// changes the whole array.
+fn changed_location<'p>(instance: &Value, pointer: &'p str) -> Option<&'p str> {
+    if pointer.is_empty() {
+        return Some(pointer);
+    }
+    if !pointer.starts_with('/') {
+        return None;
+    }
+    let (parent, _) = pointer.rsplit_once('/')?;
+    match instance.pointer(parent)? {
+        Value::Array(_) => Some(parent),
+        Value::Object(_) => Some(pointer),
+        _ => None,
+    }
+}
+
+impl Validator {
+    /// Validate `instance` and keep the results needed to revalidate it with
+    /// [`Validator::revalidate_at`] after it changes.
+    ///
+    /// It reports the same errors as [`Validator::validate_into`], but is slower as it records
+    /// which subschemas are valid at which locations.
+    #[must_use]
+    pub fn validation_state(&self, instance: &Value) -> ValidationState {
+        self.run_recorded(instance, None, BTreeMap::new())
+    }
+
+    /// Revalidate `instance` after the value at `changed_pointer` was replaced, added or removed.
+    ///
+    /// `previous` must be the state of the instance right before this change, as returned by
+    /// [`Validator::validation_state`] or an earlier call to this method. The result is the same
+    /// as [`Validator::validation_state`] for the changed instance.
+    ///
+    /// Invalidation is conservative. Every subschema applied to the changed location, to a
+    /// location within it, or to one of its parents runs again, so keywords that look at
+    /// several values at once, like `uniqueItems`, `required`, `oneOf` or
+    /// `unevaluatedProperties`, see the change. Only subschemas that were valid at other
+    /// locations are skipped; invalid ones always run again to report their errors. A change of
+    /// an array item counts as a change of the whole array, as insertions and removals shift the
+    /// items after it. Custom keywords must only depend on the value they are applied to.
+    ///
+    /// The instance is validated from scratch if `previous` was created by another validator,
+    /// or if `changed_pointer` is not a JSON pointer whose parent exists in `instance`.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "items": {"required": ["id"]},
+    ///     "uniqueItems": true
+    /// })).expect("Invalid schema");
+    /// let mut instance = json!([{"id": 1}, {"id": 2}]);
+    /// let state = validator.validation_state(&instance);
+    ///
+    /// instance[1]["id"] = json!(1);
+    /// let state = validator.revalidate_at(&instance, "/1/id", &state);
+    /// assert_eq!(state.errors()[0].to_string(), r#"[{"id":1},{"id":1}] has non-unique elements"#);
+    /// ```
+    #[must_use]
+    pub fn revalidate_at(
+        &self,
+        instance: &Value,
+        changed_pointer: &str,
+        previous: &ValidationState,
+    ) -> ValidationState {
+        let changed = match changed_location(instance, changed_pointer) {
+            Some(changed) if previous.validator.points_to(&self.root) => changed,
+            _ => return self.validation_state(instance),
+        };
+        let valid = previous
+            .valid
+            .iter()
+            .filter(|(location, _)| !is_related(location, changed))
+            .map(|(location, visits)| (location.clone(), visits.clone()))
+            .collect();
+        self.run_recorded(instance, Some(changed.to_string()), valid)
+    }
+
+    fn run_recorded(
+        &self,
+        instance: &Value,
+        changed: Option<String>,
+        valid: BTreeMap<String, Vec<Visit>>,
+    ) -> ValidationState {
+        let mut scratch = ValidationScratch::new();
+        scratch.incremental = Some(Box::new(Recorder {
+            changed,
+            valid,
+            depth: 0,
+        }));
+        let mut errors = Vec::new();
+        self.validate_into_with(instance, &mut errors, &mut scratch);
+        let recorder = scratch.incremental.expect("Recorder is set");
+        ValidationState {
+            validator: self.root.downgrade(),
+            errors: errors
+                .into_iter()
+                .map(ValidationError::into_owned)
+                .collect(),
+            valid: recorder.valid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::{
+        rand_core::{RngCore, SeedableRng},
+        ChaCha8Rng,
+    };
+    use serde_json::{json, Value};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use test_case::test_case;
+
+    use super::{changed_location, is_related};
+    use crate::{paths::LazyLocation, Keyword, ValidationError};
+
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Keyword for Counted {
+        fn validate<'i>(&self, _: &'i Value, _: &LazyLocation) -> Result<(), ValidationError<'i>> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+        fn is_valid(&self, _: &Value) -> bool {
+            true
+        }
+    }
+
+    #[test_case("/b", 1; "object property")]
+    #[test_case("/b/x", 1; "within object property")]
+    #[test_case("/a/1", 3; "array item")]
+    #[test_case("", 6; "root")]
+    fn skips_unchanged(pointer: &str, expected: usize) {
+        let visits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&visits);
+        let validator = crate::options()
+            .with_keyword("counted", move |_, _, _| {
+                Ok(Box::new(Counted(Arc::clone(&counter))))
+            })
+            .build(&json!({
+                "properties": {"a": {"items": {"counted": true}}},
+                "additionalProperties": {"counted": true}
+            }))
+            .expect("Invalid schema");
+        let instance = json!({"a": [1, 2, 3], "b": {}, "c": 1, "d": 2});
+        let state = validator.validation_state(&instance);
+        assert_eq!(visits.swap(0, Ordering::Relaxed), 6);
+        let state = validator.revalidate_at(&instance, pointer, &state);
+        assert!(state.is_valid());
+        assert_eq!(visits.load(Ordering::Relaxed), expected);
+    }
+
+    #[test_case("/a", "/a", true)]
+    #[test_case("", "/a", true)]
+    #[test_case("/a/b", "/a", true)]
+    #[test_case("/a", "/a/b", true)]
+    #[test_case("/ab", "/a", false)]
+    #[test_case("/b", "/a", false)]
+    #[test_case("/a/c", "/a/b", false)]
+    fn related(location: &str, changed: &str, expected: bool) {
+        assert_eq!(is_related(location, changed), expected);
+    }
+
+    #[test_case("", Some(""))]
+    #[test_case("/a/b", Some("/a/b"))]
+    #[test_case("/a/missing", Some("/a/missing"))]
+    #[test_case("/c/1", Some("/c"))]
+    #[test_case("/c/5", Some("/c"))]
+    #[test_case("/a/b/c", None)]
+    #[test_case("/x/y", None)]
+    #[test_case("a", None)]
+    fn changed(pointer: &str, expected: Option<&str>) {
+        let instance = json!({"a": {"b": 1}, "c": [1, 2]});
+        assert_eq!(changed_location(&instance, pointer), expected);
+    }
+
+    #[test]
+    fn stale_state() {
+        let schema = json!({"items": {"type": "integer"}});
+        let first = crate::validator_for(&schema).expect("Invalid schema");
+        let second = crate::validator_for(&schema).expect("Invalid schema");
+        let state = first.validation_state(&json!([1, 2]));
+        let instance = json!([1, "a", "b"]);
+        for state in [
+            second.revalidate_at(&instance, "/1", &state),
+            first.revalidate_at(&instance, "/x/y", &state),
+        ] {
+            let locations: Vec<_> = state
+                .errors()
+                .iter()
+                .map(|error| error.instance_path.as_str())
+                .collect();
+            assert_eq!(locations, ["/1", "/2"]);
+        }
+    }
+
+    /// Locations of all values within `value`.
+    fn locations(value: &Value, location: String, output: &mut Vec<String>) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    locations(value, format!("{location}/{key}"), output);
+                }
+            }
+            Value::Array(items) => {
+                for (idx, value) in items.iter().enumerate() {
+                    locations(value, format!("{location}/{idx}"), output);
+                }
+            }
+            _ => {}
+        }
+        output.push(location);
+    }
+
+    fn random_value(rng: &mut ChaCha8Rng) -> Value {
+        match rng.next_u32() % 8 {
+            0 => json!(null),
+            1 => json!(rng.next_u32() % 2 == 0),
+            2 => json!(rng.next_u32() % 10),
+            3 => json!(["a", "b", "c", "dd"][rng.next_u32() as usize % 4]),
+            4 => json!([rng.next_u32() % 3, rng.next_u32() % 3]),
+            5 => json!({"id": rng.next_u32() % 3, "name": "x"}),
+            6 => json!([]),
+            _ => json!({}),
+        }
+    }
+
+    /// Change the value at a random location, returning the pointer of the change.
+    fn mutate(instance: &mut Value, rng: &mut ChaCha8Rng) -> String {
+        let mut candidates = Vec::new();
+        locations(instance, String::new(), &mut candidates);
+        let target = &candidates[rng.next_u32() as usize % candidates.len()];
+        let value = instance.pointer_mut(target).expect("Exists");
+        let key = ["id", "name", "tags", "extra"][rng.next_u32() as usize % 4];
+        match (rng.next_u32() % 3, value) {
+            (0, Value::Object(object)) => {
+                if object.remove(key).is_none() {
+                    object.insert(key.to_string(), random_value(rng));
+                }
+                format!("{target}/{key}")
+            }
+            (0, Value::Array(items)) => {
+                if items.is_empty() || rng.next_u32() % 2 == 0 {
+                    items.push(random_value(rng));
+                    format!("{target}/{}", items.len() - 1)
+                } else {
+                    items.remove(0);
+                    format!("{target}/0")
+                }
+            }
+            (_, value) => {
+                *value = random_value(rng);
+                target.clone()
+            }
+        }
+    }
+
+    #[test_case(&json!({
+        "type": "object",
+        "properties": {
+            "items": {
+                "type": "array",
+                "items": {"$ref": "#/$defs/item"},
+                "uniqueItems": true,
+                "maxItems": 6
+            },
+            "meta": {
+                "oneOf": [
+                    {"required": ["id"]},
+                    {"properties": {"name": {"minLength": 2}}}
+                ]
+            }
+        },
+        "required": ["items"],
+        "additionalProperties": {"anyOf": [{"type": "integer"}, {"$ref": "#/$defs/item"}]},
+        "$defs": {
+            "item": {
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer", "maximum": 5},
+                    "tags": {"items": {"enum": ["a", "b"]}}
+                },
+                "dependentRequired": {"tags": ["name"]},
+                "unevaluatedProperties": false
+            }
+        }
+    }); "objects")]
+    #[test_case(&json!({
+        "$defs": {"node": {"items": {"$ref": "#/$defs/node"}, "contains": {"type": "integer"}}},
+        "prefixItems": [{"$ref": "#/$defs/node"}, {"not": {"type": "string"}}],
+        "items": {"if": {"type": "object"}, "then": {"required": ["id"]}, "else": {"$ref": "#/$defs/node"}},
+        "unevaluatedItems": false
+    }); "arrays")]
+    #[test_case(&json!({
+        "properties": {
+            "items": {"items": {"properties": {"id": {"maximum": 1}}, "additionalProperties": false}},
+            "meta": {"patternProperties": {"^i": {"type": "integer"}}, "additionalProperties": false}
+        },
+        "patternProperties": {"^m": {"type": "object"}},
+        "additionalProperties": {"type": "array"}
+    }); "additional properties")]
+    fn random_changes(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..20 {
+            let mut instance = json!({"items": [{"id": 1}, {"id": 2, "name": "x"}], "meta": {}});
+            let mut state = validator.validation_state(&instance);
+            for _ in 0..50 {
+                let pointer = mutate(&mut instance, &mut rng);
+                state = validator.revalidate_at(&instance, &pointer, &state);
+                let mut expected = Vec::new();
+                validator.validate_into(&instance, &mut expected);
+                let actual: Vec<_> = state
+                    .errors()
+                    .iter()
+                    .map(|error| (error.instance_path.as_str(), error.schema_path.as_str()))
+                    .collect();
+                let expected: Vec<_> = expected
+                    .iter()
+                    .map(|error| (error.instance_path.as_str(), error.schema_path.as_str()))
+                    .collect();
+                assert_eq!(actual, expected, "{instance} after a change at {pointer}");
+            }
+        }
+    }
+}
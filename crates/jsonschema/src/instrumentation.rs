@@ -0,0 +1,432 @@
+//! Hooks into the validation of individual keywords, e.g. to find out which keywords of a schema
+//! take the most time.
+//!
+//! Hooks are set via [`ValidationOptions::with_instrumentation`]. Every keyword of a validator
+//! built with a hook is wrapped so that validating it calls [`KeywordHook::enter`] before and
+//! [`KeywordHook::exit`] after, on the thread doing the validation. Keywords applying subschemas,
+//! like `properties` or `$ref`, wrap the keywords of those subschemas, so calls nest the same way
+//! the schema does. Validators built without a hook are not wrapped and pay nothing.
+//!
+//! Keywords are reported at their evaluation path, so keywords reached through `$ref` are below
+//! the `$ref`, e.g. `/properties/age/$ref/minimum`. Subschemas that are compiled once and shared
+//! between several locations, like identical subschemas or targets of several references, report
+//! all calls at the first of those locations.
+//!
+//! [`ValidationOptions::with_instrumentation`]: crate::ValidationOptions::with_instrumentation
+use std::{
+    cell::RefCell,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use ahash::AHashMap;
+use serde_json::Value;
+
+use crate::{
+    error::ErrorIterator,
+    keywords::BoxedValidator,
+    node::DeferredChecks,
+    paths::{LazyLocation, Location},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value, ValidationError,
+};
+
+/// Callbacks fired around the validation of every keyword.
+///
+/// Calls made on one thread are properly nested: every `enter` is followed by the matching
+/// `exit` before the enclosing keyword exits. With the `parallel` feature, keywords may be
+/// validated on several threads at once.
+pub trait KeywordHook: Send + Sync {
+    /// Called before `keyword` at `location` validates an instance.
+    fn enter(&self, keyword: &str, location: &Location) {
+        let _ = (keyword, location);
+    }
+    /// Called after `keyword` at `location` validated an instance, with the time it took,
+    /// including the time spent in keywords of its subschemas.
+    fn exit(&self, keyword: &str, location: &Location, elapsed: Duration);
+}
+
+/// A keyword validator reporting to a [`KeywordHook`].
+pub(crate) struct Instrumented {
+    hook: Arc<dyn KeywordHook>,
+    keyword: Box<str>,
+    location: Location,
+    validator: BoxedValidator,
+}
+
+impl Instrumented {
+    pub(crate) fn wrap(
+        hook: &Arc<dyn KeywordHook>,
+        keyword: &str,
+        location: Location,
+        validator: BoxedValidator,
+    ) -> BoxedValidator {
+        Box::new(Instrumented {
+            hook: Arc::clone(hook),
+            keyword: keyword.into(),
+            location,
+            validator,
+        })
+    }
+
+    #[inline]
+    fn measure<'a, T>(&'a self, validate: impl FnOnce(&'a BoxedValidator) -> T) -> T {
+        self.hook.enter(&self.keyword, &self.location);
+        let start = Instant::now();
+        let result = validate(&self.validator);
+        self.hook
+            .exit(&self.keyword, &self.location, start.elapsed());
+        result
+    }
+}
+
+impl Validate for Instrumented {
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        // Errors are produced lazily, so they are collected to time producing them
+        let errors: Vec<_> = self.measure(|v| v.iter_errors(instance, location).collect());
+        Box::new(errors.into_iter())
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.measure(|v| v.is_valid(instance))
+    }
+
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        self.measure(|v| v.is_valid_deferred(instance, checks))
+    }
+
+    #[cfg(feature = "parallel")]
+    fn par_is_valid(&self, instance: &Value) -> bool {
+        self.measure(|v| v.par_is_valid(instance))
+    }
+
+    #[cfg(feature = "parallel")]
+    fn par_iter_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        let errors: Vec<_> = self.measure(|v| v.par_iter_errors(instance, location).collect());
+        Box::new(errors.into_iter())
+    }
+
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.measure(|v| v.is_valid_value(instance))
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        self.measure(|v| v.validate(instance, location))
+    }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        self.measure(|v| v.collect_errors(instance, location, errors, scratch));
+    }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        self.measure(|v| v.apply(instance, location))
+    }
+}
+
+/// Time spent validating one keyword, as reported by [`TimingCollector::report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordTiming {
+    /// Name of the keyword.
+    pub keyword: String,
+    /// Location of the keyword in the schema.
+    pub location: Location,
+    /// How many times the keyword validated an instance.
+    pub calls: u64,
+    /// Time spent in the keyword, including keywords of its subschemas.
+    pub total: Duration,
+    /// Time spent in the keyword itself, excluding keywords of its subschemas.
+    pub own: Duration,
+}
+
+impl fmt::Display for KeywordTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>12?} {:>12?} {:>8} {} at \"{}\"",
+            self.own, self.total, self.calls, self.keyword, self.location
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Totals {
+    keyword: Box<str>,
+    calls: u64,
+    total: Duration,
+    own: Duration,
+}
+
+thread_local! {
+    /// Time spent in nested keywords of each keyword currently validated on this thread,
+    /// innermost last.
+    static NESTED: RefCell<Vec<Duration>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A [`KeywordHook`] that sums up the time spent in each keyword of a schema.
+///
+/// Clones share their totals, so a clone can be passed to
+/// [`ValidationOptions::with_instrumentation`] while the original is kept for reading the
+/// report.
+///
+/// ```rust
+/// use jsonschema::instrumentation::TimingCollector;
+/// use serde_json::json;
+///
+/// let timings = TimingCollector::new();
+/// let validator = jsonschema::options()
+///     .with_instrumentation(timings.clone())
+///     .build(&json!({"properties": {"name": {"type": "string", "pattern": "^[a-z]+$"}}}))
+///     .expect("Invalid schema");
+/// for _ in 0..10 {
+///     assert!(validator.is_valid(&json!({"name": "jsonschema"})));
+/// }
+///
+/// let report = timings.report();
+/// assert_eq!(report.len(), 3);
+/// let pattern = report
+///     .iter()
+///     .find(|timing| timing.keyword == "pattern")
+///     .expect("Missing keyword");
+/// assert_eq!(pattern.location.as_str(), "/properties/name/pattern");
+/// assert_eq!(pattern.calls, 10);
+/// println!("{timings}");
+/// ```
+///
+/// [`ValidationOptions::with_instrumentation`]: crate::ValidationOptions::with_instrumentation
+#[derive(Debug, Clone, Default)]
+pub struct TimingCollector {
+    totals: Arc<Mutex<AHashMap<Location, Totals>>>,
+}
+
+impl TimingCollector {
+    /// Create a collector without any timings.
+    #[must_use]
+    pub fn new() -> TimingCollector {
+        TimingCollector::default()
+    }
+
+    /// Timings of all keywords that validated an instance so far, the keyword that took the most
+    /// time itself first.
+    #[must_use]
+    pub fn report(&self) -> Vec<KeywordTiming> {
+        let totals = self.totals.lock().expect("Poisoned lock");
+        let mut report: Vec<_> = totals
+            .iter()
+            .map(|(location, totals)| KeywordTiming {
+                keyword: totals.keyword.to_string(),
+                location: location.clone(),
+                calls: totals.calls,
+                total: totals.total,
+                own: totals.own,
+            })
+            .collect();
+        report.sort_by(|left, right| {
+            right
+                .own
+                .cmp(&left.own)
+                .then_with(|| left.location.cmp(&right.location))
+        });
+        report
+    }
+
+    /// Forget all timings collected so far.
+    pub fn reset(&self) {
+        self.totals.lock().expect("Poisoned lock").clear();
+    }
+}
+
+impl KeywordHook for TimingCollector {
+    fn enter(&self, _: &str, _: &Location) {
+        NESTED.with(|nested| nested.borrow_mut().push(Duration::ZERO));
+    }
+
+    fn exit(&self, keyword: &str, location: &Location, elapsed: Duration) {
+        let nested = NESTED.with(|nested| {
+            let mut nested = nested.borrow_mut();
+            let own = nested.pop().unwrap_or_default();
+            if let Some(parent) = nested.last_mut() {
+                *parent += elapsed;
+            }
+            own
+        });
+        let mut totals = self.totals.lock().expect("Poisoned lock");
+        let totals = totals.entry(location.clone()).or_insert_with(|| Totals {
+            keyword: keyword.into(),
+            calls: 0,
+            total: Duration::ZERO,
+            own: Duration::ZERO,
+        });
+        totals.calls += 1;
+        totals.total += elapsed;
+        totals.own += elapsed.saturating_sub(nested);
+    }
+}
+
+/// A table of all timings, as returned by [`TimingCollector::report`].
+impl fmt::Display for TimingCollector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>12} {:>12} {:>8} keyword", "own", "total", "calls")?;
+        for timing in self.report() {
+            writeln!(f, "{timing}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeywordHook, TimingCollector};
+    use crate::paths::Location;
+    use serde_json::{json, Value};
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use test_case::test_case;
+
+    /// Records the order of all calls.
+    #[derive(Clone, Default)]
+    struct Calls(Arc<Mutex<Vec<String>>>);
+
+    impl KeywordHook for Calls {
+        fn enter(&self, keyword: &str, location: &Location) {
+            self.0
+                .lock()
+                .expect("Poisoned lock")
+                .push(format!("enter {keyword} {location}"));
+        }
+        fn exit(&self, keyword: &str, location: &Location, _: Duration) {
+            self.0
+                .lock()
+                .expect("Poisoned lock")
+                .push(format!("exit {keyword} {location}"));
+        }
+    }
+
+    fn calls(schema: &Value, instance: &Value) -> Vec<String> {
+        let calls = Calls::default();
+        let validator = crate::options()
+            .with_instrumentation(calls.clone())
+            .build(schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(instance));
+        let recorded = calls.0.lock().expect("Poisoned lock").clone();
+        recorded
+    }
+
+    #[test]
+    fn through_references() {
+        let schema = json!({
+            "properties": {"age": {"$ref": "#/$defs/age"}},
+            "$defs": {"age": {"minimum": 0}}
+        });
+        assert_eq!(
+            calls(&schema, &json!({"age": 1})),
+            [
+                "enter properties /properties",
+                "enter $ref /properties/age/$ref",
+                "enter minimum /properties/age/$ref/minimum",
+                "exit minimum /properties/age/$ref/minimum",
+                "exit $ref /properties/age/$ref",
+                "exit properties /properties",
+            ]
+        );
+    }
+
+    #[test]
+    fn through_applicators() {
+        let schema = json!({"anyOf": [{"type": "string"}, {"minimum": 0}]});
+        assert_eq!(
+            calls(&schema, &json!(1)),
+            [
+                "enter anyOf /anyOf",
+                "enter type /anyOf/0/type",
+                "exit type /anyOf/0/type",
+                "enter minimum /anyOf/1/minimum",
+                "exit minimum /anyOf/1/minimum",
+                "exit anyOf /anyOf",
+            ]
+        );
+    }
+
+    #[test_case(&json!({"items": {"type": "integer"}}), &json!([1, 2, "3"]) ; "items")]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/$defs/a"}}, "$defs": {"a": {"enum": [1]}}}), &json!({"a": 2}) ; "reference")]
+    #[test_case(&json!({"oneOf": [{"minimum": 1}, {"maximum": 5}]}), &json!(3) ; "one of")]
+    fn same_results(schema: &Value, instance: &Value) {
+        let plain = crate::validator_for(schema).expect("Invalid schema");
+        let instrumented = crate::options()
+            .with_instrumentation(TimingCollector::new())
+            .build(schema)
+            .expect("Invalid schema");
+        assert_eq!(instrumented.is_valid(instance), plain.is_valid(instance));
+        let errors = |validator: &crate::Validator| {
+            validator
+                .iter_errors(instance)
+                .map(|error| (error.to_string(), error.schema_path.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(errors(&instrumented), errors(&plain));
+        assert_eq!(
+            instrumented.apply(instance).basic(),
+            plain.apply(instance).basic()
+        );
+    }
+
+    #[test]
+    fn timings() {
+        let timings = TimingCollector::new();
+        let validator = crate::options()
+            .with_instrumentation(timings.clone())
+            .build(&json!({"items": {"type": "integer"}}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!([1, 2, 3])));
+        assert!(validator.validate(&json!([4])).is_ok());
+
+        let report = timings.report();
+        let mut calls: Vec<_> = report
+            .iter()
+            .map(|timing| {
+                (
+                    timing.keyword.as_str(),
+                    timing.location.as_str(),
+                    timing.calls,
+                )
+            })
+            .collect();
+        calls.sort_unstable();
+        assert_eq!(calls, [("items", "/items", 2), ("type", "/items/type", 4)]);
+        for timing in &report {
+            assert!(timing.own <= timing.total);
+        }
+        let items = report
+            .iter()
+            .find(|timing| timing.keyword == "items")
+            .expect("Missing keyword");
+        let ty = report
+            .iter()
+            .find(|timing| timing.keyword == "type")
+            .expect("Missing keyword");
+        // Time spent in `type` is part of the time spent in `items`
+        assert!(items.total >= ty.total);
+        assert_eq!(items.own, items.total - ty.total);
+        assert_eq!(timings.to_string().lines().count(), 3);
+
+        timings.reset();
+        assert!(timings.report().is_empty());
+    }
+}
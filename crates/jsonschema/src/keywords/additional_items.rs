@@ -2,16 +2,24 @@ use crate::{
     compiler,
     error::{no_error, ErrorIterator, ValidationError},
     keywords::{boolean::FalseValidator, CompilationResult},
+    lint,
     node::SchemaNode,
+    output::Evaluated,
     paths::{LazyLocation, Location},
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
-    validator::Validate,
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value, Draft,
 };
 use serde_json::{Map, Value};
 
+/// When more additional items than this are invalid, their errors are followed by one that
+/// counts them.
+const SUMMARY_THRESHOLD: usize = 1;
+
 pub(crate) struct AdditionalItemsObjectValidator {
     node: SchemaNode,
     items_count: usize,
+    location: Location,
 }
 impl AdditionalItemsObjectValidator {
     #[inline]
@@ -24,25 +32,68 @@ impl AdditionalItemsObjectValidator {
         Ok(Box::new(AdditionalItemsObjectValidator {
             node,
             items_count,
+            location: ctx.location().clone(),
         }))
     }
+
+    /// Error that counts the invalid additional items, if there are enough of them.
+    fn summary<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        invalid: usize,
+    ) -> Option<ValidationError<'i>> {
+        (invalid > SUMMARY_THRESHOLD).then(|| {
+            ValidationError::additional_items_failed(
+                self.location.clone(),
+                location.into(),
+                instance,
+                self.items_count,
+                invalid,
+            )
+        })
+    }
 }
 impl Validate for AdditionalItemsObjectValidator {
-    #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Array(items) = instance {
-            let errors: Vec<_> = items
-                .iter()
-                .enumerate()
-                .skip(self.items_count)
-                .flat_map(|(idx, item)| self.node.iter_errors(item, &location.push(idx)))
-                .collect();
+            let mut errors = Vec::new();
+            let mut invalid = 0;
+            for (idx, item) in items.iter().enumerate().skip(self.items_count) {
+                let before = errors.len();
+                errors.extend(self.node.iter_errors(item, &location.push(idx)));
+                if errors.len() > before {
+                    invalid += 1;
+                }
+            }
+            errors.extend(self.summary(instance, location, invalid));
             Box::new(errors.into_iter())
         } else {
             no_error()
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            let mut invalid = 0;
+            for (idx, item) in items.iter().enumerate().skip(self.items_count) {
+                let before = errors.len();
+                self.node
+                    .collect_errors(item, &location.push(idx), errors, scratch);
+                if errors.len() > before {
+                    invalid += 1;
+                }
+            }
+            errors.extend(self.summary(instance, location, invalid));
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             items
@@ -53,6 +104,11 @@ impl Validate for AdditionalItemsObjectValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        value::array_iter(instance)
+            .skip(self.items_count)
+            .all(|item| self.node.is_valid_value(item))
+    }
 
     fn validate<'i>(
         &self,
@@ -66,6 +122,25 @@ impl Validate for AdditionalItemsObjectValidator {
         }
         Ok(())
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        if let Value::Array(items) = instance {
+            let mut output: PartialApplication = items
+                .iter()
+                .enumerate()
+                .skip(self.items_count)
+                .map(|(idx, item)| self.node.apply_rooted(item, &location.push(idx)))
+                .collect();
+            // The annotation is `true` if the subschema was applied to any item, which are then
+            // all evaluated
+            if items.len() > self.items_count {
+                output.annotate(Evaluated::AllItems.into());
+            }
+            output
+        } else {
+            PartialApplication::valid_empty()
+        }
+    }
 }
 
 pub(crate) struct AdditionalItemsBooleanValidator {
@@ -90,6 +165,11 @@ impl Validate for AdditionalItemsBooleanValidator {
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .array_len()
+            .map_or(true, |len| len <= self.items_count)
+    }
 
     fn validate<'i>(
         &self,
@@ -103,22 +183,46 @@ impl Validate for AdditionalItemsBooleanValidator {
                     location.into(),
                     instance,
                     self.items_count,
+                    items.len(),
                 ));
             }
         }
         Ok(())
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        // The `false` subschema fails for any item it is applied to, so the annotation is never
+        // produced
+        match self.validate(instance, location) {
+            Ok(()) => PartialApplication::valid_empty(),
+            Err(error) => PartialApplication::invalid_empty(vec![error.into()]),
+        }
+    }
 }
 
 #[inline]
 pub(crate) fn compile<'a>(
     ctx: &compiler::Context,
-    parent: &Map<String, Value>,
+    parent: &'a Map<String, Value>,
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
+    if ctx.draft() == Draft::Draft202012 {
+        ctx.record_warning(lint::removed_keyword(
+            &ctx.location().join("additionalItems"),
+            r#""additionalItems" is not a keyword in Draft 2020-12 and is ignored, use "items" with "prefixItems" instead"#
+                .to_string(),
+        ));
+        return None;
+    }
     if let Some(items) = parent.get("items") {
         match items {
-            Value::Object(_) => None,
+            Value::Object(_) | Value::Bool(true) => {
+                ctx.record_warning(lint::no_op(
+                    &ctx.location().join("additionalItems"),
+                    r#""additionalItems" has no effect when "items" is a schema"#.to_string(),
+                ));
+                None
+            }
             Value::Array(items) => {
                 let kctx = ctx.new_at_location("additionalItems");
                 let items_count = items.len();
@@ -135,18 +239,14 @@ pub(crate) fn compile<'a>(
                     _ => None,
                 }
             }
-            Value::Bool(value) => {
-                if *value {
-                    None
-                } else {
-                    let location = ctx.location().join("additionalItems");
-                    Some(FalseValidator::compile(location))
-                }
+            Value::Bool(_) => {
+                let location = ctx.location().join("additionalItems");
+                Some(FalseValidator::compile(location))
             }
             _ => Some(Err(ValidationError::multiple_type_error(
                 Location::new(),
-                ctx.location().clone(),
-                schema,
+                ctx.location().join("items"),
+                items,
                 PrimitiveTypesBitMap::new()
                     .add_type(PrimitiveType::Object)
                     .add_type(PrimitiveType::Array)
@@ -154,12 +254,17 @@ pub(crate) fn compile<'a>(
             ))),
         }
     } else {
+        ctx.record_warning(lint::no_op(
+            &ctx.location().join("additionalItems"),
+            r#""additionalItems" has no effect without "items""#.to_string(),
+        ));
         None
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::{error::ValidationErrorKind, LintCode};
     use referencing::Draft;
     use serde_json::{json, Value};
     use test_case::test_case;
@@ -175,4 +280,94 @@ mod tests {
         let error = validator.validate(instance).expect_err("Should fail");
         assert_eq!(error.schema_path.as_str(), expected);
     }
+
+    #[test_case(Draft::Draft202012, &json!({"additionalItems": false, "prefixItems": [{}]}), LintCode::RemovedKeyword; "removed")]
+    #[test_case(Draft::Draft201909, &json!({"additionalItems": false}), LintCode::NoOpKeyword; "without items")]
+    #[test_case(Draft::Draft7, &json!({"additionalItems": false, "items": {}}), LintCode::NoOpKeyword; "items schema")]
+    #[test_case(Draft::Draft6, &json!({"additionalItems": false, "items": true}), LintCode::NoOpKeyword; "items true")]
+    fn ignored(draft: Draft, schema: &Value, expected: LintCode) {
+        let validator = crate::options()
+            .with_draft(draft)
+            .build(schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!([1, 2])));
+        let warnings: Vec<_> = validator
+            .warnings()
+            .iter()
+            .map(|lint| (lint.code, lint.location.as_str()))
+            .collect();
+        assert_eq!(warnings, [(expected, "/additionalItems")]);
+        let error = crate::options()
+            .with_draft(draft)
+            .with_denied_lints([expected])
+            .build(schema)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/additionalItems");
+    }
+
+    #[test_case(&json!([1, 2, 3]), 3, "Additional items are not allowed (3 was unexpected at index 2)")]
+    #[test_case(&json!([1, 2, 3, 4, 5]), 5, "Additional items are not allowed (3, 4, 5 were unexpected at indexes 2-4)")]
+    fn rejected_indexes(instance: &Value, expected_length: usize, expected: &str) {
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .build(&json!({"items": [{}, {}], "additionalItems": false}))
+            .expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::AdditionalItems { limit: 2, length } if length == expected_length
+        ));
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test_case(&json!([1, "a", 2]), &[("/additionalItems/type", "/1")])]
+    #[test_case(
+        &json!([1, "a", 2, "b"]),
+        &[("/additionalItems/type", "/1"), ("/additionalItems/type", "/3"), ("/additionalItems", "")]
+    )]
+    fn summary(instance: &Value, expected: &[(&str, &str)]) {
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .build(&json!({"items": [{}], "additionalItems": {"type": "integer"}}))
+            .expect("Invalid schema");
+        let errors: Vec<_> = validator.iter_errors(instance).collect();
+        let locations: Vec<_> = errors
+            .iter()
+            .map(|error| (error.schema_path.as_str(), error.instance_path.as_str()))
+            .collect();
+        assert_eq!(locations, expected);
+        let mut collected = Vec::new();
+        validator.validate_into(instance, &mut collected);
+        assert_eq!(collected.len(), errors.len());
+        if let Some(summary) = errors.get(2) {
+            assert!(matches!(
+                summary.kind,
+                ValidationErrorKind::AdditionalItemsFailed { limit: 1, count: 2 }
+            ));
+            assert_eq!(
+                summary.to_string(),
+                "2 items after the first 1 are not valid under the additional items schema"
+            );
+        }
+    }
+
+    #[test_case(&json!({"items": [{}], "additionalItems": {"type": "integer"}}), &json!([1, 2]), Some(json!(true)))]
+    #[test_case(&json!({"items": [{}], "additionalItems": {"type": "integer"}}), &json!([1]), None)]
+    #[test_case(&json!({"items": [{}], "additionalItems": false}), &json!([1]), None)]
+    fn annotation(schema: &Value, instance: &Value, expected: Option<Value>) {
+        let validator = crate::options()
+            .with_draft(Draft::Draft201909)
+            .build(schema)
+            .expect("Invalid schema");
+        let output = validator.apply(instance).basic();
+        assert!(output.is_valid());
+        let output = serde_json::to_value(output).expect("Serializable");
+        let annotation = output["annotations"]
+            .as_array()
+            .expect("Has annotations")
+            .iter()
+            .find(|unit| unit["keywordLocation"] == "/additionalItems")
+            .map(|unit| unit["annotations"].clone());
+        assert_eq!(annotation, expected);
+    }
 }
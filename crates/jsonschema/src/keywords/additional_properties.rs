@@ -10,14 +10,14 @@ use crate::{
     compiler,
     error::{no_error, ErrorIterator, ValidationError},
     keywords::CompilationResult,
-    node::SchemaNode,
-    output::{Annotations, BasicOutput, OutputUnit},
+    node::{DeferredChecks, SchemaNode},
+    output::{AbsoluteLocation, Annotations, BasicOutput, Evaluated, OutputUnit},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     properties::*,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
-use referencing::Uri;
 use serde_json::{Map, Value};
 
 macro_rules! is_valid {
@@ -62,6 +62,66 @@ macro_rules! iter_errors {
     }};
 }
 
+/// Check `value` against every pattern matching `property`.
+/// With `require_match`, a property that matches no pattern is invalid.
+fn are_patterns_valid(
+    patterns: &PatternedValidators,
+    property: &str,
+    value: &dyn value::Value,
+    require_match: bool,
+) -> bool {
+    let mut has_match = false;
+    for (re, node) in patterns {
+        if re.is_match(property).unwrap_or(false) {
+            has_match = true;
+            if !node.is_valid_value(value) {
+                return false;
+            }
+        }
+    }
+    has_match || !require_match
+}
+
+fn has_pattern_match(patterns: &PatternedValidators, property: &str) -> bool {
+    patterns
+        .iter()
+        .any(|(re, _)| re.is_match(property).unwrap_or(false))
+}
+
+/// A keyword whose logic an `additionalProperties` validator takes over, like `properties`.
+///
+/// The validator is compiled at the `additionalProperties` location, so annotations of such
+/// keywords are reported at their own location instead.
+struct Sibling {
+    location: Location,
+    absolute: Option<AbsoluteLocation>,
+}
+
+impl Sibling {
+    fn new(ctx: &compiler::Context, keyword: &str) -> Sibling {
+        Sibling {
+            location: ctx.location().join(keyword),
+            absolute: ctx.absolute_location(),
+        }
+    }
+
+    /// Report that this keyword evaluated the properties in `names`.
+    fn annotate<'a>(
+        &self,
+        output: &mut BasicOutput<'a>,
+        location: &LazyLocation,
+        names: Vec<String>,
+    ) {
+        *output += OutputUnit::<Annotations<'a>>::annotations_at(
+            self.absolute.as_ref(),
+            self.location.clone(),
+            location.into(),
+            Evaluated::Properties(names).into(),
+        )
+        .into();
+    }
+}
+
 /// # Schema example
 ///
 /// ```json
@@ -103,6 +163,21 @@ impl Validate for AdditionalPropertiesValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            for (name, value) in item {
+                self.node
+                    .collect_errors(value, &location.push(name.as_str()), errors, scratch);
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             item.values().all(|i| self.node.is_valid(i))
@@ -110,6 +185,18 @@ impl Validate for AdditionalPropertiesValidator {
             true
         }
     }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        if let Value::Object(item) = instance {
+            item.values().all(|value| checks.check(&self.node, value))
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .object_iter()
+            .all(|(_, value)| self.node.is_valid_value(value))
+    }
 
     fn validate<'i>(
         &self,
@@ -134,7 +221,7 @@ impl Validate for AdditionalPropertiesValidator {
                 matched_props.push(name.clone());
             }
             let mut result: PartialApplication = output.into();
-            result.annotate(Value::from(matched_props).into());
+            result.annotate(Evaluated::Properties(matched_props).into());
             result
         } else {
             PartialApplication::valid_empty()
@@ -172,6 +259,9 @@ impl Validate for AdditionalPropertiesFalseValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_len().map_or(true, |len| len == 0)
+    }
 
     fn validate<'i>(
         &self,
@@ -179,16 +269,30 @@ impl Validate for AdditionalPropertiesFalseValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            if let Some((_, value)) = item.iter().next() {
-                return Err(ValidationError::false_schema(
+            if !item.is_empty() {
+                // No properties are allowed, so every one of them is unexpected
+                return Err(ValidationError::additional_properties(
                     self.location.clone(),
                     location.into(),
-                    value,
+                    instance,
+                    item.keys().cloned().collect(),
                 ));
             }
         }
         Ok(())
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        match self.validate(instance, location) {
+            Ok(()) if instance.is_object() => {
+                let mut result = PartialApplication::valid_empty();
+                result.annotate(Evaluated::Properties(Vec::new()).into());
+                result
+            }
+            Ok(()) => PartialApplication::valid_empty(),
+            Err(error) => PartialApplication::invalid_empty(vec![error.into()]),
+        }
+    }
 }
 
 /// # Schema example
@@ -212,6 +316,7 @@ impl Validate for AdditionalPropertiesFalseValidator {
 pub(crate) struct AdditionalPropertiesNotEmptyFalseValidator<M: PropertiesValidatorsMap> {
     properties: M,
     location: Location,
+    properties_keyword: Sibling,
 }
 impl AdditionalPropertiesNotEmptyFalseValidator<SmallValidatorsMap> {
     #[inline]
@@ -222,6 +327,7 @@ impl AdditionalPropertiesNotEmptyFalseValidator<SmallValidatorsMap> {
         Ok(Box::new(AdditionalPropertiesNotEmptyFalseValidator {
             properties: compile_small_map(ctx, map)?,
             location: ctx.location().join("additionalProperties"),
+            properties_keyword: Sibling::new(ctx, "properties"),
         }))
     }
 }
@@ -234,6 +340,7 @@ impl AdditionalPropertiesNotEmptyFalseValidator<BigValidatorsMap> {
         Ok(Box::new(AdditionalPropertiesNotEmptyFalseValidator {
             properties: compile_big_map(ctx, map)?,
             location: ctx.location().join("additionalProperties"),
+            properties_keyword: Sibling::new(ctx, "properties"),
         }))
     }
 }
@@ -265,6 +372,33 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseV
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            let mut unexpected = vec![];
+            for (property, value) in item {
+                if let Some(node) = self.properties.get_validator(property) {
+                    node.collect_errors(value, &location.push(property.as_str()), errors, scratch);
+                } else {
+                    unexpected.push(property.clone());
+                }
+            }
+            if !unexpected.is_empty() {
+                errors.push(ValidationError::additional_properties(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    unexpected,
+                ));
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(props) = instance {
             are_properties_valid(&self.properties, props, |_| false)
@@ -272,6 +406,9 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseV
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        are_property_values_valid(&self.properties, instance, |_| false)
+    }
 
     fn validate<'i>(
         &self,
@@ -298,16 +435,21 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseV
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         if let Value::Object(item) = instance {
             let mut unexpected = Vec::with_capacity(item.len());
+            let mut matched_props = Vec::with_capacity(item.len());
             let mut output = BasicOutput::default();
             for (property, value) in item {
                 if let Some((_name, node)) = self.properties.get_key_validator(property) {
                     let path = location.push(property.as_str());
+                    matched_props.push(property.clone());
                     output += node.apply_rooted(value, &path);
                 } else {
                     unexpected.push(property.clone())
                 }
             }
+            self.properties_keyword
+                .annotate(&mut output, location, matched_props);
             let mut result: PartialApplication = output.into();
+            result.annotate(Evaluated::Properties(Vec::new()).into());
             if !unexpected.is_empty() {
                 result.mark_errored(
                     ValidationError::additional_properties(
@@ -356,6 +498,7 @@ impl<M: PropertiesValidatorsMap> core::fmt::Display
 pub(crate) struct AdditionalPropertiesNotEmptyValidator<M: PropertiesValidatorsMap> {
     node: SchemaNode,
     properties: M,
+    properties_keyword: Sibling,
 }
 impl AdditionalPropertiesNotEmptyValidator<SmallValidatorsMap> {
     #[inline]
@@ -368,6 +511,7 @@ impl AdditionalPropertiesNotEmptyValidator<SmallValidatorsMap> {
         Ok(Box::new(AdditionalPropertiesNotEmptyValidator {
             properties: compile_small_map(ctx, map)?,
             node: compiler::compile(&kctx, kctx.as_resource_ref(schema))?,
+            properties_keyword: Sibling::new(ctx, "properties"),
         }))
     }
 }
@@ -382,6 +526,7 @@ impl AdditionalPropertiesNotEmptyValidator<BigValidatorsMap> {
         Ok(Box::new(AdditionalPropertiesNotEmptyValidator {
             properties: compile_big_map(ctx, map)?,
             node: compiler::compile(&kctx, kctx.as_resource_ref(schema))?,
+            properties_keyword: Sibling::new(ctx, "properties"),
         }))
     }
 }
@@ -404,6 +549,25 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyValida
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(map) = instance {
+            for (property, value) in map {
+                let location = location.push(property.as_str());
+                if let Some(node) = self.properties.get_validator(property) {
+                    node.collect_errors(value, &location, errors, scratch);
+                } else {
+                    self.node.collect_errors(value, &location, errors, scratch);
+                }
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(props) = instance {
             are_properties_valid(&self.properties, props, |instance| {
@@ -413,6 +577,24 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyValida
             true
         }
     }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        if let Value::Object(props) = instance {
+            props.iter().all(|(property, value)| {
+                if let Some(node) = self.properties.get_validator(property) {
+                    checks.check(node, value)
+                } else {
+                    checks.check(&self.node, value)
+                }
+            })
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        are_property_values_valid(&self.properties, instance, |instance| {
+            self.node.is_valid_value(instance)
+        })
+    }
 
     fn validate<'i>(
         &self,
@@ -434,6 +616,7 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyValida
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         if let Value::Object(map) = instance {
             let mut matched_propnames = Vec::with_capacity(map.len());
+            let mut matched_props = Vec::with_capacity(map.len());
             let mut output = BasicOutput::default();
             for (property, value) in map {
                 let path = location.push(property.as_str());
@@ -441,15 +624,16 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyValida
                     self.properties.get_key_validator(property)
                 {
                     output += property_validators.apply_rooted(value, &path);
+                    matched_props.push(property.clone());
                 } else {
                     output += self.node.apply_rooted(value, &path);
                     matched_propnames.push(property.clone());
                 }
             }
+            self.properties_keyword
+                .annotate(&mut output, location, matched_props);
             let mut result: PartialApplication = output.into();
-            if !matched_propnames.is_empty() {
-                result.annotate(Value::from(matched_propnames).into());
-            }
+            result.annotate(Evaluated::Properties(matched_propnames).into());
             result
         } else {
             PartialApplication::valid_empty()
@@ -481,12 +665,7 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyValida
 pub(crate) struct AdditionalPropertiesWithPatternsValidator {
     node: SchemaNode,
     patterns: PatternedValidators,
-    /// We need this because `compiler::compile` uses the additionalProperties keyword to compile
-    /// this validator. That means that the schema node which contains this validator has
-    /// "additionalProperties" as it's path. However, we need to produce annotations which have the
-    /// patternProperties keyword as their path so we store the paths here.
-    pattern_keyword_path: Location,
-    pattern_keyword_absolute_location: Option<Uri<String>>,
+    patterns_keyword: Sibling,
 }
 impl AdditionalPropertiesWithPatternsValidator {
     #[inline]
@@ -499,8 +678,7 @@ impl AdditionalPropertiesWithPatternsValidator {
         Ok(Box::new(AdditionalPropertiesWithPatternsValidator {
             node: compiler::compile(&kctx, kctx.as_resource_ref(schema))?,
             patterns,
-            pattern_keyword_path: ctx.location().join("patternProperties"),
-            pattern_keyword_absolute_location: ctx.new_at_location("patternProperties").base_uri(),
+            patterns_keyword: Sibling::new(ctx, "patternProperties"),
         }))
     }
 }
@@ -529,6 +707,30 @@ impl Validate for AdditionalPropertiesWithPatternsValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            for (property, value) in item {
+                let location = location.push(property.as_str());
+                let mut has_match = false;
+                for (re, node) in &self.patterns {
+                    if re.is_match(property).unwrap_or(false) {
+                        has_match = true;
+                        node.collect_errors(value, &location, errors, scratch);
+                    }
+                }
+                if !has_match {
+                    self.node.collect_errors(value, &location, errors, scratch);
+                }
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             for (property, value) in item {
@@ -546,6 +748,12 @@ impl Validate for AdditionalPropertiesWithPatternsValidator {
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_iter().all(|(property, value)| {
+            are_patterns_valid(&self.patterns, property, value, false)
+                && (has_pattern_match(&self.patterns, property) || self.node.is_valid_value(value))
+        })
+    }
 
     fn validate<'i>(
         &self,
@@ -580,28 +788,20 @@ impl Validate for AdditionalPropertiesWithPatternsValidator {
                 for (pattern, node) in &self.patterns {
                     if pattern.is_match(property).unwrap_or(false) {
                         has_match = true;
-                        pattern_matched_propnames.push(property.clone());
                         output += node.apply_rooted(value, &path)
                     }
                 }
-                if !has_match {
+                if has_match {
+                    pattern_matched_propnames.push(property.clone());
+                } else {
                     additional_matched_propnames.push(property.clone());
                     output += self.node.apply_rooted(value, &path)
                 }
             }
-            if !pattern_matched_propnames.is_empty() {
-                output += OutputUnit::<Annotations<'_>>::annotations(
-                    self.pattern_keyword_path.clone(),
-                    location.into(),
-                    self.pattern_keyword_absolute_location.clone(),
-                    Value::from(pattern_matched_propnames).into(),
-                )
-                .into();
-            }
+            self.patterns_keyword
+                .annotate(&mut output, location, pattern_matched_propnames);
             let mut result: PartialApplication = output.into();
-            if !additional_matched_propnames.is_empty() {
-                result.annotate(Value::from(additional_matched_propnames).into())
-            }
+            result.annotate(Evaluated::Properties(additional_matched_propnames).into());
             result
         } else {
             PartialApplication::valid_empty()
@@ -633,8 +833,7 @@ impl Validate for AdditionalPropertiesWithPatternsValidator {
 pub(crate) struct AdditionalPropertiesWithPatternsFalseValidator {
     patterns: PatternedValidators,
     location: Location,
-    pattern_keyword_path: Location,
-    pattern_keyword_absolute_location: Option<Uri<String>>,
+    patterns_keyword: Sibling,
 }
 impl AdditionalPropertiesWithPatternsFalseValidator {
     #[inline]
@@ -645,8 +844,7 @@ impl AdditionalPropertiesWithPatternsFalseValidator {
         Ok(Box::new(AdditionalPropertiesWithPatternsFalseValidator {
             patterns,
             location: ctx.location().join("additionalProperties"),
-            pattern_keyword_path: ctx.location().join("patternProperties"),
-            pattern_keyword_absolute_location: ctx.new_at_location("patternProperties").base_uri(),
+            patterns_keyword: Sibling::new(ctx, "patternProperties"),
         }))
     }
 }
@@ -684,6 +882,39 @@ impl Validate for AdditionalPropertiesWithPatternsFalseValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            let mut unexpected = vec![];
+            for (property, value) in item {
+                let location = location.push(property.as_str());
+                let mut has_match = false;
+                for (re, node) in &self.patterns {
+                    if re.is_match(property).unwrap_or(false) {
+                        has_match = true;
+                        node.collect_errors(value, &location, errors, scratch);
+                    }
+                }
+                if !has_match {
+                    unexpected.push(property.clone());
+                }
+            }
+            if !unexpected.is_empty() {
+                errors.push(ValidationError::additional_properties(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    unexpected,
+                ));
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             // No properties are allowed, except ones defined in `patternProperties`
@@ -693,6 +924,11 @@ impl Validate for AdditionalPropertiesWithPatternsFalseValidator {
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .object_iter()
+            .all(|(property, value)| are_patterns_valid(&self.patterns, property, value, true))
+    }
 
     fn validate<'i>(
         &self,
@@ -732,24 +968,19 @@ impl Validate for AdditionalPropertiesWithPatternsFalseValidator {
                 for (pattern, node) in &self.patterns {
                     if pattern.is_match(property).unwrap_or(false) {
                         has_match = true;
-                        pattern_matched_props.push(property.clone());
                         output += node.apply_rooted(value, &path);
                     }
                 }
-                if !has_match {
+                if has_match {
+                    pattern_matched_props.push(property.clone());
+                } else {
                     unexpected.push(property.clone());
                 }
             }
-            if !pattern_matched_props.is_empty() {
-                output += OutputUnit::<Annotations<'_>>::annotations(
-                    self.pattern_keyword_path.clone(),
-                    location.into(),
-                    self.pattern_keyword_absolute_location.clone(),
-                    Value::from(pattern_matched_props).into(),
-                )
-                .into();
-            }
+            self.patterns_keyword
+                .annotate(&mut output, location, pattern_matched_props);
             let mut result: PartialApplication = output.into();
+            result.annotate(Evaluated::Properties(Vec::new()).into());
             if !unexpected.is_empty() {
                 result.mark_errored(
                     ValidationError::additional_properties(
@@ -798,6 +1029,8 @@ pub(crate) struct AdditionalPropertiesWithPatternsNotEmptyValidator<M: Propertie
     node: SchemaNode,
     properties: M,
     patterns: PatternedValidators,
+    properties_keyword: Sibling,
+    patterns_keyword: Sibling,
 }
 impl AdditionalPropertiesWithPatternsNotEmptyValidator<SmallValidatorsMap> {
     #[inline]
@@ -813,6 +1046,8 @@ impl AdditionalPropertiesWithPatternsNotEmptyValidator<SmallValidatorsMap> {
                 node: compiler::compile(&kctx, kctx.as_resource_ref(schema))?,
                 properties: compile_small_map(ctx, map)?,
                 patterns,
+                properties_keyword: Sibling::new(ctx, "properties"),
+                patterns_keyword: Sibling::new(ctx, "patternProperties"),
             },
         ))
     }
@@ -831,6 +1066,8 @@ impl AdditionalPropertiesWithPatternsNotEmptyValidator<BigValidatorsMap> {
                 node: compiler::compile(&kctx, kctx.as_resource_ref(schema))?,
                 properties: compile_big_map(ctx, map)?,
                 patterns,
+                properties_keyword: Sibling::new(ctx, "properties"),
+                patterns_keyword: Sibling::new(ctx, "patternProperties"),
             },
         ))
     }
@@ -870,6 +1107,34 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            for (property, value) in item {
+                let location = location.push(property.as_str());
+                let properties_node = self.properties.get_validator(property);
+                if let Some(node) = properties_node {
+                    node.collect_errors(value, &location, errors, scratch);
+                }
+                let mut has_match = false;
+                for (re, node) in &self.patterns {
+                    if re.is_match(property).unwrap_or(false) {
+                        has_match = true;
+                        node.collect_errors(value, &location, errors, scratch);
+                    }
+                }
+                if properties_node.is_none() && !has_match {
+                    self.node.collect_errors(value, &location, errors, scratch);
+                }
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             for (property, value) in item {
@@ -905,6 +1170,23 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        for (property, value) in instance.object_iter() {
+            if let Some(node) = self.properties.get_validator(property) {
+                if !node.is_valid_value(value)
+                    || !are_patterns_valid(&self.patterns, property, value, false)
+                {
+                    return false;
+                }
+            } else if !are_patterns_valid(&self.patterns, property, value, false)
+                || (!has_pattern_match(&self.patterns, property)
+                    && !self.node.is_valid_value(value))
+            {
+                return false;
+            }
+        }
+        true
+    }
 
     fn validate<'i>(
         &self,
@@ -941,18 +1223,22 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         if let Value::Object(item) = instance {
             let mut output = BasicOutput::default();
+            let mut matched_props = Vec::new();
+            let mut pattern_matched_props = Vec::new();
             let mut additional_matches = Vec::with_capacity(item.len());
             for (property, value) in item {
                 let path = location.push(property.as_str());
+                let mut has_match = false;
                 if let Some((_name, node)) = self.properties.get_key_validator(property) {
+                    matched_props.push(property.clone());
                     output += node.apply_rooted(value, &path);
                     for (pattern, node) in &self.patterns {
                         if pattern.is_match(property).unwrap_or(false) {
+                            has_match = true;
                             output += node.apply_rooted(value, &path);
                         }
                     }
                 } else {
-                    let mut has_match = false;
                     for (pattern, node) in &self.patterns {
                         if pattern.is_match(property).unwrap_or(false) {
                             has_match = true;
@@ -964,9 +1250,16 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
                         output += self.node.apply_rooted(value, &path);
                     }
                 }
+                if has_match {
+                    pattern_matched_props.push(property.clone());
+                }
             }
+            self.properties_keyword
+                .annotate(&mut output, location, matched_props);
+            self.patterns_keyword
+                .annotate(&mut output, location, pattern_matched_props);
             let mut result: PartialApplication = output.into();
-            result.annotate(Value::from(additional_matches).into());
+            result.annotate(Evaluated::Properties(additional_matches).into());
             result
         } else {
             PartialApplication::valid_empty()
@@ -1004,6 +1297,8 @@ pub(crate) struct AdditionalPropertiesWithPatternsNotEmptyFalseValidator<M: Prop
     properties: M,
     patterns: PatternedValidators,
     location: Location,
+    properties_keyword: Sibling,
+    patterns_keyword: Sibling,
 }
 impl AdditionalPropertiesWithPatternsNotEmptyFalseValidator<SmallValidatorsMap> {
     #[inline]
@@ -1017,6 +1312,8 @@ impl AdditionalPropertiesWithPatternsNotEmptyFalseValidator<SmallValidatorsMap>
                 properties: compile_small_map(ctx, map)?,
                 patterns,
                 location: ctx.location().join("additionalProperties"),
+                properties_keyword: Sibling::new(ctx, "properties"),
+                patterns_keyword: Sibling::new(ctx, "patternProperties"),
             },
         ))
     }
@@ -1033,6 +1330,8 @@ impl AdditionalPropertiesWithPatternsNotEmptyFalseValidator<BigValidatorsMap> {
                 properties: compile_big_map(ctx, map)?,
                 patterns,
                 location: ctx.location().join("additionalProperties"),
+                properties_keyword: Sibling::new(ctx, "properties"),
+                patterns_keyword: Sibling::new(ctx, "patternProperties"),
             },
         ))
     }
@@ -1109,6 +1408,20 @@ impl<M: PropertiesValidatorsMap> Validate
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        for (property, value) in instance.object_iter() {
+            if let Some(node) = self.properties.get_validator(property) {
+                if !node.is_valid_value(value)
+                    || !are_patterns_valid(&self.patterns, property, value, false)
+                {
+                    return false;
+                }
+            } else if !are_patterns_valid(&self.patterns, property, value, true) {
+                return false;
+            }
+        }
+        true
+    }
 
     fn validate<'i>(
         &self,
@@ -1150,19 +1463,23 @@ impl<M: PropertiesValidatorsMap> Validate
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         if let Value::Object(item) = instance {
             let mut output = BasicOutput::default();
+            let mut matched_props = Vec::new();
+            let mut pattern_matched_props = Vec::new();
             let mut unexpected = vec![];
             // No properties are allowed, except ones defined in `properties` or `patternProperties`
             for (property, value) in item {
                 let path = location.push(property.as_str());
+                let mut has_match = false;
                 if let Some((_name, node)) = self.properties.get_key_validator(property) {
+                    matched_props.push(property.clone());
                     output += node.apply_rooted(value, &path);
                     for (pattern, node) in &self.patterns {
                         if pattern.is_match(property).unwrap_or(false) {
+                            has_match = true;
                             output += node.apply_rooted(value, &path);
                         }
                     }
                 } else {
-                    let mut has_match = false;
                     for (pattern, node) in &self.patterns {
                         if pattern.is_match(property).unwrap_or(false) {
                             has_match = true;
@@ -1173,8 +1490,16 @@ impl<M: PropertiesValidatorsMap> Validate
                         unexpected.push(property.clone());
                     }
                 }
+                if has_match {
+                    pattern_matched_props.push(property.clone());
+                }
             }
+            self.properties_keyword
+                .annotate(&mut output, location, matched_props);
+            self.patterns_keyword
+                .annotate(&mut output, location, pattern_matched_props);
             let mut result: PartialApplication = output.into();
+            result.annotate(Evaluated::Properties(Vec::new()).into());
             if !unexpected.is_empty() {
                 result.mark_errored(
                     ValidationError::additional_properties(
@@ -1252,8 +1577,8 @@ pub(crate) fn compile<'a>(
         } else {
             Some(Err(ValidationError::single_type_error(
                 Location::new(),
-                ctx.location().clone(),
-                schema,
+                ctx.location().join("patternProperties"),
+                patterns,
                 PrimitiveType::Object,
             )))
         }
@@ -1290,7 +1615,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{error::ValidationErrorKind, tests_util};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -1696,4 +2021,28 @@ mod tests {
         tests_util::expect_errors(&schema, instance, expected);
         tests_util::assert_locations(&schema, instance, locations)
     }
+
+    #[test_case(&json!({"additionalProperties": false}), &json!({"a": 1, "b": 2}), &["a", "b"]; "false")]
+    #[test_case(&json!({"additionalProperties": false}), &json!({}), &[]; "false empty")]
+    #[test_case(&schema_1(), &json!({"foo": "a", "bar": 6, "faz": 1, "spam": 7, "haz": 1}), &["faz", "haz"]; "properties and patterns")]
+    #[test_case(&schema_1(), &json!({"barbaz": 6, "barspam": 7}), &[]; "properties and patterns matched")]
+    fn unexpected(schema: &Value, instance: &Value, expected: &[&str]) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let unexpected = |error: &crate::ValidationError<'_>| match &error.kind {
+            ValidationErrorKind::AdditionalProperties { unexpected } => unexpected.clone(),
+            kind => panic!("Unexpected kind: {kind:?}"),
+        };
+        let errors: Vec<_> = validator.iter_errors(instance).collect();
+        if expected.is_empty() {
+            assert!(errors.is_empty());
+            assert!(validator.validate(instance).is_ok());
+            return;
+        }
+        assert_eq!(errors.len(), 1);
+        assert_eq!(unexpected(&errors[0]), expected);
+        assert_eq!(errors[0].schema_path.as_str(), "/additionalProperties");
+        assert_eq!(errors[0].instance_path.as_str(), "");
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert!(expected.contains(&unexpected(&error)[0].as_str()));
+    }
 }
@@ -1,11 +1,12 @@
 use crate::{
     compiler,
     error::{ErrorIterator, ValidationError},
-    node::SchemaNode,
+    node::{DeferredChecks, SchemaNode},
     output::BasicOutput,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -19,11 +20,11 @@ impl AllOfValidator {
     #[inline]
     pub(crate) fn compile<'a>(
         ctx: &compiler::Context,
-        items: &'a [Value],
+        items: impl ExactSizeIterator<Item = (usize, &'a Value)>,
     ) -> CompilationResult<'a> {
         let ctx = ctx.new_at_location("allOf");
         let mut schemas = Vec::with_capacity(items.len());
-        for (idx, item) in items.iter().enumerate() {
+        for (idx, item) in items {
             let ctx = ctx.new_at_location(idx);
             let validators = compiler::compile(&ctx, ctx.as_resource_ref(item))?;
             schemas.push(validators)
@@ -43,9 +44,27 @@ impl Validate for AllOfValidator {
         Box::new(errors.into_iter())
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        for node in &self.schemas {
+            node.collect_errors(instance, location, errors, scratch);
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         self.schemas.iter().all(|n| n.is_valid(instance))
     }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        self.schemas.iter().all(|node| checks.check(node, instance))
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.schemas.iter().all(|n| n.is_valid_value(instance))
+    }
 
     fn validate<'i>(
         &self,
@@ -73,9 +92,13 @@ pub(crate) struct SingleValueAllOfValidator {
 
 impl SingleValueAllOfValidator {
     #[inline]
-    pub(crate) fn compile<'a>(ctx: &compiler::Context, schema: &'a Value) -> CompilationResult<'a> {
+    pub(crate) fn compile<'a>(
+        ctx: &compiler::Context,
+        idx: usize,
+        schema: &'a Value,
+    ) -> CompilationResult<'a> {
         let ctx = ctx.new_at_location("allOf");
-        let ctx = ctx.new_at_location(0);
+        let ctx = ctx.new_at_location(idx);
         let node = compiler::compile(&ctx, ctx.as_resource_ref(schema))?;
         Ok(Box::new(SingleValueAllOfValidator { node }))
     }
@@ -86,9 +109,26 @@ impl Validate for SingleValueAllOfValidator {
         self.node.iter_errors(instance, location)
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        self.node
+            .collect_errors(instance, location, errors, scratch);
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         self.node.is_valid(instance)
     }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        checks.check(&self.node, instance)
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.node.is_valid_value(instance)
+    }
 
     fn validate<'i>(
         &self,
@@ -103,6 +143,18 @@ impl Validate for SingleValueAllOfValidator {
     }
 }
 
+/// Compile `allOf` with the given branches and their indices.
+pub(crate) fn compile_branches<'a>(
+    ctx: &compiler::Context,
+    items: &[(usize, &'a Value)],
+) -> CompilationResult<'a> {
+    if let [(idx, value)] = items {
+        SingleValueAllOfValidator::compile(ctx, *idx, value)
+    } else {
+        AllOfValidator::compile(ctx, items.iter().copied())
+    }
+}
+
 #[inline]
 pub(crate) fn compile<'a>(
     ctx: &compiler::Context,
@@ -110,16 +162,15 @@ pub(crate) fn compile<'a>(
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
     if let Value::Array(items) = schema {
-        if items.len() == 1 {
-            let value = items.iter().next().expect("Vec is not empty");
-            Some(SingleValueAllOfValidator::compile(ctx, value))
+        if let [value] = items.as_slice() {
+            Some(SingleValueAllOfValidator::compile(ctx, 0, value))
         } else {
-            Some(AllOfValidator::compile(ctx, items))
+            Some(AllOfValidator::compile(ctx, items.iter().enumerate()))
         }
     } else {
         Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("allOf"),
             schema,
             PrimitiveType::Array,
         )))
@@ -1,14 +1,15 @@
 use crate::{
     compiler,
-    error::{error, no_error, ErrorIterator, ValidationError},
+    error::ValidationError,
     node::SchemaNode,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
 use serde_json::{Map, Value};
 
-use super::CompilationResult;
+use super::{BoxedValidator, CompilationResult};
 
 pub(crate) struct AnyOfValidator {
     schemas: Vec<SchemaNode>,
@@ -17,53 +18,78 @@ pub(crate) struct AnyOfValidator {
 
 impl AnyOfValidator {
     #[inline]
-    pub(crate) fn compile<'a>(ctx: &compiler::Context, schema: &'a Value) -> CompilationResult<'a> {
-        if let Value::Array(items) = schema {
-            let ctx = ctx.new_at_location("anyOf");
-            let mut schemas = Vec::with_capacity(items.len());
-            for (idx, item) in items.iter().enumerate() {
-                let ctx = ctx.new_at_location(idx);
-                let node = compiler::compile(&ctx, ctx.as_resource_ref(item))?;
-                schemas.push(node)
+    pub(crate) fn compile<'a>(
+        ctx: &compiler::Context,
+        items: impl ExactSizeIterator<Item = (usize, &'a Value)>,
+    ) -> CompilationResult<'a> {
+        let ctx = ctx.new_at_location("anyOf");
+        let mut schemas = Vec::with_capacity(items.len());
+        for (idx, item) in items {
+            let ctx = ctx.new_at_location(idx);
+            let node = compiler::compile(&ctx, ctx.as_resource_ref(item))?;
+            schemas.push(node)
+        }
+        Ok(AnyOfValidator::boxed(schemas, ctx.location().clone()))
+    }
+    pub(crate) fn boxed(schemas: Vec<SchemaNode>, location: Location) -> BoxedValidator {
+        Box::new(AnyOfValidator { schemas, location })
+    }
+
+    fn is_valid_at<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<bool, ValidationError<'i>> {
+        for node in &self.schemas {
+            if node.is_valid_at(instance, location)? {
+                return Ok(true);
             }
-            Ok(Box::new(AnyOfValidator {
-                schemas,
-                location: ctx.location().clone(),
-            }))
-        } else {
-            Err(ValidationError::single_type_error(
-                Location::new(),
-                ctx.location().clone(),
-                schema,
-                PrimitiveType::Array,
-            ))
         }
+        Ok(false)
     }
 }
 
 impl Validate for AnyOfValidator {
-    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        if self.is_valid(instance) {
-            no_error()
-        } else {
-            error(ValidationError::any_of(
-                self.location.clone(),
-                location.into(),
-                instance,
-            ))
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        for node in &self.schemas {
+            match scratch.is_valid(node, instance, location) {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(error) => {
+                    errors.push(error);
+                    return;
+                }
+            }
         }
+        errors.push(ValidationError::any_of(
+            self.location.clone(),
+            location.into(),
+            instance,
+        ));
     }
 
     fn is_valid(&self, instance: &Value) -> bool {
         self.schemas.iter().any(|s| s.is_valid(instance))
     }
+    fn any_of_branches(&self) -> &[SchemaNode] {
+        &self.schemas
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.schemas.iter().any(|s| s.is_valid_value(instance))
+    }
 
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.is_valid(instance) {
+        if self.is_valid_at(instance, location)? {
             Ok(())
         } else {
             Err(ValidationError::any_of(
@@ -99,7 +125,16 @@ pub(crate) fn compile<'a>(
     _: &'a Map<String, Value>,
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
-    Some(AnyOfValidator::compile(ctx, schema))
+    if let Value::Array(items) = schema {
+        Some(AnyOfValidator::compile(ctx, items.iter().enumerate()))
+    } else {
+        Some(Err(ValidationError::single_type_error(
+            Location::new(),
+            ctx.location().join("anyOf"),
+            schema,
+            PrimitiveType::Array,
+        )))
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,6 @@
 use crate::paths::{LazyLocation, Location};
 
-use crate::{error::ValidationError, keywords::CompilationResult, validator::Validate};
+use crate::{error::ValidationError, keywords::CompilationResult, validator::Validate, value};
 use serde_json::Value;
 
 pub(crate) struct FalseValidator {
@@ -16,6 +16,9 @@ impl Validate for FalseValidator {
     fn is_valid(&self, _: &Value) -> bool {
         false
     }
+    fn is_valid_value(&self, _: &dyn value::Value) -> bool {
+        false
+    }
 
     fn validate<'i>(
         &self,
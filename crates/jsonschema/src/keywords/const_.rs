@@ -3,7 +3,9 @@ use crate::{
     error::ValidationError,
     keywords::{helpers, CompilationResult},
     paths::Location,
+    primitive_type::PrimitiveType,
     validator::Validate,
+    value,
 };
 use serde_json::{Map, Number, Value};
 
@@ -48,6 +50,12 @@ impl Validate for ConstArrayValidator {
             false
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.array_len() == Some(self.value.len())
+            && value::array_iter(instance)
+                .zip(self.value.iter())
+                .all(|(item, expected)| value::equal(item, expected))
+    }
 }
 
 struct ConstBooleanValidator {
@@ -86,6 +94,9 @@ impl Validate for ConstBooleanValidator {
             false
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.as_bool() == Some(self.value)
+    }
 }
 
 struct ConstNullValidator {
@@ -117,6 +128,9 @@ impl Validate for ConstNullValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         instance.is_null()
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::Null
+    }
 }
 
 struct ConstNumberValidator {
@@ -131,9 +145,7 @@ impl ConstNumberValidator {
     pub(crate) fn compile(original_value: &Number, location: Location) -> CompilationResult {
         Ok(Box::new(ConstNumberValidator {
             original_value: original_value.clone(),
-            value: original_value
-                .as_f64()
-                .expect("A JSON number will always be representable as f64"),
+            value: helpers::to_f64(original_value),
             location,
         }))
     }
@@ -159,11 +171,21 @@ impl Validate for ConstNumberValidator {
 
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
-            (self.value - item.as_f64().expect("Always representable as f64")).abs() < f64::EPSILON
+            match item.as_f64() {
+                Some(item) => (self.value - item).abs() < f64::EPSILON,
+                // Beyond the range of `f64`, compare as written
+                None => *item == self.original_value,
+            }
         } else {
             false
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::Number
+            && instance
+                .as_f64()
+                .is_some_and(|item| (self.value - item).abs() < f64::EPSILON)
+    }
 }
 
 pub(crate) struct ConstObjectValidator {
@@ -205,6 +227,14 @@ impl Validate for ConstObjectValidator {
             false
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_len() == Some(self.value.len())
+            && self.value.iter().all(|(key, expected)| {
+                instance
+                    .object_get(key)
+                    .is_some_and(|item| value::equal(item, expected))
+            })
+    }
 }
 
 pub(crate) struct ConstStringValidator {
@@ -246,6 +276,9 @@ impl Validate for ConstStringValidator {
             false
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.as_str() == Some(self.value.as_str())
+    }
 }
 
 #[inline]
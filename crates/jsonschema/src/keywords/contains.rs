@@ -1,18 +1,94 @@
 use crate::{
     compiler,
-    error::ValidationError,
+    error::{ContainsMiss, ValidationError},
     keywords::CompilationResult,
     node::SchemaNode,
-    paths::LazyLocation,
-    validator::{PartialApplication, Validate},
-    Draft,
+    output::Evaluated,
+    paths::{LazyLocation, Location},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value, Draft,
 };
 use serde_json::{Map, Value};
 
-use super::helpers::map_get_u64;
+use super::helpers;
+
+/// How many of `items` are valid against `node`, counting no further than `limit`.
+///
+/// Fails if an item is nested too deeply to be checked.
+fn count_matches<'i>(
+    node: &SchemaNode,
+    items: &'i [Value],
+    location: &LazyLocation,
+    scratch: &mut ValidationScratch,
+    limit: u64,
+) -> Result<u64, ValidationError<'i>> {
+    let mut matches = 0;
+    for (idx, item) in items.iter().enumerate() {
+        if matches >= limit {
+            break;
+        }
+        if scratch.is_valid(node, item, &location.push(idx))? {
+            matches += 1;
+        }
+    }
+    Ok(matches)
+}
+
+/// The error for an array with `matches` valid items, too few or too many for the
+/// `(minContains, maxContains)` limits.
+fn error<'i>(
+    node: &SchemaNode,
+    explain: bool,
+    schema_location: Location,
+    instance: &'i Value,
+    location: &LazyLocation,
+    matches: u64,
+    limits: (Option<u64>, Option<u64>),
+) -> ValidationError<'i> {
+    let too_many = limits.1.is_some_and(|max| matches > max);
+    let nearest_miss = match instance {
+        Value::Array(items) if explain && !too_many => nearest_miss(node, items, location),
+        _ => None,
+    };
+    ValidationError::contains(
+        schema_location,
+        location.into(),
+        instance,
+        matches,
+        limits,
+        nearest_miss,
+    )
+}
+
+/// The item of `items` that came closest to being valid against `node`, see [`ContainsMiss`].
+fn nearest_miss(
+    node: &SchemaNode,
+    items: &[Value],
+    location: &LazyLocation,
+) -> Option<Box<ContainsMiss>> {
+    let mut nearest: Option<(usize, ContainsMiss)> = None;
+    for (index, item) in items.iter().enumerate() {
+        let errors: Vec<_> = node
+            .iter_errors(item, &location.push(index))
+            .map(ValidationError::into_owned)
+            .collect();
+        let Some(depth) = errors.iter().map(|error| error.schema_path.depth()).max() else {
+            continue;
+        };
+        let closer = nearest.as_ref().map_or(true, |(nearest_depth, miss)| {
+            depth > *nearest_depth || (depth == *nearest_depth && errors.len() < miss.errors.len())
+        });
+        if closer {
+            nearest = Some((depth, ContainsMiss { index, errors }));
+        }
+    }
+    nearest.map(|(_, miss)| Box::new(miss))
+}
 
 pub(crate) struct ContainsValidator {
     node: SchemaNode,
+    // Whether errors report the nearest miss
+    explain: bool,
 }
 
 impl ContainsValidator {
@@ -21,6 +97,7 @@ impl ContainsValidator {
         let ctx = ctx.new_at_location("contains");
         Ok(Box::new(ContainsValidator {
             node: compiler::compile(&ctx, ctx.as_resource_ref(schema))?,
+            explain: ctx.config().are_errors_verbose(),
         }))
     }
 }
@@ -33,6 +110,10 @@ impl Validate for ContainsValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.array_len().is_none()
+            || value::array_iter(instance).any(|i| self.node.is_valid_value(i))
+    }
 
     fn validate<'i>(
         &self,
@@ -43,16 +124,46 @@ impl Validate for ContainsValidator {
             if items.iter().any(|i| self.node.is_valid(i)) {
                 return Ok(());
             }
-            Err(ValidationError::contains(
+            Err(error(
+                &self.node,
+                self.explain,
                 self.node.location().clone(),
-                location.into(),
                 instance,
+                location,
+                0,
+                (None, None),
             ))
         } else {
             Ok(())
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            let matches = match count_matches(&self.node, items, location, scratch, 1) {
+                Ok(matches) => matches,
+                Err(error) => return errors.push(error),
+            };
+            if matches == 0 {
+                errors.push(error(
+                    &self.node,
+                    self.explain,
+                    self.node.location().clone(),
+                    instance,
+                    location,
+                    0,
+                    (None, None),
+                ));
+            }
+        }
+    }
+
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         if let Value::Array(items) = instance {
             let mut results = Vec::with_capacity(items.len());
@@ -68,21 +179,23 @@ impl Validate for ContainsValidator {
             let mut result: PartialApplication = results.into_iter().collect();
             if indices.is_empty() {
                 result.mark_errored(
-                    ValidationError::contains(
+                    error(
+                        &self.node,
+                        self.explain,
                         self.node.location().clone(),
-                        location.into(),
                         instance,
+                        location,
+                        0,
+                        (None, None),
                     )
                     .into(),
                 );
             } else {
-                result.annotate(Value::from(indices).into());
+                result.annotate(Evaluated::Indices(indices).into());
             }
             result
         } else {
-            let mut result = PartialApplication::valid_empty();
-            result.annotate(Value::Array(Vec::new()).into());
-            result
+            PartialApplication::valid_empty()
         }
     }
 }
@@ -93,6 +206,7 @@ impl Validate for ContainsValidator {
 pub(crate) struct MinContainsValidator {
     node: SchemaNode,
     min_contains: u64,
+    explain: bool,
 }
 
 impl MinContainsValidator {
@@ -106,6 +220,7 @@ impl MinContainsValidator {
         Ok(Box::new(MinContainsValidator {
             node: compiler::compile(&ctx, ctx.as_resource_ref(schema))?,
             min_contains,
+            explain: ctx.config().are_errors_verbose(),
         }))
     }
 }
@@ -131,10 +246,14 @@ impl Validate for MinContainsValidator {
                 }
             }
             if self.min_contains > 0 {
-                Err(ValidationError::contains(
+                Err(error(
+                    &self.node,
+                    self.explain,
                     self.node.location().clone(),
-                    location.into(),
                     instance,
+                    location,
+                    matches,
+                    (Some(self.min_contains), None),
                 ))
             } else {
                 Ok(())
@@ -144,6 +263,32 @@ impl Validate for MinContainsValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            let matches =
+                match count_matches(&self.node, items, location, scratch, self.min_contains) {
+                    Ok(matches) => matches,
+                    Err(error) => return errors.push(error),
+                };
+            if matches < self.min_contains {
+                errors.push(error(
+                    &self.node,
+                    self.explain,
+                    self.node.location().clone(),
+                    instance,
+                    location,
+                    matches,
+                    (Some(self.min_contains), None),
+                ));
+            }
+        }
+    }
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             let mut matches = 0;
@@ -164,6 +309,21 @@ impl Validate for MinContainsValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        if instance.array_len().is_none() {
+            return true;
+        }
+        let mut matches = 0;
+        for item in value::array_iter(instance) {
+            if self.node.is_valid_value(item) {
+                matches += 1;
+                if matches >= self.min_contains {
+                    return true;
+                }
+            }
+        }
+        self.min_contains == 0
+    }
 }
 
 /// `maxContains` validation. Used only if there is no `minContains` present.
@@ -172,6 +332,7 @@ impl Validate for MinContainsValidator {
 pub(crate) struct MaxContainsValidator {
     node: SchemaNode,
     max_contains: u64,
+    explain: bool,
 }
 
 impl MaxContainsValidator {
@@ -185,6 +346,7 @@ impl MaxContainsValidator {
         Ok(Box::new(MaxContainsValidator {
             node: compiler::compile(&ctx, ctx.as_resource_ref(schema))?,
             max_contains,
+            explain: ctx.config().are_errors_verbose(),
         }))
     }
 }
@@ -205,10 +367,14 @@ impl Validate for MaxContainsValidator {
                 {
                     matches += 1;
                     if matches > self.max_contains {
-                        return Err(ValidationError::contains(
+                        return Err(error(
+                            &self.node,
+                            self.explain,
                             self.node.location().clone(),
-                            location.into(),
                             instance,
+                            location,
+                            matches,
+                            (None, Some(self.max_contains)),
                         ));
                     }
                 }
@@ -216,10 +382,14 @@ impl Validate for MaxContainsValidator {
             if matches > 0 {
                 Ok(())
             } else {
-                Err(ValidationError::contains(
+                Err(error(
+                    &self.node,
+                    self.explain,
                     self.node.location().clone(),
-                    location.into(),
                     instance,
+                    location,
+                    0,
+                    (None, Some(self.max_contains)),
                 ))
             }
         } else {
@@ -227,6 +397,32 @@ impl Validate for MaxContainsValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            let limit = self.max_contains.saturating_add(1);
+            let matches = match count_matches(&self.node, items, location, scratch, limit) {
+                Ok(matches) => matches,
+                Err(error) => return errors.push(error),
+            };
+            if matches == 0 || matches > self.max_contains {
+                errors.push(error(
+                    &self.node,
+                    self.explain,
+                    self.node.location().clone(),
+                    instance,
+                    location,
+                    matches,
+                    (None, Some(self.max_contains)),
+                ));
+            }
+        }
+    }
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             let mut matches = 0;
@@ -247,6 +443,21 @@ impl Validate for MaxContainsValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        if instance.array_len().is_none() {
+            return true;
+        }
+        let mut matches = 0;
+        for item in value::array_iter(instance) {
+            if self.node.is_valid_value(item) {
+                matches += 1;
+                if matches > self.max_contains {
+                    return false;
+                }
+            }
+        }
+        matches != 0
+    }
 }
 
 /// `maxContains` & `minContains` validation combined.
@@ -258,6 +469,7 @@ pub(crate) struct MinMaxContainsValidator {
     node: SchemaNode,
     min_contains: u64,
     max_contains: u64,
+    explain: bool,
 }
 
 impl MinMaxContainsValidator {
@@ -272,6 +484,7 @@ impl MinMaxContainsValidator {
             node: compiler::compile(ctx, ctx.as_resource_ref(schema))?,
             min_contains,
             max_contains,
+            explain: ctx.config().are_errors_verbose(),
         }))
     }
 }
@@ -292,19 +505,27 @@ impl Validate for MinMaxContainsValidator {
                 {
                     matches += 1;
                     if matches > self.max_contains {
-                        return Err(ValidationError::contains(
+                        return Err(error(
+                            &self.node,
+                            self.explain,
                             self.node.location().join("maxContains"),
-                            location.into(),
                             instance,
+                            location,
+                            matches,
+                            (Some(self.min_contains), Some(self.max_contains)),
                         ));
                     }
                 }
             }
             if matches < self.min_contains {
-                Err(ValidationError::contains(
+                Err(error(
+                    &self.node,
+                    self.explain,
                     self.node.location().join("minContains"),
-                    location.into(),
                     instance,
+                    location,
+                    matches,
+                    (Some(self.min_contains), Some(self.max_contains)),
                 ))
             } else {
                 Ok(())
@@ -313,6 +534,42 @@ impl Validate for MinMaxContainsValidator {
             Ok(())
         }
     }
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            let limit = self.max_contains.saturating_add(1);
+            let matches = match count_matches(&self.node, items, location, scratch, limit) {
+                Ok(matches) => matches,
+                Err(error) => return errors.push(error),
+            };
+            if matches > self.max_contains {
+                errors.push(error(
+                    &self.node,
+                    self.explain,
+                    self.node.location().join("maxContains"),
+                    instance,
+                    location,
+                    matches,
+                    (Some(self.min_contains), Some(self.max_contains)),
+                ));
+            } else if matches < self.min_contains {
+                errors.push(error(
+                    &self.node,
+                    self.explain,
+                    self.node.location().join("minContains"),
+                    instance,
+                    location,
+                    matches,
+                    (Some(self.min_contains), Some(self.max_contains)),
+                ));
+            }
+        }
+    }
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             let mut matches = 0;
@@ -333,6 +590,21 @@ impl Validate for MinMaxContainsValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        if instance.array_len().is_none() {
+            return true;
+        }
+        let mut matches = 0;
+        for item in value::array_iter(instance) {
+            if self.node.is_valid_value(item) {
+                matches += 1;
+                if matches > self.max_contains {
+                    return false;
+                }
+            }
+        }
+        matches <= self.max_contains && matches >= self.min_contains
+    }
 }
 
 #[inline]
@@ -356,13 +628,19 @@ fn compile_contains<'a>(
     parent: &'a Map<String, Value>,
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
-    let min_contains = match map_get_u64(parent, ctx, "minContains").transpose() {
-        Ok(n) => n,
-        Err(err) => return Some(Err(err)),
+    let min_contains = match parent.get("minContains") {
+        Some(value) => match helpers::limit(ctx, "minContains", value) {
+            Ok(n) => Some(n),
+            Err(err) => return Some(Err(err)),
+        },
+        None => None,
     };
-    let max_contains = match map_get_u64(parent, ctx, "maxContains").transpose() {
-        Ok(n) => n,
-        Err(err) => return Some(Err(err)),
+    let max_contains = match parent.get("maxContains") {
+        Some(value) => match helpers::limit(ctx, "maxContains", value) {
+            Ok(n) => Some(n),
+            Err(err) => return Some(Err(err)),
+        },
+        None => None,
     };
 
     match (min_contains, max_contains) {
@@ -375,8 +653,9 @@ fn compile_contains<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
-    use serde_json::json;
+    use crate::{error::ValidationErrorKind, tests_util};
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[test]
     fn location() {
@@ -386,4 +665,67 @@ mod tests {
             "/contains",
         )
     }
+
+    #[test_case(&json!({"contains": {"minimum": 5}}), &json!([1, 2]), 0, None, None, "None of [1,2] are valid under the given schema"; "contains")]
+    #[test_case(&json!({"contains": {"minimum": 5}, "minContains": 3}), &json!([1, 5, 6]), 2, Some(3), None, "[1,5,6] has less than 3 items valid under the given schema (2 found)"; "min contains")]
+    #[test_case(&json!({"contains": {"minimum": 5}, "minContains": 2}), &json!([1]), 0, Some(2), None, "None of [1] are valid under the given schema"; "min contains none")]
+    #[test_case(&json!({"contains": {"minimum": 5}, "maxContains": 1}), &json!([5, 6, 7]), 2, None, Some(1), "[5,6,7] has more than 1 item valid under the given schema"; "max contains")]
+    #[test_case(&json!({"contains": {"minimum": 5}, "maxContains": 1}), &json!([1]), 0, None, Some(1), "None of [1] are valid under the given schema"; "max contains none")]
+    #[test_case(&json!({"contains": {"minimum": 5}, "minContains": 2, "maxContains": 3}), &json!([5, 1]), 1, Some(2), Some(3), "[5,1] has less than 2 items valid under the given schema (1 found)"; "min max contains")]
+    fn counts(
+        schema: &Value,
+        instance: &Value,
+        expected_matches: u64,
+        expected_min: Option<u64>,
+        expected_max: Option<u64>,
+        message: &str,
+    ) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        assert!(!validator.is_valid(instance));
+        let mut errors = vec![validator.validate(instance).expect_err("Should fail")];
+        errors.extend(validator.iter_errors(instance));
+        assert_eq!(errors.len(), 2);
+        for error in errors {
+            let ValidationErrorKind::Contains {
+                matches,
+                min_contains,
+                max_contains,
+                nearest_miss,
+            } = &error.kind
+            else {
+                panic!("Unexpected kind: {:?}", error.kind)
+            };
+            assert_eq!(*matches, expected_matches);
+            assert_eq!(*min_contains, expected_min);
+            assert_eq!(*max_contains, expected_max);
+            assert!(nearest_miss.is_none());
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test_case(&json!({"contains": {"required": ["a", "b"]}}), &json!([{}, {"a": 1}, {"b": 1}]), Some(1); "fewest errors")]
+    #[test_case(&json!({"contains": {"type": "object", "properties": {"a": {"minimum": 5}}}}), &json!([7, {"a": 1}]), Some(1); "deepest error")]
+    #[test_case(&json!({"contains": {"minimum": 5}, "minContains": 2}), &json!([6, 1, 2]), Some(1); "min contains")]
+    #[test_case(&json!({"contains": {"minimum": 5}, "maxContains": 1}), &json!([6, 7]), None; "too many")]
+    fn nearest_miss(schema: &Value, instance: &Value, expected: Option<usize>) {
+        let validator = crate::options()
+            .with_verbose_errors(true)
+            .build(schema)
+            .expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        let ValidationErrorKind::Contains { nearest_miss, .. } = &error.kind else {
+            panic!("Unexpected kind: {:?}", error.kind)
+        };
+        assert_eq!(nearest_miss.as_ref().map(|miss| miss.index), expected);
+        if let Some(miss) = nearest_miss {
+            assert!(!miss.errors.is_empty());
+            let prefix = format!("/{}", miss.index);
+            for error in &miss.errors {
+                assert!(error.instance_path.as_str().starts_with(&prefix));
+            }
+            assert!(error
+                .to_string()
+                .ends_with(&format!("(item {} is the nearest miss)", miss.index)));
+        }
+    }
 }
@@ -5,9 +5,12 @@ use crate::{
     content_media_type::ContentMediaTypeCheckType,
     error::ValidationError,
     keywords::CompilationResult,
+    lint,
+    output::Content,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::Validate,
+    validator::{PartialApplication, Validate},
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -15,6 +18,8 @@ use serde_json::{Map, Value};
 pub(crate) struct ContentMediaTypeValidator {
     media_type: String,
     func: ContentMediaTypeCheckType,
+    size_limit: Option<usize>,
+    capture: bool,
     location: Location,
 }
 
@@ -23,11 +28,15 @@ impl ContentMediaTypeValidator {
     pub(crate) fn compile(
         media_type: &str,
         func: ContentMediaTypeCheckType,
+        size_limit: Option<usize>,
+        capture: bool,
         location: Location,
     ) -> CompilationResult {
         Ok(Box::new(ContentMediaTypeValidator {
             media_type: media_type.to_string(),
             func,
+            size_limit,
+            capture,
             location,
         }))
     }
@@ -37,11 +46,16 @@ impl ContentMediaTypeValidator {
 impl Validate for ContentMediaTypeValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::String(item) = instance {
-            (self.func)(item)
+            !exceeds(self.size_limit, item) && (self.func)(item)
         } else {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.as_str().map_or(true, |item| {
+            !exceeds(self.size_limit, item) && (self.func)(item)
+        })
+    }
 
     fn validate<'i>(
         &self,
@@ -49,7 +63,14 @@ impl Validate for ContentMediaTypeValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::String(item) = instance {
-            if (self.func)(item) {
+            if let Some(limit) = self.size_limit.filter(|limit| item.len() > *limit) {
+                Err(ValidationError::content_size_limit(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    limit,
+                ))
+            } else if (self.func)(item) {
                 Ok(())
             } else {
                 Err(ValidationError::content_media_type(
@@ -63,12 +84,30 @@ impl Validate for ContentMediaTypeValidator {
             Ok(())
         }
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        match self.validate(instance, location) {
+            Ok(()) => {
+                let mut result = PartialApplication::valid_empty();
+                if let (true, Value::String(item)) = (self.capture, instance) {
+                    if let Some(content) = parse(&self.media_type, item) {
+                        result.annotate(content.into());
+                    }
+                }
+                result
+            }
+            Err(error) => PartialApplication::invalid_empty(vec![error.into()]),
+        }
+    }
 }
 
 /// Validator for `contentEncoding` keyword.
 pub(crate) struct ContentEncodingValidator {
     encoding: String,
     func: ContentEncodingCheckType,
+    size_limit: Option<usize>,
+    // Decodes valid content for capturing, `None` unless content capture is enabled
+    converter: Option<ContentEncodingConverterType>,
     location: Location,
 }
 
@@ -77,11 +116,15 @@ impl ContentEncodingValidator {
     pub(crate) fn compile(
         encoding: &str,
         func: ContentEncodingCheckType,
+        size_limit: Option<usize>,
+        converter: Option<ContentEncodingConverterType>,
         location: Location,
     ) -> CompilationResult {
         Ok(Box::new(ContentEncodingValidator {
             encoding: encoding.to_string(),
             func,
+            size_limit,
+            converter,
             location,
         }))
     }
@@ -90,11 +133,16 @@ impl ContentEncodingValidator {
 impl Validate for ContentEncodingValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::String(item) = instance {
-            (self.func)(item)
+            !exceeds(self.size_limit, item) && (self.func)(item)
         } else {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.as_str().map_or(true, |item| {
+            !exceeds(self.size_limit, item) && (self.func)(item)
+        })
+    }
 
     fn validate<'i>(
         &self,
@@ -102,7 +150,14 @@ impl Validate for ContentEncodingValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::String(item) = instance {
-            if (self.func)(item) {
+            if let Some(limit) = self.size_limit.filter(|limit| item.len() > *limit) {
+                Err(ValidationError::content_size_limit(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    limit,
+                ))
+            } else if (self.func)(item) {
                 Ok(())
             } else {
                 Err(ValidationError::content_encoding(
@@ -116,6 +171,21 @@ impl Validate for ContentEncodingValidator {
             Ok(())
         }
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        match self.validate(instance, location) {
+            Ok(()) => {
+                let mut result = PartialApplication::valid_empty();
+                if let (Some(converter), Value::String(item)) = (self.converter, instance) {
+                    if let Ok(Some(decoded)) = converter(item) {
+                        result.annotate(Content::Decoded(decoded).into());
+                    }
+                }
+                result
+            }
+            Err(error) => PartialApplication::invalid_empty(vec![error.into()]),
+        }
+    }
 }
 
 /// Combined validator for both `contentEncoding` and `contentMediaType` keywords.
@@ -124,6 +194,8 @@ pub(crate) struct ContentMediaTypeAndEncodingValidator {
     encoding: String,
     func: ContentMediaTypeCheckType,
     converter: ContentEncodingConverterType,
+    size_limit: Option<usize>,
+    capture: bool,
     location: Location,
 }
 
@@ -134,6 +206,8 @@ impl ContentMediaTypeAndEncodingValidator {
         encoding: &'a str,
         func: ContentMediaTypeCheckType,
         converter: ContentEncodingConverterType,
+        size_limit: Option<usize>,
+        capture: bool,
         location: Location,
     ) -> CompilationResult<'a> {
         Ok(Box::new(ContentMediaTypeAndEncodingValidator {
@@ -141,23 +215,82 @@ impl ContentMediaTypeAndEncodingValidator {
             encoding: encoding.to_string(),
             func,
             converter,
+            size_limit,
+            capture,
             location,
         }))
     }
+
+    fn is_valid_content(&self, item: &str) -> bool {
+        if exceeds(self.size_limit, item) {
+            return false;
+        }
+        match (self.converter)(item) {
+            Ok(None) | Err(_) => false,
+            Ok(Some(converted)) => !exceeds(self.size_limit, &converted) && (self.func)(&converted),
+        }
+    }
+
+    /// Decode `item` and check its media type, returning the decoded content.
+    fn decode<'i>(
+        &self,
+        instance: &'i Value,
+        item: &str,
+        location: &LazyLocation,
+    ) -> Result<String, ValidationError<'i>> {
+        if let Some(limit) = self.size_limit.filter(|limit| item.len() > *limit) {
+            return Err(ValidationError::content_size_limit(
+                self.location.join("contentEncoding"),
+                location.into(),
+                instance,
+                limit,
+            ));
+        }
+        match (self.converter)(item) {
+            Ok(None) => Err(ValidationError::content_encoding(
+                self.location.join("contentEncoding"),
+                location.into(),
+                instance,
+                &self.encoding,
+            )),
+            Ok(Some(converted)) => {
+                if let Some(limit) = self.size_limit.filter(|limit| converted.len() > *limit) {
+                    Err(ValidationError::content_size_limit(
+                        self.location.join("contentMediaType"),
+                        location.into(),
+                        instance,
+                        limit,
+                    ))
+                } else if (self.func)(&converted) {
+                    Ok(converted)
+                } else {
+                    Err(ValidationError::content_media_type(
+                        self.location.join("contentMediaType"),
+                        location.into(),
+                        instance,
+                        &self.media_type,
+                    ))
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
 }
 
 /// Decode the input value & check media type
 impl Validate for ContentMediaTypeAndEncodingValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::String(item) = instance {
-            match (self.converter)(item) {
-                Ok(None) | Err(_) => false,
-                Ok(Some(converted)) => (self.func)(&converted),
-            }
+            self.is_valid_content(item)
         } else {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .as_str()
+            .map_or(true, |item| self.is_valid_content(item))
+    }
 
     fn validate<'i>(
         &self,
@@ -165,33 +298,48 @@ impl Validate for ContentMediaTypeAndEncodingValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::String(item) = instance {
-            match (self.converter)(item) {
-                Ok(None) => Err(ValidationError::content_encoding(
-                    self.location.join("contentEncoding"),
-                    location.into(),
-                    instance,
-                    &self.encoding,
-                )),
-                Ok(Some(converted)) => {
-                    if (self.func)(&converted) {
-                        Ok(())
-                    } else {
-                        Err(ValidationError::content_media_type(
-                            self.location.join("contentMediaType"),
-                            location.into(),
-                            instance,
-                            &self.media_type,
-                        ))
+            self.decode(instance, item, location).map(|_| ())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        if let Value::String(item) = instance {
+            match self.decode(instance, item, location) {
+                Ok(decoded) => {
+                    let mut result = PartialApplication::valid_empty();
+                    if self.capture {
+                        let content =
+                            parse(&self.media_type, &decoded).unwrap_or(Content::Decoded(decoded));
+                        result.annotate(content.into());
                     }
+                    result
                 }
-                Err(e) => Err(e),
+                Err(error) => PartialApplication::invalid_empty(vec![error.into()]),
             }
         } else {
-            Ok(())
+            PartialApplication::valid_empty()
         }
     }
 }
 
+/// Whether `content` is longer than the limit from
+/// [`ValidationOptions::with_content_size_limit`](crate::ValidationOptions::with_content_size_limit).
+#[inline]
+fn exceeds(limit: Option<usize>, content: &str) -> bool {
+    limit.is_some_and(|limit| content.len() > limit)
+}
+
+/// Parse valid content for capturing, if its media type has a structured representation.
+fn parse(media_type: &str, content: &str) -> Option<Content> {
+    if media_type == "application/json" {
+        serde_json::from_str(content).ok().map(Content::Json)
+    } else {
+        None
+    }
+}
+
 #[inline]
 pub(crate) fn compile_media_type<'a>(
     ctx: &compiler::Context,
@@ -202,26 +350,47 @@ pub(crate) fn compile_media_type<'a>(
         Value::String(media_type) => {
             let func = match ctx.get_content_media_type_check(media_type.as_str()) {
                 Some(f) => f,
-                None => return None,
+                None => {
+                    // The encoding may still be checked on its own
+                    if !matches!(schema.get("contentEncoding"), Some(Value::String(_))) {
+                        ctx.record_warning(lint::unchecked_content(
+                            &ctx.location().join("contentMediaType"),
+                            format!(
+                                r#""{media_type}" is not a supported media type, so "contentMediaType" is not checked"#
+                            ),
+                        ));
+                    }
+                    return None;
+                }
             };
             if let Some(content_encoding) = schema.get("contentEncoding") {
                 match content_encoding {
                     Value::String(content_encoding) => {
                         let converter = match ctx.get_content_encoding_convert(content_encoding) {
                             Some(f) => f,
-                            None => return None,
+                            None => {
+                                ctx.record_warning(lint::unchecked_content(
+                                    &ctx.location().join("contentEncoding"),
+                                    format!(
+                                        r#""{content_encoding}" is not a supported content encoding, so neither "contentEncoding" nor "contentMediaType" is checked"#
+                                    ),
+                                ));
+                                return None;
+                            }
                         };
                         Some(ContentMediaTypeAndEncodingValidator::compile(
                             media_type,
                             content_encoding,
                             func,
                             converter,
+                            ctx.config().content_size_limit(),
+                            ctx.config().captures_content(),
                             ctx.location().clone(),
                         ))
                     }
                     _ => Some(Err(ValidationError::single_type_error(
                         Location::new(),
-                        ctx.location().clone(),
+                        ctx.location().join("contentEncoding"),
                         content_encoding,
                         PrimitiveType::String,
                     ))),
@@ -230,13 +399,15 @@ pub(crate) fn compile_media_type<'a>(
                 Some(ContentMediaTypeValidator::compile(
                     media_type,
                     func,
+                    ctx.config().content_size_limit(),
+                    ctx.config().captures_content(),
                     ctx.location().join("contentMediaType"),
                 ))
             }
         }
         _ => Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("contentMediaType"),
             subschema,
             PrimitiveType::String,
         ))),
@@ -249,34 +420,60 @@ pub(crate) fn compile_content_encoding<'a>(
     schema: &'a Map<String, Value>,
     subschema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
-    // Performed during media type validation
-    if schema.get("contentMediaType").is_some() {
-        // TODO. what if media type is not supported?
-        return None;
+    // Performed during media type validation, unless the media type is not supported
+    if let Some(Value::String(media_type)) = schema.get("contentMediaType") {
+        if ctx.get_content_media_type_check(media_type).is_some() {
+            return None;
+        }
     }
     match subschema {
         Value::String(content_encoding) => {
             let func = match ctx.get_content_encoding_check(content_encoding) {
                 Some(f) => f,
-                None => return None,
+                None => {
+                    let message = match schema.get("contentMediaType").and_then(Value::as_str) {
+                        Some(media_type) => format!(
+                            r#"Neither "{media_type}" media type nor "{content_encoding}" content encoding is supported, so neither is checked"#
+                        ),
+                        None => format!(
+                            r#""{content_encoding}" is not a supported content encoding, so "contentEncoding" is not checked"#
+                        ),
+                    };
+                    ctx.record_warning(lint::unchecked_content(
+                        &ctx.location().join("contentEncoding"),
+                        message,
+                    ));
+                    return None;
+                }
             };
             Some(ContentEncodingValidator::compile(
                 content_encoding,
                 func,
+                ctx.config().content_size_limit(),
+                ctx.config()
+                    .captures_content()
+                    .then(|| ctx.get_content_encoding_convert(content_encoding))
+                    .flatten(),
                 ctx.location().join("contentEncoding"),
             ))
         }
         _ => Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("contentEncoding"),
             subschema,
             PrimitiveType::String,
         ))),
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "content"))]
 mod tests {
+    use crate::{
+        error::{ValidationError, ValidationErrorKind},
+        output::Content,
+        paths::Location,
+        LintCode,
+    };
     use referencing::Draft;
     use serde_json::{json, Value};
     use test_case::test_case;
@@ -293,4 +490,150 @@ mod tests {
         let error = validator.validate(instance).expect_err("Should fail");
         assert_eq!(error.schema_path.as_str(), expected);
     }
+
+    #[test]
+    fn unknown_media_type_with_encoding() {
+        let schema =
+            json!({"contentMediaType": "application/unknown", "contentEncoding": "base64"});
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("e30=")));
+        let instance = json!("not base64!");
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/contentEncoding");
+        assert!(validator.warnings().is_empty());
+    }
+
+    #[test_case(&json!({"contentMediaType": "application/unknown"}), "/contentMediaType", r#""application/unknown" is not a supported media type, so "contentMediaType" is not checked"#)]
+    #[test_case(&json!({"contentEncoding": "base32"}), "/contentEncoding", r#""base32" is not a supported content encoding, so "contentEncoding" is not checked"#)]
+    #[test_case(&json!({"contentMediaType": "application/unknown", "contentEncoding": "base32"}), "/contentEncoding", r#"Neither "application/unknown" media type nor "base32" content encoding is supported, so neither is checked"#)]
+    #[test_case(&json!({"contentMediaType": "application/json", "contentEncoding": "base32"}), "/contentEncoding", r#""base32" is not a supported content encoding, so neither "contentEncoding" nor "contentMediaType" is checked"#)]
+    fn unchecked(schema: &Value, location: &str, message: &str) {
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .build(schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("anything")));
+        let warnings: Vec<_> = validator
+            .warnings()
+            .iter()
+            .map(|warning| {
+                (
+                    warning.code,
+                    warning.location.as_str(),
+                    warning.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(warnings, [(LintCode::UncheckedContent, location, message)]);
+    }
+
+    #[test]
+    fn unchecked_in_annotation_drafts() {
+        // Content keywords are annotations since Draft 2019-09
+        let validator = crate::options()
+            .with_draft(Draft::Draft202012)
+            .build(&json!({"contentMediaType": "application/unknown"}))
+            .expect("Invalid schema");
+        assert!(validator.warnings().is_empty());
+    }
+
+    // "[1,2,3]" is 7 bytes and "WzEsMiwzXQ==" is 12 bytes
+    #[test_case(&json!({"contentMediaType": "application/json"}), &json!("[1,2,3]"), 7, None)]
+    #[test_case(&json!({"contentMediaType": "application/json"}), &json!("[1,2,3]"), 6, Some("/contentMediaType"))]
+    #[test_case(&json!({"contentMediaType": "application/json"}), &json!("[1,2,"), 4, Some("/contentMediaType"); "checked before parsing")]
+    #[test_case(&json!({"contentEncoding": "base64"}), &json!("WzEsMiwzXQ=="), 12, None)]
+    #[test_case(&json!({"contentEncoding": "base64"}), &json!("WzEsMiwzXQ=="), 11, Some("/contentEncoding"))]
+    #[test_case(&json!({"contentMediaType": "application/json", "contentEncoding": "base64"}), &json!("WzEsMiwzXQ=="), 12, None)]
+    #[test_case(&json!({"contentMediaType": "application/json", "contentEncoding": "base64"}), &json!("WzEsMiwzXQ=="), 11, Some("/contentEncoding"); "checked before decoding")]
+    fn size_limit(schema: &Value, instance: &Value, limit: usize, expected: Option<&str>) {
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .with_content_size_limit(limit)
+            .build(schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected.is_none());
+        match expected {
+            Some(expected) => {
+                let error = validator.validate(instance).expect_err("Should fail");
+                assert_eq!(error.schema_path.as_str(), expected);
+                assert!(matches!(
+                    error.kind,
+                    ValidationErrorKind::ContentSizeLimit { limit: actual } if actual == limit
+                ));
+                assert_eq!(
+                    error.to_string(),
+                    format!("Content is longer than {limit} bytes")
+                );
+            }
+            None => assert!(validator.validate(instance).is_ok()),
+        }
+    }
+
+    #[test]
+    fn size_limit_after_decoding() {
+        fn repeat(instance: &str) -> Result<Option<String>, ValidationError<'static>> {
+            Ok(Some(format!("[{instance},{instance}]")))
+        }
+        let schema = json!({"contentMediaType": "application/json", "contentEncoding": "repeat"});
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .with_content_encoding("repeat", |_| true, repeat)
+            .with_content_size_limit(6)
+            .build(&schema)
+            .expect("Invalid schema");
+        // "[1,1]" fits, "[12,12]" does not
+        assert!(validator.is_valid(&json!("1")));
+        let instance = json!("12");
+        assert!(!validator.is_valid(&instance));
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/contentMediaType");
+    }
+
+    #[test]
+    fn unsupported_media_type_with_encoding() {
+        let schema = json!({"contentMediaType": "application/json", "contentEncoding": "base64"});
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .without_content_media_type_support("application/json")
+            .build(&schema)
+            .expect("Invalid schema");
+        // The encoding is still checked on its own
+        let instance = json!("{}");
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/contentEncoding");
+        assert!(validator.is_valid(&json!("e30=")));
+    }
+
+    #[test_case(&json!({"contentMediaType": "application/json"}), &json!("[1]"), Some(Content::Json(json!([1]))))]
+    #[test_case(&json!({"contentEncoding": "base64"}), &json!("aGVsbG8="), Some(Content::Decoded("hello".to_string())))]
+    #[test_case(&json!({"contentMediaType": "application/json", "contentEncoding": "base64"}), &json!("WzFd"), Some(Content::Json(json!([1]))))]
+    #[test_case(&json!({"contentMediaType": "application/unknown", "contentEncoding": "base64"}), &json!("aGVsbG8="), Some(Content::Decoded("hello".to_string())); "unsupported media type")]
+    #[test_case(&json!({"contentMediaType": "application/json", "contentEncoding": "base64"}), &json!("aGVsbG8="), None; "invalid")]
+    #[test_case(&json!({"contentMediaType": "application/json"}), &json!(1), None; "not a string")]
+    fn capture(schema: &Value, instance: &Value, expected: Option<Content>) {
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .with_content_capture(true)
+            .build(schema)
+            .expect("Invalid schema");
+        let output = validator.apply(instance).basic();
+        assert_eq!(
+            output.content().get(&Location::new()).copied(),
+            expected.as_ref()
+        );
+    }
+
+    #[test]
+    fn not_captured_by_default() {
+        let schema = json!({"contentEncoding": "base64"});
+        let validator = crate::options()
+            .with_draft(Draft::Draft7)
+            .build(&schema)
+            .expect("Invalid schema");
+        let output = validator.apply(&json!("aGVsbG8=")).basic();
+        assert!(output.content().is_empty());
+    }
 }
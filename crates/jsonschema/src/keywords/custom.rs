@@ -1,16 +1,20 @@
 use crate::{
+    compiler,
+    output::ErrorDescription,
     paths::{LazyLocation, Location},
-    validator::Validate,
+    validator::{PartialApplication, Validate, ValidationScratch},
     ValidationError,
 };
+use referencing::Draft;
 use serde_json::{Map, Value};
+use std::{any::Any, sync::Arc};
 
 pub(crate) struct CustomKeyword {
-    inner: Box<dyn Keyword>,
+    inner: Arc<dyn Keyword>,
 }
 
 impl CustomKeyword {
-    pub(crate) fn new(inner: Box<dyn Keyword>) -> Self {
+    pub(crate) fn new(inner: Arc<dyn Keyword>) -> Self {
         Self { inner }
     }
 }
@@ -27,6 +31,26 @@ impl Validate for CustomKeyword {
     fn is_valid(&self, instance: &Value) -> bool {
         self.inner.is_valid(instance)
     }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        let result = match scratch.context() {
+            Some(context) => self
+                .inner
+                .validate_with_context(instance, location, context),
+            None => self.inner.validate(instance, location),
+        };
+        errors.extend(result.err());
+    }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        self.inner.apply(instance, location)
+    }
 }
 
 /// Trait that allows implementing custom validation for keywords.
@@ -46,15 +70,217 @@ pub trait Keyword: Send + Sync {
     ///
     /// Could be potentilly faster than [`Keyword::validate`] method.
     fn is_valid(&self, instance: &Value) -> bool;
+    /// Validate instance with the context passed to
+    /// [`crate::Validator::validate_with_context`], e.g. with [`downcast_context`].
+    ///
+    /// The default implementation ignores the context and calls [`Keyword::validate`].
+    fn validate_with_context<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        context: &dyn Any,
+    ) -> Result<(), ValidationError<'i>> {
+        let _ = context;
+        self.validate(instance, location)
+    }
+    /// Apply the keyword to the instance and collect errors or annotations for
+    /// [`crate::Validator::apply`] output.
+    ///
+    /// Annotations may be any JSON value and are reported as is in the `annotations` of the
+    /// output unit located at the keyword. Keywords that evaluate properties or items should
+    /// follow the built-in applicators: an array of the evaluated property names like
+    /// `properties`, and the largest evaluated index, or `true` if all items are evaluated, like
+    /// `prefixItems`. `unevaluatedProperties` and `unevaluatedItems` do not read annotations, as
+    /// they are not collected during validation, but use [`Keyword::evaluated_properties`] and
+    /// [`Keyword::evaluated_items`], which should report the same properties and items.
+    ///
+    /// The default implementation reports errors from [`Keyword::validate`] and no annotations.
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        match self.validate(instance, location) {
+            Ok(()) => PartialApplication::valid_empty(),
+            Err(error) => PartialApplication::invalid_empty(vec![ErrorDescription::from(error)]),
+        }
+    }
+    /// Names of the instance properties successfully evaluated by this keyword.
+    ///
+    /// A sibling `unevaluatedProperties` keyword treats them as evaluated. By default, no
+    /// properties are evaluated.
+    fn evaluated_properties<'i>(&self, instance: &'i Value) -> Vec<&'i str> {
+        let _ = instance;
+        Vec::new()
+    }
+    /// Indexes of the instance items successfully evaluated by this keyword.
+    ///
+    /// A sibling `unevaluatedItems` keyword treats them as evaluated. By default, no items are
+    /// evaluated.
+    fn evaluated_items(&self, instance: &Value) -> Vec<usize> {
+        let _ = instance;
+        Vec::new()
+    }
+}
+
+/// Get the context passed to [`crate::Validator::validate_with_context`] as a `C`, to use in
+/// [`Keyword::validate_with_context`].
+///
+/// # Errors
+///
+/// Returns a custom error for `instance` at `location` if the context is not a `C`.
+///
+/// ```rust
+/// use jsonschema::{downcast_context, paths::LazyLocation};
+/// use serde_json::json;
+///
+/// let context: &dyn std::any::Any = &42_u64;
+/// let instance = json!(null);
+/// let location = LazyLocation::new();
+/// assert_eq!(downcast_context::<u64>(context, &instance, &location).ok(), Some(&42));
+/// let error = downcast_context::<String>(context, &instance, &location).expect_err("Not a string");
+/// assert_eq!(
+///     error.to_string(),
+///     "Expected a validation context of type `alloc::string::String`"
+/// );
+/// ```
+pub fn downcast_context<'c, 'i, C: Any>(
+    context: &'c dyn Any,
+    instance: &'i Value,
+    location: &LazyLocation,
+) -> Result<&'c C, ValidationError<'i>> {
+    context.downcast_ref::<C>().ok_or_else(|| {
+        ValidationError::custom(
+            Location::new(),
+            location.into(),
+            instance,
+            format!(
+                "Expected a validation context of type `{}`",
+                std::any::type_name::<C>()
+            ),
+        )
+    })
+}
+
+/// Initialize all custom keywords present in `parent`.
+pub(crate) fn compile_all<'a>(
+    ctx: &compiler::Context,
+    parent: &'a Map<String, Value>,
+) -> Result<Vec<Arc<dyn Keyword>>, ValidationError<'a>> {
+    let mut keywords = Vec::new();
+    for (keyword, value) in parent {
+        if let Some(factory) = ctx.get_keyword_factory(keyword) {
+            keywords.push(compile(ctx, factory.as_ref(), parent, keyword, value)?);
+        }
+    }
+    Ok(keywords)
+}
+
+/// Initialize the custom keyword `keyword` of `parent` with `factory`.
+///
+/// Errors that the factory does not locate are located at the keyword.
+/// Each keyword is initialized once, later calls share the same instance.
+pub(crate) fn compile<'a>(
+    ctx: &compiler::Context,
+    factory: &dyn KeywordFactory,
+    parent: &'a Map<String, Value>,
+    keyword: &str,
+    value: &'a Value,
+) -> Result<Arc<dyn Keyword>, ValidationError<'a>> {
+    let path = ctx.location().join(keyword);
+    if let Some(compiled) = ctx.get_custom_keyword(value, &path) {
+        return Ok(compiled);
+    }
+    let compiled: Arc<dyn Keyword> = factory
+        .init(ctx, parent, value, path.clone())
+        .map_err(|mut error| {
+            if error.instance_path.as_str().is_empty() {
+                error.instance_path = path.clone();
+            }
+            error
+        })?
+        .into();
+    ctx.store_custom_keyword(value, path, Arc::clone(&compiled));
+    Ok(compiled)
+}
+
+/// Mark properties evaluated by custom keywords.
+pub(crate) fn mark_evaluated_properties(
+    keywords: &[Arc<dyn Keyword>],
+    instance: &Value,
+    evaluated: &mut [bool],
+) {
+    if let Value::Object(obj) = instance {
+        for keyword in keywords {
+            for name in keyword.evaluated_properties(instance) {
+                if let Some(idx) = obj.keys().position(|property| property == name) {
+                    evaluated[idx] = true;
+                }
+            }
+        }
+    }
+}
+
+/// Mark items evaluated by custom keywords.
+pub(crate) fn mark_evaluated_items(
+    keywords: &[Arc<dyn Keyword>],
+    instance: &Value,
+    evaluated: &mut [bool],
+) {
+    if instance.is_array() {
+        for keyword in keywords {
+            for idx in keyword.evaluated_items(instance) {
+                if let Some(is_evaluated) = evaluated.get_mut(idx) {
+                    *is_evaluated = true;
+                }
+            }
+        }
+    }
+}
+
+/// Compilation state available to keyword factories registered with
+/// [`ValidationOptions::with_contextual_keyword`](crate::ValidationOptions::with_contextual_keyword).
+pub struct KeywordContext<'c> {
+    ctx: &'c compiler::Context<'c>,
+}
+
+impl KeywordContext<'_> {
+    /// Location of the schema containing the keyword, relative to the root of the schema.
+    ///
+    /// Combined with the parent schema passed to the factory, it allows reporting problems with
+    /// sibling keywords at their location.
+    #[must_use]
+    pub fn location(&self) -> &Location {
+        self.ctx.location()
+    }
+    /// The draft the schema containing the keyword is compiled with.
+    #[must_use]
+    pub fn draft(&self) -> Draft {
+        self.ctx.draft()
+    }
+    /// Resolve `reference` against the schema containing the keyword and return the value it
+    /// points to.
+    ///
+    /// The target may be any JSON value, like a table of allowed values, and is not compiled as
+    /// a schema. It has to be part of the schema itself or of a resource known to the registry,
+    /// as documents that are referenced only by custom keywords are not retrieved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference is invalid or does not point to an existing value.
+    pub fn resolve_value(&self, reference: &str) -> Result<&Value, referencing::Error> {
+        Ok(self.ctx.lookup(reference)?.contents())
+    }
 }
 
 pub(crate) trait KeywordFactory: Send + Sync {
     fn init<'a>(
         &self,
+        ctx: &compiler::Context,
         parent: &'a Map<String, Value>,
         schema: &'a Value,
         path: Location,
     ) -> Result<Box<dyn Keyword>, ValidationError<'a>>;
+    /// Drafts the keyword is active in, or `None` if it is active in all drafts.
+    fn drafts(&self) -> Option<&[Draft]> {
+        None
+    }
 }
 
 impl<F> KeywordFactory for F
@@ -69,6 +295,7 @@ where
 {
     fn init<'a>(
         &self,
+        _: &compiler::Context,
         parent: &'a Map<String, Value>,
         schema: &'a Value,
         path: Location,
@@ -76,3 +303,49 @@ where
         self(parent, schema, path)
     }
 }
+
+/// A factory that is passed a [`KeywordContext`].
+pub(crate) struct Contextual<F>(pub(crate) F);
+
+impl<F> KeywordFactory for Contextual<F>
+where
+    F: for<'a> Fn(
+            &KeywordContext<'_>,
+            &'a Map<String, Value>,
+            &'a Value,
+            Location,
+        ) -> Result<Box<dyn Keyword>, ValidationError<'a>>
+        + Send
+        + Sync,
+{
+    fn init<'a>(
+        &self,
+        ctx: &compiler::Context,
+        parent: &'a Map<String, Value>,
+        schema: &'a Value,
+        path: Location,
+    ) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+        (self.0)(&KeywordContext { ctx }, parent, schema, path)
+    }
+}
+
+/// A factory that is only active in schemas of some drafts.
+pub(crate) struct ForDrafts<F> {
+    pub(crate) drafts: Vec<Draft>,
+    pub(crate) factory: F,
+}
+
+impl<F: KeywordFactory> KeywordFactory for ForDrafts<F> {
+    fn init<'a>(
+        &self,
+        ctx: &compiler::Context,
+        parent: &'a Map<String, Value>,
+        schema: &'a Value,
+        path: Location,
+    ) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+        self.factory.init(ctx, parent, schema, path)
+    }
+    fn drafts(&self) -> Option<&[Draft]> {
+        Some(&self.drafts)
+    }
+}
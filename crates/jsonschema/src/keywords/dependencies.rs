@@ -3,14 +3,29 @@ use crate::{
     error::{no_error, ErrorIterator, ValidationError},
     keywords::{required, unique_items, CompilationResult},
     node::SchemaNode,
+    output::{AbsoluteLocation, BasicOutput, ErrorDescription, OutputUnit},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::Validate,
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
 use serde_json::{Map, Value};
+use std::collections::VecDeque;
 
 pub(crate) struct DependenciesValidator {
-    dependencies: Vec<(String, SchemaNode)>,
+    dependencies: Vec<(String, Dependency)>,
+    absolute: Option<AbsoluteLocation>,
+}
+
+/// What `dependencies` requires when its property is present.
+enum Dependency {
+    /// Other properties, each reported separately when missing.
+    Properties {
+        properties: Vec<Value>,
+        location: Location,
+    },
+    /// A subschema, whose errors are wrapped to name the property.
+    Schema(SchemaNode),
 }
 
 impl DependenciesValidator {
@@ -21,74 +36,237 @@ impl DependenciesValidator {
             let mut dependencies = Vec::with_capacity(map.len());
             for (key, subschema) in map {
                 let ctx = kctx.new_at_location(key.as_str());
-                let s =
-                    match subschema {
-                        Value::Array(_) => {
-                            let validators = vec![required::compile_with_path(
-                                subschema,
-                                kctx.location().clone(),
-                            )
-                            .expect("The required validator compilation does not return None")?];
-                            SchemaNode::from_array(&kctx, validators)
+                let dependency = match subschema {
+                    Value::Array(properties) => {
+                        if let Some(property) = properties.iter().find(|item| !item.is_string()) {
+                            return Err(ValidationError::single_type_error(
+                                Location::new(),
+                                ctx.location().clone(),
+                                property,
+                                PrimitiveType::String,
+                            ));
+                        }
+                        Dependency::Properties {
+                            properties: properties.clone(),
+                            location: ctx.location().clone(),
                         }
-                        _ => compiler::compile(&ctx, ctx.as_resource_ref(subschema))?,
-                    };
-                dependencies.push((key.clone(), s))
+                    }
+                    _ => {
+                        Dependency::Schema(compiler::compile(&ctx, ctx.as_resource_ref(subschema))?)
+                    }
+                };
+                dependencies.push((key.clone(), dependency))
             }
-            Ok(Box::new(DependenciesValidator { dependencies }))
+            Ok(Box::new(DependenciesValidator {
+                dependencies,
+                absolute: ctx.absolute_location(),
+            }))
         } else {
             Err(ValidationError::single_type_error(
                 Location::new(),
-                ctx.location().clone(),
+                ctx.location().join("dependencies"),
                 schema,
                 PrimitiveType::Object,
             ))
         }
     }
+
+    /// Dependencies of the properties present in `item`.
+    fn triggered<'s>(
+        &'s self,
+        item: &'s Map<String, Value>,
+    ) -> impl Iterator<Item = &'s (String, Dependency)> + 's {
+        self.dependencies
+            .iter()
+            .filter(|(property, _)| item.contains_key(property))
+    }
+}
+
+/// Properties of `properties` that are missing from `item`.
+fn missing<'a>(
+    properties: &'a [Value],
+    item: &'a Map<String, Value>,
+) -> impl Iterator<Item = &'a Value> + 'a {
+    properties
+        .iter()
+        .filter(move |property| !matches!(property, Value::String(name) if item.contains_key(name)))
 }
 
 impl Validate for DependenciesValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
-            self.dependencies
-                .iter()
-                .filter(|(property, _)| item.contains_key(property))
-                .all(move |(_, node)| node.is_valid(instance))
+            self.triggered(item)
+                .all(|(_, dependency)| match dependency {
+                    Dependency::Properties { properties, .. } => {
+                        missing(properties, item).next().is_none()
+                    }
+                    Dependency::Schema(node) => node.is_valid(instance),
+                })
         } else {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_len().is_none()
+            || self
+                .dependencies
+                .iter()
+                .filter(|(property, _)| instance.object_get(property).is_some())
+                .all(|(_, dependency)| match dependency {
+                    Dependency::Properties { properties, .. } => {
+                        properties.iter().all(|property| {
+                            property
+                                .as_str()
+                                .is_some_and(|name| instance.object_get(name).is_some())
+                        })
+                    }
+                    Dependency::Schema(node) => node.is_valid_value(instance),
+                })
+    }
 
-    #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
-            let errors: Vec<_> = self
-                .dependencies
-                .iter()
-                .filter(|(property, _)| item.contains_key(property))
-                .flat_map(move |(_, node)| node.iter_errors(instance, location))
-                .collect();
-            // TODO. custom error message for "required" case
+            let mut errors = Vec::new();
+            for (trigger, dependency) in self.triggered(item) {
+                match dependency {
+                    Dependency::Properties {
+                        properties,
+                        location: schema_location,
+                    } => {
+                        for property in missing(properties, item) {
+                            errors.push(ValidationError::missing_dependency(
+                                schema_location.clone(),
+                                location.into(),
+                                instance,
+                                trigger.clone(),
+                                property.clone(),
+                            ));
+                        }
+                    }
+                    Dependency::Schema(node) => errors.extend(
+                        node.iter_errors(instance, location)
+                            .map(|error| ValidationError::dependency_schema(trigger, error)),
+                    ),
+                }
+            }
             Box::new(errors.into_iter())
         } else {
             no_error()
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            for (trigger, dependency) in self.triggered(item) {
+                match dependency {
+                    Dependency::Properties {
+                        properties,
+                        location: schema_location,
+                    } => {
+                        for property in missing(properties, item) {
+                            errors.push(ValidationError::missing_dependency(
+                                schema_location.clone(),
+                                location.into(),
+                                instance,
+                                trigger.clone(),
+                                property.clone(),
+                            ));
+                        }
+                    }
+                    Dependency::Schema(node) => {
+                        let start = errors.len();
+                        node.collect_errors(instance, location, errors, scratch);
+                        let nested = errors.split_off(start);
+                        errors.extend(
+                            nested
+                                .into_iter()
+                                .map(|error| ValidationError::dependency_schema(trigger, error)),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            for (property, dependency) in &self.dependencies {
-                if item.contains_key(property) {
-                    dependency.validate(instance, location)?;
+            for (trigger, dependency) in self.triggered(item) {
+                match dependency {
+                    Dependency::Properties {
+                        properties,
+                        location: schema_location,
+                    } => {
+                        if let Some(property) = missing(properties, item).next() {
+                            return Err(ValidationError::missing_dependency(
+                                schema_location.clone(),
+                                location.into(),
+                                instance,
+                                trigger.clone(),
+                                property.clone(),
+                            ));
+                        }
+                    }
+                    Dependency::Schema(node) => node
+                        .validate(instance, location)
+                        .map_err(|error| ValidationError::dependency_schema(trigger, error))?,
                 }
             }
         }
         Ok(())
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        let Value::Object(item) = instance else {
+            return PartialApplication::valid_empty();
+        };
+        let mut result = BasicOutput::default();
+        // Not `triggered`, which would tie the subschemas to the lifetime of the instance
+        for (trigger, dependency) in self
+            .dependencies
+            .iter()
+            .filter(|(property, _)| item.contains_key(property))
+        {
+            match dependency {
+                Dependency::Properties {
+                    properties,
+                    location: schema_location,
+                } => {
+                    let errors: VecDeque<_> = missing(properties, item)
+                        .map(|property| {
+                            let error = ValidationError::missing_dependency(
+                                schema_location.clone(),
+                                location.into(),
+                                instance,
+                                trigger.clone(),
+                                property.clone(),
+                            );
+                            OutputUnit::error_at(
+                                self.absolute.as_ref(),
+                                schema_location.clone(),
+                                location.into(),
+                                ErrorDescription::from(error),
+                            )
+                        })
+                        .collect();
+                    if !errors.is_empty() {
+                        result += BasicOutput::Invalid(errors);
+                    }
+                }
+                Dependency::Schema(node) => result += node.apply_rooted(instance, location),
+            }
+        }
+        result.into()
+    }
 }
 
 pub(crate) struct DependentRequiredValidator {
@@ -132,7 +310,7 @@ impl DependentRequiredValidator {
         } else {
             Err(ValidationError::single_type_error(
                 Location::new(),
-                ctx.location().clone(),
+                ctx.location().join("dependentRequired"),
                 schema,
                 PrimitiveType::Object,
             ))
@@ -163,6 +341,14 @@ impl Validate for DependentRequiredValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_len().is_none()
+            || self
+                .dependencies
+                .iter()
+                .filter(|(property, _)| instance.object_get(property).is_some())
+                .all(|(_, node)| node.is_valid_value(instance))
+    }
 
     fn validate<'i>(
         &self,
@@ -200,7 +386,7 @@ impl DependentSchemasValidator {
         } else {
             Err(ValidationError::single_type_error(
                 Location::new(),
-                ctx.location().clone(),
+                ctx.location().join("dependentSchemas"),
                 schema,
                 PrimitiveType::Object,
             ))
@@ -231,6 +417,30 @@ impl Validate for DependentSchemasValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_len().is_none()
+            || self
+                .dependencies
+                .iter()
+                .filter(|(property, _)| instance.object_get(property).is_some())
+                .all(|(_, node)| node.is_valid_value(instance))
+    }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            for (property, dependency) in &self.dependencies {
+                if item.contains_key(property) {
+                    dependency.collect_errors(instance, location, errors, scratch);
+                }
+            }
+        }
+    }
 
     fn validate<'i>(
         &self,
@@ -276,13 +486,74 @@ pub(crate) fn compile_dependent_schemas<'a>(
 }
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{error::ValidationErrorKind, tests_util};
     use serde_json::{json, Value};
     use test_case::test_case;
 
-    #[test_case(&json!({"dependencies": {"bar": ["foo"]}}), &json!({"bar": 1}), "/dependencies")]
+    #[test_case(&json!({"dependencies": {"bar": ["foo"]}}), &json!({"bar": 1}), "/dependencies/bar")]
     #[test_case(&json!({"dependencies": {"bar": {"type": "string"}}}), &json!({"bar": 1}), "/dependencies/bar/type")]
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(&json!({"a": 1}), &[r#""b" is a required property when "a" is present"#, r#""c" is a required property when "a" is present"#]; "each missing")]
+    #[test_case(&json!({"a": 1, "c": 1}), &[r#""b" is a required property when "a" is present"#]; "one missing")]
+    #[test_case(&json!({"a": 1, "b": 1, "c": 1, "d": 1}), &[r#"1 is not of type "string""#]; "schema")]
+    #[test_case(&json!({"b": 1, "d": "x"}), &[]; "not triggered")]
+    fn messages(instance: &Value, expected: &[&str]) {
+        let schema = json!({"dependencies": {"a": ["b", "c"], "d": {"properties": {"d": {"type": "string"}}}}});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let messages: Vec<_> = validator
+            .iter_errors(instance)
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(messages, expected);
+        let mut errors = Vec::new();
+        validator.validate_into(instance, &mut errors);
+        let collected: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(collected, expected);
+        assert_eq!(validator.is_valid(instance), expected.is_empty());
+        assert_eq!(
+            validator
+                .validate(instance)
+                .err()
+                .map(|error| error.to_string()),
+            expected.first().map(ToString::to_string)
+        );
+    }
+
+    #[test]
+    fn missing_dependency_kind() {
+        let validator =
+            crate::validator_for(&json!({"dependencies": {"a": ["b"]}})).expect("Invalid schema");
+        let instance = json!({"a": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        let ValidationErrorKind::MissingDependency { trigger, property } = &error.kind else {
+            panic!("Unexpected kind: {:?}", error.kind)
+        };
+        assert_eq!(trigger, "a");
+        assert_eq!(property, &json!("b"));
+        assert_eq!(error.kind.missing_properties(), [json!("b")]);
+        assert_eq!(error.keyword(), "dependencies");
+    }
+
+    #[test]
+    fn dependency_schema_kind() {
+        let validator = crate::validator_for(&json!({"dependencies": {"a": {"required": ["b"]}}}))
+            .expect("Invalid schema");
+        let instance = json!({"a": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        let ValidationErrorKind::DependencySchema {
+            trigger,
+            error: nested,
+        } = &error.kind
+        else {
+            panic!("Unexpected kind: {:?}", error.kind)
+        };
+        assert_eq!(trigger, "a");
+        assert!(matches!(nested.kind, ValidationErrorKind::Required { .. }));
+        assert_eq!(error.schema_path.as_str(), "/dependencies/a/required");
+        assert_eq!(error.keyword(), "required");
+        assert_eq!(error.to_string(), r#""b" is a required property"#);
+    }
 }
@@ -0,0 +1,414 @@
+//! OpenAPI `discriminator`, which selects the `oneOf` or `anyOf` subschema an object is validated
+//! against by the value of one of its properties.
+use crate::{
+    compiler,
+    error::{error, no_error, ErrorIterator, ValidationError},
+    keywords::{any_of::AnyOfValidator, one_of::OneOfValidator, BoxedValidator, CompilationResult},
+    node::SchemaNode,
+    paths::{LazyLocation, Location},
+    primitive_type::PrimitiveType,
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
+};
+use ahash::AHashMap;
+use serde_json::Value;
+
+pub(crate) struct DiscriminatorValidator {
+    property: String,
+    /// Index of the subschema selected by each known value of `property`.
+    mapping: AHashMap<String, usize>,
+    schemas: Vec<SchemaNode>,
+    /// `oneOf` or `anyOf` over `schemas`, for instances without a known discriminator value.
+    fallback: BoxedValidator,
+    location: Location,
+}
+
+/// What the discriminator property of an instance selects.
+enum Selection<'i> {
+    Schema(usize),
+    Unknown(&'i str),
+    Missing,
+}
+
+impl DiscriminatorValidator {
+    fn select<'i>(&self, instance: &'i Value) -> Selection<'i> {
+        match instance.get(&self.property) {
+            Some(Value::String(value)) => self.select_value(value),
+            _ => Selection::Missing,
+        }
+    }
+    fn select_value<'i>(&self, value: &'i str) -> Selection<'i> {
+        match self.mapping.get(value) {
+            Some(idx) => Selection::Schema(*idx),
+            None => Selection::Unknown(value),
+        }
+    }
+    fn unknown<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        value: &str,
+    ) -> ValidationError<'i> {
+        ValidationError::unknown_discriminator(
+            self.location.clone(),
+            location.into(),
+            instance,
+            self.property.clone(),
+            value.to_string(),
+        )
+    }
+}
+
+impl Validate for DiscriminatorValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        match self.select(instance) {
+            Selection::Schema(idx) => self.schemas[idx].is_valid(instance),
+            Selection::Unknown(_) | Selection::Missing => self.fallback.is_valid(instance),
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        let selection = instance
+            .object_get(&self.property)
+            .and_then(value::Value::as_str)
+            .map_or(Selection::Missing, |value| self.select_value(value));
+        match selection {
+            Selection::Schema(idx) => self.schemas[idx].is_valid_value(instance),
+            Selection::Unknown(_) | Selection::Missing => self.fallback.is_valid_value(instance),
+        }
+    }
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        match self.select(instance) {
+            Selection::Schema(idx) => self.schemas[idx].validate(instance, location),
+            Selection::Unknown(value) => {
+                if self.fallback.is_valid(instance) {
+                    Ok(())
+                } else {
+                    Err(self.unknown(instance, location, value))
+                }
+            }
+            Selection::Missing => self.fallback.validate(instance, location),
+        }
+    }
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        match self.select(instance) {
+            Selection::Schema(idx) => self.schemas[idx].iter_errors(instance, location),
+            Selection::Unknown(_) => match self.validate(instance, location) {
+                Ok(()) => no_error(),
+                Err(err) => error(err),
+            },
+            Selection::Missing => self.fallback.iter_errors(instance, location),
+        }
+    }
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        match self.select(instance) {
+            Selection::Schema(idx) => {
+                self.schemas[idx].collect_errors(instance, location, errors, scratch);
+            }
+            Selection::Unknown(value) => {
+                if !self.fallback.is_valid(instance) {
+                    errors.push(self.unknown(instance, location, value));
+                }
+            }
+            Selection::Missing => self
+                .fallback
+                .collect_errors(instance, location, errors, scratch),
+        }
+    }
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        match self.select(instance) {
+            Selection::Schema(idx) => self.schemas[idx].apply_rooted(instance, location).into(),
+            Selection::Unknown(value) => {
+                if self.fallback.is_valid(instance) {
+                    self.fallback.apply(instance, location)
+                } else {
+                    PartialApplication::invalid_empty(vec![self
+                        .unknown(instance, location, value)
+                        .into()])
+                }
+            }
+            Selection::Missing => self.fallback.apply(instance, location),
+        }
+    }
+}
+
+/// Compile `keyword`, either `oneOf` or `anyOf`, with the `discriminator` of the same schema.
+pub(crate) fn compile<'a>(
+    ctx: &compiler::Context,
+    keyword: &'static str,
+    discriminator: &'a Value,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    let Value::Array(items) = schema else {
+        return Some(Err(ValidationError::single_type_error(
+            Location::new(),
+            ctx.location().join(keyword),
+            schema,
+            PrimitiveType::Array,
+        )));
+    };
+    let (property, mapping) = match parse(ctx, keyword, discriminator, items) {
+        Ok(parsed) => parsed,
+        Err(err) => return Some(Err(err)),
+    };
+    let ctx = ctx.new_at_location(keyword);
+    let mut schemas = Vec::with_capacity(items.len());
+    for (idx, item) in items.iter().enumerate() {
+        let ctx = ctx.new_at_location(idx);
+        match compiler::compile(&ctx, ctx.as_resource_ref(item)) {
+            Ok(node) => schemas.push(node),
+            Err(err) => return Some(Err(err)),
+        }
+    }
+    let location = ctx.location().clone();
+    let fallback = if keyword == "oneOf" {
+        OneOfValidator::boxed(schemas.clone(), location.clone())
+    } else {
+        AnyOfValidator::boxed(schemas.clone(), location.clone())
+    };
+    Some(Ok(Box::new(DiscriminatorValidator {
+        property,
+        mapping,
+        schemas,
+        fallback,
+        location,
+    })))
+}
+
+/// The discriminator property and the subschema selected by each of its values.
+///
+/// Subschemas that are a `$ref` are selected by the last segment of the reference, like `Dog`
+/// for `#/components/schemas/Dog`. Values in `mapping` are references, or such names.
+fn parse<'a>(
+    ctx: &compiler::Context,
+    keyword: &str,
+    discriminator: &'a Value,
+    items: &'a [Value],
+) -> Result<(String, AHashMap<String, usize>), ValidationError<'a>> {
+    let location = ctx.location().join("discriminator");
+    let Value::Object(object) = discriminator else {
+        return Err(ValidationError::single_type_error(
+            Location::new(),
+            location,
+            discriminator,
+            PrimitiveType::Object,
+        ));
+    };
+    let property = match object.get("propertyName") {
+        Some(Value::String(property)) => property.clone(),
+        Some(property) => {
+            return Err(ValidationError::single_type_error(
+                Location::new(),
+                location.join("propertyName"),
+                property,
+                PrimitiveType::String,
+            ))
+        }
+        None => {
+            return Err(ValidationError::required(
+                Location::new(),
+                location,
+                discriminator,
+                Value::String("propertyName".to_string()),
+            ))
+        }
+    };
+    let references: Vec<Option<&str>> = items
+        .iter()
+        .map(|item| item.get("$ref").and_then(Value::as_str))
+        .collect();
+    let mut mapping = AHashMap::new();
+    for (idx, reference) in references.iter().enumerate() {
+        if let Some(reference) = reference {
+            mapping
+                .entry(schema_name(reference).to_string())
+                .or_insert(idx);
+        }
+    }
+    match object.get("mapping") {
+        None => {}
+        Some(Value::Object(explicit)) => {
+            for (value, target) in explicit {
+                let location = location.join("mapping").join(value.as_str());
+                let Some(name) = target.as_str() else {
+                    return Err(ValidationError::single_type_error(
+                        Location::new(),
+                        location,
+                        target,
+                        PrimitiveType::String,
+                    ));
+                };
+                let Some(idx) = find(&references, name) else {
+                    return Err(ValidationError::custom(
+                        Location::new(),
+                        location,
+                        target,
+                        format!("`{name}` is not referenced by a subschema of `{keyword}`"),
+                    ));
+                };
+                mapping.insert(value.clone(), idx);
+            }
+        }
+        Some(explicit) => {
+            return Err(ValidationError::single_type_error(
+                Location::new(),
+                location.join("mapping"),
+                explicit,
+                PrimitiveType::Object,
+            ))
+        }
+    }
+    Ok((property, mapping))
+}
+
+/// Index of the subschema `target` refers to, either by reference or by name.
+fn find(references: &[Option<&str>], target: &str) -> Option<usize> {
+    references
+        .iter()
+        .position(|reference| *reference == Some(target))
+        .or_else(|| {
+            if target.contains(['/', '#']) {
+                None
+            } else {
+                references
+                    .iter()
+                    .position(|reference| reference.map(schema_name) == Some(target))
+            }
+        })
+}
+
+fn schema_name(reference: &str) -> &str {
+    reference.rsplit('/').next().unwrap_or(reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::ValidationErrorKind, Dialect};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn pets(discriminator: &Value) -> Value {
+        json!({
+            "oneOf": [
+                {"$ref": "#/components/schemas/Dog"},
+                {"$ref": "#/components/schemas/Cat"}
+            ],
+            "discriminator": discriminator,
+            "components": {
+                "schemas": {
+                    "Dog": {"required": ["petType", "bark"]},
+                    "Cat": {"required": ["petType", "meow"]}
+                }
+            }
+        })
+    }
+
+    fn validator(schema: &Value) -> crate::Validator {
+        crate::options()
+            .with_dialect(Dialect::OpenApi30)
+            .build(schema)
+            .expect("Invalid schema")
+    }
+
+    #[test_case(&json!({"propertyName": "petType"}), &json!({"petType": "Dog", "bark": 1}), true; "by name")]
+    #[test_case(&json!({"propertyName": "petType"}), &json!({"petType": "Dog", "meow": 1}), false; "by name invalid")]
+    #[test_case(&json!({"propertyName": "petType"}), &json!({"petType": "Dog", "bark": 1, "meow": 1}), true; "other subschemas ignored")]
+    #[test_case(&json!({"propertyName": "petType", "mapping": {"dog": "#/components/schemas/Dog"}}), &json!({"petType": "dog", "bark": 1}), true; "mapping reference")]
+    #[test_case(&json!({"propertyName": "petType", "mapping": {"kitty": "Cat"}}), &json!({"petType": "kitty", "bark": 1}), false; "mapping name")]
+    #[test_case(&json!({"propertyName": "petType", "mapping": {"Dog": "Cat"}}), &json!({"petType": "Dog", "meow": 1}), true; "mapping overrides name")]
+    #[test_case(&json!({"propertyName": "petType"}), &json!({"petType": "Bird", "bark": 1}), true; "unknown value valid")]
+    #[test_case(&json!({"propertyName": "petType"}), &json!({"petType": "Bird"}), false; "unknown value invalid")]
+    #[test_case(&json!({"propertyName": "petType"}), &json!({"bark": 1, "meow": 1}), false; "missing")]
+    #[test_case(&json!({"propertyName": "petType"}), &json!({"petType": 1, "bark": 1}), true; "not a string")]
+    fn instances(discriminator: &Value, instance: &Value, expected: bool) {
+        let validator = validator(&pets(discriminator));
+        assert_eq!(validator.is_valid(instance), expected);
+        assert_eq!(validator.validate(instance).is_ok(), expected);
+        assert_eq!(validator.iter_errors(instance).next().is_none(), expected);
+        assert_eq!(validator.apply(instance).basic().is_valid(), expected);
+    }
+
+    #[test]
+    fn selected_error() {
+        let validator = validator(&pets(&json!({"propertyName": "petType"})));
+        let instance = json!({"petType": "Cat", "bark": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/oneOf/1/$ref/required");
+        assert_eq!(error.to_string(), r#""meow" is a required property"#);
+    }
+
+    #[test]
+    fn unknown_error() {
+        let validator = validator(&pets(&json!({"propertyName": "petType"})));
+        let instance = json!({"petType": "Bird"});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert!(matches!(
+            &error.kind,
+            ValidationErrorKind::UnknownDiscriminator { property, value }
+                if property == "petType" && value == "Bird"
+        ));
+        assert_eq!(error.schema_path.as_str(), "/oneOf");
+        assert_eq!(
+            error.to_string(),
+            r#""Bird" is not a known value of the "petType" discriminator"#
+        );
+    }
+
+    #[test]
+    fn missing_error() {
+        let validator = validator(&pets(&json!({"propertyName": "petType"})));
+        let instance = json!({"bark": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert!(matches!(error.kind, ValidationErrorKind::OneOfNotValid));
+    }
+
+    #[test]
+    fn any_of() {
+        let schema = json!({
+            "anyOf": [
+                {"$ref": "#/definitions/Dog"},
+                {"$ref": "#/definitions/Cat"}
+            ],
+            "discriminator": {"propertyName": "petType"},
+            "definitions": {
+                "Dog": {"properties": {"bark": {"type": "boolean"}}},
+                "Cat": {"properties": {"bark": {"type": "string"}}}
+            }
+        });
+        let validator = validator(&schema);
+        assert!(validator.is_valid(&json!({"petType": "Dog", "bark": true})));
+        assert!(!validator.is_valid(&json!({"petType": "Dog", "bark": "yes"})));
+        assert!(validator.is_valid(&json!({"bark": "yes"})));
+    }
+
+    #[test]
+    fn ignored_without_dialect() {
+        let validator = crate::validator_for(&pets(&json!({"propertyName": "petType"})))
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!({"petType": "Dog", "bark": 1, "meow": 1})));
+    }
+
+    #[test_case(&json!("petType"), "/discriminator", r#""petType" is not of type "object""#; "not an object")]
+    #[test_case(&json!({}), "/discriminator", r#""propertyName" is a required property"#; "no property name")]
+    #[test_case(&json!({"propertyName": 1}), "/discriminator/propertyName", r#"1 is not of type "string""#; "property name not a string")]
+    #[test_case(&json!({"propertyName": "petType", "mapping": []}), "/discriminator/mapping", r#"[] is not of type "object""#; "mapping not an object")]
+    #[test_case(&json!({"propertyName": "petType", "mapping": {"bird": "#/components/schemas/Bird"}}), "/discriminator/mapping/bird", "`#/components/schemas/Bird` is not referenced by a subschema of `oneOf`"; "unknown reference")]
+    #[test_case(&json!({"propertyName": "petType", "mapping": {"bird": "Bird"}}), "/discriminator/mapping/bird", "`Bird` is not referenced by a subschema of `oneOf`"; "unknown name")]
+    fn compile_errors(discriminator: &Value, location: &str, message: &str) {
+        let error = crate::options()
+            .with_dialect(Dialect::OpenApi30)
+            .build(&pets(discriminator))
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), location);
+        assert_eq!(error.to_string(), message);
+    }
+}
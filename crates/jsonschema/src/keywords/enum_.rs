@@ -5,6 +5,7 @@ use crate::{
     paths::{LazyLocation, Location},
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
     validator::Validate,
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -65,6 +66,13 @@ impl Validate for EnumValidator {
             false
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        if self.types.contains_type(instance.primitive_type()) {
+            self.items.iter().any(|item| value::equal(instance, item))
+        } else {
+            false
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -110,6 +118,9 @@ impl Validate for SingleValueEnumValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         helpers::equal(&self.value, instance)
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        value::equal(instance, &self.value)
+    }
 }
 
 #[inline]
@@ -129,7 +140,7 @@ pub(crate) fn compile<'a>(
     } else {
         Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("enum"),
             schema,
             PrimitiveType::Array,
         )))
@@ -1,10 +1,11 @@
 use crate::{
     compiler,
-    error::ValidationError,
-    keywords::CompilationResult,
+    error::{InvalidExclusiveLimitReason, ValidationError},
+    keywords::{helpers, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
+    value,
 };
 use num_cmp::NumCmp;
 use serde_json::{Map, Value};
@@ -52,13 +53,16 @@ macro_rules! validate {
                     } else if let Some(item) = item.as_i64() {
                         NumCmp::num_lt(item, self.limit)
                     } else {
-                        let item = item.as_f64().expect("Always valid");
+                        let item = helpers::to_f64(item);
                         NumCmp::num_lt(item, self.limit)
                     }
                 } else {
                     true
                 }
             }
+            fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+                value::num_lt(instance, self.limit).unwrap_or(true)
+            }
         }
     };
 }
@@ -74,13 +78,16 @@ impl Validate for ExclusiveMaximumF64Validator {
             } else if let Some(item) = item.as_i64() {
                 NumCmp::num_lt(item, self.limit)
             } else {
-                let item = item.as_f64().expect("Always valid");
+                let item = helpers::to_f64(item);
                 NumCmp::num_lt(item, self.limit)
             }
         } else {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        value::num_lt(instance, self.limit).unwrap_or(true)
+    }
 
     fn validate<'i>(
         &self,
@@ -121,17 +128,24 @@ pub(crate) fn compile<'a>(
                 location,
             })))
         } else {
-            let limit = limit.as_f64().expect("Always valid");
+            let limit = helpers::to_f64(limit);
             Some(Ok(Box::new(ExclusiveMaximumF64Validator {
                 limit,
                 limit_val: (*schema).clone(),
                 location,
             })))
         }
+    } else if let Value::Bool(_) = schema {
+        Some(Err(ValidationError::invalid_exclusive_limit(
+            ctx.location().join("exclusiveMaximum"),
+            schema,
+            "exclusiveMaximum",
+            InvalidExclusiveLimitReason::BooleanAfterDraft4,
+        )))
     } else {
         Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("exclusiveMaximum"),
             schema,
             PrimitiveType::Number,
         )))
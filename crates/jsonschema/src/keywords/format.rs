@@ -1,26 +1,34 @@
 //! Validator for `format` keyword.
-use std::{
-    net::{Ipv4Addr, Ipv6Addr},
-    str::FromStr,
-    sync::Arc,
-};
+#[cfg(feature = "formats")]
+use std::str::FromStr;
+use std::sync::Arc;
 
+#[cfg(feature = "formats")]
 use email_address::EmailAddress;
-use fancy_regex::Regex;
+#[cfg(feature = "formats")]
 use once_cell::sync::Lazy;
 use serde_json::{Map, Value};
+#[cfg(feature = "formats")]
 use uuid_simd::{parse_hyphenated, Out};
 
 use crate::{
-    compiler, ecma,
+    compiler,
     error::ValidationError,
     keywords::CompilationResult,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
-    Draft,
+    value,
+};
+#[cfg(feature = "formats")]
+use crate::{
+    ecma,
+    error::{InvalidEmailReason, InvalidHostnameReason, InvalidIpAddressReason},
+    regex::Regex,
+    Draft, EmailStrictness,
 };
 
+#[cfg(feature = "formats")]
 static URI_TEMPLATE_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
         r#"^(?:(?:[^\x00-\x20"'<>%\\^`{|}]|%[0-9a-f]{2})|\{[+#./;?&=,!@|]?(?:[a-z0-9_]|%[0-9a-f]{2})+(?::[1-9][0-9]{0,3}|\*)?(?:,(?:[a-z0-9_]|%[0-9a-f]{2})+(?::[1-9][0-9]{0,3}|\*)?)*})*\z"#
@@ -28,6 +36,7 @@ static URI_TEMPLATE_RE: Lazy<Regex> = Lazy::new(|| {
     .expect("Is a valid regex")
 });
 
+#[cfg(feature = "formats")]
 fn is_valid_json_pointer(pointer: &str) -> bool {
     if pointer.is_empty() {
         // An empty string is a valid JSON Pointer
@@ -43,6 +52,7 @@ fn is_valid_json_pointer(pointer: &str) -> bool {
     is_valid_json_pointer_impl(chars)
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_relative_json_pointer(s: &str) -> bool {
     let mut chars = s.chars();
 
@@ -74,6 +84,7 @@ fn is_valid_relative_json_pointer(s: &str) -> bool {
     }
 }
 
+#[cfg(feature = "formats")]
 #[inline]
 fn is_valid_json_pointer_impl<I: Iterator<Item = char>>(chars: I) -> bool {
     let mut escaped = false;
@@ -93,6 +104,7 @@ fn is_valid_json_pointer_impl<I: Iterator<Item = char>>(chars: I) -> bool {
     !escaped
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_date(date: &str) -> bool {
     if date.len() != 10 {
         return false;
@@ -141,11 +153,13 @@ fn is_valid_date(date: &str) -> bool {
     }
 }
 
+#[cfg(feature = "formats")]
 #[inline]
 fn is_leap_year(year: u16) -> bool {
     (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
 }
 
+#[cfg(feature = "formats")]
 #[inline]
 fn parse_four_digits(bytes: &[u8]) -> Option<u16> {
     let value = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
@@ -159,6 +173,7 @@ fn parse_four_digits(bytes: &[u8]) -> Option<u16> {
     }
 }
 
+#[cfg(feature = "formats")]
 #[inline]
 fn parse_two_digits(bytes: &[u8]) -> Option<u8> {
     let value = u16::from_ne_bytes([bytes[0], bytes[1]]);
@@ -171,6 +186,7 @@ fn parse_two_digits(bytes: &[u8]) -> Option<u8> {
     }
 }
 
+#[cfg(feature = "formats")]
 macro_rules! handle_offset {
     ($sign:tt, $i:ident, $bytes:expr, $hour:expr, $minute:expr, $second:expr) => {{
         if $bytes.len() - $i != 6 {
@@ -215,6 +231,7 @@ macro_rules! handle_offset {
     }};
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_time(time: &str) -> bool {
     let bytes = time.as_bytes();
     let len = bytes.len();
@@ -274,6 +291,7 @@ fn is_valid_time(time: &str) -> bool {
     }
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_datetime(datetime: &str) -> bool {
     // Find the position of 'T' or 't' separator
     let t_pos = match datetime.bytes().position(|b| b == b'T' || b == b't') {
@@ -287,35 +305,69 @@ fn is_valid_datetime(datetime: &str) -> bool {
     is_valid_date(date_part) && is_valid_time(&time_part[1..])
 }
 
-fn is_valid_email_impl<F>(email: &str, is_valid_hostname_impl: F) -> bool
-where
-    F: Fn(&str) -> bool,
-{
-    if let Ok(parsed) = EmailAddress::from_str(email) {
-        let domain = parsed.domain();
-        if let Some(domain) = domain.strip_prefix('[').and_then(|d| d.strip_suffix(']')) {
-            if let Some(domain) = domain.strip_prefix("IPv6:") {
-                domain.parse::<Ipv6Addr>().is_ok()
-            } else {
-                domain.parse::<Ipv4Addr>().is_ok()
+/// Check `email` against RFC 5321, with the domain checked as a `hostname`, or as an
+/// `idn-hostname` if `idn` is set.
+#[cfg(feature = "formats")]
+fn check_email(
+    email: &str,
+    idn: bool,
+    strictness: EmailStrictness,
+) -> Result<(), InvalidEmailReason> {
+    let parsed = EmailAddress::from_str(email).map_err(|error| match error {
+        email_address::Error::MissingSeparator => InvalidEmailReason::MissingAt,
+        email_address::Error::LocalPartTooLong => InvalidEmailReason::LocalPartTooLong,
+        email_address::Error::DomainTooLong | email_address::Error::SubDomainTooLong => {
+            InvalidEmailReason::DomainTooLong
+        }
+        // The local part is checked first, so the domain is only wrong if the local part is not
+        _ => match email.rsplit_once('@') {
+            Some((local, _)) if EmailAddress::is_valid_local_part(local) => {
+                InvalidEmailReason::Domain
             }
+            _ => InvalidEmailReason::LocalPart,
+        },
+    })?;
+    let domain = parsed.domain();
+    if let Some(literal) = domain.strip_prefix('[').and_then(|d| d.strip_suffix(']')) {
+        let is_valid = if let Some(literal) = literal.strip_prefix("IPv6:") {
+            check_ipv6(literal).is_ok()
         } else {
-            is_valid_hostname_impl(domain)
+            check_ipv4(literal).is_ok()
+        };
+        if !is_valid {
+            return Err(InvalidEmailReason::Domain);
         }
     } else {
-        false
+        let check = if idn {
+            check_idn_hostname
+        } else {
+            check_hostname
+        };
+        check(domain).map_err(|reason| match reason {
+            InvalidHostnameReason::TooLong | InvalidHostnameReason::LabelTooLong => {
+                InvalidEmailReason::DomainTooLong
+            }
+            _ => InvalidEmailReason::Domain,
+        })?;
     }
+    if strictness == EmailStrictness::Deliverable {
+        if !parsed.display_part().is_empty() || parsed.local_part().starts_with('"') {
+            return Err(InvalidEmailReason::LocalPart);
+        }
+        if domain.starts_with('[') || !domain.contains('.') {
+            return Err(InvalidEmailReason::Domain);
+        }
+        if email.len() > 254 {
+            return Err(InvalidEmailReason::TooLong);
+        }
+    }
+    Ok(())
 }
 
-fn is_valid_email(email: &str) -> bool {
-    is_valid_email_impl(email, is_valid_hostname)
-}
-
-fn is_valid_idn_email(email: &str) -> bool {
-    is_valid_email_impl(email, is_valid_idn_hostname)
-}
-
-fn is_valid_hostname(hostname: &str) -> bool {
+/// Check `hostname` against the rules of RFC 1123: at most 253 characters in labels of 1 to 63
+/// ASCII letters, digits and hyphens that do not start or end with a hyphen.
+#[cfg(feature = "formats")]
+fn check_hostname(hostname: &str) -> Result<(), InvalidHostnameReason> {
     const VALID_CHARS: [bool; 256] = {
         let mut table = [false; 256];
         let mut i = 0;
@@ -325,57 +377,56 @@ fn is_valid_hostname(hostname: &str) -> bool {
         }
         table
     };
-    let hostname = hostname.as_bytes();
-    let len = hostname.len();
-    if len == 0 || len > 253 || hostname[len - 1] == b'.' {
-        return false;
+    if hostname.len() > 253 {
+        return Err(InvalidHostnameReason::TooLong);
     }
-
-    let mut label_start = 0;
-    let mut i = 0;
-
-    while i < len {
-        if hostname[i] == b'.' {
-            let label_len = i - label_start;
-            if label_len == 0
-                || label_len > 63
-                || hostname[label_start] == b'-'
-                || hostname[i - 1] == b'-'
-            {
-                return false;
+    for label in hostname.as_bytes().split(|byte| *byte == b'.') {
+        match label {
+            [] => return Err(InvalidHostnameReason::EmptyLabel),
+            _ if !label.iter().all(|byte| VALID_CHARS[*byte as usize]) => {
+                return Err(InvalidHostnameReason::InvalidCharacter)
             }
-            label_start = i + 1;
-        } else if !VALID_CHARS[hostname[i] as usize] {
-            return false;
+            _ if label.len() > 63 => return Err(InvalidHostnameReason::LabelTooLong),
+            [b'-', ..] | [.., b'-'] => return Err(InvalidHostnameReason::LabelHyphen),
+            _ => {}
         }
-        i += 1;
     }
-
-    let last_label_len = len - label_start;
-    !(last_label_len == 0
-        || last_label_len > 63
-        || hostname[label_start] == b'-'
-        || hostname[len - 1] == b'-')
+    Ok(())
 }
 
-fn is_valid_idn_hostname(hostname: &str) -> bool {
+/// Check `hostname` against the rules of RFC 5890 and RFC 5891, including the contextual rules
+/// of RFC 5892, and its ASCII form against the rules for `hostname`.
+#[cfg(feature = "formats")]
+fn check_idn_hostname(hostname: &str) -> Result<(), InvalidHostnameReason> {
     use idna::uts46::{AsciiDenyList, DnsLength, Hyphens, Uts46};
 
-    let Ok(ascii_hostname) = Uts46::new().to_ascii(
-        hostname.as_bytes(),
-        AsciiDenyList::STD3,
-        // Prohibit hyphens in the first, third, fourth, and last position in the label
-        Hyphens::Check,
-        DnsLength::Verify,
-    ) else {
-        return false;
-    };
+    // Hyphens and lengths are checked below, to report them as such
+    let ascii_hostname = Uts46::new()
+        .to_ascii(
+            hostname.as_bytes(),
+            AsciiDenyList::STD3,
+            Hyphens::Allow,
+            DnsLength::Ignore,
+        )
+        .map_err(|_| InvalidHostnameReason::InvalidCharacter)?;
+    check_hostname(&ascii_hostname)?;
     let (unicode_hostname, _) = Uts46::new().to_unicode(
         ascii_hostname.as_bytes(),
         AsciiDenyList::EMPTY,
         Hyphens::Allow,
     );
 
+    for label in unicode_hostname.split('.') {
+        // Prohibit hyphens in the first, third, fourth, and last position in the label
+        let mut chars = label.chars();
+        if label.starts_with('-')
+            || label.ends_with('-')
+            || (chars.nth(2) == Some('-') && chars.next() == Some('-'))
+        {
+            return Err(InvalidHostnameReason::LabelHyphen);
+        }
+    }
+
     let mut chars = unicode_hostname.chars().peekable();
     let mut previous = '\0';
     let mut has_katakana_middle_dot = false;
@@ -442,11 +493,13 @@ fn is_valid_idn_hostname(hostname: &str) -> bool {
                         | '\u{11D97}'
                 ) =>
             {
-                return false;
+                return Err(InvalidHostnameReason::InvalidContext);
             }
             // MIDDLE DOT
             // https://www.rfc-editor.org/rfc/rfc5892#appendix-A.3
-            '\u{00B7}' if previous != 'l' || chars.peek() != Some(&'l') => return false,
+            '\u{00B7}' if previous != 'l' || chars.peek() != Some(&'l') => {
+                return Err(InvalidHostnameReason::InvalidContext)
+            }
             // Greek KERAIA
             // https://www.rfc-editor.org/rfc/rfc5892#appendix-A.4
             '\u{0375}'
@@ -454,13 +507,13 @@ fn is_valid_idn_hostname(hostname: &str) -> bool {
                     .peek()
                     .map_or(false, |next| ('\u{0370}'..='\u{03FF}').contains(next)) =>
             {
-                return false
+                return Err(InvalidHostnameReason::InvalidContext)
             }
             // Hebrew GERESH and GERSHAYIM
             // https://www.rfc-editor.org/rfc/rfc5892#appendix-A.5
             // https://www.rfc-editor.org/rfc/rfc5892#appendix-A.6
             '\u{05F3}' | '\u{05F4}' if !('\u{0590}'..='\u{05FF}').contains(&previous) => {
-                return false
+                return Err(InvalidHostnameReason::InvalidContext)
             }
             // KATAKANA MIDDLE DOT
             '\u{30FB}' => has_katakana_middle_dot = true,
@@ -477,7 +530,9 @@ fn is_valid_idn_hostname(hostname: &str) -> bool {
             '\u{06F0}'..='\u{06F9}' => has_extended_arabic_indic_digits = true,
             // DISALLOWED
             '\u{0640}' | '\u{07FA}' | '\u{302E}' | '\u{302F}' | '\u{3031}' | '\u{3032}'
-            | '\u{3033}' | '\u{3034}' | '\u{3035}' | '\u{303B}' => return false,
+            | '\u{3033}' | '\u{3034}' | '\u{3035}' | '\u{303B}' => {
+                return Err(InvalidHostnameReason::InvalidCharacter)
+            }
 
             _ => {}
         }
@@ -487,12 +542,12 @@ fn is_valid_idn_hostname(hostname: &str) -> bool {
     if (has_katakana_middle_dot && !has_hiragana_katakana_han)
         || (has_arabic_indic_digits && has_extended_arabic_indic_digits)
     {
-        return false;
+        return Err(InvalidHostnameReason::InvalidContext);
     }
-
-    is_valid_hostname(&ascii_hostname)
+    Ok(())
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_duration(duration: &str) -> bool {
     let bytes = duration.as_bytes();
     let len = bytes.len();
@@ -574,45 +629,126 @@ fn is_valid_duration(duration: &str) -> bool {
     has_component && (!has_time || has_time_component)
 }
 
-fn is_valid_ipv4(ip: &str) -> bool {
-    Ipv4Addr::from_str(ip).is_ok()
+/// Check `ip` against the rules of RFC 2673: four decimal octets from 0 to 255 without leading
+/// zeros, separated by dots.
+#[cfg(feature = "formats")]
+fn check_ipv4(ip: &str) -> Result<(), InvalidIpAddressReason> {
+    if !ip.bytes().all(|byte| byte.is_ascii_digit() || byte == b'.') {
+        return Err(InvalidIpAddressReason::InvalidCharacter);
+    }
+    let mut octets = 0;
+    for octet in ip.split('.') {
+        octets += 1;
+        match octet.as_bytes() {
+            [] => return Err(InvalidIpAddressReason::OctetCount),
+            [b'0', _, ..] => return Err(InvalidIpAddressReason::LeadingZero),
+            digits if digits.len() > 3 || octet.parse::<u16>().map_or(true, |n| n > 255) => {
+                return Err(InvalidIpAddressReason::OctetOutOfRange)
+            }
+            _ => {}
+        }
+    }
+    if octets == 4 {
+        Ok(())
+    } else {
+        Err(InvalidIpAddressReason::OctetCount)
+    }
 }
 
-fn is_valid_ipv6(ip: &str) -> bool {
-    Ipv6Addr::from_str(ip).is_ok()
+/// Check `ip` against the rules of RFC 4291: eight groups of one to four hexadecimal digits
+/// separated by colons, where a single `::` stands for one or more groups of zeros and the last
+/// two groups may be written as an IPv4 address. Zone identifiers and prefix lengths are not part
+/// of an address.
+#[cfg(feature = "formats")]
+fn check_ipv6(ip: &str) -> Result<(), InvalidIpAddressReason> {
+    if ip.contains('%') {
+        return Err(InvalidIpAddressReason::ZoneIdentifier);
+    }
+    let (groups, ipv4) = if ip.contains('.') {
+        match ip.rfind(':') {
+            // Keep `::` before the IPv4 address, but not a single separating colon
+            Some(idx) if ip[..idx].ends_with(':') => (&ip[..=idx], Some(&ip[idx + 1..])),
+            Some(idx) => (&ip[..idx], Some(&ip[idx + 1..])),
+            None => ("", Some(ip)),
+        }
+    } else {
+        (ip, None)
+    };
+    let (head, tail) = match groups.split_once("::") {
+        Some((_, tail)) if tail.contains("::") => {
+            return Err(InvalidIpAddressReason::MultipleDoubleColons)
+        }
+        Some((head, tail)) => (head, Some(tail)),
+        None => (groups, None),
+    };
+    let mut count = 0;
+    for part in [Some(head), tail].into_iter().flatten() {
+        if part.is_empty() && (tail.is_some() || ipv4.is_some()) {
+            continue;
+        }
+        for group in part.split(':') {
+            count += 1;
+            if group.is_empty() {
+                return Err(InvalidIpAddressReason::EmptyGroup);
+            }
+            if !group.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                return Err(InvalidIpAddressReason::InvalidCharacter);
+            }
+            if group.len() > 4 {
+                return Err(InvalidIpAddressReason::GroupTooLong);
+            }
+        }
+    }
+    if let Some(ipv4) = ipv4 {
+        check_ipv4(ipv4)?;
+        count += 2;
+    }
+    match (tail, count) {
+        (None, 8) => Ok(()),
+        (Some(_), count) if count < 8 => Ok(()),
+        _ => Err(InvalidIpAddressReason::GroupCount),
+    }
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_iri(iri: &str) -> bool {
     referencing::Iri::parse(iri).is_ok()
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_iri_reference(iri_reference: &str) -> bool {
     referencing::IriRef::parse(iri_reference).is_ok()
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_uri(uri: &str) -> bool {
     referencing::Uri::parse(uri).is_ok()
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_uri_reference(uri_reference: &str) -> bool {
     referencing::UriRef::parse(uri_reference).is_ok()
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_regex(regex: &str) -> bool {
-    ecma::to_rust_regex(regex).is_ok()
+    ecma::check(regex).is_ok()
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_uri_template(uri_template: &str) -> bool {
     URI_TEMPLATE_RE
         .is_match(uri_template)
         .expect("Simple URI_TEMPLATE_RE pattern")
 }
 
+#[cfg(feature = "formats")]
 fn is_valid_uuid(uuid: &str) -> bool {
     let mut out = [0; 16];
     parse_hyphenated(uuid.as_bytes(), Out::from_mut(&mut out)).is_ok()
 }
 
+#[cfg(feature = "formats")]
 macro_rules! format_validators {
     ($(($validator:ident, $format:expr, $validation_fn:ident)),+ $(,)?) => {
         $(
@@ -635,6 +771,9 @@ macro_rules! format_validators {
                         true
                     }
                 }
+                fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+                    instance.as_str().map_or(true, $validation_fn)
+                }
 
                 fn validate<'i>(
                     &self,
@@ -657,16 +796,11 @@ macro_rules! format_validators {
         )+
     };
 }
+#[cfg(feature = "formats")]
 format_validators!(
     (DateValidator, "date", is_valid_date),
     (DateTimeValidator, "date-time", is_valid_datetime),
     (DurationValidator, "duration", is_valid_duration),
-    (EmailValidator, "email", is_valid_email),
-    (HostnameValidator, "hostname", is_valid_hostname),
-    (IdnEmailValidator, "idn-email", is_valid_idn_email),
-    (IdnHostnameValidator, "idn-hostname", is_valid_idn_hostname),
-    (IpV4Validator, "ipv4", is_valid_ipv4),
-    (IpV6Validator, "ipv6", is_valid_ipv6),
     (IriValidator, "iri", is_valid_iri),
     (
         IriReferenceValidator,
@@ -674,7 +808,6 @@ format_validators!(
         is_valid_iri_reference
     ),
     (JsonPointerValidator, "json-pointer", is_valid_json_pointer),
-    (RegexValidator, "regex", is_valid_regex),
     (
         RelativeJsonPointerValidator,
         "relative-json-pointer",
@@ -691,6 +824,234 @@ format_validators!(
     (UuidValidator, "uuid", is_valid_uuid),
 );
 
+/// Unlike other formats, invalid regular expressions are reported along with the reason.
+#[cfg(feature = "formats")]
+struct RegexValidator {
+    location: Location,
+}
+
+#[cfg(feature = "formats")]
+impl RegexValidator {
+    pub(crate) fn compile<'a>(ctx: &compiler::Context) -> CompilationResult<'a> {
+        let location = ctx.location().join("format");
+        Ok(Box::new(RegexValidator { location }))
+    }
+}
+
+#[cfg(feature = "formats")]
+impl Validate for RegexValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::String(item) = instance {
+            is_valid_regex(item)
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.as_str().map_or(true, is_valid_regex)
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if let Value::String(item) = instance {
+            if let Err(error) = ecma::check(item) {
+                return Err(ValidationError::invalid_regex(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    error,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `hostname` and `idn-hostname`, reporting which rule an invalid hostname breaks.
+#[cfg(feature = "formats")]
+struct HostnameValidator {
+    location: Location,
+    format: &'static str,
+    check: fn(&str) -> Result<(), InvalidHostnameReason>,
+}
+
+#[cfg(feature = "formats")]
+impl HostnameValidator {
+    pub(crate) fn compile<'a>(
+        ctx: &compiler::Context,
+        format: &'static str,
+        check: fn(&str) -> Result<(), InvalidHostnameReason>,
+    ) -> CompilationResult<'a> {
+        let location = ctx.location().join("format");
+        Ok(Box::new(HostnameValidator {
+            location,
+            format,
+            check,
+        }))
+    }
+}
+
+#[cfg(feature = "formats")]
+impl Validate for HostnameValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::String(item) = instance {
+            (self.check)(item).is_ok()
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .as_str()
+            .map_or(true, |item| (self.check)(item).is_ok())
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if let Value::String(item) = instance {
+            if let Err(reason) = (self.check)(item) {
+                return Err(ValidationError::invalid_hostname(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    self.format,
+                    reason,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `ipv4` and `ipv6`, reporting which rule an invalid address breaks.
+#[cfg(feature = "formats")]
+struct IpAddressValidator {
+    location: Location,
+    format: &'static str,
+    check: fn(&str) -> Result<(), InvalidIpAddressReason>,
+}
+
+#[cfg(feature = "formats")]
+impl IpAddressValidator {
+    pub(crate) fn compile<'a>(
+        ctx: &compiler::Context,
+        format: &'static str,
+        check: fn(&str) -> Result<(), InvalidIpAddressReason>,
+    ) -> CompilationResult<'a> {
+        let location = ctx.location().join("format");
+        Ok(Box::new(IpAddressValidator {
+            location,
+            format,
+            check,
+        }))
+    }
+}
+
+#[cfg(feature = "formats")]
+impl Validate for IpAddressValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::String(item) = instance {
+            (self.check)(item).is_ok()
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .as_str()
+            .map_or(true, |item| (self.check)(item).is_ok())
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if let Value::String(item) = instance {
+            if let Err(reason) = (self.check)(item) {
+                return Err(ValidationError::invalid_ip_address(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    self.format,
+                    reason,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `email` and `idn-email`, reporting which part of an invalid address is wrong.
+#[cfg(feature = "formats")]
+struct EmailValidator {
+    location: Location,
+    format: &'static str,
+    idn: bool,
+    strictness: EmailStrictness,
+}
+
+#[cfg(feature = "formats")]
+impl EmailValidator {
+    pub(crate) fn compile<'a>(
+        ctx: &compiler::Context,
+        format: &'static str,
+        idn: bool,
+    ) -> CompilationResult<'a> {
+        let location = ctx.location().join("format");
+        Ok(Box::new(EmailValidator {
+            location,
+            format,
+            idn,
+            strictness: ctx.config().email_strictness(),
+        }))
+    }
+    fn check(&self, email: &str) -> Result<(), InvalidEmailReason> {
+        check_email(email, self.idn, self.strictness)
+    }
+}
+
+#[cfg(feature = "formats")]
+impl Validate for EmailValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::String(item) = instance {
+            self.check(item).is_ok()
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .as_str()
+            .map_or(true, |item| self.check(item).is_ok())
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if let Value::String(item) = instance {
+            if let Err(reason) = self.check(item) {
+                return Err(ValidationError::invalid_email(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    self.format,
+                    reason,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 struct CustomFormatValidator {
     location: Location,
     format_name: String,
@@ -736,6 +1097,11 @@ impl Validate for CustomFormatValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .as_str()
+            .map_or(true, |item| self.check.is_valid(item))
+    }
 }
 
 pub(crate) trait Format: Send + Sync + 'static {
@@ -770,53 +1136,92 @@ pub(crate) fn compile<'a>(
                 func.clone(),
             ));
         }
-        let draft = ctx.draft();
-        match format.as_str() {
-            "date" => Some(DateValidator::compile(ctx)),
-            "date-time" => Some(DateTimeValidator::compile(ctx)),
-            "duration" if draft >= Draft::Draft201909 => Some(DurationValidator::compile(ctx)),
-            "email" => Some(EmailValidator::compile(ctx)),
-            "hostname" => Some(HostnameValidator::compile(ctx)),
-            "idn-email" => Some(IdnEmailValidator::compile(ctx)),
-            "idn-hostname" if draft >= Draft::Draft7 => Some(IdnHostnameValidator::compile(ctx)),
-            "ipv4" => Some(IpV4Validator::compile(ctx)),
-            "ipv6" => Some(IpV6Validator::compile(ctx)),
-            "iri" if draft >= Draft::Draft7 => Some(IriValidator::compile(ctx)),
-            "iri-reference" if draft >= Draft::Draft7 => Some(IriReferenceValidator::compile(ctx)),
-            "json-pointer" if draft >= Draft::Draft6 => Some(JsonPointerValidator::compile(ctx)),
-            "regex" => Some(RegexValidator::compile(ctx)),
-            "relative-json-pointer" if draft >= Draft::Draft7 => {
-                Some(RelativeJsonPointerValidator::compile(ctx))
-            }
-            "time" => Some(TimeValidator::compile(ctx)),
-            "uri" => Some(UriValidator::compile(ctx)),
-            "uri-reference" if draft >= Draft::Draft6 => Some(UriReferenceValidator::compile(ctx)),
-            "uri-template" if draft >= Draft::Draft6 => Some(UriTemplateValidator::compile(ctx)),
-            "uuid" if draft >= Draft::Draft201909 => Some(UuidValidator::compile(ctx)),
-            _ => {
-                if ctx.are_unknown_formats_ignored() {
-                    None
-                } else {
-                    return Some(Err(ValidationError::format(
-                        Location::new(),
-                        ctx.location().clone(),
-                        schema,
-                        "unknown format",
-                    )));
-                }
-            }
+        #[cfg(feature = "formats")]
+        if let Some(validator) = compile_builtin(ctx, format) {
+            return Some(validator);
+        }
+        #[cfg(not(feature = "formats"))]
+        if BUILTIN_FORMATS.contains(&format.as_str()) {
+            // Built-in checks are compiled out, the format is only an annotation
+            return None;
+        }
+        if ctx.are_unknown_formats_ignored() {
+            None
+        } else {
+            Some(Err(ValidationError::format(
+                Location::new(),
+                ctx.location().join("format"),
+                schema,
+                "unknown format",
+            )))
         }
     } else {
         Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("format"),
             schema,
             PrimitiveType::String,
         )))
     }
 }
 
-#[cfg(test)]
+#[cfg(feature = "formats")]
+fn compile_builtin<'a>(ctx: &compiler::Context, format: &str) -> Option<CompilationResult<'a>> {
+    let draft = ctx.draft();
+    match format {
+        "date" => Some(DateValidator::compile(ctx)),
+        "date-time" => Some(DateTimeValidator::compile(ctx)),
+        "duration" if draft >= Draft::Draft201909 => Some(DurationValidator::compile(ctx)),
+        "email" => Some(EmailValidator::compile(ctx, "email", false)),
+        "hostname" => Some(HostnameValidator::compile(ctx, "hostname", check_hostname)),
+        "idn-email" => Some(EmailValidator::compile(ctx, "idn-email", true)),
+        "idn-hostname" if draft >= Draft::Draft7 => Some(HostnameValidator::compile(
+            ctx,
+            "idn-hostname",
+            check_idn_hostname,
+        )),
+        "ipv4" => Some(IpAddressValidator::compile(ctx, "ipv4", check_ipv4)),
+        "ipv6" => Some(IpAddressValidator::compile(ctx, "ipv6", check_ipv6)),
+        "iri" if draft >= Draft::Draft7 => Some(IriValidator::compile(ctx)),
+        "iri-reference" if draft >= Draft::Draft7 => Some(IriReferenceValidator::compile(ctx)),
+        "json-pointer" if draft >= Draft::Draft6 => Some(JsonPointerValidator::compile(ctx)),
+        "regex" => Some(RegexValidator::compile(ctx)),
+        "relative-json-pointer" if draft >= Draft::Draft7 => {
+            Some(RelativeJsonPointerValidator::compile(ctx))
+        }
+        "time" => Some(TimeValidator::compile(ctx)),
+        "uri" => Some(UriValidator::compile(ctx)),
+        "uri-reference" if draft >= Draft::Draft6 => Some(UriReferenceValidator::compile(ctx)),
+        "uri-template" if draft >= Draft::Draft6 => Some(UriTemplateValidator::compile(ctx)),
+        "uuid" if draft >= Draft::Draft201909 => Some(UuidValidator::compile(ctx)),
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "formats"))]
+const BUILTIN_FORMATS: &[&str] = &[
+    "date",
+    "date-time",
+    "duration",
+    "email",
+    "hostname",
+    "idn-email",
+    "idn-hostname",
+    "ipv4",
+    "ipv6",
+    "iri",
+    "iri-reference",
+    "json-pointer",
+    "regex",
+    "relative-json-pointer",
+    "time",
+    "uri",
+    "uri-reference",
+    "uri-template",
+    "uuid",
+];
+
+#[cfg(all(test, feature = "formats"))]
 mod tests {
     use referencing::Draft;
     use serde_json::json;
@@ -864,6 +1269,51 @@ mod tests {
         assert!(validator.is_valid(&instance))
     }
 
+    #[test_case("(?=a)b", true; "lookahead")]
+    #[test_case("(?P<name>a)", false; "rust named group")]
+    fn regex(pattern: &str, expected: bool) {
+        let validator = crate::options()
+            .should_validate_formats(true)
+            .build(&json!({"format": "regex"}))
+            .expect("Valid schema");
+        assert_eq!(validator.is_valid(&json!(pattern)), expected);
+        assert_eq!(validator.validate(&json!(pattern)).is_ok(), expected);
+    }
+
+    #[test]
+    fn regex_error() {
+        let validator = crate::validator_for(&json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {"pattern": {"format": "regex"}}
+        }))
+        .expect("Valid schema");
+        let instance = json!({"pattern": "^[a-z]+(?P<digits>[0-9]*)$"});
+        let error = validator.validate(&instance).expect_err("Invalid regex");
+        assert!(matches!(
+            &error.kind,
+            ValidationErrorKind::InvalidRegex { message, offset: 7 }
+                if message == "named groups are written as (?<name>...)"
+        ));
+        assert_eq!(error.schema_path.as_str(), "/properties/pattern/format");
+        assert_eq!(
+            error.to_string(),
+            r#""^[a-z]+(?P<digits>[0-9]*)$" is not a "regex": named groups are written as (?<name>...) at offset 7"#
+        );
+    }
+
+    #[test_case(Draft::Draft4, false)]
+    #[test_case(Draft::Draft7, false)]
+    #[test_case(Draft::Draft201909, true)]
+    #[test_case(Draft::Draft202012, true)]
+    fn regex_annotation(draft: Draft, expected: bool) {
+        // Formats only annotate by default in Draft 2019-09 and later
+        let validator = crate::options()
+            .with_draft(draft)
+            .build(&json!({"format": "regex"}))
+            .expect("Valid schema");
+        assert_eq!(validator.is_valid(&json!("(")), expected);
+    }
+
     #[test]
     fn location() {
         tests_util::assert_schema_location(&json!({"format": "date"}), &json!("bla"), "/format")
@@ -1012,6 +1462,83 @@ mod tests {
         assert_eq!(validator.is_valid(&json!(input)), expected);
     }
 
+    #[test_case("192.168.0.1", None; "valid")]
+    #[test_case("127.0.0.0.1", Some(InvalidIpAddressReason::OctetCount); "too many components")]
+    #[test_case("256.256.256.256", Some(InvalidIpAddressReason::OctetOutOfRange); "out of range")]
+    #[test_case("127.0", Some(InvalidIpAddressReason::OctetCount); "not enough components")]
+    #[test_case("0x7f000001", Some(InvalidIpAddressReason::InvalidCharacter); "hex")]
+    #[test_case("192.168.1.0/24", Some(InvalidIpAddressReason::InvalidCharacter); "netmask")]
+    #[test_case("087.10.0.1", Some(InvalidIpAddressReason::LeadingZero); "leading zeros")]
+    #[test_case("87.10.0.1", None; "without leading zero")]
+    #[test_case("1২7.0.0.1", Some(InvalidIpAddressReason::InvalidCharacter); "non-ascii digit")]
+    #[test_case("1..2.3", Some(InvalidIpAddressReason::OctetCount); "empty octet")]
+    #[test_case("1000.1.1.1", Some(InvalidIpAddressReason::OctetOutOfRange); "four digits")]
+    fn ipv4_reason(input: &str, expected: Option<InvalidIpAddressReason>) {
+        assert_eq!(check_ipv4(input).err(), expected);
+    }
+
+    #[test_case("::1", None; "valid")]
+    #[test_case("12345::", Some(InvalidIpAddressReason::GroupTooLong); "out of range values")]
+    #[test_case("::abef", None; "trailing 4 hex symbols")]
+    #[test_case("::abcef", Some(InvalidIpAddressReason::GroupTooLong); "trailing 5 hex symbols")]
+    #[test_case("1:1:1:1:1:1:1:1:1:1:1:1:1:1:1:1", Some(InvalidIpAddressReason::GroupCount); "too many components")]
+    #[test_case("::laptop", Some(InvalidIpAddressReason::InvalidCharacter); "illegal characters")]
+    #[test_case("::", None; "no digits")]
+    #[test_case("::42:ff:1", None; "leading colons")]
+    #[test_case("d6::", None; "trailing colons")]
+    #[test_case(":2:3:4:5:6:7:8", Some(InvalidIpAddressReason::EmptyGroup); "missing leading octet")]
+    #[test_case("1:2:3:4:5:6:7:", Some(InvalidIpAddressReason::EmptyGroup); "missing trailing octet")]
+    #[test_case(":2:3:4::8", Some(InvalidIpAddressReason::EmptyGroup); "missing leading octet with omitted octets later")]
+    #[test_case("1:d6::42", None; "double colons in the middle")]
+    #[test_case("1::d6::42", Some(InvalidIpAddressReason::MultipleDoubleColons); "two double colons")]
+    #[test_case("1::d6:192.168.0.1", None; "mixed format")]
+    #[test_case("1:2::192.168.0.1", None; "mixed format with double colons between the sections")]
+    #[test_case("1::2:192.168.256.1", Some(InvalidIpAddressReason::OctetOutOfRange); "mixed format with octet out of range")]
+    #[test_case("1::2:192.168.ff.1", Some(InvalidIpAddressReason::InvalidCharacter); "mixed format with a hex octet")]
+    #[test_case("::ffff:192.168.0.1", None; "ipv4-mapped")]
+    #[test_case("::192.168.0.1", None; "ipv4 after double colons")]
+    #[test_case("1:2:3:4:5:::8", Some(InvalidIpAddressReason::EmptyGroup); "triple colons")]
+    #[test_case("1:2:3:4:5:6:7:8", None; "8 octets")]
+    #[test_case("1:2:3:4:5:6:7::", None; "7 octets and double colons")]
+    #[test_case("1:2:3:4:5:6:7:8::", Some(InvalidIpAddressReason::GroupCount); "8 octets and double colons")]
+    #[test_case("1:2:3:4:5:6:7", Some(InvalidIpAddressReason::GroupCount); "insufficient octets")]
+    #[test_case("1", Some(InvalidIpAddressReason::GroupCount); "no colons")]
+    #[test_case("127.0.0.1", Some(InvalidIpAddressReason::GroupCount); "ipv4 is not ipv6")]
+    #[test_case("1:2:3:4:1.2.3", Some(InvalidIpAddressReason::OctetCount); "ipv4 segment with 3 octets")]
+    #[test_case("  ::1", Some(InvalidIpAddressReason::InvalidCharacter); "leading whitespace")]
+    #[test_case("::1  ", Some(InvalidIpAddressReason::InvalidCharacter); "trailing whitespace")]
+    #[test_case("fe80::/64", Some(InvalidIpAddressReason::InvalidCharacter); "netmask")]
+    #[test_case("fe80::a%eth1", Some(InvalidIpAddressReason::ZoneIdentifier); "zone id")]
+    #[test_case("1000:1000:1000:1000:1000:1000:255.255.255.255", None; "long valid")]
+    #[test_case("100:100:100:100:100:100:255.255.255.255.255", Some(InvalidIpAddressReason::OctetCount); "long invalid with 5 octets")]
+    #[test_case("100:100:100:100:100:100:100:255.255.255.255", Some(InvalidIpAddressReason::GroupCount); "long invalid with too many groups")]
+    #[test_case("1:2:3:4:5:6:7:৪", Some(InvalidIpAddressReason::InvalidCharacter); "non-ascii digit")]
+    #[test_case("1:2::192.16৪.0.1", Some(InvalidIpAddressReason::InvalidCharacter); "non-ascii digit in the ipv4 portion")]
+    fn ipv6_reason(input: &str, expected: Option<InvalidIpAddressReason>) {
+        assert_eq!(check_ipv6(input).err(), expected);
+    }
+
+    #[test]
+    fn ip_address_error() {
+        let validator = crate::options()
+            .should_validate_formats(true)
+            .build(&json!({"format": "ipv6"}))
+            .expect("Valid schema");
+        let instance = json!("fe80::1%eth0");
+        let error = validator.validate(&instance).expect_err("Invalid address");
+        assert!(matches!(
+            &error.kind,
+            ValidationErrorKind::InvalidIpAddress {
+                format,
+                reason: InvalidIpAddressReason::ZoneIdentifier
+            } if format == "ipv6"
+        ));
+        assert_eq!(
+            error.to_string(),
+            r#""fe80::1%eth0" is not a "ipv6": zone identifiers are not allowed"#
+        );
+    }
+
     #[test]
     fn test_is_valid_datetime_panic() {
         is_valid_datetime("2624-04-25t23:14:04-256\x112");
@@ -1024,7 +1551,7 @@ mod tests {
     #[test_case("۱۲۳.example.com" ; "valid extended arabic-indic digits")]
     #[test_case("ひらがな・カタカナ.com" ; "valid katakana middle dot")]
     fn test_valid_idn_hostnames(input: &str) {
-        assert!(is_valid_idn_hostname(input));
+        assert!(check_idn_hostname(input).is_ok());
     }
 
     #[test_case("ex--ample.com" ; "hyphen at 3rd & 4th position")]
@@ -1043,12 +1570,118 @@ mod tests {
     #[test_case("١۲٣.example.com" ; "mixed arabic-indic digits")]
     #[test_case("example・com" ; "katakana middle dot without hiragana/katakana/han")]
     fn test_invalid_idn_hostnames(input: &str) {
-        assert!(!is_valid_idn_hostname(input));
+        assert!(check_idn_hostname(input).is_err());
     }
 
     #[test]
     fn test_invalid_hostname() {
-        assert!(!is_valid_hostname("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.com"));
+        assert!(check_hostname("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.com").is_err());
+    }
+
+    #[test_case("example.com", None; "valid")]
+    #[test_case(&format!("{}.com", "a.".repeat(127)), Some(InvalidHostnameReason::TooLong); "too long")]
+    #[test_case("example..com", Some(InvalidHostnameReason::EmptyLabel); "empty label")]
+    #[test_case("example.com.", Some(InvalidHostnameReason::EmptyLabel); "trailing dot")]
+    #[test_case(&format!("{}.com", "a".repeat(64)), Some(InvalidHostnameReason::LabelTooLong); "label too long")]
+    #[test_case("example-.com", Some(InvalidHostnameReason::LabelHyphen); "trailing hyphen")]
+    #[test_case("exa_mple.com", Some(InvalidHostnameReason::InvalidCharacter); "underscore")]
+    #[test_case("münchen.de", Some(InvalidHostnameReason::InvalidCharacter); "non-ascii")]
+    fn hostname_reason(input: &str, expected: Option<InvalidHostnameReason>) {
+        assert_eq!(check_hostname(input).err(), expected);
+    }
+
+    #[test_case("münchen.de", None; "valid")]
+    #[test_case(&format!("{}ü.com", "a".repeat(60)), Some(InvalidHostnameReason::LabelTooLong); "label too long as punycode")]
+    #[test_case("ex--ample.com", Some(InvalidHostnameReason::LabelHyphen); "hyphens in 3rd and 4th position")]
+    #[test_case("-ü.com", Some(InvalidHostnameReason::LabelHyphen); "leading hyphen")]
+    #[test_case("xn--example.com", Some(InvalidHostnameReason::InvalidCharacter); "invalid punycode")]
+    #[test_case("a·l", Some(InvalidHostnameReason::InvalidContext); "middle dot")]
+    fn idn_hostname_reason(input: &str, expected: Option<InvalidHostnameReason>) {
+        assert_eq!(check_idn_hostname(input).err(), expected);
+    }
+
+    #[test]
+    fn hostname_error() {
+        let validator = crate::validator_for(&json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "format": "idn-hostname"
+        }))
+        .expect("Valid schema");
+        let instance = json!("ab--c.com");
+        let error = validator.validate(&instance).expect_err("Invalid hostname");
+        assert!(matches!(
+            &error.kind,
+            ValidationErrorKind::InvalidHostname {
+                format,
+                reason: InvalidHostnameReason::LabelHyphen
+            } if format == "idn-hostname"
+        ));
+        assert_eq!(
+            error.to_string(),
+            r#""ab--c.com" is not a "idn-hostname": a label has a hyphen where it is not allowed"#
+        );
+    }
+
+    #[test_case("john@example.com", None, None; "valid")]
+    #[test_case(r#""john doe"@example.com"#, None, Some(InvalidEmailReason::LocalPart); "quoted local part")]
+    #[test_case("john@[127.0.0.1]", None, Some(InvalidEmailReason::Domain); "address literal")]
+    #[test_case("john@localhost", None, Some(InvalidEmailReason::Domain); "single label domain")]
+    #[test_case(&format!("{}@{}com", "a".repeat(64), "b.".repeat(94)), None, Some(InvalidEmailReason::TooLong); "too long")]
+    #[test_case("john.example.com", Some(InvalidEmailReason::MissingAt), Some(InvalidEmailReason::MissingAt); "missing at")]
+    #[test_case("john..doe@example.com", Some(InvalidEmailReason::LocalPart), Some(InvalidEmailReason::LocalPart); "consecutive dots")]
+    #[test_case(&format!("{}@example.com", "a".repeat(65)), Some(InvalidEmailReason::LocalPartTooLong), Some(InvalidEmailReason::LocalPartTooLong); "local part too long")]
+    #[test_case("john@exa_mple.com", Some(InvalidEmailReason::Domain), Some(InvalidEmailReason::Domain); "invalid domain")]
+    #[test_case("john@[127.0.0.300]", Some(InvalidEmailReason::Domain), Some(InvalidEmailReason::Domain); "invalid address literal")]
+    #[test_case(&format!("john@{}.com", "a".repeat(64)), Some(InvalidEmailReason::DomainTooLong), Some(InvalidEmailReason::DomainTooLong); "domain label too long")]
+    fn email_reason(
+        input: &str,
+        syntax: Option<InvalidEmailReason>,
+        deliverable: Option<InvalidEmailReason>,
+    ) {
+        assert_eq!(
+            check_email(input, false, EmailStrictness::Syntax).err(),
+            syntax
+        );
+        assert_eq!(
+            check_email(input, false, EmailStrictness::Deliverable).err(),
+            deliverable
+        );
+    }
+
+    #[test]
+    fn email_error() {
+        let validator = crate::options()
+            .should_validate_formats(true)
+            .with_email_strictness(EmailStrictness::Deliverable)
+            .build(&json!({"format": "idn-email"}))
+            .expect("Valid schema");
+        assert!(validator.is_valid(&json!("jürgen@münchen.de")));
+        let instance = json!("jürgen@münchen");
+        let error = validator.validate(&instance).expect_err("Invalid email");
+        assert!(matches!(
+            &error.kind,
+            ValidationErrorKind::InvalidEmail {
+                format,
+                reason: InvalidEmailReason::Domain
+            } if format == "idn-email"
+        ));
+        assert_eq!(
+            error.to_string(),
+            r#""jürgen@münchen" is not a "idn-email": the domain is not valid"#
+        );
+    }
+
+    #[test_case(EmailStrictness::Syntax)]
+    #[test_case(EmailStrictness::Deliverable)]
+    fn email_custom_format(strictness: EmailStrictness) {
+        let validator = crate::options()
+            .should_validate_formats(true)
+            .with_email_strictness(strictness)
+            .with_format("email", |value: &str| value.ends_with("@example.com"))
+            .build(&json!({"format": "email"}))
+            .expect("Valid schema");
+        assert!(validator.is_valid(&json!("@example.com")));
+        assert!(!validator.is_valid(&json!("john@example.org")));
     }
 
     #[test_case(""; "empty string")]
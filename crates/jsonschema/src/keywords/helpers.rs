@@ -1,7 +1,7 @@
 use num_cmp::NumCmp;
-use serde_json::{Map, Value};
+use serde_json::{Map, Number, Value};
 
-use crate::{compiler, paths::Location, primitive_type::PrimitiveType, ValidationError};
+use crate::{compiler, error::InvalidLimitReason, ValidationError};
 
 macro_rules! num_cmp {
     ($left:expr, $right:expr) => {
@@ -10,11 +10,29 @@ macro_rules! num_cmp {
         } else if let Some(b) = $right.as_i64() {
             NumCmp::num_eq($left, b)
         } else {
-            NumCmp::num_eq($left, $right.as_f64().expect("Always valid"))
+            NumCmp::num_eq($left, to_f64($right))
         }
     };
 }
 
+/// The nearest `f64` to `number`.
+///
+/// With the `arbitrary-precision` feature, numbers beyond the range of `f64`, like `1e400`, have
+/// no such value and are mapped to the infinity of their sign.
+#[inline]
+pub(crate) fn to_f64(number: &Number) -> f64 {
+    number.as_f64().unwrap_or_else(|| infinity(number))
+}
+
+#[cold]
+fn infinity(number: &Number) -> f64 {
+    if number.to_string().starts_with('-') {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    }
+}
+
 #[inline]
 pub(crate) fn equal(left: &Value, right: &Value) -> bool {
     match (left, right) {
@@ -26,8 +44,11 @@ pub(crate) fn equal(left: &Value, right: &Value) -> bool {
                 num_cmp!(a, right)
             } else if let Some(a) = left.as_i64() {
                 num_cmp!(a, right)
+            } else if left.as_f64().is_none() && right.as_f64().is_none() {
+                // Both are beyond the range of `f64`, compare them as written
+                left == right
             } else {
-                let a = left.as_f64().expect("Always valid");
+                let a = to_f64(left);
                 num_cmp!(a, right)
             }
         }
@@ -60,62 +81,76 @@ pub(crate) fn equal_objects(left: &Map<String, Value>, right: &Map<String, Value
             .all(|((ka, va), (kb, vb))| ka == kb && equal(va, vb))
 }
 
-#[inline]
-pub(crate) fn map_get_u64<'a>(
-    m: &'a Map<String, Value>,
+/// Read the limit of a keyword like `maxItems`, which must be a non-negative integer.
+///
+/// Integer-valued numbers like `2.0` are accepted by drafts after Draft 4.
+pub(crate) fn limit<'a>(
     ctx: &compiler::Context,
-    type_name: &str,
-) -> Option<Result<u64, ValidationError<'a>>> {
-    let value = m.get(type_name)?;
-    match value.as_u64() {
-        Some(n) => Some(Ok(n)),
-        None if value.is_i64() => Some(Err(ValidationError::minimum(
-            Location::new(),
-            ctx.location().clone(),
-            value,
-            0.into(),
-        ))),
-        None => {
-            if let Some(value) = value.as_f64() {
-                if value.trunc() == value {
-                    // NOTE: Imprecise cast as big integers are not supported yet
-                    #[allow(clippy::cast_possible_truncation)]
-                    return Some(Ok(value as u64));
-                }
-            }
-            Some(Err(ValidationError::single_type_error(
-                Location::new(),
-                ctx.location().clone(),
-                value,
-                PrimitiveType::Integer,
-            )))
+    keyword: &str,
+    value: &'a Value,
+) -> Result<u64, ValidationError<'a>> {
+    let reason = if let Value::Number(number) = value {
+        if let Some(limit) = number.as_u64() {
+            return Ok(limit);
+        }
+        let limit = to_f64(number);
+        if limit < 0.0 {
+            InvalidLimitReason::Negative
+        } else if limit.trunc() != limit {
+            InvalidLimitReason::Fractional
+        } else if !ctx.supports_integer_valued_numbers() {
+            InvalidLimitReason::IntegerValuedNumber
+        } else {
+            // NOTE: Imprecise cast as big integers are not supported yet
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            return Ok(limit as u64);
         }
-    }
-}
-
-/// Fail if the input value is not `u64`.
-pub(crate) fn fail_on_non_positive_integer(
-    value: &Value,
-    instance_path: Location,
-) -> ValidationError<'_> {
-    if value.is_i64() {
-        ValidationError::minimum(Location::new(), instance_path, value, 0.into())
     } else {
-        ValidationError::single_type_error(
-            Location::new(),
-            instance_path,
-            value,
-            PrimitiveType::Integer,
-        )
-    }
+        InvalidLimitReason::NotANumber
+    };
+    Err(ValidationError::invalid_limit(
+        ctx.location().join(keyword),
+        value,
+        keyword,
+        reason,
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::equal;
+    use crate::error::{InvalidLimitReason, ValidationErrorKind};
+    use referencing::Draft;
     use serde_json::{json, Value};
     use test_case::test_case;
 
+    const LIMITS: [&str; 8] = [
+        "maxItems",
+        "minItems",
+        "maxLength",
+        "minLength",
+        "maxProperties",
+        "minProperties",
+        "maxContains",
+        "minContains",
+    ];
+
+    fn compile(draft: Draft, keyword: &str, limit: &Value) -> Result<(), ValidationErrorKind> {
+        let mut schema = json!({keyword: limit});
+        if keyword.ends_with("Contains") {
+            schema["contains"] = json!(true);
+        }
+        crate::options()
+            .with_draft(draft)
+            .without_schema_validation()
+            .build(&schema)
+            .map(|_| ())
+            .map_err(|error| {
+                assert_eq!(error.instance_path.as_str(), format!("/{keyword}"));
+                error.kind
+            })
+    }
+
     #[test_case(&json!(1), &json!(1.0))]
     #[test_case(&json!([2]), &json!([2.0]))]
     #[test_case(&json!([-3]), &json!([-3.0]))]
@@ -131,4 +166,86 @@ mod tests {
     fn are_not_equal(left: &Value, right: &Value) {
         assert!(!equal(left, right))
     }
+
+    #[test_case(Draft::Draft202012, &json!(2))]
+    #[test_case(Draft::Draft202012, &json!(0))]
+    #[test_case(Draft::Draft202012, &json!(2.0))]
+    #[test_case(Draft::Draft202012, &json!(-0.0))]
+    #[test_case(Draft::Draft201909, &json!(2.0))]
+    #[test_case(Draft::Draft4, &json!(2))]
+    fn valid_limit(draft: Draft, limit: &Value) {
+        for keyword in LIMITS {
+            if keyword.ends_with("Contains") && draft < Draft::Draft201909 {
+                continue;
+            }
+            assert!(compile(draft, keyword, limit).is_ok(), "{keyword}: {limit}");
+        }
+    }
+
+    #[test_case(Draft::Draft202012, &json!(-1), InvalidLimitReason::Negative)]
+    #[test_case(Draft::Draft202012, &json!(-1.5), InvalidLimitReason::Negative)]
+    #[test_case(Draft::Draft202012, &json!(2.5), InvalidLimitReason::Fractional)]
+    #[test_case(Draft::Draft202012, &json!("2"), InvalidLimitReason::NotANumber)]
+    #[test_case(Draft::Draft202012, &json!(null), InvalidLimitReason::NotANumber)]
+    #[test_case(Draft::Draft4, &json!(2.0), InvalidLimitReason::IntegerValuedNumber)]
+    #[test_case(Draft::Draft4, &json!(-1), InvalidLimitReason::Negative)]
+    #[test_case(Draft::Draft4, &json!(2.5), InvalidLimitReason::Fractional)]
+    fn invalid_limit(draft: Draft, limit: &Value, expected: InvalidLimitReason) {
+        for keyword in LIMITS {
+            if keyword.ends_with("Contains") && draft < Draft::Draft201909 {
+                continue;
+            }
+            match compile(draft, keyword, limit) {
+                Err(ValidationErrorKind::InvalidLimit {
+                    keyword: actual,
+                    limit: value,
+                    reason,
+                }) => {
+                    assert_eq!(actual, keyword);
+                    assert_eq!(&value, limit);
+                    assert_eq!(reason, expected, "{keyword}: {limit}");
+                }
+                other => panic!("{keyword}: {limit}: unexpected {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_limit_message() {
+        let schema = json!({"maxItems": -1});
+        let error = crate::options()
+            .without_schema_validation()
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            r#"Invalid "maxItems" limit -1: must not be negative"#
+        );
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case(r#"{"minimum": 0}"#, "1e400", true)]
+    #[test_case(r#"{"minimum": 0}"#, "-1e400", false)]
+    #[test_case(r#"{"maximum": 0}"#, "1e400", false)]
+    #[test_case(r#"{"exclusiveMinimum": 0}"#, "1e400", true)]
+    #[test_case(r#"{"exclusiveMaximum": 0}"#, "-1e400", true)]
+    #[test_case(r#"{"minimum": 1e400}"#, "5", false)]
+    #[test_case(r#"{"maximum": -1e400}"#, "5", false)]
+    #[test_case(r#"{"minimum": 0, "maximum": 10}"#, "1e400", false)]
+    #[test_case(r#"{"multipleOf": 0.5}"#, "1e400", true)]
+    #[test_case(r#"{"maxItems": 1e400}"#, "[1]", true)]
+    #[test_case(r#"{"const": 1e400}"#, "1e400", true)]
+    #[test_case(r#"{"const": 1e400}"#, "2e400", false)]
+    #[test_case(r#"{"const": 1e400}"#, "5", false)]
+    #[test_case(r#"{"enum": [1e400]}"#, "1e400", true)]
+    #[test_case(r#"{"enum": [1e400]}"#, "2e400", false)]
+    #[test_case(r#"{"uniqueItems": true}"#, "[1e400, 2e400]", true)]
+    #[test_case(r#"{"uniqueItems": true}"#, "[1e400, 1e400]", false)]
+    fn beyond_f64(schema: &str, instance: &str, expected: bool) {
+        let schema: Value = serde_json::from_str(schema).expect("Valid JSON");
+        let instance: Value = serde_json::from_str(instance).expect("Valid JSON");
+        let validator = crate::validator_for(&schema).expect("Valid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.validate(&instance).is_ok(), expected);
+    }
 }
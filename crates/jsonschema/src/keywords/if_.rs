@@ -1,11 +1,11 @@
 use crate::{
     compiler,
-    error::{no_error, ErrorIterator},
+    error::{error, no_error, ErrorIterator},
     keywords::CompilationResult,
     node::SchemaNode,
     paths::LazyLocation,
-    validator::{PartialApplication, Validate},
-    ValidationError,
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value, ValidationError,
 };
 use serde_json::{Map, Value};
 
@@ -37,7 +37,11 @@ impl IfThenValidator {
 impl Validate for IfThenValidator {
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        if self.schema.is_valid(instance) {
+        let matches = match self.schema.is_valid_at(instance, location) {
+            Ok(matches) => matches,
+            Err(err) => return error(err),
+        };
+        if matches {
             let errors: Vec<_> = self.then_schema.iter_errors(instance, location).collect();
             Box::new(errors.into_iter())
         } else {
@@ -45,6 +49,23 @@ impl Validate for IfThenValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        let matches = match scratch.is_valid(&self.schema, instance, location) {
+            Ok(matches) => matches,
+            Err(error) => return errors.push(error),
+        };
+        if matches {
+            self.then_schema
+                .collect_errors(instance, location, errors, scratch);
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if self.schema.is_valid(instance) {
             self.then_schema.is_valid(instance)
@@ -52,13 +73,16 @@ impl Validate for IfThenValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        !self.schema.is_valid_value(instance) || self.then_schema.is_valid_value(instance)
+    }
 
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location)? {
             self.then_schema.validate(instance, location)
         } else {
             Ok(())
@@ -105,7 +129,11 @@ impl IfElseValidator {
 impl Validate for IfElseValidator {
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        if self.schema.is_valid(instance) {
+        let matches = match self.schema.is_valid_at(instance, location) {
+            Ok(matches) => matches,
+            Err(err) => return error(err),
+        };
+        if matches {
             no_error()
         } else {
             let errors: Vec<_> = self.else_schema.iter_errors(instance, location).collect();
@@ -113,6 +141,23 @@ impl Validate for IfElseValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        let matches = match scratch.is_valid(&self.schema, instance, location) {
+            Ok(matches) => matches,
+            Err(error) => return errors.push(error),
+        };
+        if !matches {
+            self.else_schema
+                .collect_errors(instance, location, errors, scratch);
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if self.schema.is_valid(instance) {
             true
@@ -120,13 +165,16 @@ impl Validate for IfElseValidator {
             self.else_schema.is_valid(instance)
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.schema.is_valid_value(instance) || self.else_schema.is_valid_value(instance)
+    }
 
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location)? {
             Ok(())
         } else {
             self.else_schema.validate(instance, location)
@@ -177,7 +225,11 @@ impl IfThenElseValidator {
 impl Validate for IfThenElseValidator {
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        if self.schema.is_valid(instance) {
+        let matches = match self.schema.is_valid_at(instance, location) {
+            Ok(matches) => matches,
+            Err(err) => return error(err),
+        };
+        if matches {
             let errors: Vec<_> = self.then_schema.iter_errors(instance, location).collect();
             Box::new(errors.into_iter())
         } else {
@@ -186,6 +238,26 @@ impl Validate for IfThenElseValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        let matches = match scratch.is_valid(&self.schema, instance, location) {
+            Ok(matches) => matches,
+            Err(error) => return errors.push(error),
+        };
+        if matches {
+            self.then_schema
+                .collect_errors(instance, location, errors, scratch);
+        } else {
+            self.else_schema
+                .collect_errors(instance, location, errors, scratch);
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if self.schema.is_valid(instance) {
             self.then_schema.is_valid(instance)
@@ -193,13 +265,20 @@ impl Validate for IfThenElseValidator {
             self.else_schema.is_valid(instance)
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        if self.schema.is_valid_value(instance) {
+            self.then_schema.is_valid_value(instance)
+        } else {
+            self.else_schema.is_valid_value(instance)
+        }
+    }
 
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location)? {
             self.then_schema.validate(instance, location)
         } else {
             self.else_schema.validate(instance, location)
@@ -240,7 +319,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{tests_util, BasicOutput};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -248,7 +327,45 @@ mod tests {
     #[test_case(&json!({"if": {"minimum": 0}, "then": {"multipleOf": 2}}), &json!(3), "/then/multipleOf")]
     #[test_case(&json!({"if": {"minimum": 0}, "then": {"multipleOf": 2}, "else": {"multipleOf": 2}}), &json!(-1), "/else/multipleOf")]
     #[test_case(&json!({"if": {"minimum": 0}, "then": {"multipleOf": 2}, "else": {"multipleOf": 2}}), &json!(3), "/then/multipleOf")]
+    #[test_case(&json!({"if": {"type": "object"}, "then": {"properties": {"a": {"type": "string"}}}}), &json!({"a": 1}), "/then/properties/a/type")]
+    #[test_case(&json!({"if": {"type": "object"}, "then": true, "else": {"items": {"type": "string"}}}), &json!([1]), "/else/items/type")]
+    #[test_case(&json!({"if": {"required": ["a"]}, "then": {"required": ["b"]}}), &json!({"a": 1}), "/then/required")]
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(&json!({"if": {"properties": {"a": {"const": 1}}}, "then": {"properties": {"b": true}}}), &json!({"a": 1, "b": 2}), &["/if/properties", "/then/properties"])]
+    #[test_case(&json!({"if": {"properties": {"a": {"const": 1}}}, "else": {"properties": {"b": true}}}), &json!({"a": 1, "b": 2}), &["/if/properties"])]
+    #[test_case(&json!({"if": {"properties": {"a": {"const": 1}}}, "then": true, "else": {"properties": {"b": true}}}), &json!({"a": 2, "b": 2}), &["/else/properties"])]
+    #[test_case(&json!({"if": {"properties": {"a": {"const": 1}}}, "then": {"properties": {"b": true}}}), &json!({"a": 2, "b": 2}), &[])]
+    fn annotations(schema: &Value, instance: &Value, expected: &[&str]) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let output = validator.apply(instance).basic();
+        let BasicOutput::Valid(units) = output else {
+            panic!("Expected valid output")
+        };
+        let locations: Vec<_> = units
+            .iter()
+            .map(|unit| unit.keyword_location().to_string())
+            .collect();
+        assert_eq!(locations, expected);
+    }
+
+    #[test]
+    fn failed_then_drops_if_annotations() {
+        let schema = json!({
+            "if": {"properties": {"a": {"const": 1}}},
+            "then": {"properties": {"b": {"type": "string"}}}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let output = validator.apply(&json!({"a": 1, "b": 2})).basic();
+        let BasicOutput::Invalid(units) = output else {
+            panic!("Expected invalid output")
+        };
+        let locations: Vec<_> = units
+            .iter()
+            .map(|unit| unit.keyword_location().to_string())
+            .collect();
+        assert_eq!(locations, ["/then/properties/b/type"]);
+    }
 }
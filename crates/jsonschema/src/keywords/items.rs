@@ -2,13 +2,46 @@ use crate::{
     compiler,
     error::{no_error, ErrorIterator},
     keywords::CompilationResult,
-    node::SchemaNode,
-    paths::LazyLocation,
-    validator::{PartialApplication, Validate},
-    ValidationError,
+    node::{DeferredChecks, SchemaNode},
+    output::Evaluated,
+    paths::{LazyLocation, Location},
+    primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value, Draft, ValidationError,
 };
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde_json::{Map, Value};
 
+/// Items of arrays validated in parallel are split into chunks of at least this size.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_ITEMS: usize = 1024;
+
+#[cfg(feature = "parallel")]
+fn par_is_valid_items(node: &SchemaNode, items: &[Value]) -> bool {
+    items
+        .par_iter()
+        .with_min_len(PARALLEL_MIN_ITEMS)
+        .all(|item| node.is_valid(item))
+}
+
+/// Errors of `items` in index order. `offset` is the index of the first item in the array.
+#[cfg(feature = "parallel")]
+fn par_iter_errors_items<'i>(
+    node: &SchemaNode,
+    items: &'i [Value],
+    offset: usize,
+    location: &LazyLocation,
+) -> ErrorIterator<'i> {
+    let errors: Vec<_> = items
+        .par_iter()
+        .enumerate()
+        .with_min_len(PARALLEL_MIN_ITEMS)
+        .flat_map_iter(|(idx, item)| node.iter_errors(item, &location.push(idx + offset)))
+        .collect();
+    Box::new(errors.into_iter())
+}
+
 pub(crate) struct ItemsArrayValidator {
     items: Vec<SchemaNode>,
 }
@@ -44,6 +77,20 @@ impl Validate for ItemsArrayValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            for (idx, (item, node)) in items.iter().zip(self.items.iter()).enumerate() {
+                node.collect_errors(item, &location.push(idx), errors, scratch);
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             items
@@ -54,6 +101,21 @@ impl Validate for ItemsArrayValidator {
             true
         }
     }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        if let Value::Array(items) = instance {
+            items
+                .iter()
+                .zip(self.items.iter())
+                .all(|(item, node)| checks.check(node, item))
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        value::array_iter(instance)
+            .zip(self.items.iter())
+            .all(move |(item, node)| node.is_valid_value(item))
+    }
 
     fn validate<'i>(
         &self,
@@ -67,6 +129,30 @@ impl Validate for ItemsArrayValidator {
         }
         Ok(())
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        if let Value::Array(items) = instance {
+            if !items.is_empty() {
+                let results: Vec<_> = items
+                    .iter()
+                    .zip(self.items.iter())
+                    .enumerate()
+                    .map(|(idx, (item, node))| node.apply_rooted(item, &location.push(idx)))
+                    .collect();
+                // Like `prefixItems`, the largest index a subschema was applied to, or `true` if
+                // it was applied to every item
+                let evaluated = if results.len() == items.len() {
+                    Evaluated::AllItems
+                } else {
+                    Evaluated::LargestIndex(results.len() - 1)
+                };
+                let mut output: PartialApplication = results.into_iter().collect();
+                output.annotate(evaluated.into());
+                return output;
+            }
+        }
+        PartialApplication::valid_empty()
+    }
 }
 
 pub(crate) struct ItemsObjectValidator {
@@ -96,6 +182,21 @@ impl Validate for ItemsObjectValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            for (idx, item) in items.iter().enumerate() {
+                self.node
+                    .collect_errors(item, &location.push(idx), errors, scratch);
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             items.iter().all(|i| self.node.is_valid(i))
@@ -103,6 +204,36 @@ impl Validate for ItemsObjectValidator {
             true
         }
     }
+    #[cfg(feature = "parallel")]
+    fn par_is_valid(&self, instance: &Value) -> bool {
+        if let Value::Array(items) = instance {
+            par_is_valid_items(&self.node, items)
+        } else {
+            true
+        }
+    }
+    #[cfg(feature = "parallel")]
+    fn par_iter_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        if let Value::Array(items) = instance {
+            par_iter_errors_items(&self.node, items, 0, location)
+        } else {
+            no_error()
+        }
+    }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        if let Value::Array(items) = instance {
+            items.iter().all(|item| checks.check(&self.node, item))
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        value::array_iter(instance).all(|i| self.node.is_valid_value(i))
+    }
 
     fn validate<'i>(
         &self,
@@ -126,12 +257,13 @@ impl Validate for ItemsObjectValidator {
             }
             let mut output: PartialApplication = results.into_iter().collect();
             // Per draft 2020-12 section https://json-schema.org/draft/2020-12/json-schema-core.html#rfc.section.10.3.1.2
-            // we must produce an annotation with a boolean value indicating whether the subschema
-            // was applied to any positions in the underlying array. Since the struct
-            // `ItemsObjectValidator` is not used when prefixItems is defined, this is true if
-            // there are any items in the instance.
-            let schema_was_applied = !items.is_empty();
-            output.annotate(serde_json::json!(schema_was_applied).into());
+            // we must produce an annotation with a boolean `true` if the subschema was applied to
+            // any positions in the underlying array. Since the struct `ItemsObjectValidator` is not
+            // used when prefixItems is defined, this is the case if there are any items in the
+            // instance.
+            if !items.is_empty() {
+                output.annotate(Evaluated::AllItems.into());
+            }
             output
         } else {
             PartialApplication::valid_empty()
@@ -179,6 +311,21 @@ impl Validate for ItemsObjectSkipPrefixValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            for (idx, item) in items.iter().enumerate().skip(self.skip_prefix) {
+                self.node
+                    .collect_errors(item, &location.push(idx), errors, scratch);
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             items
@@ -189,6 +336,46 @@ impl Validate for ItemsObjectSkipPrefixValidator {
             true
         }
     }
+    #[cfg(feature = "parallel")]
+    fn par_is_valid(&self, instance: &Value) -> bool {
+        match instance {
+            Value::Array(items) if items.len() > self.skip_prefix => {
+                par_is_valid_items(&self.node, &items[self.skip_prefix..])
+            }
+            _ => true,
+        }
+    }
+    #[cfg(feature = "parallel")]
+    fn par_iter_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        match instance {
+            Value::Array(items) if items.len() > self.skip_prefix => par_iter_errors_items(
+                &self.node,
+                &items[self.skip_prefix..],
+                self.skip_prefix,
+                location,
+            ),
+            _ => no_error(),
+        }
+    }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        if let Value::Array(items) = instance {
+            items
+                .iter()
+                .skip(self.skip_prefix)
+                .all(|item| checks.check(&self.node, item))
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        value::array_iter(instance)
+            .skip(self.skip_prefix)
+            .all(|i| self.node.is_valid_value(i))
+    }
 
     fn validate<'i>(
         &self,
@@ -213,10 +400,11 @@ impl Validate for ItemsObjectSkipPrefixValidator {
             }
             let mut output: PartialApplication = results.into_iter().collect();
             // Per draft 2020-12 section https://json-schema.org/draft/2020-12/json-schema-core.html#rfc.section.10.3.1.2
-            // we must produce an annotation with a boolean value indicating whether the subschema
-            // was applied to any positions in the underlying array.
-            let schema_was_applied = items.len() > self.skip_prefix;
-            output.annotate(serde_json::json!(schema_was_applied).into());
+            // we must produce an annotation with a boolean `true` if the subschema was applied to
+            // any positions in the underlying array.
+            if items.len() > self.skip_prefix {
+                output.annotate(Evaluated::AllItems.into());
+            }
             output
         } else {
             PartialApplication::valid_empty()
@@ -231,6 +419,17 @@ pub(crate) fn compile<'a>(
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
     match schema {
+        // Draft 2020-12 replaced the array form with `prefixItems`
+        Value::Array(_) if ctx.draft() == Draft::Draft202012 => {
+            Some(Err(ValidationError::multiple_type_error(
+                Location::new(),
+                ctx.location().join("items"),
+                schema,
+                PrimitiveTypesBitMap::new()
+                    .add_type(PrimitiveType::Object)
+                    .add_type(PrimitiveType::Boolean),
+            )))
+        }
         Value::Array(items) => Some(ItemsArrayValidator::compile(ctx, items)),
         Value::Object(_) | Value::Bool(false) => {
             if let Some(Value::Array(prefix_items)) = parent.get("prefixItems") {
@@ -249,6 +448,7 @@ pub(crate) fn compile<'a>(
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
+    use referencing::Draft;
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -258,4 +458,22 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test]
+    fn array_form_in_draft_2020_12() {
+        let schema = json!({"items": [{"type": "string"}]});
+        // The meta-schema rejects it as well, check the compiled keyword on its own
+        let error = crate::options()
+            .with_draft(Draft::Draft202012)
+            .without_schema_validation()
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/items");
+        assert_eq!(*error.instance, schema["items"]);
+        let validator = crate::options()
+            .with_draft(Draft::Draft201909)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!([1])));
+    }
 }
@@ -1,6 +1,9 @@
 use crate::{
     compiler,
+    error::{InvalidExclusiveLimitReason, ValidationError},
     keywords::{exclusive_minimum, minimum, CompilationResult},
+    paths::Location,
+    primitive_type::PrimitiveType,
 };
 use serde_json::{Map, Value};
 
@@ -16,3 +19,31 @@ pub(crate) fn compile<'a>(
         minimum::compile(ctx, parent, schema)
     }
 }
+
+/// Check the boolean `exclusiveMinimum`, which is applied by the sibling `minimum`.
+#[inline]
+pub(crate) fn compile_exclusive<'a>(
+    ctx: &compiler::Context,
+    parent: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    let reason = match schema {
+        Value::Bool(_) if parent.contains_key("minimum") => return None,
+        Value::Bool(_) => InvalidExclusiveLimitReason::MissingLimit,
+        Value::Number(_) => InvalidExclusiveLimitReason::NumberInDraft4,
+        _ => {
+            return Some(Err(ValidationError::single_type_error(
+                Location::new(),
+                ctx.location().join("exclusiveMinimum"),
+                schema,
+                PrimitiveType::Boolean,
+            )))
+        }
+    };
+    Some(Err(ValidationError::invalid_exclusive_limit(
+        ctx.location().join("exclusiveMinimum"),
+        schema,
+        "exclusiveMinimum",
+        reason,
+    )))
+}
@@ -1,10 +1,11 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::{type_, CompilationResult},
+    keywords::{type_, BoxedValidator, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
     validator::Validate,
+    value,
 };
 use serde_json::{json, Map, Number, Value};
 use std::convert::TryFrom;
@@ -44,7 +45,11 @@ impl MultipleTypesValidator {
                 }
             }
         }
-        Ok(Box::new(MultipleTypesValidator { types, location }))
+        Ok(Self::boxed(types, location))
+    }
+    /// A validator for already parsed types.
+    pub(crate) fn boxed(types: PrimitiveTypesBitMap, location: Location) -> BoxedValidator {
+        Box::new(MultipleTypesValidator { types, location })
     }
 }
 
@@ -62,6 +67,16 @@ impl Validate for MultipleTypesValidator {
             Value::String(_) => self.types.contains_type(PrimitiveType::String),
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        match instance.primitive_type() {
+            PrimitiveType::Number | PrimitiveType::Integer => {
+                self.types.contains_type(PrimitiveType::Number)
+                    || (self.types.contains_type(PrimitiveType::Integer)
+                        && is_integer_value(instance))
+            }
+            primitive_type => self.types.contains_type(primitive_type),
+        }
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -99,6 +114,9 @@ impl Validate for IntegerTypeValidator {
             false
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        is_integer_value(instance)
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -117,8 +135,33 @@ impl Validate for IntegerTypeValidator {
     }
 }
 
+/// Whether `num` is written without a fraction or exponent, as Draft 4 requires for integers.
 fn is_integer(num: &Number) -> bool {
-    num.is_u64() || num.is_i64()
+    if num.is_u64() || num.is_i64() {
+        return true;
+    }
+    #[cfg(feature = "arbitrary-precision")]
+    {
+        !num.to_string().contains(['.', 'e', 'E'])
+    }
+    #[cfg(not(feature = "arbitrary-precision"))]
+    {
+        is_integer_float(num.as_f64().expect("Always valid"))
+    }
+}
+
+fn is_integer_value(instance: &dyn value::Value) -> bool {
+    instance.as_u64().is_some()
+        || instance.as_i64().is_some()
+        || instance.as_f64().is_some_and(is_integer_float)
+}
+
+/// Whether a number parsed as `f64` was likely written as an integer. Integers are only parsed
+/// as floats if they are `-0` or do not fit into 64 bits, so `-0.0` and `1e100` are
+/// accepted as well. Enable the `arbitrary-precision` feature to check how the number was written.
+fn is_integer_float(number: f64) -> bool {
+    (number == 0. && number.is_sign_negative())
+        || !(-9_223_372_036_854_775_808.0..18_446_744_073_709_551_616.0).contains(&number)
 }
 
 #[inline]
@@ -149,7 +192,7 @@ pub(crate) fn compile<'a>(
         }
         _ => Some(Err(ValidationError::multiple_type_error(
             Location::new(),
-            ctx.location().clone(),
+            location,
             schema,
             PrimitiveTypesBitMap::new()
                 .add_type(PrimitiveType::String)
@@ -1,9 +1,10 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::{helpers::fail_on_non_positive_integer, CompilationResult},
+    keywords::{helpers, CompilationResult},
     paths::{LazyLocation, Location},
     validator::Validate,
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -19,22 +20,8 @@ impl MaxLengthValidator {
         schema: &'a Value,
         location: Location,
     ) -> CompilationResult<'a> {
-        if let Some(limit) = schema.as_u64() {
-            return Ok(Box::new(MaxLengthValidator { limit, location }));
-        }
-        if ctx.supports_integer_valued_numbers() {
-            if let Some(limit) = schema.as_f64() {
-                if limit.trunc() == limit {
-                    #[allow(clippy::cast_possible_truncation)]
-                    return Ok(Box::new(MaxLengthValidator {
-                        // NOTE: Imprecise cast as big integers are not supported yet
-                        limit: limit as u64,
-                        location,
-                    }));
-                }
-            }
-        }
-        Err(fail_on_non_positive_integer(schema, location))
+        let limit = helpers::limit(ctx, "maxLength", schema)?;
+        Ok(Box::new(MaxLengthValidator { limit, location }))
     }
 }
 
@@ -47,6 +34,11 @@ impl Validate for MaxLengthValidator {
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.as_str().map_or(true, |item| {
+            (bytecount::num_chars(item.as_bytes()) as u64) <= self.limit
+        })
+    }
 
     fn validate<'i>(
         &self,
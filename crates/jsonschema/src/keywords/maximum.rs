@@ -1,10 +1,11 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{helpers, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
+    value,
 };
 use num_cmp::NumCmp;
 use serde_json::{Map, Value};
@@ -52,12 +53,15 @@ macro_rules! validate {
                     } else if let Some(item) = item.as_i64() {
                         !NumCmp::num_gt(item, self.limit)
                     } else {
-                        let item = item.as_f64().expect("Always valid");
+                        let item = helpers::to_f64(item);
                         !NumCmp::num_gt(item, self.limit)
                     };
                 }
                 true
             }
+            fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+                value::num_gt(instance, self.limit).map_or(true, |gt| !gt)
+            }
         }
     };
 }
@@ -73,12 +77,15 @@ impl Validate for MaximumF64Validator {
             } else if let Some(item) = item.as_i64() {
                 !NumCmp::num_gt(item, self.limit)
             } else {
-                let item = item.as_f64().expect("Always valid");
+                let item = helpers::to_f64(item);
                 !NumCmp::num_gt(item, self.limit)
             };
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        value::num_gt(instance, self.limit).map_or(true, |gt| !gt)
+    }
 
     fn validate<'i>(
         &self,
@@ -119,7 +126,7 @@ pub(crate) fn compile<'a>(
                 location,
             })))
         } else {
-            let limit = limit.as_f64().expect("Always valid");
+            let limit = helpers::to_f64(limit);
             Some(Ok(Box::new(MaximumF64Validator {
                 limit,
                 limit_val: schema.clone(),
@@ -129,7 +136,7 @@ pub(crate) fn compile<'a>(
     } else {
         Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("maximum"),
             schema,
             PrimitiveType::Number,
         )))
@@ -1,9 +1,10 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::{helpers::fail_on_non_positive_integer, CompilationResult},
+    keywords::{helpers, CompilationResult},
     paths::{LazyLocation, Location},
     validator::Validate,
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -19,22 +20,8 @@ impl MinPropertiesValidator {
         schema: &'a Value,
         location: Location,
     ) -> CompilationResult<'a> {
-        if let Some(limit) = schema.as_u64() {
-            return Ok(Box::new(MinPropertiesValidator { limit, location }));
-        }
-        if ctx.supports_integer_valued_numbers() {
-            if let Some(limit) = schema.as_f64() {
-                if limit.trunc() == limit {
-                    #[allow(clippy::cast_possible_truncation)]
-                    return Ok(Box::new(MinPropertiesValidator {
-                        // NOTE: Imprecise cast as big integers are not supported yet
-                        limit: limit as u64,
-                        location,
-                    }));
-                }
-            }
-        }
-        Err(fail_on_non_positive_integer(schema, location))
+        let limit = helpers::limit(ctx, "minProperties", schema)?;
+        Ok(Box::new(MinPropertiesValidator { limit, location }))
     }
 }
 
@@ -47,6 +34,11 @@ impl Validate for MinPropertiesValidator {
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .object_len()
+            .map_or(true, |len| (len as u64) >= self.limit)
+    }
 
     fn validate<'i>(
         &self,
@@ -54,12 +46,14 @@ impl Validate for MinPropertiesValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            if (item.len() as u64) < self.limit {
+            let count = item.len() as u64;
+            if count < self.limit {
                 return Err(ValidationError::min_properties(
                     self.location.clone(),
                     location.into(),
                     instance,
                     self.limit,
+                    count,
                 ));
             }
         }
@@ -79,8 +73,9 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
-    use serde_json::json;
+    use crate::{error::ValidationErrorKind, tests_util};
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[test]
     fn location() {
@@ -90,4 +85,16 @@ mod tests {
             "/minProperties",
         )
     }
+
+    #[test_case(&json!({"minProperties": 2}); "single")]
+    #[test_case(&json!({"minProperties": 2, "maxProperties": 5}); "fused")]
+    fn count(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let instance = json!({"a": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::MinProperties { limit: 2, count: 1 }
+        ));
+    }
 }
@@ -8,6 +8,7 @@ pub(crate) mod contains;
 pub(crate) mod content;
 pub(crate) mod custom;
 pub(crate) mod dependencies;
+pub(crate) mod discriminator;
 pub(crate) mod enum_;
 pub(crate) mod exclusive_maximum;
 pub(crate) mod exclusive_minimum;
@@ -27,11 +28,13 @@ pub(crate) mod minimum;
 pub(crate) mod multiple_of;
 pub(crate) mod not;
 pub(crate) mod one_of;
+pub(crate) mod openapi_30;
 pub(crate) mod pattern;
 pub(crate) mod pattern_properties;
 pub(crate) mod prefix_items;
 pub(crate) mod properties;
 pub(crate) mod property_names;
+pub(crate) mod ranges;
 pub(crate) mod ref_;
 pub(crate) mod required;
 pub(crate) mod type_;
@@ -43,7 +46,7 @@ use core::fmt;
 use referencing::{Draft, Vocabulary};
 use serde_json::{Map, Value};
 
-use crate::{compiler, error, validator::Validate};
+use crate::{compiler, error, options::Dialect, paths::Location, validator::Validate};
 
 pub(crate) type CompilationResult<'a> = Result<BoxedValidator, error::ValidationError<'a>>;
 pub(crate) type BoxedValidator = Box<dyn Validate + Send + Sync>;
@@ -55,6 +58,12 @@ type CompileFunc<'a> =
 pub(crate) enum Keyword {
     Buildin(BuiltinKeyword),
     Custom(Box<str>),
+    /// A keyword moved into this node from a nested `allOf` branch, with its location relative
+    /// to the node.
+    Hoisted {
+        keyword: Box<Keyword>,
+        location: Location,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -158,6 +167,56 @@ impl Keyword {
         match self {
             Self::Buildin(d) => d.as_str(),
             Self::Custom(s) => s,
+            Self::Hoisted { keyword, .. } => keyword.as_str(),
+        }
+    }
+    /// A rough estimate of how expensive this keyword is to check, used to order keywords for
+    /// `is_valid`. Custom keywords are assumed to be the most expensive.
+    pub(crate) fn cost(&self) -> u8 {
+        match self {
+            Self::Buildin(keyword) => keyword.cost(),
+            Self::Custom(_) => 5,
+            Self::Hoisted { keyword, .. } => keyword.cost(),
+        }
+    }
+}
+
+impl BuiltinKeyword {
+    fn cost(&self) -> u8 {
+        match self {
+            Self::Type | Self::Const | Self::Enum | Self::Required => 0,
+            Self::Maximum
+            | Self::Minimum
+            | Self::ExclusiveMaximum
+            | Self::ExclusiveMinimum
+            | Self::MultipleOf
+            | Self::MaxItems
+            | Self::MinItems
+            | Self::MaxLength
+            | Self::MinLength
+            | Self::MaxProperties
+            | Self::MinProperties
+            | Self::DependentRequired => 1,
+            Self::Format | Self::UniqueItems | Self::ContentMediaType | Self::ContentEncoding => 2,
+            Self::Pattern | Self::PatternProperties | Self::PropertyNames => 3,
+            Self::Ref
+            | Self::RecursiveRef
+            | Self::DynamicRef
+            | Self::AdditionalItems
+            | Self::AdditionalProperties
+            | Self::AllOf
+            | Self::AnyOf
+            | Self::OneOf
+            | Self::Not
+            | Self::If
+            | Self::Dependencies
+            | Self::DependentSchemas
+            | Self::Items
+            | Self::PrefixItems
+            | Self::Contains
+            | Self::Properties
+            | Self::UnevaluatedItems
+            | Self::UnevaluatedProperties => 4,
         }
     }
 }
@@ -178,6 +237,11 @@ pub(crate) fn get_for_draft<'a>(
     ctx: &compiler::Context<'a>,
     keyword: &'a str,
 ) -> Option<(Keyword, CompileFunc<'a>)> {
+    if ctx.config().dialect() == Some(Dialect::OpenApi30) {
+        if let Some(compiled) = openapi_30::get(keyword) {
+            return Some(compiled);
+        }
+    }
     match (ctx.draft(), keyword) {
         // Keywords common to all drafts
         (_, "$ref") => Some((BuiltinKeyword::Ref.into(), ref_::compile_ref)),
@@ -265,6 +329,14 @@ pub(crate) fn get_for_draft<'a>(
             BuiltinKeyword::Minimum.into(),
             legacy::minimum_draft_4::compile,
         )),
+        (Draft::Draft4, "exclusiveMaximum") => Some((
+            BuiltinKeyword::ExclusiveMaximum.into(),
+            legacy::maximum_draft_4::compile_exclusive,
+        )),
+        (Draft::Draft4, "exclusiveMinimum") => Some((
+            BuiltinKeyword::ExclusiveMinimum.into(),
+            legacy::minimum_draft_4::compile_exclusive,
+        )),
         (Draft::Draft4, "type") => {
             Some((BuiltinKeyword::Type.into(), legacy::type_draft_4::compile))
         }
@@ -393,6 +465,8 @@ pub(crate) fn get_for_draft<'a>(
 
 #[cfg(test)]
 mod tests {
+    use crate::error::{InvalidExclusiveLimitReason, ValidationErrorKind};
+    use referencing::Draft;
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -408,7 +482,7 @@ mod tests {
     #[test_case(&json!({"exclusiveMaximum": 3.0}), &json!(3.0), r#"3.0 is greater than or equal to the maximum of 3.0"#)]
     #[test_case(&json!({"exclusiveMinimum": 1}), &json!(1.0), r#"1.0 is less than or equal to the minimum of 1"#)]
     #[test_case(&json!({"exclusiveMinimum": 1.0}), &json!(1), r#"1 is less than or equal to the minimum of 1.0"#)]
-    #[test_case(&json!({"format": "ipv4"}), &json!("2001:0db8:85a3:0000:0000:8a2e:0370:7334"), r#""2001:0db8:85a3:0000:0000:8a2e:0370:7334" is not a "ipv4""#)]
+    #[cfg_attr(feature = "formats", test_case(&json!({"format": "ipv4"}), &json!("2001:0db8:85a3:0000:0000:8a2e:0370:7334"), r#""2001:0db8:85a3:0000:0000:8a2e:0370:7334" is not a "ipv4": it has a character that is not allowed"#))]
     #[test_case(&json!({"maximum": 3}), &json!(3.5), r#"3.5 is greater than the maximum of 3"#)]
     #[test_case(&json!({"maximum": 3.0}), &json!(3.5), r#"3.5 is greater than the maximum of 3.0"#)]
     #[test_case(&json!({"minimum": 3}), &json!(2.5), r#"2.5 is less than the minimum of 3"#)]
@@ -523,4 +597,104 @@ mod tests {
         assert_eq!(errors[0].to_string(), r#""foo" is a required property"#);
         assert_eq!(errors[1].to_string(), r#""bar" is a required property"#);
     }
+
+    #[test_case(Draft::Draft4, &json!({"maximum": 5, "exclusiveMaximum": true}), &json!(5); "draft 4 boolean maximum")]
+    #[test_case(Draft::Draft4, &json!({"minimum": 5, "exclusiveMinimum": true}), &json!(5); "draft 4 boolean minimum")]
+    #[test_case(Draft::Draft6, &json!({"exclusiveMaximum": 5}), &json!(5); "draft 6 numeric maximum")]
+    #[test_case(Draft::Draft202012, &json!({"exclusiveMinimum": 5}), &json!(5); "draft 2020-12 numeric minimum")]
+    fn exclusive_limit_forms(draft: Draft, schema: &Value, instance: &Value) {
+        let validator = crate::options()
+            .with_draft(draft)
+            .build(schema)
+            .expect("Valid schema");
+        assert!(!validator.is_valid(instance));
+    }
+
+    #[test_case(Draft::Draft4, &json!({"exclusiveMaximum": true}), InvalidExclusiveLimitReason::MissingLimit, r#""exclusiveMaximum": true requires a sibling "maximum" in Draft 4"#)]
+    #[test_case(Draft::Draft4, &json!({"exclusiveMinimum": false}), InvalidExclusiveLimitReason::MissingLimit, r#""exclusiveMinimum": false requires a sibling "minimum" in Draft 4"#)]
+    #[test_case(Draft::Draft4, &json!({"exclusiveMaximum": 5}), InvalidExclusiveLimitReason::NumberInDraft4, r#""exclusiveMaximum" must be a boolean in Draft 4, not 5. Use "maximum": 5 with "exclusiveMaximum": true instead"#)]
+    #[test_case(Draft::Draft4, &json!({"minimum": 1, "exclusiveMinimum": 5}), InvalidExclusiveLimitReason::NumberInDraft4, r#""exclusiveMinimum" must be a boolean in Draft 4, not 5. Use "minimum": 5 with "exclusiveMinimum": true instead"#)]
+    #[test_case(Draft::Draft6, &json!({"maximum": 5, "exclusiveMaximum": true}), InvalidExclusiveLimitReason::BooleanAfterDraft4, r#""exclusiveMaximum" must be a number in Draft 6 and later, not true. Move the "maximum" value to "exclusiveMaximum" instead"#)]
+    #[test_case(Draft::Draft7, &json!({"exclusiveMinimum": false}), InvalidExclusiveLimitReason::BooleanAfterDraft4, r#""exclusiveMinimum" must be a number in Draft 6 and later, not false. Move the "minimum" value to "exclusiveMinimum" instead"#)]
+    #[test_case(Draft::Draft201909, &json!({"minimum": 5, "exclusiveMinimum": true}), InvalidExclusiveLimitReason::BooleanAfterDraft4, r#""exclusiveMinimum" must be a number in Draft 6 and later, not true. Move the "minimum" value to "exclusiveMinimum" instead"#)]
+    #[test_case(Draft::Draft202012, &json!({"exclusiveMaximum": true}), InvalidExclusiveLimitReason::BooleanAfterDraft4, r#""exclusiveMaximum" must be a number in Draft 6 and later, not true. Move the "maximum" value to "exclusiveMaximum" instead"#)]
+    fn invalid_exclusive_limit_forms(
+        draft: Draft,
+        schema: &Value,
+        expected: InvalidExclusiveLimitReason,
+        message: &str,
+    ) {
+        // The meta-schemas reject these too, check the compiled keywords on their own
+        let error = crate::options()
+            .with_draft(draft)
+            .without_schema_validation()
+            .build(schema)
+            .expect_err("Should fail");
+        let ValidationErrorKind::InvalidExclusiveLimit {
+            keyword, reason, ..
+        } = &error.kind
+        else {
+            panic!("Unexpected error: {:?}", error.kind)
+        };
+        assert_eq!(*reason, expected);
+        assert_eq!(error.instance_path.as_str(), format!("/{keyword}"));
+        assert_eq!(error.to_string(), message);
+        // And the meta-schema still rejects the schema
+        assert!(crate::options().with_draft(draft).build(schema).is_err());
+    }
+
+    #[test_case(Draft::Draft202012, &json!({"allOf": 1}), "/properties/foo/allOf", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"anyOf": 1}), "/properties/foo/anyOf", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"oneOf": 1}), "/properties/foo/oneOf", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"not": 1}), "/properties/foo/not", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"contains": 1}), "/properties/foo/contains", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"enum": 1}), "/properties/foo/enum", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"prefixItems": 1}), "/properties/foo/prefixItems", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"properties": 1}), "/properties/foo/properties", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"additionalProperties": false, "properties": 1}), "/properties/foo/properties", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"additionalProperties": 1}), "/properties/foo/additionalProperties", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"patternProperties": 1}), "/properties/foo/patternProperties", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"additionalProperties": false, "patternProperties": 1}), "/properties/foo/patternProperties", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"patternProperties": {"[": {}}}), "/properties/foo/patternProperties/[", &json!("["))]
+    #[test_case(Draft::Draft202012, &json!({"patternProperties": {"[": {}, "a": {}}}), "/properties/foo/patternProperties/[", &json!("["))]
+    #[test_case(Draft::Draft202012, &json!({"additionalProperties": false, "patternProperties": {"[": {}}}), "/properties/foo/patternProperties/[", &json!("["))]
+    #[test_case(Draft::Draft202012, &json!({"unevaluatedProperties": false, "patternProperties": {"[": {}}}), "/properties/foo/patternProperties/[", &json!("["))]
+    #[test_case(Draft::Draft202012, &json!({"unevaluatedProperties": 1}), "/properties/foo/unevaluatedProperties", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"unevaluatedItems": 1}), "/properties/foo/unevaluatedItems", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"maximum": "a"}), "/properties/foo/maximum", &json!("a"))]
+    #[test_case(Draft::Draft202012, &json!({"minimum": "a"}), "/properties/foo/minimum", &json!("a"))]
+    #[test_case(Draft::Draft202012, &json!({"exclusiveMaximum": "a"}), "/properties/foo/exclusiveMaximum", &json!("a"))]
+    #[test_case(Draft::Draft202012, &json!({"exclusiveMinimum": "a"}), "/properties/foo/exclusiveMinimum", &json!("a"))]
+    #[test_case(Draft::Draft202012, &json!({"multipleOf": "a"}), "/properties/foo/multipleOf", &json!("a"))]
+    #[test_case(Draft::Draft202012, &json!({"maxLength": -1}), "/properties/foo/maxLength", &json!(-1))]
+    #[test_case(Draft::Draft202012, &json!({"minItems": "a"}), "/properties/foo/minItems", &json!("a"))]
+    #[test_case(Draft::Draft202012, &json!({"pattern": 1}), "/properties/foo/pattern", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"pattern": "["}), "/properties/foo/pattern", &json!("["))]
+    #[test_case(Draft::Draft202012, &json!({"$ref": 1}), "/properties/foo/$ref", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"$dynamicRef": 1}), "/properties/foo/$dynamicRef", &json!(1))]
+    #[test_case(Draft::Draft201909, &json!({"$recursiveRef": 1}), "/properties/foo/$recursiveRef", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"type": 1}), "/properties/foo/type", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"type": "foo"}), "/properties/foo/type", &json!("foo"))]
+    #[test_case(Draft::Draft202012, &json!({"type": [1]}), "/properties/foo/type", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"type": [1, "string"]}), "/properties/foo/type", &json!(1))]
+    #[test_case(Draft::Draft4, &json!({"type": 1}), "/properties/foo/type", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"required": 1}), "/properties/foo/required", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"required": ["a", 1]}), "/properties/foo/required", &json!(1))]
+    #[test_case(Draft::Draft7, &json!({"additionalItems": false, "items": 1}), "/properties/foo/items", &json!(1))]
+    #[test_case(Draft::Draft7, &json!({"dependencies": 1}), "/properties/foo/dependencies", &json!(1))]
+    #[test_case(Draft::Draft7, &json!({"dependencies": {"a": [1]}}), "/properties/foo/dependencies/a", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"dependentRequired": 1}), "/properties/foo/dependentRequired", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"dependentRequired": {"a": 1}}), "/properties/foo/dependentRequired/a", &json!(1))]
+    #[test_case(Draft::Draft202012, &json!({"dependentSchemas": 1}), "/properties/foo/dependentSchemas", &json!(1))]
+    fn compile_error_location(draft: Draft, subschema: &Value, location: &str, instance: &Value) {
+        // The meta-schemas reject these, check the compiled keywords on their own
+        let schema = json!({"properties": {"foo": subschema}});
+        let error = crate::options()
+            .with_draft(draft)
+            .without_schema_validation()
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), location);
+        assert_eq!(&*error.instance, instance);
+    }
 }
@@ -1,10 +1,11 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{helpers, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
+    value,
 };
 use fraction::{BigFraction, BigUint};
 use serde_json::{Map, Value};
@@ -24,26 +25,37 @@ impl MultipleOfFloatValidator {
     }
 }
 
+impl MultipleOfFloatValidator {
+    #[inline]
+    fn is_multiple(&self, item: f64) -> bool {
+        let remainder = (item / self.multiple_of) % 1.;
+        if remainder.is_nan() {
+            // Involves heap allocations via the underlying `BigUint` type
+            let fraction = BigFraction::from(item) / BigFraction::from(self.multiple_of);
+            if let Some(denom) = fraction.denom() {
+                denom == &BigUint::from(1_u8)
+            } else {
+                true
+            }
+        } else {
+            remainder < f64::EPSILON
+        }
+    }
+}
+
 impl Validate for MultipleOfFloatValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
-            let item = item.as_f64().expect("Always valid");
-            let remainder = (item / self.multiple_of) % 1.;
-            if remainder.is_nan() {
-                // Involves heap allocations via the underlying `BigUint` type
-                let fraction = BigFraction::from(item) / BigFraction::from(self.multiple_of);
-                if let Some(denom) = fraction.denom() {
-                    denom == &BigUint::from(1_u8)
-                } else {
-                    true
-                }
-            } else {
-                remainder < f64::EPSILON
-            }
+            self.is_multiple(helpers::to_f64(item))
         } else {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .as_f64()
+            .map_or(true, |item| self.is_multiple(item))
+    }
 
     fn validate<'i>(
         &self,
@@ -80,7 +92,7 @@ impl MultipleOfIntegerValidator {
 impl Validate for MultipleOfIntegerValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
-            let item = item.as_f64().expect("Always valid");
+            let item = helpers::to_f64(item);
             // As the divisor has its fractional part as zero, then any value with a non-zero
             // fractional part can't be a multiple of this divisor, therefore it is short-circuited
             item.fract() == 0. && (item % self.multiple_of) == 0.
@@ -88,6 +100,11 @@ impl Validate for MultipleOfIntegerValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.as_f64().map_or(true, |item| {
+            item.fract() == 0. && (item % self.multiple_of) == 0.
+        })
+    }
 
     fn validate<'i>(
         &self,
@@ -113,7 +130,7 @@ pub(crate) fn compile<'a>(
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
     if let Value::Number(multiple_of) = schema {
-        let multiple_of = multiple_of.as_f64().expect("Always valid");
+        let multiple_of = helpers::to_f64(multiple_of);
         let location = ctx.location().join("multipleOf");
         if multiple_of.fract() == 0. {
             Some(MultipleOfIntegerValidator::compile(multiple_of, location))
@@ -123,7 +140,7 @@ pub(crate) fn compile<'a>(
     } else {
         Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("multipleOf"),
             schema,
             PrimitiveType::Number,
         )))
@@ -1,6 +1,12 @@
 use crate::{
-    compiler, error::ValidationError, keywords::CompilationResult, node::SchemaNode,
-    paths::LazyLocation, validator::Validate,
+    compiler,
+    error::{NotMatch, ValidationError},
+    keywords::CompilationResult,
+    node::SchemaNode,
+    output::BasicOutput,
+    paths::{LazyLocation, Segment},
+    validator::{Validate, ValidationScratch},
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -8,6 +14,8 @@ pub(crate) struct NotValidator {
     // needed only for error representation
     original: Value,
     node: SchemaNode,
+    // Whether to tell why the negated schema matched
+    verbose: bool,
 }
 
 impl NotValidator {
@@ -17,6 +25,36 @@ impl NotValidator {
         Ok(Box::new(NotValidator {
             original: schema.clone(),
             node: compiler::compile(&ctx, ctx.as_resource_ref(schema))?,
+            verbose: ctx.config().are_errors_verbose(),
+        }))
+    }
+
+    fn explain(&self, instance: &Value, location: &LazyLocation) -> Option<Box<NotMatch>> {
+        if !self.verbose {
+            return None;
+        }
+        let annotations = match self.node.apply_rooted(instance, location) {
+            BasicOutput::Valid(units) => units
+                .iter()
+                .map(|unit| (unit.keyword_location().clone(), unit.value().into_owned()))
+                .collect(),
+            BasicOutput::Invalid(_) => Vec::new(),
+        };
+        // Branches left out during compilation are not among the compiled ones, so the index is
+        // taken from the location of the matching branch
+        let any_of_branch = self
+            .node
+            .any_of_branches()
+            .iter()
+            .find(|branch| branch.is_valid(instance))
+            .and_then(|branch| match branch.location().typed_segments().last() {
+                Some(Segment::Index(idx)) => Some(idx),
+                _ => None,
+            });
+        Some(Box::new(NotMatch {
+            location: self.node.location().clone(),
+            any_of_branch,
+            annotations,
         }))
     }
 }
@@ -25,13 +63,16 @@ impl Validate for NotValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         !self.node.is_valid(instance)
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        !self.node.is_valid_value(instance)
+    }
 
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.is_valid(instance) {
+        if !self.node.is_valid_at(instance, location)? {
             Ok(())
         } else {
             Err(ValidationError::not(
@@ -39,9 +80,30 @@ impl Validate for NotValidator {
                 location.into(),
                 instance,
                 self.original.clone(),
+                self.explain(instance, location),
             ))
         }
     }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        match scratch.is_valid(&self.node, instance, location) {
+            Ok(true) => errors.push(ValidationError::not(
+                self.node.location().clone(),
+                location.into(),
+                instance,
+                self.original.clone(),
+                self.explain(instance, location),
+            )),
+            Ok(false) => {}
+            Err(error) => errors.push(error),
+        }
+    }
 }
 
 #[inline]
@@ -55,8 +117,9 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
-    use serde_json::json;
+    use crate::{error::ValidationErrorKind, tests_util};
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[test]
     fn location() {
@@ -66,4 +129,83 @@ mod tests {
             "/not",
         )
     }
+
+    #[test_case(&json!({"not": {"type": "string"}}), &json!("foo"), None, "/not")]
+    #[test_case(&json!({"not": {"anyOf": [{"type": "string"}, {"type": "integer"}]}}), &json!("foo"), Some(0), "/not/anyOf/0")]
+    #[test_case(&json!({"not": {"anyOf": [{"type": "string"}, {"type": "integer"}]}}), &json!(1), Some(1), "/not/anyOf/1")]
+    #[test_case(&json!({"properties": {"a": {"not": {"anyOf": [false, true]}}}}), &json!({"a": 1}), Some(1), "/properties/a/not/anyOf/1")]
+    fn verbose(schema: &Value, instance: &Value, branch: Option<usize>, expected: &str) {
+        let validator = crate::options()
+            .with_verbose_errors(true)
+            .build(schema)
+            .expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        let ValidationErrorKind::Not {
+            matched: Some(matched),
+            ..
+        } = &error.kind
+        else {
+            panic!("Expected details on the match, got {:?}", error.kind)
+        };
+        assert_eq!(matched.any_of_branch, branch);
+        assert_eq!(matched.matched_location().as_str(), expected);
+    }
+
+    #[test]
+    fn verbose_compiles_branches_once() {
+        // The root, `not` and both branches
+        let schema = json!({"not": {"anyOf": [{"type": "string"}, {"type": "integer"}]}});
+        let validator = crate::options()
+            .with_verbose_errors(true)
+            .with_max_compiled_nodes(4)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.validate(&json!(1)).is_err());
+    }
+
+    #[test]
+    fn verbose_annotations() {
+        let schema = json!({"not": {"properties": {"a": true}, "title": "Forbidden"}});
+        let validator = crate::options()
+            .with_verbose_errors(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        let instance = json!({"a": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        let ValidationErrorKind::Not {
+            matched: Some(matched),
+            ..
+        } = &error.kind
+        else {
+            panic!("Expected details on the match, got {:?}", error.kind)
+        };
+        let annotations: Vec<_> = matched
+            .annotations
+            .iter()
+            .map(|(location, value)| (location.as_str(), value))
+            .collect();
+        assert_eq!(
+            annotations,
+            [
+                ("/not", &json!({"title": "Forbidden"})),
+                ("/not/properties", &json!(["a"]))
+            ]
+        );
+    }
+
+    #[test]
+    fn not_verbose_by_default() {
+        let validator =
+            crate::validator_for(&json!({"not": {"type": "string"}})).expect("Invalid schema");
+        let instance = json!("foo");
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::Not { matched: None, .. }
+        ));
+        assert_eq!(
+            error.to_string(),
+            r#"{"type":"string"} is not allowed for "foo""#
+        );
+    }
 }
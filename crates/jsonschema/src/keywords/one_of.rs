@@ -1,12 +1,13 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{BoxedValidator, CompilationResult},
     node::SchemaNode,
     output::BasicOutput,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -17,27 +18,21 @@ pub(crate) struct OneOfValidator {
 
 impl OneOfValidator {
     #[inline]
-    pub(crate) fn compile<'a>(ctx: &compiler::Context, schema: &'a Value) -> CompilationResult<'a> {
-        if let Value::Array(items) = schema {
-            let ctx = ctx.new_at_location("oneOf");
-            let mut schemas = Vec::with_capacity(items.len());
-            for (idx, item) in items.iter().enumerate() {
-                let ctx = ctx.new_at_location(idx);
-                let node = compiler::compile(&ctx, ctx.as_resource_ref(item))?;
-                schemas.push(node)
-            }
-            Ok(Box::new(OneOfValidator {
-                schemas,
-                location: ctx.location().clone(),
-            }))
-        } else {
-            Err(ValidationError::single_type_error(
-                Location::new(),
-                ctx.location().clone(),
-                schema,
-                PrimitiveType::Array,
-            ))
+    pub(crate) fn compile<'a>(
+        ctx: &compiler::Context,
+        items: impl ExactSizeIterator<Item = (usize, &'a Value)>,
+    ) -> CompilationResult<'a> {
+        let ctx = ctx.new_at_location("oneOf");
+        let mut schemas = Vec::with_capacity(items.len());
+        for (idx, item) in items {
+            let ctx = ctx.new_at_location(idx);
+            let node = compiler::compile(&ctx, ctx.as_resource_ref(item))?;
+            schemas.push(node)
         }
+        Ok(OneOfValidator::boxed(schemas, ctx.location().clone()))
+    }
+    pub(crate) fn boxed(schemas: Vec<SchemaNode>, location: Location) -> BoxedValidator {
+        Box::new(OneOfValidator { schemas, location })
     }
 
     fn get_first_valid(&self, instance: &Value) -> Option<usize> {
@@ -61,6 +56,44 @@ impl OneOfValidator {
             .skip(idx + 1)
             .any(|n| n.is_valid(instance))
     }
+
+    /// How many subschemas are valid according to `is_valid`, counting no further than two.
+    fn count_valid<'i>(
+        &self,
+        mut is_valid: impl FnMut(&SchemaNode) -> Result<bool, ValidationError<'i>>,
+    ) -> Result<usize, ValidationError<'i>> {
+        let mut count = 0;
+        for node in &self.schemas {
+            if is_valid(node)? {
+                count += 1;
+                if count == 2 {
+                    break;
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    fn error<'i>(
+        &self,
+        count: usize,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Option<ValidationError<'i>> {
+        match count {
+            0 => Some(ValidationError::one_of_not_valid(
+                self.location.clone(),
+                location.into(),
+                instance,
+            )),
+            1 => None,
+            _ => Some(ValidationError::one_of_multiple_valid(
+                self.location.clone(),
+                location.into(),
+                instance,
+            )),
+        }
+    }
 }
 
 impl Validate for OneOfValidator {
@@ -68,27 +101,31 @@ impl Validate for OneOfValidator {
         let first_valid_idx = self.get_first_valid(instance);
         first_valid_idx.map_or(false, |idx| !self.are_others_valid(instance, idx))
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        let mut valid = self.schemas.iter().filter(|n| n.is_valid_value(instance));
+        valid.next().is_some() && valid.next().is_none()
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        let first_valid_idx = self.get_first_valid(instance);
-        if let Some(idx) = first_valid_idx {
-            if self.are_others_valid(instance, idx) {
-                return Err(ValidationError::one_of_multiple_valid(
-                    self.location.clone(),
-                    location.into(),
-                    instance,
-                ));
-            }
-            Ok(())
-        } else {
-            Err(ValidationError::one_of_not_valid(
-                self.location.clone(),
-                location.into(),
-                instance,
-            ))
+        let count = self.count_valid(|node| node.is_valid_at(instance, location))?;
+        match self.error(count, instance, location) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        match self.count_valid(|node| scratch.is_valid(node, instance, location)) {
+            Ok(count) => errors.extend(self.error(count, instance, location)),
+            Err(error) => errors.push(error),
         }
     }
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
@@ -119,7 +156,16 @@ pub(crate) fn compile<'a>(
     _: &'a Map<String, Value>,
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
-    Some(OneOfValidator::compile(ctx, schema))
+    if let Value::Array(items) = schema {
+        Some(OneOfValidator::compile(ctx, items.iter().enumerate()))
+    } else {
+        Some(Err(ValidationError::single_type_error(
+            Location::new(),
+            ctx.location().join("oneOf"),
+            schema,
+            PrimitiveType::Array,
+        )))
+    }
 }
 
 #[cfg(test)]
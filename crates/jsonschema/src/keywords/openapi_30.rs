@@ -0,0 +1,254 @@
+//! Keywords that differ in schema objects of OpenAPI 3.0 from Draft 4.
+use crate::{
+    compiler,
+    error::ValidationError,
+    keywords::{
+        any_of, discriminator, items, legacy::type_draft_4, one_of, BuiltinKeyword,
+        CompilationResult, CompileFunc, Keyword,
+    },
+    paths::Location,
+    primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
+};
+use serde_json::{json, Map, Value};
+use std::convert::TryFrom;
+
+/// How `keyword` is compiled in OpenAPI 3.0 schemas, if it differs from Draft 4.
+pub(crate) fn get<'a>(keyword: &str) -> Option<(Keyword, CompileFunc<'a>)> {
+    match keyword {
+        "type" => Some((BuiltinKeyword::Type.into(), compile_type)),
+        "nullable" => Some((Keyword::custom("nullable"), compile_nullable)),
+        "items" => Some((BuiltinKeyword::Items.into(), compile_items)),
+        "additionalItems" => Some((
+            BuiltinKeyword::AdditionalItems.into(),
+            compile_additional_items,
+        )),
+        "dependencies" => Some((BuiltinKeyword::Dependencies.into(), compile_dependencies)),
+        "patternProperties" => Some((
+            BuiltinKeyword::PatternProperties.into(),
+            compile_pattern_properties,
+        )),
+        "oneOf" => Some((BuiltinKeyword::OneOf.into(), compile_one_of)),
+        "anyOf" => Some((BuiltinKeyword::AnyOf.into(), compile_any_of)),
+        _ => None,
+    }
+}
+
+/// A single type, which also allows `null` if the schema is `nullable`.
+fn compile_type<'a>(
+    ctx: &compiler::Context,
+    parent: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    let location = ctx.location().join("type");
+    let Value::String(name) = schema else {
+        return Some(Err(ValidationError::single_type_error(
+            Location::new(),
+            location,
+            schema,
+            PrimitiveType::String,
+        )));
+    };
+    let primitive_type = match PrimitiveType::try_from(name.as_str()) {
+        Ok(PrimitiveType::Null) | Err(()) => {
+            return Some(Err(ValidationError::enumeration(
+                Location::new(),
+                location,
+                schema,
+                &json!(["array", "boolean", "integer", "number", "object", "string"]),
+            )))
+        }
+        Ok(primitive_type) => primitive_type,
+    };
+    if parent.get("nullable") == Some(&Value::Bool(true)) {
+        let types = PrimitiveTypesBitMap::new()
+            .add_type(primitive_type)
+            .add_type(PrimitiveType::Null);
+        Some(Ok(type_draft_4::MultipleTypesValidator::boxed(
+            types, location,
+        )))
+    } else {
+        type_draft_4::compile(ctx, parent, schema)
+    }
+}
+
+/// `nullable` only affects `type` and must be a boolean.
+fn compile_nullable<'a>(
+    ctx: &compiler::Context,
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    if schema.is_boolean() {
+        None
+    } else {
+        Some(Err(ValidationError::single_type_error(
+            Location::new(),
+            ctx.location().join("nullable"),
+            schema,
+            PrimitiveType::Boolean,
+        )))
+    }
+}
+
+/// `items` must be a single schema.
+fn compile_items<'a>(
+    ctx: &'a compiler::Context,
+    parent: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    if schema.is_array() {
+        Some(Err(ValidationError::single_type_error(
+            Location::new(),
+            ctx.location().join("items"),
+            schema,
+            PrimitiveType::Object,
+        )))
+    } else {
+        items::compile(ctx, parent, schema)
+    }
+}
+
+/// `oneOf` selects a single subschema by the `discriminator` of the schema, if it has one.
+fn compile_one_of<'a>(
+    ctx: &compiler::Context,
+    parent: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    match parent.get("discriminator") {
+        Some(discriminator) => discriminator::compile(ctx, "oneOf", discriminator, schema),
+        None => one_of::compile(ctx, parent, schema),
+    }
+}
+
+/// `anyOf` selects a single subschema by the `discriminator` of the schema, if it has one.
+fn compile_any_of<'a>(
+    ctx: &compiler::Context,
+    parent: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    match parent.get("discriminator") {
+        Some(discriminator) => discriminator::compile(ctx, "anyOf", discriminator, schema),
+        None => any_of::compile(ctx, parent, schema),
+    }
+}
+
+fn compile_additional_items<'a>(
+    ctx: &compiler::Context,
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    unsupported(ctx, "additionalItems", schema)
+}
+
+fn compile_dependencies<'a>(
+    ctx: &compiler::Context,
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    unsupported(ctx, "dependencies", schema)
+}
+
+fn compile_pattern_properties<'a>(
+    ctx: &compiler::Context,
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    unsupported(ctx, "patternProperties", schema)
+}
+
+fn unsupported<'a>(
+    ctx: &compiler::Context,
+    keyword: &str,
+    schema: &'a Value,
+) -> Option<CompilationResult<'a>> {
+    Some(Err(ValidationError::custom(
+        Location::new(),
+        ctx.location().join(keyword),
+        schema,
+        format!("`{keyword}` is not supported in OpenAPI 3.0 schemas"),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Dialect;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn validator(schema: &Value) -> crate::Validator {
+        crate::options()
+            .with_dialect(Dialect::OpenApi30)
+            .build(schema)
+            .expect("Invalid schema")
+    }
+
+    #[test_case(&json!({"type": "string", "nullable": true}), &[json!("a"), json!(null)], &[json!(1)]; "nullable")]
+    #[test_case(&json!({"type": "string", "nullable": false}), &[json!("a")], &[json!(null)]; "not nullable")]
+    #[test_case(&json!({"type": "integer"}), &[json!(1)], &[json!(null), json!(1.5)]; "nullable by default")]
+    #[test_case(&json!({"nullable": true}), &[json!(null), json!(1)], &[]; "nullable without type")]
+    #[test_case(&json!({"type": "object", "nullable": true, "required": ["a"]}), &[json!(null), json!({"a": 1})], &[json!({})]; "null skips object keywords")]
+    #[test_case(&json!({"type": "string", "nullable": true, "enum": ["a"]}), &[json!("a")], &[json!(null), json!("b")]; "enum restricts null")]
+    #[test_case(&json!({"properties": {"a": {"type": "number", "nullable": true}}}), &[json!({"a": null}), json!({"a": 1.5})], &[json!({"a": "1"})]; "nested")]
+    #[test_case(&json!({"type": "array", "items": {"type": "string", "nullable": true}}), &[json!(["a", null])], &[json!([1])]; "items")]
+    #[test_case(&json!({"maximum": 5, "exclusiveMaximum": true}), &[json!(4)], &[json!(5)]; "boolean exclusive maximum")]
+    #[test_case(&json!({"minimum": 5, "exclusiveMinimum": false}), &[json!(5)], &[json!(4)]; "boolean exclusive minimum")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "maximum": 5, "exclusiveMaximum": true}), &[json!(4)], &[json!(5)]; "draft 4 regardless of schema")]
+    #[test_case(&json!({"allOf": [{"type": "string", "nullable": true}, {"maxLength": 1}]}), &[json!(null), json!("a")], &[json!("ab")]; "all of")]
+    fn instances(schema: &Value, valid: &[Value], invalid: &[Value]) {
+        let validator = validator(schema);
+        for instance in valid {
+            assert!(validator.is_valid(instance), "{instance} should be valid");
+            assert!(validator.validate(instance).is_ok());
+        }
+        for instance in invalid {
+            assert!(
+                !validator.is_valid(instance),
+                "{instance} should be invalid"
+            );
+            assert!(validator.validate(instance).is_err());
+        }
+    }
+
+    #[test]
+    fn optimizations_keep_nullable() {
+        let schema = json!({"allOf": [{"type": "string", "nullable": true}]});
+        let validator = crate::options()
+            .with_dialect(Dialect::OpenApi30)
+            .with_optimization_level(crate::OptimizationLevel::Prune)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(null)));
+    }
+
+    #[test]
+    fn nullable_error() {
+        let validator = validator(&json!({"type": "string", "nullable": true}));
+        let instance = json!(1);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.to_string(), r#"1 is not of types "null", "string""#);
+        assert_eq!(error.schema_path.as_str(), "/type");
+    }
+
+    #[test_case(&json!({"type": ["string", "null"]}), "/type", r#"["string","null"] is not of type "string""#; "type array")]
+    #[test_case(&json!({"type": "null"}), "/type", r#""null" is not one of ["array","boolean","integer","number","object","string"]"#; "null type")]
+    #[test_case(&json!({"properties": {"a": {"type": "strin"}}}), "/properties/a/type", r#""strin" is not valid under any of the schemas listed in the 'anyOf' keyword"#; "unknown type")]
+    #[test_case(&json!({"type": "string", "nullable": "yes"}), "/nullable", r#""yes" is not of type "boolean""#; "nullable not boolean")]
+    #[test_case(&json!({"items": [{"type": "string"}]}), "/items", r#"[{"type":"string"}] is not of type "object""#; "items array")]
+    #[test_case(&json!({"patternProperties": {"^a": {}}}), "/patternProperties", "`patternProperties` is not supported in OpenAPI 3.0 schemas"; "pattern properties")]
+    #[test_case(&json!({"items": {}, "additionalItems": false}), "/additionalItems", "`additionalItems` is not supported in OpenAPI 3.0 schemas"; "additional items")]
+    #[test_case(&json!({"dependencies": {"a": ["b"]}}), "/dependencies", "`dependencies` is not supported in OpenAPI 3.0 schemas"; "dependencies")]
+    fn compile_errors(schema: &Value, location: &str, message: &str) {
+        let error = crate::options()
+            .with_dialect(Dialect::OpenApi30)
+            .build(schema)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), location);
+        assert_eq!(error.to_string(), message);
+    }
+
+    #[test]
+    fn json_schema_unchanged() {
+        let validator = crate::draft4::new(&json!({"type": ["string", "null"], "nullable": false}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(null)));
+    }
+}
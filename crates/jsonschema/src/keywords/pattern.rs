@@ -4,7 +4,9 @@ use crate::{
     keywords::CompilationResult,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
+    regex::Regex,
     validator::Validate,
+    value,
 };
 use ahash::AHashMap;
 use once_cell::sync::Lazy;
@@ -15,7 +17,7 @@ use std::{collections::VecDeque, sync::Mutex};
 static REGEX_CACHE: Lazy<Mutex<LruCache>> = Lazy::new(|| Mutex::new(LruCache::new(10)));
 
 struct LruCache {
-    map: AHashMap<String, fancy_regex::Regex>,
+    map: AHashMap<String, Regex>,
     queue: VecDeque<String>,
     capacity: usize,
 }
@@ -29,7 +31,7 @@ impl LruCache {
         }
     }
 
-    fn get(&mut self, key: &str) -> Option<&fancy_regex::Regex> {
+    fn get(&mut self, key: &str) -> Option<&Regex> {
         if let Some(value) = self.map.get(key) {
             let index = self.queue.iter().position(|x| x == key).unwrap();
             let k = self.queue.remove(index).unwrap();
@@ -40,7 +42,7 @@ impl LruCache {
         }
     }
 
-    fn insert(&mut self, key: String, value: fancy_regex::Regex) -> Option<fancy_regex::Regex> {
+    fn insert(&mut self, key: String, value: Regex) -> Option<Regex> {
         if self.map.len() >= self.capacity && !self.map.contains_key(&key) {
             if let Some(lru_key) = self.queue.pop_front() {
                 self.map.remove(&lru_key);
@@ -59,7 +61,7 @@ impl LruCache {
 
 pub(crate) struct PatternValidator {
     original: String,
-    pattern: fancy_regex::Regex,
+    pattern: Regex,
     location: Location,
 }
 
@@ -71,18 +73,18 @@ impl PatternValidator {
     ) -> CompilationResult<'a> {
         match pattern {
             Value::String(item) => {
+                ctx.record_pattern(item);
                 let mut cache = REGEX_CACHE.lock().expect("Lock is poisoned");
                 let pattern = if let Some(regex) = cache.get(item) {
                     regex.clone()
                 } else {
-                    let regex = match ecma::to_rust_regex(item)
-                        .map(|pattern| fancy_regex::Regex::new(&pattern))
+                    let regex = match ecma::to_rust_regex(item).map(|pattern| Regex::new(&pattern))
                     {
                         Ok(Ok(r)) => r,
                         _ => {
                             return Err(ValidationError::format(
                                 Location::new(),
-                                ctx.location().clone(),
+                                ctx.location().join("pattern"),
                                 pattern,
                                 "regex",
                             ))
@@ -99,7 +101,7 @@ impl PatternValidator {
             }
             _ => Err(ValidationError::single_type_error(
                 Location::new(),
-                ctx.location().clone(),
+                ctx.location().join("pattern"),
                 pattern,
                 PrimitiveType::String,
             )),
@@ -144,6 +146,11 @@ impl Validate for PatternValidator {
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .as_str()
+            .map_or(true, |item| self.pattern.is_match(item).unwrap_or(false))
+    }
 }
 
 #[inline]
@@ -161,6 +168,7 @@ mod tests {
     use serde_json::json;
     use test_case::test_case;
 
+    #[cfg(feature = "fancy-regex")]
     #[test_case("^(?!eo:)", "eo:bands", false)]
     #[test_case("^(?!eo:)", "proj:epsg", true)]
     fn negative_lookbehind_match(pattern: &str, text: &str, is_matching: bool) {
@@ -3,12 +3,13 @@ use crate::{
     error::{no_error, ErrorIterator, ValidationError},
     keywords::CompilationResult,
     node::SchemaNode,
-    output::BasicOutput,
+    output::{BasicOutput, Evaluated},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    regex::Regex,
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
-use fancy_regex::Regex;
 use serde_json::{Map, Value};
 
 pub(crate) struct PatternPropertiesValidator {
@@ -25,15 +26,14 @@ impl PatternPropertiesValidator {
         let mut patterns = Vec::with_capacity(map.len());
         for (pattern, subschema) in map {
             let pctx = ctx.new_at_location(pattern.as_str());
+            ctx.record_pattern(pattern);
             patterns.push((
                 match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
                     Ok(Ok(r)) => r,
                     _ => {
-                        return Err(ValidationError::format(
-                            Location::new(),
-                            ctx.location().clone(),
-                            subschema,
-                            "regex",
+                        return Err(ValidationError::invalid_pattern(
+                            pctx.location().clone(),
+                            pattern,
                         ))
                     }
                 },
@@ -66,6 +66,24 @@ impl Validate for PatternPropertiesValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            for (re, node) in &self.patterns {
+                for (key, value) in item {
+                    if re.is_match(key).unwrap_or(false) {
+                        node.collect_errors(value, &location.push(key.as_str()), errors, scratch);
+                    }
+                }
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             self.patterns.iter().all(move |(re, node)| {
@@ -77,6 +95,14 @@ impl Validate for PatternPropertiesValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.patterns.iter().all(move |(re, node)| {
+            instance
+                .object_iter()
+                .filter(move |(key, _)| re.is_match(key).unwrap_or(false))
+                .all(move |(_key, value)| node.is_valid_value(value))
+        })
+    }
 
     fn validate<'i>(
         &self,
@@ -108,8 +134,13 @@ impl Validate for PatternPropertiesValidator {
                     }
                 }
             }
+            if self.patterns.len() > 1 {
+                // A property matching several patterns is evaluated once
+                matched_propnames.sort_unstable();
+                matched_propnames.dedup();
+            }
             let mut result: PartialApplication = sub_results.into();
-            result.annotate(Value::from(matched_propnames).into());
+            result.annotate(Evaluated::Properties(matched_propnames).into());
             result
         } else {
             PartialApplication::valid_empty()
@@ -131,16 +162,15 @@ impl SingleValuePatternPropertiesValidator {
     ) -> CompilationResult<'a> {
         let kctx = ctx.new_at_location("patternProperties");
         let pctx = kctx.new_at_location(pattern);
+        ctx.record_pattern(pattern);
         Ok(Box::new(SingleValuePatternPropertiesValidator {
             pattern: {
                 match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
                     Ok(Ok(r)) => r,
                     _ => {
-                        return Err(ValidationError::format(
-                            Location::new(),
-                            kctx.location().clone(),
-                            schema,
-                            "regex",
+                        return Err(ValidationError::invalid_pattern(
+                            pctx.location().clone(),
+                            pattern,
                         ))
                     }
                 }
@@ -168,6 +198,23 @@ impl Validate for SingleValuePatternPropertiesValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            for (key, value) in item {
+                if self.pattern.is_match(key).unwrap_or(false) {
+                    self.node
+                        .collect_errors(value, &location.push(key.as_str()), errors, scratch);
+                }
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             item.iter()
@@ -177,6 +224,12 @@ impl Validate for SingleValuePatternPropertiesValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .object_iter()
+            .filter(move |(key, _)| self.pattern.is_match(key).unwrap_or(false))
+            .all(move |(_key, value)| self.node.is_valid_value(value))
+    }
 
     fn validate<'i>(
         &self,
@@ -205,7 +258,7 @@ impl Validate for SingleValuePatternPropertiesValidator {
                 }
             }
             let mut result: PartialApplication = outputs.into();
-            result.annotate(Value::from(matched_propnames).into());
+            result.annotate(Evaluated::Properties(matched_propnames).into());
             result
         } else {
             PartialApplication::valid_empty()
@@ -235,7 +288,7 @@ pub(crate) fn compile<'a>(
             } else {
                 Some(Err(ValidationError::single_type_error(
                     Location::new(),
-                    ctx.location().clone(),
+                    ctx.location().join("patternProperties"),
                     schema,
                     PrimitiveType::Object,
                 )))
@@ -1,10 +1,12 @@
 use crate::{
     compiler,
     error::{no_error, ErrorIterator, ValidationError},
-    node::SchemaNode,
+    node::{DeferredChecks, SchemaNode},
+    output::Evaluated,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -48,6 +50,20 @@ impl Validate for PrefixItemsValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Array(items) = instance {
+            for (idx, (node, item)) in self.schemas.iter().zip(items.iter()).enumerate() {
+                node.collect_errors(item, &location.push(idx), errors, scratch);
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             self.schemas
@@ -58,6 +74,22 @@ impl Validate for PrefixItemsValidator {
             true
         }
     }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        if let Value::Array(items) = instance {
+            self.schemas
+                .iter()
+                .zip(items.iter())
+                .all(|(node, item)| checks.check(node, item))
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.schemas
+            .iter()
+            .zip(value::array_iter(instance))
+            .all(|(n, i)| n.is_valid_value(i))
+    }
 
     fn validate<'i>(
         &self,
@@ -88,13 +120,13 @@ impl Validate for PrefixItemsValidator {
                 // we must produce an annotation with the largest index of the underlying
                 // array which the subschema was applied. The value MAY be a boolean true if
                 // a subschema was applied to every index of the instance.
-                let schema_was_applied: Value = if results.len() == items.len() {
-                    true.into()
+                let evaluated = if results.len() == items.len() {
+                    Evaluated::AllItems
                 } else {
-                    max_index_applied.into()
+                    Evaluated::LargestIndex(max_index_applied)
                 };
                 let mut output: PartialApplication = results.into_iter().collect();
-                output.annotate(schema_was_applied.into());
+                output.annotate(evaluated.into());
                 return output;
             }
         }
@@ -113,7 +145,7 @@ pub(crate) fn compile<'a>(
     } else {
         Some(Err(ValidationError::single_type_error(
             Location::new(),
-            ctx.location().clone(),
+            ctx.location().join("prefixItems"),
             schema,
             PrimitiveType::Array,
         )))
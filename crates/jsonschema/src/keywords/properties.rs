@@ -2,11 +2,12 @@ use crate::{
     compiler,
     error::{no_error, ErrorIterator, ValidationError},
     keywords::CompilationResult,
-    node::SchemaNode,
-    output::BasicOutput,
+    node::{DeferredChecks, SchemaNode},
+    output::{BasicOutput, Evaluated},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
 use serde_json::{Map, Value};
 
@@ -32,7 +33,7 @@ impl PropertiesValidator {
             }
             _ => Err(ValidationError::single_type_error(
                 Location::new(),
-                ctx.location().clone(),
+                ctx.location().join("properties"),
                 schema,
                 PrimitiveType::Object,
             )),
@@ -61,6 +62,22 @@ impl Validate for PropertiesValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Value::Object(item) = instance {
+            for (name, node) in &self.properties {
+                if let Some(item) = item.get(name) {
+                    node.collect_errors(item, &location.push(name.as_str()), errors, scratch);
+                }
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             self.properties.iter().all(move |(name, node)| {
@@ -71,6 +88,22 @@ impl Validate for PropertiesValidator {
             true
         }
     }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        if let Value::Object(item) = instance {
+            self.properties
+                .iter()
+                .all(|(name, node)| item.get(name).map_or(true, |item| checks.check(node, item)))
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.properties.iter().all(move |(name, node)| {
+            instance
+                .object_get(name)
+                .map_or(true, |item| node.is_valid_value(item))
+        })
+    }
 
     fn validate<'i>(
         &self,
@@ -99,7 +132,7 @@ impl Validate for PropertiesValidator {
                 }
             }
             let mut application: PartialApplication = result.into();
-            application.annotate(Value::from(matched_props).into());
+            application.annotate(Evaluated::Properties(matched_props).into());
             application
         } else {
             PartialApplication::valid_empty()
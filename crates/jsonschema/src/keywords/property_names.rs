@@ -4,10 +4,25 @@ use crate::{
     keywords::CompilationResult,
     node::SchemaNode,
     paths::{LazyLocation, Location},
-    validator::{PartialApplication, Validate},
+    primitive_type::PrimitiveType,
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
 };
 use serde_json::{Map, Value};
 
+/// A property name viewed as a string instance, so the boolean path does not
+/// have to allocate a `Value::String` for every key.
+struct PropertyName<'a>(&'a str);
+
+impl value::Value for PropertyName<'_> {
+    fn primitive_type(&self) -> PrimitiveType {
+        PrimitiveType::String
+    }
+    fn as_str(&self) -> Option<&str> {
+        Some(self.0)
+    }
+}
+
 pub(crate) struct PropertyNamesObjectValidator {
     node: SchemaNode,
 }
@@ -51,16 +66,32 @@ impl Validate for PropertyNamesObjectValidator {
         }
     }
 
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        _scratch: &mut ValidationScratch,
+    ) {
+        // Errors are reported for the wrapped property names, skip building them when valid
+        if !self.is_valid(instance) {
+            errors.extend(self.iter_errors(instance, location));
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = &instance {
-            item.keys().all(move |key| {
-                let wrapper = Value::String(key.to_string());
-                self.node.is_valid(&wrapper)
-            })
+            item.keys()
+                .all(move |key| self.node.is_valid_value(&PropertyName(key)))
         } else {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance
+            .object_iter()
+            .all(move |(key, _)| self.node.is_valid_value(&PropertyName(key)))
+    }
 
     fn validate<'i>(
         &self,
@@ -121,6 +152,9 @@ impl Validate for PropertyNamesBooleanValidator {
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_len().map_or(true, |len| len == 0)
+    }
 
     fn validate<'i>(
         &self,
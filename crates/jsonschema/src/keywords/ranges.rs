@@ -0,0 +1,690 @@
+//! Bounds of the same kind on one schema, like `minimum` and `maximum`, checked together.
+//!
+//! Every bound keyword compiles to its own validator, so a schema with `minimum` and `maximum`
+//! would check whether the instance is a number twice. After a schema is compiled, its bounds
+//! are replaced by a single validator that does it once. Errors are still reported by each
+//! violated bound at its own location.
+use std::collections::VecDeque;
+
+use num_cmp::NumCmp;
+use referencing::Draft;
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    compiler,
+    error::{ErrorIterator, ValidationError},
+    keywords::{helpers, BoxedValidator, Keyword},
+    output::{AbsoluteLocation, ErrorDescription, OutputUnit},
+    paths::{LazyLocation, Location},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
+};
+
+const NUMERIC: [&str; 4] = ["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum"];
+const LENGTHS: [&str; 2] = ["minLength", "maxLength"];
+const ITEMS: [&str; 2] = ["minItems", "maxItems"];
+const PROPERTIES: [&str; 2] = ["minProperties", "maxProperties"];
+
+/// Replace bounds of the same kind among `validators` with a single validator, placed where
+/// the first of them was.
+pub(crate) fn fuse(
+    ctx: &compiler::Context,
+    schema: &Map<String, Value>,
+    validators: &mut Vec<(Keyword, BoxedValidator)>,
+) {
+    // Draft 4 bounds depend on the boolean `exclusiveMinimum` / `exclusiveMaximum`
+    if ctx.draft() != Draft::Draft4 {
+        if let Some(positions) = positions(validators, &NUMERIC) {
+            let bounds = positions
+                .iter()
+                .map(|idx| NumericBound::new(ctx, schema, validators[*idx].0.as_str()))
+                .collect();
+            let validator = NumericRangeValidator::new(bounds, ctx.absolute_location());
+            replace(validators, &positions, Box::new(validator));
+        }
+    }
+    for (measure, group) in [
+        (Measure::Length, &LENGTHS),
+        (Measure::Items, &ITEMS),
+        (Measure::Properties, &PROPERTIES),
+    ] {
+        if let Some(positions) = positions(validators, group) {
+            let bounds = positions
+                .iter()
+                .map(|idx| SizeBound::new(ctx, schema, validators[*idx].0.as_str()))
+                .collect();
+            let validator = SizeRangeValidator {
+                measure,
+                bounds,
+                absolute: ctx.absolute_location(),
+            };
+            replace(validators, &positions, Box::new(validator));
+        }
+    }
+}
+
+/// Positions of built-in validators for keywords in `group`, if there are at least two.
+fn positions(validators: &[(Keyword, BoxedValidator)], group: &[&str]) -> Option<Vec<usize>> {
+    let positions: Vec<_> = validators
+        .iter()
+        .enumerate()
+        .filter(|(_, (keyword, _))| {
+            matches!(keyword, Keyword::Buildin(_)) && group.contains(&keyword.as_str())
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+    (positions.len() > 1).then_some(positions)
+}
+
+fn replace(
+    validators: &mut Vec<(Keyword, BoxedValidator)>,
+    positions: &[usize],
+    validator: BoxedValidator,
+) {
+    validators[positions[0]].1 = validator;
+    for idx in positions[1..].iter().rev() {
+        validators.remove(*idx);
+    }
+}
+
+/// Report `errors` at the locations of the bounds that produced them.
+fn apply_bounds<'a, 'b, 'i>(
+    absolute: Option<&AbsoluteLocation>,
+    location: &LazyLocation,
+    errors: impl Iterator<Item = (&'b Location, ValidationError<'i>)>,
+) -> PartialApplication<'a> {
+    let instance_location: Location = location.into();
+    let child_results: VecDeque<_> = errors
+        .map(|(keyword_location, error)| {
+            OutputUnit::error_at(
+                absolute,
+                keyword_location.clone(),
+                instance_location.clone(),
+                ErrorDescription::from(error),
+            )
+        })
+        .collect();
+    if child_results.is_empty() {
+        PartialApplication::valid_empty()
+    } else {
+        PartialApplication::Invalid {
+            errors: Vec::new(),
+            child_results,
+        }
+    }
+}
+
+/// Run `$body` with `$item` bound to `$number` as its most precise primitive type.
+macro_rules! with_number {
+    ($number:expr, |$item:ident| $body:expr) => {
+        if let Some($item) = $number.as_u64() {
+            $body
+        } else if let Some($item) = $number.as_i64() {
+            $body
+        } else {
+            let $item = helpers::to_f64($number);
+            $body
+        }
+    };
+}
+
+#[derive(Clone, Copy)]
+enum Limit {
+    U64(u64),
+    I64(i64),
+    F64(f64),
+}
+
+impl Limit {
+    fn new(limit: &Number) -> Self {
+        with_number!(limit, |limit| limit.into())
+    }
+    /// Whether `item` is less than the limit.
+    fn is_above<T>(self, item: T) -> bool
+    where
+        T: Copy + NumCmp<u64> + NumCmp<i64> + NumCmp<f64>,
+    {
+        match self {
+            Limit::U64(limit) => NumCmp::num_lt(item, limit),
+            Limit::I64(limit) => NumCmp::num_lt(item, limit),
+            Limit::F64(limit) => NumCmp::num_lt(item, limit),
+        }
+    }
+    /// Whether `item` is greater than the limit.
+    fn is_below<T>(self, item: T) -> bool
+    where
+        T: Copy + NumCmp<u64> + NumCmp<i64> + NumCmp<f64>,
+    {
+        match self {
+            Limit::U64(limit) => NumCmp::num_gt(item, limit),
+            Limit::I64(limit) => NumCmp::num_gt(item, limit),
+            Limit::F64(limit) => NumCmp::num_gt(item, limit),
+        }
+    }
+    fn is_less_than(self, other: Limit) -> bool {
+        match self {
+            Limit::U64(limit) => other.is_above(limit),
+            Limit::I64(limit) => other.is_above(limit),
+            Limit::F64(limit) => other.is_above(limit),
+        }
+    }
+    fn value_lt(self, instance: &dyn value::Value) -> Option<bool> {
+        match self {
+            Limit::U64(limit) => value::num_lt(instance, limit),
+            Limit::I64(limit) => value::num_lt(instance, limit),
+            Limit::F64(limit) => value::num_lt(instance, limit),
+        }
+    }
+    fn value_gt(self, instance: &dyn value::Value) -> Option<bool> {
+        match self {
+            Limit::U64(limit) => value::num_gt(instance, limit),
+            Limit::I64(limit) => value::num_gt(instance, limit),
+            Limit::F64(limit) => value::num_gt(instance, limit),
+        }
+    }
+}
+
+impl From<u64> for Limit {
+    fn from(limit: u64) -> Self {
+        Limit::U64(limit)
+    }
+}
+
+impl From<i64> for Limit {
+    fn from(limit: i64) -> Self {
+        Limit::I64(limit)
+    }
+}
+
+impl From<f64> for Limit {
+    fn from(limit: f64) -> Self {
+        Limit::F64(limit)
+    }
+}
+
+/// A lower or upper limit.
+#[derive(Clone, Copy)]
+struct Edge {
+    limit: Limit,
+    exclusive: bool,
+}
+
+impl Edge {
+    /// Whether `item` is within this edge as a lower limit.
+    fn admits_above<T>(self, item: T) -> bool
+    where
+        T: Copy + NumCmp<u64> + NumCmp<i64> + NumCmp<f64>,
+    {
+        if self.exclusive {
+            self.limit.is_below(item)
+        } else {
+            !self.limit.is_above(item)
+        }
+    }
+    /// Whether `item` is within this edge as an upper limit.
+    fn admits_below<T>(self, item: T) -> bool
+    where
+        T: Copy + NumCmp<u64> + NumCmp<i64> + NumCmp<f64>,
+    {
+        if self.exclusive {
+            self.limit.is_above(item)
+        } else {
+            !self.limit.is_below(item)
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum NumericKind {
+    Minimum,
+    Maximum,
+    ExclusiveMinimum,
+    ExclusiveMaximum,
+}
+
+impl NumericKind {
+    const fn is_lower(self) -> bool {
+        matches!(self, NumericKind::Minimum | NumericKind::ExclusiveMinimum)
+    }
+}
+
+struct NumericBound {
+    kind: NumericKind,
+    edge: Edge,
+    limit_val: Value,
+    location: Location,
+}
+
+impl NumericBound {
+    fn new(ctx: &compiler::Context, schema: &Map<String, Value>, keyword: &str) -> Self {
+        let kind = match keyword {
+            "minimum" => NumericKind::Minimum,
+            "maximum" => NumericKind::Maximum,
+            "exclusiveMinimum" => NumericKind::ExclusiveMinimum,
+            _ => NumericKind::ExclusiveMaximum,
+        };
+        let limit_val = schema[keyword].clone();
+        let edge = Edge {
+            limit: Limit::new(limit_val.as_number().expect("Compiled as a bound")),
+            exclusive: matches!(
+                kind,
+                NumericKind::ExclusiveMinimum | NumericKind::ExclusiveMaximum
+            ),
+        };
+        NumericBound {
+            kind,
+            edge,
+            limit_val,
+            location: ctx.location().join(keyword),
+        }
+    }
+    fn is_valid(&self, item: &Number) -> bool {
+        if self.kind.is_lower() {
+            with_number!(item, |item| self.edge.admits_above(item))
+        } else {
+            with_number!(item, |item| self.edge.admits_below(item))
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        let limit = self.edge.limit;
+        match self.kind {
+            NumericKind::Minimum => limit.value_lt(instance).map_or(true, |lt| !lt),
+            NumericKind::Maximum => limit.value_gt(instance).map_or(true, |gt| !gt),
+            NumericKind::ExclusiveMinimum => limit.value_gt(instance).unwrap_or(true),
+            NumericKind::ExclusiveMaximum => limit.value_lt(instance).unwrap_or(true),
+        }
+    }
+    fn error<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ValidationError<'i> {
+        let (schema_path, instance_path, limit) = (
+            self.location.clone(),
+            location.into(),
+            self.limit_val.clone(),
+        );
+        match self.kind {
+            NumericKind::Minimum => {
+                ValidationError::minimum(schema_path, instance_path, instance, limit)
+            }
+            NumericKind::Maximum => {
+                ValidationError::maximum(schema_path, instance_path, instance, limit)
+            }
+            NumericKind::ExclusiveMinimum => {
+                ValidationError::exclusive_minimum(schema_path, instance_path, instance, limit)
+            }
+            NumericKind::ExclusiveMaximum => {
+                ValidationError::exclusive_maximum(schema_path, instance_path, instance, limit)
+            }
+        }
+    }
+}
+
+/// The edge of `bounds` on one side that excludes the most values.
+fn tightest(bounds: &[NumericBound], lower: bool) -> Option<Edge> {
+    bounds
+        .iter()
+        .filter(|bound| bound.kind.is_lower() == lower)
+        .map(|bound| bound.edge)
+        .reduce(|current, edge| {
+            let (inner, outer) = if lower {
+                (edge.limit, current.limit)
+            } else {
+                (current.limit, edge.limit)
+            };
+            let is_tighter = outer.is_less_than(inner);
+            let is_equal = !is_tighter && !inner.is_less_than(outer);
+            if is_tighter || (is_equal && edge.exclusive) {
+                edge
+            } else {
+                current
+            }
+        })
+}
+
+/// `minimum`, `maximum`, `exclusiveMinimum` and `exclusiveMaximum` of one schema.
+pub(crate) struct NumericRangeValidator {
+    /// The tightest edges, which decide whether an instance is valid
+    lower: Option<Edge>,
+    upper: Option<Edge>,
+    /// All bounds, to report each of them that is violated
+    bounds: Vec<NumericBound>,
+    absolute: Option<AbsoluteLocation>,
+}
+
+impl NumericRangeValidator {
+    fn new(bounds: Vec<NumericBound>, absolute: Option<AbsoluteLocation>) -> Self {
+        NumericRangeValidator {
+            lower: tightest(&bounds, true),
+            upper: tightest(&bounds, false),
+            bounds,
+            absolute,
+        }
+    }
+    fn admits<T>(&self, item: T) -> bool
+    where
+        T: Copy + NumCmp<u64> + NumCmp<i64> + NumCmp<f64>,
+    {
+        self.lower.map_or(true, |edge| edge.admits_above(item))
+            && self.upper.map_or(true, |edge| edge.admits_below(item))
+    }
+    fn violated<'s, 'i>(
+        &'s self,
+        instance: &'i Value,
+        location: &'s LazyLocation,
+    ) -> impl Iterator<Item = (&'s Location, ValidationError<'i>)> + 's
+    where
+        'i: 's,
+    {
+        let item = instance.as_number();
+        self.bounds
+            .iter()
+            .filter(move |bound| item.is_some_and(|item| !bound.is_valid(item)))
+            .map(move |bound| (&bound.location, bound.error(instance, location)))
+    }
+}
+
+impl Validate for NumericRangeValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::Number(item) = instance {
+            with_number!(item, |item| self.admits(item))
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.bounds
+            .iter()
+            .all(|bound| bound.is_valid_value(instance))
+    }
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        match self.violated(instance, location).next() {
+            Some((_, error)) => Err(error),
+            None => Ok(()),
+        }
+    }
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        let errors: Vec<_> = self
+            .violated(instance, location)
+            .map(|(_, error)| error)
+            .collect();
+        Box::new(errors.into_iter())
+    }
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        _scratch: &mut ValidationScratch,
+    ) {
+        errors.extend(self.violated(instance, location).map(|(_, error)| error));
+    }
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        apply_bounds(
+            self.absolute.as_ref(),
+            location,
+            self.violated(instance, location),
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Measure {
+    Length,
+    Items,
+    Properties,
+}
+
+impl Measure {
+    fn size(self, instance: &Value) -> Option<u64> {
+        match (self, instance) {
+            (Measure::Length, Value::String(item)) => {
+                Some(bytecount::num_chars(item.as_bytes()) as u64)
+            }
+            (Measure::Items, Value::Array(items)) => Some(items.len() as u64),
+            (Measure::Properties, Value::Object(item)) => Some(item.len() as u64),
+            _ => None,
+        }
+    }
+    fn size_of_value(self, instance: &dyn value::Value) -> Option<u64> {
+        match self {
+            Measure::Length => instance
+                .as_str()
+                .map(|item| bytecount::num_chars(item.as_bytes()) as u64),
+            Measure::Items => instance.array_len().map(|len| len as u64),
+            Measure::Properties => instance.object_len().map(|len| len as u64),
+        }
+    }
+}
+
+struct SizeBound {
+    is_upper: bool,
+    limit: u64,
+    location: Location,
+}
+
+impl SizeBound {
+    fn new(ctx: &compiler::Context, schema: &Map<String, Value>, keyword: &str) -> Self {
+        let value = &schema[keyword];
+        // Integer-valued floats like `2.0` are accepted as limits where the draft allows them
+        #[allow(clippy::cast_possible_truncation)]
+        let limit = value.as_u64().unwrap_or_else(|| {
+            // Limits beyond the range of `f64` are infinite
+            value.as_f64().map_or(u64::MAX, |limit| limit as u64)
+        });
+        SizeBound {
+            is_upper: keyword.starts_with("max"),
+            limit,
+            location: ctx.location().join(keyword),
+        }
+    }
+    fn is_valid(&self, size: u64) -> bool {
+        if self.is_upper {
+            size <= self.limit
+        } else {
+            size >= self.limit
+        }
+    }
+}
+
+/// `minLength` and `maxLength`, `minItems` and `maxItems`, or `minProperties` and
+/// `maxProperties` of one schema.
+pub(crate) struct SizeRangeValidator {
+    measure: Measure,
+    bounds: Vec<SizeBound>,
+    absolute: Option<AbsoluteLocation>,
+}
+
+impl SizeRangeValidator {
+    fn violated<'s, 'i>(
+        &'s self,
+        instance: &'i Value,
+        location: &'s LazyLocation,
+    ) -> impl Iterator<Item = (&'s Location, ValidationError<'i>)> + 's
+    where
+        'i: 's,
+    {
+        let size = self.measure.size(instance);
+        self.bounds
+            .iter()
+            .filter_map(move |bound| {
+                size.filter(|size| !bound.is_valid(*size))
+                    .map(|size| (bound, size))
+            })
+            .map(move |(bound, size)| {
+                let (schema_path, instance_path) = (bound.location.clone(), location.into());
+                let error = match (self.measure, bound.is_upper) {
+                    (Measure::Length, false) => ValidationError::min_length(
+                        schema_path,
+                        instance_path,
+                        instance,
+                        bound.limit,
+                    ),
+                    (Measure::Length, true) => ValidationError::max_length(
+                        schema_path,
+                        instance_path,
+                        instance,
+                        bound.limit,
+                    ),
+                    (Measure::Items, false) => ValidationError::min_items(
+                        schema_path,
+                        instance_path,
+                        instance,
+                        bound.limit,
+                    ),
+                    (Measure::Items, true) => ValidationError::max_items(
+                        schema_path,
+                        instance_path,
+                        instance,
+                        bound.limit,
+                    ),
+                    (Measure::Properties, false) => ValidationError::min_properties(
+                        schema_path,
+                        instance_path,
+                        instance,
+                        bound.limit,
+                        size,
+                    ),
+                    (Measure::Properties, true) => ValidationError::max_properties(
+                        schema_path,
+                        instance_path,
+                        instance,
+                        bound.limit,
+                        size,
+                    ),
+                };
+                (&bound.location, error)
+            })
+    }
+}
+
+impl Validate for SizeRangeValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.measure.size(instance).map_or(true, |size| {
+            self.bounds.iter().all(|bound| bound.is_valid(size))
+        })
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.measure.size_of_value(instance).map_or(true, |size| {
+            self.bounds.iter().all(|bound| bound.is_valid(size))
+        })
+    }
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        match self.violated(instance, location).next() {
+            Some((_, error)) => Err(error),
+            None => Ok(()),
+        }
+    }
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        let errors: Vec<_> = self
+            .violated(instance, location)
+            .map(|(_, error)| error)
+            .collect();
+        Box::new(errors.into_iter())
+    }
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        _scratch: &mut ValidationScratch,
+    ) {
+        errors.extend(self.violated(instance, location).map(|(_, error)| error));
+    }
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        apply_bounds(
+            self.absolute.as_ref(),
+            location,
+            self.violated(instance, location),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn errors(schema: &Value, instance: &Value) -> Vec<(String, String)> {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        validator
+            .iter_errors(instance)
+            .map(|error| (error.schema_path.to_string(), error.to_string()))
+            .collect()
+    }
+
+    fn basic_locations(schema: &Value, instance: &Value) -> Vec<String> {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        let output = serde_json::to_value(validator.apply(instance).basic()).expect("Serializable");
+        output["errors"]
+            .as_array()
+            .map(|errors| {
+                errors
+                    .iter()
+                    .map(|unit| {
+                        unit["keywordLocation"]
+                            .as_str()
+                            .expect("String")
+                            .to_string()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test_case(&json!({"minimum": 1, "maximum": 10}), &json!(0), &["/minimum"])]
+    #[test_case(&json!({"minimum": 1, "maximum": 10}), &json!(11), &["/maximum"])]
+    #[test_case(&json!({"minimum": 1, "maximum": 10}), &json!(5), &[])]
+    #[test_case(&json!({"minimum": 1, "maximum": 10}), &json!("a"), &[])]
+    #[test_case(&json!({"exclusiveMinimum": 1, "exclusiveMaximum": 2.5}), &json!(1), &["/exclusiveMinimum"])]
+    #[test_case(&json!({"exclusiveMinimum": 1, "exclusiveMaximum": 2.5}), &json!(2.5), &["/exclusiveMaximum"])]
+    #[test_case(&json!({"minimum": -1, "exclusiveMinimum": 3, "maximum": 18446744073709551615_u64}), &json!(-2), &["/exclusiveMinimum", "/minimum"])]
+    #[test_case(&json!({"minimum": 5, "maximum": 3}), &json!(4), &["/maximum", "/minimum"])]
+    #[test_case(&json!({"minimum": 3, "exclusiveMinimum": 3}), &json!(3), &["/exclusiveMinimum"])]
+    #[test_case(&json!({"maximum": 2.5, "exclusiveMaximum": 10}), &json!(5), &["/maximum"])]
+    #[test_case(&json!({"maximum": 10, "exclusiveMaximum": 2.5}), &json!(5), &["/exclusiveMaximum"])]
+    #[test_case(&json!({"minimum": -5, "exclusiveMinimum": -5.5}), &json!(-5.2), &["/minimum"])]
+    #[test_case(&json!({"minLength": 2, "maxLength": 3}), &json!("a"), &["/minLength"])]
+    #[test_case(&json!({"minLength": 2, "maxLength": 3}), &json!("abcd"), &["/maxLength"])]
+    #[test_case(&json!({"minLength": 2, "maxLength": 3}), &json!([1]), &[])]
+    #[test_case(&json!({"minItems": 2.0, "maxItems": 3}), &json!([1]), &["/minItems"])]
+    #[test_case(&json!({"minItems": 2, "maxItems": 3}), &json!([1, 2, 3, 4]), &["/maxItems"])]
+    #[test_case(&json!({"minProperties": 1, "maxProperties": 2}), &json!({}), &["/minProperties"])]
+    #[test_case(&json!({"minProperties": 1, "maxProperties": 2}), &json!({"a": 1, "b": 2, "c": 3}), &["/maxProperties"])]
+    #[test_case(&json!({"minProperties": 1, "maxProperties": 2}), &json!([]), &[])]
+    fn errors_point_to_bounds(schema: &Value, instance: &Value, expected: &[&str]) {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        let locations: Vec<_> = errors(schema, instance)
+            .into_iter()
+            .map(|(location, _)| location)
+            .collect();
+        assert_eq!(locations, expected);
+        assert_eq!(validator.is_valid(instance), expected.is_empty());
+        assert_eq!(validator.validate(instance).is_ok(), expected.is_empty());
+        assert_eq!(basic_locations(schema, instance), expected);
+    }
+
+    #[test_case(&json!({"minimum": 3, "maximum": 1}), &json!(2))]
+    #[test_case(&json!({"exclusiveMinimum": 2, "exclusiveMaximum": 2}), &json!(2))]
+    #[test_case(&json!({"minLength": 3, "maxLength": 1}), &json!("ab"))]
+    #[test_case(&json!({"minItems": 3, "maxItems": 1}), &json!([1, 2]))]
+    #[test_case(&json!({"minProperties": 3, "maxProperties": 1}), &json!({"a": 1, "b": 2}))]
+    fn same_errors_as_single_bounds(schema: &Value, instance: &Value) {
+        // Schemas with a single bound are not fused
+        let mut expected: Vec<_> = schema
+            .as_object()
+            .expect("Object")
+            .iter()
+            .flat_map(|(keyword, value)| errors(&json!({keyword: value}), instance))
+            .collect();
+        expected.sort();
+        let mut fused = errors(schema, instance);
+        fused.sort();
+        assert_eq!(fused, expected);
+    }
+}
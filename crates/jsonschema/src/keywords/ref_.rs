@@ -1,22 +1,31 @@
-use std::{rc::Rc, sync::Arc};
+use std::{
+    borrow::Cow,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
 
 use crate::{
-    compiler,
+    compiler::{self, ResolverComponents},
     error::ErrorIterator,
     keywords::CompilationResult,
-    node::SchemaNode,
+    node::{DeferredChecks, SchemaNode, WeakSchemaNode},
+    output::ErrorDescription,
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
-    validator::{PartialApplication, Validate},
-    ValidationError, ValidationOptions,
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value, ValidationError, ValidationOptions,
 };
+use ahash::AHashMap;
 use once_cell::sync::OnceCell;
 use referencing::{Draft, List, Registry, Resource, Uri, VocabularySet};
 use serde_json::{Map, Value};
 
 pub(crate) enum RefValidator {
     Default { inner: SchemaNode },
-    Lazy(LazyRefValidator),
+    Lazy(Arc<LazyRefValidator>),
 }
 
 impl RefValidator {
@@ -28,55 +37,80 @@ impl RefValidator {
         keyword: &str,
     ) -> Option<CompilationResult<'a>> {
         let location = ctx.location().join(keyword);
+        if ctx.are_references_lazy() {
+            return Some(Ok(Box::new(RefValidator::Lazy(
+                LazyRefValidator::deferred(ctx, reference, keyword, location),
+            ))));
+        }
+        let key = match ctx.reference_key(reference) {
+            Ok(key) => key,
+            Err(error) => return Some(Err(error.into())),
+        };
+        if let Some(inner) = key
+            .as_ref()
+            .and_then(|key| ctx.new_at_location(keyword).find_compiled_reference(key))
+        {
+            return Some(Ok(Box::new(RefValidator::Default { inner })));
+        }
+        if let Some(result) =
+            Self::compile_recursive(ctx, reference, is_recursive, keyword, &key, &location)
+        {
+            return result;
+        }
+        let (contents, resolver, draft) = match ctx.lookup(reference) {
+            Ok(resolved) => resolved.into_inner(),
+            Err(error) => return Some(Err(error.into())),
+        };
+        let vocabularies = ctx.registry.find_vocabularies(draft, contents);
+        let resource_ref = draft.create_resource_ref(contents);
+        let resource_pointer = compiler::resource_pointer(&resolver, contents, reference);
+        let ctx = ctx.with_resolver_and_draft(
+            resolver,
+            resource_ref.draft(),
+            vocabularies,
+            location,
+            resource_pointer,
+        );
         Some(
-            if let Some((base_uri, scopes, resource)) = {
-                match ctx.lookup_maybe_recursive(reference, is_recursive) {
-                    Ok(resolved) => resolved,
-                    Err(error) => return Some(Err(error)),
-                }
-            } {
-                // NOTE: A better approach would be to compare the absolute locations
-                if let Value::Object(contents) = resource.contents() {
-                    if let Some(Some(resolved)) = contents.get(keyword).map(Value::as_str) {
-                        if resolved == reference {
-                            return None;
-                        }
-                    }
-                }
-                Ok(Box::new(RefValidator::Lazy(LazyRefValidator {
-                    resource,
-                    config: Arc::clone(ctx.config()),
-                    registry: Arc::clone(&ctx.registry),
-                    base_uri,
-                    scopes,
-                    location,
-                    vocabularies: ctx.vocabularies().clone(),
-                    draft: ctx.draft(),
-                    inner: OnceCell::default(),
-                })))
-            } else {
-                let (contents, resolver, draft) = match ctx.lookup(reference) {
-                    Ok(resolved) => resolved.into_inner(),
-                    Err(error) => return Some(Err(error.into())),
-                };
-                let vocabularies = ctx.registry.find_vocabularies(draft, contents);
-                let resource_ref = draft.create_resource_ref(contents);
-                let ctx = ctx.with_resolver_and_draft(
-                    resolver,
-                    resource_ref.draft(),
-                    vocabularies,
-                    location,
-                );
-                let inner = match compiler::compile_with(&ctx, resource_ref)
-                    .map_err(|err| err.into_owned())
-                {
-                    Ok(inner) => inner,
-                    Err(error) => return Some(Err(error)),
-                };
-                Ok(Box::new(RefValidator::Default { inner }))
+            match ctx
+                .compile_reference(key, resource_ref)
+                .map_err(|err| err.into_owned())
+            {
+                Ok(inner) => Ok(Box::new(RefValidator::Default { inner })),
+                Err(error) => Err(error),
             },
         )
     }
+    /// Compile a reference to a resource that is already being compiled.
+    ///
+    /// Returns `None` if the reference is not known to be recursive, and `Some(None)` if it
+    /// points to itself.
+    // Kept out of line, as compiling a chain of references recurses through `RefValidator::compile`
+    #[inline(never)]
+    fn compile_recursive<'a>(
+        ctx: &compiler::Context,
+        reference: &str,
+        is_recursive: bool,
+        keyword: &str,
+        key: &Option<Arc<Uri<String>>>,
+        location: &Location,
+    ) -> Option<Option<CompilationResult<'a>>> {
+        let components = match ctx.lookup_maybe_recursive(reference, is_recursive) {
+            Ok(components) => components?,
+            Err(error) => return Some(Some(Err(error))),
+        };
+        // NOTE: A better approach would be to compare the absolute locations
+        if let Value::Object(contents) = components.2.contents() {
+            if let Some(Some(resolved)) = contents.get(keyword).map(Value::as_str) {
+                if resolved == reference {
+                    return Some(None);
+                }
+            }
+        }
+        Some(Some(Ok(Box::new(RefValidator::Lazy(
+            LazyRefValidator::recursive(ctx, components, key.clone(), location.clone()),
+        )))))
+    }
 }
 
 /// Lazily evaluated validator used for recursive references.
@@ -86,16 +120,105 @@ impl RefValidator {
 /// by building and caching the next subtree lazily. Though, other memory
 /// representation for the validation tree may allow building cycles easier and
 /// lazy evaluation won't be needed.
+///
+/// It is also used for all references if they are configured to be lazy, in which case the
+/// reference is resolved on first use as well.
 pub(crate) struct LazyRefValidator {
-    resource: Resource,
+    target: Target,
     config: Arc<ValidationOptions>,
     registry: Arc<Registry>,
     scopes: List<Uri<String>>,
     base_uri: Arc<Uri<String>>,
     vocabularies: VocabularySet,
     location: Location,
+    /// Location of the target within its resource
+    resource_pointer: Location,
     draft: Draft,
-    inner: OnceCell<SchemaNode>,
+    /// Absolute location of the target if it can be shared with other references
+    key: Option<Arc<Uri<String>>>,
+    pending: PendingReferences,
+    inner: OnceCell<Result<CompiledTarget, ValidationError<'static>>>,
+}
+
+enum CompiledTarget {
+    Owned(SchemaNode),
+    /// Target compiled by another reference. It is not owned, as it may contain this reference.
+    Shared(WeakSchemaNode),
+}
+
+enum Target {
+    /// Already resolved resource
+    Resource(Resource),
+    /// Reference that is resolved on first use
+    Reference { reference: String, keyword: String },
+}
+
+/// State shared by all lazy references of a validator.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PendingReferences(Arc<LazyReferences>);
+
+#[derive(Debug, Default)]
+struct LazyReferences {
+    /// References created with [`ValidationOptions::with_lazy_references`] that may not be
+    /// compiled yet.
+    validators: Mutex<Vec<Weak<LazyRefValidator>>>,
+    /// Whether compiled targets can be shared, i.e. the schema has no dynamic references.
+    share_targets: AtomicBool,
+    /// Compiled targets by their absolute location. Recursive references compile their target
+    /// once instead of once per nesting level.
+    targets: Mutex<AHashMap<Arc<Uri<String>>, WeakSchemaNode>>,
+}
+
+impl PendingReferences {
+    fn push(&self, validator: &Arc<LazyRefValidator>) {
+        self.0
+            .validators
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(Arc::downgrade(validator));
+    }
+    pub(crate) fn set_share_targets(&self, share_targets: bool) {
+        self.0.share_targets.store(share_targets, Ordering::Relaxed);
+    }
+    pub(crate) fn share_targets(&self) -> bool {
+        self.0.share_targets.load(Ordering::Relaxed)
+    }
+    fn find_target(&self, key: &Arc<Uri<String>>) -> Option<WeakSchemaNode> {
+        self.0
+            .targets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(key)
+            .cloned()
+    }
+    fn insert_target(&self, key: &Arc<Uri<String>>, node: &SchemaNode) {
+        self.0
+            .targets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(Arc::clone(key), node.downgrade());
+    }
+    /// Compile all pending references eagerly, including the ones found in already compiled
+    /// targets.
+    pub(crate) fn compile_all(&self) {
+        loop {
+            let pending = std::mem::take(
+                &mut *self
+                    .0
+                    .validators
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            );
+            if pending.is_empty() {
+                break;
+            }
+            for validator in pending.iter().filter_map(Weak::upgrade) {
+                validator
+                    .inner
+                    .get_or_init(|| validator.resolve_target(false));
+            }
+        }
+    }
 }
 
 impl LazyRefValidator {
@@ -110,54 +233,239 @@ impl LazyRefValidator {
             base_uri = resolver.resolve_against(&base_uri.borrow(), id)?;
         };
         Ok(Box::new(LazyRefValidator {
-            resource,
+            target: Target::Resource(resource),
             config: Arc::clone(ctx.config()),
             registry: Arc::clone(&ctx.registry),
             base_uri,
             scopes,
             vocabularies: ctx.vocabularies().clone(),
             location: ctx.location().join("$recursiveRef"),
+            resource_pointer: Location::new(),
             draft: ctx.draft(),
+            key: None,
+            pending: ctx.pending_references().clone(),
             inner: OnceCell::default(),
         }))
     }
-    fn lazy_compile(&self) -> &SchemaNode {
-        self.inner.get_or_init(|| {
-            let resolver = self
-                .registry
-                .resolver_from_raw_parts(self.base_uri.clone(), self.scopes.clone());
-
-            let ctx = compiler::Context::new(
-                Arc::clone(&self.config),
-                Arc::clone(&self.registry),
-                Rc::new(resolver),
-                self.vocabularies.clone(),
-                self.draft,
-                self.location.clone(),
-            );
-            // INVARIANT: This schema was already used during compilation before detecting a
-            // reference cycle that lead to building this validator.
-            compiler::compile(&ctx, self.resource.as_ref()).expect("Invalid schema")
+    /// A reference to a resource that is already being compiled.
+    // Kept out of line, as compiling a chain of references recurses through `RefValidator::compile`
+    #[inline(never)]
+    fn recursive(
+        ctx: &compiler::Context,
+        (base_uri, scopes, resource, resource_pointer): ResolverComponents,
+        key: Option<Arc<Uri<String>>>,
+        location: Location,
+    ) -> Arc<LazyRefValidator> {
+        Arc::new(LazyRefValidator {
+            target: Target::Resource(resource),
+            key,
+            config: Arc::clone(ctx.config()),
+            registry: Arc::clone(&ctx.registry),
+            base_uri,
+            scopes,
+            location,
+            resource_pointer,
+            vocabularies: ctx.vocabularies().clone(),
+            draft: ctx.draft(),
+            pending: ctx.pending_references().clone(),
+            inner: OnceCell::default(),
         })
     }
+    /// A reference that is resolved and compiled on first use.
+    fn deferred(
+        ctx: &compiler::Context,
+        reference: &str,
+        keyword: &str,
+        location: Location,
+    ) -> Arc<LazyRefValidator> {
+        let validator = Arc::new(LazyRefValidator {
+            target: Target::Reference {
+                reference: reference.to_string(),
+                keyword: keyword.to_string(),
+            },
+            config: Arc::clone(ctx.config()),
+            registry: Arc::clone(&ctx.registry),
+            base_uri: ctx.resolver_base_uri(),
+            scopes: ctx.scopes(),
+            vocabularies: ctx.vocabularies().clone(),
+            location,
+            // Found when the reference is resolved
+            resource_pointer: Location::new(),
+            draft: ctx.draft(),
+            key: ctx.reference_key(reference).ok().flatten(),
+            pending: ctx.pending_references().clone(),
+            inner: OnceCell::default(),
+        });
+        ctx.pending_references().push(&validator);
+        validator
+    }
+    fn compile_target(
+        &self,
+        lazy_references: bool,
+    ) -> Result<SchemaNode, ValidationError<'static>> {
+        let resolver = self
+            .registry
+            .resolver_from_raw_parts(self.base_uri.clone(), self.scopes.clone());
+        let ctx = compiler::Context::new(
+            Arc::clone(&self.config),
+            Arc::clone(&self.registry),
+            Rc::new(resolver),
+            self.vocabularies.clone(),
+            self.draft,
+            self.location.clone(),
+        )
+        .with_pending_references(self.pending.clone(), lazy_references)
+        .with_resource_pointer(self.resource_pointer.clone());
+        match &self.target {
+            Target::Resource(resource) => {
+                compiler::compile(&ctx, resource.as_ref()).map_err(ValidationError::into_owned)
+            }
+            Target::Reference { reference, keyword } => {
+                let (contents, resolver, draft) = match ctx.lookup(reference) {
+                    Ok(resolved) => resolved.into_inner(),
+                    Err(error) => {
+                        let mut error = ValidationError::from(error);
+                        // Compilation errors point to the schema via `instance_path`
+                        error.instance_path = self.location.clone();
+                        return Err(error);
+                    }
+                };
+                if contents.get(keyword).and_then(Value::as_str) == Some(reference) {
+                    // Infinite reference to the same location
+                    return Ok(SchemaNode::from_boolean(&ctx, None));
+                }
+                let vocabularies = ctx.registry.find_vocabularies(draft, contents);
+                let resource_ref = draft.create_resource_ref(contents);
+                let resource_pointer = compiler::resource_pointer(&resolver, contents, reference);
+                let ctx = ctx.with_resolver_and_draft(
+                    resolver,
+                    resource_ref.draft(),
+                    vocabularies,
+                    self.location.clone(),
+                    resource_pointer,
+                );
+                compiler::compile_with(&ctx, resource_ref).map_err(ValidationError::into_owned)
+            }
+        }
+    }
+    /// Reuse the target compiled by another reference to the same location, or compile it.
+    fn resolve_target(
+        &self,
+        lazy_references: bool,
+    ) -> Result<CompiledTarget, ValidationError<'static>> {
+        let Some(key) = &self.key else {
+            return self
+                .compile_target(lazy_references)
+                .map(CompiledTarget::Owned);
+        };
+        if let Some(node) = self.pending.find_target(key) {
+            return Ok(CompiledTarget::Shared(node));
+        }
+        let node = self.compile_target(lazy_references)?;
+        self.pending.insert_target(key, &node);
+        Ok(CompiledTarget::Owned(node))
+    }
+    fn lazy_compile(&self) -> Result<Cow<'_, SchemaNode>, &ValidationError<'static>> {
+        match self
+            .inner
+            .get_or_init(|| self.resolve_target(self.config.are_references_lazy()))
+        {
+            Ok(CompiledTarget::Owned(node)) => Ok(Cow::Borrowed(node)),
+            Ok(CompiledTarget::Shared(node)) => Ok(Cow::Owned(
+                node.upgrade_at(&self.location)
+                    .expect("Shared targets live as long as the validator"),
+            )),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Report a failed compilation of a lazily compiled target for `instance`.
+fn compilation_error<'i>(
+    error: &ValidationError<'static>,
+    instance: &'i Value,
+    location: &LazyLocation,
+) -> ValidationError<'i> {
+    // During compilation the schema is the instance, so its location is in `instance_path`
+    ValidationError::custom(
+        error.instance_path.clone(),
+        location.into(),
+        instance,
+        error.to_string(),
+    )
 }
 
 impl Validate for LazyRefValidator {
     fn is_valid(&self, instance: &Value) -> bool {
-        self.lazy_compile().is_valid(instance)
+        self.lazy_compile()
+            .is_ok_and(|node| node.is_valid(instance))
+    }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        self.lazy_compile()
+            .is_ok_and(|node| checks.check(&node, instance))
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.lazy_compile()
+            .is_ok_and(|node| node.is_valid_value(instance))
+    }
+    #[cfg(feature = "parallel")]
+    fn par_is_valid(&self, instance: &Value) -> bool {
+        self.lazy_compile()
+            .is_ok_and(|node| node.par_is_valid(instance))
+    }
+    #[cfg(feature = "parallel")]
+    fn par_iter_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        match self.lazy_compile() {
+            Ok(node) => node.par_iter_errors(instance, location),
+            Err(error) => Box::new(std::iter::once(compilation_error(
+                error, instance, location,
+            ))),
+        }
     }
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        self.lazy_compile().validate(instance, location)
+        match self.lazy_compile() {
+            Ok(node) => node.validate(instance, location),
+            Err(error) => Err(compilation_error(error, instance, location)),
+        }
     }
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        self.lazy_compile().iter_errors(instance, location)
+        match self.lazy_compile() {
+            Ok(node) => node.iter_errors(instance, location),
+            Err(error) => Box::new(std::iter::once(compilation_error(
+                error, instance, location,
+            ))),
+        }
+    }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        match self.lazy_compile() {
+            Ok(node) => node.collect_errors(instance, location, errors, scratch),
+            Err(error) => errors.push(compilation_error(error, instance, location)),
+        }
     }
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
-        self.lazy_compile().apply(instance, location)
+        match self.lazy_compile() {
+            Ok(Cow::Borrowed(node)) => node.apply(instance, location),
+            // Annotations of a shared target can't borrow from it
+            Ok(Cow::Owned(node)) => node.apply(instance, location).into_owned(),
+            Err(error) => PartialApplication::invalid_empty(vec![ErrorDescription::from(
+                compilation_error(error, instance, location),
+            )]),
+        }
     }
 }
 
@@ -168,6 +476,36 @@ impl Validate for RefValidator {
             RefValidator::Lazy(lazy) => lazy.is_valid(instance),
         }
     }
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        match self {
+            RefValidator::Default { inner } => checks.check(inner, instance),
+            RefValidator::Lazy(lazy) => lazy.is_valid_deferred(instance, checks),
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        match self {
+            RefValidator::Default { inner } => inner.is_valid_value(instance),
+            RefValidator::Lazy(lazy) => lazy.is_valid_value(instance),
+        }
+    }
+    #[cfg(feature = "parallel")]
+    fn par_is_valid(&self, instance: &Value) -> bool {
+        match self {
+            RefValidator::Default { inner } => inner.par_is_valid(instance),
+            RefValidator::Lazy(lazy) => lazy.par_is_valid(instance),
+        }
+    }
+    #[cfg(feature = "parallel")]
+    fn par_iter_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        match self {
+            RefValidator::Default { inner } => inner.par_iter_errors(instance, location),
+            RefValidator::Lazy(lazy) => lazy.par_iter_errors(instance, location),
+        }
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -184,6 +522,21 @@ impl Validate for RefValidator {
             RefValidator::Lazy(lazy) => lazy.iter_errors(instance, location),
         }
     }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        match self {
+            RefValidator::Default { inner } => {
+                inner.collect_errors(instance, location, errors, scratch);
+            }
+            RefValidator::Lazy(lazy) => lazy.collect_errors(instance, location, errors, scratch),
+        }
+    }
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         match self {
             RefValidator::Default { inner } => inner.apply(instance, location),
@@ -192,10 +545,14 @@ impl Validate for RefValidator {
     }
 }
 
-fn invalid_reference<'a>(ctx: &compiler::Context, schema: &'a Value) -> ValidationError<'a> {
+fn invalid_reference<'a>(
+    ctx: &compiler::Context,
+    schema: &'a Value,
+    keyword: &str,
+) -> ValidationError<'a> {
     ValidationError::single_type_error(
         Location::new(),
-        ctx.location().clone(),
+        ctx.location().join(keyword),
         schema,
         PrimitiveType::String,
     )
@@ -214,8 +571,9 @@ pub(crate) fn compile_impl<'a>(
         .unwrap_or_default();
     if let Some(reference) = schema.as_str() {
         RefValidator::compile(ctx, reference, is_recursive, keyword)
+            .map(|result| at_keyword(ctx, result, keyword))
     } else {
-        Some(Err(invalid_reference(ctx, schema)))
+        Some(Err(invalid_reference(ctx, schema, keyword)))
     }
 }
 
@@ -246,15 +604,29 @@ pub(crate) fn compile_recursive_ref<'a>(
     Some(
         schema
             .as_str()
-            .ok_or_else(|| invalid_reference(ctx, schema))
-            .and_then(|_| LazyRefValidator::compile(ctx)),
+            .ok_or_else(|| invalid_reference(ctx, schema, "$recursiveRef"))
+            .and_then(|_| at_keyword(ctx, LazyRefValidator::compile(ctx), "$recursiveRef")),
     )
 }
 
+/// Locate resolution errors, which know nothing about the schema, at the reference keyword.
+fn at_keyword<'a>(
+    ctx: &compiler::Context,
+    result: CompilationResult<'a>,
+    keyword: &str,
+) -> CompilationResult<'a> {
+    result.map_err(|mut error| {
+        if error.instance_path.as_str().is_empty() {
+            error.instance_path = ctx.location().join(keyword);
+        }
+        error
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
-    use referencing::{Draft, Retrieve, Uri};
+    use referencing::{Draft, Resource, Retrieve, Uri};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -270,6 +642,25 @@ mod tests {
         &json!({"foo": 42}),
         "/properties/foo/$ref/type"
     )]
+    #[test_case(
+        &json!({
+            "items": {"$ref": "#"},
+            "minimum": 0
+        }),
+        &json!([[[-1]]]),
+        "/items/$ref/items/$ref/items/$ref/minimum"
+    )]
+    #[test_case(
+        &json!({
+            "properties": {
+                "a": {"$ref": "#"},
+                "b": {"$ref": "#"}
+            },
+            "minimum": 0
+        }),
+        &json!({"a": {"b": {"a": -1}}}),
+        "/properties/a/$ref/properties/b/$ref/properties/a/$ref/minimum"
+    )]
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
@@ -321,6 +712,87 @@ mod tests {
         );
     }
 
+    #[test_case(Draft::Draft4, true)]
+    #[test_case(Draft::Draft6, true)]
+    #[test_case(Draft::Draft7, true)]
+    #[test_case(Draft::Draft201909, false)]
+    #[test_case(Draft::Draft202012, false)]
+    fn siblings(draft: Draft, ignored: bool) {
+        let schema = json!({
+            "definitions": {"int": {"type": "integer"}},
+            "properties": {
+                "a": {"$ref": "#/definitions/int", "maximum": 5, "description": "Small"}
+            }
+        });
+        let validator = crate::options()
+            .with_draft(draft)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!({"a": "x"})));
+        assert_eq!(validator.is_valid(&json!({"a": 10})), ignored);
+        let warnings: Vec<_> = validator
+            .warnings()
+            .iter()
+            .map(|warning| (warning.code, warning.location.as_str()))
+            .collect();
+        if ignored {
+            assert_eq!(
+                warnings,
+                [(crate::LintCode::IgnoredRefSibling, "/properties/a/maximum")]
+            );
+        } else {
+            assert!(warnings.is_empty());
+        }
+    }
+
+    #[test_case("urn:example:person#/$defs/name"; "pointer")]
+    #[test_case("urn:example:person#name"; "anchor")]
+    fn urn(reference: &str) {
+        let person = json!({
+            "$id": "urn:example:person",
+            "$defs": {"name": {"$anchor": "name", "type": "string"}}
+        });
+        let validator = crate::options()
+            .with_resource(
+                "urn:example:person",
+                referencing::Resource::from_contents(person).expect("Invalid resource"),
+            )
+            .build(&json!({"properties": {"name": {"$ref": reference}}}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"name": "Alice"})));
+        let instance = json!({"name": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            error
+                .absolute_keyword_location()
+                .as_ref()
+                .map(referencing::Uri::as_str),
+            Some("urn:example:person#/$defs/name/type")
+        );
+    }
+
+    #[test_case(
+        "HTTP://Example.COM/schemas/name.json",
+        "http://example.com/schemas/name.json"
+    )]
+    #[test_case(
+        "http://example.com:80/schemas/./name.json",
+        "HTTP://EXAMPLE.COM/other/../schemas/name.json"
+    )]
+    #[test_case(
+        "https://example.com/schemas/%6Eame.json",
+        "https://example.com:443/schemas/name.json"
+    )]
+    fn normalized_uris(id: &str, reference: &str) {
+        let schema = json!({
+            "$defs": {"name": {"$id": id, "type": "string"}},
+            "properties": {"name": {"$ref": reference}}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"name": "Alice"})));
+        assert!(!validator.is_valid(&json!({"name": 1})));
+    }
+
     #[test]
     fn test_relative_base_uri() {
         let schema = json!({
@@ -494,4 +966,226 @@ mod tests {
         let validator = crate::validator_for(&json!({"$ref": "#"})).expect("Invalid schema");
         assert!(validator.is_valid(&json!(42)));
     }
+
+    #[test]
+    fn shared_reference_targets() {
+        use crate::{paths::LazyLocation, Keyword, ValidationError};
+        use std::sync::Mutex;
+
+        // Addresses of probes that were used during validation
+        static USED: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+        // Not zero-sized, so every instance has its own address
+        struct Probe(#[allow(dead_code)] u8);
+
+        impl Keyword for Probe {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                Ok(())
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                USED.lock()
+                    .expect("Lock is poisoned")
+                    .push(self as *const Self as usize);
+                true
+            }
+        }
+
+        let schema = json!({
+            "properties": {
+                "home": {"$ref": "#/$defs/address"},
+                "work": {"$ref": "#/$defs/address"},
+                "billing": {"items": {"$ref": "#/$defs/address"}},
+                "remote": {"$ref": "https://example.com/address.json"},
+                "other": {"$ref": "https://example.com/address.json"},
+                "tree": {"$ref": "#/$defs/tree"},
+                "forest": {"items": {"$ref": "#/$defs/tree"}}
+            },
+            "$defs": {
+                "address": {"probe": true, "required": ["city"]},
+                "tree": {
+                    "type": "object",
+                    "properties": {"children": {"items": {"$ref": "#/$defs/tree"}}}
+                }
+            }
+        });
+        let remote = json!({"$ref": "#/$defs/inner", "$defs": {"inner": {"probe": true}}});
+        let validator = crate::options()
+            .with_resource(
+                "https://example.com/address.json",
+                referencing::Resource::from_contents(remote).expect("Invalid resource"),
+            )
+            .with_keyword("probe", |_, _, _| Ok(Box::new(Probe(0))))
+            .build(&schema)
+            .expect("Invalid schema");
+        let instance = json!({
+            "home": {"city": "A"},
+            "work": {"city": "B"},
+            "billing": [{"city": "C"}],
+            "remote": 1,
+            "other": 2,
+            "tree": {"children": [{"children": []}]},
+            "forest": [{"children": [{}]}]
+        });
+        assert!(validator.is_valid(&instance));
+        let mut used = USED.lock().expect("Lock is poisoned").clone();
+        assert_eq!(used.len(), 5);
+        used.sort_unstable();
+        used.dedup();
+        // One node for `#/$defs/address` and one for the remote target
+        assert_eq!(used.len(), 2);
+        let instance = json!({"billing": [{}]});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            error.schema_path.as_str(),
+            "/properties/billing/items/$ref/required"
+        );
+        assert!(!validator.is_valid(&json!({"forest": [{"children": [{"children": [1]}]}]})));
+    }
+
+    #[test]
+    fn lazy_references() {
+        let schema = json!({
+            "properties": {
+                "used": {"$ref": "#/$defs/positive"},
+                "missing": {"$ref": "#/$defs/missing"},
+                "broken": {"$ref": "#/$defs/broken"},
+                "tree": {"$ref": "#/$defs/tree"},
+                "self": {"$ref": "#/$defs/self"}
+            },
+            "$defs": {
+                "positive": {"minimum": 0},
+                "broken": {"pattern": "("},
+                "tree": {"items": {"$ref": "#/$defs/tree"}, "maxItems": 2},
+                "self": {"$ref": "#/$defs/self"}
+            }
+        });
+        let validator = crate::options()
+            .with_lazy_references(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"used": 1, "tree": [[], [[]]], "self": 1})));
+        assert!(!validator.is_valid(&json!({"tree": [[1, 2, 3]]})));
+        let instance = json!({"used": -1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/properties/used/$ref/minimum");
+        for (instance, location) in [
+            (json!({"missing": 1}), "/properties/missing/$ref"),
+            // Compile errors of the target are located at the malformed keyword
+            (json!({"broken": "a"}), "/properties/broken/$ref/pattern"),
+        ] {
+            let error = validator.validate(&instance).expect_err("Should fail");
+            assert_eq!(error.schema_path.as_str(), location);
+            assert!(!validator.is_valid(&instance));
+            assert_eq!(validator.iter_errors(&instance).count(), 1);
+            assert!(!validator.apply(&instance).basic().is_valid());
+        }
+        validator.precompile();
+        assert!(!validator.is_valid(&json!({"tree": [[1, 2, 3]]})));
+    }
+
+    #[test]
+    fn lazy_references_compile_once() {
+        use crate::{paths::LazyLocation, Keyword, ValidationError};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Anything;
+
+        impl Keyword for Anything {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                Ok(())
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                true
+            }
+        }
+
+        static COMPILED: AtomicUsize = AtomicUsize::new(0);
+        let schema = json!({
+            "items": {"$ref": "#/$defs/item"},
+            "$defs": {"item": {"anything": true}}
+        });
+        let validator = crate::options()
+            .with_lazy_references(true)
+            .with_keyword("anything", |_, _, _| {
+                COMPILED.fetch_add(1, Ordering::SeqCst);
+                Ok(Box::new(Anything))
+            })
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(COMPILED.load(Ordering::SeqCst), 0);
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| assert!(validator.is_valid(&json!([1, 2]))));
+            }
+        });
+        validator.precompile();
+        assert_eq!(COMPILED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test_case(
+        json!({"properties": {"a": {"$ref": "#/$defs/missing"}}}),
+        "/properties/a/$ref",
+        "Pointer '/$defs/missing' does not exist in 'json-schema:///'";
+        "missing pointer"
+    )]
+    #[test_case(
+        json!({"properties": {"a": {"$ref": "#/properties/a/items"}}}),
+        "/properties/a/$ref",
+        "Pointer '/properties/a/items' does not exist in 'json-schema:///', the last segment that exists is 'a'";
+        "partially missing pointer"
+    )]
+    #[test_case(
+        json!({"properties": {"a": {"$dynamicRef": "#/$defs/missing"}}}),
+        "/properties/a/$dynamicRef",
+        "Pointer '/$defs/missing' does not exist in 'json-schema:///'";
+        "dynamic reference"
+    )]
+    #[test_case(
+        json!({"prefixItems": [{"$ref": "urn:example:missing"}]}),
+        "/prefixItems/0/$ref",
+        "Resource 'urn:example:missing' is not registered";
+        "unregistered urn"
+    )]
+    #[test_case(
+        json!({"properties": {"a~b": {"$ref": "ftp://example.com/schema.json"}}}),
+        "/properties/a~0b/$ref",
+        "Retrieving resources with the 'ftp' URI scheme is not supported";
+        "unsupported scheme"
+    )]
+    #[test_case(
+        json!({"properties": {"a": {"$ref": "http://example.com:port/"}}}),
+        "/properties/a/$ref",
+        "Invalid URI reference 'http://example.com:port/': unexpected character at index 19";
+        "invalid uri"
+    )]
+    fn reference_error_location(schema: Value, location: &str, message: &str) {
+        let error = crate::validator_for(&schema).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), location);
+        assert_eq!(error.to_string(), message);
+    }
+
+    #[test]
+    fn similar_resource_suggestion() {
+        let schema = json!({"properties": {"a": {"$ref": "urn:example:person"}}});
+        let error = crate::options()
+            .with_resource(
+                "urn:example:Person",
+                Resource::from_contents(json!({"type": "object"})).expect("Invalid resource"),
+            )
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/properties/a/$ref");
+        assert_eq!(
+            error.to_string(),
+            "Resource 'urn:example:person' is not registered. Did you mean 'urn:example:Person'?"
+        );
+    }
 }
@@ -1,13 +1,17 @@
 use crate::{
     compiler,
     error::{no_error, ErrorIterator, ValidationError},
-    keywords::CompilationResult,
+    keywords::{BoxedValidator, CompilationResult},
     paths::{LazyLocation, Location},
     primitive_type::PrimitiveType,
     validator::Validate,
+    value,
 };
 use serde_json::{Map, Value};
 
+/// `required` lists at least this long are checked by walking the instance once.
+const SORTED_THRESHOLD: usize = 16;
+
 pub(crate) struct RequiredValidator {
     required: Vec<String>,
     location: Location,
@@ -30,7 +34,15 @@ impl RequiredValidator {
                 }
             }
         }
-        Ok(Box::new(RequiredValidator { required, location }))
+        Ok(Self::boxed(required, location))
+    }
+    /// A validator for already parsed property names.
+    pub(crate) fn boxed(required: Vec<String>, location: Location) -> BoxedValidator {
+        if required.len() >= SORTED_THRESHOLD {
+            Box::new(SortedRequiredValidator::new(&required, location))
+        } else {
+            Box::new(RequiredValidator { required, location })
+        }
     }
 }
 
@@ -44,6 +56,13 @@ impl Validate for RequiredValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_len().is_none()
+            || self
+                .required
+                .iter()
+                .all(|property_name| instance.object_get(property_name).is_some())
+    }
 
     fn validate<'i>(
         &self,
@@ -51,17 +70,22 @@ impl Validate for RequiredValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            for property_name in &self.required {
-                if !item.contains_key(property_name) {
-                    return Err(ValidationError::required(
-                        self.location.clone(),
-                        location.into(),
-                        instance,
-                        // Value enum is needed for proper string escaping
-                        Value::String(property_name.clone()),
-                    ));
-                }
+            if self.is_valid(instance) {
+                return Ok(());
             }
+            let missing = self
+                .required
+                .iter()
+                .filter(|property_name| !item.contains_key(*property_name))
+                // Value enum is needed for proper string escaping
+                .map(|property_name| Value::String(property_name.clone()))
+                .collect();
+            return Err(missing_error(
+                self.location.clone(),
+                location.into(),
+                instance,
+                missing,
+            ));
         }
         Ok(())
     }
@@ -87,6 +111,166 @@ impl Validate for RequiredValidator {
     }
 }
 
+/// One error for all missing properties, so `validate` reports the same kind for any list length.
+fn missing_error<'i>(
+    location: Location,
+    instance_path: Location,
+    instance: &'i Value,
+    mut missing: Vec<Value>,
+) -> ValidationError<'i> {
+    if missing.len() == 1 {
+        ValidationError::required(location, instance_path, instance, missing.remove(0))
+    } else {
+        ValidationError::required_properties(location, instance_path, instance, missing)
+    }
+}
+
+/// A validator for long `required` lists.
+///
+/// Instead of looking up every required name, the instance's keys are searched for in the
+/// sorted names, so wide objects are walked once.
+pub(crate) struct SortedRequiredValidator {
+    /// Unique required names in sorted order.
+    sorted: Vec<Box<str>>,
+    /// Index into `sorted` for each name in schema order, so errors follow that order.
+    positions: Vec<usize>,
+    location: Location,
+}
+
+impl SortedRequiredValidator {
+    fn new(required: &[String], location: Location) -> SortedRequiredValidator {
+        let mut sorted: Vec<Box<str>> = required.iter().map(|name| name.as_str().into()).collect();
+        sorted.sort_unstable();
+        sorted.dedup();
+        let positions = required
+            .iter()
+            .map(|name| {
+                sorted
+                    .binary_search_by(|probe| (**probe).cmp(name))
+                    .expect("Every name is in the sorted list")
+            })
+            .collect();
+        SortedRequiredValidator {
+            sorted,
+            positions,
+            location,
+        }
+    }
+
+    /// Call `found` with the index of every sorted name among `keys`.
+    ///
+    /// Objects usually iterate their keys in sorted order, so both lists are walked together.
+    /// Keys out of order are looked up instead.
+    #[inline]
+    fn walk<'k>(&self, keys: impl Iterator<Item = &'k str>, mut found: impl FnMut(usize)) {
+        let mut next = 0;
+        for key in keys {
+            // All names before `next` are smaller than a key in order
+            if next == 0 || &*self.sorted[next - 1] < key {
+                while next < self.sorted.len() && &*self.sorted[next] < key {
+                    next += 1;
+                }
+                if next < self.sorted.len() && &*self.sorted[next] == key {
+                    found(next);
+                    next += 1;
+                }
+            } else if let Ok(position) = self.sorted.binary_search_by(|probe| (**probe).cmp(key)) {
+                found(position);
+            }
+        }
+    }
+
+    /// Whether all names occur among `keys`.
+    #[inline]
+    fn all_present<'k>(&self, len: usize, keys: impl Iterator<Item = &'k str>) -> bool {
+        if len < self.sorted.len() {
+            return false;
+        }
+        // Keys are unique, so every name is present if as many of them are found
+        let mut count = 0;
+        self.walk(keys, |_| count += 1);
+        count == self.sorted.len()
+    }
+
+    /// Bitset of the sorted names present in `item`.
+    fn present(&self, item: &Map<String, Value>) -> Vec<u64> {
+        let mut present = vec![0_u64; (self.sorted.len() + 63) / 64];
+        self.walk(item.keys().map(String::as_str), |position| {
+            present[position / 64] |= 1 << (position % 64);
+        });
+        present
+    }
+
+    /// Missing names in schema order.
+    fn missing<'s>(&'s self, item: &Map<String, Value>) -> impl Iterator<Item = &'s str> {
+        let present = self.present(item);
+        self.positions
+            .iter()
+            .filter(move |&&position| present[position / 64] & (1 << (position % 64)) == 0)
+            .map(|&position| &*self.sorted[position])
+    }
+}
+
+impl Validate for SortedRequiredValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::Object(item) = instance {
+            self.all_present(item.len(), item.keys().map(String::as_str))
+        } else {
+            true
+        }
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        match instance.object_len() {
+            Some(len) => self.all_present(len, instance.object_iter().map(|(key, _)| key)),
+            None => true,
+        }
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if let Value::Object(item) = instance {
+            if self.is_valid(instance) {
+                return Ok(());
+            }
+            // Value enum is needed for proper string escaping
+            let missing = self
+                .missing(item)
+                .map(|name| Value::String(name.to_string()))
+                .collect();
+            return Err(missing_error(
+                self.location.clone(),
+                location.into(),
+                instance,
+                missing,
+            ));
+        }
+        Ok(())
+    }
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        if let Value::Object(item) = instance {
+            if !self.is_valid(instance) {
+                let errors: Vec<_> = self
+                    .missing(item)
+                    .map(|name| {
+                        ValidationError::required(
+                            self.location.clone(),
+                            location.into(),
+                            instance,
+                            // Value enum is needed for proper string escaping
+                            Value::String(name.to_string()),
+                        )
+                    })
+                    .collect();
+                return Box::new(errors.into_iter());
+            }
+        }
+        no_error()
+    }
+}
+
 pub(crate) struct SingleItemRequiredValidator {
     value: String,
     location: Location,
@@ -127,6 +311,9 @@ impl Validate for SingleItemRequiredValidator {
             true
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.object_len().is_none() || instance.object_get(&self.value).is_some()
+    }
 }
 
 #[inline]
@@ -171,13 +358,86 @@ pub(crate) fn compile_with_path(schema: &Value, location: Location) -> Option<Co
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
-    use serde_json::{json, Value};
+    use super::SortedRequiredValidator;
+    use crate::{error::ValidationErrorKind, paths::Location, tests_util};
+    use serde_json::{json, Map, Value};
     use test_case::test_case;
 
+    fn names(count: usize) -> Vec<String> {
+        // Not sorted, `p10` comes before `p2`
+        (0..count).rev().map(|idx| format!("p{idx}")).collect()
+    }
+
+    fn object(names: &[String], skip: &[usize]) -> Value {
+        let mut object = Map::new();
+        for (idx, name) in names.iter().enumerate() {
+            if !skip.contains(&idx) {
+                object.insert(name.clone(), json!(1));
+            }
+        }
+        object.insert("extra".to_string(), json!(1));
+        Value::Object(object)
+    }
+
     #[test_case(&json!({"required": ["a"]}), &json!({}), "/required")]
     #[test_case(&json!({"required": ["a", "b"]}), &json!({}), "/required")]
+    #[test_case(&json!({"required": names(20)}), &json!({}), "/required")]
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(20, &[]; "none")]
+    #[test_case(20, &[7]; "one")]
+    #[test_case(20, &[0, 12, 19]; "several")]
+    #[test_case(5, &[]; "short none")]
+    #[test_case(5, &[3]; "short one")]
+    #[test_case(5, &[0, 2, 4]; "short several")]
+    fn missing_properties(count: usize, missing: &[usize]) {
+        let required = names(count);
+        let validator =
+            crate::validator_for(&json!({"required": required})).expect("Invalid schema");
+        let instance = object(&required, missing);
+        let expected: Vec<_> = missing.iter().map(|&idx| json!(required[idx])).collect();
+        assert_eq!(validator.is_valid(&instance), missing.is_empty());
+        let errors: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| error.kind.missing_properties().to_vec())
+            .collect();
+        assert_eq!(errors.concat(), expected);
+        assert!(errors.iter().all(|properties| properties.len() == 1));
+        match validator.validate(&instance) {
+            Ok(()) => assert!(missing.is_empty()),
+            Err(error) => {
+                assert_eq!(error.kind.missing_properties(), expected);
+                assert_eq!(
+                    matches!(error.kind, ValidationErrorKind::Required { .. }),
+                    missing.len() == 1
+                );
+            }
+        }
+        assert!(validator.is_valid(&json!([])));
+    }
+
+    #[test]
+    fn long_list_message() {
+        let validator =
+            crate::validator_for(&json!({"required": names(16)})).expect("Invalid schema");
+        let instance = json!({"p0": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        let message = error.to_string();
+        assert!(message.starts_with(r#""p15", "p14", "p13", "p12", "p11", "p10", "p9", "#));
+        assert!(message.ends_with(r#""p1" are required properties"#));
+    }
+
+    #[test]
+    fn unsorted_keys() {
+        let required = names(20);
+        let validator = SortedRequiredValidator::new(&required, Location::new());
+        let mut found = Vec::new();
+        validator.walk(
+            ["p3", "p1", "zz", "p19", "p0", "a", "p4", "p5"].into_iter(),
+            |position| found.push(&*validator.sorted[position]),
+        );
+        assert_eq!(found, ["p3", "p1", "p19", "p0", "p4", "p5"]);
+    }
 }
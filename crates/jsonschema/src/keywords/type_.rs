@@ -1,10 +1,11 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{BoxedValidator, CompilationResult},
     paths::Location,
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
     validator::Validate,
+    value,
 };
 use serde_json::{json, Map, Number, Value};
 use std::convert::TryFrom;
@@ -38,8 +39,8 @@ impl MultipleTypesValidator {
                 }
                 _ => {
                     return Err(ValidationError::single_type_error(
-                        location,
                         Location::new(),
+                        location,
                         item,
                         PrimitiveType::String,
                     ))
@@ -48,6 +49,10 @@ impl MultipleTypesValidator {
         }
         Ok(Box::new(MultipleTypesValidator { types, location }))
     }
+    /// A validator for already parsed types.
+    pub(crate) fn boxed(types: PrimitiveTypesBitMap, location: Location) -> BoxedValidator {
+        Box::new(MultipleTypesValidator { types, location })
+    }
 }
 
 impl Validate for MultipleTypesValidator {
@@ -64,6 +69,16 @@ impl Validate for MultipleTypesValidator {
             Value::String(_) => self.types.contains_type(PrimitiveType::String),
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        match instance.primitive_type() {
+            PrimitiveType::Number | PrimitiveType::Integer => {
+                self.types.contains_type(PrimitiveType::Number)
+                    || (self.types.contains_type(PrimitiveType::Integer)
+                        && value::is_integer(instance))
+            }
+            primitive_type => self.types.contains_type(primitive_type),
+        }
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -97,6 +112,9 @@ impl Validate for NullTypeValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         instance.is_null()
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::Null
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -130,6 +148,9 @@ impl Validate for BooleanTypeValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         instance.is_boolean()
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::Boolean
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -163,6 +184,9 @@ impl Validate for StringTypeValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         instance.is_string()
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::String
+    }
 
     fn validate<'i>(
         &self,
@@ -197,6 +221,9 @@ impl Validate for ArrayTypeValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         instance.is_array()
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::Array
+    }
 
     fn validate<'i>(
         &self,
@@ -231,6 +258,9 @@ impl Validate for ObjectTypeValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         instance.is_object()
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::Object
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -264,6 +294,9 @@ impl Validate for NumberTypeValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         instance.is_number()
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::Number
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -301,6 +334,9 @@ impl Validate for IntegerTypeValidator {
             false
         }
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        instance.primitive_type() == PrimitiveType::Number && value::is_integer(instance)
+    }
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -319,8 +355,52 @@ impl Validate for IntegerTypeValidator {
     }
 }
 
-fn is_integer(num: &Number) -> bool {
-    num.is_u64() || num.is_i64() || num.as_f64().expect("Always valid").fract() == 0.
+/// Whether `num` has no fractional part, like `1`, `1.0` or `1e2`, as in Draft 6 and later.
+pub(crate) fn is_integer(num: &Number) -> bool {
+    if num.is_u64() || num.is_i64() {
+        return true;
+    }
+    #[cfg(feature = "arbitrary-precision")]
+    {
+        has_integer_value(&num.to_string())
+    }
+    #[cfg(not(feature = "arbitrary-precision"))]
+    {
+        num.as_f64().expect("Always valid").fract() == 0.
+    }
+}
+
+/// Whether the number written as `literal` has no fractional part. It is exact, unlike checking
+/// the nearest `f64`, e.g. `9007199254740993.5` is not an integer.
+#[cfg(feature = "arbitrary-precision")]
+fn has_integer_value(literal: &str) -> bool {
+    let unsigned = literal.strip_prefix('-').unwrap_or(literal);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => {
+            let exponent = exponent
+                .parse::<i64>()
+                .unwrap_or(if exponent.starts_with('-') {
+                    i64::MIN
+                } else {
+                    i64::MAX
+                });
+            (mantissa, exponent)
+        }
+        None => (unsigned, 0),
+    };
+    let (integral, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let shift = usize::try_from(exponent.unsigned_abs()).unwrap_or(usize::MAX);
+    // Digits that end up after the decimal point once the exponent is applied
+    if exponent >= 0 {
+        fraction.bytes().skip(shift).all(|digit| digit == b'0')
+    } else {
+        integral
+            .bytes()
+            .rev()
+            .take(shift)
+            .chain(fraction.bytes())
+            .all(|digit| digit == b'0')
+    }
 }
 
 #[inline]
@@ -351,7 +431,7 @@ pub(crate) fn compile<'a>(
         }
         _ => Some(Err(ValidationError::multiple_type_error(
             Location::new(),
-            ctx.location().clone(),
+            location,
             schema,
             PrimitiveTypesBitMap::new()
                 .add_type(PrimitiveType::String)
@@ -384,10 +464,85 @@ fn compile_single_type<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{tests_util, Draft};
     use serde_json::{json, Value};
     use test_case::test_case;
 
+    fn is_integer(draft: Draft, instance: &str) -> bool {
+        let instance: Value = serde_json::from_str(instance).expect("Invalid JSON");
+        [
+            json!({"type": "integer"}),
+            json!({"type": ["integer", "null"]}),
+        ]
+        .iter()
+        .map(|schema| {
+            crate::options()
+                .with_draft(draft)
+                .build(schema)
+                .expect("Invalid schema")
+                .is_valid(&instance)
+        })
+        .reduce(|single, multiple| {
+            assert_eq!(single, multiple, "{instance}");
+            single
+        })
+        .expect("Not empty")
+    }
+
+    #[test_case("1", true; "an integer is an integer")]
+    #[test_case("1.1", false; "a float is not an integer")]
+    #[test_case("1.5", false)]
+    #[test_case("1.0", false; "a float with zero fractional part is not an integer")]
+    #[test_case("1e2", false; "an exponent is not an integer")]
+    #[test_case("-0", true; "negative zero")]
+    #[test_case("9007199254740993.0", false)]
+    #[test_case("12345678910111213141516171819202122232425262728293031", true; "a bignum is an integer")]
+    #[test_case("-12345678910111213141516171819202122232425262728293031", true; "a negative bignum is an integer")]
+    #[test_case("\"1\"", false; "a string is not an integer")]
+    fn integer_draft4(instance: &str, expected: bool) {
+        assert_eq!(is_integer(Draft::Draft4, instance), expected);
+    }
+
+    #[test_case(Draft::Draft6)]
+    #[test_case(Draft::Draft7)]
+    #[test_case(Draft::Draft201909)]
+    #[test_case(Draft::Draft202012)]
+    fn integer(draft: Draft) {
+        for (instance, expected) in [
+            ("1", true),
+            ("1.1", false),
+            ("1.5", false),
+            ("1.0", true),
+            ("1e2", true),
+            ("1.5e1", true),
+            ("15e-1", false),
+            ("-0", true),
+            ("-0.0", true),
+            ("9007199254740993.0", true),
+            (
+                "12345678910111213141516171819202122232425262728293031",
+                true,
+            ),
+            ("\"1\"", false),
+        ] {
+            assert_eq!(is_integer(draft, instance), expected, "{instance}");
+        }
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case(Draft::Draft4, "-0.0", false)]
+    #[test_case(Draft::Draft4, "1e100", false)]
+    #[test_case(Draft::Draft4, "0.0", false)]
+    #[test_case(Draft::Draft7, "9007199254740993.5", false)]
+    #[test_case(Draft::Draft7, "100e-2", true)]
+    #[test_case(Draft::Draft7, "150e-2", false)]
+    #[test_case(Draft::Draft7, "0.001e3", true)]
+    #[test_case(Draft::Draft7, "1e-99999999999999999999", false)]
+    #[test_case(Draft::Draft7, "1.5e99999999999999999999", true)]
+    fn integer_exact(draft: Draft, instance: &str, expected: bool) {
+        assert_eq!(is_integer(draft, instance), expected);
+    }
+
     #[test_case(&json!({"type": "array"}), &json!(1), "/type")]
     #[test_case(&json!({"type": "boolean"}), &json!(1), "/type")]
     #[test_case(&json!({"type": "integer"}), &json!("f"), "/type")]
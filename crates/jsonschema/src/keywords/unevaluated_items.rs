@@ -1,11 +1,13 @@
 use referencing::Draft;
 use serde_json::{Map, Value};
+use std::sync::Arc;
 
 use crate::{
     compiler,
+    keywords::custom::{self, Keyword},
     node::SchemaNode,
     paths::{LazyLocation, Location},
-    validator::Validate,
+    validator::{Validate, ValidationScratch},
     ValidationError,
 };
 
@@ -46,39 +48,30 @@ impl<F: ItemsFilter> UnevaluatedItemsValidator<F> {
     }
 }
 
-impl<F: ItemsFilter> Validate for UnevaluatedItemsValidator<F> {
-    fn is_valid(&self, instance: &Value) -> bool {
-        if let Value::Array(items) = instance {
-            // NOTE: It could be a packed bitset instead
-            let mut indexes = vec![false; items.len()];
-            self.filter.mark_evaluated_indexes(instance, &mut indexes);
-
-            for (item, is_evaluated) in items.iter().zip(indexes) {
-                if !is_evaluated && !self.filter.is_valid(item) {
-                    return false;
-                }
-            }
-        }
-        true
+impl<F: ItemsFilter> UnevaluatedItemsValidator<F> {
+    fn mark_evaluated(&self, instance: &Value, len: usize, indexes: &mut Vec<bool>) {
+        // NOTE: It could be a packed bitset instead
+        indexes.clear();
+        indexes.resize(len, false);
+        self.filter.mark_evaluated_indexes(instance, indexes);
     }
 
-    fn validate<'i>(
+    fn unevaluated_error<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
-    ) -> Result<(), ValidationError<'i>> {
+        indexes: &mut Vec<bool>,
+    ) -> Option<ValidationError<'i>> {
         if let Value::Array(items) = instance {
-            // NOTE: It could be a packed bitset instead
-            let mut indexes = vec![false; items.len()];
-            self.filter.mark_evaluated_indexes(instance, &mut indexes);
+            self.mark_evaluated(instance, items.len(), indexes);
             let mut unevaluated = vec![];
-            for (item, is_evaluated) in items.iter().zip(indexes) {
+            for (item, is_evaluated) in items.iter().zip(indexes.iter()) {
                 if !is_evaluated && !self.filter.is_valid(item) {
                     unevaluated.push(item.to_string());
                 }
             }
             if !unevaluated.is_empty() {
-                return Err(ValidationError::unevaluated_items(
+                return Some(ValidationError::unevaluated_items(
                     self.location.clone(),
                     location.into(),
                     instance,
@@ -86,12 +79,52 @@ impl<F: ItemsFilter> Validate for UnevaluatedItemsValidator<F> {
                 ));
             }
         }
-        Ok(())
+        None
+    }
+}
+
+impl<F: ItemsFilter> Validate for UnevaluatedItemsValidator<F> {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::Array(items) = instance {
+            let mut indexes = Vec::new();
+            self.mark_evaluated(instance, items.len(), &mut indexes);
+
+            for (item, is_evaluated) in items.iter().zip(indexes) {
+                if !is_evaluated && !self.filter.is_valid(item) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        match self.unevaluated_error(instance, location, &mut Vec::new()) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        let mut indexes = std::mem::take(&mut scratch.evaluated);
+        errors.extend(self.unevaluated_error(instance, location, &mut indexes));
+        scratch.evaluated = indexes;
     }
 }
 
 struct Draft2019ItemsFilter {
     unevaluated: Option<SchemaNode>,
+    custom: Vec<Arc<dyn Keyword>>,
     contains: Option<SchemaNode>,
     ref_: Option<Box<Self>>,
     recursive_ref: Option<Box<Self>>,
@@ -127,22 +160,25 @@ impl ItemsFilter for Draft2019ItemsFilter {
         let mut conditional = None;
 
         if let Some(subschema) = parent.get("if") {
+            // `then` and `else` contribute even when `if` is a boolean schema
+            let mut if_ = None;
             if let Value::Object(if_parent) = subschema {
-                let mut then_ = None;
-                if let Some(Value::Object(subschema)) = parent.get("then") {
-                    then_ = Some(Self::new(ctx, subschema)?);
-                }
-                let mut else_ = None;
-                if let Some(Value::Object(subschema)) = parent.get("else") {
-                    else_ = Some(Self::new(ctx, subschema)?);
-                }
-                conditional = Some(Box::new(ConditionalFilter {
-                    condition: compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
-                    if_: Self::new(ctx, if_parent)?,
-                    then_,
-                    else_,
-                }));
+                if_ = Some(Self::new(ctx, if_parent)?);
+            }
+            let mut then_ = None;
+            if let Some(Value::Object(subschema)) = parent.get("then") {
+                then_ = Some(Self::new(ctx, subschema)?);
             }
+            let mut else_ = None;
+            if let Some(Value::Object(subschema)) = parent.get("else") {
+                else_ = Some(Self::new(ctx, subschema)?);
+            }
+            conditional = Some(Box::new(ConditionalFilter {
+                condition: compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
+                if_,
+                then_,
+                else_,
+            }));
         }
 
         let mut contains = None;
@@ -151,19 +187,20 @@ impl ItemsFilter for Draft2019ItemsFilter {
         };
         let mut unevaluated = None;
         if let Some(subschema) = parent.get("unevaluatedItems") {
-            unevaluated = Some(compiler::compile(ctx, ctx.as_resource_ref(subschema))?);
+            let kctx = ctx.new_at_location("unevaluatedItems");
+            unevaluated = Some(compiler::compile(&kctx, kctx.as_resource_ref(subschema))?);
         };
         let mut all_of = None;
         if let Some(Some(subschemas)) = parent.get("allOf").map(Value::as_array) {
-            all_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            all_of = Some(CombinatorFilter::new(ctx, "allOf", subschemas)?);
         };
         let mut any_of = None;
         if let Some(Some(subschemas)) = parent.get("anyOf").map(Value::as_array) {
-            any_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            any_of = Some(CombinatorFilter::new(ctx, "anyOf", subschemas)?);
         };
         let mut one_of = None;
         if let Some(Some(subschemas)) = parent.get("oneOf").map(Value::as_array) {
-            one_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            one_of = Some(CombinatorFilter::new(ctx, "oneOf", subschemas)?);
         };
         let mut items = None;
         if let Some(subschema) = parent.get("items") {
@@ -180,6 +217,7 @@ impl ItemsFilter for Draft2019ItemsFilter {
 
         Ok(Draft2019ItemsFilter {
             unevaluated,
+            custom: custom::compile_all(ctx, parent)?,
             contains,
             ref_,
             recursive_ref,
@@ -208,6 +246,8 @@ impl ItemsFilter for Draft2019ItemsFilter {
             recursive_ref.mark_evaluated_indexes(instance, indexes);
         }
 
+        custom::mark_evaluated_items(&self.custom, instance, indexes);
+
         if let Some(conditional) = &self.conditional {
             conditional.mark_evaluated_indexes(instance, indexes);
         }
@@ -266,6 +306,7 @@ impl ItemsFilter for Draft2019ItemsFilter {
 
 struct DefaultItemsFilter {
     unevaluated: Option<SchemaNode>,
+    custom: Vec<Arc<dyn Keyword>>,
     contains: Option<SchemaNode>,
     ref_: Option<Box<Self>>,
     dynamic_ref: Option<Box<Self>>,
@@ -303,22 +344,25 @@ impl ItemsFilter for DefaultItemsFilter {
         let mut conditional = None;
 
         if let Some(subschema) = parent.get("if") {
+            // `then` and `else` contribute even when `if` is a boolean schema
+            let mut if_ = None;
             if let Value::Object(if_parent) = subschema {
-                let mut then_ = None;
-                if let Some(Value::Object(subschema)) = parent.get("then") {
-                    then_ = Some(Self::new(ctx, subschema)?);
-                }
-                let mut else_ = None;
-                if let Some(Value::Object(subschema)) = parent.get("else") {
-                    else_ = Some(Self::new(ctx, subschema)?);
-                }
-                conditional = Some(Box::new(ConditionalFilter {
-                    condition: compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
-                    if_: Self::new(ctx, if_parent)?,
-                    then_,
-                    else_,
-                }));
+                if_ = Some(Self::new(ctx, if_parent)?);
             }
+            let mut then_ = None;
+            if let Some(Value::Object(subschema)) = parent.get("then") {
+                then_ = Some(Self::new(ctx, subschema)?);
+            }
+            let mut else_ = None;
+            if let Some(Value::Object(subschema)) = parent.get("else") {
+                else_ = Some(Self::new(ctx, subschema)?);
+            }
+            conditional = Some(Box::new(ConditionalFilter {
+                condition: compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
+                if_,
+                then_,
+                else_,
+            }));
         }
 
         let mut prefix_items = None;
@@ -332,24 +376,26 @@ impl ItemsFilter for DefaultItemsFilter {
         };
         let mut unevaluated = None;
         if let Some(subschema) = parent.get("unevaluatedItems") {
-            unevaluated = Some(compiler::compile(ctx, ctx.as_resource_ref(subschema))?);
+            let kctx = ctx.new_at_location("unevaluatedItems");
+            unevaluated = Some(compiler::compile(&kctx, kctx.as_resource_ref(subschema))?);
         };
         let mut all_of = None;
         if let Some(Some(subschemas)) = parent.get("allOf").map(Value::as_array) {
-            all_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            all_of = Some(CombinatorFilter::new(ctx, "allOf", subschemas)?);
         };
         let mut any_of = None;
         if let Some(Some(subschemas)) = parent.get("anyOf").map(Value::as_array) {
-            any_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            any_of = Some(CombinatorFilter::new(ctx, "anyOf", subschemas)?);
         };
 
         let mut one_of = None;
         if let Some(Some(subschemas)) = parent.get("oneOf").map(Value::as_array) {
-            one_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            one_of = Some(CombinatorFilter::new(ctx, "oneOf", subschemas)?);
         };
 
         Ok(DefaultItemsFilter {
             unevaluated,
+            custom: custom::compile_all(ctx, parent)?,
             contains,
             ref_,
             dynamic_ref,
@@ -381,6 +427,8 @@ impl ItemsFilter for DefaultItemsFilter {
             dynamic_ref.mark_evaluated_indexes(instance, indexes);
         }
 
+        custom::mark_evaluated_items(&self.custom, instance, indexes);
+
         if let Some(limit) = self.prefix_items {
             for idx in indexes.iter_mut().take(limit) {
                 *idx = true;
@@ -457,14 +505,18 @@ impl<F: ItemsFilter> CombinatorFilter<F> {
 impl<F: ItemsFilter> CombinatorFilter<F> {
     fn new<'a>(
         ctx: &'a compiler::Context,
+        keyword: &'static str,
         subschemas: &'a [Value],
     ) -> Result<CombinatorFilter<F>, ValidationError<'a>> {
+        let kctx = ctx.new_at_location(keyword);
         let mut buffer = Vec::with_capacity(subschemas.len());
-        for subschema in subschemas {
+        for (idx, subschema) in subschemas.iter().enumerate() {
             if let Value::Object(parent) = subschema {
+                let ctx = kctx.new_at_location(idx);
                 buffer.push((
-                    compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
-                    F::new(ctx, parent)?,
+                    compiler::compile(&ctx, ctx.as_resource_ref(subschema))
+                        .map_err(ValidationError::into_owned)?,
+                    F::new(&ctx, parent).map_err(ValidationError::into_owned)?,
                 ));
             }
         }
@@ -474,7 +526,7 @@ impl<F: ItemsFilter> CombinatorFilter<F> {
 
 struct ConditionalFilter<F> {
     condition: SchemaNode,
-    if_: F,
+    if_: Option<F>,
     then_: Option<F>,
     else_: Option<F>,
 }
@@ -482,7 +534,9 @@ struct ConditionalFilter<F> {
 impl<F: ItemsFilter> ConditionalFilter<F> {
     fn mark_evaluated_indexes(&self, instance: &Value, indexes: &mut Vec<bool>) {
         if self.condition.is_valid(instance) {
-            self.if_.mark_evaluated_indexes(instance, indexes);
+            if let Some(if_) = &self.if_ {
+                if_.mark_evaluated_indexes(instance, indexes);
+            }
             if let Some(then_) = &self.then_ {
                 then_.mark_evaluated_indexes(instance, indexes);
             }
@@ -591,4 +645,17 @@ mod tests {
         assert!(validator.is_valid(&valid_instance));
         assert!(validator.validate(&valid_instance).is_ok());
     }
+
+    #[test]
+    fn boolean_if() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "if": true,
+            "then": {"prefixItems": [true]},
+            "unevaluatedItems": false
+        });
+        let validator = crate::validator_for(&schema).expect("Schema should compile");
+        assert!(validator.is_valid(&json!([1])));
+        assert!(!validator.is_valid(&json!([1, 2])));
+    }
 }
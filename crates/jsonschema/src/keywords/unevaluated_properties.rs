@@ -1,16 +1,16 @@
 use std::{rc::Rc, sync::Arc};
 
-use ahash::AHashSet;
-use fancy_regex::Regex;
 use once_cell::sync::OnceCell;
 use referencing::{Draft, List, Registry, Resource, Uri, VocabularySet};
 use serde_json::{Map, Value};
 
 use crate::{
     compiler, ecma,
+    keywords::custom::{self, Keyword},
     node::SchemaNode,
     paths::{LazyLocation, Location},
-    validator::Validate,
+    regex::Regex,
+    validator::{Validate, ValidationScratch},
     ValidationError, ValidationOptions,
 };
 
@@ -30,11 +30,8 @@ pub(crate) trait PropertiesFilter: Send + Sync + Sized + 'static {
             .unwrap_or(false)
     }
 
-    fn mark_evaluated_properties<'i>(
-        &self,
-        instance: &'i Value,
-        properties: &mut AHashSet<&'i String>,
-    );
+    /// Mark the properties of `instance` evaluated by this schema, indexed by their position.
+    fn mark_evaluated_properties(&self, instance: &Value, evaluated: &mut [bool]);
 }
 
 pub(crate) struct UnevaluatedPropertiesValidator<F: PropertiesFilter> {
@@ -55,25 +52,30 @@ impl<F: PropertiesFilter> UnevaluatedPropertiesValidator<F> {
     }
 }
 
-impl<F: PropertiesFilter> Validate for UnevaluatedPropertiesValidator<F> {
-    fn validate<'i>(
+impl<F: PropertiesFilter> UnevaluatedPropertiesValidator<F> {
+    fn mark_evaluated(&self, instance: &Value, len: usize, evaluated: &mut Vec<bool>) {
+        evaluated.clear();
+        evaluated.resize(len, false);
+        self.filter.mark_evaluated_properties(instance, evaluated);
+    }
+
+    fn unevaluated_error<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
-    ) -> Result<(), ValidationError<'i>> {
+        evaluated: &mut Vec<bool>,
+    ) -> Option<ValidationError<'i>> {
         if let Value::Object(properties) = instance {
-            let mut evaluated = AHashSet::new();
-            self.filter
-                .mark_evaluated_properties(instance, &mut evaluated);
+            self.mark_evaluated(instance, properties.len(), evaluated);
 
             let mut unevaluated = vec![];
-            for (property, value) in properties {
-                if !evaluated.contains(property) && !self.filter.is_valid(value) {
+            for ((property, value), is_evaluated) in properties.iter().zip(evaluated.iter()) {
+                if !is_evaluated && !self.filter.is_valid(value) {
                     unevaluated.push(property.clone());
                 }
             }
             if !unevaluated.is_empty() {
-                return Err(ValidationError::unevaluated_properties(
+                return Some(ValidationError::unevaluated_properties(
                     self.location.clone(),
                     location.into(),
                     instance,
@@ -81,17 +83,41 @@ impl<F: PropertiesFilter> Validate for UnevaluatedPropertiesValidator<F> {
                 ));
             }
         }
-        Ok(())
+        None
+    }
+}
+
+impl<F: PropertiesFilter> Validate for UnevaluatedPropertiesValidator<F> {
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        match self.unevaluated_error(instance, location, &mut Vec::new()) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        let mut evaluated = std::mem::take(&mut scratch.evaluated);
+        errors.extend(self.unevaluated_error(instance, location, &mut evaluated));
+        scratch.evaluated = evaluated;
     }
 
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(properties) = instance {
-            let mut evaluated = AHashSet::new();
-            self.filter
-                .mark_evaluated_properties(instance, &mut evaluated);
+            let mut evaluated = Vec::new();
+            self.mark_evaluated(instance, properties.len(), &mut evaluated);
 
-            for (property, value) in properties {
-                if !evaluated.contains(property) && !self.filter.is_valid(value) {
+            for ((_, value), is_evaluated) in properties.iter().zip(evaluated) {
+                if !is_evaluated && !self.filter.is_valid(value) {
                     return false;
                 }
             }
@@ -102,10 +128,11 @@ impl<F: PropertiesFilter> Validate for UnevaluatedPropertiesValidator<F> {
 
 struct Draft2019PropertiesFilter {
     unevaluated: Option<SchemaNode>,
+    custom: Vec<Arc<dyn Keyword>>,
     additional: Option<SchemaNode>,
     properties: Vec<(String, SchemaNode)>,
     dependent: Vec<(String, Self)>,
-    pattern_properties: Vec<(fancy_regex::Regex, SchemaNode)>,
+    pattern_properties: Vec<(Regex, SchemaNode)>,
     ref_: Option<Box<Self>>,
     recursive_ref: Option<LazyReference<Self>>,
     conditional: Option<Box<ConditionalFilter<Self>>>,
@@ -120,17 +147,13 @@ enum ReferenceFilter<T> {
 }
 
 impl<F: PropertiesFilter> ReferenceFilter<F> {
-    fn mark_evaluated_properties<'i>(
-        &self,
-        instance: &'i Value,
-        properties: &mut AHashSet<&'i String>,
-    ) {
+    fn mark_evaluated_properties(&self, instance: &Value, evaluated: &mut [bool]) {
         match self {
             ReferenceFilter::Recursive(filter) => filter
                 .get_or_init()
-                .mark_evaluated_properties(instance, properties),
+                .mark_evaluated_properties(instance, evaluated),
             ReferenceFilter::Default(filter) => {
-                filter.mark_evaluated_properties(instance, properties)
+                filter.mark_evaluated_properties(instance, evaluated)
             }
         }
     }
@@ -223,22 +246,25 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
         let mut conditional = None;
 
         if let Some(subschema) = parent.get("if") {
+            // `then` and `else` contribute even when `if` is a boolean schema
+            let mut if_ = None;
             if let Value::Object(if_parent) = subschema {
-                let mut then_ = None;
-                if let Some(Value::Object(subschema)) = parent.get("then") {
-                    then_ = Some(Self::new(ctx, subschema)?);
-                }
-                let mut else_ = None;
-                if let Some(Value::Object(subschema)) = parent.get("else") {
-                    else_ = Some(Self::new(ctx, subschema)?);
-                }
-                conditional = Some(Box::new(ConditionalFilter {
-                    condition: compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
-                    if_: Self::new(ctx, if_parent)?,
-                    then_,
-                    else_,
-                }));
+                if_ = Some(Self::new(ctx, if_parent)?);
+            }
+            let mut then_ = None;
+            if let Some(Value::Object(subschema)) = parent.get("then") {
+                then_ = Some(Self::new(ctx, subschema)?);
             }
+            let mut else_ = None;
+            if let Some(Value::Object(subschema)) = parent.get("else") {
+                else_ = Some(Self::new(ctx, subschema)?);
+            }
+            conditional = Some(Box::new(ConditionalFilter {
+                condition: compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
+                if_,
+                then_,
+                else_,
+            }));
         }
 
         let mut properties = Vec::new();
@@ -268,15 +294,14 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
         let mut pattern_properties = Vec::new();
         if let Some(Value::Object(patterns)) = parent.get("patternProperties") {
             for (pattern, schema) in patterns {
+                ctx.record_pattern(pattern);
                 pattern_properties.push((
                     match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
                         Ok(Ok(r)) => r,
                         _ => {
-                            return Err(ValidationError::format(
-                                Location::new(),
-                                ctx.location().clone(),
-                                schema,
-                                "regex",
+                            return Err(ValidationError::invalid_pattern(
+                                ctx.location().join("patternProperties").join(pattern),
+                                pattern,
                             ))
                         }
                     },
@@ -287,25 +312,27 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
 
         let mut unevaluated = None;
         if let Some(subschema) = parent.get("unevaluatedProperties") {
-            unevaluated = Some(compiler::compile(ctx, ctx.as_resource_ref(subschema))?);
+            let kctx = ctx.new_at_location("unevaluatedProperties");
+            unevaluated = Some(compiler::compile(&kctx, kctx.as_resource_ref(subschema))?);
         };
 
         let mut all_of = None;
         if let Some(Some(subschemas)) = parent.get("allOf").map(Value::as_array) {
-            all_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            all_of = Some(CombinatorFilter::new(ctx, "allOf", subschemas)?);
         };
         let mut any_of = None;
         if let Some(Some(subschemas)) = parent.get("anyOf").map(Value::as_array) {
-            any_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            any_of = Some(CombinatorFilter::new(ctx, "anyOf", subschemas)?);
         };
 
         let mut one_of = None;
         if let Some(Some(subschemas)) = parent.get("oneOf").map(Value::as_array) {
-            one_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            one_of = Some(CombinatorFilter::new(ctx, "oneOf", subschemas)?);
         };
 
         Ok(Draft2019PropertiesFilter {
             unevaluated,
+            custom: custom::compile_all(ctx, parent)?,
             properties,
             dependent,
             additional,
@@ -319,44 +346,42 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
         })
     }
 
-    fn mark_evaluated_properties<'i>(
-        &self,
-        instance: &'i Value,
-        properties: &mut AHashSet<&'i String>,
-    ) {
+    fn mark_evaluated_properties(&self, instance: &Value, evaluated: &mut [bool]) {
         if let Some(ref_) = &self.ref_ {
-            ref_.mark_evaluated_properties(instance, properties);
+            ref_.mark_evaluated_properties(instance, evaluated);
         }
 
+        custom::mark_evaluated_properties(&self.custom, instance, evaluated);
+
         if let Some(recursive_ref) = &self.recursive_ref {
             recursive_ref
                 .get_or_init()
-                .mark_evaluated_properties(instance, properties);
+                .mark_evaluated_properties(instance, evaluated);
         }
 
         if let Value::Object(obj) = instance {
-            for (property, value) in obj {
+            for (idx, (property, value)) in obj.iter().enumerate() {
                 for (p, node) in &self.properties {
                     if property == p && node.is_valid(value) {
-                        properties.insert(property);
+                        evaluated[idx] = true;
                         continue;
                     }
                 }
                 if let Some(additional) = self.additional.as_ref() {
                     if additional.is_valid(value) {
-                        properties.insert(property);
+                        evaluated[idx] = true;
                         continue;
                     }
                 }
                 if let Some(unevaluated) = self.unevaluated.as_ref() {
                     if unevaluated.is_valid(value) {
-                        properties.insert(property);
+                        evaluated[idx] = true;
                         continue;
                     }
                 }
                 for (pattern, _) in &self.pattern_properties {
                     if pattern.is_match(property).unwrap() {
-                        properties.insert(property);
+                        evaluated[idx] = true;
                     }
                 }
             }
@@ -364,12 +389,12 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
                 if !obj.contains_key(property) {
                     continue;
                 }
-                subschema.mark_evaluated_properties(instance, properties);
+                subschema.mark_evaluated_properties(instance, evaluated);
             }
         }
 
         if let Some(conditional) = &self.conditional {
-            conditional.mark_evaluated_properties(instance, properties);
+            conditional.mark_evaluated_properties(instance, evaluated);
         }
 
         if let Some(combinator) = &self.all_of {
@@ -378,7 +403,7 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
                 .iter()
                 .all(|(v, _)| v.is_valid(instance))
             {
-                combinator.mark_evaluated_properties(instance, properties);
+                combinator.mark_evaluated_properties(instance, evaluated);
             }
         }
 
@@ -388,7 +413,7 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
                 .iter()
                 .any(|(v, _)| v.is_valid(instance))
             {
-                combinator.mark_evaluated_properties(instance, properties);
+                combinator.mark_evaluated_properties(instance, evaluated);
             }
         }
 
@@ -401,7 +426,7 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
             if result.iter().filter(|v| **v).count() == 1 {
                 for ((_, subschema), is_valid) in combinator.subschemas.iter().zip(result) {
                     if is_valid {
-                        subschema.mark_evaluated_properties(instance, properties);
+                        subschema.mark_evaluated_properties(instance, evaluated);
                         break;
                     }
                 }
@@ -416,10 +441,11 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
 
 struct DefaultPropertiesFilter {
     unevaluated: Option<SchemaNode>,
+    custom: Vec<Arc<dyn Keyword>>,
     additional: Option<SchemaNode>,
     properties: Vec<(String, SchemaNode)>,
     dependent: Vec<(String, Self)>,
-    pattern_properties: Vec<(fancy_regex::Regex, SchemaNode)>,
+    pattern_properties: Vec<(Regex, SchemaNode)>,
     ref_: Option<ReferenceFilter<Self>>,
     dynamic_ref: Option<Box<Self>>,
     conditional: Option<Box<ConditionalFilter<Self>>>,
@@ -480,22 +506,25 @@ impl PropertiesFilter for DefaultPropertiesFilter {
         let mut conditional = None;
 
         if let Some(subschema) = parent.get("if") {
+            // `then` and `else` contribute even when `if` is a boolean schema
+            let mut if_ = None;
             if let Value::Object(if_parent) = subschema {
-                let mut then_ = None;
-                if let Some(Value::Object(subschema)) = parent.get("then") {
-                    then_ = Some(Self::new(ctx, subschema)?);
-                }
-                let mut else_ = None;
-                if let Some(Value::Object(subschema)) = parent.get("else") {
-                    else_ = Some(Self::new(ctx, subschema)?);
-                }
-                conditional = Some(Box::new(ConditionalFilter {
-                    condition: compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
-                    if_: Self::new(ctx, if_parent)?,
-                    then_,
-                    else_,
-                }));
+                if_ = Some(Self::new(ctx, if_parent)?);
             }
+            let mut then_ = None;
+            if let Some(Value::Object(subschema)) = parent.get("then") {
+                then_ = Some(Self::new(ctx, subschema)?);
+            }
+            let mut else_ = None;
+            if let Some(Value::Object(subschema)) = parent.get("else") {
+                else_ = Some(Self::new(ctx, subschema)?);
+            }
+            conditional = Some(Box::new(ConditionalFilter {
+                condition: compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
+                if_,
+                then_,
+                else_,
+            }));
         }
 
         let mut properties = Vec::new();
@@ -525,15 +554,14 @@ impl PropertiesFilter for DefaultPropertiesFilter {
         let mut pattern_properties = Vec::new();
         if let Some(Value::Object(patterns)) = parent.get("patternProperties") {
             for (pattern, schema) in patterns {
+                ctx.record_pattern(pattern);
                 pattern_properties.push((
                     match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
                         Ok(Ok(r)) => r,
                         _ => {
-                            return Err(ValidationError::format(
-                                Location::new(),
-                                ctx.location().clone(),
-                                schema,
-                                "regex",
+                            return Err(ValidationError::invalid_pattern(
+                                ctx.location().join("patternProperties").join(pattern),
+                                pattern,
                             ))
                         }
                     },
@@ -544,25 +572,27 @@ impl PropertiesFilter for DefaultPropertiesFilter {
 
         let mut unevaluated = None;
         if let Some(subschema) = parent.get("unevaluatedProperties") {
-            unevaluated = Some(compiler::compile(ctx, ctx.as_resource_ref(subschema))?);
+            let kctx = ctx.new_at_location("unevaluatedProperties");
+            unevaluated = Some(compiler::compile(&kctx, kctx.as_resource_ref(subschema))?);
         };
 
         let mut all_of = None;
         if let Some(Some(subschemas)) = parent.get("allOf").map(Value::as_array) {
-            all_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            all_of = Some(CombinatorFilter::new(ctx, "allOf", subschemas)?);
         };
         let mut any_of = None;
         if let Some(Some(subschemas)) = parent.get("anyOf").map(Value::as_array) {
-            any_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            any_of = Some(CombinatorFilter::new(ctx, "anyOf", subschemas)?);
         };
 
         let mut one_of = None;
         if let Some(Some(subschemas)) = parent.get("oneOf").map(Value::as_array) {
-            one_of = Some(CombinatorFilter::new(ctx, subschemas)?);
+            one_of = Some(CombinatorFilter::new(ctx, "oneOf", subschemas)?);
         };
 
         Ok(DefaultPropertiesFilter {
             unevaluated,
+            custom: custom::compile_all(ctx, parent)?,
             properties,
             dependent,
             additional,
@@ -576,42 +606,40 @@ impl PropertiesFilter for DefaultPropertiesFilter {
         })
     }
 
-    fn mark_evaluated_properties<'i>(
-        &self,
-        instance: &'i Value,
-        properties: &mut AHashSet<&'i String>,
-    ) {
+    fn mark_evaluated_properties(&self, instance: &Value, evaluated: &mut [bool]) {
         if let Some(ref_) = &self.ref_ {
-            ref_.mark_evaluated_properties(instance, properties);
+            ref_.mark_evaluated_properties(instance, evaluated);
         }
 
+        custom::mark_evaluated_properties(&self.custom, instance, evaluated);
+
         if let Some(recursive_ref) = &self.dynamic_ref {
-            recursive_ref.mark_evaluated_properties(instance, properties);
+            recursive_ref.mark_evaluated_properties(instance, evaluated);
         }
 
         if let Value::Object(obj) = instance {
-            for (property, value) in obj {
+            for (idx, (property, value)) in obj.iter().enumerate() {
                 for (p, node) in &self.properties {
                     if property == p && node.is_valid(value) {
-                        properties.insert(property);
+                        evaluated[idx] = true;
                         continue;
                     }
                 }
                 if let Some(additional) = self.additional.as_ref() {
                     if additional.is_valid(value) {
-                        properties.insert(property);
+                        evaluated[idx] = true;
                         continue;
                     }
                 }
                 if let Some(unevaluated) = self.unevaluated.as_ref() {
                     if unevaluated.is_valid(value) {
-                        properties.insert(property);
+                        evaluated[idx] = true;
                         continue;
                     }
                 }
                 for (pattern, _) in &self.pattern_properties {
                     if pattern.is_match(property).unwrap() {
-                        properties.insert(property);
+                        evaluated[idx] = true;
                     }
                 }
             }
@@ -619,12 +647,12 @@ impl PropertiesFilter for DefaultPropertiesFilter {
                 if !obj.contains_key(property) {
                     continue;
                 }
-                subschema.mark_evaluated_properties(instance, properties);
+                subschema.mark_evaluated_properties(instance, evaluated);
             }
         }
 
         if let Some(conditional) = &self.conditional {
-            conditional.mark_evaluated_properties(instance, properties);
+            conditional.mark_evaluated_properties(instance, evaluated);
         }
 
         if let Some(combinator) = &self.all_of {
@@ -633,7 +661,7 @@ impl PropertiesFilter for DefaultPropertiesFilter {
                 .iter()
                 .all(|(v, _)| v.is_valid(instance))
             {
-                combinator.mark_evaluated_properties(instance, properties);
+                combinator.mark_evaluated_properties(instance, evaluated);
             }
         }
 
@@ -643,7 +671,7 @@ impl PropertiesFilter for DefaultPropertiesFilter {
                 .iter()
                 .any(|(v, _)| v.is_valid(instance))
             {
-                combinator.mark_evaluated_properties(instance, properties);
+                combinator.mark_evaluated_properties(instance, evaluated);
             }
         }
 
@@ -656,7 +684,7 @@ impl PropertiesFilter for DefaultPropertiesFilter {
             if result.iter().filter(|v| **v).count() == 1 {
                 for ((_, subschema), is_valid) in combinator.subschemas.iter().zip(result) {
                     if is_valid {
-                        subschema.mark_evaluated_properties(instance, properties);
+                        subschema.mark_evaluated_properties(instance, evaluated);
                         break;
                     }
                 }
@@ -674,13 +702,9 @@ struct CombinatorFilter<F> {
 }
 
 impl<F: PropertiesFilter> CombinatorFilter<F> {
-    fn mark_evaluated_properties<'i>(
-        &self,
-        instance: &'i Value,
-        properties: &mut AHashSet<&'i String>,
-    ) {
+    fn mark_evaluated_properties(&self, instance: &Value, evaluated: &mut [bool]) {
         for (_, subschema) in &self.subschemas {
-            subschema.mark_evaluated_properties(instance, properties);
+            subschema.mark_evaluated_properties(instance, evaluated);
         }
     }
 }
@@ -688,14 +712,18 @@ impl<F: PropertiesFilter> CombinatorFilter<F> {
 impl<F: PropertiesFilter> CombinatorFilter<F> {
     fn new<'a>(
         ctx: &'a compiler::Context,
+        keyword: &'static str,
         subschemas: &'a [Value],
     ) -> Result<CombinatorFilter<F>, ValidationError<'a>> {
+        let kctx = ctx.new_at_location(keyword);
         let mut buffer = Vec::with_capacity(subschemas.len());
-        for subschema in subschemas {
+        for (idx, subschema) in subschemas.iter().enumerate() {
             if let Value::Object(parent) = subschema {
+                let ctx = kctx.new_at_location(idx);
                 buffer.push((
-                    compiler::compile(ctx, ctx.as_resource_ref(subschema))?,
-                    F::new(ctx, parent)?,
+                    compiler::compile(&ctx, ctx.as_resource_ref(subschema))
+                        .map_err(ValidationError::into_owned)?,
+                    F::new(&ctx, parent).map_err(ValidationError::into_owned)?,
                 ));
             }
         }
@@ -705,24 +733,22 @@ impl<F: PropertiesFilter> CombinatorFilter<F> {
 
 struct ConditionalFilter<F> {
     condition: SchemaNode,
-    if_: F,
+    if_: Option<F>,
     then_: Option<F>,
     else_: Option<F>,
 }
 
 impl<F: PropertiesFilter> ConditionalFilter<F> {
-    fn mark_evaluated_properties<'i>(
-        &self,
-        instance: &'i Value,
-        properties: &mut AHashSet<&'i String>,
-    ) {
+    fn mark_evaluated_properties(&self, instance: &Value, evaluated: &mut [bool]) {
         if self.condition.is_valid(instance) {
-            self.if_.mark_evaluated_properties(instance, properties);
+            if let Some(if_) = &self.if_ {
+                if_.mark_evaluated_properties(instance, evaluated);
+            }
             if let Some(then_) = &self.then_ {
-                then_.mark_evaluated_properties(instance, properties);
+                then_.mark_evaluated_properties(instance, evaluated);
             }
         } else if let Some(else_) = &self.else_ {
-            else_.mark_evaluated_properties(instance, properties);
+            else_.mark_evaluated_properties(instance, evaluated);
         }
     }
 }
@@ -754,6 +780,7 @@ pub(crate) fn compile<'a>(
 mod tests {
     use crate::{tests_util, Draft};
     use serde_json::json;
+    use test_case::test_case;
 
     #[test]
     fn one_of() {
@@ -926,4 +953,23 @@ mod tests {
         assert!(!validator.is_valid(&instance));
         assert!(validator.validate(&instance).is_err());
     }
+
+    #[test_case(Draft::Draft201909)]
+    #[test_case(Draft::Draft202012)]
+    fn boolean_if(draft: Draft) {
+        let schema = json!({
+            "if": true,
+            "then": {"properties": {"foo": true}},
+            "unevaluatedProperties": false
+        });
+        tests_util::is_valid_with_draft(draft, &schema, &json!({"foo": 1}));
+        tests_util::is_not_valid_with_draft(draft, &schema, &json!({"bar": 1}));
+        let schema = json!({
+            "if": false,
+            "else": {"properties": {"foo": true}},
+            "unevaluatedProperties": false
+        });
+        tests_util::is_valid_with_draft(draft, &schema, &json!({"foo": 1}));
+        tests_util::is_not_valid_with_draft(draft, &schema, &json!({"bar": 1}));
+    }
 }
@@ -3,7 +3,9 @@ use crate::{
     error::ValidationError,
     keywords::{helpers::equal, CompilationResult},
     paths::Location,
+    primitive_type::PrimitiveType,
     validator::Validate,
+    value,
 };
 use ahash::{AHashSet, AHasher};
 use serde_json::{Map, Value};
@@ -30,7 +32,7 @@ impl Hash for HashedValue<'_> {
             Value::Bool(ref item) => item.hash(state),
             Value::Number(ref item) => {
                 if let Some(number) = item.as_f64() {
-                    number.to_bits().hash(state)
+                    hash_number(number, state);
                 } else if let Some(number) = item.as_u64() {
                     number.hash(state);
                 } else if let Some(number) = item.as_i64() {
@@ -59,6 +61,18 @@ impl Hash for HashedValue<'_> {
     }
 }
 
+/// Hash a number so that numbers comparing equal hash the same, e.g. `0` and `-0.0`, or `1` and
+/// `1.0`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn hash_number<H: Hasher>(number: f64, state: &mut H) {
+    if number.fract() == 0.0 && number >= i64::MIN as f64 && number < i64::MAX as f64 {
+        // Also maps `-0.0` to `0`
+        (number as i64).hash(state);
+    } else {
+        number.to_bits().hash(state);
+    }
+}
+
 // Empirically calculated threshold after which the validator resorts to hashing.
 // Calculated for an array of mixed types, large homogeneous arrays of primitive values might be
 // processed faster with different thresholds, but this one gives a good baseline for the common
@@ -96,6 +110,69 @@ pub(crate) fn is_unique(items: &[Value]) -> bool {
     }
 }
 
+/// The same as `HashedValue`, but for values that are not `serde_json::Value`.
+struct HashedDynValue<'a>(&'a dyn value::Value);
+
+impl PartialEq for HashedDynValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        value::equal_values(self.0, other.0)
+    }
+}
+
+impl Eq for HashedDynValue<'_> {}
+
+impl Hash for HashedDynValue<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.0.primitive_type() {
+            PrimitiveType::Null => state.write_u32(3_221_225_473),
+            PrimitiveType::Boolean => self.0.as_bool().hash(state),
+            PrimitiveType::Number | PrimitiveType::Integer => {
+                if let Some(number) = self.0.as_f64() {
+                    hash_number(number, state);
+                }
+            }
+            PrimitiveType::String => self.0.as_str().hash(state),
+            PrimitiveType::Array => {
+                for item in value::array_iter(self.0) {
+                    HashedDynValue(item).hash(state);
+                }
+            }
+            PrimitiveType::Object => {
+                let mut hash = 0;
+                for (key, value) in self.0.object_iter() {
+                    let mut item_hasher = AHasher::default();
+                    key.hash(&mut item_hasher);
+                    HashedDynValue(value).hash(&mut item_hasher);
+                    hash ^= item_hasher.finish();
+                }
+                state.write_u64(hash);
+            }
+        }
+    }
+}
+
+fn is_unique_value(instance: &dyn value::Value) -> bool {
+    let size = instance.array_len().unwrap_or(0);
+    if size <= 1 {
+        true
+    } else if size <= ITEMS_SIZE_THRESHOLD {
+        let items: Vec<_> = value::array_iter(instance).collect();
+        for (idx, item) in items.iter().enumerate() {
+            for other in &items[idx + 1..] {
+                if value::equal_values(*item, *other) {
+                    return false;
+                }
+            }
+        }
+        true
+    } else {
+        let mut seen = AHashSet::with_capacity(size);
+        value::array_iter(instance)
+            .map(HashedDynValue)
+            .all(move |x| seen.insert(x))
+    }
+}
+
 pub(crate) struct UniqueItemsValidator {
     location: Location,
 }
@@ -116,6 +193,9 @@ impl Validate for UniqueItemsValidator {
         }
         true
     }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        is_unique_value(instance)
+    }
 
     fn validate<'i>(
         &self,
@@ -154,7 +234,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use super::{is_unique, ITEMS_SIZE_THRESHOLD};
+    use super::{is_unique, is_unique_value, ITEMS_SIZE_THRESHOLD};
     use crate::tests_util;
     use serde_json::{json, Value};
     use test_case::test_case;
@@ -176,6 +256,9 @@ mod tests {
     #[test_case(&[json!(1), json!(2), json!(1)] => false; "three non-unique elements")]
     #[test_case(&[json!(1), json!(2), json!(3), json!(4), json!(5), json!(6), json!(7), json!(8), json!(9), json!(10), json!(11), json!(12), json!(13), json!(14), json!(15), json!(1.0)] => false; "positive numbers with fractions")]
     #[test_case(&[json!(-1), json!(-2), json!(-3), json!(-4), json!(-5), json!(-6), json!(-7), json!(-8), json!(-9), json!(-10), json!(-11), json!(-12), json!(-13), json!(-14), json!(-15), json!(-1.0)] => false; "negative numbers with fractions")]
+    #[test_case(&[json!(0), json!(1), json!(2), json!(3), json!(4), json!(5), json!(6), json!(7), json!(8), json!(9), json!(10), json!(11), json!(12), json!(13), json!(14), json!(-0.0)] => false; "negative zero")]
+    #[test_case(&[json!(0.0), json!(1), json!(2), json!(3), json!(4), json!(5), json!(6), json!(7), json!(8), json!(9), json!(10), json!(11), json!(12), json!(13), json!(14), json!([-0.0])] => true; "negative zero in array")]
+    #[test_case(&[json!([0.0]), json!(1), json!(2), json!(3), json!(4), json!(5), json!(6), json!(7), json!(8), json!(9), json!(10), json!(11), json!(12), json!(13), json!(14), json!([-0.0])] => false; "nested negative zero")]
     #[test_case(&[json!(1), json!("string"), json!(true), json!(null), json!({"key": "value"}), json!([1, 2, 3])] => true; "mixed types")]
     #[test_case(&[json!({"a": 1, "b": 1}), json!({"a": 1, "b": 2}), json!({"a": 1, "b": 3})] => true; "complex objects unique")]
     #[test_case(&[json!({"a": 1, "b": 2}), json!({"b": 2, "a": 1}), json!({"a": 1, "b": 2})] => false; "complex objects non-unique")]
@@ -197,4 +280,16 @@ mod tests {
         arr[size - 1] = json!(1);
         is_unique(&arr)
     }
+
+    #[test_case(json!(-0.0); "negative zero")]
+    #[test_case(json!(0.0); "zero")]
+    #[test_case(json!(3.0); "integer-valued float")]
+    fn foreign_values_hash_like_equal_numbers(duplicate: Value) {
+        let mut items = (0..=ITEMS_SIZE_THRESHOLD)
+            .map(|i| json!(i))
+            .collect::<Vec<_>>();
+        items.push(duplicate);
+        assert!(!is_unique(&items));
+        assert!(!is_unique_value(&Value::Array(items)));
+    }
 }
@@ -171,9 +171,9 @@
 //!
 //! You can disable the default behavior using crate features:
 //!
-//! - Disable HTTP resolving: `default-features = false, features = ["resolve-file"]`
-//! - Disable file resolving: `default-features = false, features = ["resolve-http"]`
-//! - Disable both: `default-features = false`
+//! - Disable HTTP resolving: `default-features = false, features = ["resolve-file", "formats", "content", "fancy-regex"]`
+//! - Disable file resolving: `default-features = false, features = ["resolve-http", "formats", "content", "fancy-regex"]`
+//! - Disable both: `default-features = false, features = ["formats", "content", "fancy-regex"]`
 //!
 //! You can implement a custom retriever to handle external references. Here's an example that uses a static map of schemas:
 //!
@@ -400,6 +400,15 @@
 //! # }
 //! ```
 //!
+//! Factories registered with [`ValidationOptions::with_contextual_keyword`] are also passed a
+//! [`KeywordContext`], which resolves references in the keyword's value to data that is not a
+//! schema, like a table of allowed values, and provides the location of the schema containing
+//! the keyword. Errors returned by factories without an instance path are located at the keyword.
+//!
+//! Data that changes between validations, like the current time, can be passed to
+//! [`Validator::validate_with_context`] and is available to keywords implementing
+//! [`Keyword::validate_with_context`].
+//!
 //! # Custom Formats
 //!
 //! JSON Schema allows for format validation through the `format` keyword. While `jsonschema`
@@ -447,55 +456,248 @@
 //! - Format validation can be disabled globally or per-draft using [`ValidationOptions`].
 //!   Ensure format validation is enabled if you're using custom formats.
 //!
-//! # WebAssembly support
+//! # Linting Schemas
 //!
-//! When using `jsonschema` in WASM environments, be aware that external references are
-//! not supported by default due to WASM limitations:
-//!    - No filesystem access (`resolve-file` feature)
-//!    - No direct HTTP requests, at least right now (`resolve-http` feature)
+//! [`lint`] reports schema mistakes that validation alone does not catch, like `minimum` next to
+//! `"type": "string"`, `minLength` above `maxLength`, or `enum` values that the sibling keywords
+//! reject. Each [`Lint`] has a [`LintCode`], the location of the offending keyword and a message.
+//! [`ValidationOptions::with_denied_lints`] turns selected lints into build errors.
+//! With [`OptimizationLevel::Prune`], parts of the schema that never affect validation are
+//! left out of the validator and reported via [`Validator::warnings`] in the same form.
+//!
+//! ```rust
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use jsonschema::{Draft, LintCode};
+//! use serde_json::json;
+//!
+//! let schema = json!({"type": "string", "minimum": 3});
+//! for lint in jsonschema::lint(&schema, Draft::Draft202012)? {
+//!     println!("{lint}");
+//! }
 //!
-//! To use `jsonschema` in WASM, disable default features:
+//! let result = jsonschema::options()
+//!     .with_denied_lints([LintCode::IgnoredKeyword])
+//!     .build(&schema);
+//! assert!(result.is_err());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Parallel Validation
+//!
+//! With the `parallel` feature enabled, `Validator::par_is_valid` and
+//! `Validator::par_iter_errors` split the items of large top-level arrays between the threads
+//! of the [`rayon`](https://docs.rs/rayon) thread pool, and `Validator::par_validate_batch`
+//! validates many instances at once on it:
 //!
 //! ```toml
-//! jsonschema = { version = "x.y.z", default-features = false }
+//! jsonschema = { version = "x.y.z", features = ["parallel"] }
 //! ```
 //!
+//! # Diagnostics
+//!
+//! The `diagnostics` feature adds `diagnostics::Report` that renders validation errors against
+//! the source text of the instance, with the offending value underlined like in compiler output.
+//!
+//! # Instrumentation
+//!
+//! The `instrumentation` feature adds `ValidationOptions::with_instrumentation`, which reports
+//! the time spent in every keyword of a schema to an `instrumentation::KeywordHook`.
+//! `instrumentation::TimingCollector` sums it up per keyword location, to find the keywords
+//! that dominate validation time.
+//!
+//! ```toml
+//! jsonschema = { version = "x.y.z", features = ["instrumentation"] }
+//! ```
+//!
+//! # Tracing
+//!
+//! The `tracing` feature emits [`tracing`](https://docs.rs/tracing) spans and events:
+//!
+//! - An `info` span `build` for every validator build, with the draft, the number of compiled
+//!   schema nodes and the number of retrieved documents.
+//! - An `info` span `retrieve` for every retrieved document, with its URI.
+//! - A `debug` span for every call to `Validator::validate`, `Validator::iter_errors`,
+//!   `Validator::is_valid` and the like, but not for the keywords they check.
+//! - A `debug` event for every validation error, with its instance and schema locations.
+//!
+//! Fields and messages are only formatted if the subscriber enables their level.
+//!
+//! ```toml
+//! jsonschema = { version = "x.y.z", features = ["tracing"] }
+//! ```
+//!
+//! # YAML and CBOR Instances
+//!
+//! The `yaml` and `cbor` features add `from_yaml_str` and `from_cbor_slice` that convert
+//! documents into [`serde_json::Value`], and `Validator::is_valid_yaml` /
+//! `Validator::is_valid_cbor` shortcuts. Values without a JSON counterpart, like YAML sequence
+//! keys or CBOR byte strings, produce a `ConversionError` with the path to the offending value.
+//!
+//! ```toml
+//! jsonschema = { version = "x.y.z", features = ["yaml", "cbor"] }
+//! ```
+//!
+//! # Instance Generation
+//!
+//! The `generate` feature adds `generate` and `Generator` that build instances valid under a
+//! schema, e.g. examples for documentation or contract tests. The output depends only on the
+//! state of the given [`rand`](https://docs.rs/rand) generator, so a seeded one produces stable
+//! results. Schemas that can not be satisfied produce a `GenerateError` with the location of
+//! the offending keyword.
+//!
+//! `generate_invalid` builds instances that break exactly one keyword instead, together with
+//! the schema and instance locations the validator reports for them, e.g. for fuzzing handlers
+//! of invalid input.
+//!
+//! ```toml
+//! jsonschema = { version = "x.y.z", features = ["generate"] }
+//! ```
+//!
+//! # Arbitrary Precision Numbers
+//!
+//! Numbers are compared as `u64`, `i64` or `f64`, so the fractional part of a number like
+//! `9007199254740993.5` is lost when checking whether it is an `integer`. The
+//! `arbitrary-precision` feature enables `serde_json/arbitrary_precision` and checks how such
+//! numbers are written instead. Draft 4 requires integers to be written without a fraction or
+//! exponent, which is only known exactly with this feature.
+//! Numbers beyond the range of `f64`, like `1e400`, are then accepted as well. Keywords like
+//! `minimum` compare them as infinities of their sign, while `const` and `enum` compare two such
+//! numbers as they are written.
+//!
+//! ```toml
+//! jsonschema = { version = "x.y.z", features = ["arbitrary-precision"] }
+//! ```
+//!
+//! # Conformance Testing
+//!
+//! The `testing` feature adds `testsuite::run`, which runs the official
+//! [JSON Schema Test Suite](https://github.com/json-schema-org/JSON-Schema-Test-Suite) from a
+//! local checkout against validators built with your own `ValidationOptions`, and returns a
+//! `testsuite::Report` with every failing test. Cases your configuration does not support can be
+//! skipped with `testsuite::Filter::skip`.
+//!
+//! ```toml
+//! [dev-dependencies]
+//! jsonschema = { version = "x.y.z", features = ["testing"] }
+//! ```
+//!
+//! # Optional Features
+//!
+//! The following default features can be disabled to reduce binary size:
+//!
+//! - `formats`: built-in `format` checks. Without it only custom formats registered via
+//!   [`ValidationOptions::with_format`] are validated; built-in format names are accepted as
+//!   annotations.
+//! - `content`: the built-in `base64` encoding and `application/json` media type for
+//!   `contentEncoding` and `contentMediaType`. Custom ones can still be registered.
+//! - `fancy-regex`: look-around and backreferences in `pattern` and `patternProperties`.
+//!   Without it, patterns are matched in linear time and such constructs make the schema
+//!   invalid.
+//!
+//! ```toml
+//! jsonschema = { version = "x.y.z", default-features = false, features = ["resolve-file", "resolve-http"] }
+//! ```
+//!
+//! # WebAssembly support
+//!
+//! `jsonschema` builds for WASM targets, including `wasm32-unknown-unknown`, with default
+//! features. Meta-schemas are embedded in the crate, but external references are not
+//! retrieved due to WASM limitations:
+//!    - No filesystem access (`resolve-file` feature)
+//!    - No direct HTTP requests, at least right now (`resolve-http` feature)
+//!
+//! Both features have no effect on WASM and schemas with external references fail to build
+//! with [`ValidationErrorKind::Referencing`](error::ValidationErrorKind::Referencing).
+//! On `wasm32-unknown-unknown`, hash maps are seeded without a runtime source of randomness.
+//!
 //! For external references in WASM you may want to implement a custom retriever.
 //! See the [External References](#external-references) section for implementation details.
 
+mod batch;
+mod cache;
+mod compare;
 pub(crate) mod compiler;
 mod content_encoding;
 mod content_media_type;
+#[cfg(any(feature = "yaml", feature = "cbor"))]
+mod convert;
+mod deserialize;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
 mod ecma;
 pub mod error;
+pub mod fix;
+#[cfg(feature = "generate")]
+mod generate;
+mod incremental;
+#[cfg(feature = "instrumentation")]
+pub mod instrumentation;
 mod keywords;
+mod lint;
 mod node;
+mod normalize;
+mod optimize;
 mod options;
 pub mod output;
 pub mod paths;
 pub mod primitive_type;
+mod projection;
 pub(crate) mod properties;
+mod prune;
+mod regex;
 mod retriever;
+mod serialize;
+mod source;
+mod stats;
+#[cfg(feature = "testing")]
+pub mod testsuite;
 mod validator;
+pub mod value;
+mod walk;
 
+pub use batch::{BatchMode, BatchOptions, BatchReport};
+#[cfg(feature = "generate")]
+pub use compare::compare_generated;
+pub use compare::{compare, CompareReport, Disagreement, Rejection, Side};
+#[cfg(feature = "cbor")]
+pub use convert::from_cbor_slice;
+#[cfg(feature = "yaml")]
+pub use convert::from_yaml_str;
+#[cfg(any(feature = "yaml", feature = "cbor"))]
+pub use convert::{ConversionError, ConversionErrorKind};
+pub use deserialize::ValidatingDeserializer;
 pub use error::{ErrorIterator, ValidationError};
-pub use keywords::custom::Keyword;
-pub use options::ValidationOptions;
+pub use fix::suggest_fixes;
+#[cfg(feature = "generate")]
+pub use generate::{
+    generate, generate_invalid, GenerateError, GenerateErrorKind, Generator, InvalidInstance,
+};
+pub use incremental::ValidationState;
+pub use keywords::custom::{downcast_context, Keyword, KeywordContext};
+pub use lint::{lint, Lint, LintCode};
+pub use normalize::{normalize, NormalizeError, NormalizeErrorKind, Normalizer};
+pub use options::{
+    Dialect, EmailStrictness, KeywordOrder, MetaSchemaPolicy, OptimizationLevel, ValidationOptions,
+};
 pub use output::BasicOutput;
-pub use referencing::{Draft, Error as ReferencingError, Resource, Retrieve, Uri};
-pub use validator::Validator;
+pub use projection::ConstrainedPaths;
+pub use prune::prune;
+pub use referencing;
+pub use referencing::{Draft, Error as ReferencingError, Registry, Resource, Retrieve, Uri};
+pub use source::{SourceSpan, SpannedError, ValidateStrError};
+pub use stats::SchemaStats;
+pub use validator::{Budget, CompiledRegistry, ValidationScratch, Validator, ValidatorConfig};
+pub use walk::{walk, Walk};
 
 use serde_json::Value;
 
-#[cfg(all(
-    target_arch = "wasm32",
-    any(feature = "resolve-http", feature = "resolve-file")
-))]
-compile_error!("Features 'resolve-http' and 'resolve-file' are not supported on WASM.");
-
 /// Validate `instance` against `schema` and get a `true` if the instance is valid and `false`
 /// otherwise. Draft is detected automatically.
 ///
+/// Validators for recently used schemas are cached, so calling this function in a loop with the
+/// same schema compiles it only once.
+///
 /// # Examples
 ///
 /// ```rust
@@ -512,12 +714,14 @@ compile_error!("Features 'resolve-http' and 'resolve-file' are not supported on
 #[must_use]
 #[inline]
 pub fn is_valid(schema: &Value, instance: &Value) -> bool {
-    validator_for(schema)
-        .expect("Invalid schema")
-        .is_valid(instance)
+    cache::get(schema, None).is_valid(instance)
 }
 
 /// Validate `instance` against `schema` and return the first error if any. Draft is detected automatically.
+/// The returned error does not borrow `instance`.
+///
+/// Validators for recently used schemas are cached, so calling this function in a loop with the
+/// same schema compiles it only once.
 ///
 /// # Examples
 ///
@@ -533,10 +737,10 @@ pub fn is_valid(schema: &Value, instance: &Value) -> bool {
 ///
 /// This function panics if an invalid schema is passed.
 #[inline]
-pub fn validate<'i>(schema: &Value, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-    validator_for(schema)
-        .expect("Invalid schema")
+pub fn validate(schema: &Value, instance: &Value) -> Result<(), ValidationError<'static>> {
+    cache::get(schema, None)
         .validate(instance)
+        .map_err(ValidationError::into_owned)
 }
 
 /// Create a validator for the input schema with automatic draft detection and default options.
@@ -662,7 +866,7 @@ pub mod draft4 {
     /// ```
     #[must_use]
     pub fn is_valid(schema: &Value, instance: &Value) -> bool {
-        new(schema).expect("Invalid schema").is_valid(instance)
+        crate::cache::get(schema, Some(Draft::Draft4)).is_valid(instance)
     }
     /// Validate an instance against a schema using Draft 4 specifications without creating a validator.
     ///
@@ -678,12 +882,16 @@ pub mod draft4 {
     /// assert!(jsonschema::draft4::validate(&schema, &valid).is_ok());
     /// assert!(jsonschema::draft4::validate(&schema, &invalid).is_err());
     /// ```
-    pub fn validate<'i>(schema: &Value, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-        new(schema).expect("Invalid schema").validate(instance)
+    pub fn validate(schema: &Value, instance: &Value) -> Result<(), ValidationError<'static>> {
+        crate::cache::get(schema, Some(Draft::Draft4))
+            .validate(instance)
+            .map_err(ValidationError::into_owned)
     }
     /// Creates a [`ValidationOptions`] builder pre-configured for JSON Schema Draft 4.
     ///
-    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft4)`.
+    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft4)`,
+    /// except that schemas whose `$schema` declares another draft are rejected unless
+    /// [`ValidationOptions::allow_mismatched_meta_schema`] is set.
     ///
     /// # Examples
     ///
@@ -706,9 +914,7 @@ pub mod draft4 {
     /// See [`ValidationOptions`] for all available configuration options.
     #[must_use]
     pub fn options() -> ValidationOptions {
-        let mut options = crate::options();
-        options.with_draft(Draft::Draft4);
-        options
+        ValidationOptions::for_draft(Draft::Draft4)
     }
 }
 
@@ -767,7 +973,7 @@ pub mod draft6 {
     /// ```
     #[must_use]
     pub fn is_valid(schema: &Value, instance: &Value) -> bool {
-        new(schema).expect("Invalid schema").is_valid(instance)
+        crate::cache::get(schema, Some(Draft::Draft6)).is_valid(instance)
     }
     /// Validate an instance against a schema using Draft 6 specifications without creating a validator.
     ///
@@ -783,12 +989,16 @@ pub mod draft6 {
     /// assert!(jsonschema::draft6::validate(&schema, &valid).is_ok());
     /// assert!(jsonschema::draft6::validate(&schema, &invalid).is_err());
     /// ```
-    pub fn validate<'i>(schema: &Value, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-        new(schema).expect("Invalid schema").validate(instance)
+    pub fn validate(schema: &Value, instance: &Value) -> Result<(), ValidationError<'static>> {
+        crate::cache::get(schema, Some(Draft::Draft6))
+            .validate(instance)
+            .map_err(ValidationError::into_owned)
     }
     /// Creates a [`ValidationOptions`] builder pre-configured for JSON Schema Draft 6.
     ///
-    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft6)`.
+    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft6)`,
+    /// except that schemas whose `$schema` declares another draft are rejected unless
+    /// [`ValidationOptions::allow_mismatched_meta_schema`] is set.
     ///
     /// # Examples
     ///
@@ -811,9 +1021,7 @@ pub mod draft6 {
     /// See [`ValidationOptions`] for all available configuration options.
     #[must_use]
     pub fn options() -> ValidationOptions {
-        let mut options = crate::options();
-        options.with_draft(Draft::Draft6);
-        options
+        ValidationOptions::for_draft(Draft::Draft6)
     }
 }
 
@@ -872,7 +1080,7 @@ pub mod draft7 {
     /// ```
     #[must_use]
     pub fn is_valid(schema: &Value, instance: &Value) -> bool {
-        new(schema).expect("Invalid schema").is_valid(instance)
+        crate::cache::get(schema, Some(Draft::Draft7)).is_valid(instance)
     }
     /// Validate an instance against a schema using Draft 7 specifications without creating a validator.
     ///
@@ -888,12 +1096,16 @@ pub mod draft7 {
     /// assert!(jsonschema::draft7::validate(&schema, &valid).is_ok());
     /// assert!(jsonschema::draft7::validate(&schema, &invalid).is_err());
     /// ```
-    pub fn validate<'i>(schema: &Value, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-        new(schema).expect("Invalid schema").validate(instance)
+    pub fn validate(schema: &Value, instance: &Value) -> Result<(), ValidationError<'static>> {
+        crate::cache::get(schema, Some(Draft::Draft7))
+            .validate(instance)
+            .map_err(ValidationError::into_owned)
     }
     /// Creates a [`ValidationOptions`] builder pre-configured for JSON Schema Draft 7.
     ///
-    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft7)`.
+    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft7)`,
+    /// except that schemas whose `$schema` declares another draft are rejected unless
+    /// [`ValidationOptions::allow_mismatched_meta_schema`] is set.
     ///
     /// # Examples
     ///
@@ -916,9 +1128,7 @@ pub mod draft7 {
     /// See [`ValidationOptions`] for all available configuration options.
     #[must_use]
     pub fn options() -> ValidationOptions {
-        let mut options = crate::options();
-        options.with_draft(Draft::Draft7);
-        options
+        ValidationOptions::for_draft(Draft::Draft7)
     }
 }
 
@@ -977,7 +1187,7 @@ pub mod draft201909 {
     /// ```
     #[must_use]
     pub fn is_valid(schema: &Value, instance: &Value) -> bool {
-        new(schema).expect("Invalid schema").is_valid(instance)
+        crate::cache::get(schema, Some(Draft::Draft201909)).is_valid(instance)
     }
     /// Validate an instance against a schema using Draft 2019-09 specifications without creating a validator.
     ///
@@ -993,12 +1203,16 @@ pub mod draft201909 {
     /// assert!(jsonschema::draft201909::validate(&schema, &valid).is_ok());
     /// assert!(jsonschema::draft201909::validate(&schema, &invalid).is_err());
     /// ```
-    pub fn validate<'i>(schema: &Value, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-        new(schema).expect("Invalid schema").validate(instance)
+    pub fn validate(schema: &Value, instance: &Value) -> Result<(), ValidationError<'static>> {
+        crate::cache::get(schema, Some(Draft::Draft201909))
+            .validate(instance)
+            .map_err(ValidationError::into_owned)
     }
     /// Creates a [`ValidationOptions`] builder pre-configured for JSON Schema Draft 2019-09.
     ///
-    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft201909)`.
+    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft201909)`,
+    /// except that schemas whose `$schema` declares another draft are rejected unless
+    /// [`ValidationOptions::allow_mismatched_meta_schema`] is set.
     ///
     /// # Examples
     ///
@@ -1021,9 +1235,7 @@ pub mod draft201909 {
     /// See [`ValidationOptions`] for all available configuration options.
     #[must_use]
     pub fn options() -> ValidationOptions {
-        let mut options = crate::options();
-        options.with_draft(Draft::Draft201909);
-        options
+        ValidationOptions::for_draft(Draft::Draft201909)
     }
 }
 
@@ -1085,7 +1297,7 @@ pub mod draft202012 {
     /// ```
     #[must_use]
     pub fn is_valid(schema: &Value, instance: &Value) -> bool {
-        new(schema).expect("Invalid schema").is_valid(instance)
+        crate::cache::get(schema, Some(Draft::Draft202012)).is_valid(instance)
     }
     /// Validate an instance against a schema using Draft 2020-12 specifications without creating a validator.
     ///
@@ -1101,12 +1313,16 @@ pub mod draft202012 {
     /// assert!(jsonschema::draft202012::validate(&schema, &valid).is_ok());
     /// assert!(jsonschema::draft202012::validate(&schema, &invalid).is_err());
     /// ```
-    pub fn validate<'i>(schema: &Value, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-        new(schema).expect("Invalid schema").validate(instance)
+    pub fn validate(schema: &Value, instance: &Value) -> Result<(), ValidationError<'static>> {
+        crate::cache::get(schema, Some(Draft::Draft202012))
+            .validate(instance)
+            .map_err(ValidationError::into_owned)
     }
     /// Creates a [`ValidationOptions`] builder pre-configured for JSON Schema Draft 2020-12.
     ///
-    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft202012)`.
+    /// This function provides a shorthand for `jsonschema::options().with_draft(Draft::Draft202012)`,
+    /// except that schemas whose `$schema` declares another draft are rejected unless
+    /// [`ValidationOptions::allow_mismatched_meta_schema`] is set.
     ///
     /// # Examples
     ///
@@ -1129,16 +1345,14 @@ pub mod draft202012 {
     /// See [`ValidationOptions`] for all available configuration options.
     #[must_use]
     pub fn options() -> ValidationOptions {
-        let mut options = crate::options();
-        options.with_draft(Draft::Draft202012);
-        options
+        ValidationOptions::for_draft(Draft::Draft202012)
     }
 }
 
 #[cfg(test)]
 pub(crate) mod tests_util {
     use super::Validator;
-    use crate::ValidationError;
+    use crate::{value::tests::Foreign, ValidationError};
     use serde_json::Value;
 
     #[track_caller]
@@ -1148,6 +1362,11 @@ pub(crate) mod tests_util {
             "{} should not be valid (via is_valid)",
             instance
         );
+        assert!(
+            !validator.is_valid(&Foreign::from(instance)),
+            "{} should not be valid (via is_valid on a foreign value)",
+            instance
+        );
         assert!(
             validator.validate(instance).is_err(),
             "{} should not be valid (via validate)",
@@ -1208,6 +1427,11 @@ pub(crate) mod tests_util {
             "{} should be valid (via is_valid)",
             instance
         );
+        assert!(
+            validator.is_valid(&Foreign::from(instance)),
+            "{} should be valid (via is_valid on a foreign value)",
+            instance
+        );
         assert!(
             validator.validate(instance).is_ok(),
             "{} should be valid (via is_valid)",
@@ -1337,17 +1561,22 @@ mod tests {
 
     #[test]
     fn test_invalid_schema_keyword() {
-        let schema = json!({
-            // Note `https`, not `http`
-            "$schema": "https://json-schema.org/draft-07/schema",
-        });
+        let schema = json!({"$schema": "urn:example:unknown-dialect"});
         let error = crate::validator_for(&schema).expect_err("Should fail");
         assert_eq!(
             error.to_string(),
-            "Unknown specification: https://json-schema.org/draft-07/schema"
+            "Unknown specification: urn:example:unknown-dialect"
         );
     }
 
+    #[test]
+    fn test_schema_keyword_https() {
+        // Note `https`, not `http`
+        let schema = json!({"$schema": "https://json-schema.org/draft-07/schema"});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.draft(), Draft::Draft7);
+    }
+
     #[test_case(Draft::Draft4)]
     #[test_case(Draft::Draft6)]
     #[test_case(Draft::Draft7)]
@@ -1365,6 +1594,16 @@ mod tests {
         assert!(crate::validator_for(&schema).is_err())
     }
 
+    #[test]
+    fn collect_references() {
+        let schema = json!({"properties": {"a": {"$ref": "https://example.com/a.json"}}});
+        let references =
+            crate::referencing::collect_references(&schema, Draft::Draft7).expect("Invalid schema");
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].location(), "/properties/a");
+        assert!(references[0].is_external());
+    }
+
     #[test]
     fn validation_error_propagation() {
         fn foo() -> Result<(), Box<dyn std::error::Error>> {
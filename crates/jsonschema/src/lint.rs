@@ -0,0 +1,640 @@
+//! Static analysis of schemas for mistakes that make keywords or whole subschemas useless.
+use std::{collections::BTreeMap, fmt};
+
+use serde_json::{Map, Value};
+
+use crate::{paths::Location, regex::Regex, Draft, ValidationError};
+
+/// A problem found in a schema by [`lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// What kind of problem this is.
+    pub code: LintCode,
+    /// Location of the offending keyword in the schema.
+    pub location: Location,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#"{}: {} at "{}""#,
+            self.code, self.message, self.location
+        )
+    }
+}
+
+/// Kinds of problems reported by [`lint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum LintCode {
+    /// A keyword applies only to types that `type` rules out, e.g. `minimum` with
+    /// `"type": "string"`.
+    IgnoredKeyword,
+    /// Lower and upper bounds leave no valid values, e.g. `minLength` above `maxLength` or
+    /// equal `exclusiveMinimum` and `exclusiveMaximum`.
+    EmptyRange,
+    /// A required property is not allowed by `properties` and `additionalProperties`.
+    UnsatisfiableRequired,
+    /// An `enum` or `const` value is invalid under the keywords next to it.
+    InvalidEnumValue,
+    /// `allOf` contains `false`, so the schema never validates.
+    FalseAllOf,
+    /// `anyOf` or `oneOf` contains `false`, which never matches.
+    UnreachableBranch,
+    /// A keyword never fails, e.g. `"minItems": 0` or `if` without `then` and `else`.
+    NoOpKeyword,
+    /// A keyword next to `$ref` in Draft 7 and earlier, where `$ref` overrides all other
+    /// keywords.
+    IgnoredRefSibling,
+    /// `contentMediaType` or `contentEncoding` names a media type or encoding that has no
+    /// registered check, so the content is not checked.
+    UncheckedContent,
+    /// A keyword that the draft no longer supports, like `additionalItems` in Draft 2020-12, so
+    /// it is ignored.
+    RemovedKeyword,
+}
+
+impl LintCode {
+    /// The code as used in messages, e.g. `empty-range`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            LintCode::IgnoredKeyword => "ignored-keyword",
+            LintCode::EmptyRange => "empty-range",
+            LintCode::UnsatisfiableRequired => "unsatisfiable-required",
+            LintCode::InvalidEnumValue => "invalid-enum-value",
+            LintCode::FalseAllOf => "false-all-of",
+            LintCode::UnreachableBranch => "unreachable-branch",
+            LintCode::NoOpKeyword => "no-op-keyword",
+            LintCode::IgnoredRefSibling => "ignored-ref-sibling",
+            LintCode::UncheckedContent => "unchecked-content",
+            LintCode::RemovedKeyword => "removed-keyword",
+        }
+    }
+}
+
+impl fmt::Display for LintCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Find keywords in `schema` that can never do what their author intended, like `minimum` next
+/// to `"type": "string"` or `minLength` above `maxLength`.
+///
+/// Only keywords that are compiled into a validator under `draft` are analyzed, so unknown
+/// keywords and keywords from other drafts are skipped. Subschemas behind local references are
+/// reported at the location they are written at. To reject schemas with certain lints when
+/// building a validator, use [`ValidationOptions::with_denied_lints`](crate::ValidationOptions::with_denied_lints).
+///
+/// # Errors
+///
+/// Returns an error if the schema is invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use jsonschema::{Draft, LintCode};
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "properties": {
+///         "age": {"type": "string", "minimum": 0},
+///         "name": {"minLength": 5, "maxLength": 3}
+///     }
+/// });
+/// let lints = jsonschema::lint(&schema, Draft::Draft202012)?;
+/// assert_eq!(lints[0].code, LintCode::IgnoredKeyword);
+/// assert_eq!(lints[0].location.as_str(), "/properties/age/minimum");
+/// assert_eq!(lints[1].code, LintCode::EmptyRange);
+/// assert_eq!(lints[1].location.as_str(), "/properties/name/minLength");
+/// # Ok(())
+/// # }
+/// ```
+pub fn lint(schema: &Value, draft: Draft) -> Result<Vec<Lint>, ValidationError<'static>> {
+    let validator = crate::options().with_draft(draft).build(schema)?;
+    let mut lints = check(schema, draft, &validator.keyword_locations);
+    lints.extend(validator.warnings);
+    Ok(lints)
+}
+
+/// A subschema with the keywords compiled from it.
+struct Subschema<'a> {
+    location: Location,
+    schema: &'a Map<String, Value>,
+    keywords: Vec<&'a str>,
+}
+
+/// Analyze subschemas of `schema` that contain the compiled `keywords`.
+pub(crate) fn check(schema: &Value, draft: Draft, keywords: &[(Location, String)]) -> Vec<Lint> {
+    // Keyed by the location subschemas are written at
+    let mut subschemas: BTreeMap<String, Subschema<'_>> = BTreeMap::new();
+    for (location, keyword) in keywords {
+        let parent = &location.as_str()[..location.as_str().len() - keyword.len() - 1];
+        if let Some((Value::Object(object), location)) = resolve(schema, parent) {
+            // The same subschema may be reachable through multiple references
+            let subschema = subschemas
+                .entry(location.as_str().to_string())
+                .or_insert_with(|| Subschema {
+                    location,
+                    schema: object,
+                    keywords: Vec::new(),
+                });
+            if !subschema.keywords.contains(&keyword.as_str()) {
+                subschema.keywords.push(keyword);
+            }
+        }
+    }
+    let mut lints = Vec::new();
+    for Subschema {
+        location,
+        schema,
+        keywords,
+    } in subschemas.into_values()
+    {
+        lints.extend(
+            keywords
+                .iter()
+                .filter_map(|keyword| ignored_keyword(&location, schema, keyword)),
+        );
+        lints.extend(
+            keywords
+                .iter()
+                .filter_map(|keyword| no_op_keyword(&location, schema, keyword)),
+        );
+        empty_ranges(&mut lints, &location, schema);
+        unsatisfiable_required(&mut lints, &location, schema);
+        invalid_enum_values(&mut lints, &location, schema, draft);
+        false_all_of(&mut lints, &location, schema);
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(value) = schema.get(keyword).filter(|_| keywords.contains(&keyword)) {
+                lints.extend(unreachable_branches(&location, keyword, value));
+            }
+        }
+    }
+    lints
+}
+
+/// Find the subschema at a keyword location, following local references to where the
+/// subschema is written.
+fn resolve<'a>(root: &'a Value, pointer: &str) -> Option<(&'a Value, Location)> {
+    let mut current = root;
+    let mut location = Location::new();
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        match current {
+            Value::Object(object) => {
+                // Fragments in embedded resources are relative to those resources
+                if !std::ptr::eq(current, root) && object.contains_key("$id") {
+                    return None;
+                }
+                let value = object.get(&segment)?;
+                if let ("$ref" | "$dynamicRef" | "$recursiveRef", Value::String(reference)) =
+                    (segment.as_str(), value)
+                {
+                    let fragment = reference.strip_prefix('#')?;
+                    let fragment = referencing::uri::decode_pointer(fragment).ok()?;
+                    current = root.pointer(&fragment)?;
+                    location = Location::new();
+                    for segment in fragment.split('/').skip(1) {
+                        location =
+                            location.join(segment.replace("~1", "/").replace("~0", "~").as_str());
+                    }
+                } else {
+                    current = value;
+                    location = location.join(segment.as_str());
+                }
+            }
+            Value::Array(items) => {
+                let idx: usize = segment.parse().ok()?;
+                current = items.get(idx)?;
+                location = location.join(idx);
+            }
+            _ => return None,
+        }
+    }
+    Some((current, location))
+}
+
+/// Report `keyword` next to `$ref` in drafts where `$ref` overrides its siblings.
+pub(crate) fn ignored_ref_sibling(location: &Location, keyword: &str) -> Lint {
+    Lint {
+        code: LintCode::IgnoredRefSibling,
+        location: location.join(keyword),
+        message: format!(r#""{keyword}" is ignored because it is next to "$ref""#),
+    }
+}
+
+/// Report a content keyword that is not checked because its value is not supported.
+pub(crate) fn unchecked_content(location: &Location, message: String) -> Lint {
+    Lint {
+        code: LintCode::UncheckedContent,
+        location: location.clone(),
+        message,
+    }
+}
+
+/// Report a keyword that is ignored because the draft removed it.
+pub(crate) fn removed_keyword(location: &Location, message: String) -> Lint {
+    Lint {
+        code: LintCode::RemovedKeyword,
+        location: location.clone(),
+        message,
+    }
+}
+
+/// Report a keyword that is not compiled because it never has an effect next to its siblings.
+pub(crate) fn no_op(location: &Location, message: String) -> Lint {
+    Lint {
+        code: LintCode::NoOpKeyword,
+        location: location.clone(),
+        message,
+    }
+}
+
+/// Keywords that only apply to instances of certain types.
+fn applies_to(keyword: &str) -> Option<&'static str> {
+    match keyword {
+        "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" | "multipleOf" => {
+            Some("number")
+        }
+        "minLength" | "maxLength" | "pattern" => Some("string"),
+        "items" | "prefixItems" | "additionalItems" | "minItems" | "maxItems" | "uniqueItems"
+        | "contains" | "minContains" | "maxContains" | "unevaluatedItems" => Some("array"),
+        "properties"
+        | "patternProperties"
+        | "additionalProperties"
+        | "required"
+        | "minProperties"
+        | "maxProperties"
+        | "propertyNames"
+        | "dependentRequired"
+        | "dependentSchemas"
+        | "dependencies"
+        | "unevaluatedProperties" => Some("object"),
+        _ => None,
+    }
+}
+
+/// Report `keyword` if `type` rules out all instances it applies to.
+pub(crate) fn ignored_keyword(
+    location: &Location,
+    schema: &Map<String, Value>,
+    keyword: &str,
+) -> Option<Lint> {
+    let applies_to = applies_to(keyword)?;
+    let is_allowed = |ty: &str| ty == applies_to || (applies_to == "number" && ty == "integer");
+    let allowed = match schema.get("type")? {
+        Value::String(ty) => is_allowed(ty),
+        Value::Array(types) => types.iter().filter_map(Value::as_str).any(is_allowed),
+        _ => return None,
+    };
+    if allowed {
+        None
+    } else {
+        Some(Lint {
+            code: LintCode::IgnoredKeyword,
+            location: location.join(keyword),
+            message: format!(
+                r#""{keyword}" only applies to {applies_to} instances, but "type" is {}"#,
+                schema["type"]
+            ),
+        })
+    }
+}
+
+/// Report `keyword` if it never fails.
+pub(crate) fn no_op_keyword(
+    location: &Location,
+    schema: &Map<String, Value>,
+    keyword: &str,
+) -> Option<Lint> {
+    let message = match (keyword, schema.get(keyword)?) {
+        ("minLength" | "minItems" | "minProperties", limit) if limit.as_f64() == Some(0.0) => {
+            format!(r#""{keyword}" of 0 is always satisfied"#)
+        }
+        ("required", Value::Array(required)) if required.is_empty() => {
+            r#""required" is empty"#.to_string()
+        }
+        ("if", _) if !schema.contains_key("then") && !schema.contains_key("else") => {
+            r#""if" has no effect without "then" or "else""#.to_string()
+        }
+        _ => return None,
+    };
+    Some(Lint {
+        code: LintCode::NoOpKeyword,
+        location: location.join(keyword),
+        message,
+    })
+}
+
+fn empty_ranges(lints: &mut Vec<Lint>, location: &Location, schema: &Map<String, Value>) {
+    for (min, max) in [
+        ("minLength", "maxLength"),
+        ("minItems", "maxItems"),
+        ("minProperties", "maxProperties"),
+        ("minContains", "maxContains"),
+    ] {
+        if let (Some(low), Some(high)) = (
+            schema.get(min).and_then(Value::as_f64),
+            schema.get(max).and_then(Value::as_f64),
+        ) {
+            if low > high {
+                lints.push(Lint {
+                    code: LintCode::EmptyRange,
+                    location: location.join(min),
+                    message: format!(
+                        r#""{min}" of {} is greater than "{max}" of {}"#,
+                        schema[min], schema[max]
+                    ),
+                });
+            }
+        }
+    }
+    // The tightest lower and upper bounds, with whether they are exclusive
+    let bound = |inclusive: &'static str, exclusive: &'static str, lower: bool| {
+        let mut bound = schema
+            .get(inclusive)
+            .filter(|value| value.is_number())
+            // Boolean `exclusiveMinimum` / `exclusiveMaximum` from Draft 4
+            .map(|value| {
+                (
+                    inclusive,
+                    value,
+                    schema.get(exclusive) == Some(&Value::Bool(true)),
+                )
+            });
+        if let Some(value) = schema.get(exclusive).filter(|value| value.is_number()) {
+            let tighter = bound.map_or(true, |(_, current, _)| {
+                let (value, current) = (value.as_f64(), current.as_f64());
+                if lower {
+                    value >= current
+                } else {
+                    value <= current
+                }
+            });
+            if tighter {
+                bound = Some((exclusive, value, true));
+            }
+        }
+        bound
+    };
+    if let (Some((min, low, low_exclusive)), Some((max, high, high_exclusive))) = (
+        bound("minimum", "exclusiveMinimum", true),
+        bound("maximum", "exclusiveMaximum", false),
+    ) {
+        let (low_value, high_value) = (low.as_f64(), high.as_f64());
+        if low_value > high_value || (low_value == high_value && (low_exclusive || high_exclusive))
+        {
+            lints.push(Lint {
+                code: LintCode::EmptyRange,
+                location: location.join(min),
+                message: format!(r#"No number is within "{min}" of {low} and "{max}" of {high}"#),
+            });
+        }
+    }
+}
+
+fn unsatisfiable_required(lints: &mut Vec<Lint>, location: &Location, schema: &Map<String, Value>) {
+    let Some(Value::Array(required)) = schema.get("required") else {
+        return;
+    };
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+    let patterns: Vec<Option<Regex>> = match schema.get("patternProperties") {
+        Some(Value::Object(patterns)) => patterns
+            .keys()
+            .map(|pattern| {
+                crate::ecma::to_rust_regex(pattern)
+                    .ok()
+                    .and_then(|pattern| Regex::new(&pattern).ok())
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    for (idx, name) in required.iter().enumerate() {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        let message = match properties.and_then(|properties| properties.get(name)) {
+            Some(Value::Bool(false)) => format!(r#""{name}" is required, but its schema is false"#),
+            Some(_) => continue,
+            None if closed => {
+                // Patterns that fail to compile or match are given the benefit of the doubt
+                let matches_pattern = patterns.iter().any(|regex| {
+                    regex
+                        .as_ref()
+                        .map_or(true, |regex| regex.is_match(name).unwrap_or(true))
+                });
+                if matches_pattern {
+                    continue;
+                }
+                format!(
+                    r#""{name}" is required, but not allowed by "properties" and "additionalProperties""#
+                )
+            }
+            None => continue,
+        };
+        lints.push(Lint {
+            code: LintCode::UnsatisfiableRequired,
+            location: location.join("required").join(idx),
+            message,
+        });
+    }
+}
+
+fn has_references(value: &Value) -> bool {
+    match value {
+        Value::Object(object) => object.iter().any(|(key, value)| {
+            matches!(key.as_str(), "$ref" | "$dynamicRef" | "$recursiveRef")
+                || has_references(value)
+        }),
+        Value::Array(items) => items.iter().any(has_references),
+        _ => false,
+    }
+}
+
+fn invalid_enum_values(
+    lints: &mut Vec<Lint>,
+    location: &Location,
+    schema: &Map<String, Value>,
+    draft: Draft,
+) {
+    if !schema.contains_key("enum") && !schema.contains_key("const") {
+        return;
+    }
+    let mut siblings = schema.clone();
+    siblings.remove("enum");
+    siblings.remove("const");
+    // References would be resolved against the wrong document
+    let siblings = Value::Object(siblings);
+    if has_references(&siblings) {
+        return;
+    }
+    let Ok(validator) = crate::options().with_draft(draft).build(&siblings) else {
+        return;
+    };
+    let mut check = |value: &Value, location: Location| {
+        if let Some(error) = validator.iter_errors(value).next() {
+            lints.push(Lint {
+                code: LintCode::InvalidEnumValue,
+                location,
+                message: format!("Value is never valid: {error}"),
+            });
+        }
+    };
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        for (idx, value) in values.iter().enumerate() {
+            check(value, location.join("enum").join(idx));
+        }
+    }
+    if let Some(value) = schema.get("const") {
+        check(value, location.join("const"));
+    }
+}
+
+/// Report `false` branches of `anyOf` or `oneOf`.
+pub(crate) fn unreachable_branches(location: &Location, keyword: &str, value: &Value) -> Vec<Lint> {
+    match (keyword, value) {
+        ("anyOf" | "oneOf", Value::Array(subschemas)) => subschemas
+            .iter()
+            .enumerate()
+            .filter(|(_, subschema)| **subschema == Value::Bool(false))
+            .map(|(idx, _)| Lint {
+                code: LintCode::UnreachableBranch,
+                location: location.join(keyword).join(idx),
+                message: "The subschema never validates".to_string(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn false_all_of(lints: &mut Vec<Lint>, location: &Location, schema: &Map<String, Value>) {
+    if let Some(Value::Array(subschemas)) = schema.get("allOf") {
+        for (idx, subschema) in subschemas.iter().enumerate() {
+            if subschema == &Value::Bool(false) {
+                lints.push(Lint {
+                    code: LintCode::FalseAllOf,
+                    location: location.join("allOf").join(idx),
+                    message: "The schema never validates".to_string(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{lint, LintCode};
+    use crate::{error::ValidationErrorKind, Draft};
+
+    fn lints(schema: &Value, draft: Draft) -> Vec<(LintCode, String)> {
+        lint(schema, draft)
+            .expect("Valid schema")
+            .into_iter()
+            .map(|lint| (lint.code, lint.location.as_str().to_string()))
+            .collect()
+    }
+
+    #[test_case(&json!({"type": "string", "minimum": 3}), &[(LintCode::IgnoredKeyword, "/minimum")]; "ignored keyword")]
+    #[test_case(&json!({"type": ["integer", "null"], "minimum": 3, "minLength": 1}), &[(LintCode::IgnoredKeyword, "/minLength")]; "ignored keyword with type list")]
+    #[test_case(&json!({"type": "object", "items": {"type": "string"}}), &[(LintCode::IgnoredKeyword, "/items")]; "ignored applicator")]
+    #[test_case(&json!({"minLength": 5, "maxLength": 3}), &[(LintCode::EmptyRange, "/minLength")]; "string length")]
+    #[test_case(&json!({"minItems": 2, "maxItems": 1, "minProperties": 1, "maxProperties": 1}), &[(LintCode::EmptyRange, "/minItems")]; "items")]
+    #[test_case(&json!({"exclusiveMinimum": 3, "exclusiveMaximum": 3}), &[(LintCode::EmptyRange, "/exclusiveMinimum")]; "equal exclusive bounds")]
+    #[test_case(&json!({"minimum": 3, "exclusiveMaximum": 3}), &[(LintCode::EmptyRange, "/minimum")]; "inclusive and exclusive bounds")]
+    #[test_case(&json!({"minimum": 3, "maximum": 3}), &[]; "single number")]
+    #[test_case(&json!({"minimum": 1, "exclusiveMinimum": 5, "maximum": 4}), &[(LintCode::EmptyRange, "/exclusiveMinimum")]; "tightest bound")]
+    #[test_case(&json!({"properties": {"a": {}}, "required": ["a", "b"], "additionalProperties": false}), &[(LintCode::UnsatisfiableRequired, "/required/1")]; "closed object")]
+    #[test_case(&json!({"patternProperties": {"^b": {}}, "required": ["b", "c"], "additionalProperties": false}), &[(LintCode::UnsatisfiableRequired, "/required/1")]; "pattern properties")]
+    #[test_case(&json!({"properties": {"a": false}, "required": ["a"]}), &[(LintCode::UnsatisfiableRequired, "/required/0")]; "false property")]
+    #[test_case(&json!({"type": "string", "maxLength": 2, "enum": ["a", "abc", 1]}), &[(LintCode::InvalidEnumValue, "/enum/1"), (LintCode::InvalidEnumValue, "/enum/2")]; "enum")]
+    #[test_case(&json!({"minimum": 5, "const": 3}), &[(LintCode::InvalidEnumValue, "/const")]; "const")]
+    #[test_case(&json!({"allOf": [{"type": "string"}, false]}), &[(LintCode::FalseAllOf, "/allOf/1")]; "false all of")]
+    #[test_case(&json!({"anyOf": [{"type": "string"}, false], "oneOf": [false, {}]}), &[(LintCode::UnreachableBranch, "/anyOf/1"), (LintCode::UnreachableBranch, "/oneOf/0")]; "unreachable branches")]
+    #[test_case(&json!({"minItems": 0, "minLength": 1, "required": []}), &[(LintCode::NoOpKeyword, "/minItems"), (LintCode::NoOpKeyword, "/required")]; "no-op keywords")]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/$defs/positive"}, "b": {"$ref": "#/$defs/positive"}}, "$defs": {"positive": {"type": "string", "exclusiveMinimum": 0}}}), &[(LintCode::IgnoredKeyword, "/$defs/positive/exclusiveMinimum")]; "references")]
+    #[test_case(&json!({"type": "string", "maximum": 3, "properties": {"x": {"type": "integer"}}, "maxContains": 1, "prefixItems": [{}]}), &[(LintCode::IgnoredKeyword, "/maximum"), (LintCode::IgnoredKeyword, "/prefixItems"), (LintCode::IgnoredKeyword, "/properties")]; "multiple")]
+    fn lints_2020_12(schema: &Value, expected: &[(LintCode, &str)]) {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(code, location)| (*code, (*location).to_string()))
+            .collect();
+        assert_eq!(lints(schema, Draft::Draft202012), expected);
+    }
+
+    #[test]
+    fn draft_specific() {
+        // Boolean exclusive bounds
+        let schema = json!({"minimum": 3, "maximum": 3, "exclusiveMaximum": true});
+        assert_eq!(
+            lints(&schema, Draft::Draft4),
+            vec![(LintCode::EmptyRange, "/minimum".to_string())]
+        );
+        // `prefixItems` is not a keyword in Draft 7
+        let schema = json!({"type": "object", "prefixItems": [{}], "minContains": 1});
+        assert_eq!(lints(&schema, Draft::Draft7), vec![]);
+        // `$ref` overrides its siblings before Draft 2019-09
+        let schema = json!({"$ref": "#/definitions/a", "definitions": {"a": {}}, "minimum": 1});
+        assert_eq!(
+            lints(&schema, Draft::Draft7),
+            vec![(LintCode::IgnoredRefSibling, "/minimum".to_string())]
+        );
+        assert_eq!(lints(&schema, Draft::Draft201909), vec![]);
+    }
+
+    #[test]
+    fn messages() {
+        let schema = json!({
+            "type": "string",
+            "minimum": 3,
+            "properties": {"a": {"exclusiveMinimum": 3, "exclusiveMaximum": 3}}
+        });
+        let messages: Vec<_> = lint(&schema, Draft::Draft202012)
+            .expect("Valid schema")
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                r#"ignored-keyword: "minimum" only applies to number instances, but "type" is "string" at "/minimum""#,
+                r#"ignored-keyword: "properties" only applies to object instances, but "type" is "string" at "/properties""#,
+                r#"empty-range: No number is within "exclusiveMinimum" of 3 and "exclusiveMaximum" of 3 at "/properties/a/exclusiveMinimum""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn denied_lints() {
+        let schema = json!({"properties": {"a": {"minLength": 5, "maxLength": 3}}});
+        assert!(crate::options()
+            .with_denied_lints([LintCode::IgnoredKeyword])
+            .build(&schema)
+            .is_ok());
+        let error = crate::options()
+            .with_denied_lints([LintCode::EmptyRange])
+            .build(&schema)
+            .expect_err("Should fail");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::Lint {
+                code: LintCode::EmptyRange,
+                ..
+            }
+        ));
+        assert_eq!(error.instance_path.as_str(), "/properties/a/minLength");
+        assert_eq!(
+            error.to_string(),
+            r#""minLength" of 5 is greater than "maxLength" of 3 (empty-range)"#
+        );
+    }
+}
@@ -1,23 +1,150 @@
 use crate::{
     compiler::Context,
-    error::ErrorIterator,
+    error::{error, ErrorIterator},
+    incremental::Enter,
     keywords::{BoxedValidator, Keyword},
-    output::{Annotations, BasicOutput, ErrorDescription, OutputUnit},
-    paths::{LazyLocation, Location, LocationSegment},
-    validator::{PartialApplication, Validate},
-    ValidationError,
+    options::KeywordOrder,
+    output::{AbsoluteLocation, Annotations, BasicOutput, ErrorDescription, OutputUnit},
+    paths::{LazyLocation, Location},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value, ValidationError,
 };
 use ahash::AHashMap;
-use referencing::{uri, Uri};
+
+#[cfg(feature = "instrumentation")]
+use crate::instrumentation::Instrumented;
+
 use serde_json::Value;
-use std::{cell::OnceCell, collections::VecDeque, fmt};
+use std::{
+    cell::{Cell, OnceCell},
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, OnceLock, Weak},
+};
 
 /// A node in the schema tree, returned by [`compiler::compile`]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct SchemaNode {
-    validators: NodeValidators,
+    validators: Arc<NodeValidators>,
     location: Location,
-    absolute_path: Option<Uri<String>>,
+    absolute: Option<AbsoluteLocation>,
+    /// Where shared validators were originally compiled. Locations reported by them are moved
+    /// from there to `location`.
+    origin: Option<Arc<Origin>>,
+    /// Instances nested deeper than this fail instead of being validated.
+    max_instance_depth: usize,
+}
+
+/// The node whose validators a [`SchemaNode`] shares.
+#[derive(Debug)]
+struct Origin {
+    location: Location,
+    absolute: Option<AbsoluteLocation>,
+}
+
+/// A [`SchemaNode`] that does not own its validators.
+///
+/// Used by recursive references pointing to a node that contains them, which would otherwise
+/// form a reference cycle.
+#[derive(Debug, Clone)]
+pub(crate) struct WeakSchemaNode {
+    validators: Weak<NodeValidators>,
+    origin: Arc<Origin>,
+    max_instance_depth: usize,
+}
+
+impl WeakSchemaNode {
+    /// Reuse the validators of the original node at `location`.
+    ///
+    /// Returns `None` if the original node was dropped.
+    pub(crate) fn upgrade_at(&self, location: &Location) -> Option<SchemaNode> {
+        Some(SchemaNode {
+            validators: self.validators.upgrade()?,
+            location: location.clone(),
+            absolute: self
+                .origin
+                .absolute
+                .as_ref()
+                .map(|absolute| absolute.at(location)),
+            origin: (&self.origin.location != location).then(|| Arc::clone(&self.origin)),
+            max_instance_depth: self.max_instance_depth,
+        })
+    }
+    /// Whether this is a reference to the validators of `node`.
+    pub(crate) fn points_to(&self, node: &SchemaNode) -> bool {
+        std::ptr::eq(self.validators.as_ptr(), Arc::as_ptr(&node.validators))
+    }
+}
+
+/// How deep `is_valid` may recurse before checks are deferred to [`DeferredChecks`].
+const MAX_RECURSION_DEPTH: usize = 64;
+
+thread_local! {
+    /// How deeply `is_valid` checks are nested on this thread.
+    static NESTING: Cell<usize> = const { Cell::new(0) };
+    /// The instance of the innermost check of applicators like `anyOf` on this thread, and how
+    /// many distinct instances such checks are nested through.
+    static IN_PLACE: Cell<(*const Value, usize)> = const { Cell::new((std::ptr::null(), 0)) };
+    /// How many checks on this thread were given up because they were nested too deeply.
+    static TRUNCATED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Run `check` one level deeper in the nesting of `is_valid` checks.
+fn nested<T>(check: impl FnOnce() -> T) -> T {
+    struct Leave;
+    impl Drop for Leave {
+        fn drop(&mut self) {
+            NESTING.with(|nesting| nesting.set(nesting.get() - 1));
+        }
+    }
+    NESTING.with(|nesting| nesting.set(nesting.get() + 1));
+    let _leave = Leave;
+    check()
+}
+
+fn truncated() -> u64 {
+    TRUNCATED.with(Cell::get)
+}
+
+/// Subschema checks that must all pass for `is_valid` to succeed.
+///
+/// Checks run recursively until a fixed depth is reached. Deeper checks are queued and run
+/// once the stack unwinds, so deeply nested instances do not overflow the stack.
+pub(crate) struct DeferredChecks<'i> {
+    pending: Vec<(SchemaNode, &'i Value)>,
+}
+
+impl<'i> DeferredChecks<'i> {
+    const fn new() -> Self {
+        DeferredChecks {
+            pending: Vec::new(),
+        }
+    }
+
+    /// Check `instance` against `node` now, or defer it if the recursion is already deep.
+    ///
+    /// Deferred checks report `true` here, their actual result is taken into account by `run`.
+    /// Trivially true nodes are not checked at all.
+    #[inline]
+    pub(crate) fn check(&mut self, node: &SchemaNode, instance: &'i Value) -> bool {
+        if node.is_trivially_true() {
+            true
+        } else if NESTING.with(Cell::get) < MAX_RECURSION_DEPTH {
+            nested(|| node.is_valid_deferred(instance, self))
+        } else {
+            self.pending.push((node.clone(), instance));
+            true
+        }
+    }
+
+    fn run(&mut self) -> bool {
+        while let Some((node, instance)) = self.pending.pop() {
+            if !node.is_valid_deferred(instance, self) {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 enum NodeValidators {
@@ -56,14 +183,87 @@ struct KeywordValidators {
     // We should probably use AHashMap here but it breaks a bunch of test which assume
     // validators are in a particular order
     validators: Vec<(Keyword, BoxedValidator)>,
+    /// Indices into `validators`, cheapest keyword first, in which `is_valid` checks them.
+    /// `None` if that is the order of `validators` already.
+    by_cost: Option<Box<[usize]>>,
 }
 
+impl KeywordValidators {
+    fn new(
+        validators: Vec<(Keyword, BoxedValidator)>,
+        unmatched_keywords: Option<AHashMap<String, Value>>,
+        order: KeywordOrder,
+    ) -> KeywordValidators {
+        let by_cost = if order == KeywordOrder::Optimized && validators.len() > 1 {
+            let mut indices: Vec<_> = (0..validators.len()).collect();
+            // Stable, so keywords of equal cost keep their order
+            indices.sort_by_key(|&idx| validators[idx].0.cost());
+            (!indices
+                .iter()
+                .enumerate()
+                .all(|(position, &idx)| position == idx))
+            .then(|| indices.into_boxed_slice())
+        } else {
+            None
+        };
+        KeywordValidators {
+            unmatched_keywords,
+            validators,
+            by_cost,
+        }
+    }
+
+    /// Validators in the order `is_valid` checks them.
+    #[inline]
+    fn by_cost(&self) -> impl Iterator<Item = &BoxedValidator> {
+        self.keywords_by_cost().map(|(_, validator)| validator)
+    }
+
+    /// Keywords and their validators in the order `is_valid` checks them.
+    #[inline]
+    fn keywords_by_cost(&self) -> impl Iterator<Item = &(Keyword, BoxedValidator)> {
+        let order = self.by_cost.as_deref();
+        (0..self.validators.len())
+            .map(move |position| &self.validators[order.map_or(position, |o| o[position])])
+    }
+}
+
+/// Validators of all trivially true subschemas, see [`SchemaNode::trivially_true`].
+static TRIVIALLY_TRUE: OnceLock<Arc<NodeValidators>> = OnceLock::new();
+
 impl SchemaNode {
+    /// A node for a subschema that accepts every instance without producing annotations, like
+    /// `true` or `{}`.
+    ///
+    /// All such nodes share the same validators, so they cost no allocation and applicators
+    /// skip them without checking anything.
+    pub(crate) fn trivially_true(ctx: &Context<'_>) -> SchemaNode {
+        let validators =
+            TRIVIALLY_TRUE.get_or_init(|| Arc::new(NodeValidators::Boolean { validator: None }));
+        SchemaNode {
+            location: ctx.location().clone(),
+            absolute: ctx.absolute_location(),
+            validators: Arc::clone(validators),
+            origin: None,
+            max_instance_depth: ctx.config().max_instance_depth(),
+        }
+    }
+
+    /// Whether this node was created by [`SchemaNode::trivially_true`].
+    #[inline]
+    pub(crate) fn is_trivially_true(&self) -> bool {
+        TRIVIALLY_TRUE
+            .get()
+            .is_some_and(|validators| Arc::ptr_eq(validators, &self.validators))
+    }
+
     pub(crate) fn from_boolean(ctx: &Context<'_>, validator: Option<BoxedValidator>) -> SchemaNode {
         SchemaNode {
             location: ctx.location().clone(),
-            absolute_path: ctx.base_uri(),
-            validators: NodeValidators::Boolean { validator },
+            absolute: ctx.absolute_location(),
+            validators: Arc::new(NodeValidators::Boolean { validator }),
+            origin: None,
+            max_instance_depth: ctx.config().max_instance_depth(),
         }
     }
 
@@ -72,26 +272,174 @@ impl SchemaNode {
         validators: Vec<(Keyword, BoxedValidator)>,
         unmatched_keywords: Option<AHashMap<String, Value>>,
     ) -> SchemaNode {
+        #[cfg(feature = "instrumentation")]
+        let validators = instrument(ctx, validators);
         SchemaNode {
             location: ctx.location().clone(),
-            absolute_path: ctx.base_uri(),
-            validators: NodeValidators::Keyword(KeywordValidators {
-                unmatched_keywords,
+            absolute: ctx.absolute_location(),
+            validators: Arc::new(NodeValidators::Keyword(KeywordValidators::new(
                 validators,
-            }),
+                unmatched_keywords,
+                ctx.config().keyword_order(),
+            ))),
+            origin: None,
+            max_instance_depth: ctx.config().max_instance_depth(),
         }
     }
 
     pub(crate) fn from_array(ctx: &Context<'_>, validators: Vec<BoxedValidator>) -> SchemaNode {
         SchemaNode {
             location: ctx.location().clone(),
-            absolute_path: ctx.base_uri(),
-            validators: NodeValidators::Array { validators },
+            absolute: ctx.absolute_location(),
+            validators: Arc::new(NodeValidators::Array { validators }),
+            origin: None,
+            max_instance_depth: ctx.config().max_instance_depth(),
+        }
+    }
+
+    /// Reuse the validators of this node at the location of `ctx`.
+    pub(crate) fn share(&self, ctx: &Context<'_>) -> SchemaNode {
+        self.shared(ctx.location(), ctx.absolute_location())
+    }
+
+    /// Reuse the validators of this node at `location`, as the target of another reference.
+    pub(crate) fn share_at(&self, location: &Location) -> SchemaNode {
+        let absolute = self.absolute.as_ref().map(|absolute| absolute.at(location));
+        self.shared(location, absolute)
+    }
+
+    fn shared(&self, location: &Location, absolute: Option<AbsoluteLocation>) -> SchemaNode {
+        let origin = self.origin();
+        SchemaNode {
+            validators: Arc::clone(&self.validators),
+            location: location.clone(),
+            absolute,
+            origin: (&origin.location != location).then_some(origin),
+            max_instance_depth: self.max_instance_depth,
         }
     }
 
+    fn origin(&self) -> Arc<Origin> {
+        self.origin.clone().unwrap_or_else(|| {
+            Arc::new(Origin {
+                location: self.location.clone(),
+                absolute: self.absolute.clone(),
+            })
+        })
+    }
+
+    /// A reference to this node that does not keep its validators alive.
+    pub(crate) fn downgrade(&self) -> WeakSchemaNode {
+        WeakSchemaNode {
+            validators: Arc::downgrade(&self.validators),
+            origin: self.origin(),
+            max_instance_depth: self.max_instance_depth,
+        }
+    }
+
+    /// Identifies the validators of this node, which are shared by all nodes of the same
+    /// subschema.
+    pub(crate) fn id(&self) -> usize {
+        Arc::as_ptr(&self.validators) as usize
+    }
+
+    /// Fail if `location` points deeper into the instance than allowed.
+    fn check_depth<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if location.depth() > self.max_instance_depth {
+            Err(self.too_deep(instance, location))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn too_deep<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ValidationError<'i> {
+        ValidationError::max_instance_depth(
+            self.location.clone(),
+            location.into(),
+            instance,
+            self.max_instance_depth,
+        )
+    }
+
+    /// Like `is_valid`, or `None` if applicators that can not defer their checks, like `anyOf`,
+    /// are nested through more levels of the instance than the instance depth limit.
+    pub(crate) fn try_is_valid(&self, instance: &Value) -> Option<bool> {
+        struct Restore((*const Value, usize));
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                IN_PLACE.with(|in_place| in_place.set(self.0));
+            }
+        }
+        if self.is_trivially_true() {
+            return Some(true);
+        }
+        let (outer, mut depth) = IN_PLACE.with(Cell::get);
+        if !std::ptr::eq(outer, instance) {
+            depth += 1;
+        }
+        if depth > self.max_instance_depth {
+            TRUNCATED.with(|truncated| truncated.set(truncated.get().wrapping_add(1)));
+            return None;
+        }
+        let _restore = Restore(IN_PLACE.with(|in_place| in_place.replace((instance, depth))));
+        let before = truncated();
+        let valid = nested(|| {
+            let mut checks = DeferredChecks::new();
+            self.is_valid_deferred(instance, &mut checks) && checks.run()
+        });
+        // Results of nested checks that were given up are not reliable, e.g. under `not`
+        (truncated() == before).then_some(valid)
+    }
+
+    /// Whether `instance` is valid, for applicators like `anyOf` that validate subschemas in
+    /// place.
+    ///
+    /// Fails with `MaxInstanceDepth` if such applicators are nested too deeply to check it.
+    pub(crate) fn is_valid_at<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<bool, ValidationError<'i>> {
+        self.try_is_valid(instance)
+            .ok_or_else(|| self.too_deep(instance, location))
+    }
+
+    /// The error of the first keyword that `is_valid` finds to fail, if any.
+    ///
+    /// Keywords are checked without tracking locations, and only the failing one is validated
+    /// again to build its error.
+    pub(crate) fn validate_first<'i>(&self, instance: &'i Value) -> Option<ValidationError<'i>> {
+        let location = LazyLocation::new();
+        match &*self.validators {
+            NodeValidators::Keyword(kvs) if self.origin.is_none() => {
+                let (keyword, validator) = kvs
+                    .keywords_by_cost()
+                    .find(|(_, validator)| !validator.is_valid(instance))?;
+                if let Err(mut error) = validator.validate(instance, &location) {
+                    self.attribute(&mut error, Some(keyword));
+                    return Some(error);
+                }
+            }
+            _ if self.is_valid(instance) => return None,
+            _ => {}
+        }
+        self.validate(instance, &location).err()
+    }
+
+    /// Compiled subschemas of this node's `anyOf` keyword, if it has one.
+    pub(crate) fn any_of_branches(&self) -> &[SchemaNode] {
+        self.validators()
+            .map(|validator| validator.any_of_branches())
+            .find(|branches| !branches.is_empty())
+            .unwrap_or_default()
+    }
+
     pub(crate) fn validators(&self) -> impl ExactSizeIterator<Item = &BoxedValidator> {
-        match &self.validators {
+        match &*self.validators {
             NodeValidators::Boolean { validator } => {
                 if let Some(v) = validator {
                     NodeValidatorsIter::BooleanValidators(std::iter::once(v))
@@ -144,7 +492,9 @@ impl SchemaNode {
         OutputUnit::<ErrorDescription>::error(
             self.location.clone(),
             location.into(),
-            self.absolute_path.clone(),
+            self.absolute
+                .as_ref()
+                .map(|absolute| absolute.resolve(&self.location)),
             error,
         )
     }
@@ -158,15 +508,17 @@ impl SchemaNode {
         OutputUnit::<Annotations<'_>>::annotations(
             self.location.clone(),
             location.into(),
-            self.absolute_path.clone(),
+            self.absolute
+                .as_ref()
+                .map(|absolute| absolute.resolve(&self.location)),
             annotations,
         )
     }
 
-    /// Helper function to apply an iterator of `(Into<PathChunk>, Validate)` to a value. This is
+    /// Helper function to apply an iterator of `(ValidatorPath, Validate)` to a value. This is
     /// useful as a keyword schemanode has a set of validators keyed by their keywords, so the
-    /// `Into<Pathchunk>` is a `String` whereas an array schemanode has an array of validators so
-    /// the `Into<PathChunk>` is a `usize`
+    /// `ValidatorPath` is a `Keyword` whereas an array schemanode has an array of validators so
+    /// the `ValidatorPath` is a `usize`
     fn apply_subschemas<'a, I, P>(
         &self,
         instance: &Value,
@@ -176,11 +528,10 @@ impl SchemaNode {
     ) -> PartialApplication<'a>
     where
         I: Iterator<Item = (P, &'a Box<dyn Validate + Send + Sync + 'a>)> + 'a,
-        P: Into<LocationSegment<'a>> + fmt::Display,
+        P: ValidatorPath,
     {
         let mut success_results: VecDeque<OutputUnit<Annotations>> = VecDeque::new();
         let mut error_results = VecDeque::new();
-        let mut buffer = String::new();
         let instance_location: OnceCell<Location> = OnceCell::new();
 
         macro_rules! instance_location {
@@ -192,13 +543,9 @@ impl SchemaNode {
         for (path, validator) in path_and_validators {
             macro_rules! make_absolute_location {
                 ($location:expr) => {
-                    self.absolute_path.as_ref().map(|absolute_path| {
-                        uri::encode_to($location.as_str(), &mut buffer);
-                        let resolved = absolute_path
-                            .with_fragment(Some(uri::EncodedString::new_or_panic(&buffer)));
-                        buffer.clear();
-                        resolved
-                    })
+                    self.absolute
+                        .as_ref()
+                        .map(|absolute| absolute.resolve(&$location))
                 };
             }
             match validator.apply(instance, location) {
@@ -207,7 +554,7 @@ impl SchemaNode {
                     child_results,
                 } => {
                     if let Some(annotations) = annotations {
-                        let location = self.location.join(path);
+                        let location = path.join_to(&self.location);
                         let absolute_location = make_absolute_location!(location);
                         success_results.push_front(OutputUnit::<Annotations<'a>>::annotations(
                             location,
@@ -222,7 +569,7 @@ impl SchemaNode {
                     errors: these_errors,
                     child_results,
                 } => {
-                    let location = self.location.join(path);
+                    let location = path.join_to(&self.location);
                     error_results.reserve(child_results.len() + these_errors.len());
                     error_results.extend(child_results);
                     error_results.extend(these_errors.into_iter().map(|error| {
@@ -256,22 +603,86 @@ impl SchemaNode {
     }
 }
 
-impl Validate for SchemaNode {
-    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        match &self.validators {
+impl SchemaNode {
+    /// Move errors reported by shared validators to this node's location.
+    fn rebase_errors<'i>(&self, errors: ErrorIterator<'i>) -> ErrorIterator<'i> {
+        if let Some(origin) = &self.origin {
+            let origin = Arc::clone(origin);
+            let target = self.location.clone();
+            let absolute = self.absolute.clone();
+            Box::new(
+                errors.map(move |error| rebase_error(error, &origin, &target, absolute.as_ref())),
+            )
+        } else {
+            errors
+        }
+    }
+
+    /// Where the validators of this node were compiled, which their errors point to.
+    fn compiled_at(&self) -> (&Location, Option<&AbsoluteLocation>) {
+        match &self.origin {
+            Some(origin) => (&origin.location, origin.absolute.as_ref()),
+            None => (&self.location, self.absolute.as_ref()),
+        }
+    }
+
+    /// Length of the location of the schema holding `keyword` in this node.
+    fn keyword_parent(&self, keyword: Option<&Keyword>) -> usize {
+        let (location, _) = self.compiled_at();
+        match keyword {
+            Some(Keyword::Hoisted {
+                location: relative, ..
+            }) => location.as_str().len() + relative.as_str().rfind('/').unwrap_or_default(),
+            _ => location.as_str().len(),
+        }
+    }
+
+    /// Record that `keyword` of this node produced `error`.
+    fn attribute(&self, error: &mut ValidationError<'_>, keyword: Option<&Keyword>) {
+        let (location, absolute) = self.compiled_at();
+        error.attribute(absolute, location, self.keyword_parent(keyword));
+    }
+
+    /// Record that `keyword` of this node produced `errors`.
+    fn attribute_all<'i>(
+        &self,
+        errors: ErrorIterator<'i>,
+        keyword: Option<&Keyword>,
+    ) -> ErrorIterator<'i> {
+        let (location, absolute) = self.compiled_at();
+        let (location, absolute) = (location.clone(), absolute.cloned());
+        let parent = self.keyword_parent(keyword);
+        Box::new(errors.map(move |mut error| {
+            error.attribute(absolute.as_ref(), &location, parent);
+            error
+        }))
+    }
+
+    fn iter_errors_unshared<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        match &*self.validators {
             NodeValidators::Keyword(kvs) if kvs.validators.len() == 1 => {
-                kvs.validators[0].1.iter_errors(instance, location)
+                let (keyword, validator) = &kvs.validators[0];
+                self.attribute_all(validator.iter_errors(instance, location), Some(keyword))
             }
             NodeValidators::Keyword(kvs) => Box::new(
                 kvs.validators
                     .iter()
-                    .flat_map(|(_, v)| v.iter_errors(instance, location))
+                    .flat_map(|(keyword, v)| {
+                        v.iter_errors(instance, location).map(move |mut error| {
+                            self.attribute(&mut error, Some(keyword));
+                            error
+                        })
+                    })
                     .collect::<Vec<_>>()
                     .into_iter(),
             ),
             NodeValidators::Boolean {
                 validator: Some(v), ..
-            } => v.iter_errors(instance, location),
+            } => self.attribute_all(v.iter_errors(instance, location), None),
             NodeValidators::Boolean {
                 validator: None, ..
             } => Box::new(std::iter::empty()),
@@ -285,15 +696,20 @@ impl Validate for SchemaNode {
         }
     }
 
-    fn validate<'i>(
+    fn validate_unshared<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        match &self.validators {
+        match &*self.validators {
             NodeValidators::Keyword(kvs) => {
-                for (_, validator) in &kvs.validators {
-                    validator.validate(instance, location)?;
+                for (keyword, validator) in &kvs.validators {
+                    validator
+                        .validate(instance, location)
+                        .map_err(|mut error| {
+                            self.attribute(&mut error, Some(keyword));
+                            error
+                        })?;
                 }
             }
             NodeValidators::Array { validators } => {
@@ -302,48 +718,55 @@ impl Validate for SchemaNode {
                 }
             }
             NodeValidators::Boolean { validator: Some(_) } => {
-                return Err(ValidationError::false_schema(
-                    self.location.clone(),
+                let (schema_location, _) = self.compiled_at();
+                let mut error = ValidationError::false_schema(
+                    schema_location.clone(),
                     location.into(),
                     instance,
-                ))
+                );
+                self.attribute(&mut error, None);
+                return Err(error);
             }
             NodeValidators::Boolean { validator: None } => return Ok(()),
         }
         Ok(())
     }
 
-    fn is_valid(&self, instance: &Value) -> bool {
-        match &self.validators {
-            // If we only have one validator then calling it's `is_valid` directly does
-            // actually save the 20 or so instructions required to call the `slice::Iter::all`
-            // implementation. Validators at the leaf of a tree are all single node validators so
-            // this optimization can have significant cumulative benefits
-            NodeValidators::Keyword(kvs) if kvs.validators.len() == 1 => {
-                kvs.validators[0].1.is_valid(instance)
-            }
-            NodeValidators::Keyword(kvs) => {
-                for (_, v) in &kvs.validators {
-                    if !v.is_valid(instance) {
-                        return false;
-                    }
-                }
-                true
-            }
-            NodeValidators::Array { validators } => validators.iter().all(|v| v.is_valid(instance)),
-            NodeValidators::Boolean { validator: Some(_) } => false,
-            NodeValidators::Boolean { validator: None } => true,
-        }
-    }
-
-    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
-        match self.validators {
+    fn apply_unshared<'a>(
+        &'a self,
+        instance: &Value,
+        location: &LazyLocation,
+    ) -> PartialApplication<'a> {
+        match *self.validators {
             NodeValidators::Array { ref validators } => {
                 self.apply_subschemas(instance, location, validators.iter().enumerate(), None)
             }
             NodeValidators::Boolean { ref validator } => {
                 if let Some(validator) = validator {
-                    validator.apply(instance, location)
+                    // Report the errors at this node rather than at the parent keyword, so that
+                    // a `false` reached through `$ref` points to its own absolute location
+                    match validator.apply(instance, location) {
+                        PartialApplication::Invalid {
+                            errors,
+                            mut child_results,
+                        } => {
+                            let (keyword_location, absolute) = self.compiled_at();
+                            let instance_location: Location = location.into();
+                            for error in errors {
+                                child_results.push_front(OutputUnit::<ErrorDescription>::error(
+                                    keyword_location.clone(),
+                                    instance_location.clone(),
+                                    absolute.map(|absolute| absolute.resolve(keyword_location)),
+                                    error,
+                                ));
+                            }
+                            PartialApplication::Invalid {
+                                errors: Vec::new(),
+                                child_results,
+                            }
+                        }
+                        valid => valid,
+                    }
                 } else {
                     PartialApplication::Valid {
                         annotations: None,
@@ -355,6 +778,7 @@ impl Validate for SchemaNode {
                 let KeywordValidators {
                     ref unmatched_keywords,
                     ref validators,
+                    ..
                 } = *kvals;
                 let annotations: Option<Annotations<'a>> =
                     unmatched_keywords.as_ref().map(Annotations::from);
@@ -369,6 +793,285 @@ impl Validate for SchemaNode {
     }
 }
 
+impl Validate for SchemaNode {
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        if let Err(err) = self.check_depth(instance, location) {
+            return error(err);
+        }
+        self.rebase_errors(self.iter_errors_unshared(instance, location))
+    }
+
+    #[cfg(feature = "parallel")]
+    fn par_iter_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        if let Err(err) = self.check_depth(instance, location) {
+            return error(err);
+        }
+        let errors: Vec<_> = match &*self.validators {
+            NodeValidators::Keyword(kvs) => kvs
+                .validators
+                .iter()
+                .flat_map(|(keyword, v)| {
+                    v.par_iter_errors(instance, location).map(move |mut error| {
+                        self.attribute(&mut error, Some(keyword));
+                        error
+                    })
+                })
+                .collect(),
+            NodeValidators::Boolean {
+                validator: Some(v), ..
+            } => self
+                .attribute_all(v.iter_errors(instance, location), None)
+                .collect(),
+            NodeValidators::Boolean {
+                validator: None, ..
+            } => Vec::new(),
+            NodeValidators::Array { validators } => validators
+                .iter()
+                .flat_map(|v| v.par_iter_errors(instance, location))
+                .collect(),
+        };
+        self.rebase_errors(Box::new(errors.into_iter()))
+    }
+
+    #[cfg(feature = "parallel")]
+    fn par_is_valid(&self, instance: &Value) -> bool {
+        match &*self.validators {
+            NodeValidators::Keyword(kvs) => kvs.by_cost().all(|v| v.par_is_valid(instance)),
+            NodeValidators::Array { validators } => {
+                validators.iter().all(|v| v.par_is_valid(instance))
+            }
+            NodeValidators::Boolean { validator: Some(_) } => false,
+            NodeValidators::Boolean { validator: None } => true,
+        }
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        self.check_depth(instance, location)?;
+        let result = self.validate_unshared(instance, location);
+        if let Some(origin) = &self.origin {
+            result.map_err(|error| {
+                rebase_error(error, origin, &self.location, self.absolute.as_ref())
+            })
+        } else {
+            result
+        }
+    }
+
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if let Err(error) = self.check_depth(instance, location) {
+            errors.push(error);
+            return;
+        }
+        if scratch.first_error_only && !errors.is_empty() {
+            return;
+        }
+        if let Some(paths) = &scratch.projection {
+            if !paths.contains_location(location) {
+                return;
+            }
+        }
+        if !scratch.visit(&self.location, location) {
+            return;
+        }
+        let pending = match scratch
+            .incremental
+            .as_mut()
+            .map(|recorder| recorder.enter(self, location))
+        {
+            Some(Enter::Skip) => return,
+            Some(Enter::Run(pending)) => pending,
+            None => None,
+        };
+        let start = errors.len();
+        match &*self.validators {
+            NodeValidators::Keyword(kvs) => {
+                for (keyword, validator) in &kvs.validators {
+                    let start = errors.len();
+                    if scratch.first_error_only && start > 0 {
+                        break;
+                    }
+                    validator.collect_errors(instance, location, errors, scratch);
+                    for error in &mut errors[start..] {
+                        self.attribute(error, Some(keyword));
+                    }
+                }
+            }
+            NodeValidators::Array { validators } => {
+                for validator in validators {
+                    validator.collect_errors(instance, location, errors, scratch);
+                }
+            }
+            NodeValidators::Boolean {
+                validator: Some(validator),
+            } => {
+                validator.collect_errors(instance, location, errors, scratch);
+                for error in &mut errors[start..] {
+                    self.attribute(error, None);
+                }
+            }
+            NodeValidators::Boolean { validator: None } => {}
+        }
+        if let Some(origin) = &self.origin {
+            for error in &mut errors[start..] {
+                rebase_error_in_place(error, origin, &self.location, self.absolute.as_ref());
+            }
+        }
+        if let (Some(pending), Some(recorder)) = (pending, scratch.incremental.as_mut()) {
+            recorder.exit(pending, errors.len() == start);
+        }
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.try_is_valid(instance).unwrap_or(false)
+    }
+
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        match &*self.validators {
+            // If we only have one validator then calling it's `is_valid` directly does
+            // actually save the 20 or so instructions required to call the `slice::Iter::all`
+            // implementation. Validators at the leaf of a tree are all single node validators so
+            // this optimization can have significant cumulative benefits
+            NodeValidators::Keyword(kvs) if kvs.validators.len() == 1 => {
+                kvs.validators[0].1.is_valid_deferred(instance, checks)
+            }
+            NodeValidators::Keyword(kvs) => {
+                for v in kvs.by_cost() {
+                    if !v.is_valid_deferred(instance, checks) {
+                        return false;
+                    }
+                }
+                true
+            }
+            NodeValidators::Array { validators } => validators
+                .iter()
+                .all(|v| v.is_valid_deferred(instance, checks)),
+            NodeValidators::Boolean { validator: Some(_) } => false,
+            NodeValidators::Boolean { validator: None } => true,
+        }
+    }
+
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        match &*self.validators {
+            NodeValidators::Keyword(kvs) if kvs.validators.len() == 1 => {
+                kvs.validators[0].1.is_valid_value(instance)
+            }
+            NodeValidators::Keyword(kvs) => kvs.by_cost().all(|v| v.is_valid_value(instance)),
+            NodeValidators::Array { validators } => {
+                validators.iter().all(|v| v.is_valid_value(instance))
+            }
+            NodeValidators::Boolean { validator: Some(_) } => false,
+            NodeValidators::Boolean { validator: None } => true,
+        }
+    }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        if self.is_trivially_true() {
+            return PartialApplication::valid_empty();
+        }
+        if let Err(error) = self.check_depth(instance, location) {
+            return PartialApplication::invalid_empty(vec![ErrorDescription::from(error)]);
+        }
+        let mut result = self.apply_unshared(instance, location);
+        if let Some(origin) = &self.origin {
+            let absolutes = origin.absolute.as_ref().zip(self.absolute.as_ref());
+            match &mut result {
+                PartialApplication::Valid { child_results, .. } => {
+                    for unit in child_results {
+                        unit.rebase(&origin.location, &self.location, absolutes);
+                    }
+                }
+                PartialApplication::Invalid { child_results, .. } => {
+                    for unit in child_results {
+                        unit.rebase(&origin.location, &self.location, absolutes);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Wrap `validators` so that they report to the instrumentation hook, if there is one.
+#[cfg(feature = "instrumentation")]
+fn instrument(
+    ctx: &Context<'_>,
+    validators: Vec<(Keyword, BoxedValidator)>,
+) -> Vec<(Keyword, BoxedValidator)> {
+    let Some(hook) = ctx.config().instrumentation() else {
+        return validators;
+    };
+    validators
+        .into_iter()
+        .map(|(keyword, validator)| {
+            let location = (&keyword).join_to(ctx.location());
+            let validator = Instrumented::wrap(hook, keyword.as_str(), location, validator);
+            (keyword, validator)
+        })
+        .collect()
+}
+
+/// Where a validator is located relative to the node that holds it.
+trait ValidatorPath: Copy {
+    fn join_to(self, location: &Location) -> Location;
+}
+
+impl ValidatorPath for usize {
+    fn join_to(self, location: &Location) -> Location {
+        location.join(self)
+    }
+}
+
+impl ValidatorPath for &Keyword {
+    fn join_to(self, location: &Location) -> Location {
+        match self {
+            Keyword::Hoisted {
+                location: relative, ..
+            } => relative
+                .rebase(&Location::new(), location)
+                .expect("Relative locations are within the root"),
+            keyword => location.join(keyword),
+        }
+    }
+}
+
+fn rebase_error<'i>(
+    mut error: ValidationError<'i>,
+    origin: &Origin,
+    target: &Location,
+    absolute: Option<&AbsoluteLocation>,
+) -> ValidationError<'i> {
+    rebase_error_in_place(&mut error, origin, target, absolute);
+    error
+}
+
+fn rebase_error_in_place(
+    error: &mut ValidationError<'_>,
+    origin: &Origin,
+    target: &Location,
+    absolute: Option<&AbsoluteLocation>,
+) {
+    if let Some(schema_path) = error.schema_path.rebase(&origin.location, target) {
+        error.schema_path = schema_path;
+    }
+    if let (Some(from), Some(to)) = (&origin.absolute, absolute) {
+        error.rebase_keyword_origin(from, to);
+    }
+}
+
 enum NodeValidatorsIter<'a> {
     NoValidator,
     BooleanValidators(std::iter::Once<&'a BoxedValidator>),
@@ -0,0 +1,838 @@
+//! Canonical form of schemas for comparison and caching.
+use std::{error, fmt};
+
+use ahash::AHashMap;
+use referencing::{uri, Draft};
+use serde_json::{Map, Value};
+
+use crate::{paths::Location, ValidationError};
+
+const META_SCHEMA: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// An error that occurred while normalizing a schema.
+#[derive(Debug)]
+pub struct NormalizeError {
+    /// Location of the keyword that could not be normalized.
+    pub location: Location,
+    /// Type of normalization error.
+    pub kind: NormalizeErrorKind,
+}
+
+/// Kinds of errors that may happen during schema normalization.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum NormalizeErrorKind {
+    /// The schema is not valid.
+    InvalidSchema(Box<ValidationError<'static>>),
+    /// A keyword whose meaning is not known to be preserved by the canonical form.
+    Unsupported { reason: String },
+}
+
+impl NormalizeError {
+    fn unsupported(location: &Location, reason: impl Into<String>) -> NormalizeError {
+        NormalizeError {
+            location: location.clone(),
+            kind: NormalizeErrorKind::Unsupported {
+                reason: reason.into(),
+            },
+        }
+    }
+}
+
+impl error::Error for NormalizeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            NormalizeErrorKind::InvalidSchema(error) => Some(error.as_ref()),
+            NormalizeErrorKind::Unsupported { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for NormalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            NormalizeErrorKind::InvalidSchema(error) => return error.fmt(f),
+            NormalizeErrorKind::Unsupported { reason } => f.write_str(reason)?,
+        }
+        write!(f, r#" at "{}""#, self.location)
+    }
+}
+
+/// Rewrite `schema` of the given `draft` into its canonical form, keeping annotations.
+///
+/// See [`Normalizer`] for details.
+///
+/// # Errors
+///
+/// Returns an error if the schema is invalid or uses keywords that can not be rewritten
+/// without possibly changing what it accepts.
+pub fn normalize(schema: &Value, draft: Draft) -> Result<Value, NormalizeError> {
+    Normalizer::new().normalize(schema, draft)
+}
+
+/// Rewrites schemas into a canonical Draft 2020-12 form, e.g. to deduplicate schemas that
+/// differ only in the way they are written.
+///
+/// The canonical form has sorted object keys and a `$schema` of Draft 2020-12 at the root, and
+/// uses one spelling for keywords that have several:
+///
+/// - `definitions` becomes `$defs`, and references into it are updated;
+/// - `dependencies` is split into `dependentRequired` and `dependentSchemas`;
+/// - an array in `items` becomes `prefixItems`, and `additionalItems` becomes `items`;
+/// - a boolean `exclusiveMaximum` / `exclusiveMinimum` of Draft 4 takes the value of the
+///   sibling `maximum` / `minimum`;
+/// - `enum` with a single value becomes `const`.
+///
+/// Keywords that the draft of the schema does not know, and keywords next to `$ref` in Draft 7
+/// and earlier, are left out, because they are ignored during validation. The normalized schema
+/// accepts exactly the same instances as the original one. When that can not be guaranteed,
+/// normalization fails instead, e.g. for `format` that is asserted by default before Draft
+/// 2019-09, Draft 4 `integer` that rejects `1.0`, embedded resources with their own `$id`, or
+/// `$recursiveRef`.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use jsonschema::{Draft, Normalizer};
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "title": "Price",
+///     "properties": {"amount": {"$ref": "#/definitions/positive"}},
+///     "definitions": {"positive": {"minimum": 0, "exclusiveMinimum": true}},
+///     "dependencies": {"amount": ["currency"]}
+/// });
+/// let normalized = Normalizer::new()
+///     .without_annotations()
+///     .normalize(&schema, Draft::Draft4)?;
+/// assert_eq!(
+///     normalized,
+///     json!({
+///         "$defs": {"positive": {"exclusiveMinimum": 0}},
+///         "$schema": "https://json-schema.org/draft/2020-12/schema",
+///         "dependentRequired": {"amount": ["currency"]},
+///         "properties": {"amount": {"$ref": "#/$defs/positive"}}
+///     })
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Normalizer {
+    remove_annotations: bool,
+}
+
+impl Normalizer {
+    /// Create a normalizer that keeps annotations.
+    #[must_use]
+    pub fn new() -> Normalizer {
+        Normalizer::default()
+    }
+    /// Leave out keywords that never affect validation: `title`, `description`, `default`,
+    /// `examples`, `deprecated`, `readOnly`, `writeOnly` and `$comment`.
+    #[must_use]
+    pub fn without_annotations(mut self) -> Self {
+        self.remove_annotations = true;
+        self
+    }
+    /// Rewrite `schema` of the given `draft` into its canonical form. A `$schema` in `schema`
+    /// takes precedence over `draft`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema is invalid or uses keywords that can not be rewritten
+    /// without possibly changing what it accepts.
+    pub fn normalize(&self, schema: &Value, draft: Draft) -> Result<Value, NormalizeError> {
+        crate::options()
+            .with_draft(draft)
+            .build(schema)
+            .map_err(|error| NormalizeError {
+                location: error.schema_path.clone(),
+                kind: NormalizeErrorKind::InvalidSchema(Box::new(error)),
+            })?;
+        let draft = Draft::from_schema(schema).unwrap_or(draft);
+        let id = schema
+            .get(if draft == Draft::Draft4 { "id" } else { "$id" })
+            .and_then(Value::as_str);
+        let mut state = State {
+            draft,
+            remove_annotations: self.remove_annotations,
+            id: id.map(|id| id.trim_end_matches('#')),
+            moved: AHashMap::new(),
+            references: Vec::new(),
+        };
+        let root = Location::new();
+        let mut normalized = state.schema(schema, &root, &root)?;
+        state.rewrite_references(&mut normalized)?;
+        Ok(normalized)
+    }
+}
+
+struct State<'a> {
+    draft: Draft,
+    remove_annotations: bool,
+    /// The root `$id`, which references may use instead of an empty URI.
+    id: Option<&'a str>,
+    /// Locations of the kept subschemas in the normalized schema, by their original location.
+    moved: AHashMap<String, Location>,
+    /// References to rewrite: the normalized location of the subschema, the keyword, the
+    /// reference and the original location of the keyword.
+    references: Vec<(Location, &'static str, &'a str, Location)>,
+}
+
+impl<'a> State<'a> {
+    fn schema(
+        &mut self,
+        schema: &'a Value,
+        from: &Location,
+        to: &Location,
+    ) -> Result<Value, NormalizeError> {
+        self.moved.insert(from.as_str().to_string(), to.clone());
+        let Value::Object(object) = schema else {
+            return Ok(schema.clone());
+        };
+        let is_root = from.as_str().is_empty();
+        self.check(schema, object, from, is_root)?;
+        let ignores_siblings = object.contains_key("$ref") && self.draft <= Draft::Draft7;
+        let mut output = Map::new();
+        let mut dependent_required = Map::new();
+        let mut dependent_schemas = Map::new();
+        for (keyword, value) in object {
+            let location = from.join(keyword);
+            if (ignores_siblings && !matches!(keyword.as_str(), "$ref" | "definitions" | "$defs"))
+                || (is_keyword(Draft::Draft202012, keyword) && !is_keyword(self.draft, keyword))
+                || (self.remove_annotations && is_annotation(keyword))
+            {
+                continue;
+            }
+            match keyword.as_str() {
+                "$schema" => {}
+                "id" if self.draft == Draft::Draft4 => {
+                    output.insert("$id".to_string(), value.clone());
+                }
+                "$ref" | "$dynamicRef" => {
+                    let keyword = if keyword == "$ref" {
+                        "$ref"
+                    } else {
+                        "$dynamicRef"
+                    };
+                    if let Some(reference) = value.as_str() {
+                        self.references
+                            .push((to.clone(), keyword, reference, location));
+                    }
+                    output.insert(keyword.to_string(), value.clone());
+                }
+                "definitions" | "$defs" => {
+                    if keyword == "definitions" && object.contains_key("$defs") {
+                        return Err(NormalizeError::unsupported(
+                            &location,
+                            r#""definitions" next to "$defs""#,
+                        ));
+                    }
+                    let defs = self.schemas(value, &location, &to.join("$defs"))?;
+                    output.insert("$defs".to_string(), defs);
+                }
+                "dependencies" | "dependentRequired" | "dependentSchemas" => {
+                    for (name, dependency) in value.as_object().into_iter().flatten() {
+                        let (target, dependency) =
+                            if keyword == "dependentRequired" || dependency.is_array() {
+                                (&mut dependent_required, sorted(dependency))
+                            } else {
+                                let from = location.join(name);
+                                let to = to.join("dependentSchemas").join(name);
+                                (&mut dependent_schemas, self.schema(dependency, &from, &to)?)
+                            };
+                        if target.insert(name.clone(), dependency).is_some() {
+                            return Err(NormalizeError::unsupported(
+                                &location.join(name),
+                                format!(r#"Dependency "{name}" is declared twice"#),
+                            ));
+                        }
+                    }
+                }
+                "items" if value.is_array() => {
+                    let items = self.schemas(value, &location, &to.join("prefixItems"))?;
+                    output.insert("prefixItems".to_string(), items);
+                }
+                "additionalItems" => {
+                    // Only applies next to an array in `items`
+                    if object.get("items").is_some_and(Value::is_array) {
+                        let items = self.schema(value, &location, &to.join("items"))?;
+                        output.insert("items".to_string(), items);
+                    }
+                }
+                "maximum" | "minimum" | "exclusiveMaximum" | "exclusiveMinimum"
+                    if self.draft == Draft::Draft4 =>
+                {
+                    let (limit, exclusive) = match keyword.as_str() {
+                        "maximum" | "exclusiveMaximum" => ("maximum", "exclusiveMaximum"),
+                        _ => ("minimum", "exclusiveMinimum"),
+                    };
+                    if object.get(exclusive) == Some(&Value::Bool(true)) {
+                        if keyword == exclusive {
+                            output.insert(exclusive.to_string(), object[limit].clone());
+                        }
+                    } else if keyword == limit {
+                        output.insert(limit.to_string(), value.clone());
+                    }
+                }
+                "additionalProperties"
+                | "contains"
+                | "contentSchema"
+                | "else"
+                | "if"
+                | "items"
+                | "not"
+                | "propertyNames"
+                | "then"
+                | "unevaluatedItems"
+                | "unevaluatedProperties" => {
+                    let subschema = self.schema(value, &location, &to.join(keyword))?;
+                    output.insert(keyword.clone(), subschema);
+                }
+                "allOf" | "anyOf" | "oneOf" | "prefixItems" | "properties"
+                | "patternProperties" => {
+                    let subschemas = self.schemas(value, &location, &to.join(keyword))?;
+                    output.insert(keyword.clone(), subschemas);
+                }
+                _ => {
+                    output.insert(keyword.clone(), sorted(value));
+                }
+            }
+        }
+        if !dependent_required.is_empty() {
+            output.insert(
+                "dependentRequired".to_string(),
+                Value::Object(dependent_required),
+            );
+        }
+        if !dependent_schemas.is_empty() {
+            output.insert(
+                "dependentSchemas".to_string(),
+                Value::Object(dependent_schemas),
+            );
+        }
+        if let Some(Value::Array(values)) = output.get("enum") {
+            if let [value] = values.as_slice() {
+                let value = value.clone();
+                match output.get("const") {
+                    None => {
+                        output.remove("enum");
+                        output.insert("const".to_string(), value);
+                    }
+                    Some(constant) if *constant == value => {
+                        output.remove("enum");
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if is_root {
+            output.insert(
+                "$schema".to_string(),
+                Value::String(META_SCHEMA.to_string()),
+            );
+        }
+        Ok(Value::Object(sort_keys(output)))
+    }
+
+    /// Normalize subschemas in an array or in an object, like `allOf` or `properties`.
+    fn schemas(
+        &mut self,
+        value: &'a Value,
+        from: &Location,
+        to: &Location,
+    ) -> Result<Value, NormalizeError> {
+        match value {
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, item)| self.schema(item, &from.join(idx), &to.join(idx)))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Value::Object(map) => {
+                let mut output = Map::new();
+                for (name, item) in map {
+                    output.insert(
+                        name.clone(),
+                        self.schema(item, &from.join(name), &to.join(name))?,
+                    );
+                }
+                Ok(Value::Object(sort_keys(output)))
+            }
+            _ => Ok(sorted(value)),
+        }
+    }
+
+    /// Reject keywords whose meaning differs in Draft 2020-12 or depends on the resource
+    /// structure that normalization changes.
+    fn check(
+        &self,
+        schema: &Value,
+        object: &Map<String, Value>,
+        location: &Location,
+        is_root: bool,
+    ) -> Result<(), NormalizeError> {
+        let id = if self.draft == Draft::Draft4 {
+            "id"
+        } else {
+            "$id"
+        };
+        if let Some(value) = object.get(id) {
+            let location = location.join(id);
+            if !is_root {
+                return Err(NormalizeError::unsupported(
+                    &location,
+                    "Embedded resources are not supported",
+                ));
+            }
+            if value
+                .as_str()
+                .is_some_and(|id| id.trim_end_matches('#').contains('#'))
+            {
+                return Err(NormalizeError::unsupported(
+                    &location,
+                    "Anchors in identifiers are not supported",
+                ));
+            }
+            if object.contains_key("$ref") && self.draft <= Draft::Draft7 {
+                return Err(NormalizeError::unsupported(
+                    &location,
+                    r#"An identifier next to "$ref""#,
+                ));
+            }
+        }
+        if !is_root && Draft::from_schema(schema).is_some_and(|draft| draft != self.draft) {
+            return Err(NormalizeError::unsupported(
+                &location.join("$schema"),
+                "Subschemas of another draft are not supported",
+            ));
+        }
+        for keyword in ["$recursiveRef", "$recursiveAnchor"] {
+            if self.draft == Draft::Draft201909 && object.contains_key(keyword) {
+                return Err(NormalizeError::unsupported(
+                    &location.join(keyword),
+                    format!(r#""{keyword}" has no equivalent in Draft 2020-12"#),
+                ));
+            }
+        }
+        if self.draft <= Draft::Draft7
+            && object.contains_key("format")
+            && !object.contains_key("$ref")
+        {
+            return Err(NormalizeError::unsupported(
+                &location.join("format"),
+                r#""format" is asserted by default before Draft 2019-09"#,
+            ));
+        }
+        if matches!(self.draft, Draft::Draft6 | Draft::Draft7) && !object.contains_key("$ref") {
+            for keyword in ["contentMediaType", "contentEncoding"] {
+                if object.contains_key(keyword) {
+                    return Err(NormalizeError::unsupported(
+                        &location.join(keyword),
+                        format!(r#""{keyword}" is asserted before Draft 2019-09"#),
+                    ));
+                }
+            }
+        }
+        if self.draft == Draft::Draft4 && !object.contains_key("$ref") {
+            let integer = Value::String("integer".to_string());
+            let has_integer = match object.get("type") {
+                Some(Value::Array(types)) => types.contains(&integer),
+                Some(ty) => *ty == integer,
+                None => false,
+            };
+            if has_integer {
+                return Err(NormalizeError::unsupported(
+                    &location.join("type"),
+                    r#"Draft 4 "integer" rejects numbers with a fractional part like 1.0"#,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Point references within the document to the new locations of their targets.
+    fn rewrite_references(&self, normalized: &mut Value) -> Result<(), NormalizeError> {
+        for (to, keyword, reference, location) in &self.references {
+            let (base, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+            let is_local = base.is_empty() || Some(base) == self.id;
+            if !is_local || !fragment.starts_with('/') {
+                continue;
+            }
+            let pointer = uri::decode_pointer(fragment)
+                .map_err(|_| NormalizeError::unsupported(location, "Invalid reference"))?;
+            let target = self.moved.get(pointer.as_ref()).ok_or_else(|| {
+                NormalizeError::unsupported(
+                    location,
+                    format!(r#"Reference "{reference}" points to a value that is not kept"#),
+                )
+            })?;
+            let mut rewritten = format!("{base}#");
+            uri::encode_to(target.as_str(), &mut rewritten);
+            if let Some(Value::Object(object)) = normalized.pointer_mut(to.as_str()) {
+                object.insert((*keyword).to_string(), Value::String(rewritten));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `keyword` affects validation in `draft`, or can be the target of references.
+fn is_keyword(draft: Draft, keyword: &str) -> bool {
+    match keyword {
+        "$ref"
+        | "additionalItems"
+        | "additionalProperties"
+        | "allOf"
+        | "anyOf"
+        | "dependencies"
+        | "enum"
+        | "format"
+        | "items"
+        | "maxItems"
+        | "maxLength"
+        | "maxProperties"
+        | "minItems"
+        | "minLength"
+        | "minProperties"
+        | "multipleOf"
+        | "not"
+        | "oneOf"
+        | "pattern"
+        | "patternProperties"
+        | "properties"
+        | "required"
+        | "uniqueItems"
+        | "maximum"
+        | "minimum"
+        | "exclusiveMaximum"
+        | "exclusiveMinimum"
+        | "type"
+        | "definitions"
+        | "$defs" => true,
+        "$id" | "const" | "contains" | "propertyNames" => draft >= Draft::Draft6,
+        "if" | "then" | "else" => draft >= Draft::Draft7,
+        "$anchor"
+        | "dependentRequired"
+        | "dependentSchemas"
+        | "maxContains"
+        | "minContains"
+        | "unevaluatedItems"
+        | "unevaluatedProperties" => draft >= Draft::Draft201909,
+        "$dynamicAnchor" | "$dynamicRef" | "prefixItems" => draft == Draft::Draft202012,
+        _ => false,
+    }
+}
+
+fn is_annotation(keyword: &str) -> bool {
+    matches!(
+        keyword,
+        "title"
+            | "description"
+            | "default"
+            | "examples"
+            | "deprecated"
+            | "readOnly"
+            | "writeOnly"
+            | "$comment"
+    )
+}
+
+/// Copy of `value` with object keys in sorted order, even with `serde_json/preserve_order`.
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+        Value::Object(object) => Value::Object(sort_keys(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), sorted(value)))
+                .collect(),
+        )),
+        _ => value.clone(),
+    }
+}
+
+fn sort_keys(object: Map<String, Value>) -> Map<String, Value> {
+    let mut entries: Vec<_> = object.into_iter().collect();
+    entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+    entries.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::{
+        rand_core::{RngCore, SeedableRng},
+        ChaCha8Rng,
+    };
+    use referencing::Draft;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{normalize, NormalizeErrorKind, Normalizer};
+
+    #[test_case(
+        &json!({"type": "string", "enum": ["a"]}),
+        Draft::Draft202012,
+        &json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "const": "a", "type": "string"});
+        "enum with one value"
+    )]
+    #[test_case(
+        &json!({"enum": ["a"], "const": "a"}),
+        Draft::Draft202012,
+        &json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "const": "a"});
+        "enum next to the same const"
+    )]
+    #[test_case(
+        &json!({"enum": ["a"], "const": "b"}),
+        Draft::Draft202012,
+        &json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "const": "b", "enum": ["a"]});
+        "enum next to another const"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"$ref": "#/definitions/a"}}, "definitions": {"a": {"minimum": 1}}}),
+        Draft::Draft7,
+        &json!({
+            "$defs": {"a": {"minimum": 1}},
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "properties": {"a": {"$ref": "#/$defs/a"}}
+        });
+        "definitions"
+    )]
+    #[test_case(
+        &json!({"dependencies": {"a": ["b"], "c": {"required": ["d"]}}, "dependentRequired": {"e": ["f"]}}),
+        Draft::Draft201909,
+        &json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "dependentRequired": {"a": ["b"], "e": ["f"]},
+            "dependentSchemas": {"c": {"required": ["d"]}}
+        });
+        "dependencies"
+    )]
+    #[test_case(
+        &json!({"items": [{"type": "string"}], "additionalItems": false, "$ref": "#/items/0"}),
+        Draft::Draft201909,
+        &json!({
+            "$ref": "#/prefixItems/0",
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "items": false,
+            "prefixItems": [{"type": "string"}]
+        });
+        "items array"
+    )]
+    #[test_case(
+        &json!({"items": {}, "additionalItems": false}),
+        Draft::Draft7,
+        &json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "items": {}});
+        "additional items without an array"
+    )]
+    #[test_case(
+        &json!({"maximum": 5, "exclusiveMaximum": true, "minimum": 1, "exclusiveMinimum": false}),
+        Draft::Draft4,
+        &json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "exclusiveMaximum": 5, "minimum": 1});
+        "draft 4 exclusive limits"
+    )]
+    #[test_case(
+        &json!({"id": "https://example.com/root", "const": 1, "contains": {}, "minLength": 1}),
+        Draft::Draft4,
+        &json!({"$id": "https://example.com/root", "$schema": "https://json-schema.org/draft/2020-12/schema", "minLength": 1});
+        "keywords unknown to the draft"
+    )]
+    #[test_case(
+        &json!({"$ref": "#/definitions/a", "type": "string", "definitions": {"a": {}}}),
+        Draft::Draft7,
+        &json!({"$defs": {"a": {}}, "$ref": "#/$defs/a", "$schema": "https://json-schema.org/draft/2020-12/schema"});
+        "ignored siblings of references"
+    )]
+    #[test_case(
+        &json!({"title": "A", "x-extension": {"b": 1, "a": 2}, "$schema": "https://json-schema.org/draft/2020-12/schema"}),
+        Draft::Draft7,
+        &json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "title": "A", "x-extension": {"a": 2, "b": 1}});
+        "annotations and unknown keywords are kept"
+    )]
+    #[test_case(
+        &json!({
+            "properties": {
+                "a": {"$ref": "#/definitions/caf%C3%A9"},
+                "b": {"$ref": "#/definitions/a%2Fb"},
+                "c": {"$ref": "#/definitions/a~0b"},
+                "d": {"$ref": "#/definitions/a%25b"}
+            },
+            "definitions": {"café": {"minimum": 1}, "a/b": {"minimum": 2}, "a~b": {"minimum": 3}, "a%b": {"minimum": 4}}
+        }),
+        Draft::Draft7,
+        &json!({
+            "$defs": {"café": {"minimum": 1}, "a/b": {"minimum": 2}, "a~b": {"minimum": 3}, "a%b": {"minimum": 4}},
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "properties": {
+                "a": {"$ref": "#/$defs/caf%C3%A9"},
+                "b": {"$ref": "#/$defs/a~1b"},
+                "c": {"$ref": "#/$defs/a~0b"},
+                "d": {"$ref": "#/$defs/a%25b"}
+            }
+        });
+        "encoded reference segments"
+    )]
+    #[test_case(&json!(false), Draft::Draft202012, &json!(false); "boolean")]
+    fn normalized(schema: &Value, draft: Draft, expected: &Value) {
+        assert_eq!(
+            &normalize(schema, draft).expect("Normalizable schema"),
+            expected
+        );
+    }
+
+    #[test]
+    fn without_annotations() {
+        let schema = json!({
+            "title": "A",
+            "description": "B",
+            "$comment": "C",
+            "properties": {"title": {"default": 1, "examples": [2], "deprecated": true}}
+        });
+        assert_eq!(
+            Normalizer::new()
+                .without_annotations()
+                .normalize(&schema, Draft::Draft202012)
+                .expect("Normalizable schema"),
+            json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "properties": {"title": {}}})
+        );
+    }
+
+    #[test]
+    fn key_order() {
+        let left = json!({"required": ["a"], "properties": {"b": {}, "a": {"enum": [1]}}});
+        let right = json!({"properties": {"a": {"const": 1}, "b": {}}, "required": ["a"]});
+        let left = normalize(&left, Draft::Draft202012).expect("Normalizable schema");
+        let right = normalize(&right, Draft::Draft202012).expect("Normalizable schema");
+        assert_eq!(left.to_string(), right.to_string());
+    }
+
+    #[test_case(&json!({"type": "integer"}), Draft::Draft4, "/type"; "draft 4 integer")]
+    #[test_case(&json!({"format": "email"}), Draft::Draft7, "/format"; "asserted format")]
+    #[test_case(&json!({"contentMediaType": "application/json"}), Draft::Draft7, "/contentMediaType"; "asserted content")]
+    #[test_case(&json!({"$defs": {"a": {"$id": "https://example.com/a"}}}), Draft::Draft202012, "/$defs/a/$id"; "embedded resource")]
+    #[test_case(&json!({"$recursiveRef": "#"}), Draft::Draft201909, "/$recursiveRef"; "recursive reference")]
+    #[test_case(&json!({"definitions": {}, "$defs": {}}), Draft::Draft7, "/definitions"; "definitions next to defs")]
+    #[test_case(
+        &json!({"properties": {"a": {"$ref": "#/properties/b/properties/c"}, "b": {"$ref": "#", "properties": {"c": {}}}}}),
+        Draft::Draft7,
+        "/properties/a/$ref";
+        "reference to an ignored sibling"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"$schema": "http://json-schema.org/draft-04/schema#"}}}),
+        Draft::Draft7,
+        "/properties/a/$schema";
+        "subschema of another draft"
+    )]
+    fn unsupported(schema: &Value, draft: Draft, location: &str) {
+        let error = normalize(schema, draft).expect_err("Should not be normalized");
+        assert!(matches!(error.kind, NormalizeErrorKind::Unsupported { .. }));
+        assert_eq!(error.location.as_str(), location);
+    }
+
+    #[test]
+    fn invalid_schema() {
+        let error = normalize(&json!({"minimum": "1"}), Draft::Draft202012)
+            .expect_err("Should not be normalized");
+        assert!(matches!(error.kind, NormalizeErrorKind::InvalidSchema(_)));
+    }
+
+    fn random_value(rng: &mut ChaCha8Rng, depth: usize) -> Value {
+        const STRINGS: &[&str] = &["", "a", "b", "c", "foo", "bar", "ab"];
+        let choices = if depth == 0 { 6 } else { 8 };
+        let pick = |rng: &mut ChaCha8Rng, n: usize| rng.next_u32() as usize % n;
+        match pick(rng, choices) {
+            0 => Value::Null,
+            1 => Value::Bool(rng.next_u32() & 1 == 0),
+            2 => json!(pick(rng, 14) as i64 - 3),
+            3 => json!([0.5, 1.0, 4.5, 5.0, 5.5, -0.5][pick(rng, 6)]),
+            4 | 5 => json!(STRINGS[pick(rng, STRINGS.len())]),
+            6 => Value::Array(
+                (0..pick(rng, 4))
+                    .map(|_| random_value(rng, depth - 1))
+                    .collect(),
+            ),
+            _ => Value::Object(
+                (0..pick(rng, 4))
+                    .map(|_| {
+                        let key = STRINGS[1 + pick(rng, STRINGS.len() - 1)].to_string();
+                        (key, random_value(rng, depth - 1))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test_case(&json!({
+        "title": "Draft 4",
+        "properties": {
+            "a": {"$ref": "#/definitions/limit"},
+            "b": {"enum": ["foo"]},
+            "c": {"const": 1, "type": ["string", "null"]}
+        },
+        "definitions": {"limit": {"maximum": 5, "exclusiveMaximum": true, "minimum": 0}},
+        "dependencies": {"a": ["b"], "c": {"required": ["foo"]}},
+        "items": [{"type": "string"}, {"type": "number"}],
+        "additionalItems": {"type": "boolean"}
+    }), Draft::Draft4; "draft 4")]
+    #[test_case(&json!({
+        "contains": {"minimum": 3},
+        "minContains": 2,
+        "if": {"required": ["a"]},
+        "then": {"maxProperties": 1},
+        "propertyNames": {"enum": ["a", "b"]},
+        "dependentRequired": {"a": ["b"]}
+    }), Draft::Draft6; "draft 6")]
+    #[test_case(&json!({
+        "$ref": "#/definitions/object",
+        "type": "string",
+        "definitions": {
+            "object": {
+                "properties": {"foo": {"const": 1}, "bar": {"$ref": "#/definitions/object"}},
+                "if": {"required": ["a"]},
+                "then": {"minProperties": 2},
+                "else": {"type": ["array", "object"], "items": [{"type": "integer"}], "additionalItems": false}
+            }
+        }
+    }), Draft::Draft7; "draft 7")]
+    #[test_case(&json!({
+        "items": [{"type": "integer"}, {"enum": [[1, 2]]}],
+        "unevaluatedItems": false,
+        "dependentSchemas": {"a": {"required": ["b"]}},
+        "dependencies": {"b": ["c"]},
+        "contains": {"type": "string"},
+        "maxContains": 1,
+        "prefixItems": [{"type": "null"}]
+    }), Draft::Draft201909; "draft 2019-09")]
+    #[test_case(&json!({
+        "$defs": {"positive": {"exclusiveMinimum": 0}},
+        "anyOf": [{"$ref": "#/$defs/positive"}, {"enum": ["a"]}, {"type": "object", "additionalProperties": {"$ref": "#"}}],
+        "dependencies": {"a": {"properties": {"b": false}}},
+        "unevaluatedProperties": {"type": "number"}
+    }), Draft::Draft202012; "draft 2020-12")]
+    #[test_case(&json!({
+        "anyOf": [{"$ref": "#/definitions/caf%C3%A9"}, {"$ref": "#/definitions/a%2Fb"}, {"$ref": "#/definitions/a~0b"}],
+        "definitions": {"café": {"type": "integer", "minimum": 1}, "a/b": {"type": "string"}, "a~b": {"type": "array"}}
+    }), Draft::Draft7; "encoded reference segments")]
+    fn same_as_original(schema: &Value, draft: Draft) {
+        let original = crate::options()
+            .with_draft(draft)
+            .build(schema)
+            .expect("Valid schema");
+        let normalized = normalize(schema, draft).expect("Normalizable schema");
+        let normalized = crate::validator_for(&normalized).expect("Valid schema");
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let mut valid = 0;
+        for _ in 0..5000 {
+            let instance = random_value(&mut rng, 3);
+            let expected = original.is_valid(&instance);
+            assert_eq!(normalized.is_valid(&instance), expected, "{instance}");
+            valid += usize::from(expected);
+        }
+        // Both outcomes are covered
+        assert!(valid > 0 && valid < 5000);
+    }
+}
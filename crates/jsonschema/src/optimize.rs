@@ -0,0 +1,803 @@
+//! Optional rewrites of schemas that make validation faster without changing its results.
+//!
+//! Keywords of `allOf` branches are moved into the schema containing them, which saves a step
+//! through the `allOf` node for every validated instance. Hoisted keywords are compiled at the
+//! location of their branch, so errors still point to where the keywords are written.
+//!
+//! Keywords that never change whether an instance is valid, like `false` branches of `anyOf`,
+//! are left out and recorded as warnings instead.
+use std::{cmp::Ordering, collections::VecDeque};
+
+use ahash::AHashMap;
+use referencing::Draft;
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    compiler::{self, Context},
+    error::{no_error, ErrorIterator, ValidationError},
+    keywords::{
+        self, all_of, any_of::AnyOfValidator, helpers, legacy::type_draft_4,
+        one_of::OneOfValidator, required::RequiredValidator, type_::MultipleTypesValidator,
+        BoxedValidator, BuiltinKeyword, Keyword,
+    },
+    lint::{self, Lint},
+    node::SchemaNode,
+    options::OptimizationLevel,
+    output::{AbsoluteLocation, OutputUnit},
+    paths::{LazyLocation, Location},
+    primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
+    validator::{PartialApplication, Validate, ValidationScratch},
+    value,
+};
+
+const ALL_TYPES: [PrimitiveType; 7] = [
+    PrimitiveType::Array,
+    PrimitiveType::Boolean,
+    PrimitiveType::Integer,
+    PrimitiveType::Null,
+    PrimitiveType::Number,
+    PrimitiveType::Object,
+    PrimitiveType::String,
+];
+
+/// A keyword of the compiled schema or of one of its hoisted `allOf` branches.
+struct Source<'a> {
+    /// Location of the schema that contains the keyword.
+    location: Location,
+    schema: &'a Map<String, Value>,
+    keyword: &'a str,
+    value: &'a Value,
+}
+
+enum Entry<'a> {
+    Keyword(Source<'a>),
+    /// Consecutive branches of the `allOf` at `location` that can not be hoisted, with their
+    /// indices.
+    AllOf {
+        location: Location,
+        items: Vec<(usize, &'a Value)>,
+    },
+    /// Multiple occurrences of the same keyword, checked at once.
+    Merged(Vec<Source<'a>>),
+    /// `anyOf` or `oneOf` with only the branches that can match, with their indices.
+    Branches {
+        source: Source<'a>,
+        items: Vec<(usize, &'a Value)>,
+    },
+}
+
+/// Whether `schema` is compiled with its `allOf` branches hoisted into it or with some of its
+/// keywords left out.
+pub(crate) fn applies(ctx: &Context, schema: &Map<String, Value>) -> bool {
+    let level = ctx.config().optimization_level();
+    // Rewrites do not know that `nullable` changes `type`
+    level != OptimizationLevel::None
+        && ctx.config().dialect().is_none()
+        && schema.iter().any(|(keyword, value)| {
+            (value.is_array() && is_builtin(ctx, "allOf") && keyword == "allOf")
+                || (level >= OptimizationLevel::Prune
+                    && (removal(ctx, ctx.location(), schema, keyword).is_some()
+                        || reachable_branches(ctx, keyword, value).is_some()))
+        })
+}
+
+/// Whether `keyword` is compiled by the built-in validator.
+fn is_builtin(ctx: &Context, keyword: &str) -> bool {
+    ctx.get_keyword_factory(keyword).is_none()
+        && !ctx.config().is_keyword_disabled(keyword)
+        && keywords::get_for_draft(ctx, keyword).is_some()
+}
+
+/// Why `keyword` can be left out of `schema` without changing which instances are valid.
+fn removal(
+    ctx: &Context,
+    location: &Location,
+    schema: &Map<String, Value>,
+    keyword: &str,
+) -> Option<Lint> {
+    if !is_builtin(ctx, keyword) {
+        return None;
+    }
+    lint::no_op_keyword(location, schema, keyword).or_else(|| {
+        // Instances the keyword applies to have to fail on `type` already
+        if is_builtin(ctx, "type") {
+            lint::ignored_keyword(location, schema, keyword)
+        } else {
+            None
+        }
+    })
+}
+
+/// Branches of `anyOf` or `oneOf` that may match, if there are branches that never match.
+///
+/// `false` never counts as a match for `oneOf` either, so leaving it out does not change how
+/// many branches match.
+fn reachable_branches<'a>(
+    ctx: &Context,
+    keyword: &str,
+    value: &'a Value,
+) -> Option<Vec<(usize, &'a Value)>> {
+    let ("anyOf" | "oneOf", Value::Array(items)) = (keyword, value) else {
+        return None;
+    };
+    if !is_builtin(ctx, keyword) {
+        return None;
+    }
+    let reachable: Vec<_> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| **item != Value::Bool(false))
+        .collect();
+    // Without any matching branch the keyword is kept as written, so it still fails
+    if reachable.is_empty() || reachable.len() == items.len() {
+        None
+    } else {
+        Some(reachable)
+    }
+}
+
+fn flatten<'a>(
+    ctx: &Context,
+    location: &Location,
+    schema: &'a Map<String, Value>,
+    entries: &mut Vec<Entry<'a>>,
+) {
+    let prune = ctx.config().optimization_level() >= OptimizationLevel::Prune;
+    for (keyword, value) in schema {
+        match value {
+            Value::Array(items) if keyword == "allOf" && is_builtin(ctx, keyword) => {
+                for (idx, item) in items.iter().enumerate() {
+                    match item {
+                        Value::Bool(true) => {}
+                        Value::Object(branch) if is_hoistable(ctx, branch) => {
+                            flatten(ctx, &location.join("allOf").join(idx), branch, entries);
+                        }
+                        _ => {
+                            // Keep consecutive branches together, so errors are reported in order
+                            if let Some(Entry::AllOf {
+                                location: previous,
+                                items,
+                            }) = entries.last_mut()
+                            {
+                                if previous == location
+                                    && items.last().is_some_and(|(last, _)| last + 1 == idx)
+                                {
+                                    items.push((idx, item));
+                                    continue;
+                                }
+                            }
+                            entries.push(Entry::AllOf {
+                                location: location.clone(),
+                                items: vec![(idx, item)],
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {
+                let source = Source {
+                    location: location.clone(),
+                    schema,
+                    keyword,
+                    value,
+                };
+                if !prune {
+                    entries.push(Entry::Keyword(source));
+                } else if let Some(warning) = removal(ctx, location, schema, keyword) {
+                    ctx.record_warning(warning);
+                } else if let Some(items) = reachable_branches(ctx, keyword, value) {
+                    for warning in lint::unreachable_branches(location, keyword, value) {
+                        ctx.record_warning(warning);
+                    }
+                    entries.push(Entry::Branches { source, items });
+                } else {
+                    entries.push(Entry::Keyword(source));
+                }
+            }
+        }
+    }
+}
+
+/// Whether keywords of `branch` mean the same when compiled as part of the schema containing
+/// the `allOf`.
+fn is_hoistable(ctx: &Context, branch: &Map<String, Value>) -> bool {
+    branch.keys().all(|keyword| match keyword.as_str() {
+        // These change how references are resolved
+        "$id" | "$schema" | "$anchor" | "$dynamicAnchor" | "$recursiveAnchor" | "$vocabulary" => {
+            false
+        }
+        "id" if ctx.draft() == Draft::Draft4 => false,
+        // Older drafts ignore keywords next to `$ref`
+        "$ref" => ctx.supports_adjacent_validation(),
+        // Unknown keywords are reported as annotations of the branch
+        keyword => ctx.is_known_keyword(keyword) || ctx.get_keyword_factory(keyword).is_some(),
+    })
+}
+
+fn is_mergeable(ctx: &Context, keyword: &str) -> bool {
+    let mergeable = match keyword {
+        "type" | "required" | "exclusiveMinimum" | "exclusiveMaximum" | "minLength"
+        | "maxLength" | "minItems" | "maxItems" | "minProperties" | "maxProperties" => true,
+        // Draft 4 makes these exclusive via sibling keywords
+        "minimum" | "maximum" => ctx.draft() != Draft::Draft4,
+        _ => false,
+    };
+    mergeable
+        && ctx.get_keyword_factory(keyword).is_none()
+        && !ctx.config().is_keyword_disabled(keyword)
+}
+
+/// Group keywords that occur multiple times at the position of their first occurrence.
+fn merge<'a>(ctx: &Context, entries: Vec<Entry<'a>>) -> Vec<Entry<'a>> {
+    let mut counts: AHashMap<&str, usize> = AHashMap::new();
+    for entry in &entries {
+        if let Entry::Keyword(source) = entry {
+            if is_mergeable(ctx, source.keyword) {
+                *counts.entry(source.keyword).or_default() += 1;
+            }
+        }
+    }
+    let mut positions: AHashMap<&str, usize> = AHashMap::new();
+    let mut merged = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match entry {
+            Entry::Keyword(source)
+                if counts.get(source.keyword).is_some_and(|count| *count > 1) =>
+            {
+                if let Some(Entry::Merged(sources)) = positions
+                    .get(source.keyword)
+                    .and_then(|position| merged.get_mut(*position))
+                {
+                    sources.push(source);
+                } else {
+                    positions.insert(source.keyword, merged.len());
+                    merged.push(Entry::Merged(vec![source]));
+                }
+            }
+            entry => merged.push(entry),
+        }
+    }
+    merged
+}
+
+/// The keyword under which a validator compiled at `location` is stored in the node of `ctx`.
+fn relative_keyword(ctx: &Context, location: &Location, keyword: Keyword) -> Keyword {
+    if location == ctx.location() {
+        keyword
+    } else {
+        let location = location
+            .rebase(ctx.location(), &Location::new())
+            .expect("Branches are within the schema")
+            .join(&keyword);
+        Keyword::Hoisted {
+            keyword: Box::new(keyword),
+            location,
+        }
+    }
+}
+
+impl<'a> Source<'a> {
+    fn compile(
+        &self,
+        ctx: &Context,
+    ) -> Result<Option<(Keyword, BoxedValidator)>, ValidationError<'a>> {
+        let branch = ctx.at_location(self.location.clone());
+        let compiled = compiler::compile_keyword(&branch, self.schema, self.keyword, self.value)?;
+        Ok(compiled.map(|(keyword, validator)| {
+            (relative_keyword(ctx, &self.location, keyword), validator)
+        }))
+    }
+}
+
+/// Compile `schema` with its `allOf` branches hoisted into it.
+pub(crate) fn compile<'a>(
+    ctx: &Context,
+    schema: &'a Map<String, Value>,
+) -> Result<SchemaNode, ValidationError<'a>> {
+    let mut entries = Vec::with_capacity(schema.len());
+    flatten(ctx, ctx.location(), schema, &mut entries);
+    if ctx.config().optimization_level() >= OptimizationLevel::Merge {
+        entries = merge(ctx, entries);
+    }
+    let mut validators = Vec::with_capacity(entries.len());
+    let mut annotations = AHashMap::new();
+    for entry in entries {
+        match entry {
+            Entry::Keyword(source) => {
+                if let Some(compiled) = source.compile(ctx)? {
+                    validators.push(compiled);
+                } else if source.location == *ctx.location()
+                    && !ctx.config().is_keyword_disabled(source.keyword)
+                    && !ctx.is_known_keyword(source.keyword)
+                {
+                    // Treat all non-validation keywords as annotations
+                    annotations.insert(source.keyword.to_string(), source.value.clone());
+                }
+            }
+            Entry::AllOf { location, items } => {
+                let branch = ctx.at_location(location.clone());
+                let validator = all_of::compile_branches(&branch, &items)?;
+                branch.record_keyword("allOf");
+                let keyword = relative_keyword(ctx, &location, BuiltinKeyword::AllOf.into());
+                validators.push((keyword, validator));
+            }
+            Entry::Branches { source, items } => {
+                let branch = ctx.at_location(source.location.clone());
+                let (keyword, validator) = if source.keyword == "anyOf" {
+                    (
+                        BuiltinKeyword::AnyOf,
+                        AnyOfValidator::compile(&branch, items.into_iter())?,
+                    )
+                } else {
+                    (
+                        BuiltinKeyword::OneOf,
+                        OneOfValidator::compile(&branch, items.into_iter())?,
+                    )
+                };
+                branch.record_keyword(source.keyword);
+                validators.push((
+                    relative_keyword(ctx, &source.location, keyword.into()),
+                    validator,
+                ));
+            }
+            Entry::Merged(sources) => {
+                let mut compiled = Vec::with_capacity(sources.len());
+                for source in &sources {
+                    if let Some((keyword, validator)) = source.compile(ctx)? {
+                        compiled.push((keyword, source.location.join(source.keyword), validator));
+                    }
+                }
+                match merged_check(ctx, &sources) {
+                    Some(check) if compiled.len() == sources.len() => {
+                        let keyword = compiled[0].0.clone();
+                        let merged = MergedValidator {
+                            check,
+                            sources: compiled
+                                .into_iter()
+                                .map(|(_, location, validator)| (location, validator))
+                                .collect(),
+                            location: ctx.location().clone(),
+                            absolute: ctx.absolute_location(),
+                        };
+                        validators.push((keyword, Box::new(merged)));
+                    }
+                    _ => validators.extend(
+                        compiled
+                            .into_iter()
+                            .map(|(keyword, _, validator)| (keyword, validator)),
+                    ),
+                }
+            }
+        }
+    }
+    let annotations = if annotations.is_empty() {
+        None
+    } else {
+        Some(annotations)
+    };
+    Ok(SchemaNode::from_keywords(ctx, validators, annotations))
+}
+
+fn parse_types(value: &Value) -> Option<PrimitiveTypesBitMap> {
+    let mut types = PrimitiveTypesBitMap::new();
+    let mut add = |value: &Value| -> Option<()> {
+        let primitive_type = PrimitiveType::try_from(value.as_str()?).ok()?;
+        types |= primitive_type;
+        // Integers are numbers too
+        if primitive_type == PrimitiveType::Number {
+            types |= PrimitiveType::Integer;
+        }
+        Some(())
+    };
+    match value {
+        Value::String(_) => add(value)?,
+        Value::Array(items) => {
+            for item in items {
+                add(item)?;
+            }
+        }
+        _ => return None,
+    }
+    Some(types)
+}
+
+fn compare(left: &Number, right: &Number) -> Ordering {
+    if let (Some(left), Some(right)) = (left.as_i64(), right.as_i64()) {
+        left.cmp(&right)
+    } else if let (Some(left), Some(right)) = (left.as_u64(), right.as_u64()) {
+        left.cmp(&right)
+    } else {
+        let (left, right) = (helpers::to_f64(left), helpers::to_f64(right));
+        left.partial_cmp(&right).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// A single validator that accepts exactly the instances valid under all `sources`.
+fn merged_check(ctx: &Context, sources: &[Source<'_>]) -> Option<BoxedValidator> {
+    // Errors are reported by the original keywords, the location is never used
+    let location = ctx.location().clone();
+    match sources.first()?.keyword {
+        "type" => {
+            let mut types = ALL_TYPES
+                .iter()
+                .fold(PrimitiveTypesBitMap::new(), |types, ty| types.add_type(*ty));
+            for source in sources {
+                let other = parse_types(source.value)?;
+                types = ALL_TYPES
+                    .iter()
+                    .filter(|ty| types.contains_type(**ty) && other.contains_type(**ty))
+                    .fold(PrimitiveTypesBitMap::new(), |types, ty| types.add_type(*ty));
+            }
+            // Draft 4 does not count numbers with a zero fractional part as integers
+            if ctx.draft() == Draft::Draft4 {
+                Some(type_draft_4::MultipleTypesValidator::boxed(types, location))
+            } else {
+                Some(MultipleTypesValidator::boxed(types, location))
+            }
+        }
+        "required" => {
+            let mut required: Vec<String> = Vec::new();
+            for source in sources {
+                for item in source.value.as_array()? {
+                    let name = item.as_str()?;
+                    if !required.iter().any(|existing| existing == name) {
+                        required.push(name.to_string());
+                    }
+                }
+            }
+            Some(RequiredValidator::boxed(required, location))
+        }
+        keyword => {
+            // Only the tightest bound matters
+            let lower = keyword.starts_with("min") || keyword == "exclusiveMinimum";
+            let mut tightest: Option<(&Source<'_>, &Number)> = None;
+            for source in sources {
+                let Value::Number(limit) = source.value else {
+                    return None;
+                };
+                let is_tighter = tightest.map_or(true, |(_, current)| {
+                    let ordering = compare(limit, current);
+                    if lower {
+                        ordering == Ordering::Greater
+                    } else {
+                        ordering == Ordering::Less
+                    }
+                });
+                if is_tighter {
+                    tightest = Some((source, limit));
+                }
+            }
+            let (source, _) = tightest?;
+            let branch = ctx.at_location(source.location.clone());
+            let (_, compile) = keywords::get_for_draft(&branch, source.keyword)?;
+            compile(&branch, source.schema, source.value)?.ok()
+        }
+    }
+}
+
+/// Occurrences of the same keyword in a schema and its hoisted `allOf` branches.
+///
+/// Instances are checked once by `check`. Only invalid instances go through the original
+/// keywords, so errors point to where each of them is written.
+struct MergedValidator {
+    check: BoxedValidator,
+    sources: Vec<(Location, BoxedValidator)>,
+    location: Location,
+    absolute: Option<AbsoluteLocation>,
+}
+
+impl MergedValidator {
+    /// Record that the keyword at `keyword_location` produced `error`.
+    fn attribute(&self, error: &mut ValidationError<'_>, keyword_location: &Location) {
+        let parent = keyword_location.as_str().rfind('/').unwrap_or_default();
+        error.attribute(self.absolute.as_ref(), &self.location, parent);
+    }
+}
+
+impl Validate for MergedValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.check.is_valid(instance)
+    }
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        self.check.is_valid_value(instance)
+    }
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if !self.check.is_valid(instance) {
+            for (keyword_location, validator) in &self.sources {
+                validator
+                    .validate(instance, location)
+                    .map_err(|mut error| {
+                        self.attribute(&mut error, keyword_location);
+                        error
+                    })?;
+            }
+        }
+        Ok(())
+    }
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        if self.check.is_valid(instance) {
+            no_error()
+        } else {
+            let errors: Vec<_> = self
+                .sources
+                .iter()
+                .flat_map(|(keyword_location, validator)| {
+                    validator
+                        .iter_errors(instance, location)
+                        .map(move |mut error| {
+                            self.attribute(&mut error, keyword_location);
+                            error
+                        })
+                })
+                .collect();
+            Box::new(errors.into_iter())
+        }
+    }
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        if !self.check.is_valid(instance) {
+            for (keyword_location, validator) in &self.sources {
+                let start = errors.len();
+                validator.collect_errors(instance, location, errors, scratch);
+                for error in &mut errors[start..] {
+                    self.attribute(error, keyword_location);
+                }
+            }
+        }
+    }
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        if self.check.is_valid(instance) {
+            return PartialApplication::valid_empty();
+        }
+        let mut child_results = VecDeque::new();
+        for (keyword_location, validator) in &self.sources {
+            if let PartialApplication::Invalid {
+                errors,
+                child_results: children,
+            } = validator.apply(instance, location)
+            {
+                child_results.extend(children);
+                for error in errors {
+                    child_results.push_back(OutputUnit::error_at(
+                        self.absolute.as_ref(),
+                        keyword_location.clone(),
+                        location.into(),
+                        error,
+                    ));
+                }
+            }
+        }
+        PartialApplication::Invalid {
+            errors: Vec::new(),
+            child_results,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{Draft, LintCode, OptimizationLevel};
+
+    fn validators(schema: &Value, draft: Draft) -> [crate::Validator; 3] {
+        [
+            OptimizationLevel::None,
+            OptimizationLevel::Flatten,
+            OptimizationLevel::Merge,
+        ]
+        .map(|level| {
+            crate::options()
+                .with_draft(draft)
+                .with_optimization_level(level)
+                .build(schema)
+                .expect("Valid schema")
+        })
+    }
+
+    fn errors(validator: &crate::Validator, instance: &Value) -> Vec<(String, String)> {
+        validator
+            .iter_errors(instance)
+            .map(|error| (error.schema_path.to_string(), error.to_string()))
+            .collect()
+    }
+
+    fn basic(validator: &crate::Validator, instance: &Value) -> Value {
+        serde_json::to_value(validator.apply(instance).basic()).expect("Serializable")
+    }
+
+    #[test_case(&json!({"allOf": [{"type": "integer"}, {"minimum": 0}, {"maximum": 10}]}), &[json!(5), json!(-1), json!(11), json!(1.5), json!("a")]; "bounds in branches")]
+    #[test_case(&json!({"minimum": 2, "allOf": [{"minimum": 5}, {"allOf": [{"minimum": 3}, true]}]}), &[json!(1), json!(4), json!(5)]; "tightest minimum")]
+    #[test_case(&json!({"maxLength": 5, "allOf": [{"maxLength": 3}, {"minLength": 1}]}), &[json!(""), json!("abcd"), json!("abc")]; "lengths")]
+    #[test_case(&json!({"exclusiveMaximum": 10, "allOf": [{"exclusiveMaximum": 1e20}, {"exclusiveMaximum": 18446744073709551615_u64}]}), &[json!(9), json!(10)]; "mixed number types")]
+    #[test_case(&json!({"type": ["number", "string"], "allOf": [{"type": ["integer", "null"]}, {"type": "number"}]}), &[json!(1), json!(1.5), json!("a"), json!(null), json!(2.0)]; "type intersection")]
+    #[test_case(&json!({"type": "string", "allOf": [{"type": "integer"}]}), &[json!(1), json!("a")]; "empty type intersection")]
+    #[test_case(&json!({"required": ["a"], "allOf": [{"required": ["b", "a"]}, {"required": ["c"], "properties": {"c": {"type": "string"}}}]}), &[json!({"a": 1, "b": 2, "c": "x"}), json!({"c": 1}), json!({}), json!(1)]; "required union")]
+    #[test_case(&json!({"allOf": [{"$id": "https://example.com/a", "type": "string"}, {"maxLength": 2}]}), &[json!("abc"), json!(1)]; "branch with id is kept")]
+    #[test_case(&json!({"allOf": [{"x-unknown": 1, "type": "string"}, {"type": "string", "minLength": 2}, false]}), &[json!("a"), json!(1)]; "partially hoisted")]
+    #[test_case(&json!({"properties": {"a": {"allOf": [{"$ref": "#/$defs/positive"}, {"maximum": 3}]}}, "$defs": {"positive": {"allOf": [{"exclusiveMinimum": 0}]}}}), &[json!({"a": 2}), json!({"a": 0}), json!({"a": 4})]; "references")]
+    #[test_case(&json!({"allOf": [{"unevaluatedProperties": false}, {"properties": {"a": true}}]}), &[json!({}), json!({"a": 1})]; "unevaluated properties")]
+    fn same_results(schema: &Value, instances: &[Value]) {
+        assert_same_results(schema, instances, Draft::Draft202012);
+    }
+
+    #[test_case(&json!({"type": "number", "allOf": [{"type": "integer"}]}), &[json!(1), json!(1.0), json!(1.5), json!("a")]; "integer type")]
+    #[test_case(&json!({"type": ["integer", "string"], "allOf": [{"type": ["number", "null"]}]}), &[json!(1), json!(2.0), json!(null)]; "type intersection")]
+    #[test_case(&json!({"allOf": [{"maximum": 5, "exclusiveMaximum": true}, {"maximum": 6}]}), &[json!(4), json!(5), json!(6)]; "boolean exclusive maximum")]
+    fn same_results_draft4(schema: &Value, instances: &[Value]) {
+        assert_same_results(schema, instances, Draft::Draft4);
+    }
+
+    fn assert_same_results(schema: &Value, instances: &[Value], draft: Draft) {
+        let [plain, flattened, merged] = validators(schema, draft);
+        for instance in instances {
+            for optimized in [&flattened, &merged] {
+                assert_eq!(plain.is_valid(instance), optimized.is_valid(instance));
+                assert_eq!(
+                    plain.validate(instance).is_ok(),
+                    optimized.validate(instance).is_ok()
+                );
+                assert_eq!(
+                    plain.apply(instance).flag(),
+                    optimized.apply(instance).flag()
+                );
+            }
+            let expected = errors(&plain, instance);
+            assert_eq!(errors(&flattened, instance), expected);
+            // Merged keywords report errors together
+            let mut merged = errors(&merged, instance);
+            merged.sort();
+            let mut expected = expected;
+            expected.sort();
+            assert_eq!(merged, expected);
+        }
+    }
+
+    #[test]
+    fn basic_output_locations() {
+        let schema = json!({"minimum": 2, "allOf": [{"type": "integer", "minimum": 5}, {"allOf": [{"maximum": 3}]}]});
+        let [plain, flattened, merged] = validators(&schema, Draft::Draft202012);
+        let locations = |validator: &crate::Validator| {
+            let output = basic(validator, &json!(4));
+            let mut locations: Vec<_> = output["errors"]
+                .as_array()
+                .expect("Invalid")
+                .iter()
+                .map(|unit| {
+                    unit["keywordLocation"]
+                        .as_str()
+                        .expect("String")
+                        .to_string()
+                })
+                .collect();
+            locations.sort();
+            locations
+        };
+        let expected = ["/allOf/0/minimum", "/allOf/1/allOf/0/maximum"];
+        assert_eq!(locations(&plain), expected);
+        assert_eq!(locations(&flattened), expected);
+        assert_eq!(locations(&merged), expected);
+    }
+
+    #[test]
+    fn keyword_locations() {
+        let schema =
+            json!({"allOf": [{"minimum": 0}, {"$id": "https://example.com", "maximum": 1}]});
+        let [plain, flattened, _] = validators(&schema, Draft::Draft202012);
+        assert_eq!(
+            plain.keyword_locations().collect::<Vec<_>>(),
+            flattened.keyword_locations().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn draft4_exclusive_bounds() {
+        let schema = json!({
+            "maximum": 10,
+            "allOf": [{"maximum": 5, "exclusiveMaximum": true}, {"maximum": 8}]
+        });
+        let [plain, flattened, merged] = validators(&schema, Draft::Draft4);
+        for instance in [json!(4), json!(5), json!(9)] {
+            assert_eq!(errors(&plain, &instance), errors(&flattened, &instance));
+            assert_eq!(errors(&plain, &instance), errors(&merged, &instance));
+        }
+    }
+
+    #[test]
+    fn draft7_ref_siblings() {
+        // Keywords next to `$ref` are ignored in Draft 7, the branch must stay as is
+        let schema = json!({
+            "allOf": [{"$ref": "#/definitions/a", "minimum": 100}],
+            "definitions": {"a": {"type": "integer"}}
+        });
+        let [plain, flattened, merged] = validators(&schema, Draft::Draft7);
+        for instance in [json!(1), json!("a")] {
+            assert_eq!(errors(&plain, &instance), errors(&flattened, &instance));
+            assert_eq!(errors(&plain, &instance), errors(&merged, &instance));
+        }
+    }
+
+    fn pruned(schema: &Value) -> crate::Validator {
+        crate::options()
+            .with_optimization_level(OptimizationLevel::Prune)
+            .build(schema)
+            .expect("Valid schema")
+    }
+
+    fn warnings(validator: &crate::Validator) -> Vec<(LintCode, &str)> {
+        validator
+            .warnings()
+            .iter()
+            .map(|warning| (warning.code, warning.location.as_str()))
+            .collect()
+    }
+
+    #[test_case(&json!({"anyOf": [{"type": "string"}, false, {"minimum": 3}]}), &[json!("a"), json!(4), json!(1)], &[(LintCode::UnreachableBranch, "/anyOf/1")]; "false in any of")]
+    #[test_case(&json!({"oneOf": [false, {"type": "integer"}, {"minimum": 3}]}), &[json!(1), json!(4), json!(2.5), json!("a")], &[(LintCode::UnreachableBranch, "/oneOf/0")]; "false in one of")]
+    #[test_case(&json!({"anyOf": [false, false]}), &[json!(1)], &[]; "only false branches")]
+    #[test_case(&json!({"type": "array", "properties": {"a": {"type": "string"}}, "required": ["a"], "minItems": 1}), &[json!([]), json!([1]), json!({"a": 1})], &[(LintCode::IgnoredKeyword, "/properties"), (LintCode::IgnoredKeyword, "/required")]; "object keywords")]
+    #[test_case(&json!({"type": ["integer", "null"], "minimum": 1, "maxLength": 1}), &[json!(0), json!(null), json!("abc")], &[(LintCode::IgnoredKeyword, "/maxLength")]; "type list")]
+    #[test_case(&json!({"minItems": 0, "minLength": 0.0, "required": [], "if": {"minimum": 1}, "maxItems": 1}), &[json!([]), json!([1, 2]), json!("")], &[(LintCode::NoOpKeyword, "/if"), (LintCode::NoOpKeyword, "/minItems"), (LintCode::NoOpKeyword, "/minLength"), (LintCode::NoOpKeyword, "/required")]; "no-op keywords")]
+    #[test_case(&json!({"allOf": [{"type": "string", "minItems": 2}], "properties": {"a": {"anyOf": [false, {"const": 1}]}}}), &[json!("a"), json!({"a": 1}), json!({"a": 2})], &[(LintCode::IgnoredKeyword, "/allOf/0/minItems"), (LintCode::UnreachableBranch, "/properties/a/anyOf/0")]; "nested")]
+    fn pruned_results(schema: &Value, instances: &[Value], expected: &[(LintCode, &str)]) {
+        let [plain, _, _] = validators(schema, Draft::Draft202012);
+        let pruned = pruned(schema);
+        for instance in instances {
+            assert_eq!(plain.is_valid(instance), pruned.is_valid(instance));
+            assert_eq!(
+                plain.validate(instance).is_ok(),
+                pruned.validate(instance).is_ok()
+            );
+            assert_eq!(plain.apply(instance).flag(), pruned.apply(instance).flag());
+        }
+        assert_eq!(warnings(&pruned), expected);
+        assert!(plain.warnings().is_empty());
+    }
+
+    #[test]
+    fn pruned_branch_locations() {
+        let schema = json!({"anyOf": [false, {"type": "string"}, {"minimum": 3}]});
+        let output = basic(&pruned(&schema), &json!(1));
+        let locations: Vec<_> = output["errors"]
+            .as_array()
+            .expect("Invalid")
+            .iter()
+            .map(|unit| unit["keywordLocation"].as_str().expect("String"))
+            .collect();
+        assert_eq!(locations, ["/anyOf/1/type", "/anyOf/2/minimum"]);
+    }
+
+    #[test]
+    fn custom_type_keeps_keywords() {
+        // A replaced `type` keyword may accept anything, so keywords next to it are kept
+        let schema = json!({"type": "string", "minimum": 3});
+        let validator = crate::options()
+            .with_optimization_level(OptimizationLevel::Prune)
+            .without_keyword("type")
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(validator.warnings().is_empty());
+        assert!(!validator.is_valid(&json!(1)));
+    }
+
+    #[test]
+    fn denied_removed_keywords() {
+        let schema = json!({"type": "string", "minItems": 0});
+        let error = crate::options()
+            .with_optimization_level(OptimizationLevel::Prune)
+            .with_denied_lints([LintCode::NoOpKeyword])
+            .build(&schema)
+            .expect_err("Denied lint");
+        assert_eq!(error.instance_path.as_str(), "/minItems");
+    }
+}
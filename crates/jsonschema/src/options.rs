@@ -5,20 +5,118 @@ use crate::{
         DEFAULT_CONTENT_ENCODING_CHECKS_AND_CONVERTERS,
     },
     content_media_type::{ContentMediaTypeCheckType, DEFAULT_CONTENT_MEDIA_TYPE_CHECKS},
-    keywords::{custom::KeywordFactory, format::Format},
+    keywords::{
+        custom::{Contextual, ForDrafts, KeywordContext, KeywordFactory},
+        format::Format,
+    },
     paths::Location,
     retriever::DefaultRetriever,
-    Keyword, ValidationError, Validator,
+    CompiledRegistry, Keyword, LintCode, ValidationError, Validator,
 };
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use referencing::{uri, Draft, Resource, Retrieve};
 use serde_json::Value;
 use std::{fmt, sync::Arc};
 
+#[cfg(feature = "instrumentation")]
+use crate::instrumentation::KeywordHook;
+
+const DEFAULT_MAX_INSTANCE_DEPTH: usize = 128;
+const DEFAULT_MAX_SCHEMA_DEPTH: usize = 128;
+const DEFAULT_MAX_COMPILED_NODES: usize = 1_000_000;
+
+/// How much a schema is rewritten to validate faster, see
+/// [`ValidationOptions::with_optimization_level`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum OptimizationLevel {
+    /// Compile the schema as written.
+    #[default]
+    None,
+    /// Move keywords of `allOf` branches into the schema containing them.
+    Flatten,
+    /// Like `Flatten`, and check keywords that occur multiple times, like `minimum`, at once.
+    Merge,
+    /// Like `Merge`, and remove `false` branches of `anyOf` and `oneOf`, keywords that `type`
+    /// rules out and keywords that never fail, like `"minItems": 0`. What was removed is
+    /// available via [`Validator::warnings`](crate::Validator::warnings).
+    Prune,
+}
+
+/// The order in which keywords of a schema are checked by `is_valid`, see
+/// [`ValidationOptions::with_keyword_order`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeywordOrder {
+    /// Check keywords in the same order as `validate` and `iter_errors` do.
+    Preserve,
+    /// Check cheap keywords, like `type` or `required`, before `pattern` and applicators.
+    #[default]
+    Optimized,
+}
+
+/// How strictly `format: "email"` and `format: "idn-email"` are checked, see
+/// [`ValidationOptions::with_email_strictness`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EmailStrictness {
+    /// Any address the specifications allow, including quoted local parts like
+    /// `"john doe"@example.com`, address literals like `john@[127.0.0.1]` and domains without
+    /// dots like `john@localhost`, as the JSON Schema test suite expects.
+    #[default]
+    Syntax,
+    /// Like `Syntax`, but only addresses that look deliverable: no quoted local parts, no address
+    /// literals, a dot in the domain, and at most 254 characters in total as RFC 5321 requires.
+    Deliverable,
+}
+
+/// A dialect of JSON Schema defined by another specification, see
+/// [`ValidationOptions::with_dialect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// Schema objects of OpenAPI 3.0, a subset of Draft 4 extended with `nullable`.
+    OpenApi30,
+}
+
+/// How to choose the draft of a schema whose `$schema` is not the meta-schema of a known draft,
+/// see [`ValidationOptions::with_meta_schema_policy`].
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub enum MetaSchemaPolicy {
+    /// Retrieve the meta-schema and use the draft it declares in its own `$schema`. Fail with
+    /// [`ReferencingError::UnknownSpecification`](crate::ReferencingError::UnknownSpecification)
+    /// naming the URI if it can not be retrieved or declares no known draft either.
+    #[default]
+    Error,
+    /// Use the given draft without retrieving the meta-schema.
+    FallbackTo(Draft),
+    /// Ask the callback for the draft of the given meta-schema URI. `None` fails like
+    /// [`MetaSchemaPolicy::Error`], without retrieving the meta-schema.
+    #[allow(clippy::type_complexity)]
+    Custom(Arc<dyn Fn(&str) -> Option<Draft> + Send + Sync>),
+}
+
+impl fmt::Debug for MetaSchemaPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetaSchemaPolicy::Error => f.write_str("Error"),
+            MetaSchemaPolicy::FallbackTo(draft) => {
+                f.debug_tuple("FallbackTo").field(draft).finish()
+            }
+            MetaSchemaPolicy::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
 /// Configuration options for JSON Schema validation.
 #[derive(Clone)]
 pub struct ValidationOptions {
     pub(crate) draft: Option<Draft>,
+    dialect: Option<Dialect>,
+    /// Whether a `$schema` that contradicts the forced draft is an error.
+    reject_mismatched_meta_schema: bool,
+    meta_schema_policy: MetaSchemaPolicy,
     content_media_type_checks: AHashMap<&'static str, Option<ContentMediaTypeCheckType>>,
     content_encoding_checks_and_converters:
         AHashMap<&'static str, Option<(ContentEncodingCheckType, ContentEncodingConverterType)>>,
@@ -27,16 +125,36 @@ pub struct ValidationOptions {
     /// Additional resources that should be addressable during validation.
     pub(crate) resources: AHashMap<String, Resource>,
     formats: AHashMap<String, Arc<dyn Format>>,
-    validate_formats: Option<bool>,
+    pub(crate) validate_formats: Option<bool>,
     pub(crate) validate_schema: bool,
     ignore_unknown_formats: bool,
+    email_strictness: EmailStrictness,
     keywords: AHashMap<String, Arc<dyn KeywordFactory>>,
+    disabled_keywords: AHashSet<String>,
+    denied_lints: AHashSet<LintCode>,
+    lazy_references: bool,
+    validate_defaults: bool,
+    validate_examples: bool,
+    reject_duplicate_keys: bool,
+    verbose_errors: bool,
+    optimization_level: OptimizationLevel,
+    keyword_order: KeywordOrder,
+    max_instance_depth: usize,
+    max_schema_depth: usize,
+    max_compiled_nodes: usize,
+    content_size_limit: Option<usize>,
+    capture_content: bool,
+    #[cfg(feature = "instrumentation")]
+    instrumentation: Option<Arc<dyn KeywordHook>>,
 }
 
 impl Default for ValidationOptions {
     fn default() -> Self {
         ValidationOptions {
             draft: None,
+            dialect: None,
+            meta_schema_policy: MetaSchemaPolicy::Error,
+            reject_mismatched_meta_schema: false,
             content_media_type_checks: AHashMap::default(),
             content_encoding_checks_and_converters: AHashMap::default(),
             retriever: Arc::new(DefaultRetriever),
@@ -45,35 +163,83 @@ impl Default for ValidationOptions {
             validate_formats: None,
             validate_schema: true,
             ignore_unknown_formats: true,
+            email_strictness: EmailStrictness::Syntax,
             keywords: AHashMap::default(),
+            disabled_keywords: AHashSet::default(),
+            denied_lints: AHashSet::default(),
+            lazy_references: false,
+            validate_defaults: false,
+            validate_examples: false,
+            reject_duplicate_keys: false,
+            verbose_errors: false,
+            optimization_level: OptimizationLevel::None,
+            keyword_order: KeywordOrder::Optimized,
+            max_instance_depth: DEFAULT_MAX_INSTANCE_DEPTH,
+            max_schema_depth: DEFAULT_MAX_SCHEMA_DEPTH,
+            max_compiled_nodes: DEFAULT_MAX_COMPILED_NODES,
+            content_size_limit: None,
+            capture_content: false,
+            #[cfg(feature = "instrumentation")]
+            instrumentation: None,
         }
     }
 }
 
 impl ValidationOptions {
-    /// Return the draft version, or the default if not set.
-    pub(crate) fn draft(&self) -> Draft {
-        self.draft.unwrap_or_default()
+    /// Options with `draft` forced, as used by the per-draft modules like [`crate::draft7`].
+    pub(crate) fn for_draft(draft: Draft) -> Self {
+        let mut options = Self::default();
+        options.with_draft(draft);
+        options.reject_mismatched_meta_schema = true;
+        options
     }
     pub(crate) fn draft_for(&self, contents: &Value) -> Result<Draft, ValidationError<'static>> {
         // Preference:
+        //  - Implied by the dialect
         //  - Explicitly set
         //  - Autodetected
         //  - Default
+        if self.dialect.is_some() {
+            return Ok(Draft::Draft4);
+        }
         if let Some(draft) = self.draft {
+            if self.reject_mismatched_meta_schema {
+                if let Some(declared) = Draft::from_schema(contents) {
+                    if declared != draft {
+                        return Err(ValidationError::custom(
+                            Location::new(),
+                            Location::new().join("$schema"),
+                            &contents["$schema"],
+                            format!("Schema declares {declared:?} via `$schema`, but {draft:?} is required"),
+                        )
+                        .into_owned());
+                    }
+                }
+            }
             Ok(draft)
         } else {
             let default = Draft::default();
             match default.detect(contents) {
                 Ok(draft) => Ok(draft),
                 Err(referencing::Error::UnknownSpecification { specification }) => {
-                    // Try to retrieve the specification and detect its draft
-                    if let Ok(Ok(retrieved)) = uri::from_str(&specification)
-                        .map(|uri| self.retriever.retrieve(&uri.borrow()))
-                    {
-                        Ok(default.detect(&retrieved)?)
-                    } else {
-                        Err(referencing::Error::UnknownSpecification { specification }.into())
+                    match &self.meta_schema_policy {
+                        MetaSchemaPolicy::Error => {
+                            // Try to retrieve the specification and detect its draft
+                            if let Ok(Ok(retrieved)) = uri::from_str(&specification)
+                                .map(|uri| self.retriever.retrieve(&uri.borrow()))
+                            {
+                                Ok(default.detect(&retrieved)?)
+                            } else {
+                                Err(referencing::Error::UnknownSpecification { specification }
+                                    .into())
+                            }
+                        }
+                        MetaSchemaPolicy::FallbackTo(draft) => Ok(*draft),
+                        MetaSchemaPolicy::Custom(callback) => {
+                            callback(&specification).ok_or_else(|| {
+                                referencing::Error::UnknownSpecification { specification }.into()
+                            })
+                        }
                     }
                 }
                 Err(error) => Err(error.into()),
@@ -98,6 +264,36 @@ impl ValidationOptions {
     pub fn build(&self, schema: &Value) -> Result<Validator, ValidationError<'static>> {
         compiler::build_validator(self.clone(), schema)
     }
+    /// Build validators for a set of `(URI, schema)` documents that reference each other.
+    ///
+    /// References are resolved against the whole set and subschemas are shared between the
+    /// returned validators. Use [`CompiledRegistry::validator_for`] to get the validator rooted
+    /// at a document.
+    ///
+    /// # Errors
+    ///
+    /// Fails if any schema is invalid or if two documents define the same `$id`, in which case
+    /// the error names both documents.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let result = jsonschema::options().compile_all([
+    ///     ("https://example.com/a", json!({"$id": "https://example.com/shared"})),
+    ///     ("https://example.com/b", json!({"$id": "https://example.com/shared"})),
+    /// ]);
+    /// assert!(result.is_err());
+    /// ```
+    pub fn compile_all(
+        &self,
+        documents: impl IntoIterator<Item = (impl Into<String>, Value)>,
+    ) -> Result<CompiledRegistry, ValidationError<'static>> {
+        let documents = documents
+            .into_iter()
+            .map(|(uri, schema)| (uri.into(), schema))
+            .collect();
+        compiler::build_registry(self.clone(), documents)
+    }
     /// Sets the JSON Schema draft version.
     ///
     /// ```rust
@@ -112,6 +308,83 @@ impl ValidationOptions {
         self
     }
 
+    /// Compile schemas as the given dialect. Dialects are based on Draft 4, which is used
+    /// regardless of [`ValidationOptions::with_draft`] and `$schema`.
+    ///
+    /// With [`Dialect::OpenApi30`]:
+    ///
+    /// - `nullable: true` allows `null` in addition to the declared `type`;
+    /// - `type` must be a single type other than `"null"`, and `items` a single schema;
+    /// - `patternProperties`, `additionalItems` and `dependencies` are rejected;
+    /// - `exclusiveMinimum` and `exclusiveMaximum` are booleans, as in Draft 4;
+    /// - `oneOf` and `anyOf` with a `discriminator` validate objects only against the subschema
+    ///   selected by the discriminator property, through `mapping` or the name at the end of the
+    ///   subschema's `$ref`. Objects without the property, or with a value that selects no
+    ///   subschema, are validated against all subschemas, and the latter fail with
+    ///   [`ValidationErrorKind::UnknownDiscriminator`](crate::error::ValidationErrorKind::UnknownDiscriminator).
+    ///
+    /// Schemas that do not follow these rules fail to compile. [`OptimizationLevel`] is
+    /// ignored for this dialect.
+    ///
+    /// ```rust
+    /// use jsonschema::Dialect;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"type": "string", "nullable": true});
+    /// let validator = jsonschema::options()
+    ///     .with_dialect(Dialect::OpenApi30)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!(null)));
+    /// assert!(!validator.is_valid(&json!(1)));
+    ///
+    /// let schema = json!({"type": ["string", "null"]});
+    /// assert!(jsonschema::options()
+    ///     .with_dialect(Dialect::OpenApi30)
+    ///     .build(&schema)
+    ///     .is_err());
+    /// ```
+    pub fn with_dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = Some(dialect);
+        self
+    }
+    /// Choose how to handle a `$schema` that is not the meta-schema of a known draft, like a
+    /// custom dialect. Meta-schema URIs of known drafts are recognized with or without a
+    /// trailing `#`, and over both `http` and `https`. Unless the draft is set with
+    /// [`ValidationOptions::with_draft`], the default [`MetaSchemaPolicy::Error`] retrieves the
+    /// meta-schema to find its draft and fails otherwise.
+    ///
+    /// ```rust
+    /// use jsonschema::{Draft, MetaSchemaPolicy};
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "$schema": "https://example.com/our-dialect",
+    ///     "prefixItems": [{"type": "string"}]
+    /// });
+    /// let validator = jsonschema::options()
+    ///     .with_meta_schema_policy(MetaSchemaPolicy::FallbackTo(Draft::Draft202012))
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// assert_eq!(validator.draft(), Draft::Draft202012);
+    /// assert!(!validator.is_valid(&json!([1])));
+    ///
+    /// let error = jsonschema::options()
+    ///     .with_meta_schema_policy(MetaSchemaPolicy::Custom(std::sync::Arc::new(|uri| {
+    ///         uri.starts_with("https://example.com/legacy/").then_some(Draft::Draft7)
+    ///     })))
+    ///     .build(&schema)
+    ///     .expect_err("Unknown dialect");
+    /// assert!(error.to_string().contains("https://example.com/our-dialect"));
+    /// ```
+    pub fn with_meta_schema_policy(&mut self, policy: MetaSchemaPolicy) -> &mut Self {
+        self.meta_schema_policy = policy;
+        self
+    }
+    pub(crate) const fn dialect(&self) -> Option<Dialect> {
+        self.dialect
+    }
+
     pub(crate) fn get_content_media_type_check(
         &self,
         media_type: &str,
@@ -148,6 +421,65 @@ impl ValidationOptions {
         self.retriever = Arc::new(retriever);
         self
     }
+    /// Limit the size of strings checked by `contentMediaType` and `contentEncoding`, in bytes.
+    ///
+    /// Longer strings fail with [`ValidationErrorKind::ContentSizeLimit`] without being decoded
+    /// or parsed. Content decoded by `contentEncoding` is subject to the same limit before its
+    /// media type is checked. There is no limit by default.
+    ///
+    /// ```rust
+    /// use jsonschema::Draft;
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_draft(Draft::Draft7)
+    ///     .with_content_size_limit(16)
+    ///     .build(&json!({"contentMediaType": "application/json"}))
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!("[1, 2, 3]")));
+    /// assert!(!validator.is_valid(&json!("[1, 2, 3, 4, 5, 6, 7]")));
+    /// ```
+    ///
+    /// [`ValidationErrorKind::ContentSizeLimit`]: crate::error::ValidationErrorKind::ContentSizeLimit
+    pub fn with_content_size_limit(&mut self, bytes: usize) -> &mut Self {
+        self.content_size_limit = Some(bytes);
+        self
+    }
+    pub(crate) const fn content_size_limit(&self) -> Option<usize> {
+        self.content_size_limit
+    }
+    /// Capture the content decoded by `contentEncoding` and parsed by `contentMediaType` in the
+    /// output of [`Validator::apply`], so it does not have to be decoded again after validation.
+    ///
+    /// Content is available via [`BasicOutput::content`] and [`OutputUnit::content`]. Content of
+    /// `application/json` is parsed into a [`serde_json::Value`], which the check alone does not
+    /// need to do, so capturing is disabled by default.
+    ///
+    /// ```rust
+    /// use jsonschema::{output::Content, Draft};
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_draft(Draft::Draft7)
+    ///     .with_content_capture(true)
+    ///     .build(&json!({"contentEncoding": "base64"}))
+    ///     .expect("Invalid schema");
+    /// let instance = json!("aGVsbG8=");
+    /// let output = validator.apply(&instance).basic();
+    /// let content: Vec<_> = output.content().into_values().collect();
+    /// assert_eq!(content, [&Content::Decoded("hello".to_string())]);
+    /// ```
+    ///
+    /// [`Validator::apply`]: crate::Validator::apply
+    /// [`BasicOutput::content`]: crate::BasicOutput::content
+    /// [`OutputUnit::content`]: crate::output::OutputUnit::content
+    pub fn with_content_capture(&mut self, capture: bool) -> &mut Self {
+        self.capture_content = capture;
+        self
+    }
+    pub(crate) const fn captures_content(&self) -> bool {
+        self.capture_content
+    }
     /// Remove support for a specific content media type validation.
     pub fn without_content_media_type_support(&mut self, media_type: &'static str) -> &mut Self {
         self.content_media_type_checks.insert(media_type, None);
@@ -361,8 +693,10 @@ impl ValidationOptions {
         self.validate_formats = Some(yes);
         self
     }
-    pub(crate) fn validate_formats(&self) -> Option<bool> {
+    /// Whether `format` is asserted for `draft`, unless overridden by [`Self::should_validate_formats`].
+    pub(crate) fn validates_formats_for(&self, draft: Draft) -> bool {
         self.validate_formats
+            .unwrap_or_else(|| matches!(draft, Draft::Draft4 | Draft::Draft6 | Draft::Draft7))
     }
     /// Set whether to ignore unknown formats.
     ///
@@ -373,9 +707,380 @@ impl ValidationOptions {
         self
     }
 
+    /// Set how strictly `format: "email"` and `format: "idn-email"` are checked when formats
+    /// are validated. Defaults to [`EmailStrictness::Syntax`]. Formats registered under these
+    /// names with [`Self::with_format`] are used instead, whatever the strictness.
+    ///
+    /// ```rust
+    /// use jsonschema::EmailStrictness;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"format": "email"});
+    /// let validator = jsonschema::options()
+    ///     .should_validate_formats(true)
+    ///     .with_email_strictness(EmailStrictness::Deliverable)
+    ///     .build(&schema)
+    ///     .expect("Valid schema");
+    /// assert!(validator.is_valid(&json!("john@example.com")));
+    /// assert!(!validator.is_valid(&json!("john@localhost")));
+    /// assert!(!validator.is_valid(&json!(r#""john doe"@example.com"#)));
+    /// ```
+    pub fn with_email_strictness(&mut self, strictness: EmailStrictness) -> &mut Self {
+        self.email_strictness = strictness;
+        self
+    }
+    pub(crate) const fn email_strictness(&self) -> EmailStrictness {
+        self.email_strictness
+    }
+
+    /// Allow schemas whose `$schema` declares a different draft than the one forced by
+    /// per-draft entry points like [`crate::draft7::options`]. The forced draft is used anyway.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"$schema": "http://json-schema.org/draft-07/schema#"});
+    /// assert!(jsonschema::draft4::new(&schema).is_err());
+    /// assert!(jsonschema::draft4::options()
+    ///     .allow_mismatched_meta_schema()
+    ///     .build(&schema)
+    ///     .is_ok());
+    /// ```
+    pub fn allow_mismatched_meta_schema(&mut self) -> &mut Self {
+        self.reject_mismatched_meta_schema = false;
+        self
+    }
+
     pub(crate) const fn are_unknown_formats_ignored(&self) -> bool {
         self.ignore_unknown_formats
     }
+    /// Set whether `$ref` targets are compiled on first use instead of during `build`.
+    ///
+    /// Useful for huge schemas where only a small part is used. Errors in lazily compiled targets
+    /// are reported as validation errors pointing to the target. Use [`Validator::precompile`] to
+    /// compile everything upfront later.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "used": {"$ref": "#/$defs/positive"},
+    ///         "unused": {"$ref": "#/$defs/missing"}
+    ///     },
+    ///     "$defs": {"positive": {"minimum": 0}}
+    /// });
+    /// // Fails eagerly, as `#/$defs/missing` does not exist
+    /// assert!(jsonschema::validator_for(&schema).is_err());
+    /// let validator = jsonschema::options()
+    ///     .with_lazy_references(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!({"used": 1})));
+    /// assert!(validator.validate(&json!({"unused": 1})).is_err());
+    /// ```
+    ///
+    /// [`Validator::precompile`]: crate::Validator::precompile
+    pub fn with_lazy_references(&mut self, yes: bool) -> &mut Self {
+        self.lazy_references = yes;
+        self
+    }
+    pub(crate) const fn are_references_lazy(&self) -> bool {
+        self.lazy_references
+    }
+    /// Fail to build a validator if a `default` value is not valid under the subschema it is
+    /// in, with [`ValidationErrorKind::InvalidDefault`](crate::error::ValidationErrorKind::InvalidDefault)
+    /// located at the value and carrying its validation errors.
+    ///
+    /// Every subschema is checked, including ones that are only reachable through references.
+    /// This is meant for catching schema mistakes in CI, as it makes building a validator slower.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {"port": {"type": "integer", "minimum": 1, "default": 0}}
+    /// });
+    /// let error = jsonschema::options()
+    ///     .with_validate_defaults(true)
+    ///     .build(&schema)
+    ///     .expect_err("Invalid default");
+    /// assert_eq!(error.instance_path.as_str(), "/properties/port/default");
+    /// ```
+    pub fn with_validate_defaults(&mut self, yes: bool) -> &mut Self {
+        self.validate_defaults = yes;
+        self
+    }
+    pub(crate) const fn validates_defaults(&self) -> bool {
+        self.validate_defaults
+    }
+    /// Like [`Self::with_validate_defaults`], but for each value of `examples`.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"type": "string", "examples": ["a", 1]});
+    /// let error = jsonschema::options()
+    ///     .with_validate_examples(true)
+    ///     .build(&schema)
+    ///     .expect_err("Invalid example");
+    /// assert_eq!(error.instance_path.as_str(), "/examples/1");
+    /// ```
+    pub fn with_validate_examples(&mut self, yes: bool) -> &mut Self {
+        self.validate_examples = yes;
+        self
+    }
+    pub(crate) const fn validates_examples(&self) -> bool {
+        self.validate_examples
+    }
+    /// Report keys that appear more than once in the same object in
+    /// [`Validator::validate_str`](crate::Validator::validate_str), with
+    /// [`ValidationErrorKind::DuplicateKey`](crate::error::ValidationErrorKind::DuplicateKey).
+    ///
+    /// Parsed values only keep the last of duplicate keys, so they can't be detected afterwards.
+    /// Duplicate keys are reported together with the other validation errors, regardless of the
+    /// schema.
+    ///
+    /// ```rust
+    /// use jsonschema::ValidateStrError;
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_reject_duplicate_keys(true)
+    ///     .build(&json!({"type": "object"}))
+    ///     .expect("Valid schema");
+    /// let Err(ValidateStrError::Invalid(errors)) = validator.validate_str(r#"{"a": 1, "a": 2}"#)
+    /// else {
+    ///     panic!("Should fail");
+    /// };
+    /// assert_eq!(
+    ///     errors[0].error.to_string(),
+    ///     r#"Duplicate key "a", first at line 1, column 2"#
+    /// );
+    /// ```
+    pub fn with_reject_duplicate_keys(&mut self, yes: bool) -> &mut Self {
+        self.reject_duplicate_keys = yes;
+        self
+    }
+    pub(crate) const fn rejects_duplicate_keys(&self) -> bool {
+        self.reject_duplicate_keys
+    }
+    /// Collect details on why validation failed where that takes extra work. Currently,
+    /// `not` errors report the location of the matching schema, the matching `anyOf` subschema
+    /// and the annotations of the negated schema in
+    /// [`ValidationErrorKind::Not`](crate::error::ValidationErrorKind::Not), and `contains`
+    /// errors report the errors of the item that came closest to matching in
+    /// [`ValidationErrorKind::Contains`](crate::error::ValidationErrorKind::Contains).
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"not": {"anyOf": [{"type": "string"}, {"minimum": 10}]}});
+    /// let validator = jsonschema::options()
+    ///     .with_verbose_errors(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// let instance = json!(42);
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     r#"{"anyOf":[{"type":"string"},{"minimum":10}]} is not allowed for 42 (matches /not/anyOf/1)"#
+    /// );
+    /// ```
+    pub fn with_verbose_errors(&mut self, yes: bool) -> &mut Self {
+        self.verbose_errors = yes;
+        self
+    }
+    pub(crate) const fn are_errors_verbose(&self) -> bool {
+        self.verbose_errors
+    }
+    /// Set how much the schema is rewritten during `build` to validate faster.
+    ///
+    /// Generated schemas often wrap keywords in `allOf` branches, each of which costs an extra
+    /// step during validation. With [`OptimizationLevel::Flatten`], keywords of `allOf` branches
+    /// are moved into the schema containing them, when it does not change what they mean.
+    /// [`OptimizationLevel::Merge`] additionally checks repeated `type`, `required` and bounds
+    /// like `minimum` at once. Errors still point to the keywords as written, though with
+    /// [`OptimizationLevel::Merge`] they may be reported in a different order.
+    /// [`OptimizationLevel::Prune`] also removes parts of the schema that never change whether
+    /// an instance is valid, so errors that accompany another error, like `properties` next to
+    /// a failing `"type": "string"`, are no longer reported.
+    ///
+    /// ```rust
+    /// use jsonschema::OptimizationLevel;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "allOf": [
+    ///         {"type": "integer", "minimum": 0},
+    ///         {"allOf": [{"maximum": 10}]}
+    ///     ]
+    /// });
+    /// let validator = jsonschema::options()
+    ///     .with_optimization_level(OptimizationLevel::Merge)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!(5)));
+    /// let instance = json!(11);
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(error.schema_path.as_str(), "/allOf/1/allOf/0/maximum");
+    /// ```
+    pub fn with_optimization_level(&mut self, level: OptimizationLevel) -> &mut Self {
+        self.optimization_level = level;
+        self
+    }
+    pub(crate) const fn optimization_level(&self) -> OptimizationLevel {
+        self.optimization_level
+    }
+    /// Set the order in which `is_valid` checks the keywords of each schema.
+    ///
+    /// By default, keywords that are cheap to check, like `type`, `const`, `enum` or `required`,
+    /// run first, and `pattern` and keywords applying subschemas, like `properties` or `$ref`,
+    /// run last, so invalid instances are rejected as early as possible. `validate` and
+    /// `iter_errors` always check keywords in schema order, so errors are reported
+    /// deterministically. With [`KeywordOrder::Preserve`], `is_valid` uses that order too,
+    /// which matters for custom keywords with side effects.
+    ///
+    /// ```rust
+    /// use jsonschema::KeywordOrder;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"type": "string", "pattern": "^a+$"});
+    /// let validator = jsonschema::options()
+    ///     .with_keyword_order(KeywordOrder::Preserve)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!("aaa")));
+    /// assert!(!validator.is_valid(&json!(42)));
+    /// ```
+    pub fn with_keyword_order(&mut self, order: KeywordOrder) -> &mut Self {
+        self.keyword_order = order;
+        self
+    }
+    pub(crate) const fn keyword_order(&self) -> KeywordOrder {
+        self.keyword_order
+    }
+    /// Report the validation of every keyword to `hook`, e.g. a
+    /// [`TimingCollector`](crate::instrumentation::TimingCollector) to find out which keywords
+    /// take the most time.
+    ///
+    /// ```rust
+    /// use jsonschema::{instrumentation::KeywordHook, paths::Location};
+    /// use serde_json::json;
+    /// use std::time::Duration;
+    ///
+    /// struct Print;
+    ///
+    /// impl KeywordHook for Print {
+    ///     fn exit(&self, keyword: &str, location: &Location, elapsed: Duration) {
+    ///         println!("{keyword} at {location} took {elapsed:?}");
+    ///     }
+    /// }
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_instrumentation(Print)
+    ///     .build(&json!({"minimum": 1}))
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!(2)));
+    /// ```
+    #[cfg(feature = "instrumentation")]
+    pub fn with_instrumentation(&mut self, hook: impl KeywordHook + 'static) -> &mut Self {
+        self.instrumentation = Some(Arc::new(hook));
+        self
+    }
+    #[cfg(feature = "instrumentation")]
+    pub(crate) fn instrumentation(&self) -> Option<&Arc<dyn KeywordHook>> {
+        self.instrumentation.as_ref()
+    }
+    /// Set how deeply nested instances may be when reporting errors.
+    ///
+    /// Reporting errors recurses into the instance, so validating very deeply nested data could
+    /// otherwise exhaust the stack. Values nested deeper than `depth` fail with
+    /// [`ValidationErrorKind::MaxInstanceDepth`] instead. Defaults to 128, the nesting limit
+    /// `serde_json` applies when parsing.
+    ///
+    /// [`Validator::is_valid`] checks instances of any depth without exhausting the stack, except
+    /// under applicators that check subschemas in place, like `anyOf`, `oneOf`, `not` or `if`.
+    /// Values nested deeper than `depth` through those are not valid.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_max_instance_depth(1)
+    ///     .build(&json!({"items": {"$ref": "#"}}))
+    ///     .expect("Invalid schema");
+    /// assert!(validator.validate(&json!([[]])).is_ok());
+    /// assert!(validator.validate(&json!([[[]]])).is_err());
+    /// assert!(validator.is_valid(&json!([[[]]])));
+    /// ```
+    ///
+    /// [`ValidationErrorKind::MaxInstanceDepth`]: crate::error::ValidationErrorKind::MaxInstanceDepth
+    /// [`Validator::is_valid`]: crate::Validator::is_valid
+    pub fn with_max_instance_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_instance_depth = depth;
+        self
+    }
+    pub(crate) const fn max_instance_depth(&self) -> usize {
+        self.max_instance_depth
+    }
+    /// Set how deeply nested schemas may be.
+    ///
+    /// Building a validator recurses into the schema, so adversarial schemas could otherwise
+    /// exhaust the stack. Schemas, additional resources and retrieved documents nested deeper than
+    /// `depth` are rejected with [`ValidationErrorKind::MaxSchemaDepth`], as are chains of `$ref`
+    /// that lead as deep into the schema. Defaults to 128.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"not": {"not": {"type": "integer"}}});
+    /// assert!(jsonschema::options()
+    ///     .with_max_schema_depth(1)
+    ///     .build(&schema)
+    ///     .is_err());
+    /// ```
+    ///
+    /// [`ValidationErrorKind::MaxSchemaDepth`]: crate::error::ValidationErrorKind::MaxSchemaDepth
+    pub fn with_max_schema_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_schema_depth = depth;
+        self
+    }
+    pub(crate) const fn max_schema_depth(&self) -> usize {
+        self.max_schema_depth
+    }
+    /// Set how many schema nodes a validator may consist of.
+    ///
+    /// Every subschema and every `$ref` target is compiled into a node, so this bounds the memory
+    /// used for validators built from very large schemas or remote documents. `true` and `{}`
+    /// subschemas are not counted, as they all share a single node that is never allocated.
+    /// Exceeding the limit fails with [`ValidationErrorKind::MaxCompiledNodes`]. Defaults to
+    /// 1,000,000.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"a": {"type": "string"}, "b": {"minimum": 0}}});
+    /// assert!(jsonschema::options()
+    ///     .with_max_compiled_nodes(2)
+    ///     .build(&schema)
+    ///     .is_err());
+    /// // Empty subschemas are free
+    /// let schema = json!({"properties": {"a": {}, "b": true}});
+    /// assert!(jsonschema::options()
+    ///     .with_max_compiled_nodes(2)
+    ///     .build(&schema)
+    ///     .is_ok());
+    /// ```
+    ///
+    /// [`ValidationErrorKind::MaxCompiledNodes`]: crate::error::ValidationErrorKind::MaxCompiledNodes
+    pub fn with_max_compiled_nodes(&mut self, nodes: usize) -> &mut Self {
+        self.max_compiled_nodes = nodes;
+        self
+    }
+    pub(crate) const fn max_compiled_nodes(&self) -> usize {
+        self.max_compiled_nodes
+    }
     /// Register a custom keyword validator.
     ///
     /// ## Example
@@ -447,9 +1152,267 @@ impl ValidationOptions {
         self
     }
 
+    /// Replace a built-in keyword with a custom implementation.
+    ///
+    /// This is the same as [`ValidationOptions::with_keyword`], but makes the intent explicit when
+    /// `name` is a keyword defined by the specification.
+    ///
+    /// ```rust
+    /// # use jsonschema::{paths::{LazyLocation, Location}, Keyword, ValidationError};
+    /// # use serde_json::{json, Value};
+    /// struct AlwaysValid;
+    ///
+    /// impl Keyword for AlwaysValid {
+    ///     fn validate<'i>(&self, _: &'i Value, _: &LazyLocation) -> Result<(), ValidationError<'i>> {
+    ///         Ok(())
+    ///     }
+    ///     fn is_valid(&self, _: &Value) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_keyword_override("maxLength", |_, _, _| Ok(Box::new(AlwaysValid)))
+    ///     .build(&json!({"maxLength": 1}))
+    ///     .expect("A valid schema");
+    ///
+    /// assert!(validator.is_valid(&json!("long string")));
+    /// ```
+    pub fn with_keyword_override<N, F>(&mut self, name: N, factory: F) -> &mut Self
+    where
+        N: Into<String>,
+        F: for<'a> Fn(
+                &'a serde_json::Map<String, Value>,
+                &'a Value,
+                Location,
+            ) -> Result<Box<dyn Keyword>, ValidationError<'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.with_keyword(name, factory)
+    }
+
+    /// Register a custom keyword that is only active in schemas of the given drafts.
+    ///
+    /// The draft is the one each schema resource is compiled with, so it follows `$schema` in
+    /// subresources. In other drafts, the keyword is handled as if it was not registered, e.g.
+    /// as an unknown keyword that only produces an annotation.
+    ///
+    /// ```rust
+    /// # use jsonschema::{paths::{LazyLocation, Location}, Draft, Keyword, ValidationError};
+    /// # use serde_json::{json, Value};
+    /// struct Never;
+    ///
+    /// impl Keyword for Never {
+    ///     fn validate<'i>(&self, instance: &'i Value, location: &LazyLocation) -> Result<(), ValidationError<'i>> {
+    ///         Err(ValidationError::custom(Location::new(), location.into(), instance, "Never valid"))
+    ///     }
+    ///     fn is_valid(&self, _: &Value) -> bool {
+    ///         false
+    ///     }
+    /// }
+    ///
+    /// let schema = json!({"media": {"type": "image/png"}});
+    /// let mut options = jsonschema::options();
+    /// options.with_keyword_for_drafts("media", [Draft::Draft7], |_, _, _| Ok(Box::new(Never)));
+    /// let draft7 = options.clone().with_draft(Draft::Draft7).build(&schema).expect("A valid schema");
+    /// assert!(!draft7.is_valid(&json!("image")));
+    /// let draft201909 = options.with_draft(Draft::Draft201909).build(&schema).expect("A valid schema");
+    /// assert!(draft201909.is_valid(&json!("image")));
+    /// ```
+    pub fn with_keyword_for_drafts<N, D, F>(&mut self, name: N, drafts: D, factory: F) -> &mut Self
+    where
+        N: Into<String>,
+        D: IntoIterator<Item = Draft>,
+        F: for<'a> Fn(
+                &'a serde_json::Map<String, Value>,
+                &'a Value,
+                Location,
+            ) -> Result<Box<dyn Keyword>, ValidationError<'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.keywords.insert(
+            name.into(),
+            Arc::new(ForDrafts {
+                drafts: drafts.into_iter().collect(),
+                factory,
+            }),
+        );
+        self
+    }
+
+    /// Register a custom keyword whose factory is also passed a [`KeywordContext`], e.g. to
+    /// resolve references in the keyword's value to data that is not a schema.
+    ///
+    /// ```rust
+    /// # use jsonschema::{paths::{LazyLocation, Location}, Keyword, ValidationError};
+    /// # use serde_json::{json, Value};
+    /// struct OneOf(Vec<Value>);
+    ///
+    /// impl Keyword for OneOf {
+    ///     fn validate<'i>(
+    ///         &self,
+    ///         instance: &'i Value,
+    ///         location: &LazyLocation,
+    ///     ) -> Result<(), ValidationError<'i>> {
+    ///         if self.is_valid(instance) {
+    ///             Ok(())
+    ///         } else {
+    ///             Err(ValidationError::custom(
+    ///                 Location::new(),
+    ///                 location.into(),
+    ///                 instance,
+    ///                 "Unknown value",
+    ///             ))
+    ///         }
+    ///     }
+    ///     fn is_valid(&self, instance: &Value) -> bool {
+    ///         self.0.contains(instance)
+    ///     }
+    /// }
+    ///
+    /// let schema = json!({
+    ///     "x-lookup": {"$ref": "#/definitions/tables/currencies"},
+    ///     "definitions": {"tables": {"currencies": ["EUR", "USD"]}}
+    /// });
+    /// let validator = jsonschema::options()
+    ///     .with_contextual_keyword("x-lookup", |ctx, _, value, _| {
+    ///         let reference = value["$ref"].as_str().expect("A reference");
+    ///         let table = ctx.resolve_value(reference)?;
+    ///         Ok(Box::new(OneOf(table.as_array().cloned().unwrap_or_default())))
+    ///     })
+    ///     .build(&schema)
+    ///     .expect("A valid schema");
+    ///
+    /// assert!(validator.is_valid(&json!("EUR")));
+    /// assert!(!validator.is_valid(&json!("GBP")));
+    /// ```
+    pub fn with_contextual_keyword<N, F>(&mut self, name: N, factory: F) -> &mut Self
+    where
+        N: Into<String>,
+        F: for<'a> Fn(
+                &KeywordContext<'_>,
+                &'a serde_json::Map<String, Value>,
+                &'a Value,
+                Location,
+            ) -> Result<Box<dyn Keyword>, ValidationError<'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.keywords
+            .insert(name.into(), Arc::new(Contextual(factory)));
+        self
+    }
+
     pub(crate) fn get_keyword_factory(&self, name: &str) -> Option<&Arc<dyn KeywordFactory>> {
         self.keywords.get(name)
     }
+    pub(crate) fn has_custom_keywords(&self) -> bool {
+        !self.keywords.is_empty()
+    }
+    /// Ignore the given keyword completely. Can be called multiple times to disable several
+    /// keywords.
+    ///
+    /// Keywords that apply subschemas, like `properties` or `allOf`, can not be disabled, as it
+    /// would change the validation semantics too drastically. Building a validator fails if any
+    /// of them is disabled.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::options()
+    ///     .should_validate_formats(true)
+    ///     .without_keyword("format")
+    ///     .build(&json!({"format": "email"}))
+    ///     .expect("A valid schema");
+    ///
+    /// assert!(validator.is_valid(&json!("not an email")));
+    /// assert!(jsonschema::options()
+    ///     .without_keyword("properties")
+    ///     .build(&json!({}))
+    ///     .is_err());
+    /// ```
+    pub fn without_keyword(&mut self, name: impl Into<String>) -> &mut Self {
+        self.disabled_keywords.insert(name.into());
+        self
+    }
+    pub(crate) fn is_keyword_disabled(&self, name: &str) -> bool {
+        self.disabled_keywords.contains(name)
+    }
+    /// Ensure that no applicator keyword is disabled.
+    pub(crate) fn check_disabled_keywords(&self) -> Result<(), ValidationError<'static>> {
+        const APPLICATORS: &[&str] = &[
+            "$ref",
+            "$dynamicRef",
+            "$recursiveRef",
+            "additionalItems",
+            "additionalProperties",
+            "allOf",
+            "anyOf",
+            "contains",
+            "dependencies",
+            "dependentSchemas",
+            "else",
+            "if",
+            "items",
+            "not",
+            "oneOf",
+            "patternProperties",
+            "prefixItems",
+            "properties",
+            "propertyNames",
+            "then",
+            "unevaluatedItems",
+            "unevaluatedProperties",
+        ];
+        let mut disabled: Vec<_> = self
+            .disabled_keywords
+            .iter()
+            .map(String::as_str)
+            .filter(|name| APPLICATORS.contains(name))
+            .collect();
+        if disabled.is_empty() {
+            Ok(())
+        } else {
+            disabled.sort();
+            Err(ValidationError::custom(
+                Location::new(),
+                Location::new(),
+                &Value::Null,
+                format!(
+                    "Applicator keywords can not be disabled: {}",
+                    disabled.join(", ")
+                ),
+            )
+            .into_owned())
+        }
+    }
+    /// Fail to build a validator if the schema has any of the given [lints](crate::lint).
+    ///
+    /// This is meant for catching schema mistakes in CI, analyzing the schema makes building
+    /// a validator slower.
+    ///
+    /// ```rust
+    /// use jsonschema::LintCode;
+    /// use serde_json::json;
+    ///
+    /// let result = jsonschema::options()
+    ///     .with_denied_lints([LintCode::EmptyRange, LintCode::UnsatisfiableRequired])
+    ///     .build(&json!({"minLength": 5, "maxLength": 3}));
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn with_denied_lints(&mut self, lints: impl IntoIterator<Item = LintCode>) -> &mut Self {
+        self.denied_lints.extend(lints);
+        self
+    }
+    pub(crate) fn denied_lints(&self) -> &AHashSet<LintCode> {
+        &self.denied_lints
+    }
 }
 
 impl fmt::Debug for ValidationOptions {
@@ -467,7 +1430,12 @@ impl fmt::Debug for ValidationOptions {
 
 #[cfg(test)]
 mod tests {
-    use serde_json::json;
+    use super::{KeywordOrder, MetaSchemaPolicy};
+    use crate::{error::ValidationErrorKind, Draft};
+    use referencing::{Retrieve, Uri};
+    use serde_json::{json, Map, Value};
+    use std::sync::Arc;
+    use test_case::test_case;
 
     fn custom(s: &str) -> bool {
         s.ends_with("42!")
@@ -484,4 +1452,490 @@ mod tests {
         assert!(!validator.is_valid(&json!("foo")));
         assert!(validator.is_valid(&json!("foo42!")));
     }
+
+    #[test]
+    fn without_keyword() {
+        let schema = json!({
+            "format": "email",
+            "contentMediaType": "application/json",
+            "properties": {"a": {"maxLength": 1}}
+        });
+        let validator = crate::draft7::options()
+            .should_validate_formats(true)
+            .without_keyword("format")
+            .without_keyword("contentMediaType")
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(validator.is_valid(&json!("{")));
+        // Nested keywords are disabled too
+        let validator = crate::options()
+            .without_keyword("maxLength")
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(validator.is_valid(&json!({"a": "long"})));
+    }
+
+    #[test]
+    fn without_applicator_keyword() {
+        let error = crate::options()
+            .without_keyword("properties")
+            .without_keyword("allOf")
+            .without_keyword("format")
+            .build(&json!({}))
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Applicator keywords can not be disabled: allOf, properties"
+        );
+    }
+
+    #[test]
+    fn mismatched_meta_schema() {
+        let schema = json!({"$schema": "http://json-schema.org/draft-07/schema#"});
+        let error = crate::draft4::new(&schema).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Schema declares Draft7 via `$schema`, but Draft4 is required"
+        );
+        assert_eq!(error.instance_path.as_str(), "/$schema");
+        // Matching `$schema`
+        assert!(crate::draft7::new(&schema).is_ok());
+        // Explicitly allowed
+        let validator = crate::draft4::options()
+            .allow_mismatched_meta_schema()
+            .build(&schema)
+            .expect("Valid schema");
+        assert_eq!(validator.draft(), crate::Draft::Draft4);
+        // Only per-draft entry points are strict
+        assert!(crate::options()
+            .with_draft(crate::Draft::Draft4)
+            .build(&schema)
+            .is_ok());
+    }
+
+    #[test_case(KeywordOrder::Optimized, 0; "optimized")]
+    #[test_case(KeywordOrder::Preserve, 1; "preserve")]
+    fn keyword_order(order: KeywordOrder, expected: usize) {
+        use crate::{paths::LazyLocation, Keyword, ValidationError};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Counted<'a>(&'a AtomicUsize);
+
+        impl Keyword for Counted<'static> {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                Ok(())
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                true
+            }
+        }
+
+        static CALLS: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+        let calls = &CALLS[expected];
+        // In schema order, `required` is checked last
+        let schema = json!({
+            "counted": true,
+            "properties": {"a": {"type": "string"}},
+            "required": ["b"]
+        });
+        let validator = crate::options()
+            .with_keyword_order(order)
+            .with_keyword("counted", move |_, _, _| Ok(Box::new(Counted(calls))))
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(!validator.is_valid(&json!({"a": "x"})));
+        assert_eq!(calls.load(Ordering::SeqCst), expected);
+        assert!(validator.is_valid(&json!({"a": "x", "b": 1})));
+        // Errors are reported in schema order regardless
+        let instance = json!({"a": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/properties/a/type");
+        let locations: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| error.schema_path.to_string())
+            .collect();
+        assert_eq!(locations, ["/properties/a/type", "/required"]);
+    }
+
+    /// Options with a `media` keyword that only accepts strings, active in Draft 7 only.
+    fn media_options() -> crate::ValidationOptions {
+        use crate::{
+            paths::{LazyLocation, Location},
+            Keyword, ValidationError,
+        };
+
+        struct Media;
+
+        impl Keyword for Media {
+            fn validate<'i>(
+                &self,
+                instance: &'i Value,
+                location: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                if self.is_valid(instance) {
+                    Ok(())
+                } else {
+                    Err(ValidationError::custom(
+                        Location::new(),
+                        location.into(),
+                        instance,
+                        "Not a media string",
+                    ))
+                }
+            }
+            fn is_valid(&self, instance: &Value) -> bool {
+                instance.is_string()
+            }
+        }
+
+        let mut options = crate::options();
+        options.with_keyword_for_drafts("media", [Draft::Draft7], |_, _, _| Ok(Box::new(Media)));
+        options
+    }
+
+    #[test_case(Draft::Draft7, false; "active")]
+    #[test_case(Draft::Draft201909, true; "inactive")]
+    fn keyword_for_drafts(draft: Draft, expected: bool) {
+        let schema = json!({"media": {"binaryEncoding": "base64"}});
+        let validator = media_options()
+            .with_draft(draft)
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(validator.is_valid(&json!("image")));
+        assert_eq!(validator.is_valid(&json!(42)), expected);
+        let keywords: Vec<_> = validator
+            .keyword_locations()
+            .map(|(location, _)| location.as_str().to_string())
+            .collect();
+        assert_eq!(keywords.is_empty(), expected);
+    }
+
+    #[test_case("http://json-schema.org/draft-07/schema#", "https://json-schema.org/draft/2019-09/schema", false; "draft 7 subresource")]
+    #[test_case("https://json-schema.org/draft/2019-09/schema", "http://json-schema.org/draft-07/schema#", true; "draft 2019-09 subresource")]
+    fn keyword_for_drafts_subresource(inner: &str, outer: &str, expected: bool) {
+        let schema = json!({
+            "$schema": outer,
+            "properties": {
+                "a": {"$ref": "https://example.com/media.json"},
+                "b": {"media": true}
+            },
+            "$defs": {
+                "media": {"$id": "https://example.com/media.json", "$schema": inner, "media": true}
+            },
+            "definitions": {
+                "media": {"$id": "https://example.com/media.json", "$schema": inner, "media": true}
+            }
+        });
+        let validator = media_options().build(&schema).expect("Valid schema");
+        assert_eq!(validator.is_valid(&json!({"a": 42})), expected);
+        assert_eq!(validator.is_valid(&json!({"b": 42})), !expected);
+    }
+
+    fn nested_not(depth: usize) -> Value {
+        let mut schema = json!({"type": "integer"});
+        for _ in 0..depth {
+            let mut object = Map::new();
+            object.insert("not".to_string(), schema);
+            schema = Value::Object(object);
+        }
+        schema
+    }
+
+    // Dropping a deeply nested value recurses as well
+    fn unnest(mut schema: Value) {
+        while let Some(inner) = schema
+            .as_object_mut()
+            .and_then(|object| object.remove("not"))
+        {
+            schema = inner;
+        }
+    }
+
+    #[test]
+    fn max_schema_depth() {
+        let schema = nested_not(100_000);
+        let error = crate::validator_for(&schema).expect_err("Too deep");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::MaxSchemaDepth { limit: 128 }
+        ));
+        assert_eq!(error.instance_path.as_str(), "/not".repeat(129));
+        assert_eq!(error.to_string(), "Schema is nested deeper than 128 levels");
+        unnest(schema);
+
+        let schema = nested_not(3);
+        assert!(crate::validator_for(&schema).is_ok());
+        let error = crate::options()
+            .with_max_schema_depth(2)
+            .build(&schema)
+            .expect_err("Too deep");
+        assert_eq!(error.instance_path.as_str(), "/not/not/not");
+    }
+
+    #[test]
+    fn max_schema_depth_references() {
+        let mut defs = Map::new();
+        for idx in 0..100_000 {
+            defs.insert(
+                format!("a{idx}"),
+                json!({"$ref": format!("#/$defs/a{}", idx + 1)}),
+            );
+        }
+        defs.insert("a100000".to_string(), json!({"type": "integer"}));
+        let schema = json!({"$ref": "#/$defs/a0", "$defs": defs});
+        let error = crate::validator_for(&schema).expect_err("Too deep");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::MaxSchemaDepth { limit: 128 }
+        ));
+        assert_eq!(error.instance_path.as_str(), "/$ref".repeat(129));
+    }
+
+    #[test]
+    fn max_schema_depth_retrieved() {
+        struct Deep;
+
+        impl Retrieve for Deep {
+            fn retrieve(
+                &self,
+                _: &Uri<&str>,
+            ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(nested_not(10))
+            }
+        }
+
+        let schema = json!({"$ref": "https://example.com/deep.json"});
+        let error = crate::options()
+            .with_retriever(Deep)
+            .with_max_schema_depth(5)
+            .build(&schema)
+            .expect_err("Too deep");
+        assert!(error
+            .to_string()
+            .ends_with("Schema is nested deeper than 5 levels"));
+    }
+
+    #[test]
+    fn max_compiled_nodes() {
+        let schema = json!({"properties": {"a": {"minimum": 1}, "b": {"maximum": 1}}});
+        assert!(crate::options()
+            .with_max_compiled_nodes(3)
+            .build(&schema)
+            .is_ok());
+        let error = crate::options()
+            .with_max_compiled_nodes(2)
+            .build(&schema)
+            .expect_err("Too many nodes");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::MaxCompiledNodes { limit: 2 }
+        ));
+        assert_eq!(error.instance_path.as_str(), "/properties/b");
+        assert_eq!(error.to_string(), "Schema has more than 2 nodes");
+    }
+
+    #[test]
+    fn trivially_true_nodes_are_not_compiled() {
+        // Only the root, `/properties/c` and `/not` are compiled
+        let schema = json!({
+            "properties": {"a": true, "b": {}, "c": {"type": "string"}},
+            "additionalProperties": true,
+            "items": {},
+            "allOf": [{}, true],
+            "not": {"not": {}}
+        });
+        let validator = crate::options()
+            .with_max_compiled_nodes(3)
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(crate::options()
+            .with_max_compiled_nodes(2)
+            .build(&schema)
+            .is_err());
+        let instance = json!({"a": 1, "b": [], "c": "c"});
+        assert!(validator.is_valid(&instance));
+        assert!(validator.validate(&instance).is_ok());
+        assert_eq!(
+            serde_json::to_value(validator.apply(&instance).basic()).expect("Serializable"),
+            json!({
+                "valid": true,
+                "annotations": [{
+                    "keywordLocation": "/properties",
+                    "instanceLocation": "",
+                    "annotations": ["a", "b", "c"]
+                }]
+            })
+        );
+    }
+
+    struct Dialects;
+
+    impl Retrieve for Dialects {
+        fn retrieve(
+            &self,
+            uri: &Uri<&str>,
+        ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+            match uri.as_str() {
+                "https://example.com/draft4-dialect" => {
+                    Ok(json!({"$schema": "http://json-schema.org/draft-04/schema#"}))
+                }
+                _ => Err("Not found".into()),
+            }
+        }
+    }
+
+    fn dialect_draft(schema: &Value, policy: MetaSchemaPolicy) -> Result<Draft, String> {
+        crate::options()
+            .with_retriever(Dialects)
+            .with_meta_schema_policy(policy)
+            .build(schema)
+            .map(|validator| validator.draft())
+            .map_err(|error| error.to_string())
+    }
+
+    #[test_case("https://example.com/draft4-dialect", Ok(Draft::Draft4); "retrieved")]
+    #[test_case(
+        "https://example.com/our-dialect",
+        Err("Unknown specification: https://example.com/our-dialect".to_string());
+        "not retrieved"
+    )]
+    fn meta_schema_policy_error(uri: &str, expected: Result<Draft, String>) {
+        let schema = json!({"$schema": uri});
+        assert_eq!(dialect_draft(&schema, MetaSchemaPolicy::Error), expected);
+    }
+
+    #[test]
+    fn meta_schema_policy_fallback() {
+        let schema = json!({
+            "$schema": "https://example.com/our-dialect",
+            "prefixItems": [{"type": "string"}]
+        });
+        let policy = MetaSchemaPolicy::FallbackTo(Draft::Draft202012);
+        assert_eq!(
+            dialect_draft(&schema, policy.clone()),
+            Ok(Draft::Draft202012)
+        );
+        let validator = crate::options()
+            .with_retriever(Dialects)
+            .with_meta_schema_policy(policy)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!([1])));
+        // Known drafts are still detected
+        let schema = json!({"$schema": "http://json-schema.org/draft-04/schema#"});
+        let policy = MetaSchemaPolicy::FallbackTo(Draft::Draft202012);
+        assert_eq!(dialect_draft(&schema, policy), Ok(Draft::Draft4));
+    }
+
+    #[test_case("https://example.com/legacy/v1", Ok(Draft::Draft7); "mapped")]
+    #[test_case(
+        "https://example.com/draft4-dialect",
+        Err("Unknown specification: https://example.com/draft4-dialect".to_string());
+        "not mapped"
+    )]
+    fn meta_schema_policy_custom(uri: &str, expected: Result<Draft, String>) {
+        let policy = MetaSchemaPolicy::Custom(Arc::new(|uri| {
+            uri.starts_with("https://example.com/legacy/")
+                .then_some(Draft::Draft7)
+        }));
+        let schema = json!({"$schema": uri});
+        assert_eq!(dialect_draft(&schema, policy), expected);
+    }
+
+    #[test_case("http://json-schema.org/draft-07/schema#", Draft::Draft7)]
+    #[test_case("https://json-schema.org/draft-07/schema", Draft::Draft7)]
+    #[test_case("https://json-schema.org/draft-04/schema#", Draft::Draft4)]
+    #[test_case("http://json-schema.org/draft/2019-09/schema", Draft::Draft201909)]
+    #[test_case("https://json-schema.org/draft/2020-12/schema#", Draft::Draft202012)]
+    fn meta_schema_variants(uri: &str, expected: Draft) {
+        let schema = json!({"$schema": uri});
+        assert_eq!(
+            dialect_draft(&schema, MetaSchemaPolicy::Error),
+            Ok(expected)
+        );
+    }
+
+    #[test_case(&json!({"type": "string", "default": "a", "examples": ["b"]}), None; "valid")]
+    #[test_case(&json!({"type": "string", "default": 1}), Some(("/default", "/type")); "root")]
+    #[test_case(
+        &json!({"properties": {"port": {"type": "integer", "minimum": 1, "default": 0}}}),
+        Some(("/properties/port/default", "/properties/port/minimum"));
+        "nested"
+    )]
+    #[test_case(
+        &json!({"$defs": {"port": {"type": "integer", "default": "a"}}}),
+        Some(("/$defs/port/default", "/$defs/port/type"));
+        "unreferenced definition"
+    )]
+    #[test_case(
+        &json!({
+            "properties": {"port": {"$ref": "#/$defs/port", "default": "a"}},
+            "$defs": {"port": {"type": "integer"}}
+        }),
+        Some(("/properties/port/default", "/properties/port/$ref/type"));
+        "reference"
+    )]
+    #[test_case(
+        &json!({"$defs": {"a": {
+            "$id": "https://example.com/a",
+            "properties": {"b": {"$ref": "#/$defs/b", "default": "a"}},
+            "$defs": {"b": {"type": "integer"}}
+        }}}),
+        Some(("/$defs/a/properties/b/default", "/$defs/a/properties/b/$ref/type"));
+        "embedded resource"
+    )]
+    #[test_case(
+        &json!({"properties": {"a%20b": {"type": "integer", "default": "a"}}}),
+        Some(("/properties/a%20b/default", "/properties/a%20b/type"));
+        "percent sign"
+    )]
+    #[test_case(&json!({"type": "string", "examples": ["a", 1]}), Some(("/examples/1", "/type")); "examples")]
+    fn validate_defaults(schema: &Value, expected: Option<(&str, &str)>) {
+        let result = crate::options()
+            .with_validate_defaults(true)
+            .with_validate_examples(true)
+            .build(schema);
+        let error = match (result, expected) {
+            (Ok(_), None) => return,
+            (Err(error), Some(_)) => error,
+            (result, _) => panic!("Unexpected result: {:?}", result.err()),
+        };
+        let ValidationErrorKind::InvalidDefault { errors, .. } = &error.kind else {
+            panic!("Unexpected error: {error}");
+        };
+        let (instance_path, schema_path) = expected.expect("Invalid schema");
+        assert_eq!(error.instance_path.as_str(), instance_path);
+        assert_eq!(errors[0].schema_path.as_str(), schema_path);
+    }
+
+    #[test]
+    fn validate_defaults_disabled() {
+        let schema = json!({"type": "string", "default": 1, "examples": [1]});
+        assert!(crate::validator_for(&schema).is_ok());
+        assert!(crate::options()
+            .with_validate_examples(true)
+            .build(&json!({"type": "string", "default": 1}))
+            .is_ok());
+        assert!(crate::options()
+            .with_validate_defaults(true)
+            .build(&json!({"type": "string", "examples": [1]}))
+            .is_ok());
+    }
+
+    #[test]
+    fn invalid_default_message() {
+        let error = crate::options()
+            .with_validate_defaults(true)
+            .build(&json!({"properties": {"a": {"type": "string", "minLength": 2, "default": 1}}}))
+            .expect_err("Invalid default");
+        assert_eq!(
+            error.to_string(),
+            r#"Invalid "default" value 1: 1 is not of type "string""#
+        );
+    }
 }
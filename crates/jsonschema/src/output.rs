@@ -5,15 +5,17 @@
 
 use std::{
     borrow::Cow,
-    collections::VecDeque,
+    collections::{BTreeMap, VecDeque},
     fmt,
     iter::{FromIterator, Sum},
     ops::AddAssign,
+    sync::Arc,
 };
 
-use crate::{paths::Location, validator::PartialApplication, ValidationError};
-use ahash::AHashMap;
-use referencing::Uri;
+pub use crate::validator::PartialApplication;
+use crate::{compiler, paths::Location, ValidationError};
+use ahash::{AHashMap, AHashSet};
+use referencing::{uri, Uri};
 use serde::ser::SerializeMap;
 
 use crate::{node::SchemaNode, paths::LazyLocation, Validator};
@@ -109,7 +111,7 @@ impl<'a, 'b> Output<'a, 'b> {
 
 /// The "basic" output format. See the documentation for [`Output::basic`] for
 /// examples of how to use this.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum BasicOutput<'a> {
     /// The schema was valid, collected annotations can be examined
     Valid(VecDeque<OutputUnit<Annotations<'a>>>),
@@ -126,6 +128,188 @@ impl<'a> BasicOutput<'a> {
             BasicOutput::Invalid(..) => false,
         }
     }
+
+    /// Errors grouped by the instance location they refer to, ordered by location.
+    ///
+    /// Errors with the same absolute keyword location and description are reported once, e.g.
+    /// when several references lead to the same subschema. Valid output has no errors.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "name": {"type": "string", "minLength": 2},
+    ///         "age": {"minimum": 0}
+    ///     }
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let instance = json!({"name": 1, "age": -1});
+    /// let output = validator.apply(&instance).basic();
+    /// let errors = output.by_instance_location();
+    /// let fields: Vec<_> = errors.keys().map(|location| location.as_str()).collect();
+    /// assert_eq!(fields, ["/age", "/name"]);
+    /// assert_eq!(errors.values().map(Vec::len).sum::<usize>(), 2);
+    /// ```
+    #[must_use]
+    pub fn by_instance_location(&self) -> BTreeMap<Location, Vec<&OutputUnit<ErrorDescription>>> {
+        let mut groups: BTreeMap<Location, Vec<&OutputUnit<ErrorDescription>>> = BTreeMap::new();
+        let mut seen = AHashSet::new();
+        for unit in self.errors() {
+            if seen.insert((unit.instance_location.as_str(), unit.identity())) {
+                groups
+                    .entry(unit.instance_location.clone())
+                    .or_default()
+                    .push(unit);
+            }
+        }
+        groups
+    }
+
+    /// Errors at the given instance location, a JSON pointer like `/address/city`.
+    ///
+    /// Errors at nested locations are not included. Like
+    /// [`BasicOutput::by_instance_location`], identical errors are reported once.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"address": {"properties": {"city": {"type": "string"}}}}});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let instance = json!({"address": {"city": 42}});
+    /// let output = validator.apply(&instance).basic();
+    /// let errors = output.errors_for("/address/city");
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].keyword_location().as_str(), "/properties/address/properties/city/type");
+    /// assert!(output.errors_for("/address").is_empty());
+    /// ```
+    #[must_use]
+    pub fn errors_for(&self, pointer: &str) -> Vec<&OutputUnit<ErrorDescription>> {
+        let mut seen = AHashSet::new();
+        self.errors()
+            .filter(|unit| {
+                unit.instance_location.as_str() == pointer && seen.insert(unit.identity())
+            })
+            .collect()
+    }
+
+    /// Content decoded or parsed by `contentEncoding` and `contentMediaType`, keyed by the
+    /// instance location of the string it came from. Only captured when
+    /// [`ValidationOptions::with_content_capture`](crate::ValidationOptions::with_content_capture)
+    /// is enabled, and empty for invalid output. If several subschemas capture content at the
+    /// same location, the first one is kept.
+    ///
+    /// ```rust
+    /// use jsonschema::{output::Content, Draft};
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "payload": {"contentEncoding": "base64", "contentMediaType": "application/json"}
+    ///     }
+    /// });
+    /// let validator = jsonschema::options()
+    ///     .with_draft(Draft::Draft7)
+    ///     .with_content_capture(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// // `{"id": 1}` encoded in base64
+    /// let instance = json!({"payload": "eyJpZCI6IDF9"});
+    /// let output = validator.apply(&instance).basic();
+    /// let content = output.content();
+    /// let (location, payload) = content.iter().next().expect("Content is captured");
+    /// assert_eq!(location.as_str(), "/payload");
+    /// assert_eq!(payload, &&Content::Json(json!({"id": 1})));
+    /// ```
+    #[must_use]
+    pub fn content(&self) -> BTreeMap<Location, &Content> {
+        let mut content = BTreeMap::new();
+        if let BasicOutput::Valid(units) = self {
+            for unit in units {
+                if let Some(captured) = unit.content() {
+                    content
+                        .entry(unit.instance_location.clone())
+                        .or_insert(captured);
+                }
+            }
+        }
+        content
+    }
+
+    /// Errors in the order they were found. Valid output has no errors.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"items": {"type": "integer"}}))
+    ///     .expect("Invalid schema");
+    /// let output = validator.apply(&json!([1, "a", "b"])).basic();
+    /// let locations: Vec<_> = output
+    ///     .errors()
+    ///     .map(|unit| unit.instance_location().as_str())
+    ///     .collect();
+    /// assert_eq!(locations, ["/1", "/2"]);
+    /// ```
+    pub fn errors(&self) -> impl Iterator<Item = &OutputUnit<ErrorDescription>> {
+        match self {
+            BasicOutput::Valid(..) => None,
+            BasicOutput::Invalid(errors) => Some(errors.iter()),
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    /// Annotations collected by subschemas. Invalid output has no annotations.
+    pub fn annotations(&self) -> impl Iterator<Item = &OutputUnit<Annotations<'a>>> {
+        match self {
+            BasicOutput::Valid(annotations) => Some(annotations.iter()),
+            BasicOutput::Invalid(..) => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    /// Combine the output with the output of validating another instance or schema, like `+=`.
+    ///
+    /// The result is valid if both outputs are, with the annotations of both. Otherwise, it has
+    /// the errors of both. Outputs of many validations can be combined with
+    /// [`Iterator::sum`].
+    ///
+    /// ```rust
+    /// use jsonschema::BasicOutput;
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"type": "integer"}))
+    ///     .expect("Invalid schema");
+    /// let first = json!(1);
+    /// let second = json!("a");
+    /// let output = validator.apply(&first).basic().merge(validator.apply(&second).basic());
+    /// assert!(!output.is_valid());
+    ///
+    /// let documents = [json!(1), json!("a"), json!(null)];
+    /// let output: BasicOutput = documents
+    ///     .iter()
+    ///     .map(|document| validator.apply(document).basic())
+    ///     .sum();
+    /// assert_eq!(output.errors().count(), 2);
+    /// ```
+    #[must_use]
+    pub fn merge(mut self, other: BasicOutput<'a>) -> BasicOutput<'a> {
+        self += other;
+        self
+    }
+}
+
+impl<'a> From<BasicOutput<'a>>
+    for Result<VecDeque<OutputUnit<Annotations<'a>>>, VecDeque<OutputUnit<ErrorDescription>>>
+{
+    /// The annotations of valid output, or the errors of invalid output.
+    fn from(output: BasicOutput<'a>) -> Self {
+        match output {
+            BasicOutput::Valid(annotations) => Ok(annotations),
+            BasicOutput::Invalid(errors) => Err(errors),
+        }
+    }
 }
 
 impl<'a> From<OutputUnit<Annotations<'a>>> for BasicOutput<'a> {
@@ -240,26 +424,225 @@ impl<T> OutputUnit<T> {
     /// The absolute location in the schema of the keyword. This will be
     /// different to `keyword_location` if the schema is a resolved reference.
     pub fn absolute_keyword_location(&self) -> Option<Uri<&str>> {
+        self.reported_absolute_keyword_location()
+            .map(|uri| uri.borrow())
+    }
+
+    fn reported_absolute_keyword_location(&self) -> Option<&Uri<String>> {
         self.absolute_keyword_location
             .as_ref()
-            .map(|uri| uri.borrow())
+            .filter(|uri| is_reported(uri))
     }
 
     /// The location in the instance
     pub const fn instance_location(&self) -> &Location {
         &self.instance_location
     }
+
+    /// Move this unit from a schema node compiled at `from` to the same node used at `to`.
+    pub(crate) fn rebase(
+        &mut self,
+        from: &Location,
+        to: &Location,
+        absolute: Option<(&AbsoluteLocation, &AbsoluteLocation)>,
+    ) {
+        let Some(keyword_location) = self.keyword_location.rebase(from, to) else {
+            return;
+        };
+        if let (Some(unit), Some((from, to))) = (&self.absolute_keyword_location, absolute) {
+            // Units that point into another schema keep their absolute location
+            if let Some(rebased) = to.rebase(unit, from) {
+                self.absolute_keyword_location = Some(rebased);
+            }
+        }
+        self.keyword_location = keyword_location;
+    }
 }
 
-impl OutputUnit<Annotations<'_>> {
+impl OutputUnit<ErrorDescription> {
+    /// What makes two errors the same: where the failed keyword is written and the description.
+    ///
+    /// Errors of a subschema reached through different references share the absolute location.
+    fn identity(&self) -> (&str, &str) {
+        let location = match &self.absolute_keyword_location {
+            Some(absolute) => absolute.as_str(),
+            None => self.keyword_location.as_str(),
+        };
+        (location, self.value.as_str())
+    }
+}
+
+impl<'a> OutputUnit<Annotations<'a>> {
+    /// Annotations at `keyword_location`, with the absolute location resolved against `schema`.
+    pub(crate) fn annotations_at(
+        schema: Option<&AbsoluteLocation>,
+        keyword_location: Location,
+        instance_location: Location,
+        annotations: Annotations<'a>,
+    ) -> Self {
+        OutputUnit {
+            absolute_keyword_location: schema
+                .filter(|schema| is_reported(schema.uri()))
+                .map(|schema| schema.resolve(&keyword_location)),
+            keyword_location,
+            instance_location,
+            value: annotations,
+        }
+    }
+
     /// The annotations found at this output unit
     #[must_use]
     pub fn value(&self) -> Cow<'_, serde_json::Value> {
         self.value.value()
     }
+
+    /// What an applicator keyword, like `properties` or `items`, evaluated.
+    ///
+    /// ```rust
+    /// use jsonschema::output::Evaluated;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {"a": true},
+    ///     "additionalProperties": {"type": "integer"}
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let output = validator.apply(&json!({"a": 1, "b": 2})).basic();
+    /// let jsonschema::BasicOutput::Valid(units) = output else {
+    ///     panic!("Should be valid");
+    /// };
+    /// let evaluated: Vec<_> = units
+    ///     .iter()
+    ///     .filter_map(|unit| unit.evaluated())
+    ///     .collect();
+    /// assert_eq!(
+    ///     evaluated,
+    ///     [
+    ///         &Evaluated::Properties(vec!["b".to_string()]),
+    ///         &Evaluated::Properties(vec!["a".to_string()]),
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn evaluated(&self) -> Option<&Evaluated> {
+        self.value.evaluated()
+    }
+
+    /// Content decoded or parsed by `contentEncoding` and `contentMediaType`. Only captured when
+    /// [`ValidationOptions::with_content_capture`](crate::ValidationOptions::with_content_capture)
+    /// is enabled.
+    #[must_use]
+    pub fn content(&self) -> Option<&Content> {
+        self.value.content()
+    }
+
+    pub(crate) fn into_owned(self) -> OutputUnit<Annotations<'static>> {
+        OutputUnit {
+            keyword_location: self.keyword_location,
+            instance_location: self.instance_location,
+            absolute_keyword_location: self.absolute_keyword_location,
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+/// Absolute location of a schema, used to resolve the absolute locations of its keywords.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AbsoluteLocation {
+    /// URI of the schema's resource with the schema's location within it as fragment
+    uri: Arc<Uri<String>>,
+    /// Location of the schema in the validated schema
+    location: Location,
+}
+
+impl AbsoluteLocation {
+    pub(crate) fn new(
+        base_uri: &Uri<String>,
+        resource_pointer: &Location,
+        location: &Location,
+    ) -> Self {
+        let mut buffer = String::new();
+        uri::encode_to(resource_pointer.as_str(), &mut buffer);
+        AbsoluteLocation {
+            uri: Arc::new(base_uri.with_fragment(Some(uri::EncodedString::new_or_panic(&buffer)))),
+            location: location.clone(),
+        }
+    }
+    /// The same schema used at `location`.
+    pub(crate) fn at(&self, location: &Location) -> Self {
+        AbsoluteLocation {
+            uri: Arc::clone(&self.uri),
+            location: location.clone(),
+        }
+    }
+    pub(crate) fn uri(&self) -> &Arc<Uri<String>> {
+        &self.uri
+    }
+    /// Absolute location of `keyword_location`, which is within this schema.
+    pub(crate) fn resolve(&self, keyword_location: &Location) -> Uri<String> {
+        let relative = keyword_location
+            .as_str()
+            .strip_prefix(self.location.as_str())
+            .unwrap_or_default();
+        resolve_relative(&self.uri, relative)
+    }
+    /// Move `absolute`, a location within the schema at `from`, to the same place in this one.
+    ///
+    /// Returns `None` if `absolute` is not within `from`.
+    pub(crate) fn rebase(
+        &self,
+        absolute: &Uri<String>,
+        from: &AbsoluteLocation,
+    ) -> Option<Uri<String>> {
+        let rest = absolute.as_str().strip_prefix(from.uri.as_str())?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+        let mut buffer = self
+            .uri
+            .fragment()
+            .map_or_else(String::new, |f| f.as_str().to_string());
+        buffer.push_str(rest);
+        Some(
+            self.uri
+                .with_fragment(Some(uri::EncodedString::new_or_panic(&buffer))),
+        )
+    }
+}
+
+/// Whether `uri` is reported as an absolute location.
+///
+/// Schemas without a base URI are located under the default `json-schema:///` URI, which is
+/// only used internally.
+pub(crate) fn is_reported(uri: &Uri<String>) -> bool {
+    uri.scheme().as_str() != compiler::DEFAULT_SCHEME
+}
+
+/// `relative`, an unencoded JSON pointer, resolved against the absolute location of a schema.
+pub(crate) fn resolve_relative(schema: &Uri<String>, relative: &str) -> Uri<String> {
+    let mut buffer = schema
+        .fragment()
+        .map_or_else(String::new, |f| f.as_str().to_string());
+    uri::encode_to(relative, &mut buffer);
+    schema.with_fragment(Some(uri::EncodedString::new_or_panic(&buffer)))
 }
 
 impl OutputUnit<ErrorDescription> {
+    /// An error at `keyword_location`, with the absolute location resolved against `schema`.
+    pub(crate) fn error_at(
+        schema: Option<&AbsoluteLocation>,
+        keyword_location: Location,
+        instance_location: Location,
+        error: ErrorDescription,
+    ) -> Self {
+        OutputUnit {
+            absolute_keyword_location: schema.map(|schema| schema.resolve(&keyword_location)),
+            keyword_location,
+            instance_location,
+            value: error,
+        }
+    }
+
     /// The error for this output unit
     #[must_use]
     pub const fn error_description(&self) -> &ErrorDescription {
@@ -283,6 +666,40 @@ impl<'a> Annotations<'a> {
                     .expect("&AHashMap<String, serde_json::Value> cannot fail serializing");
                 Cow::Owned(value)
             }
+            AnnotationsInner::Evaluated(evaluated) => Cow::Owned(evaluated.to_value()),
+            AnnotationsInner::Content(content) => Cow::Owned(content.to_value()),
+        }
+    }
+
+    /// What an applicator keyword, like `properties` or `items`, evaluated.
+    #[must_use]
+    pub fn evaluated(&self) -> Option<&Evaluated> {
+        match &self.0 {
+            AnnotationsInner::Evaluated(evaluated) => Some(evaluated),
+            _ => None,
+        }
+    }
+
+    /// Content decoded or parsed by `contentEncoding` and `contentMediaType`.
+    #[must_use]
+    pub fn content(&self) -> Option<&Content> {
+        match &self.0 {
+            AnnotationsInner::Content(content) => Some(content),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn into_owned(self) -> Annotations<'static> {
+        match self.0 {
+            AnnotationsInner::Value(v) => Annotations(AnnotationsInner::Value(v)),
+            AnnotationsInner::Evaluated(evaluated) => {
+                Annotations(AnnotationsInner::Evaluated(evaluated))
+            }
+            AnnotationsInner::Content(content) => Annotations(AnnotationsInner::Content(content)),
+            AnnotationsInner::ValueRef(v) => v.clone().into(),
+            AnnotationsInner::UnmatchedKeywords(kvs) => serde_json::to_value(kvs)
+                .expect("&AHashMap<String, serde_json::Value> cannot fail serializing")
+                .into(),
         }
     }
 }
@@ -292,6 +709,92 @@ enum AnnotationsInner<'a> {
     UnmatchedKeywords(&'a AHashMap<String, serde_json::Value>),
     ValueRef(&'a serde_json::Value),
     Value(Box<serde_json::Value>),
+    Evaluated(Evaluated),
+    Content(Content),
+}
+
+/// The annotation of an applicator keyword, describing which parts of the instance it applied
+/// its subschemas to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Evaluated {
+    /// Names of the properties matched by `properties`, `patternProperties` or
+    /// `additionalProperties`. Serialized as an array of strings.
+    Properties(Vec<String>),
+    /// The largest index `prefixItems`, or `items` with an array of schemas, applied a subschema
+    /// to. Serialized as a number.
+    LargestIndex(usize),
+    /// `prefixItems` applied a subschema to every item, or `items` to any item. Serialized as
+    /// `true`.
+    AllItems,
+    /// Indices of the items matching `contains`. Serialized as an array of numbers.
+    Indices(Vec<usize>),
+}
+
+impl Evaluated {
+    fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Evaluated annotations cannot fail serializing")
+    }
+}
+
+impl serde::Serialize for Evaluated {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Properties(names) => names.serialize(serializer),
+            Self::LargestIndex(index) => serializer.serialize_u64(*index as u64),
+            Self::AllItems => serializer.serialize_bool(true),
+            Self::Indices(indices) => indices.serialize(serializer),
+        }
+    }
+}
+
+/// Content of a string instance decoded by `contentEncoding` or parsed according to
+/// `contentMediaType`, captured when
+/// [`ValidationOptions::with_content_capture`](crate::ValidationOptions::with_content_capture)
+/// is enabled.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Content {
+    /// Content decoded by `contentEncoding`, for media types that are not parsed.
+    Decoded(String),
+    /// Content parsed as `application/json`, after decoding it if `contentEncoding` is present.
+    Json(serde_json::Value),
+}
+
+impl Content {
+    fn to_value(&self) -> serde_json::Value {
+        match self {
+            Self::Decoded(decoded) => serde_json::Value::String(decoded.clone()),
+            Self::Json(value) => value.clone(),
+        }
+    }
+}
+
+impl serde::Serialize for Content {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Decoded(decoded) => decoded.serialize(serializer),
+            Self::Json(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl From<Content> for Annotations<'_> {
+    fn from(content: Content) -> Self {
+        Annotations(AnnotationsInner::Content(content))
+    }
+}
+
+impl From<Evaluated> for Annotations<'_> {
+    fn from(evaluated: Evaluated) -> Self {
+        Annotations(AnnotationsInner::Evaluated(evaluated))
+    }
 }
 
 impl<'a> From<&'a AHashMap<String, serde_json::Value>> for Annotations<'a> {
@@ -322,6 +825,12 @@ impl ErrorDescription {
     pub fn into_inner(self) -> String {
         self.0
     }
+    /// The error description as a string slice.
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 impl fmt::Display for ErrorDescription {
@@ -371,6 +880,8 @@ impl<'a> serde::Serialize for AnnotationsInner<'a> {
             Self::UnmatchedKeywords(kvs) => kvs.serialize(serializer),
             Self::Value(v) => v.serialize(serializer),
             Self::ValueRef(v) => v.serialize(serializer),
+            Self::Evaluated(evaluated) => evaluated.serialize(serializer),
+            Self::Content(content) => content.serialize(serializer),
         }
     }
 }
@@ -383,8 +894,8 @@ impl<'a> serde::Serialize for OutputUnit<Annotations<'a>> {
         let mut map_ser = serializer.serialize_map(Some(4))?;
         map_ser.serialize_entry("keywordLocation", self.keyword_location.as_str())?;
         map_ser.serialize_entry("instanceLocation", self.instance_location.as_str())?;
-        if let Some(absolute) = &self.absolute_keyword_location {
-            map_ser.serialize_entry("absoluteKeywordLocation", &absolute)?;
+        if let Some(absolute) = self.reported_absolute_keyword_location() {
+            map_ser.serialize_entry("absoluteKeywordLocation", absolute)?;
         }
         map_ser.serialize_entry("annotations", &self.value)?;
         map_ser.end()
@@ -399,8 +910,8 @@ impl serde::Serialize for OutputUnit<ErrorDescription> {
         let mut map_ser = serializer.serialize_map(Some(4))?;
         map_ser.serialize_entry("keywordLocation", self.keyword_location.as_str())?;
         map_ser.serialize_entry("instanceLocation", self.instance_location.as_str())?;
-        if let Some(absolute) = &self.absolute_keyword_location {
-            map_ser.serialize_entry("absoluteKeywordLocation", &absolute)?;
+        if let Some(absolute) = self.reported_absolute_keyword_location() {
+            map_ser.serialize_entry("absoluteKeywordLocation", absolute)?;
         }
         map_ser.serialize_entry("error", &self.value)?;
         map_ser.end()
@@ -1,5 +1,13 @@
 //! Facilities for working with paths within schemas or validated instances.
-use std::{fmt, sync::Arc};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use serde_json::Value;
 
 use crate::keywords::Keyword;
 
@@ -29,6 +37,7 @@ impl<'a> fmt::Display for LocationSegment<'a> {
 pub struct LazyLocation<'a, 'b> {
     pub(crate) segment: LocationSegment<'a>,
     pub(crate) parent: Option<&'b LazyLocation<'b, 'a>>,
+    depth: usize,
 }
 
 impl Default for LazyLocation<'_, '_> {
@@ -44,6 +53,7 @@ impl<'a, 'b> LazyLocation<'a, 'b> {
             // The value does not matter, it will never be used
             segment: LocationSegment::Index(0),
             parent: None,
+            depth: 0,
         }
     }
 
@@ -53,8 +63,15 @@ impl<'a, 'b> LazyLocation<'a, 'b> {
         LazyLocation {
             segment: segment.into(),
             parent: Some(self),
+            depth: self.depth + 1,
         }
     }
+
+    /// Number of segments in this JSON pointer, i.e. how deep it points into the instance.
+    #[inline]
+    pub(crate) const fn depth(&self) -> usize {
+        self.depth
+    }
 }
 
 impl<'a> From<&'a LazyLocation<'_, '_>> for Location {
@@ -88,7 +105,8 @@ impl<'a> From<&'a LazyLocation<'_, '_>> for Location {
             }
         }
 
-        for segment in segments.iter().rev() {
+        let mut indices = Vec::new();
+        for (position, segment) in segments.iter().rev().enumerate() {
             buffer.push('/');
             match segment {
                 LocationSegment::Property(property) => {
@@ -97,11 +115,44 @@ impl<'a> From<&'a LazyLocation<'_, '_>> for Location {
                 LocationSegment::Index(idx) => {
                     let mut itoa_buffer = itoa::Buffer::new();
                     buffer.push_str(itoa_buffer.format(*idx));
+                    indices.push(position);
                 }
             }
         }
 
-        Location(Arc::new(buffer))
+        Location::from_parts(buffer, indices)
+    }
+}
+
+/// A segment of a [`Location`], as yielded by [`Location::typed_segments`].
+///
+/// Unlike [`Location::segments`], it tells array indices from object keys that look like
+/// numbers.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Segment<'a> {
+    /// Unescaped key within a JSON object.
+    Key(Cow<'a, str>),
+    /// Index within a JSON array.
+    Index(usize),
+}
+
+impl Segment<'_> {
+    /// Convert this segment into one that does not borrow from its [`Location`].
+    #[must_use]
+    pub fn into_owned(self) -> Segment<'static> {
+        match self {
+            Segment::Key(key) => Segment::Key(Cow::Owned(key.into_owned())),
+            Segment::Index(idx) => Segment::Index(idx),
+        }
+    }
+}
+
+impl fmt::Display for Segment<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Key(key) => f.write_str(key),
+            Segment::Index(idx) => f.write_str(itoa::Buffer::new().format(*idx)),
+        }
     }
 }
 
@@ -110,6 +161,7 @@ impl<'a> From<&'a Keyword> for LocationSegment<'a> {
         match value {
             Keyword::Buildin(k) => LocationSegment::Property(k.as_str()),
             Keyword::Custom(s) => LocationSegment::Property(s),
+            Keyword::Hoisted { keyword, .. } => LocationSegment::from(&**keyword),
         }
     }
 }
@@ -136,38 +188,189 @@ impl From<usize> for LocationSegment<'_> {
 }
 
 /// A cheap to clone JSON pointer that represents location with a JSON value.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Location(Arc<String>);
+///
+/// Cloning a `Location` shares its buffer instead of copying it. Locations are compared by
+/// their JSON pointer, regardless of whether a segment like `0` is an array index or an object
+/// key.
+///
+/// ```rust
+/// use jsonschema::paths::Location;
+///
+/// let location = Location::new().join("properties").join("a/b");
+/// assert_eq!(location.as_str(), "/properties/a~1b");
+/// assert_eq!(location.segments().collect::<Vec<_>>(), ["properties", "a/b"]);
+///
+/// let items: Location = ["items", "0"].into_iter().collect();
+/// assert_eq!(items, Location::new().join("items").join(0));
+/// ```
+#[derive(Clone)]
+pub struct Location(Arc<Pointer>);
+
+#[derive(Default)]
+struct Pointer {
+    /// The escaped JSON pointer.
+    string: String,
+    /// Positions of the segments that are array indices, in increasing order.
+    indices: Vec<usize>,
+}
 
 impl Location {
     /// Create a new, empty `Location`.
     pub fn new() -> Self {
-        Self(Arc::new(String::new()))
+        Self(Arc::default())
+    }
+    fn from_parts(string: String, indices: Vec<usize>) -> Self {
+        Self(Arc::new(Pointer { string, indices }))
+    }
+    /// Create a `Location` from a JSON pointer with already escaped segments within `document`.
+    ///
+    /// Segments pointing into arrays of `document` are indices, all others are keys.
+    pub(crate) fn from_escaped(pointer: &str, document: &Value) -> Self {
+        let mut indices = Vec::new();
+        let mut current = Some(document);
+        for (position, segment) in pointer.split('/').skip(1).enumerate() {
+            current = match current {
+                Some(Value::Array(items)) => {
+                    let item = segment.parse::<usize>().ok().and_then(|idx| items.get(idx));
+                    if item.is_some() {
+                        indices.push(position);
+                    }
+                    item
+                }
+                Some(Value::Object(object)) => {
+                    if segment.contains('~') {
+                        object.get(&segment.replace("~1", "/").replace("~0", "~"))
+                    } else {
+                        object.get(segment)
+                    }
+                }
+                _ => None,
+            };
+        }
+        Self::from_parts(pointer.to_string(), indices)
     }
-    pub(crate) fn join<'a>(&self, segment: impl Into<LocationSegment<'a>>) -> Self {
-        let parent = self.0.as_str();
+    /// Create a new `Location` with `segment` appended to this one.
+    #[must_use]
+    pub fn join<'a>(&self, segment: impl Into<LocationSegment<'a>>) -> Self {
+        let parent = self.as_str();
         match segment.into() {
             LocationSegment::Property(property) => {
                 let mut buffer = String::with_capacity(parent.len() + property.len() + 1);
                 buffer.push_str(parent);
                 buffer.push('/');
                 write_escaped_str(&mut buffer, property);
-                Self(Arc::new(buffer))
+                Self::from_parts(buffer, self.0.indices.clone())
             }
             LocationSegment::Index(idx) => {
                 let mut buffer = itoa::Buffer::new();
                 let segment = buffer.format(idx);
-                Self(Arc::new(format!("{parent}/{segment}")))
+                let mut indices = Vec::with_capacity(self.0.indices.len() + 1);
+                indices.extend_from_slice(&self.0.indices);
+                indices.push(self.depth());
+                Self::from_parts(format!("{parent}/{segment}"), indices)
             }
         }
     }
+    /// Number of segments in this location.
+    pub(crate) fn depth(&self) -> usize {
+        self.as_str().bytes().filter(|byte| *byte == b'/').count()
+    }
+    /// Replace the `from` prefix of this location with `to`.
+    ///
+    /// Returns `None` if this location is not within `from`.
+    pub(crate) fn rebase(&self, from: &Location, to: &Location) -> Option<Self> {
+        let rest = self.as_str().strip_prefix(from.as_str())?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+        let mut buffer = String::with_capacity(to.as_str().len() + rest.len());
+        buffer.push_str(to.as_str());
+        buffer.push_str(rest);
+        let (from_depth, to_depth) = (from.depth(), to.depth());
+        let mut indices = to.0.indices.clone();
+        indices.extend(
+            self.0
+                .indices
+                .iter()
+                .filter(|position| **position >= from_depth)
+                .map(|position| position - from_depth + to_depth),
+        );
+        Some(Self::from_parts(buffer, indices))
+    }
+    /// Iterate over the unescaped segments of this location.
+    ///
+    /// Array indices are yielded as strings, like in the JSON pointer the location represents.
+    pub fn segments(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.as_str().split('/').skip(1).map(unescape)
+    }
+    /// Iterate over the segments of this location, telling array indices from object keys.
+    ///
+    /// ```rust
+    /// use jsonschema::paths::Segment;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"additionalProperties": {"items": {"type": "string"}}});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let instance = json!({"0": ["a", 1]});
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(
+    ///     error.instance_path.typed_segments().collect::<Vec<_>>(),
+    ///     [Segment::Key("0".into()), Segment::Index(1)]
+    /// );
+    /// ```
+    pub fn typed_segments(&self) -> impl Iterator<Item = Segment<'_>> {
+        let mut indices = self.0.indices.iter().copied().peekable();
+        self.as_str()
+            .split('/')
+            .skip(1)
+            .enumerate()
+            .map(move |(position, segment)| {
+                if indices.next_if_eq(&position).is_some() {
+                    if let Ok(idx) = segment.parse() {
+                        return Segment::Index(idx);
+                    }
+                }
+                Segment::Key(unescape(segment))
+            })
+    }
+    /// Follow this location into `root`.
+    ///
+    /// Returns `None` if there is no value at this location, or if it points into an object with
+    /// an index or into an array with a key.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"items": {"minimum": 5}});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let instance = json!([7, 3]);
+    /// let error = validator.validate(&instance).expect_err("Should fail");
+    /// assert_eq!(error.instance_path.resolve(&instance), Some(&json!(3)));
+    /// ```
+    #[must_use]
+    pub fn resolve<'v>(&self, root: &'v Value) -> Option<&'v Value> {
+        self.typed_segments()
+            .try_fold(root, |value, segment| match (value, segment) {
+                (Value::Object(object), Segment::Key(key)) => object.get(key.as_ref()),
+                (Value::Array(items), Segment::Index(idx)) => items.get(idx),
+                _ => None,
+            })
+    }
     /// Get a string slice representing the location.
     pub fn as_str(&self) -> &str {
-        &self.0
+        &self.0.string
     }
     /// Get a byte slice representing the location.
     pub fn as_bytes(&self) -> &[u8] {
-        self.0.as_bytes()
+        self.as_str().as_bytes()
+    }
+}
+
+pub(crate) fn unescape(segment: &str) -> Cow<'_, str> {
+    if segment.contains('~') {
+        Cow::Owned(segment.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(segment)
     }
 }
 
@@ -215,6 +418,62 @@ impl Default for Location {
     }
 }
 
+impl fmt::Debug for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Location").field(&self.as_str()).finish()
+    }
+}
+
+impl PartialEq for Location {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Location {}
+
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Location {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for Location {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl<'a, S: Into<LocationSegment<'a>>> FromIterator<S> for Location {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        let mut buffer = String::new();
+        let mut indices = Vec::new();
+        for (position, segment) in iter.into_iter().enumerate() {
+            buffer.push('/');
+            match segment.into() {
+                LocationSegment::Property(property) => write_escaped_str(&mut buffer, property),
+                LocationSegment::Index(idx) => {
+                    buffer.push_str(itoa::Buffer::new().format(idx));
+                    indices.push(position);
+                }
+            }
+        }
+        Self::from_parts(buffer, indices)
+    }
+}
+
+impl serde::Serialize for Location {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
 impl fmt::Display for Location {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(self.as_str())
@@ -224,6 +483,7 @@ impl fmt::Display for Location {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
     use test_case::test_case;
 
     #[test]
@@ -280,4 +540,119 @@ mod tests {
         let loc = Location::new().join(segment);
         assert_eq!(loc.as_str(), expected);
     }
+
+    #[test_case("", &[]; "root")]
+    #[test_case("/a/0", &["a", "0"]; "property and index")]
+    #[test_case("/tilde~0/slash~1", &["tilde~", "slash/"]; "escaped")]
+    #[test_case("/~01", &["~1"]; "escaped tilde before one")]
+    #[test_case("/a//b", &["a", "", "b"]; "empty segment")]
+    fn test_location_segments(location: &str, expected: &[&str]) {
+        let location = Location::from_escaped(location, &Value::Null);
+        assert_eq!(location.segments().collect::<Vec<_>>(), expected);
+        assert_eq!(expected.iter().copied().collect::<Location>(), location);
+    }
+
+    #[test]
+    fn test_location_from_iter() {
+        let location: Location = [LocationSegment::from("items"), LocationSegment::from(1)]
+            .into_iter()
+            .collect();
+        assert_eq!(location.as_str(), "/items/1");
+    }
+
+    #[test]
+    fn test_location_typed_segments() {
+        let root = LazyLocation::new();
+        let key = root.push("0");
+        let index = key.push(1);
+        let location = Location::from(&index.push("a/b"));
+        assert_eq!(location.as_str(), "/0/1/a~1b");
+        assert_eq!(
+            location.typed_segments().collect::<Vec<_>>(),
+            [
+                Segment::Key("0".into()),
+                Segment::Index(1),
+                Segment::Key("a/b".into())
+            ]
+        );
+        let joined = Location::new().join("0").join(1).join("a/b");
+        assert!(location.typed_segments().eq(joined.typed_segments()));
+    }
+
+    #[test]
+    fn test_location_typed_segments_from_escaped() {
+        let document = json!({"allOf": [{"properties": {"0": {}}}]});
+        let location = Location::from_escaped("/allOf/0/properties/0", &document);
+        assert_eq!(
+            location.typed_segments().collect::<Vec<_>>(),
+            [
+                Segment::Key("allOf".into()),
+                Segment::Index(0),
+                Segment::Key("properties".into()),
+                Segment::Key("0".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_location_typed_segments_rebase() {
+        let location = Location::new().join("a").join(2).join("b").join(3);
+        let from = Location::new().join("a").join(2);
+        let to = Location::new().join(0).join("x").join("y");
+        let rebased = location.rebase(&from, &to).expect("Within `from`");
+        assert_eq!(
+            rebased.typed_segments().collect::<Vec<_>>(),
+            [
+                Segment::Index(0),
+                Segment::Key("x".into()),
+                Segment::Key("y".into()),
+                Segment::Key("b".into()),
+                Segment::Index(3)
+            ]
+        );
+    }
+
+    #[test_case(Location::new(), Some(&json!({"a": [1, {"b/c": 2}]})); "root")]
+    #[test_case(Location::new().join("a").join(1).join("b/c"), Some(&json!(2)); "nested")]
+    #[test_case(Location::new().join("a").join("1"), None; "key into array")]
+    #[test_case(Location::new().join(0), None; "index into object")]
+    #[test_case(Location::new().join("a").join(2), None; "missing")]
+    fn test_location_resolve(location: Location, expected: Option<&Value>) {
+        let root = json!({"a": [1, {"b/c": 2}]});
+        assert_eq!(location.resolve(&root), expected);
+    }
+
+    #[test]
+    fn test_segment_into_owned() {
+        let location = Location::new().join("a~b");
+        let segment = location
+            .typed_segments()
+            .next()
+            .expect("Has a segment")
+            .into_owned();
+        drop(location);
+        assert_eq!(segment, Segment::Key("a~b".into()));
+        assert_eq!(segment.to_string(), "a~b");
+    }
+
+    #[test]
+    fn test_location_clone_shares_buffer() {
+        let location = Location::new().join("property");
+        let cloned = location.clone();
+        assert!(Arc::ptr_eq(&location.0, &cloned.0));
+    }
+
+    #[test_case("/a/b", Some("/x/y/b"); "nested")]
+    #[test_case("/a", Some("/x/y"); "same")]
+    #[test_case("/ab", None; "not a segment boundary")]
+    #[test_case("/c", None; "outside")]
+    fn test_location_rebase(location: &str, expected: Option<&str>) {
+        let location = Location::from_escaped(location, &Value::Null);
+        let from = Location::new().join("a");
+        let to = Location::new().join("x").join("y");
+        assert_eq!(
+            location.rebase(&from, &to).as_ref().map(Location::as_str),
+            expected
+        );
+    }
 }
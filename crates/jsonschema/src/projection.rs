@@ -0,0 +1,473 @@
+//! Parts of instances that a schema can constrain.
+use std::{borrow::Cow, collections::BTreeMap, sync::Arc};
+
+use serde_json::Value;
+
+use crate::{
+    paths::{self, LazyLocation, Location, LocationSegment, Segment},
+    validator::ValidationScratch,
+    ValidationError, Validator,
+};
+
+/// Locations of an instance that compiled keywords may be applied to, as returned by
+/// [`Validator::constrained_paths`].
+///
+/// Values at other locations never make an instance invalid, so they do not have to be
+/// validated, or even parsed. The analysis is conservative: everything within a location is
+/// constrained if a keyword there may look at any value within it, like `const`, `enum`,
+/// `uniqueItems`, `patternProperties`, `propertyNames`, `unevaluatedProperties`,
+/// `unevaluatedItems`, custom keywords, and references that are recursive or compiled lazily.
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let validator = jsonschema::validator_for(&json!({
+///     "properties": {
+///         "id": {"type": "integer"},
+///         "tags": {"items": {"type": "string"}},
+///         "meta": {"const": {"version": 1}}
+///     },
+///     "additionalProperties": true
+/// })).expect("Invalid schema");
+/// let paths = validator.constrained_paths();
+/// assert_eq!(paths.patterns(), ["", "/id", "/meta/**", "/tags", "/tags/*"]);
+/// assert!(paths.contains("/tags/3"));
+/// assert!(paths.contains("/meta/version"));
+/// assert!(!paths.contains("/payload"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstrainedPaths {
+    /// `None` if no keyword was compiled at all
+    root: Option<PathNode>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct PathNode {
+    /// A keyword is applied to the value at this location
+    applied: bool,
+    /// Keywords may look at any value within this location
+    everything: bool,
+    /// Locations of properties or items, by their unescaped key or index
+    children: BTreeMap<String, PathNode>,
+    /// Location of any property or item
+    any: Option<Box<PathNode>>,
+}
+
+/// A segment of an instance location a keyword may be applied to.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    Key(String),
+    Any,
+}
+
+impl PathNode {
+    fn insert(&mut self, path: &[Step], everything: bool) {
+        if self.everything {
+            return;
+        }
+        match path.split_first() {
+            None if everything => {
+                *self = PathNode {
+                    applied: true,
+                    everything: true,
+                    ..PathNode::default()
+                };
+            }
+            None => self.applied = true,
+            Some((Step::Key(key), rest)) => self
+                .children
+                .entry(key.clone())
+                .or_default()
+                .insert(rest, everything),
+            Some((Step::Any, rest)) => self
+                .any
+                .get_or_insert_with(Box::default)
+                .insert(rest, everything),
+        }
+    }
+    fn matches(&self, tokens: &[Cow<'_, str>]) -> bool {
+        if self.everything {
+            return true;
+        }
+        match tokens.split_first() {
+            None => true,
+            Some((token, rest)) => {
+                self.children
+                    .get(token.as_ref())
+                    .is_some_and(|child| child.matches(rest))
+                    || self.any.as_ref().is_some_and(|any| any.matches(rest))
+            }
+        }
+    }
+    fn collect_patterns(&self, path: &mut String, patterns: &mut Vec<String>) {
+        if self.everything {
+            patterns.push(format!("{path}/**"));
+            return;
+        }
+        if self.applied {
+            patterns.push(path.clone());
+        }
+        let len = path.len();
+        for (key, child) in &self.children {
+            path.push('/');
+            path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+            child.collect_patterns(path, patterns);
+            path.truncate(len);
+        }
+        if let Some(any) = &self.any {
+            path.push_str("/*");
+            any.collect_patterns(path, patterns);
+            path.truncate(len);
+        }
+    }
+}
+
+impl ConstrainedPaths {
+    /// Analyze the keyword locations of a validator, sorted by their pointers.
+    pub(crate) fn new(keywords: &[(Location, String)]) -> ConstrainedPaths {
+        let mut root: Option<PathNode> = None;
+        for (location, keyword) in keywords {
+            let (path, everything) = instance_path(location, keyword, keywords);
+            root.get_or_insert_with(PathNode::default)
+                .insert(&path, everything);
+        }
+        ConstrainedPaths { root }
+    }
+    /// Whether keywords may be applied to the value at `pointer`, or to any value within it.
+    ///
+    /// Returns `false` if `pointer` is not a JSON pointer.
+    #[must_use]
+    pub fn contains(&self, pointer: &str) -> bool {
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return false;
+        }
+        let tokens: Vec<_> = pointer.split('/').skip(1).map(paths::unescape).collect();
+        self.matches(&tokens)
+    }
+    /// Instance locations that keywords may be applied to, as JSON pointers where `*` stands
+    /// for any property or item and a trailing `**` for the location and everything within it.
+    #[must_use]
+    pub fn patterns(&self) -> Vec<String> {
+        let mut patterns = Vec::new();
+        if let Some(root) = &self.root {
+            root.collect_patterns(&mut String::new(), &mut patterns);
+        }
+        patterns
+    }
+    pub(crate) fn contains_location(&self, location: &LazyLocation) -> bool {
+        let mut tokens = Vec::new();
+        let mut current = location;
+        while let Some(parent) = current.parent {
+            tokens.push(match &current.segment {
+                LocationSegment::Property(property) => Cow::Borrowed(*property),
+                LocationSegment::Index(idx) => Cow::Owned(idx.to_string()),
+            });
+            current = parent;
+        }
+        tokens.reverse();
+        self.matches(&tokens)
+    }
+    fn matches(&self, tokens: &[Cow<'_, str>]) -> bool {
+        self.root.as_ref().is_some_and(|root| root.matches(tokens))
+    }
+}
+
+/// The instance location the keyword at `location` is applied to, and whether it may look at
+/// every value within it.
+fn instance_path(
+    location: &Location,
+    keyword: &str,
+    keywords: &[(Location, String)],
+) -> (Vec<Step>, bool) {
+    let segments: Vec<_> = location.typed_segments().collect();
+    let mut path = Vec::new();
+    let mut idx = 0;
+    // The last segment is the keyword itself
+    while idx + 1 < segments.len() {
+        let Segment::Key(applicator) = &segments[idx] else {
+            return (path, true);
+        };
+        let next = &segments[idx + 1];
+        match (applicator.as_ref(), next) {
+            ("properties", Segment::Key(key)) => {
+                path.push(Step::Key(key.to_string()));
+                idx += 2;
+            }
+            ("items" | "prefixItems", Segment::Index(item)) => {
+                path.push(Step::Key(item.to_string()));
+                idx += 2;
+            }
+            ("items" | "additionalItems" | "additionalProperties" | "contains", _) => {
+                path.push(Step::Any);
+                idx += 1;
+            }
+            ("allOf" | "anyOf" | "oneOf" | "dependentSchemas" | "dependencies", _) => idx += 2,
+            ("not" | "if" | "then" | "else" | "$ref" | "$dynamicRef" | "$recursiveRef", _) => {
+                idx += 1;
+            }
+            // `patternProperties`, `propertyNames`, `unevaluated*` and custom keywords
+            _ => return (path, true),
+        }
+    }
+    let everything = match keyword {
+        "$ref" | "$dynamicRef" | "$recursiveRef" => !has_subschema_keywords(location, keywords),
+        "type"
+        | "nullable"
+        | "maximum"
+        | "minimum"
+        | "exclusiveMaximum"
+        | "exclusiveMinimum"
+        | "multipleOf"
+        | "maxLength"
+        | "minLength"
+        | "pattern"
+        | "format"
+        | "contentMediaType"
+        | "contentEncoding"
+        | "maxItems"
+        | "minItems"
+        | "maxProperties"
+        | "minProperties"
+        | "required"
+        | "dependentRequired"
+        | "dependencies"
+        | "dependentSchemas"
+        | "properties"
+        | "additionalProperties"
+        | "items"
+        | "prefixItems"
+        | "additionalItems"
+        | "contains"
+        | "allOf"
+        | "anyOf"
+        | "oneOf"
+        | "not"
+        | "if" => false,
+        _ => true,
+    };
+    (path, everything)
+}
+
+/// Whether keywords were compiled within the target of the reference at `location`. Targets of
+/// recursive and lazily compiled references are compiled on first use, so nothing is known
+/// about them.
+fn has_subschema_keywords(location: &Location, keywords: &[(Location, String)]) -> bool {
+    let prefix = format!("{}/", location.as_str());
+    let start = keywords.partition_point(|(other, _)| other.as_str() < prefix.as_str());
+    keywords
+        .get(start)
+        .is_some_and(|(other, _)| other.as_str().starts_with(&prefix))
+}
+
+impl Validator {
+    /// Locations of an instance that keywords of this validator may be applied to. See
+    /// [`ConstrainedPaths`].
+    #[must_use]
+    pub fn constrained_paths(&self) -> &ConstrainedPaths {
+        &self.constrained_paths
+    }
+
+    /// Validate `instance` like [`Validator::validate`], but without applying subschemas to
+    /// values outside of [`Validator::constrained_paths`].
+    ///
+    /// The result is the same as validating the whole instance, as no keyword can reject the
+    /// skipped values.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first validation error found.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "properties": {"id": {"type": "integer"}},
+    ///     "additionalProperties": {}
+    /// })).expect("Invalid schema");
+    /// assert!(validator
+    ///     .validate_projection(&json!({"id": 1, "payload": {"large": [1, 2, 3]}}))
+    ///     .is_ok());
+    /// let instance = json!({"id": "1", "payload": null});
+    /// let error = validator
+    ///     .validate_projection(&instance)
+    ///     .expect_err("Invalid id");
+    /// assert_eq!(error.instance_path.as_str(), "/id");
+    /// ```
+    pub fn validate_projection<'i>(&self, instance: &'i Value) -> Result<(), ValidationError<'i>> {
+        let mut scratch = ValidationScratch::new();
+        scratch.projection = Some(Arc::clone(&self.constrained_paths));
+        let mut errors = Vec::new();
+        self.validate_into_with(instance, &mut errors, &mut scratch);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{Draft, Keyword, ValidationError};
+
+    #[test_case(&json!({}), &[]; "empty")]
+    #[test_case(&json!({"type": "object"}), &[""]; "root")]
+    #[test_case(
+        &json!({"properties": {"a": {"properties": {"b": {"minimum": 1}}}}}),
+        &["", "/a", "/a/b"];
+        "nested properties"
+    )]
+    #[test_case(
+        &json!({"properties": {"a/b": {"type": "string"}}}),
+        &["", "/a~1b"];
+        "escaped"
+    )]
+    #[test_case(
+        &json!({"additionalProperties": {"items": {"type": "string"}}}),
+        &["", "/*", "/*/*"];
+        "wildcards"
+    )]
+    #[test_case(
+        &json!({"prefixItems": [{"type": "string"}], "contains": {"type": "integer"}}),
+        &["", "/0", "/*"];
+        "prefix items"
+    )]
+    #[test_case(
+        &json!({"anyOf": [{"properties": {"a": {"type": "string"}}}, {"required": ["b"]}]}),
+        &["", "/a"];
+        "in place applicators"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"enum": [{"b": 1}]}}}),
+        &["", "/a/**"];
+        "enum"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"patternProperties": {"^x": {"type": "string"}}}}}),
+        &["", "/a/**"];
+        "pattern properties"
+    )]
+    #[test_case(
+        &json!({"propertyNames": {"maxLength": 3}}),
+        &["/**"];
+        "property names"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"type": "string"}}, "unevaluatedProperties": false}),
+        &["/**"];
+        "unevaluated properties"
+    )]
+    #[test_case(
+        &json!({
+            "properties": {"head": {"$ref": "#/$defs/node"}},
+            "$defs": {"node": {"properties": {"next": {"$ref": "#/$defs/node"}}}}
+        }),
+        &["", "/head", "/head/next/**"];
+        "recursive reference"
+    )]
+    #[test_case(
+        &json!({
+            "properties": {"a": {"$ref": "#/$defs/name"}},
+            "$defs": {"name": {"type": "string"}}
+        }),
+        &["", "/a"];
+        "reference"
+    )]
+    fn patterns(schema: &Value, expected: &[&str]) {
+        let validator = crate::options()
+            .with_draft(Draft::Draft202012)
+            .build(schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.constrained_paths().patterns(), expected);
+    }
+
+    #[test]
+    fn lazy_references() {
+        let validator = crate::options()
+            .with_lazy_references(true)
+            .build(&json!({
+                "properties": {"a": {"$ref": "#/$defs/name"}},
+                "$defs": {"name": {"type": "string"}}
+            }))
+            .expect("Invalid schema");
+        assert_eq!(validator.constrained_paths().patterns(), ["", "/a/**"]);
+    }
+
+    #[test]
+    fn custom_keyword() {
+        struct Anything;
+
+        impl Keyword for Anything {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &crate::paths::LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                Ok(())
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                true
+            }
+        }
+
+        let validator = crate::options()
+            .with_keyword("x-anything", |_, _, _| Ok(Box::new(Anything)))
+            .build(&json!({"properties": {"a": {"x-anything": true}}}))
+            .expect("Invalid schema");
+        assert_eq!(validator.constrained_paths().patterns(), ["", "/a/**"]);
+    }
+
+    #[test_case("", true)]
+    #[test_case("/id", true)]
+    #[test_case("/id/x", false)]
+    #[test_case("/tags/0", true)]
+    #[test_case("/tags/0/x", false)]
+    #[test_case("/meta/a/b", true)]
+    #[test_case("/a~1b", true)]
+    #[test_case("/other", false)]
+    #[test_case("id", false; "not a pointer")]
+    fn contains(pointer: &str, expected: bool) {
+        let validator = crate::validator_for(&json!({
+            "properties": {
+                "id": {"type": "integer"},
+                "tags": {"items": {"type": "string"}},
+                "meta": {"uniqueItems": true},
+                "a/b": {"type": "string"}
+            }
+        }))
+        .expect("Invalid schema");
+        assert_eq!(validator.constrained_paths().contains(pointer), expected);
+    }
+
+    #[test_case(&json!({"id": 1, "tags": ["a"], "extra": {"deep": [1, {}]}}))]
+    #[test_case(&json!({"id": "1", "tags": ["a"]}))]
+    #[test_case(&json!({"id": 1, "tags": ["a", 1]}))]
+    #[test_case(&json!({"id": 1, "meta": {"unknown": 1}}))]
+    #[test_case(&json!([1, 2]))]
+    fn same_as_validate(instance: &Value) {
+        let validator = crate::validator_for(&json!({
+            "properties": {
+                "id": {"type": "integer"},
+                "tags": {"items": {"type": "string"}},
+                "meta": {"additionalProperties": false, "properties": {"version": {}}}
+            },
+            "additionalProperties": {}
+        }))
+        .expect("Invalid schema");
+        let expected = validator.validate(instance).err().map(|error| {
+            (
+                error.instance_path.to_string(),
+                error.schema_path.to_string(),
+            )
+        });
+        let projected = validator.validate_projection(instance).err().map(|error| {
+            (
+                error.instance_path.to_string(),
+                error.schema_path.to_string(),
+            )
+        });
+        assert_eq!(projected, expected);
+    }
+}
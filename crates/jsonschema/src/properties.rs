@@ -1,6 +1,5 @@
-use crate::{compiler, ecma, node::SchemaNode, paths::Location, validator::Validate as _};
+use crate::{compiler, ecma, node::SchemaNode, regex::Regex, validator::Validate as _, value};
 use ahash::AHashMap;
-use fancy_regex::Regex;
 use serde_json::{Map, Value};
 
 use crate::ValidationError;
@@ -102,6 +101,24 @@ where
     })
 }
 
+pub(crate) fn are_property_values_valid<M, F>(
+    prop_map: &M,
+    instance: &dyn value::Value,
+    check: F,
+) -> bool
+where
+    M: PropertiesValidatorsMap,
+    F: Fn(&dyn value::Value) -> bool,
+{
+    instance.object_iter().all(|(property, instance)| {
+        if let Some(validator) = prop_map.get_validator(property) {
+            validator.is_valid_value(instance)
+        } else {
+            check(instance)
+        }
+    })
+}
+
 /// Create a vector of pattern-validators pairs.
 #[inline]
 pub(crate) fn compile_patterns<'a>(
@@ -112,17 +129,16 @@ pub(crate) fn compile_patterns<'a>(
     let mut compiled_patterns = Vec::with_capacity(obj.len());
     for (pattern, subschema) in obj {
         let pctx = kctx.new_at_location(pattern.as_str());
+        ctx.record_pattern(pattern);
         if let Ok(Ok(compiled_pattern)) =
             ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern))
         {
             let node = compiler::compile(&pctx, pctx.as_resource_ref(subschema))?;
             compiled_patterns.push((compiled_pattern, node));
         } else {
-            return Err(ValidationError::format(
-                Location::new(),
-                kctx.location().clone(),
-                subschema,
-                "regex",
+            return Err(ValidationError::invalid_pattern(
+                pctx.location().clone(),
+                pattern,
             ));
         }
     }
@@ -130,23 +146,23 @@ pub(crate) fn compile_patterns<'a>(
 }
 
 macro_rules! compile_dynamic_prop_map_validator {
-    ($validator:tt, $properties:ident, $( $arg:expr ),* $(,)*) => {{
+    ($validator:tt, $properties:ident, $ctx:expr, $( $arg:expr ),* $(,)*) => {{
         if let Value::Object(map) = $properties {
             if map.len() < 40 {
                 Some($validator::<SmallValidatorsMap>::compile(
-                    map, $($arg, )*
+                    map, $ctx, $($arg, )*
                 ))
             } else {
                 Some($validator::<BigValidatorsMap>::compile(
-                    map, $($arg, )*
+                    map, $ctx, $($arg, )*
                 ))
             }
         } else {
-            Some(Err(ValidationError::custom(
-                Location::new(),
+            Some(Err(ValidationError::single_type_error(
                 Location::new(),
+                $ctx.location().join("properties"),
                 $properties,
-                "Unexpected type",
+                PrimitiveType::Object,
             )))
         }
     }};
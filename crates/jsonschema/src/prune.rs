@@ -0,0 +1,437 @@
+//! Removal of definitions that a schema never uses.
+use ahash::{AHashMap, AHashSet};
+use referencing::{Draft, Registry, Resolver, Retrieve, Uri};
+use serde_json::Value;
+
+use crate::{compiler::DEFAULT_ROOT_URL, paths::Location, walk};
+
+/// Remove the `$defs` and `definitions` members of `schema` that can not be reached from its
+/// root, e.g. before publishing it. Returns the pruned schema and the sorted locations of the
+/// removed members.
+///
+/// Reachability follows all subschemas except for definitions, and the targets of `$ref`,
+/// `$dynamicRef` and `$recursiveRef`, whether they are written as JSON Pointers, anchors or
+/// `$id` of embedded resources. As the target of `$dynamicRef` and `$recursiveRef` depends on
+/// the dynamic scope, every subschema with a matching `$dynamicAnchor` or a `$recursiveAnchor`
+/// is reachable through them. A member is kept whole if anything inside of it is reachable.
+/// References to other documents are not followed, so definitions used only by them are
+/// removed.
+///
+/// When reachability can not be determined, the schema is returned unchanged. That is the
+/// case if a subschema declares an unknown `$schema`, whose keywords may hold references, or if
+/// a reference within the schema does not resolve to a subschema. Subschemas equal to the
+/// target of an anchor are all kept, as they can not be told apart.
+///
+/// ```rust
+/// use serde_json::json;
+/// use jsonschema::Draft;
+///
+/// let schema = json!({
+///     "properties": {"price": {"$ref": "#/$defs/positive"}},
+///     "$defs": {
+///         "positive": {"minimum": 0},
+///         "legacy": {"$ref": "#/$defs/positive"}
+///     }
+/// });
+/// let (pruned, removed) = jsonschema::prune(&schema, Draft::Draft202012);
+/// assert_eq!(
+///     pruned,
+///     json!({
+///         "properties": {"price": {"$ref": "#/$defs/positive"}},
+///         "$defs": {"positive": {"minimum": 0}}
+///     })
+/// );
+/// assert_eq!(removed[0].as_str(), "/$defs/legacy");
+/// ```
+#[must_use]
+pub fn prune(schema: &Value, draft: Draft) -> (Value, Vec<Location>) {
+    let mut pruned = schema.clone();
+    let Some(removed) = unreachable_definitions(schema, draft) else {
+        return (pruned, Vec::new());
+    };
+    for location in &removed {
+        let (parent, name) = location
+            .as_str()
+            .rsplit_once('/')
+            .expect("Definitions are not at the root");
+        if let Some(Value::Object(definitions)) = pruned.pointer_mut(parent) {
+            definitions.remove(&name.replace("~1", "/").replace("~0", "~"));
+        }
+    }
+    (pruned, removed)
+}
+
+/// Stands in for documents outside of the pruned schema, which are never walked.
+struct External;
+
+impl Retrieve for External {
+    fn retrieve(&self, _: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Value::Bool(true))
+    }
+}
+
+/// A subschema of the pruned schema.
+struct Subschema<'a> {
+    schema: &'a Value,
+    location: Location,
+    draft: Draft,
+    resolver: Resolver<'a>,
+}
+
+/// Locations of the outermost definitions that are not reachable from the root, or `None` if
+/// that can not be determined.
+fn unreachable_definitions(schema: &Value, draft: Draft) -> Option<Vec<Location>> {
+    let draft = draft.detect(schema).ok()?;
+    let registry = Registry::options()
+        .draft(draft)
+        .retriever(Box::new(External))
+        .try_new(DEFAULT_ROOT_URL, draft.create_resource(schema.clone()))
+        .ok()?;
+    let resolver = registry.try_resolver(DEFAULT_ROOT_URL).ok()?;
+    // Resolved targets are compared by address, so everything is looked up in the registry's copy
+    let root = resolver.lookup("").ok()?.contents();
+    let mut subschemas = AHashMap::new();
+    index(Location::new(), root, draft, &resolver, &mut subschemas)?;
+    let documents: AHashSet<Uri<String>> = subschemas
+        .values()
+        .map(|subschema| walk::document_of(&subschema.resolver.base_uri()))
+        .collect();
+
+    let mut reachable = AHashSet::new();
+    let mut stack = vec![root];
+    while let Some(schema) = stack.pop() {
+        if !reachable.insert(schema as *const Value) {
+            continue;
+        }
+        let Value::Object(object) = schema else {
+            continue;
+        };
+        let Subschema {
+            location,
+            draft,
+            resolver,
+            ..
+        } = &subschemas[&(schema as *const Value)];
+        let definitions: AHashSet<*const Value> = ["$defs", "definitions"]
+            .into_iter()
+            .filter_map(|keyword| object.get(keyword).and_then(Value::as_object))
+            .flat_map(|definitions| definitions.values().map(|value| value as *const Value))
+            .collect();
+        stack.extend(
+            walk::subschemas(location, schema, *draft)
+                .into_iter()
+                .map(|(_, subschema)| subschema)
+                .filter(|subschema| !definitions.contains(&(*subschema as *const Value))),
+        );
+        for keyword in ["$ref", "$dynamicRef", "$recursiveRef"] {
+            let Some(reference) = object.get(keyword).and_then(Value::as_str) else {
+                continue;
+            };
+            let uri = resolver
+                .resolve_against(&resolver.base_uri().borrow(), reference)
+                .ok()?;
+            if !documents.contains(&walk::document_of(&uri)) {
+                continue;
+            }
+            let resolved = resolver.lookup(reference).ok()?;
+            let target = resolved.contents();
+            if subschemas.contains_key(&(target as *const Value)) {
+                stack.push(target);
+            } else {
+                // Anchors and embedded resources resolve to copies made by the registry
+                let document = walk::document_of(&resolved.resolver().base_uri());
+                let found = stack.len();
+                stack.extend(
+                    subschemas
+                        .values()
+                        .filter(|subschema| {
+                            subschema.schema == target
+                                && walk::document_of(&subschema.resolver.base_uri()) == document
+                        })
+                        .map(|subschema| subschema.schema),
+                );
+                if stack.len() == found {
+                    return None;
+                }
+            }
+            // Anything the reference may resolve to in a different dynamic scope
+            let fragment = uri.fragment().map_or("", |fragment| fragment.as_str());
+            let (anchor, expected) = match keyword {
+                "$dynamicRef" if !fragment.is_empty() && !fragment.starts_with('/') => {
+                    ("$dynamicAnchor", Value::from(fragment))
+                }
+                "$recursiveRef" => ("$recursiveAnchor", Value::Bool(true)),
+                _ => continue,
+            };
+            stack.extend(
+                subschemas
+                    .values()
+                    .map(|subschema| subschema.schema)
+                    .filter(|subschema| subschema.get(anchor) == Some(&expected)),
+            );
+        }
+    }
+
+    let reachable: Vec<&Location> = reachable
+        .iter()
+        .map(|schema| &subschemas[schema].location)
+        .collect();
+    let mut candidates: Vec<&Location> = subschemas
+        .values()
+        .map(|subschema| &subschema.location)
+        .filter(|location| is_definition(root, location.as_str(), &subschemas))
+        .collect();
+    candidates.sort();
+    let mut removed: Vec<Location> = Vec::new();
+    for candidate in candidates {
+        let is_within = |location: &Location| {
+            location
+                .as_str()
+                .strip_prefix(candidate.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+        let is_nested = removed.iter().any(|outer| {
+            candidate
+                .as_str()
+                .strip_prefix(outer.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+        });
+        if !is_nested && !reachable.iter().any(|location| is_within(location)) {
+            removed.push(candidate.clone());
+        }
+    }
+    Some(removed)
+}
+
+/// Whether the subschema at `location` is a member of `$defs` or `definitions` of another
+/// subschema, rather than e.g. of a property named `$defs`.
+fn is_definition(
+    root: &Value,
+    location: &str,
+    subschemas: &AHashMap<*const Value, Subschema<'_>>,
+) -> bool {
+    let Some((container, _)) = location.rsplit_once('/') else {
+        return false;
+    };
+    match container.rsplit_once('/') {
+        Some((parent, "$defs" | "definitions")) => root
+            .pointer(parent)
+            .is_some_and(|parent| subschemas.contains_key(&(parent as *const Value))),
+        _ => false,
+    }
+}
+
+/// Record all subschemas of `schema` together with their drafts and resolvers.
+fn index<'a>(
+    location: Location,
+    schema: &'a Value,
+    draft: Draft,
+    resolver: &Resolver<'a>,
+    subschemas: &mut AHashMap<*const Value, Subschema<'a>>,
+) -> Option<()> {
+    let draft = draft.detect(schema).ok()?;
+    let resolver = resolver
+        .in_subresource(draft.create_resource_ref(schema))
+        .ok()?;
+    for (location, subschema) in walk::subschemas(&location, schema, draft) {
+        index(location, subschema, draft, &resolver, subschemas)?;
+    }
+    subschemas.insert(
+        schema as *const Value,
+        Subschema {
+            schema,
+            location,
+            draft,
+            resolver,
+        },
+    );
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::prune;
+    use crate::Draft;
+
+    #[test_case(
+        &json!({"properties": {"a": {"$ref": "#/$defs/used"}}, "$defs": {"used": {}, "unused": {}}}),
+        Draft::Draft202012,
+        &["/$defs/unused"];
+        "unused"
+    )]
+    #[test_case(
+        &json!({"$ref": "#/$defs/a", "$defs": {"a": {"$ref": "#/$defs/b"}, "b": {}, "c": {"$ref": "#/$defs/b"}}}),
+        Draft::Draft202012,
+        &["/$defs/c"];
+        "transitive"
+    )]
+    #[test_case(
+        &json!({"$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"$ref": "#/$defs/a"}}}),
+        Draft::Draft202012,
+        &["/$defs/a", "/$defs/b"];
+        "unreachable cycle"
+    )]
+    #[test_case(
+        &json!({"$ref": "#/$defs/a/properties/b", "$defs": {"a": {"properties": {"b": {}}}}}),
+        Draft::Draft202012,
+        &[];
+        "pointer into definition"
+    )]
+    #[test_case(
+        &json!({"$ref": "#/$defs/a/$defs/b", "$defs": {"a": {"$defs": {"b": {}, "c": {}}}}}),
+        Draft::Draft202012,
+        &["/$defs/a/$defs/c"];
+        "nested definitions"
+    )]
+    #[test_case(
+        &json!({"$defs": {"a": {"$defs": {"b": {}}}}}),
+        Draft::Draft202012,
+        &["/$defs/a"];
+        "outermost only"
+    )]
+    #[test_case(
+        &json!({"items": {"$ref": "#item"}, "$defs": {"a": {"$anchor": "item"}, "b": {"$anchor": "other"}}}),
+        Draft::Draft202012,
+        &["/$defs/b"];
+        "anchor"
+    )]
+    #[test_case(
+        &json!({
+            "$id": "https://example.com/root",
+            "items": {"$ref": "item"},
+            "$defs": {"a": {"$id": "item"}, "b": {"$id": "other"}}
+        }),
+        Draft::Draft202012,
+        &["/$defs/b"];
+        "embedded resource"
+    )]
+    #[test_case(
+        &json!({
+            "$dynamicAnchor": "node",
+            "items": {"$dynamicRef": "#node"},
+            "$defs": {"a": {"$dynamicAnchor": "node"}, "b": {"$anchor": "node2"}}
+        }),
+        Draft::Draft202012,
+        &["/$defs/b"];
+        "dynamic anchor"
+    )]
+    #[test_case(
+        &json!({
+            "$recursiveAnchor": true,
+            "items": {"$recursiveRef": "#"},
+            "$defs": {"a": {"$recursiveAnchor": true}, "b": {}}
+        }),
+        Draft::Draft201909,
+        &["/$defs/b"];
+        "recursive anchor"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"$ref": "#/definitions/a"}}, "definitions": {"a": {}, "b": {}}}),
+        Draft::Draft7,
+        &["/definitions/b"];
+        "draft 7"
+    )]
+    #[test_case(
+        &json!({"properties": {"$defs": {"properties": {"a": {}}}}}),
+        Draft::Draft202012,
+        &[];
+        "property named like definitions"
+    )]
+    #[test_case(
+        &json!({"$ref": "https://example.com/other#/$defs/a", "$defs": {"a": {}}}),
+        Draft::Draft202012,
+        &["/$defs/a"];
+        "other document"
+    )]
+    #[test_case(
+        &json!({"$defs": {"a~/b": {}}}),
+        Draft::Draft202012,
+        &["/$defs/a~0~1b"];
+        "escaped name"
+    )]
+    fn removed(schema: &Value, draft: Draft, expected: &[&str]) {
+        let (pruned, removed) = prune(schema, draft);
+        let removed: Vec<_> = removed.iter().map(|location| location.as_str()).collect();
+        assert_eq!(removed, expected);
+        for location in expected {
+            assert!(schema.pointer(location).is_some());
+            assert!(pruned.pointer(location).is_none());
+        }
+    }
+
+    #[test_case(
+        &json!({"$defs": {"a": {"$schema": "https://example.com/dialect", "x-ref": "#/$defs/b"}, "b": {}}});
+        "unknown dialect"
+    )]
+    #[test_case(&json!({"$ref": "#/$defs/missing", "$defs": {"a": {}}}); "unresolvable reference")]
+    #[test_case(&json!({"$ref": "#/$defs", "$defs": {"a": {}}}); "reference to non-schema")]
+    fn unchanged(schema: &Value) {
+        let (pruned, removed) = prune(schema, Draft::Draft202012);
+        assert_eq!(&pruned, schema);
+        assert!(removed.is_empty());
+    }
+
+    #[test_case(&json!({
+        "type": "object",
+        "properties": {
+            "name": {"$ref": "#/$defs/name"},
+            "tags": {"type": "array", "items": {"$ref": "#tag"}},
+            "children": {"type": "array", "items": {"$ref": "#"}}
+        },
+        "$defs": {
+            "name": {"type": "string", "minLength": 1},
+            "tag": {"$anchor": "tag", "enum": ["a", "b"]},
+            "unused": {"type": "integer"},
+            "legacy": {"$ref": "#/$defs/name", "maxLength": 3}
+        }
+    }), Draft::Draft202012; "draft 2020-12")]
+    #[test_case(&json!({
+        "type": "object",
+        "properties": {
+            "name": {"$ref": "#/definitions/name"},
+            "tags": {"type": "array", "items": {"$ref": "#/definitions/tag"}}
+        },
+        "definitions": {
+            "name": {"type": "string", "minLength": 1},
+            "tag": {"enum": ["a", "b"]},
+            "unused": {"type": "integer"}
+        }
+    }), Draft::Draft7; "draft 7")]
+    fn same_validation(schema: &Value, draft: Draft) {
+        let (pruned, removed) = prune(schema, draft);
+        assert!(!removed.is_empty());
+        let original = crate::options()
+            .with_draft(draft)
+            .build(schema)
+            .expect("Valid schema");
+        let pruned = crate::options()
+            .with_draft(draft)
+            .build(&pruned)
+            .expect("Valid schema");
+        for instance in [
+            json!({}),
+            json!({"name": "abc"}),
+            json!({"name": ""}),
+            json!({"name": 1}),
+            json!({"name": "abcdef"}),
+            json!({"tags": ["a", "b"]}),
+            json!({"tags": ["c"]}),
+            json!({"children": [{"name": "a"}, {"name": ""}]}),
+            json!({"children": [{"tags": ["b"]}]}),
+            json!(null),
+            json!([1]),
+        ] {
+            assert_eq!(original.is_valid(&instance), pruned.is_valid(&instance));
+            let errors = |validator: &crate::Validator| {
+                validator
+                    .iter_errors(&instance)
+                    .map(|error| (error.instance_path.to_string(), error.to_string()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(errors(&original), errors(&pruned));
+        }
+    }
+}
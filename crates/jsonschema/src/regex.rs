@@ -0,0 +1,33 @@
+//! Regular expression engine behind `pattern`, `patternProperties` and friends.
+//!
+//! With the `fancy-regex` feature (enabled by default) patterns may use look-around and
+//! backreferences. Without it, patterns are matched by `regex-automata` in linear time and such
+//! constructs are rejected when the schema is compiled.
+#[cfg(feature = "fancy-regex")]
+pub(crate) use fancy_regex::Regex;
+
+#[cfg(not(feature = "fancy-regex"))]
+pub(crate) use fallback::Regex;
+
+#[cfg(not(feature = "fancy-regex"))]
+mod fallback {
+    use std::convert::Infallible;
+
+    use regex_automata::meta;
+
+    #[derive(Debug, Clone)]
+    pub(crate) struct Regex(meta::Regex);
+
+    impl Regex {
+        pub(crate) fn new(pattern: &str) -> Result<Regex, Box<meta::BuildError>> {
+            meta::Regex::new(pattern).map(Regex).map_err(Box::new)
+        }
+
+        /// Mirrors `fancy_regex::Regex::is_match`, which fails when the backtrack limit is hit.
+        /// Matching here never fails.
+        #[allow(clippy::unnecessary_wraps)]
+        pub(crate) fn is_match(&self, text: &str) -> Result<bool, Infallible> {
+            Ok(self.0.is_match(text))
+        }
+    }
+}
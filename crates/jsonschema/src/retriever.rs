@@ -9,7 +9,11 @@ impl Retrieve for DefaultRetriever {
     fn retrieve(&self, uri: &Uri<&str>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
         #[cfg(target_arch = "wasm32")]
         {
-            Err("External references are not supported in WASM".into())
+            Err(format!(
+                "Can not retrieve `{}`: external references are not supported in WASM without a custom retriever",
+                uri.as_str()
+            )
+            .into())
         }
         #[cfg(not(target_arch = "wasm32"))]
         match uri.scheme().as_str() {
@@ -45,7 +49,14 @@ impl Retrieve for DefaultRetriever {
                     Err("`resolve-file` feature or a custom resolver is required to resolve external schemas via files".into())
                 }
             }
-            scheme => Err(format!("Unknown scheme {scheme}").into()),
+            // URNs only name resources, there is nothing to retrieve them from
+            "urn" => Err(Box::new(referencing::Error::DocumentNotRegistered {
+                uri: uri.as_str().to_string(),
+                known_similar: Vec::new(),
+            })),
+            scheme => Err(Box::new(referencing::Error::UnsupportedScheme {
+                scheme: scheme.to_string(),
+            })),
         }
     }
 }
@@ -163,7 +174,10 @@ mod tests {
         assert!(result.is_err());
         let error = result.unwrap_err().to_string();
         #[cfg(not(target_arch = "wasm32"))]
-        assert!(error.contains("Unknown scheme"));
+        assert_eq!(
+            error,
+            "Retrieving resources with the 'unknown-schema' URI scheme is not supported"
+        );
         #[cfg(target_arch = "wasm32")]
         assert!(error.contains("External references are not supported in WASM"));
     }
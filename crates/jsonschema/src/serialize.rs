@@ -0,0 +1,619 @@
+//! Validation of any [`serde::Serialize`] type without building a [`serde_json::Value`].
+//!
+//! Serialization events are collected into a compact tree which implements [`value::Value`],
+//! so the compiled schema can check it directly. Objects are stored as sorted vectors that borrow
+//! struct field names, which is cheaper to build than `serde_json::Map` with owned keys.
+use crate::{primitive_type::PrimitiveType, value};
+use serde::ser::{self, Error as _, Serialize};
+use std::borrow::Cow;
+
+#[derive(Debug)]
+pub(crate) enum Node {
+    Null,
+    Bool(bool),
+    PosInt(u64),
+    NegInt(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Node>),
+    /// Entries sorted by key. Struct field names are borrowed, as they are always `'static`.
+    Object(Vec<(Cow<'static, str>, Node)>),
+}
+
+impl value::Value for Node {
+    fn primitive_type(&self) -> PrimitiveType {
+        match self {
+            Node::Null => PrimitiveType::Null,
+            Node::Bool(_) => PrimitiveType::Boolean,
+            Node::PosInt(_) | Node::NegInt(_) | Node::Float(_) => PrimitiveType::Number,
+            Node::String(_) => PrimitiveType::String,
+            Node::Array(_) => PrimitiveType::Array,
+            Node::Object(_) => PrimitiveType::Object,
+        }
+    }
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Node::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Node::String(value) => Some(value),
+            _ => None,
+        }
+    }
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Node::PosInt(value) => Some(*value),
+            _ => None,
+        }
+    }
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            Node::PosInt(value) => i64::try_from(*value).ok(),
+            Node::NegInt(value) => Some(*value),
+            _ => None,
+        }
+    }
+    #[allow(clippy::cast_precision_loss)]
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Node::PosInt(value) => Some(*value as f64),
+            Node::NegInt(value) => Some(*value as f64),
+            Node::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+    fn array_len(&self) -> Option<usize> {
+        match self {
+            Node::Array(items) => Some(items.len()),
+            _ => None,
+        }
+    }
+    fn array_get(&self, idx: usize) -> Option<&dyn value::Value> {
+        match self {
+            Node::Array(items) => items.get(idx).map(|item| item as &dyn value::Value),
+            _ => None,
+        }
+    }
+    fn object_len(&self) -> Option<usize> {
+        match self {
+            Node::Object(entries) => Some(entries.len()),
+            _ => None,
+        }
+    }
+    fn object_get(&self, key: &str) -> Option<&dyn value::Value> {
+        match self {
+            Node::Object(entries) => entries
+                .binary_search_by(|(k, _)| k.as_ref().cmp(key))
+                .ok()
+                .map(|idx| &entries[idx].1 as &dyn value::Value),
+            _ => None,
+        }
+    }
+    fn object_iter(&self) -> Box<dyn Iterator<Item = (&str, &dyn value::Value)> + '_> {
+        match self {
+            Node::Object(entries) => Box::new(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.as_ref(), v as &dyn value::Value)),
+            ),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+/// Serialize `value` into a [`Node`] tree, following the same rules as `serde_json::to_value`.
+pub(crate) fn to_node<T: Serialize + ?Sized>(value: &T) -> Result<Node, serde_json::Error> {
+    value.serialize(Serializer)
+}
+
+fn into_object(mut entries: Vec<(Cow<'static, str>, Node)>) -> Node {
+    // Keep the last value for duplicated keys, like `serde_json::Map::insert` does
+    entries.reverse();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries.dedup_by(|(a, _), (b, _)| a == b);
+    Node::Object(entries)
+}
+
+fn tagged(variant: &'static str, value: Node) -> Node {
+    Node::Object(vec![(Cow::Borrowed(variant), value)])
+}
+
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Node;
+    type Error = serde_json::Error;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, value: bool) -> Result<Node, Self::Error> {
+        Ok(Node::Bool(value))
+    }
+    fn serialize_i8(self, value: i8) -> Result<Node, Self::Error> {
+        self.serialize_i64(i64::from(value))
+    }
+    fn serialize_i16(self, value: i16) -> Result<Node, Self::Error> {
+        self.serialize_i64(i64::from(value))
+    }
+    fn serialize_i32(self, value: i32) -> Result<Node, Self::Error> {
+        self.serialize_i64(i64::from(value))
+    }
+    fn serialize_i64(self, value: i64) -> Result<Node, Self::Error> {
+        Ok(u64::try_from(value).map_or(Node::NegInt(value), Node::PosInt))
+    }
+    fn serialize_i128(self, value: i128) -> Result<Node, Self::Error> {
+        if let Ok(value) = u64::try_from(value) {
+            Ok(Node::PosInt(value))
+        } else if let Ok(value) = i64::try_from(value) {
+            Ok(Node::NegInt(value))
+        } else {
+            Err(Self::Error::custom("number out of range"))
+        }
+    }
+    fn serialize_u8(self, value: u8) -> Result<Node, Self::Error> {
+        Ok(Node::PosInt(u64::from(value)))
+    }
+    fn serialize_u16(self, value: u16) -> Result<Node, Self::Error> {
+        Ok(Node::PosInt(u64::from(value)))
+    }
+    fn serialize_u32(self, value: u32) -> Result<Node, Self::Error> {
+        Ok(Node::PosInt(u64::from(value)))
+    }
+    fn serialize_u64(self, value: u64) -> Result<Node, Self::Error> {
+        Ok(Node::PosInt(value))
+    }
+    fn serialize_u128(self, value: u128) -> Result<Node, Self::Error> {
+        u64::try_from(value)
+            .map(Node::PosInt)
+            .map_err(|_| Self::Error::custom("number out of range"))
+    }
+    fn serialize_f32(self, value: f32) -> Result<Node, Self::Error> {
+        self.serialize_f64(f64::from(value))
+    }
+    fn serialize_f64(self, value: f64) -> Result<Node, Self::Error> {
+        // Non-finite numbers are not representable in JSON
+        if value.is_finite() {
+            Ok(Node::Float(value))
+        } else {
+            Ok(Node::Null)
+        }
+    }
+    fn serialize_char(self, value: char) -> Result<Node, Self::Error> {
+        Ok(Node::String(value.to_string()))
+    }
+    fn serialize_str(self, value: &str) -> Result<Node, Self::Error> {
+        Ok(Node::String(value.to_string()))
+    }
+    fn serialize_bytes(self, value: &[u8]) -> Result<Node, Self::Error> {
+        Ok(Node::Array(
+            value.iter().map(|&b| Node::PosInt(u64::from(b))).collect(),
+        ))
+    }
+    fn serialize_none(self) -> Result<Node, Self::Error> {
+        Ok(Node::Null)
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Node, Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<Node, Self::Error> {
+        Ok(Node::Null)
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Node, Self::Error> {
+        Ok(Node::Null)
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Node, Self::Error> {
+        Ok(Node::String(variant.to_string()))
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Node, Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Node, Self::Error> {
+        Ok(tagged(variant, value.serialize(self)?))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeVec {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeTupleVariant {
+            variant,
+            items: Vec::with_capacity(len),
+        })
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeMap {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeStructVariant {
+            variant,
+            entries: Vec::with_capacity(len),
+        })
+    }
+}
+
+struct SerializeVec {
+    items: Vec<Node>,
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = Node;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.items.push(to_node(value)?);
+        Ok(())
+    }
+    fn end(self) -> Result<Node, Self::Error> {
+        Ok(Node::Array(self.items))
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = Node;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+    fn end(self) -> Result<Node, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = Node;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+    fn end(self) -> Result<Node, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeTupleVariant {
+    variant: &'static str,
+    items: Vec<Node>,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = Node;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.items.push(to_node(value)?);
+        Ok(())
+    }
+    fn end(self) -> Result<Node, Self::Error> {
+        Ok(tagged(self.variant, Node::Array(self.items)))
+    }
+}
+
+struct SerializeMap {
+    entries: Vec<(Cow<'static, str>, Node)>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Node;
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.next_key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .next_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.entries.push((Cow::Owned(key), to_node(value)?));
+        Ok(())
+    }
+    fn end(self) -> Result<Node, Self::Error> {
+        Ok(into_object(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Node;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.entries.push((Cow::Borrowed(key), to_node(value)?));
+        Ok(())
+    }
+    fn end(self) -> Result<Node, Self::Error> {
+        Ok(into_object(self.entries))
+    }
+}
+
+struct SerializeStructVariant {
+    variant: &'static str,
+    entries: Vec<(Cow<'static, str>, Node)>,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = Node;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.entries.push((Cow::Borrowed(key), to_node(value)?));
+        Ok(())
+    }
+    fn end(self) -> Result<Node, Self::Error> {
+        Ok(tagged(self.variant, into_object(self.entries)))
+    }
+}
+
+/// Object keys must serialize to strings; numbers, booleans and chars are stringified.
+struct KeySerializer;
+
+fn key_must_be_a_string() -> serde_json::Error {
+    serde_json::Error::custom("key must be a string")
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = serde_json::Error;
+
+    type SerializeSeq = ser::Impossible<String, Self::Error>;
+    type SerializeTuple = ser::Impossible<String, Self::Error>;
+    type SerializeTupleStruct = ser::Impossible<String, Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<String, Self::Error>;
+    type SerializeMap = ser::Impossible<String, Self::Error>;
+    type SerializeStruct = ser::Impossible<String, Self::Error>;
+    type SerializeStructVariant = ser::Impossible<String, Self::Error>;
+
+    fn serialize_bool(self, value: bool) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_i8(self, value: i8) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_i16(self, value: i16) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_i32(self, value: i32) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_i64(self, value: i64) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_i128(self, value: i128) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_u8(self, value: u8) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_u16(self, value: u16) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_u32(self, value: u32) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_u64(self, value: u64) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_u128(self, value: u128) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_f32(self, _value: f32) -> Result<String, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_f64(self, _value: f64) -> Result<String, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_char(self, value: char) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_str(self, value: &str) -> Result<String, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn serialize_bytes(self, _value: &[u8]) -> Result<String, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_none(self) -> Result<String, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_unit(self) -> Result<String, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Self::Error> {
+        Ok(variant.to_string())
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(key_must_be_a_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_node;
+    use crate::value::Value as _;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Item {
+        name: &'static str,
+        tags: Vec<&'static str>,
+        price: Option<f64>,
+    }
+
+    #[derive(Serialize)]
+    enum Shape {
+        Unit,
+        Circle(f64),
+        Point(i32, i32),
+        Rect { w: u8, h: u8 },
+    }
+
+    #[track_caller]
+    fn assert_same_as_to_value<T: Serialize>(instance: &T) {
+        let node = to_node(instance).expect("Serializable");
+        let expected = serde_json::to_value(instance).expect("Serializable");
+        assert_eq!(node.to_json(), expected);
+    }
+
+    #[test]
+    fn structs_and_enums() {
+        assert_same_as_to_value(&Item {
+            name: "a",
+            tags: vec!["x", "y"],
+            price: None,
+        });
+        assert_same_as_to_value(&Shape::Unit);
+        assert_same_as_to_value(&Shape::Circle(1.5));
+        assert_same_as_to_value(&Shape::Point(-1, 2));
+        assert_same_as_to_value(&Shape::Rect { w: 1, h: 2 });
+    }
+
+    #[test]
+    fn scalars() {
+        assert_same_as_to_value(&f64::NAN);
+        assert_same_as_to_value(&u64::MAX);
+        assert_same_as_to_value(&i64::MIN);
+        assert_same_as_to_value(&'c');
+        assert_same_as_to_value(&());
+    }
+
+    #[test]
+    fn non_string_keys() {
+        assert_same_as_to_value(&BTreeMap::from([(1, true), (2, false)]));
+        let map = BTreeMap::from([((1, 2), true)]);
+        assert!(to_node(&map).is_err());
+        assert!(serde_json::to_value(&map).is_err());
+    }
+}
@@ -0,0 +1,517 @@
+//! Locating validation errors in the source text of an instance.
+use std::{collections::hash_map::Entry, error, fmt, ops::Range};
+
+use ahash::AHashMap;
+use serde_json::Value;
+
+use crate::{
+    paths::{Location, LocationSegment},
+    ValidationError,
+};
+
+/// Position of a value in the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// Byte range of the value.
+    pub range: Range<usize>,
+    /// Line where the value starts, starting from 1.
+    pub line: usize,
+    /// Column in characters where the value starts, starting from 1.
+    pub column: usize,
+}
+
+/// A validation error together with the position of the offending value in the source text.
+#[derive(Debug)]
+pub struct SpannedError {
+    /// The validation error.
+    pub error: ValidationError<'static>,
+    /// Position of the value at `error.instance_path`. `None` if the path does not point to a
+    /// value in the text, e.g. when it is set by a custom keyword.
+    pub span: Option<SourceSpan>,
+}
+
+/// An error returned by [`Validator::validate_str`](crate::Validator::validate_str).
+#[derive(Debug)]
+pub enum ValidateStrError {
+    /// The text is not valid JSON.
+    Parse(serde_json::Error),
+    /// The instance is not valid under the schema.
+    Invalid(Vec<SpannedError>),
+}
+
+impl error::Error for ValidateStrError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ValidateStrError::Parse(error) => Some(error),
+            ValidateStrError::Invalid(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ValidateStrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidateStrError::Parse(error) => error.fmt(f),
+            ValidateStrError::Invalid(errors) => {
+                for (idx, spanned) in errors.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str("\n")?;
+                    }
+                    if let Some(span) = &spanned.span {
+                        write!(f, "{}:{}: ", span.line, span.column)?;
+                    }
+                    spanned.error.fmt(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Instance paths to locate, as a tree of path segments.
+#[derive(Default)]
+struct Targets {
+    errors: Vec<usize>,
+    children: AHashMap<String, Targets>,
+}
+
+impl Targets {
+    fn insert(&mut self, pointer: &str, error: usize) {
+        let mut node = self;
+        for segment in pointer.split('/').skip(1) {
+            let segment = segment.replace("~1", "/").replace("~0", "~");
+            node = node.children.entry(segment).or_default();
+        }
+        node.errors.push(error);
+    }
+}
+
+/// Find the positions of values at the instance paths of `errors` in a single pass over `text`.
+///
+/// With `duplicate_keys`, errors for keys that appear more than once in the same object come
+/// first. `text` must be valid JSON.
+pub(crate) fn locate(
+    text: &str,
+    errors: Vec<ValidationError<'static>>,
+    duplicate_keys: bool,
+) -> Vec<SpannedError> {
+    if errors.is_empty() && !duplicate_keys {
+        return Vec::new();
+    }
+    let (mut ranges, duplicates) = scan(
+        text,
+        errors.iter().map(|error| error.instance_path.as_str()),
+        duplicate_keys,
+    );
+    // Both keys of every duplicate are located after the values at the error paths
+    for duplicate in &duplicates {
+        ranges.push(Some(duplicate.first.clone()));
+        ranges.push(Some(duplicate.second.clone()));
+    }
+    let mut spans = lines_and_columns(text, ranges).into_iter();
+    let error_spans: Vec<_> = spans.by_ref().take(errors.len()).collect();
+    let mut located = Vec::with_capacity(duplicates.len() + errors.len());
+    for duplicate in duplicates {
+        let first = spans.next().flatten().expect("Keys are located");
+        let second = spans.next().flatten().expect("Keys are located");
+        // Parse the object again, as the parsed instance may not have it if its key is duplicated
+        let object = serde_json::Deserializer::from_str(&text[duplicate.object..])
+            .into_iter::<Value>()
+            .next()
+            .and_then(Result::ok)
+            .unwrap_or(Value::Null);
+        located.push(SpannedError {
+            span: Some(second.clone()),
+            error: ValidationError::duplicate_key(
+                duplicate.location,
+                object,
+                duplicate.key,
+                first,
+                second,
+            ),
+        });
+    }
+    located.extend(
+        errors
+            .into_iter()
+            .zip(error_spans)
+            .map(|(error, span)| SpannedError { error, span }),
+    );
+    located
+}
+
+/// Find the positions of values at JSON Pointers `paths` in a single pass over `text`.
+///
+/// `text` must be valid JSON.
+#[cfg(feature = "diagnostics")]
+pub(crate) fn spans<'p>(
+    text: &str,
+    paths: impl Iterator<Item = &'p str>,
+) -> Vec<Option<SourceSpan>> {
+    let (ranges, _) = scan(text, paths, false);
+    lines_and_columns(text, ranges)
+}
+
+/// A key that appears more than once in the same object.
+struct DuplicateKey {
+    /// Location of the object
+    location: Location,
+    /// Offset of the object in the text
+    object: usize,
+    key: String,
+    first: Range<usize>,
+    second: Range<usize>,
+}
+
+/// Find the byte ranges of values at JSON Pointers `paths`, and optionally all duplicate keys.
+fn scan<'p>(
+    text: &str,
+    paths: impl Iterator<Item = &'p str>,
+    duplicate_keys: bool,
+) -> (Vec<Option<Range<usize>>>, Vec<DuplicateKey>) {
+    let mut targets = Targets::default();
+    let mut count = 0;
+    for (idx, path) in paths.enumerate() {
+        targets.insert(path, idx);
+        count += 1;
+    }
+    let mut ranges = vec![None; count];
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        position: 0,
+        ranges: &mut ranges,
+        duplicates: duplicate_keys.then(|| (Location::new(), Vec::new())),
+    };
+    scanner.value(Some(&targets));
+    let duplicates = scanner
+        .duplicates
+        .map(|(_, duplicates)| duplicates)
+        .unwrap_or_default();
+    (ranges, duplicates)
+}
+
+/// Compute lines & columns of `ranges` in one sweep over `text`.
+fn lines_and_columns(text: &str, ranges: Vec<Option<Range<usize>>>) -> Vec<Option<SourceSpan>> {
+    let count = ranges.len();
+    let mut order: Vec<usize> = (0..count).filter(|&i| ranges[i].is_some()).collect();
+    order.sort_by_key(|&i| ranges[i].as_ref().map(|range: &Range<usize>| range.start));
+    let mut spans = vec![None; count];
+    let (mut line, mut line_start, mut offset) = (1, 0, 0);
+    for idx in order {
+        let range = ranges[idx].clone().expect("Filtered above");
+        for (position, byte) in text.as_bytes()[offset..range.start].iter().enumerate() {
+            if *byte == b'\n' {
+                line += 1;
+                line_start = offset + position + 1;
+            }
+        }
+        offset = range.start;
+        let column = text[line_start..range.start].chars().count() + 1;
+        spans[idx] = Some(SourceSpan {
+            range,
+            line,
+            column,
+        });
+    }
+    spans
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    ranges: &'a mut [Option<Range<usize>>],
+    /// Location of the current value and the duplicate keys found so far, if they are looked for
+    duplicates: Option<(Location, Vec<DuplicateKey>)>,
+}
+
+impl Scanner<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> u8 {
+        self.bytes[self.position]
+    }
+
+    /// Move the current location to `segment` if duplicate keys are looked for, and return the
+    /// location to restore afterwards.
+    fn enter<'s>(&mut self, segment: impl Into<LocationSegment<'s>>) -> Option<Location> {
+        let (location, _) = self.duplicates.as_mut()?;
+        let child = location.join(segment);
+        Some(std::mem::replace(location, child))
+    }
+
+    fn leave(&mut self, parent: Option<Location>) {
+        if let (Some((location, _)), Some(parent)) = (self.duplicates.as_mut(), parent) {
+            *location = parent;
+        }
+    }
+
+    /// Scan a value and record its range if it is targeted. The recursion depth is bounded by
+    /// the recursion limit of `serde_json` that parsed the same text before.
+    fn value(&mut self, targets: Option<&Targets>) {
+        self.skip_whitespace();
+        let start = self.position;
+        // Only descend into containers that hold targeted values
+        let children = targets.filter(|targets| !targets.children.is_empty());
+        match self.peek() {
+            b'{' => {
+                self.position += 1;
+                self.skip_whitespace();
+                if self.peek() == b'}' {
+                    self.position += 1;
+                } else {
+                    // Ranges of the keys seen so far, if duplicate keys are looked for
+                    let mut keys: Option<AHashMap<String, Range<usize>>> =
+                        self.duplicates.as_ref().map(|_| AHashMap::new());
+                    loop {
+                        self.skip_whitespace();
+                        let key_start = self.position;
+                        self.string();
+                        let key_range = key_start..self.position;
+                        let key: Option<String> = if children.is_some() || keys.is_some() {
+                            serde_json::from_slice(&self.bytes[key_range.clone()]).ok()
+                        } else {
+                            None
+                        };
+                        let child =
+                            children.and_then(|targets| targets.children.get(key.as_deref()?));
+                        if let (Some(keys), Some(key)) = (keys.as_mut(), &key) {
+                            match keys.entry(key.clone()) {
+                                Entry::Occupied(first) => {
+                                    let (location, duplicates) =
+                                        self.duplicates.as_mut().expect("Keys are tracked");
+                                    duplicates.push(DuplicateKey {
+                                        location: location.clone(),
+                                        object: start,
+                                        key: key.clone(),
+                                        first: first.get().clone(),
+                                        second: key_range,
+                                    });
+                                }
+                                Entry::Vacant(entry) => {
+                                    entry.insert(key_range);
+                                }
+                            }
+                        }
+                        self.skip_whitespace();
+                        // Colon
+                        self.position += 1;
+                        let parent = match &key {
+                            Some(key) => self.enter(key),
+                            None => None,
+                        };
+                        self.value(child);
+                        self.leave(parent);
+                        self.skip_whitespace();
+                        self.position += 1;
+                        if self.bytes[self.position - 1] == b'}' {
+                            break;
+                        }
+                    }
+                }
+            }
+            b'[' => {
+                self.position += 1;
+                self.skip_whitespace();
+                if self.peek() == b']' {
+                    self.position += 1;
+                } else {
+                    let mut idx = 0_usize;
+                    loop {
+                        let child = children.and_then(|targets| {
+                            targets.children.get(itoa::Buffer::new().format(idx))
+                        });
+                        let parent = self.enter(idx);
+                        self.value(child);
+                        self.leave(parent);
+                        self.skip_whitespace();
+                        self.position += 1;
+                        if self.bytes[self.position - 1] == b']' {
+                            break;
+                        }
+                        idx += 1;
+                    }
+                }
+            }
+            b'"' => self.string(),
+            _ => {
+                while let Some(byte) = self.bytes.get(self.position) {
+                    if matches!(byte, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r') {
+                        break;
+                    }
+                    self.position += 1;
+                }
+            }
+        }
+        if let Some(targets) = targets {
+            for idx in &targets.errors {
+                self.ranges[*idx] = Some(start..self.position);
+            }
+        }
+    }
+
+    fn string(&mut self) {
+        // Opening quote
+        self.position += 1;
+        loop {
+            match self.bytes[self.position] {
+                b'\\' => self.position += 2,
+                b'"' => {
+                    self.position += 1;
+                    return;
+                }
+                _ => self.position += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::{SourceSpan, ValidateStrError};
+    use crate::error::ValidationErrorKind;
+
+    fn spans(schema: &serde_json::Value, text: &str) -> Vec<(String, Option<SourceSpan>)> {
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        match validator.validate_str(text) {
+            Err(ValidateStrError::Invalid(errors)) => errors
+                .into_iter()
+                .map(|spanned| (spanned.error.instance_path.to_string(), spanned.span))
+                .collect(),
+            other => panic!("Unexpected result: {other:?}"),
+        }
+    }
+
+    #[test_case(&json!({"type": "string"}), " 42 ", "", 1..3, 1, 2; "root")]
+    #[test_case(&json!({"properties": {"a/b": {"items": {"type": "string"}}}}), "{\n  \"x\": [1, 2],\n  \"a/b\": [\"ok\", {\"n\": [1, \"]\"]}]\n}", "/a~1b/1", 33..48, 3, 17; "nested")]
+    #[test_case(&json!({"properties": {"é": {"type": "string"}}}), "{\"\\u00e9\": true}", "/é", 11..15, 1, 12; "escaped key")]
+    #[test_case(&json!({"properties": {"ключ": {"type": "string"}}}), "{\"ключ\":\n\n  \"x\", \"x\": 1, \"ключ\": null}", "/ключ", 41..45, 3, 24; "duplicate key uses the last value")]
+    fn span(
+        schema: &serde_json::Value,
+        text: &str,
+        path: &str,
+        range: std::ops::Range<usize>,
+        line: usize,
+        column: usize,
+    ) {
+        assert_eq!(
+            spans(schema, text),
+            vec![(
+                path.to_string(),
+                Some(SourceSpan {
+                    range,
+                    line,
+                    column
+                })
+            )]
+        );
+    }
+
+    #[test]
+    fn multiple_errors() {
+        let schema = json!({"items": {"type": "integer"}});
+        let located = spans(&schema, "[\"a\",\n1,\n\"b\"]");
+        assert_eq!(
+            located
+                .iter()
+                .map(|(path, span)| {
+                    let span = span.as_ref().expect("Located");
+                    (path.as_str(), span.line, span.column)
+                })
+                .collect::<Vec<_>>(),
+            vec![("/0", 1, 2), ("/2", 3, 1)]
+        );
+    }
+
+    fn duplicates(text: &str) -> Vec<(String, String, usize, usize)> {
+        let validator = crate::options()
+            .with_reject_duplicate_keys(true)
+            .build(&json!({}))
+            .expect("Valid schema");
+        match validator.validate_str(text) {
+            Ok(()) => Vec::new(),
+            Err(ValidateStrError::Invalid(errors)) => errors
+                .into_iter()
+                .map(|spanned| {
+                    let ValidationErrorKind::DuplicateKey { key, first, second } =
+                        spanned.error.kind
+                    else {
+                        panic!("Unexpected error: {}", spanned.error);
+                    };
+                    assert_eq!(spanned.span, Some(second.clone()));
+                    (
+                        spanned.error.instance_path.to_string(),
+                        key,
+                        first.range.start,
+                        second.range.start,
+                    )
+                })
+                .collect(),
+            Err(error) => panic!("Unexpected error: {error}"),
+        }
+    }
+
+    #[test_case("{\"a\": 1, \"b\": {\"a\": 2}}", &[]; "unique")]
+    #[test_case("{\"a\": 1, \"a\": 2}", &[("", "a", 1, 9)]; "root")]
+    #[test_case("[{}, {\"x\": {\"k/1\": 1, \"k/1\": 2}}]", &[("/1/x", "k/1", 12, 22)]; "nested")]
+    #[test_case("{\"a\": 1, \"\\u0061\": 2}", &[("", "a", 1, 9)]; "escaped")]
+    #[test_case("{\"a\": 1, \"a\": 2, \"a\": 3}", &[("", "a", 1, 9), ("", "a", 1, 17)]; "repeated")]
+    #[test_case("{\"a\": {\"b\": 1, \"b\": 2}, \"a\": 3}", &[("/a", "b", 7, 15), ("", "a", 1, 24)]; "in duplicate")]
+    #[test_case("{\"a\": [{\"b\": 1, \"b\": 2}], \"a\": 3}", &[("/a/0", "b", 8, 16), ("", "a", 1, 26)]; "in replaced value")]
+    fn duplicate_keys(text: &str, expected: &[(&str, &str, usize, usize)]) {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(path, key, first, second)| (path.to_string(), key.to_string(), *first, *second))
+            .collect();
+        assert_eq!(duplicates(text), expected);
+    }
+
+    #[test]
+    fn duplicate_key_instance() {
+        let validator = crate::options()
+            .with_reject_duplicate_keys(true)
+            .build(&json!({}))
+            .expect("Valid schema");
+        let Err(ValidateStrError::Invalid(errors)) =
+            validator.validate_str("{\"a\": [{\"b\": 1, \"b\": 2}], \"a\": 3}")
+        else {
+            panic!("Should fail");
+        };
+        assert_eq!(*errors[0].error.instance, json!({"b": 2}));
+        assert_eq!(*errors[1].error.instance, json!({"a": 3}));
+    }
+
+    #[test]
+    fn duplicate_keys_with_other_errors() {
+        let schema = json!({"properties": {"a": {"type": "string"}}});
+        let text = "{\"a\": \"x\",\n \"a\": 2}";
+        let validator = crate::validator_for(&schema).expect("Valid schema");
+        assert!(matches!(
+            validator.validate_str(text),
+            Err(ValidateStrError::Invalid(errors)) if errors.len() == 1
+        ));
+        let validator = crate::options()
+            .with_reject_duplicate_keys(true)
+            .build(&schema)
+            .expect("Valid schema");
+        let error = validator.validate_str(text).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "2:2: Duplicate key \"a\", first at line 1, column 2\n2:7: 2 is not of type \"string\""
+        );
+    }
+
+    #[test]
+    fn valid_and_malformed() {
+        let validator = crate::validator_for(&json!({"type": "array"})).expect("Valid schema");
+        assert!(validator.validate_str("[1, 2]").is_ok());
+        let error = validator.validate_str("[1, ").expect_err("Should fail");
+        assert!(matches!(error, ValidateStrError::Parse(_)));
+    }
+}
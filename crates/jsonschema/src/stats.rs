@@ -0,0 +1,173 @@
+//! Size and complexity of compiled validators.
+use std::collections::BTreeMap;
+
+use crate::Validator;
+
+/// Size and complexity of a compiled validator, e.g. to reject schemas from untrusted sources
+/// that exceed some thresholds.
+///
+/// Keywords are counted at every location they are compiled at, so subschemas that are shared
+/// between several locations, like identical subschemas or targets of several references, count
+/// once per location. Keywords that only annotate, like `title`, are not counted.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use serde_json::json;
+///
+/// let validator = jsonschema::validator_for(&json!({
+///     "properties": {
+///         "id": {"type": "string", "pattern": "^[a-z]+$"},
+///         "tags": {"items": {"type": "string"}}
+///     }
+/// }))?;
+/// let stats = validator.stats();
+/// assert_eq!(stats.keywords["type"], 2);
+/// assert_eq!(stats.max_depth, 4);
+/// assert_eq!(stats.patterns, 1);
+/// assert!(stats.cost < 100, "Schema is too complex");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SchemaStats {
+    /// Number of compiled keywords by keyword name.
+    pub keywords: BTreeMap<String, usize>,
+    /// Number of segments in the deepest keyword location, including segments for references.
+    pub max_depth: usize,
+    /// Number of distinct regular expressions.
+    pub patterns: usize,
+    /// Total length of the distinct regular expressions in bytes, as a measure of their size.
+    pub pattern_bytes: usize,
+    /// Number of documents outside of the schema it references.
+    pub external_documents: usize,
+    /// Rough estimate of the work done to validate an instance, meant for comparing schemas.
+    ///
+    /// Every keyword counts one, keywords applying subschemas two, `uniqueItems` four and
+    /// keywords matching regular expressions or formats eight. The size of instances is not
+    /// taken into account.
+    pub cost: u64,
+}
+
+impl SchemaStats {
+    pub(crate) fn of(validator: &Validator) -> SchemaStats {
+        let mut keywords = BTreeMap::new();
+        let mut max_depth = 0;
+        let mut cost = 0;
+        for (location, keyword) in validator.keyword_locations() {
+            *keywords.entry(keyword.to_string()).or_insert(0) += 1;
+            max_depth = max_depth.max(location.depth());
+            cost += keyword_cost(keyword);
+        }
+        SchemaStats {
+            keywords,
+            max_depth,
+            patterns: validator.patterns.len(),
+            pattern_bytes: validator.patterns.iter().map(String::len).sum(),
+            external_documents: validator.external_references.len(),
+            cost,
+        }
+    }
+    /// Total number of compiled keywords.
+    #[must_use]
+    pub fn keyword_count(&self) -> usize {
+        self.keywords.values().sum()
+    }
+}
+
+fn keyword_cost(keyword: &str) -> u64 {
+    match keyword {
+        "pattern" | "patternProperties" | "format" => 8,
+        "uniqueItems" => 4,
+        "$ref"
+        | "$dynamicRef"
+        | "$recursiveRef"
+        | "allOf"
+        | "anyOf"
+        | "oneOf"
+        | "not"
+        | "if"
+        | "then"
+        | "else"
+        | "properties"
+        | "additionalProperties"
+        | "items"
+        | "prefixItems"
+        | "additionalItems"
+        | "contains"
+        | "propertyNames"
+        | "dependentSchemas"
+        | "dependencies"
+        | "unevaluatedProperties"
+        | "unevaluatedItems" => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn fixture() {
+        let validator = crate::validator_for(&json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "pattern": "^[a-z]+$"},
+                "tags": {
+                    "type": "array",
+                    "items": {"type": "string", "pattern": "^#"},
+                    "uniqueItems": true
+                },
+                "address": {"$ref": "#/$defs/address"}
+            },
+            "required": ["name"],
+            "$defs": {
+                "address": {
+                    "type": "object",
+                    "patternProperties": {"^[a-z]+$": {"type": "string"}}
+                }
+            }
+        }))
+        .expect("Invalid schema");
+        let stats = validator.stats();
+        assert_eq!(
+            stats.keywords,
+            BTreeMap::from([
+                ("$ref".to_string(), 1),
+                ("items".to_string(), 1),
+                ("pattern".to_string(), 2),
+                ("patternProperties".to_string(), 1),
+                ("properties".to_string(), 1),
+                ("required".to_string(), 1),
+                ("type".to_string(), 6),
+                ("uniqueItems".to_string(), 1),
+            ])
+        );
+        assert_eq!(stats.keyword_count(), 14);
+        // `/properties/address/$ref/patternProperties/^[a-z]+$/type`
+        assert_eq!(stats.max_depth, 6);
+        assert_eq!(stats.patterns, 2);
+        assert_eq!(stats.pattern_bytes, 10);
+        assert_eq!(stats.external_documents, 0);
+        assert_eq!(stats.cost, 41);
+    }
+
+    #[test]
+    fn external_documents() {
+        let validator = crate::options()
+            .with_resource(
+                "https://example.com/name.json",
+                referencing::Resource::from_contents(json!({"type": "string"}))
+                    .expect("Valid resource"),
+            )
+            .build(&json!({"items": {"$ref": "https://example.com/name.json"}}))
+            .expect("Invalid schema");
+        let stats = validator.stats();
+        assert_eq!(stats.external_documents, 1);
+        assert_eq!(stats.keyword_count(), 3);
+    }
+}
@@ -0,0 +1,457 @@
+//! Running the [JSON Schema Test Suite](https://github.com/json-schema-org/JSON-Schema-Test-Suite)
+//! against a custom configuration.
+//!
+//! Crates that register their own keywords, formats or retrievers can check that their
+//! configuration keeps the standard semantics with a single test:
+//!
+//! ```rust,no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use jsonschema::{testsuite::Filter, Draft};
+//!
+//! let filter = Filter::new()
+//!     .with_optional(true)
+//!     .skip("optional/bignum.json");
+//! let report = jsonschema::testsuite::run(
+//!     "JSON-Schema-Test-Suite",
+//!     || {
+//!         let mut options = jsonschema::options();
+//!         options.with_format("even", |value: &str| value.len() % 2 == 0);
+//!         options
+//!     },
+//!     Draft::Draft202012,
+//!     &filter,
+//! )?;
+//! assert!(report.is_success(), "{report}");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The suite is read from a checkout of the test suite repository at runtime. Documents under its
+//! `remotes` directory are registered as resources at `http://localhost:1234/`, where the tests
+//! expect them, so no server is needed.
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use referencing::Draft;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::ValidationOptions;
+
+/// The URI the test suite expects its remote documents at.
+const REMOTES_URI: &str = "http://localhost:1234/";
+
+#[derive(Deserialize)]
+struct Case {
+    description: String,
+    schema: Value,
+    tests: Vec<Test>,
+}
+
+#[derive(Deserialize)]
+struct Test {
+    description: String,
+    data: Value,
+    valid: bool,
+}
+
+/// Selection of the test suite cases to run.
+///
+/// Tests are identified as `<file>::<case>::<test>`, where `<file>` is the path of the test file
+/// relative to the draft directory, e.g. `optional/bignum.json::integer::a bignum is an integer`.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    optional: bool,
+    only: Option<String>,
+    skip: Vec<String>,
+}
+
+impl Filter {
+    /// Select all required tests.
+    #[must_use]
+    pub fn new() -> Filter {
+        Filter::default()
+    }
+    /// Whether to run the tests in the `optional` directory. Disabled by default.
+    #[must_use]
+    pub fn with_optional(mut self, yes: bool) -> Filter {
+        self.optional = yes;
+        self
+    }
+    /// Run only the tests whose identifier starts with `prefix`.
+    #[must_use]
+    pub fn only(mut self, prefix: impl Into<String>) -> Filter {
+        self.only = Some(prefix.into());
+        self
+    }
+    /// Skip the tests whose identifier starts with `prefix`, e.g. cases known to be unsupported
+    /// by the configuration. Skipped tests are counted in [`Report::skipped`].
+    #[must_use]
+    pub fn skip(mut self, prefix: impl Into<String>) -> Filter {
+        self.skip.push(prefix.into());
+        self
+    }
+    fn is_selected(&self, id: &str) -> bool {
+        (self.optional || !id.starts_with("optional/"))
+            && self
+                .only
+                .as_ref()
+                .map_or(true, |prefix| id.starts_with(prefix))
+    }
+    fn is_skipped(&self, id: &str) -> bool {
+        self.skip.iter().any(|prefix| id.starts_with(prefix))
+    }
+}
+
+/// Why a test suite test failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FailureKind {
+    /// The schema of the case failed to build.
+    Build(String),
+    /// The instance should be valid, but is not. Carries the first validation error.
+    Invalid(String),
+    /// The instance should be invalid, but is not.
+    Valid,
+    /// `Validator::is_valid`, `Validator::validate` and `Validator::iter_errors` disagree on
+    /// whether the instance is valid.
+    Inconsistent,
+}
+
+/// A failed test suite test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// Path of the test file relative to the draft directory.
+    pub file: String,
+    /// Description of the case, i.e. the schema being tested.
+    pub case: String,
+    /// Description of the test, i.e. the instance being validated.
+    pub description: String,
+    /// What went wrong.
+    pub kind: FailureKind,
+}
+
+impl Failure {
+    /// Identifier of the test, as matched by [`Filter`].
+    #[must_use]
+    pub fn id(&self) -> String {
+        format!("{}::{}::{}", self.file, self.case, self.description)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.id())?;
+        match &self.kind {
+            FailureKind::Build(error) => write!(f, "schema failed to build: {error}"),
+            FailureKind::Invalid(error) => write!(f, "expected valid, got: {error}"),
+            FailureKind::Valid => f.write_str("expected invalid"),
+            FailureKind::Inconsistent => f.write_str("validation methods disagree"),
+        }
+    }
+}
+
+/// Results of a test suite run.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Number of passed tests.
+    pub passed: usize,
+    /// Number of tests skipped by [`Filter::skip`].
+    pub skipped: usize,
+    /// Failed tests, in the order of the suite.
+    pub failures: Vec<Failure>,
+}
+
+impl Report {
+    /// Whether all tests that ran have passed.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} passed, {} failed, {} skipped",
+            self.passed,
+            self.failures.len(),
+            self.skipped
+        )?;
+        for failure in &self.failures {
+            writeln!(f, "{failure}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Run the tests of `draft` from the test suite checkout at `path` that are selected by `filter`.
+///
+/// Every case builds its schema with fresh options from `options`, with the draft set to `draft`.
+/// Optional tests also validate formats, unless `options` decides about it explicitly.
+///
+/// # Errors
+///
+/// Returns an error if the suite can not be read, e.g. because `path` is not a checkout of the
+/// test suite or does not contain tests for `draft`.
+pub fn run<F>(
+    path: impl AsRef<Path>,
+    options: F,
+    draft: Draft,
+    filter: &Filter,
+) -> io::Result<Report>
+where
+    F: Fn() -> ValidationOptions,
+{
+    let path = path.as_ref();
+    let mut remotes = Vec::new();
+    let remotes_path = path.join("remotes");
+    for file in json_files(&remotes_path)? {
+        let contents = read_json::<Value>(&file)?;
+        let uri = format!("{REMOTES_URI}{}", relative(&remotes_path, &file));
+        let draft = draft.detect(&contents).unwrap_or(draft);
+        remotes.push((uri, draft.create_resource(contents)));
+    }
+    let tests_path = path.join("tests").join(directory(draft)?);
+    let mut report = Report::default();
+    for file in json_files(&tests_path)? {
+        let name = relative(&tests_path, &file);
+        let is_optional = name.starts_with("optional/");
+        for case in read_json::<Vec<Case>>(&file)? {
+            let prefix = format!("{name}::{}::", case.description);
+            if !case
+                .tests
+                .iter()
+                .any(|test| filter.is_selected(&format!("{prefix}{}", test.description)))
+            {
+                continue;
+            }
+            let mut options = options();
+            options.with_draft(draft);
+            if is_optional && options.validate_formats.is_none() {
+                options.should_validate_formats(true);
+            }
+            options.with_resources(remotes.iter().cloned());
+            let validator = options.build(&case.schema);
+            for test in case.tests {
+                let id = format!("{prefix}{}", test.description);
+                if !filter.is_selected(&id) {
+                    continue;
+                }
+                if filter.is_skipped(&id) {
+                    report.skipped += 1;
+                    continue;
+                }
+                let kind = match &validator {
+                    Ok(validator) => check(validator, &test),
+                    Err(error) => Some(FailureKind::Build(error.to_string())),
+                };
+                match kind {
+                    Some(kind) => report.failures.push(Failure {
+                        file: name.clone(),
+                        case: case.description.clone(),
+                        description: test.description,
+                        kind,
+                    }),
+                    None => report.passed += 1,
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn check(validator: &crate::Validator, test: &Test) -> Option<FailureKind> {
+    let is_valid = validator.is_valid(&test.data);
+    let first = validator.validate(&test.data).err();
+    let has_errors = validator.iter_errors(&test.data).next().is_some();
+    if is_valid != first.is_none() || is_valid == has_errors {
+        Some(FailureKind::Inconsistent)
+    } else if let Some(error) = first.filter(|_| test.valid) {
+        Some(FailureKind::Invalid(error.to_string()))
+    } else if is_valid && !test.valid {
+        Some(FailureKind::Valid)
+    } else {
+        None
+    }
+}
+
+fn directory(draft: Draft) -> io::Result<&'static str> {
+    match draft {
+        Draft::Draft4 => Ok("draft4"),
+        Draft::Draft6 => Ok("draft6"),
+        Draft::Draft7 => Ok("draft7"),
+        Draft::Draft201909 => Ok("draft2019-09"),
+        Draft::Draft202012 => Ok("draft2020-12"),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("The test suite has no tests for {draft:?}"),
+        )),
+    }
+}
+
+/// All JSON files below `root`, sorted by path.
+fn json_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut directories = vec![root.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(&directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Path of `file` relative to `root`, with `/` as separator.
+fn relative(root: &Path, file: &Path) -> String {
+    file.strip_prefix(root)
+        .expect("Files are below their root")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<T> {
+    let text = fs::read_to_string(path)?;
+    serde_json::from_str(&text).map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {error}", path.display()),
+        )
+    })
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::{run, Failure, FailureKind, Filter};
+    use referencing::Draft;
+    use serde_json::json;
+    use std::{fs, path::Path};
+
+    fn write(root: &Path, path: &str, contents: &serde_json::Value) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("Has parent")).expect("Failed to create directory");
+        fs::write(path, contents.to_string()).expect("Failed to write file");
+    }
+
+    fn suite() -> tempfile::TempDir {
+        let root = tempfile::tempdir().expect("Failed to create directory");
+        write(
+            root.path(),
+            "remotes/integer.json",
+            &json!({"type": "integer"}),
+        );
+        write(
+            root.path(),
+            "tests/draft7/type.json",
+            &json!([
+                {
+                    "description": "integer",
+                    "schema": {"type": "integer"},
+                    "tests": [
+                        {"description": "an integer", "data": 1, "valid": true},
+                        {"description": "a string", "data": "a", "valid": false}
+                    ]
+                },
+                {
+                    "description": "remote",
+                    "schema": {"$ref": "http://localhost:1234/integer.json"},
+                    "tests": [{"description": "a string", "data": "a", "valid": false}]
+                }
+            ]),
+        );
+        write(
+            root.path(),
+            "tests/draft7/optional/format/even.json",
+            &json!([{
+                "description": "even",
+                "schema": {"format": "even"},
+                "tests": [
+                    {"description": "even length", "data": "ab", "valid": true},
+                    {"description": "odd length", "data": "abc", "valid": false}
+                ]
+            }]),
+        );
+        root
+    }
+
+    fn options() -> crate::ValidationOptions {
+        let mut options = crate::options();
+        options.with_format("even", |value: &str| value.len() % 2 == 0);
+        options
+    }
+
+    #[test]
+    fn passes() {
+        let suite = suite();
+        let report = run(
+            suite.path(),
+            options,
+            Draft::Draft7,
+            &Filter::new().with_optional(true),
+        )
+        .expect("Readable suite");
+        assert!(report.is_success(), "{report}");
+        assert_eq!(report.passed, 5);
+        assert_eq!(report.skipped, 0);
+    }
+
+    #[test]
+    fn failures() {
+        let suite = suite();
+        // Without the custom format, the odd length string is valid
+        let report = run(
+            suite.path(),
+            crate::options,
+            Draft::Draft7,
+            &Filter::new().with_optional(true),
+        )
+        .expect("Readable suite");
+        assert_eq!(
+            report.failures,
+            [Failure {
+                file: "optional/format/even.json".to_string(),
+                case: "even".to_string(),
+                description: "odd length".to_string(),
+                kind: FailureKind::Valid,
+            }]
+        );
+        assert_eq!(
+            report.to_string(),
+            "4 passed, 1 failed, 0 skipped\noptional/format/even.json::even::odd length: expected invalid\n"
+        );
+    }
+
+    #[test]
+    fn filter() {
+        let suite = suite();
+        let run = |filter: &Filter| {
+            run(suite.path(), options, Draft::Draft7, filter).expect("Readable suite")
+        };
+        // Optional tests are not selected by default
+        assert_eq!(run(&Filter::new()).passed, 3);
+        let report = run(&Filter::new().skip("type.json::remote"));
+        assert_eq!((report.passed, report.skipped), (2, 1));
+        let report = run(&Filter::new().with_optional(true).only("optional/"));
+        assert_eq!((report.passed, report.skipped), (2, 0));
+    }
+
+    #[test]
+    fn missing_draft() {
+        let suite = suite();
+        assert!(run(suite.path(), options, Draft::Draft4, &Filter::new()).is_err());
+    }
+}
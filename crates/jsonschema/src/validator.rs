@@ -2,14 +2,26 @@
 //! The main idea is to create a tree from the input JSON Schema. This tree will contain
 //! everything needed to perform such validation in runtime.
 use crate::{
-    error::{error, no_error, ErrorIterator},
-    node::SchemaNode,
+    error::{error, no_error, ErrorIterator, ValidationErrorKind},
+    incremental::Recorder,
+    keywords::ref_::PendingReferences,
+    lint::Lint,
+    node::{DeferredChecks, SchemaNode},
     output::{Annotations, ErrorDescription, Output, OutputUnit},
-    paths::LazyLocation,
-    Draft, ValidationError, ValidationOptions,
+    paths::{LazyLocation, Location},
+    projection::ConstrainedPaths,
+    serialize, value, Draft, ValidationError, ValidationOptions,
 };
+use ahash::AHashMap;
+use referencing::uri;
 use serde_json::Value;
-use std::{collections::VecDeque, sync::Arc};
+use std::{
+    any::Any,
+    collections::VecDeque,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 /// The Validate trait represents a predicate over some JSON value. Some validators are very simple
 /// predicates such as "a value which is a string", whereas others may be much more complex,
@@ -36,6 +48,41 @@ pub(crate) trait Validate: Send + Sync {
     // It is faster for cases when the result is not needed (like anyOf), since errors are
     // not constructed
     fn is_valid(&self, instance: &Value) -> bool;
+    /// The same as `is_valid`, but subschemas may be checked through `checks`, which defers them
+    /// instead of recursing when validation is deeply nested.
+    ///
+    /// Only validators that are valid exactly when all of their checked subschemas are valid may
+    /// use `checks`, as a deferred check is assumed to pass until it actually runs.
+    fn is_valid_deferred<'i>(&self, instance: &'i Value, checks: &mut DeferredChecks<'i>) -> bool {
+        let _ = checks;
+        self.is_valid(instance)
+    }
+    /// Like `is_valid`, but the items of large arrays may be checked on the `rayon` thread pool.
+    #[cfg(feature = "parallel")]
+    fn par_is_valid(&self, instance: &Value) -> bool {
+        self.is_valid(instance)
+    }
+    /// Like `iter_errors`, but the items of large arrays may be validated on the `rayon` thread
+    /// pool. Errors are reported in the same order.
+    #[cfg(feature = "parallel")]
+    fn par_iter_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        self.iter_errors(instance, location)
+    }
+    /// The same as `is_valid`, but for instances that are not `serde_json::Value`.
+    ///
+    /// The default implementation converts the instance to `serde_json::Value`, which is correct
+    /// but slow, so validators should override it.
+    fn is_valid_value(&self, instance: &dyn value::Value) -> bool {
+        if let Some(instance) = instance.as_serde_json() {
+            self.is_valid(instance)
+        } else {
+            self.is_valid(&instance.to_json())
+        }
+    }
 
     fn validate<'i>(
         &self,
@@ -43,6 +90,21 @@ pub(crate) trait Validate: Send + Sync {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>>;
 
+    /// Push all errors produced by this validator to `errors`.
+    ///
+    /// The default implementation collects `iter_errors`. Validators composed of other
+    /// validators override it, so that nested validation writes to the same buffer instead of
+    /// allocating an iterator at every level.
+    fn collect_errors<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+        errors: &mut Vec<ValidationError<'i>>,
+        _scratch: &mut ValidationScratch,
+    ) {
+        errors.extend(self.iter_errors(instance, location));
+    }
+
     /// `apply` applies this validator and any sub-validators it is composed of to the value in
     /// question and collects the resulting annotations or errors. Note that the result of `apply`
     /// is a `PartialApplication`.
@@ -97,19 +159,44 @@ pub(crate) trait Validate: Send + Sync {
             PartialApplication::invalid_empty(errors)
         }
     }
+
+    /// Subschemas of `anyOf`, so `not` can tell which of them matched. Empty for other keywords.
+    fn any_of_branches(&self) -> &[SchemaNode] {
+        &[]
+    }
+}
+
+/// Report a validation failure as a `tracing` event.
+#[cfg(feature = "tracing")]
+fn trace_error(error: &ValidationError<'_>) {
+    tracing::debug!(
+        instance_path = %error.instance_path,
+        schema_path = %error.schema_path,
+        "{error}"
+    );
+}
+
+/// Report validation failures as `tracing` events within the current span once they are produced.
+#[cfg(feature = "tracing")]
+fn trace_errors(errors: ErrorIterator<'_>) -> ErrorIterator<'_> {
+    let span = tracing::Span::current();
+    Box::new(errors.inspect(move |error| span.in_scope(|| trace_error(error))))
 }
 
-/// The result of applying a validator to an instance. As explained in the documentation for
-/// `Validate::apply` this is a "partial" result because it does not include information about
-/// where the error or annotation occurred.
+/// The result of applying a validator to an instance, e.g. in [`crate::Keyword::apply`].
+///
+/// This is a "partial" result because it does not include information about where the error or
+/// annotation occurred, the enclosing schema fills it in.
 #[derive(Clone, PartialEq)]
-pub(crate) enum PartialApplication<'a> {
+pub enum PartialApplication<'a> {
+    /// The validator passed
     Valid {
         /// Annotations produced by this validator
         annotations: Option<Annotations<'a>>,
         /// Any outputs produced by validators which are children of this validator
         child_results: VecDeque<OutputUnit<Annotations<'a>>>,
     },
+    /// The validator failed
     Invalid {
         /// Errors which caused this schema to be invalid
         errors: Vec<ErrorDescription>,
@@ -120,7 +207,8 @@ pub(crate) enum PartialApplication<'a> {
 
 impl<'a> PartialApplication<'a> {
     /// Create an empty `PartialApplication` which is valid
-    pub(crate) fn valid_empty() -> PartialApplication<'static> {
+    #[must_use]
+    pub fn valid_empty() -> PartialApplication<'static> {
         PartialApplication::Valid {
             annotations: None,
             child_results: VecDeque::new(),
@@ -128,7 +216,8 @@ impl<'a> PartialApplication<'a> {
     }
 
     /// Create an empty `PartialApplication` which is invalid
-    pub(crate) fn invalid_empty(errors: Vec<ErrorDescription>) -> PartialApplication<'static> {
+    #[must_use]
+    pub fn invalid_empty(errors: Vec<ErrorDescription>) -> PartialApplication<'static> {
         PartialApplication::Invalid {
             errors,
             child_results: VecDeque::new(),
@@ -137,7 +226,7 @@ impl<'a> PartialApplication<'a> {
 
     /// Set the annotation that will be returned for the current validator. If this
     /// `PartialApplication` is invalid then this method does nothing
-    pub(crate) fn annotate(&mut self, new_annotations: Annotations<'a>) {
+    pub fn annotate(&mut self, new_annotations: Annotations<'a>) {
         match self {
             Self::Valid { annotations, .. } => *annotations = Some(new_annotations),
             Self::Invalid { .. } => {}
@@ -147,7 +236,7 @@ impl<'a> PartialApplication<'a> {
     /// Set the error that will be returned for the current validator. If this
     /// `PartialApplication` is valid then this method converts this application into
     /// `PartialApplication::Invalid`
-    pub(crate) fn mark_errored(&mut self, error: ErrorDescription) {
+    pub fn mark_errored(&mut self, error: ErrorDescription) {
         match self {
             Self::Invalid { errors, .. } => errors.push(error),
             Self::Valid { .. } => {
@@ -158,6 +247,177 @@ impl<'a> PartialApplication<'a> {
             }
         }
     }
+
+    /// Copy all borrowed annotations so the result outlives the schema it came from.
+    pub(crate) fn into_owned(self) -> PartialApplication<'static> {
+        match self {
+            Self::Valid {
+                annotations,
+                child_results,
+            } => PartialApplication::Valid {
+                annotations: annotations.map(Annotations::into_owned),
+                child_results: child_results
+                    .into_iter()
+                    .map(OutputUnit::into_owned)
+                    .collect(),
+            },
+            Self::Invalid {
+                errors,
+                child_results,
+            } => PartialApplication::Invalid {
+                errors,
+                child_results,
+            },
+        }
+    }
+}
+
+/// Temporary buffers reused between calls to [`Validator::validate_into_with`].
+///
+/// Keywords like `unevaluatedProperties` need per-call bookkeeping. Passing the same scratch to
+/// subsequent validations keeps these allocations around instead of creating them anew.
+///
+/// A scratch is not thread-safe to share: it must only be used by one validation at a time, so
+/// keep one per thread or per worker.
+#[derive(Default)]
+pub struct ValidationScratch<'c> {
+    pub(crate) evaluated: Vec<bool>,
+    /// Set only by [`Validator::validation_state`] and [`Validator::revalidate_at`]
+    pub(crate) incremental: Option<Box<Recorder>>,
+    /// Set only by [`Validator::validate_projection`]
+    pub(crate) projection: Option<Arc<ConstrainedPaths>>,
+    /// Set only by [`Validator::validate_with_budget`]
+    budget: Option<BudgetState>,
+    /// Set only by [`Validator::validate_with_context`]
+    context: Option<&'c (dyn Any + Sync)>,
+    /// Set only by [`Validator::validate_with_context`], to skip checks once an error is found
+    pub(crate) first_error_only: bool,
+}
+
+impl fmt::Debug for ValidationScratch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidationScratch")
+            .field("evaluated", &self.evaluated)
+            .field("incremental", &self.incremental)
+            .field("projection", &self.projection)
+            .field("budget", &self.budget)
+            .field("context", &self.context.map(|_| ".."))
+            .field("first_error_only", &self.first_error_only)
+            .finish()
+    }
+}
+
+impl<'c> ValidationScratch<'c> {
+    /// Create an empty scratch.
+    #[must_use]
+    pub fn new() -> Self {
+        ValidationScratch::default()
+    }
+    /// Count a visit of `instance` at `location` by the schema at `schema` towards the budget.
+    ///
+    /// Returns `false` once the budget is exceeded, and the visit should be skipped.
+    #[inline]
+    pub(crate) fn visit(&mut self, schema: &Location, location: &LazyLocation) -> bool {
+        match &mut self.budget {
+            Some(budget) => budget.visit(schema, location),
+            None => true,
+        }
+    }
+    /// The context passed to [`Validator::validate_with_context`], if any.
+    pub(crate) fn context(&self) -> Option<&'c (dyn Any + Sync)> {
+        self.context
+    }
+    /// Whether `instance` is valid against `node`.
+    ///
+    /// Under a budget or with a context, the check goes through the same path as the rest of the
+    /// validation. Otherwise, it is the same as `node.is_valid_at(instance, location)`. Instances
+    /// nested too deeply fail with `MaxInstanceDepth` either way.
+    pub(crate) fn is_valid<'i>(
+        &mut self,
+        node: &SchemaNode,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<bool, ValidationError<'i>> {
+        if self.budget.is_none() && self.context.is_none() {
+            return node.is_valid_at(instance, location);
+        }
+        let mut errors = Vec::new();
+        node.collect_errors(instance, location, &mut errors, self);
+        match errors
+            .iter()
+            .position(|error| matches!(error.kind, ValidationErrorKind::MaxInstanceDepth { .. }))
+        {
+            Some(idx) => Err(errors.swap_remove(idx)),
+            None => Ok(errors.is_empty()),
+        }
+    }
+}
+
+/// Limits for a single call to [`Validator::validate_with_budget`].
+///
+/// ```rust
+/// use jsonschema::Budget;
+/// use std::time::Duration;
+///
+/// let budget = Budget {
+///     max_nodes: 100_000,
+///     max_duration: Some(Duration::from_millis(50)),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    /// How many times subschemas may be applied to values of the instance.
+    pub max_nodes: usize,
+    /// How long validation may take. Checked every time a subschema is applied.
+    pub max_duration: Option<Duration>,
+}
+
+impl Default for Budget {
+    /// A budget without limits.
+    fn default() -> Self {
+        Budget {
+            max_nodes: usize::MAX,
+            max_duration: None,
+        }
+    }
+}
+
+/// Progress of a validation against its [`Budget`].
+#[derive(Debug)]
+struct BudgetState {
+    budget: Budget,
+    started: Instant,
+    visited: usize,
+    /// Schema and instance locations of the visit that exceeded the budget
+    exceeded: Option<(Location, Location)>,
+}
+
+impl BudgetState {
+    fn new(budget: Budget) -> BudgetState {
+        BudgetState {
+            budget,
+            started: Instant::now(),
+            visited: 0,
+            exceeded: None,
+        }
+    }
+
+    fn visit(&mut self, schema: &Location, location: &LazyLocation) -> bool {
+        if self.exceeded.is_some() {
+            return false;
+        }
+        if self.visited >= self.budget.max_nodes
+            || self
+                .budget
+                .max_duration
+                .is_some_and(|limit| self.started.elapsed() > limit)
+        {
+            self.exceeded = Some((schema.clone(), location.into()));
+            return false;
+        }
+        self.visited += 1;
+        true
+    }
 }
 
 /// A compiled JSON Schema validator.
@@ -169,6 +429,15 @@ impl<'a> PartialApplication<'a> {
 pub struct Validator {
     pub(crate) root: SchemaNode,
     pub(crate) config: Arc<ValidationOptions>,
+    pub(crate) draft: Draft,
+    pub(crate) schema: Arc<Value>,
+    pub(crate) keyword_locations: Vec<(Location, String)>,
+    pub(crate) constrained_paths: Arc<ConstrainedPaths>,
+    /// Distinct sources of the regular expressions compiled into this validator
+    pub(crate) patterns: Vec<String>,
+    pub(crate) external_references: Vec<String>,
+    pub(crate) pending: PendingReferences,
+    pub(crate) warnings: Vec<Lint>,
 }
 
 impl Validator {
@@ -196,20 +465,410 @@ impl Validator {
     /// Run validation against `instance` and return an iterator over [`ValidationError`] in the error case.
     #[inline]
     pub fn validate<'i>(&self, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-        self.root.validate(instance, &LazyLocation::new())
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validate").entered();
+        let result = self.root.validate(instance, &LazyLocation::new());
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &result {
+            trace_error(error);
+        }
+        result
+    }
+    /// Check whether `instance` is valid as quickly as [`Validator::is_valid`], and describe the
+    /// first failure found if it is not.
+    ///
+    /// Keywords are checked in the order `is_valid` uses, without tracking any locations. Only
+    /// the keyword that fails is validated again to build a complete error, so this is faster
+    /// than [`Validator::validate`] for invalid instances. The error may differ from the one
+    /// `validate` reports, as `validate` checks keywords in schema order.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"age": {"type": "integer"}}, "required": ["name"]});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// assert!(validator.validate_first(&json!({"name": "Alice"})).is_none());
+    /// let instance = json!({"age": "old"});
+    /// let error = validator.validate_first(&instance).expect("Invalid instance");
+    /// // `required` is cheaper to check than `properties`
+    /// assert_eq!(error.to_string(), r#""name" is a required property"#);
+    /// ```
+    #[must_use]
+    pub fn validate_first<'i>(&self, instance: &'i Value) -> Option<ValidationError<'i>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validate_first").entered();
+        let error = self.root.validate_first(instance);
+        #[cfg(feature = "tracing")]
+        if let Some(error) = &error {
+            trace_error(error);
+        }
+        error
     }
     /// Run validation against `instance` and return an iterator over [`ValidationError`] in the error case.
     #[inline]
     pub fn iter_errors<'i>(&'i self, instance: &'i Value) -> ErrorIterator<'i> {
-        self.root.iter_errors(instance, &LazyLocation::new())
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("iter_errors").entered();
+        let errors = self.root.iter_errors(instance, &LazyLocation::new());
+        #[cfg(feature = "tracing")]
+        let errors = trace_errors(errors);
+        errors
+    }
+    /// Parse `text` as JSON, validate it and locate every error in the text.
+    ///
+    /// Each [`SpannedError`](crate::SpannedError) carries the byte range, line and column of the
+    /// value at the error's instance path. The positions are found in a single pass over the text
+    /// after validation, so valid instances are not indexed at all.
+    ///
+    /// With [`ValidationOptions::with_reject_duplicate_keys`], the text is always scanned for
+    /// keys that appear more than once in the same object, as parsing only keeps the last one.
+    /// They are reported as
+    /// [`ValidationErrorKind::DuplicateKey`](crate::error::ValidationErrorKind::DuplicateKey)
+    /// before other errors, located at the repeated key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidateStrError::Parse`](crate::ValidateStrError::Parse) if `text` is not valid
+    /// JSON and [`ValidateStrError::Invalid`](crate::ValidateStrError::Invalid) with all
+    /// validation errors otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use jsonschema::ValidateStrError;
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"items": {"type": "integer"}}))
+    ///     .expect("Valid schema");
+    /// let Err(ValidateStrError::Invalid(errors)) = validator.validate_str("[1,\n \"2\"]") else {
+    ///     panic!("Should fail");
+    /// };
+    /// let span = errors[0].span.as_ref().expect("Located");
+    /// assert_eq!(errors[0].error.instance_path.as_str(), "/1");
+    /// assert_eq!((span.line, span.column), (2, 2));
+    /// assert_eq!(span.range, 5..8);
+    /// ```
+    pub fn validate_str(&self, text: &str) -> Result<(), crate::ValidateStrError> {
+        let instance: Value = serde_json::from_str(text).map_err(crate::ValidateStrError::Parse)?;
+        let errors: Vec<_> = self
+            .iter_errors(&instance)
+            .map(ValidationError::into_owned)
+            .collect();
+        let errors = crate::source::locate(text, errors, self.config.rejects_duplicate_keys());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::ValidateStrError::Invalid(errors))
+        }
+    }
+    /// Run validation against `instance` like [`Validator::iter_errors`], but validate the items
+    /// of large arrays in parallel on the `rayon` thread pool.
+    ///
+    /// Errors are reported in the same order as by [`Validator::iter_errors`].
+    #[cfg(feature = "parallel")]
+    pub fn par_iter_errors<'i>(&'i self, instance: &'i Value) -> ErrorIterator<'i> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("par_iter_errors").entered();
+        let errors = self.root.par_iter_errors(instance, &LazyLocation::new());
+        #[cfg(feature = "tracing")]
+        let errors = trace_errors(errors);
+        errors
+    }
+    /// Run validation against `instance` and store all errors in `errors`.
+    ///
+    /// `errors` is cleared first, but keeps its capacity, so reusing the same buffer across calls
+    /// avoids allocating it for every instance. Errors are reported in the same order as
+    /// [`Validator::iter_errors`]. As errors borrow from the instance, the buffer can only be
+    /// reused for instances that outlive it.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"maxLength": 3})).expect("Invalid schema");
+    /// let instances = [json!("foo"), json!("foobar")];
+    /// let mut errors = Vec::new();
+    /// for instance in &instances {
+    ///     validator.validate_into(instance, &mut errors);
+    ///     println!("{} errors", errors.len());
+    /// }
+    /// ```
+    pub fn validate_into<'i>(&self, instance: &'i Value, errors: &mut Vec<ValidationError<'i>>) {
+        self.validate_into_with(instance, errors, &mut ValidationScratch::new());
+    }
+    /// The same as [`Validator::validate_into`], but also reuses other temporary buffers from
+    /// `scratch`.
+    ///
+    /// ```rust
+    /// use jsonschema::ValidationScratch;
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "properties": {"id": {"type": "integer"}},
+    ///     "unevaluatedProperties": false
+    /// })).expect("Invalid schema");
+    /// let mut errors = Vec::new();
+    /// let mut scratch = ValidationScratch::new();
+    /// let instance = json!({"id": 1, "extra": true});
+    /// validator.validate_into_with(&instance, &mut errors, &mut scratch);
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    pub fn validate_into_with<'i>(
+        &self,
+        instance: &'i Value,
+        errors: &mut Vec<ValidationError<'i>>,
+        scratch: &mut ValidationScratch,
+    ) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validate_into").entered();
+        errors.clear();
+        self.root
+            .collect_errors(instance, &LazyLocation::new(), errors, scratch);
+        #[cfg(feature = "tracing")]
+        errors.iter().for_each(trace_error);
+    }
+    /// Run validation against `instance` like [`Validator::validate`], but stop once `budget`
+    /// is spent.
+    ///
+    /// Every application of a subschema to a value of the instance counts as one visited node,
+    /// and the deadline is checked before each of them. Use it for untrusted input that could
+    /// otherwise keep a single call busy for a long time. A single keyword, like `uniqueItems`
+    /// on one large array, is not interrupted. Other entry points do not track any budget.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first validation error like [`Validator::validate`], or
+    /// [`ValidationErrorKind::BudgetExceeded`](crate::error::ValidationErrorKind::BudgetExceeded)
+    /// located where validation stopped.
+    ///
+    /// ```rust
+    /// use jsonschema::{error::ValidationErrorKind, Budget};
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"items": {"type": "integer"}}))
+    ///     .expect("Invalid schema");
+    /// let budget = Budget {
+    ///     max_nodes: 3,
+    ///     ..Budget::default()
+    /// };
+    /// assert!(validator.validate_with_budget(&json!([1, 2]), budget).is_ok());
+    /// let instance = json!([1, 2, 3, 4]);
+    /// let error = validator
+    ///     .validate_with_budget(&instance, budget)
+    ///     .expect_err("Too many nodes");
+    /// assert!(matches!(
+    ///     error.kind,
+    ///     ValidationErrorKind::BudgetExceeded { visited: 3, .. }
+    /// ));
+    /// assert_eq!(error.instance_path.as_str(), "/2");
+    /// ```
+    pub fn validate_with_budget<'i>(
+        &self,
+        instance: &'i Value,
+        budget: Budget,
+    ) -> Result<(), ValidationError<'i>> {
+        let mut scratch = ValidationScratch {
+            budget: Some(BudgetState::new(budget)),
+            ..ValidationScratch::default()
+        };
+        let mut errors = Vec::new();
+        self.root
+            .collect_errors(instance, &LazyLocation::new(), &mut errors, &mut scratch);
+        let state = scratch.budget.expect("Budget is set");
+        if let Some((schema_path, instance_path)) = state.exceeded {
+            let value = instance.pointer(instance_path.as_str()).unwrap_or(instance);
+            return Err(ValidationError::budget_exceeded(
+                schema_path,
+                instance_path,
+                value,
+                state.visited,
+                state.started.elapsed(),
+            ));
+        }
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+    /// Run validation against `instance` like [`Validator::validate`], and pass `context` to
+    /// custom keywords through [`Keyword::validate_with_context`](crate::Keyword::validate_with_context).
+    ///
+    /// Use it for data that changes between calls, like the current time or the tenant of a
+    /// request. Built-in keywords ignore the context, and other entry points do not pass any.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first validation error like [`Validator::validate`].
+    ///
+    /// ```rust
+    /// use jsonschema::{
+    ///     downcast_context, paths::{LazyLocation, Location}, Keyword, ValidationError,
+    /// };
+    /// use serde_json::{json, Value};
+    ///
+    /// struct Tenant(&'static str);
+    ///
+    /// struct SameTenant;
+    ///
+    /// impl Keyword for SameTenant {
+    ///     fn validate<'i>(&self, _: &'i Value, _: &LazyLocation) -> Result<(), ValidationError<'i>> {
+    ///         Ok(())
+    ///     }
+    ///     fn validate_with_context<'i>(
+    ///         &self,
+    ///         instance: &'i Value,
+    ///         location: &LazyLocation,
+    ///         context: &dyn std::any::Any,
+    ///     ) -> Result<(), ValidationError<'i>> {
+    ///         let tenant = downcast_context::<Tenant>(context, instance, location)?;
+    ///         if instance == tenant.0 {
+    ///             Ok(())
+    ///         } else {
+    ///             Err(ValidationError::custom(Location::new(), location.into(), instance, "Wrong tenant"))
+    ///         }
+    ///     }
+    ///     fn is_valid(&self, _: &Value) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_keyword("x-same-tenant", |_, _, _| Ok(Box::new(SameTenant)))
+    ///     .build(&json!({"properties": {"tenant": {"x-same-tenant": true}}}))
+    ///     .expect("Invalid schema");
+    /// let instance = json!({"tenant": "acme"});
+    /// assert!(validator.validate_with_context(&instance, &Tenant("acme")).is_ok());
+    /// assert!(validator.validate_with_context(&instance, &Tenant("other")).is_err());
+    /// ```
+    pub fn validate_with_context<'i, C: Any + Sync>(
+        &self,
+        instance: &'i Value,
+        context: &C,
+    ) -> Result<(), ValidationError<'i>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validate_with_context").entered();
+        let mut scratch = ValidationScratch {
+            context: Some(context),
+            first_error_only: true,
+            ..ValidationScratch::default()
+        };
+        let mut errors = Vec::new();
+        self.root
+            .collect_errors(instance, &LazyLocation::new(), &mut errors, &mut scratch);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
     }
     /// Run validation against `instance` but return a boolean result instead of an iterator.
     /// It is useful for cases, where it is important to only know the fact if the data is valid or not.
     /// This approach is much faster, than [`Validator::validate`].
+    ///
+    /// Besides [`serde_json::Value`], any type implementing [`value::Value`] can be validated.
     #[must_use]
     #[inline]
-    pub fn is_valid(&self, instance: &Value) -> bool {
-        self.root.is_valid(instance)
+    pub fn is_valid<V: value::Value>(&self, instance: &V) -> bool {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("is_valid").entered();
+        if let Some(instance) = instance.as_serde_json() {
+            self.root.is_valid(instance)
+        } else {
+            self.root.is_valid_value(instance)
+        }
+    }
+    /// Parse `instance` as YAML with [`crate::from_yaml_str`] and check whether it is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConversionError`](crate::ConversionError) if the document can not be converted
+    /// into JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), jsonschema::ConversionError> {
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"required": ["name"]})).expect("Valid schema");
+    /// assert!(validator.is_valid_yaml("name: Jane")?);
+    /// assert!(!validator.is_valid_yaml("age: 42")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn is_valid_yaml(&self, instance: &str) -> Result<bool, crate::ConversionError> {
+        Ok(self.root.is_valid(&crate::from_yaml_str(instance)?))
+    }
+    /// Decode `instance` as CBOR with [`crate::from_cbor_slice`] and check whether it is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConversionError`](crate::ConversionError) if the item can not be converted into
+    /// JSON.
+    #[cfg(feature = "cbor")]
+    pub fn is_valid_cbor(&self, instance: &[u8]) -> Result<bool, crate::ConversionError> {
+        Ok(self.root.is_valid(&crate::from_cbor_slice(instance)?))
+    }
+    /// Run validation against `instance` like [`Validator::is_valid`], but check the items of
+    /// large arrays in parallel on the `rayon` thread pool.
+    ///
+    /// Only the items of a top-level array are split between threads, including when the root
+    /// schema applies `items` via `$ref`. Nested arrays are checked by the thread that handles
+    /// their parent item.
+    #[cfg(feature = "parallel")]
+    #[must_use]
+    pub fn par_is_valid(&self, instance: &Value) -> bool {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("par_is_valid").entered();
+        self.root.par_is_valid(instance)
+    }
+    /// Validate any [`serde::Serialize`] value without converting it to [`serde_json::Value`] first.
+    ///
+    /// Valid instances are checked on a lightweight intermediate representation. Only when the
+    /// instance is invalid, it is converted to [`serde_json::Value`] to build an error whose
+    /// instance path refers to struct field names and sequence indexes. If `instance` can not be
+    /// represented as JSON (e.g. a map with non-string keys), a custom error is returned.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Payload {
+    ///     count: i32,
+    /// }
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "properties": {"count": {"minimum": 0}}
+    /// })).expect("Invalid schema");
+    ///
+    /// assert!(validator.validate_serialize(&Payload { count: 1 }).is_ok());
+    /// let error = validator
+    ///     .validate_serialize(&Payload { count: -1 })
+    ///     .expect_err("Should fail");
+    /// assert_eq!(error.instance_path.as_str(), "/count");
+    /// ```
+    pub fn validate_serialize<T: serde::Serialize + ?Sized>(
+        &self,
+        instance: &T,
+    ) -> Result<(), ValidationError<'static>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validate_serialize").entered();
+        match serialize::to_node(instance) {
+            Ok(node) => {
+                if self.root.is_valid_value(&node) {
+                    return Ok(());
+                }
+                self.validate(&value::Value::to_json(&node))
+                    .map_err(ValidationError::into_owned)
+            }
+            Err(error) => Err(ValidationError::custom(
+                Location::new(),
+                Location::new(),
+                &Value::Null,
+                error.to_string(),
+            )
+            .into_owned()),
+        }
     }
     /// Apply the schema and return an [`Output`]. No actual work is done at this point, the
     /// evaluation of the schema is deferred until a method is called on the `Output`. This is
@@ -256,72 +915,388 @@ impl Validator {
         Output::new(self, &self.root, instance)
     }
 
-    /// The [`Draft`] which was used to build this validator.
+    /// The [`Draft`] which was used to build this validator, either set explicitly or detected
+    /// from `$schema`.
+    ///
+    /// ```rust
+    /// use jsonschema::Draft;
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "$schema": "http://json-schema.org/draft-07/schema#"
+    /// })).expect("Invalid schema");
+    /// assert_eq!(validator.draft(), Draft::Draft7);
+    /// ```
     #[must_use]
     pub fn draft(&self) -> Draft {
-        self.config.draft()
+        self.draft
     }
 
-    /// The [`ValidationOptions`] that were used to build this validator.
+    /// The configuration this validator was built with, as it applies to its draft.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "$schema": "http://json-schema.org/draft-07/schema#"
+    /// })).expect("Invalid schema");
+    /// // Draft 7 asserts `format` by default
+    /// assert!(validator.config().validates_formats());
+    /// ```
     #[must_use]
-    pub fn config(&self) -> Arc<ValidationOptions> {
-        Arc::clone(&self.config)
+    pub fn config(&self) -> ValidatorConfig<'_> {
+        ValidatorConfig { validator: self }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        error::ValidationError,
-        keywords::custom::Keyword,
-        paths::{LazyLocation, Location},
-        primitive_type::PrimitiveType,
-        Validator,
-    };
-    use fancy_regex::Regex;
-    use num_cmp::NumCmp;
-    use once_cell::sync::Lazy;
-    use serde_json::{json, Map, Value};
+    /// The schema this validator was compiled from.
+    #[must_use]
+    pub fn schema(&self) -> &Arc<Value> {
+        &self.schema
+    }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn load(path: &str, idx: usize) -> Value {
-        use std::{fs::File, io::Read, path::Path};
-        let path = Path::new(path);
-        let mut file = File::open(path).unwrap();
-        let mut content = String::new();
-        file.read_to_string(&mut content).ok().unwrap();
-        let data: Value = serde_json::from_str(&content).unwrap();
-        let case = &data.as_array().unwrap()[idx];
-        case.get("schema").unwrap().clone()
+    /// A hash of [`Validator::schema`] that is the same across runs, platforms and versions of
+    /// this crate, e.g. to log which schema an instance failed.
+    ///
+    /// It is the 64-bit FNV-1a hash of the schema serialized as compact JSON with object keys
+    /// sorted, so the order of keys and whitespace in the original document do not matter.
+    /// The schema is hashed on every call.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let first = jsonschema::validator_for(&json!({"type": "string", "minLength": 1}))
+    ///     .expect("Invalid schema");
+    /// let second = jsonschema::validator_for(&json!({"minLength": 1, "type": "string"}))
+    ///     .expect("Invalid schema");
+    /// assert_eq!(first.schema_hash(), second.schema_hash());
+    /// ```
+    #[must_use]
+    pub fn schema_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        write_canonical(&mut hasher, &self.schema);
+        hasher.0
     }
 
-    #[test]
-    fn only_keyword() {
-        // When only one keyword is specified
-        let schema = json!({"type": "string"});
-        let validator = crate::validator_for(&schema).unwrap();
-        let value1 = json!("AB");
-        let value2 = json!(1);
-        // And only this validator
-        assert_eq!(validator.root.validators().len(), 1);
-        assert!(validator.validate(&value1).is_ok());
-        assert!(validator.validate(&value2).is_err());
+    /// Locations of all keywords that were compiled into this validator, paired with the keyword
+    /// names. Annotation-only and unknown keywords are not included.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({
+    ///     "properties": {"name": {"type": "string"}}
+    /// })).expect("Invalid schema");
+    ///
+    /// let keywords: Vec<_> = validator
+    ///     .keyword_locations()
+    ///     .map(|(location, keyword)| (location.as_str(), keyword))
+    ///     .collect();
+    /// assert_eq!(
+    ///     keywords,
+    ///     [("/properties", "properties"), ("/properties/name/type", "type")]
+    /// );
+    /// ```
+    pub fn keyword_locations(&self) -> impl Iterator<Item = (&Location, &str)> {
+        self.keyword_locations
+            .iter()
+            .map(|(location, keyword)| (location, keyword.as_str()))
     }
 
-    #[test]
-    #[cfg(not(target_arch = "wasm32"))]
-    fn validate_ref() {
-        let schema = load("tests/suite/tests/draft7/ref.json", 1);
-        let value = json!({"bar": 3});
-        let validator = crate::validator_for(&schema).unwrap();
-        assert!(validator.validate(&value).is_ok());
-        let value = json!({"bar": true});
-        assert!(validator.validate(&value).is_err());
+    /// Size and complexity of the compiled schema, e.g. to reject overly complex schemas from
+    /// untrusted sources. See [`SchemaStats`](crate::SchemaStats).
+    #[must_use]
+    pub fn stats(&self) -> crate::SchemaStats {
+        crate::SchemaStats::of(self)
     }
 
-    #[test]
-    fn wrong_schema_type() {
-        let schema = json!([1]);
+    /// Parts of the schema that were left out during compilation because they never change
+    /// whether an instance is valid, like `false` in `anyOf`, keywords next to `$ref` in
+    /// Draft 7 and earlier, or `additionalItems` without `items` and in Draft 2020-12. Apart
+    /// from these, only [`OptimizationLevel::Prune`](crate::OptimizationLevel::Prune) leaves
+    /// anything out.
+    ///
+    /// ```rust
+    /// use jsonschema::{LintCode, OptimizationLevel};
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_optimization_level(OptimizationLevel::Prune)
+    ///     .build(&json!({"anyOf": [{"type": "string"}, false]}))
+    ///     .expect("Invalid schema");
+    ///
+    /// let warnings = validator.warnings();
+    /// assert_eq!(warnings[0].code, LintCode::UnreachableBranch);
+    /// assert_eq!(warnings[0].location.as_str(), "/anyOf/1");
+    /// ```
+    #[must_use]
+    pub fn warnings(&self) -> &[Lint] {
+        &self.warnings
+    }
+
+    /// Compile all `$ref` targets that are not compiled yet, e.g. to warm up a validator built
+    /// with [`ValidationOptions::with_lazy_references`]. Does nothing otherwise.
+    ///
+    /// Errors in the targets are still reported as validation errors.
+    pub fn precompile(&self) {
+        self.pending.compile_all();
+    }
+
+    /// Absolute URIs of the external documents this validator depends on, i.e. documents that
+    /// were fetched via the retriever or referenced resources registered with
+    /// [`ValidationOptions::with_resource`]. Useful for dependency tracking and cache invalidation.
+    pub fn external_references(&self) -> impl Iterator<Item = &str> {
+        self.external_references.iter().map(String::as_str)
+    }
+}
+
+/// A read-only view of the configuration of a [`Validator`], returned by [`Validator::config`].
+///
+/// Settings that depend on the draft, like whether `format` is asserted, are reported for the
+/// draft the validator was built for.
+#[derive(Clone, Copy)]
+pub struct ValidatorConfig<'a> {
+    validator: &'a Validator,
+}
+
+impl ValidatorConfig<'_> {
+    /// The [`Draft`] the validator was built for. Same as [`Validator::draft`].
+    #[must_use]
+    pub fn draft(&self) -> Draft {
+        self.validator.draft
+    }
+    /// Whether `format` is asserted, as set by [`ValidationOptions::should_validate_formats`]
+    /// or by default for the draft.
+    #[must_use]
+    pub fn validates_formats(&self) -> bool {
+        self.options().validates_formats_for(self.validator.draft)
+    }
+    /// Whether unknown formats are ignored. See [`ValidationOptions::should_ignore_unknown_formats`].
+    #[must_use]
+    pub fn ignores_unknown_formats(&self) -> bool {
+        self.options().are_unknown_formats_ignored()
+    }
+    /// Whether `$ref` targets are compiled on first use. See [`ValidationOptions::with_lazy_references`].
+    #[must_use]
+    pub fn has_lazy_references(&self) -> bool {
+        self.options().are_references_lazy()
+    }
+    /// Whether `default` values were checked against their subschemas. See
+    /// [`ValidationOptions::with_validate_defaults`].
+    #[must_use]
+    pub fn validates_defaults(&self) -> bool {
+        self.options().validates_defaults()
+    }
+    /// Whether `examples` were checked against their subschemas. See
+    /// [`ValidationOptions::with_validate_examples`].
+    #[must_use]
+    pub fn validates_examples(&self) -> bool {
+        self.options().validates_examples()
+    }
+    /// Whether [`Validator::validate_str`] reports duplicate keys. See
+    /// [`ValidationOptions::with_reject_duplicate_keys`].
+    #[must_use]
+    pub fn rejects_duplicate_keys(&self) -> bool {
+        self.options().rejects_duplicate_keys()
+    }
+    /// Whether errors carry extra details. See [`ValidationOptions::with_verbose_errors`].
+    #[must_use]
+    pub fn has_verbose_errors(&self) -> bool {
+        self.options().are_errors_verbose()
+    }
+    /// See [`ValidationOptions::with_optimization_level`].
+    #[must_use]
+    pub fn optimization_level(&self) -> crate::OptimizationLevel {
+        self.options().optimization_level()
+    }
+    /// See [`ValidationOptions::with_email_strictness`].
+    #[must_use]
+    pub fn email_strictness(&self) -> crate::EmailStrictness {
+        self.options().email_strictness()
+    }
+    /// See [`ValidationOptions::with_keyword_order`].
+    #[must_use]
+    pub fn keyword_order(&self) -> crate::KeywordOrder {
+        self.options().keyword_order()
+    }
+    /// See [`ValidationOptions::with_max_instance_depth`].
+    #[must_use]
+    pub fn max_instance_depth(&self) -> usize {
+        self.options().max_instance_depth()
+    }
+    /// See [`ValidationOptions::with_content_size_limit`].
+    #[must_use]
+    pub fn content_size_limit(&self) -> Option<usize> {
+        self.options().content_size_limit()
+    }
+    /// See [`ValidationOptions::with_content_capture`].
+    #[must_use]
+    pub fn captures_content(&self) -> bool {
+        self.options().captures_content()
+    }
+    /// The [`ValidationOptions`] the validator was built with, e.g. to build another validator
+    /// with the same settings.
+    #[must_use]
+    pub fn options(&self) -> &Arc<ValidationOptions> {
+        &self.validator.config
+    }
+}
+
+impl std::fmt::Debug for ValidatorConfig<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ValidatorConfig")
+            .field("draft", &self.draft())
+            .field("validates_formats", &self.validates_formats())
+            .field("options", self.options())
+            .finish()
+    }
+}
+
+/// Validators for a set of documents compiled together with [`ValidationOptions::compile_all`].
+///
+/// References between the documents are resolved within the set, and subschemas reached from
+/// several documents are compiled once and shared by their validators.
+#[derive(Debug)]
+pub struct CompiledRegistry {
+    pub(crate) validators: Vec<Validator>,
+    /// Position in `validators` by the document URI and its root `$id`
+    pub(crate) index: AHashMap<String, usize>,
+}
+
+impl CompiledRegistry {
+    /// The validator rooted at the document with the given URI or root `$id`.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let registry = jsonschema::options()
+    ///     .compile_all([
+    ///         ("https://example.com/name", json!({"type": "string"})),
+    ///         ("https://example.com/person", json!({
+    ///             "properties": {"name": {"$ref": "name"}}
+    ///         })),
+    ///     ])
+    ///     .expect("Valid schemas");
+    /// let validator = registry
+    ///     .validator_for("https://example.com/person")
+    ///     .expect("Known document");
+    /// assert!(!validator.is_valid(&json!({"name": 42})));
+    /// ```
+    #[must_use]
+    pub fn validator_for(&self, uri: &str) -> Option<&Validator> {
+        let uri = uri::from_str(uri.trim_end_matches('#')).ok()?;
+        self.index
+            .get(uri.as_str())
+            .map(|&idx| &self.validators[idx])
+    }
+    /// Validators of all documents, in the order they were given.
+    pub fn validators(&self) -> impl Iterator<Item = &Validator> {
+        self.validators.iter()
+    }
+}
+
+/// 64-bit FNV-1a, which is simple enough to give the same results everywhere.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Feed `value` to `hasher` as compact JSON with sorted object keys.
+fn write_canonical(hasher: &mut Fnv1a, value: &Value) {
+    match value {
+        Value::Array(items) => {
+            hasher.write(b"[");
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    hasher.write(b",");
+                }
+                write_canonical(hasher, item);
+            }
+            hasher.write(b"]");
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            hasher.write(b"{");
+            for (idx, (key, value)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    hasher.write(b",");
+                }
+                hasher.write(Value::from(key.as_str()).to_string().as_bytes());
+                hasher.write(b":");
+                write_canonical(hasher, value);
+            }
+            hasher.write(b"}");
+        }
+        // Scalars have a single compact representation
+        _ => hasher.write(value.to_string().as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::Regex;
+    use crate::{
+        error::{ValidationError, ValidationErrorKind},
+        keywords::custom::Keyword,
+        paths::{LazyLocation, Location},
+        primitive_type::PrimitiveType,
+        validator::{PartialApplication, ValidationScratch},
+        Budget, Draft, Validator,
+    };
+    use num_cmp::NumCmp;
+    use once_cell::sync::Lazy;
+    use serde_json::{json, Map, Value};
+    use std::time::Duration;
+    use test_case::test_case;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load(path: &str, idx: usize) -> Value {
+        use std::{fs::File, io::Read, path::Path};
+        let path = Path::new(path);
+        let mut file = File::open(path).unwrap();
+        let mut content = String::new();
+        file.read_to_string(&mut content).ok().unwrap();
+        let data: Value = serde_json::from_str(&content).unwrap();
+        let case = &data.as_array().unwrap()[idx];
+        case.get("schema").unwrap().clone()
+    }
+
+    #[test]
+    fn only_keyword() {
+        // When only one keyword is specified
+        let schema = json!({"type": "string"});
+        let validator = crate::validator_for(&schema).unwrap();
+        let value1 = json!("AB");
+        let value2 = json!(1);
+        // And only this validator
+        assert_eq!(validator.root.validators().len(), 1);
+        assert!(validator.validate(&value1).is_ok());
+        assert!(validator.validate(&value2).is_err());
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn validate_ref() {
+        let schema = load("tests/suite/tests/draft7/ref.json", 1);
+        let value = json!({"bar": 3});
+        let validator = crate::validator_for(&schema).unwrap();
+        assert!(validator.validate(&value).is_ok());
+        let value = json!({"bar": true});
+        assert!(validator.validate(&value).is_err());
+    }
+
+    #[test]
+    fn wrong_schema_type() {
+        let schema = json!([1]);
         let validator = crate::validator_for(&schema);
         assert!(validator.is_err());
     }
@@ -571,9 +1546,1129 @@ mod tests {
         assert_eq!(error.to_string(), "\"foo\" is not of type \"number\"");
     }
 
+    #[test]
+    fn custom_keyword_apply_and_evaluated_properties() {
+        /// Requires properties with the given prefix to be strings.
+        struct PrefixedStrings(String);
+        impl PrefixedStrings {
+            fn matching<'i>(&self, instance: &'i Value) -> Vec<(&'i str, &'i Value)> {
+                instance
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter(|(key, _)| key.starts_with(&self.0))
+                    .map(|(key, value)| (key.as_str(), value))
+                    .collect()
+            }
+        }
+        impl Keyword for PrefixedStrings {
+            fn validate<'i>(
+                &self,
+                instance: &'i Value,
+                location: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                if self.is_valid(instance) {
+                    Ok(())
+                } else {
+                    Err(ValidationError::custom(
+                        Location::new(),
+                        location.into(),
+                        instance,
+                        "Prefixed property is not a string",
+                    ))
+                }
+            }
+            fn is_valid(&self, instance: &Value) -> bool {
+                self.matching(instance)
+                    .iter()
+                    .all(|(_, value)| value.is_string())
+            }
+            fn apply<'a>(
+                &'a self,
+                instance: &Value,
+                location: &LazyLocation,
+            ) -> PartialApplication<'a> {
+                let mut result = PartialApplication::valid_empty();
+                if let Err(error) = self.validate(instance, location) {
+                    result.mark_errored(error.into());
+                } else {
+                    let names: Vec<_> = self
+                        .matching(instance)
+                        .into_iter()
+                        .map(|(key, _)| key)
+                        .collect();
+                    result.annotate(Value::from(names).into());
+                }
+                result
+            }
+            fn evaluated_properties<'i>(&self, instance: &'i Value) -> Vec<&'i str> {
+                self.matching(instance)
+                    .into_iter()
+                    .filter(|(_, value)| value.is_string())
+                    .map(|(key, _)| key)
+                    .collect()
+            }
+        }
+
+        let schema = json!({
+            "prefixed-strings": "x-",
+            "properties": {"id": {"type": "integer"}},
+            "unevaluatedProperties": false
+        });
+        let validator = crate::options()
+            .with_keyword("prefixed-strings", |_, schema: &Value, _| {
+                Ok(Box::new(PrefixedStrings(
+                    schema.as_str().unwrap_or_default().to_string(),
+                )) as Box<dyn Keyword>)
+            })
+            .build(&schema)
+            .expect("Invalid schema");
+
+        assert!(validator.is_valid(&json!({"id": 1, "x-a": "b"})));
+        assert!(!validator.is_valid(&json!({"id": 1, "x-a": 2})));
+        assert!(!validator.is_valid(&json!({"id": 1, "y": "b"})));
+
+        let output = serde_json::to_value(validator.apply(&json!({"x-a": "b"})).basic())
+            .expect("Serializable");
+        let annotations = output["annotations"]
+            .as_array()
+            .expect("Annotations")
+            .iter()
+            .find(|unit| unit["keywordLocation"] == "/prefixed-strings")
+            .expect("Custom keyword annotations");
+        assert_eq!(annotations["annotations"], json!(["x-a"]));
+        let output = validator.apply(&json!({"x-a": 1})).basic();
+        assert!(!output.is_valid());
+    }
+
+    #[test]
+    fn custom_keyword_error_code_and_payload() {
+        struct Even(Location);
+        impl Keyword for Even {
+            fn validate<'i>(
+                &self,
+                instance: &'i Value,
+                location: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                if self.is_valid(instance) {
+                    Ok(())
+                } else {
+                    Err(ValidationError::custom(
+                        self.0.clone(),
+                        location.into(),
+                        instance,
+                        format!("{instance} is not even"),
+                    )
+                    .with_code("not-even")
+                    .with_payload(json!({"remainder": 1})))
+                }
+            }
+            fn is_valid(&self, instance: &Value) -> bool {
+                instance.as_u64().map_or(true, |n| n % 2 == 0)
+            }
+        }
+
+        let schema = json!({"items": {"even": true}});
+        let validator = crate::options()
+            .with_keyword("even", |_, _, location| {
+                Ok(Box::new(Even(location)) as Box<dyn Keyword>)
+            })
+            .build(&schema)
+            .expect("Invalid schema");
+        let instance = json!([2, 3]);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.schema_path.as_str(), "/items/even");
+        assert_eq!(error.instance_path.as_str(), "/1");
+        assert_eq!(error.keyword(), "even");
+        let ValidationErrorKind::Custom {
+            message,
+            code,
+            payload,
+        } = &error.kind
+        else {
+            panic!("Unexpected error kind: {:?}", error.kind);
+        };
+        assert_eq!(message, "3 is not even");
+        assert_eq!(code.as_deref(), Some("not-even"));
+        assert_eq!(payload, &Some(json!({"remainder": 1})));
+
+        let output =
+            serde_json::to_value(validator.apply(&instance).basic()).expect("Serializable");
+        assert_eq!(
+            output,
+            json!({
+                "valid": false,
+                "errors": [{
+                    "keywordLocation": "/items/even",
+                    "instanceLocation": "/1",
+                    "error": "3 is not even"
+                }]
+            })
+        );
+    }
+
+    /// Accepts the values listed in a table that the keyword's value references.
+    struct Lookup(Vec<Value>);
+
+    impl Keyword for Lookup {
+        fn validate<'i>(
+            &self,
+            instance: &'i Value,
+            location: &LazyLocation,
+        ) -> Result<(), ValidationError<'i>> {
+            if self.is_valid(instance) {
+                Ok(())
+            } else {
+                Err(ValidationError::custom(
+                    Location::new(),
+                    location.into(),
+                    instance,
+                    "Unknown value",
+                ))
+            }
+        }
+        fn is_valid(&self, instance: &Value) -> bool {
+            self.0.contains(instance)
+        }
+    }
+
+    fn lookup_options() -> crate::ValidationOptions {
+        let mut options = crate::options();
+        options
+            .with_resource(
+                "https://example.com/tables.json",
+                crate::Resource::from_contents(json!({"currencies": ["EUR", "USD"]}))
+                    .expect("Valid resource"),
+            )
+            .with_contextual_keyword("x-lookup", |ctx, _, value, _| {
+                let reference = value["$ref"].as_str().unwrap_or_default();
+                let table = ctx.resolve_value(reference)?;
+                Ok(Box::new(Lookup(
+                    table.as_array().cloned().unwrap_or_default(),
+                )))
+            });
+        options
+    }
+
+    #[test_case(&json!({"x-lookup": {"$ref": "#/definitions/tables/currencies"}, "definitions": {"tables": {"currencies": ["EUR", "USD"]}}}); "local")]
+    #[test_case(&json!({"x-lookup": {"$ref": "https://example.com/tables.json#/currencies"}}); "registry")]
+    #[test_case(&json!({"$id": "https://example.com/root.json", "x-lookup": {"$ref": "tables.json#/currencies"}}); "relative")]
+    #[test_case(&json!({"properties": {"a": {"$id": "https://example.com/a.json", "x-lookup": {"$ref": "tables.json#/currencies"}}}}); "nested base")]
+    fn custom_keyword_resolve_value(schema: &Value) {
+        let validator = lookup_options().build(schema).expect("Invalid schema");
+        let (valid, invalid) = if schema.get("properties").is_some() {
+            (json!({"a": "EUR"}), json!({"a": "GBP"}))
+        } else {
+            (json!("EUR"), json!("GBP"))
+        };
+        assert!(validator.is_valid(&valid));
+        assert!(!validator.is_valid(&invalid));
+    }
+
+    #[test]
+    fn custom_keyword_resolve_value_not_compiled() {
+        // The target would be an invalid schema
+        let schema = json!({
+            "x-lookup": {"$ref": "#/definitions/tables/odd"},
+            "definitions": {"tables": {"odd": [{"type": 42}]}}
+        });
+        let validator = lookup_options().build(&schema).expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"type": 42})));
+        let keywords: Vec<_> = validator
+            .keyword_locations()
+            .map(|(location, _)| location.as_str().to_string())
+            .collect();
+        assert_eq!(keywords, ["/x-lookup"]);
+    }
+
+    #[test]
+    fn custom_keyword_resolve_value_missing() {
+        let schema = json!({"x-lookup": {"$ref": "#/definitions/missing"}});
+        let error = lookup_options().build(&schema).expect_err("Should fail");
+        assert!(matches!(error.kind, ValidationErrorKind::Referencing(_)));
+    }
+
+    /// Percentages between 0 and 100, only allowed next to `"type": "number"`.
+    struct Percent;
+
+    impl Keyword for Percent {
+        fn validate<'i>(
+            &self,
+            instance: &'i Value,
+            location: &LazyLocation,
+        ) -> Result<(), ValidationError<'i>> {
+            if self.is_valid(instance) {
+                Ok(())
+            } else {
+                Err(ValidationError::custom(
+                    Location::new(),
+                    location.into(),
+                    instance,
+                    "Not a percentage",
+                ))
+            }
+        }
+        fn is_valid(&self, instance: &Value) -> bool {
+            match instance.as_f64() {
+                Some(value) => (0.0..=100.0).contains(&value),
+                None => true,
+            }
+        }
+    }
+
+    fn units_options() -> crate::ValidationOptions {
+        let mut options = crate::options();
+        options.with_contextual_keyword("x-units", |ctx, parent, value, _| {
+            if parent.get("type") != Some(&json!("number")) {
+                return Err(ValidationError::custom(
+                    Location::new(),
+                    ctx.location().join("type"),
+                    parent.get("type").unwrap_or(&Value::Null),
+                    "`x-units` requires `\"type\": \"number\"`",
+                ));
+            }
+            if value == "percent" {
+                Ok(Box::new(Percent))
+            } else {
+                Err(ValidationError::custom(
+                    Location::new(),
+                    Location::new(),
+                    value,
+                    "Unknown units",
+                ))
+            }
+        });
+        options
+    }
+
+    #[test]
+    fn custom_keyword_siblings() {
+        let schema = json!({
+            "properties": {"ratio": {"type": "number", "x-units": "percent"}}
+        });
+        let validator = units_options().build(&schema).expect("Valid schema");
+        assert!(validator.is_valid(&json!({"ratio": 42.5})));
+        let instance = json!({"ratio": 142});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.to_string(), "Not a percentage");
+        assert_eq!(error.instance_path.as_str(), "/ratio");
+    }
+
+    #[test_case(&json!({"properties": {"ratio": {"type": "string", "x-units": "percent"}}}), "/properties/ratio/type", "`x-units` requires `\"type\": \"number\"`"; "sibling")]
+    #[test_case(&json!({"items": {"type": "number", "x-units": "kelvin"}}), "/items/x-units", "Unknown units"; "located at the keyword")]
+    fn custom_keyword_compile_error(schema: &Value, location: &str, message: &str) {
+        let error = units_options().build(schema).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), location);
+        assert_eq!(error.to_string(), message);
+    }
+
+    /// The current date as `YYYY-MM-DD`.
+    struct Now(&'static str);
+
+    /// Dates that are not before the given one, as of the `Now` context. Without a context,
+    /// every date is valid.
+    struct NotBefore;
+
+    impl Keyword for NotBefore {
+        fn validate<'i>(&self, _: &'i Value, _: &LazyLocation) -> Result<(), ValidationError<'i>> {
+            Ok(())
+        }
+        fn validate_with_context<'i>(
+            &self,
+            instance: &'i Value,
+            location: &LazyLocation,
+            context: &dyn std::any::Any,
+        ) -> Result<(), ValidationError<'i>> {
+            let now = crate::downcast_context::<Now>(context, instance, location)?;
+            match instance.as_str() {
+                Some(date) if date < now.0 => Err(ValidationError::custom(
+                    Location::new(),
+                    location.into(),
+                    instance,
+                    format!("{date} is in the past"),
+                )),
+                _ => Ok(()),
+            }
+        }
+        fn is_valid(&self, _: &Value) -> bool {
+            true
+        }
+    }
+
+    #[test_case(&json!({"properties": {"expires": {"x-not-before": true}}}); "properties")]
+    #[test_case(&json!({"properties": {"expires": {"anyOf": [{"x-not-before": true}, {"type": "null"}]}}}); "any of")]
+    #[test_case(&json!({"properties": {"expires": {"not": {"not": {"x-not-before": true}}}}}); "not")]
+    fn custom_keyword_context(schema: &Value) {
+        let validator = crate::options()
+            .with_keyword("x-not-before", |_, _, _| Ok(Box::new(NotBefore)))
+            .build(schema)
+            .expect("Valid schema");
+        let instance = json!({"expires": "2024-06-01"});
+        assert!(validator
+            .validate_with_context(&instance, &Now("2024-01-01"))
+            .is_ok());
+        assert!(validator
+            .validate_with_context(&instance, &Now("2025-01-01"))
+            .is_err());
+        // Other entry points do not pass any context
+        assert!(validator.validate(&instance).is_ok());
+        assert!(validator.is_valid(&instance));
+    }
+
+    #[test]
+    fn custom_keyword_context_type() {
+        let validator = crate::options()
+            .with_keyword("x-not-before", |_, _, _| Ok(Box::new(NotBefore)))
+            .build(&json!({"x-not-before": true}))
+            .expect("Valid schema");
+        let instance = json!("2024-06-01");
+        let error = validator
+            .validate_with_context(&instance, &"2025-01-01")
+            .expect_err("Should fail");
+        assert!(error
+            .to_string()
+            .starts_with("Expected a validation context of type"));
+    }
+
+    #[test]
+    fn custom_keyword_context_stops_at_first_error() {
+        struct Calls(std::sync::atomic::AtomicUsize);
+
+        struct Failing;
+
+        impl Keyword for Failing {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                Ok(())
+            }
+            fn validate_with_context<'i>(
+                &self,
+                instance: &'i Value,
+                location: &LazyLocation,
+                context: &dyn std::any::Any,
+            ) -> Result<(), ValidationError<'i>> {
+                let calls = crate::downcast_context::<Calls>(context, instance, location)?;
+                calls.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                Err(ValidationError::custom(
+                    Location::new(),
+                    location.into(),
+                    instance,
+                    "Failed",
+                ))
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                true
+            }
+        }
+
+        let validator = crate::options()
+            .with_keyword("x-failing", |_, _, _| Ok(Box::new(Failing)))
+            .build(&json!({"items": {"x-failing": true}, "x-failing": true}))
+            .expect("Valid schema");
+        let calls = Calls(std::sync::atomic::AtomicUsize::new(0));
+        let instance = json!([1, 2, 3]);
+        let error = validator
+            .validate_with_context(&instance, &calls)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/0");
+        assert_eq!(calls.0.into_inner(), 1);
+    }
+
+    #[test]
+    fn validate_serialize() {
+        #[derive(serde::Serialize)]
+        struct Item {
+            id: u32,
+            tags: Vec<&'static str>,
+        }
+        let schema = json!({
+            "items": {
+                "properties": {
+                    "id": {"minimum": 1},
+                    "tags": {"items": {"maxLength": 3}}
+                }
+            }
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let valid = vec![Item {
+            id: 1,
+            tags: vec!["a"],
+        }];
+        assert!(validator.validate_serialize(&valid).is_ok());
+        let invalid = vec![
+            Item {
+                id: 1,
+                tags: vec!["a"],
+            },
+            Item {
+                id: 2,
+                tags: vec!["ok", "too long"],
+            },
+        ];
+        let error = validator
+            .validate_serialize(&invalid)
+            .expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/1/tags/1");
+        // Not representable as JSON
+        let map = std::collections::BTreeMap::from([((1, 2), 3)]);
+        let error = validator.validate_serialize(&map).expect_err("Should fail");
+        assert_eq!(error.to_string(), "key must be a string");
+    }
+
+    #[test]
+    fn introspection() {
+        struct Remote;
+
+        impl referencing::Retrieve for Remote {
+            fn retrieve(
+                &self,
+                uri: &referencing::Uri<&str>,
+            ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+                match uri.as_str() {
+                    "https://example.com/integer.json" => Ok(json!({"type": "integer"})),
+                    "https://example.com/positive.json" => Ok(json!({"minimum": 0})),
+                    _ => Err("Not found".into()),
+                }
+            }
+        }
+
+        let schema = json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "count": {
+                    "allOf": [
+                        {"$ref": "integer.json"},
+                        {"$ref": "positive.json#"}
+                    ]
+                },
+                "name": {"$ref": "#/$defs/name"}
+            },
+            "$defs": {"name": {"type": "string", "title": "Name"}}
+        });
+        let validator = crate::options()
+            .with_retriever(Remote)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(
+            validator.external_references().collect::<Vec<_>>(),
+            [
+                "https://example.com/integer.json",
+                "https://example.com/positive.json"
+            ]
+        );
+        assert_eq!(
+            validator
+                .keyword_locations()
+                .map(|(location, keyword)| (location.as_str(), keyword))
+                .collect::<Vec<_>>(),
+            [
+                ("/properties", "properties"),
+                ("/properties/count/allOf", "allOf"),
+                ("/properties/count/allOf/0/$ref", "$ref"),
+                ("/properties/count/allOf/0/$ref/type", "type"),
+                ("/properties/count/allOf/1/$ref", "$ref"),
+                ("/properties/count/allOf/1/$ref/minimum", "minimum"),
+                ("/properties/name/$ref", "$ref"),
+                ("/properties/name/$ref/type", "type"),
+            ]
+        );
+    }
+
+    #[test]
+    fn shared_subschemas_report_their_own_locations() {
+        let point = json!({
+            "properties": {"x": {"type": "integer", "maximum": 10}},
+            "required": ["x"]
+        });
+        let schema = json!({
+            "$id": "https://example.com/shapes.json",
+            "properties": {"start": point, "end": point}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let instance = json!({"start": {"x": 1}, "end": {"x": 11}});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            error.schema_path.as_str(),
+            "/properties/end/properties/x/maximum"
+        );
+        assert_eq!(error.instance_path.as_str(), "/end/x");
+        let errors: Vec<_> = validator
+            .iter_errors(&json!({"start": {}, "end": {"x": "1"}}))
+            .map(|error| error.schema_path.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "/properties/end/properties/x/type",
+                "/properties/start/required"
+            ]
+        );
+        let output = serde_json::to_value(validator.apply(&instance).basic()).expect("Valid JSON");
+        assert_eq!(
+            output["errors"][0]["keywordLocation"],
+            "/properties/end/properties/x/maximum"
+        );
+        assert_eq!(
+            output["errors"][0]["absoluteKeywordLocation"],
+            "https://example.com/shapes.json#/properties/end/properties/x/maximum"
+        );
+        let keywords = validator
+            .keyword_locations()
+            .filter(|(location, _)| location.as_str().starts_with("/properties/end"))
+            .count();
+        assert_eq!(keywords, 4);
+    }
+
+    #[test]
+    fn validate_into_matches_iter_errors() {
+        let schema = json!({
+            "$defs": {
+                "point": {
+                    "properties": {"x": {"type": "integer"}},
+                    "required": ["x"]
+                }
+            },
+            "properties": {
+                "start": {"$ref": "#/$defs/point"},
+                "end": {"$ref": "#/$defs/point"},
+                "tags": {"items": {"type": "string"}},
+                "extra": {"patternProperties": {"^x_": {"type": "boolean"}}}
+            },
+            "if": {"required": ["kind"]},
+            "then": {"properties": {"kind": {"enum": ["a", "b"]}}},
+            "allOf": [{"minProperties": 2}],
+            "unevaluatedProperties": false
+        });
+        let validator = crate::validator_for(&schema).expect("Valid schema");
+        let mut errors = Vec::new();
+        let mut scratch = ValidationScratch::new();
+        let instances = [
+            json!({"start": {"x": 1}, "end": {"x": 2}}),
+            json!({"start": {}, "end": {"x": "2"}, "tags": [1, "a", 2], "other": 1}),
+            json!({"kind": "c", "extra": {"x_a": 1, "x_b": true, "y": 1}}),
+        ];
+        for instance in &instances {
+            let expected: Vec<_> = validator
+                .iter_errors(instance)
+                .map(|error| (error.schema_path.to_string(), error.to_string()))
+                .collect();
+            validator.validate_into_with(instance, &mut errors, &mut scratch);
+            let actual: Vec<_> = errors
+                .iter()
+                .map(|error| (error.schema_path.to_string(), error.to_string()))
+                .collect();
+            assert_eq!(actual, expected);
+        }
+        assert_eq!(errors.len(), 3);
+        assert!(errors
+            .iter()
+            .any(|error| error.schema_path.as_str() == "/then/properties/kind/enum"));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_iter_errors_preserves_order() {
+        let schema = json!({
+            "$ref": "#/$defs/list",
+            "$defs": {
+                "list": {"type": "array", "items": {"type": "integer", "minimum": 0}}
+            }
+        });
+        let validator = crate::validator_for(&schema).expect("Valid schema");
+        let describe = |error: ValidationError| {
+            (
+                error.instance_path.to_string(),
+                error.schema_path.to_string(),
+            )
+        };
+        let invalid = Value::Array(
+            (0..10_000)
+                .map(|idx| match idx {
+                    idx if idx % 7 == 0 => json!(-1),
+                    idx if idx % 11 == 0 => json!("a"),
+                    idx => json!(idx),
+                })
+                .collect(),
+        );
+        let expected: Vec<_> = validator.iter_errors(&invalid).map(describe).collect();
+        let actual: Vec<_> = validator.par_iter_errors(&invalid).map(describe).collect();
+        assert_eq!(actual, expected);
+        assert!(!validator.par_is_valid(&invalid));
+
+        let valid = Value::Array((0..10_000).map(|idx| json!(idx)).collect());
+        assert!(validator.par_is_valid(&valid));
+        assert_eq!(validator.par_iter_errors(&valid).count(), 0);
+    }
+
+    #[test]
+    fn deeply_nested_instance() {
+        fn nested(depth: usize, innermost: Value) -> Value {
+            let mut instance = innermost;
+            for _ in 0..depth {
+                instance = Value::Array(vec![instance]);
+            }
+            instance
+        }
+        // Dropping a deeply nested value recurses as well
+        fn unnest(mut instance: Value) {
+            while let Value::Array(mut items) = instance {
+                instance = items.pop().unwrap_or(Value::Null);
+            }
+        }
+
+        let validator = crate::validator_for(&json!({"type": "array", "items": {"$ref": "#"}}))
+            .expect("Valid schema");
+        let valid = nested(50_000, json!([]));
+        let invalid = nested(50_000, json!(1));
+        assert!(validator.is_valid(&valid));
+        assert!(!validator.is_valid(&invalid));
+
+        let error = validator.validate(&valid).expect_err("Too deep");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::MaxInstanceDepth { limit: 128 }
+        ));
+        assert_eq!(error.instance_path.as_str(), "/0".repeat(129));
+        assert_eq!(
+            error.to_string(),
+            "Instance is nested deeper than 128 levels"
+        );
+        assert_eq!(validator.iter_errors(&valid).count(), 1);
+        assert!(!validator.apply(&valid).basic().is_valid());
+        unnest(valid);
+        unnest(invalid);
+
+        let validator = crate::options()
+            .with_max_instance_depth(2)
+            .build(&json!({"items": {"items": {"minimum": 0}}}))
+            .expect("Valid schema");
+        assert!(validator.validate(&json!([[1]])).is_ok());
+        let instance = json!([[-1]]);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/0/0");
+        assert!(matches!(error.kind, ValidationErrorKind::Minimum { .. }));
+    }
+
+    #[test_case(&json!({"anyOf": [{"type": "string"}, {"properties": {"a": {"$ref": "#"}}}]}); "anyOf")]
+    #[test_case(&json!({"oneOf": [{"type": "string"}, {"type": "object", "properties": {"a": {"$ref": "#"}}}]}); "oneOf")]
+    #[test_case(&json!({"properties": {"a": {"not": {"not": {"$ref": "#"}}}}}); "not")]
+    #[test_case(&json!({"if": {"type": "object"}, "then": {"properties": {"a": {"$ref": "#"}}}, "else": {"type": "string"}}); "if")]
+    fn deeply_nested_instance_in_place_applicators(schema: &Value) {
+        fn nested(depth: usize) -> Value {
+            let mut instance = json!("leaf");
+            for _ in 0..depth {
+                let mut object = serde_json::Map::new();
+                object.insert("a".to_string(), instance);
+                instance = Value::Object(object);
+            }
+            instance
+        }
+        // Dropping a deeply nested value recurses as well
+        fn unnest(mut instance: Value) {
+            while let Value::Object(mut object) = instance {
+                instance = object.remove("a").unwrap_or(Value::Null);
+            }
+        }
+
+        let validator = crate::validator_for(schema).expect("Valid schema");
+        let shallow = nested(100);
+        assert!(validator.is_valid(&shallow));
+        assert!(validator.validate(&shallow).is_ok());
+
+        let deep = nested(50_000);
+        assert!(!validator.is_valid(&deep));
+        let error = validator.validate(&deep).expect_err("Too deep");
+        assert!(
+            matches!(
+                error.kind,
+                ValidationErrorKind::MaxInstanceDepth { limit: 128 }
+            ),
+            "{error}"
+        );
+        assert!(validator
+            .iter_errors(&deep)
+            .any(|error| matches!(error.kind, ValidationErrorKind::MaxInstanceDepth { .. })));
+        unnest(deep);
+    }
+
     #[test]
     fn test_validator_is_send_and_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
         assert_send_sync::<Validator>();
     }
+
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#"}), None, Draft::Draft4; "detected")]
+    #[test_case(&json!({}), Some(Draft::Draft6), Draft::Draft6; "explicit")]
+    #[test_case(&json!({}), None, Draft::Draft202012; "default")]
+    fn detected_draft(schema: &Value, draft: Option<Draft>, expected: Draft) {
+        let mut options = crate::options();
+        if let Some(draft) = draft {
+            options.with_draft(draft);
+        }
+        let validator = options.build(schema).expect("Invalid schema");
+        assert_eq!(validator.draft(), expected);
+        assert_eq!(validator.config().draft(), expected);
+    }
+
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#"}), None, true)]
+    #[test_case(&json!({}), None, false)]
+    #[test_case(&json!({}), Some(true), true)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#"}), Some(false), false)]
+    fn config_validates_formats(schema: &Value, validate: Option<bool>, expected: bool) {
+        let mut options = crate::options();
+        if let Some(validate) = validate {
+            options.should_validate_formats(validate);
+        }
+        let validator = options.build(schema).expect("Invalid schema");
+        assert_eq!(validator.config().validates_formats(), expected);
+    }
+
+    #[test]
+    fn config_options() {
+        let validator = crate::options()
+            .with_verbose_errors(true)
+            .with_content_size_limit(10)
+            .build(&json!({}))
+            .expect("Invalid schema");
+        let config = validator.config();
+        assert!(config.has_verbose_errors());
+        assert!(!config.has_lazy_references());
+        assert_eq!(config.content_size_limit(), Some(10));
+        // The options can be reused for another validator
+        let other = (**config.options())
+            .clone()
+            .build(&json!({}))
+            .expect("Invalid schema");
+        assert!(other.config().has_verbose_errors());
+    }
+
+    #[test]
+    fn schema() {
+        let schema = json!({"type": "string"});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(**validator.schema(), schema);
+    }
+
+    #[test_case(&json!({"type": "string", "minLength": 1}))]
+    #[test_case(&json!({"minLength": 1, "type": "string"}))]
+    fn schema_hash_is_stable(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        // FNV-1a of `{"minLength":1,"type":"string"}`
+        assert_eq!(validator.schema_hash(), 0x63f5_0329_2d4b_e7c5);
+    }
+
+    #[test_case(&json!({"type": "string"}), &json!({"type": "integer"}))]
+    #[test_case(&json!({"minimum": 1}), &json!({"minimum": 1.0}))]
+    #[test_case(&json!({"enum": ["a", "b"]}), &json!({"enum": ["b", "a"]}))]
+    #[test_case(&json!({"enum": ["a,b"]}), &json!({"enum": ["a", "b"]}))]
+    fn schema_hash_differs(left: &Value, right: &Value) {
+        let left = crate::validator_for(left).expect("Invalid schema");
+        let right = crate::validator_for(right).expect("Invalid schema");
+        assert_ne!(left.schema_hash(), right.schema_hash());
+    }
+
+    fn documents() -> Vec<(&'static str, Value)> {
+        vec![
+            (
+                "https://example.com/person",
+                json!({
+                    "properties": {
+                        "name": {"$ref": "name"},
+                        "friends": {"items": {"$ref": "#"}}
+                    }
+                }),
+            ),
+            (
+                "https://example.com/schemas/name",
+                json!({"$id": "https://example.com/name", "type": "string", "minLength": 1}),
+            ),
+        ]
+    }
+
+    #[test_case("https://example.com/person", &json!({"name": "Ann", "friends": [{"name": "Bob"}]}), &json!({"friends": [{"name": ""}]}))]
+    #[test_case("https://example.com/person#", &json!({"name": "Ann"}), &json!({"name": 42}))]
+    #[test_case("https://example.com/name", &json!("Ann"), &json!(""))]
+    #[test_case("https://example.com/schemas/name", &json!("Ann"), &json!(42))]
+    fn compile_all(uri: &str, valid: &Value, invalid: &Value) {
+        let registry = crate::options()
+            .compile_all(documents())
+            .expect("Invalid schemas");
+        let validator = registry.validator_for(uri).expect("Unknown document");
+        assert!(validator.is_valid(valid));
+        assert!(!validator.is_valid(invalid));
+    }
+
+    #[test]
+    fn compile_all_documents() {
+        let registry = crate::options()
+            .compile_all(documents())
+            .expect("Invalid schemas");
+        assert!(registry
+            .validator_for("https://example.com/unknown")
+            .is_none());
+        assert_eq!(
+            registry
+                .validators()
+                .map(|validator| validator.schema().as_ref())
+                .collect::<Vec<_>>(),
+            documents()
+                .iter()
+                .map(|(_, schema)| schema)
+                .collect::<Vec<_>>()
+        );
+        let person = registry
+            .validator_for("https://example.com/person")
+            .expect("Unknown document");
+        assert_eq!(
+            person.external_references().collect::<Vec<_>>(),
+            ["https://example.com/name"]
+        );
+        assert!(person
+            .keyword_locations()
+            .any(|(location, _)| location.as_str() == "/properties/name/$ref/minLength"));
+        let name = registry
+            .validator_for("https://example.com/name")
+            .expect("Unknown document");
+        assert_eq!(name.external_references().count(), 0);
+        assert_eq!(
+            name.keyword_locations()
+                .map(|(location, _)| location.as_str())
+                .collect::<Vec<_>>(),
+            ["/minLength", "/type"]
+        );
+    }
+
+    #[test_case(
+        &[
+            ("https://example.com/a", json!({"$id": "https://example.com/shared"})),
+            ("https://example.com/b", json!({"$id": "https://example.com/shared"})),
+        ],
+        "Identifier 'https://example.com/shared' is defined by both 'https://example.com/a' and 'https://example.com/b'";
+        "root identifiers"
+    )]
+    #[test_case(
+        &[
+            ("https://example.com/a", json!({"$defs": {"b": {"$id": "b"}}})),
+            ("https://example.com/b", json!({})),
+        ],
+        "Identifier 'https://example.com/b' is defined by both 'https://example.com/b' and 'https://example.com/a'";
+        "embedded identifier"
+    )]
+    fn compile_all_duplicate_ids(documents: &[(&str, Value)], expected: &str) {
+        let error = crate::options()
+            .compile_all(documents.iter().cloned())
+            .expect_err("Duplicate identifiers should be rejected");
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn budget_max_nodes() {
+        let validator = crate::validator_for(&json!({
+            "properties": {"tags": {"items": {"type": "string"}}}
+        }))
+        .expect("Invalid schema");
+        let budget = Budget {
+            max_nodes: 4,
+            ..Budget::default()
+        };
+        let instance = json!({"tags": ["a", "b", "c"]});
+        let error = validator
+            .validate_with_budget(&instance, budget)
+            .expect_err("Budget is exceeded");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::BudgetExceeded { visited: 4, .. }
+        ));
+        assert_eq!(error.instance_path.as_str(), "/tags/2");
+        assert_eq!(error.schema_path.as_str(), "/properties/tags/items");
+        assert_eq!(*error.instance, json!("c"));
+        assert!(error
+            .to_string()
+            .starts_with("Validation budget exceeded after 4 nodes and "));
+        assert!(validator
+            .validate_with_budget(&json!({"tags": ["a", "b"]}), budget)
+            .is_ok());
+    }
+
+    #[test]
+    fn budget_max_duration() {
+        let validator = crate::validator_for(&json!({"type": "integer"})).expect("Invalid schema");
+        let budget = Budget {
+            max_duration: Some(Duration::ZERO),
+            ..Budget::default()
+        };
+        let instance = json!(1);
+        let error = validator
+            .validate_with_budget(&instance, budget)
+            .expect_err("Deadline has passed");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::BudgetExceeded { visited: 0, .. }
+        ));
+        assert_eq!(error.instance_path.as_str(), "");
+    }
+
+    #[test]
+    fn budget_stops_in_applicators() {
+        let validator = crate::validator_for(&json!({
+            "anyOf": [{"items": {"type": "string"}}, {"type": "object"}]
+        }))
+        .expect("Invalid schema");
+        let budget = Budget {
+            max_nodes: 3,
+            ..Budget::default()
+        };
+        // Without a budget, the first failing subschema would be hidden by `anyOf`
+        let instance = json!(["a", "b", "c"]);
+        let error = validator
+            .validate_with_budget(&instance, budget)
+            .expect_err("Budget is exceeded");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::BudgetExceeded { visited: 3, .. }
+        ));
+        assert_eq!(error.schema_path.as_str(), "/anyOf/0/items");
+    }
+
+    #[test_case(&json!({"type": "string"}), &json!("a"); "valid")]
+    #[test_case(&json!({"properties": {"a": {"maxLength": 1}}}), &json!({"a": "b"}); "valid nested")]
+    #[test_case(&json!(true), &json!(1); "true schema")]
+    #[test_case(&json!({"items": {"minimum": 2}}), &json!([3, 4]); "valid items")]
+    fn validate_first_valid(schema: &Value, instance: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        assert!(validator.validate_first(instance).is_none());
+    }
+
+    #[test_case(&json!({"type": "string"}), &json!(1), "", "/type"; "single keyword")]
+    #[test_case(&json!({"properties": {"a": {"properties": {"b": {"type": "string"}}}}}), &json!({"a": {"b": 1}}), "/a/b", "/properties/a/properties/b/type"; "nested")]
+    #[test_case(&json!({"items": {"minimum": 2}}), &json!([3, 1]), "/1", "/items/minimum"; "items")]
+    #[test_case(&json!({"properties": {"a": {"type": "string"}}, "required": ["b"]}), &json!({"a": 1}), "", "/required"; "cheapest keyword first")]
+    #[test_case(&json!({"$ref": "#/$defs/a", "$defs": {"a": {"minimum": 2}}}), &json!(1), "", "/$ref/minimum"; "reference")]
+    #[test_case(&json!(false), &json!(1), "", ""; "false schema")]
+    fn validate_first_invalid(
+        schema: &Value,
+        instance: &Value,
+        instance_path: &str,
+        schema_path: &str,
+    ) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let error = validator
+            .validate_first(instance)
+            .expect("Invalid instance");
+        assert_eq!(error.instance_path.as_str(), instance_path);
+        assert_eq!(error.schema_path.as_str(), schema_path);
+    }
+
+    #[test]
+    fn validate_first_matches_validate() {
+        let validator = crate::options()
+            .with_keyword_order(crate::KeywordOrder::Preserve)
+            .build(&json!({"properties": {"a": {"type": "string"}}, "required": ["b"]}))
+            .expect("Invalid schema");
+        let instance = json!({"a": 1});
+        let error = validator
+            .validate_first(&instance)
+            .expect("Invalid instance");
+        let expected = validator.validate(&instance).expect_err("Invalid instance");
+        assert_eq!(error.schema_path, expected.schema_path);
+        assert_eq!(error.instance_path, expected.instance_path);
+    }
+
+    #[test_case(&json!({"anyOf": [{"type": "string"}, {"minimum": 5}]}), &json!(3))]
+    #[test_case(&json!({"anyOf": [{"type": "string"}, {"minimum": 5}]}), &json!(7))]
+    #[test_case(&json!({"oneOf": [{"type": "integer"}, {"minimum": 5}]}), &json!(7))]
+    #[test_case(&json!({"oneOf": [{"type": "integer"}, {"minimum": 5}]}), &json!(3))]
+    #[test_case(&json!({"not": {"type": "string"}}), &json!("a"))]
+    #[test_case(&json!({"if": {"type": "string"}, "then": {"minLength": 2}}), &json!("a"))]
+    #[test_case(&json!({"if": {"type": "string"}, "else": {"minimum": 2}}), &json!(1))]
+    #[test_case(&json!({"contains": {"type": "string"}}), &json!([1, 2]))]
+    #[test_case(&json!({"contains": {"type": "string"}, "minContains": 2}), &json!([1, "a"]))]
+    #[test_case(&json!({"contains": {"type": "string"}, "maxContains": 1}), &json!(["a", "b"]))]
+    #[test_case(&json!({"contains": {"type": "string"}, "minContains": 2, "maxContains": 3}), &json!(["a"]))]
+    #[test_case(&json!({"contains": {"type": "string"}, "minContains": 1, "maxContains": 1}), &json!(["a", "b"]))]
+    #[test_case(&json!({"dependentSchemas": {"a": {"required": ["b"]}}}), &json!({"a": 1}))]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "dependencies": {"a": {"required": ["b"]}}}), &json!({"a": 1}))]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "items": [{}], "additionalItems": {"type": "string"}}), &json!([1, 2]))]
+    fn budget_unlimited(schema: &Value, instance: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let expected = validator.validate(instance).err().map(|error| {
+            (
+                error.to_string(),
+                error.instance_path.to_string(),
+                error.schema_path.to_string(),
+            )
+        });
+        let actual = validator
+            .validate_with_budget(instance, Budget::default())
+            .err()
+            .map(|error| {
+                (
+                    error.to_string(),
+                    error.instance_path.to_string(),
+                    error.schema_path.to_string(),
+                )
+            });
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "tracing")]
+    struct Traced;
+
+    #[cfg(feature = "tracing")]
+    impl referencing::Retrieve for Traced {
+        fn retrieve(
+            &self,
+            _: &referencing::Uri<&str>,
+        ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+            tracing::info!("Retrieving");
+            Ok(json!({"type": "string"}))
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tracing_test::traced_test]
+    #[test]
+    fn tracing_build() {
+        crate::options()
+            .with_retriever(Traced)
+            .build(&json!({"properties": {"name": {"$ref": "https://example.com/name"}}}))
+            .expect("Invalid schema");
+        assert!(logs_contain(
+            "build{draft=Draft202012}:retrieve{uri=https://example.com/name}: jsonschema::validator::tests: Retrieving"
+        ));
+        assert!(logs_contain(
+            "build{draft=Draft202012 nodes=3 external_references=1}: jsonschema::compiler: Compiled schema validators=1"
+        ));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tracing_test::traced_test]
+    #[test]
+    fn tracing_validation() {
+        let validator = crate::options()
+            .with_format("traced", |_: &str| {
+                tracing::info!("Checking format");
+                true
+            })
+            .should_validate_formats(true)
+            .build(&json!({"properties": {"age": {"minimum": 0}, "name": {"format": "traced"}}}))
+            .expect("Invalid schema");
+        let instance = json!({"age": -1, "name": "Jane"});
+        assert!(validator.is_valid(&json!({"name": "Jane"})));
+        assert!(validator.validate(&instance).is_err());
+        assert_eq!(validator.iter_errors(&instance).count(), 1);
+        let mut errors = Vec::new();
+        validator.validate_into(&instance, &mut errors);
+        assert!(logs_contain(
+            "is_valid: jsonschema::validator::tests: Checking format"
+        ));
+        for span in ["validate", "iter_errors", "validate_into"] {
+            assert!(logs_contain(&format!(
+                "{span}: jsonschema::validator: -1 is less than the minimum of 0 instance_path=/age schema_path=/properties/age/minimum"
+            )));
+        }
+    }
 }
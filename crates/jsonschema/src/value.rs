@@ -0,0 +1,516 @@
+//! Abstraction over JSON-like instances.
+//!
+//! Validation is not tied to [`serde_json::Value`]: any type implementing [`Value`] can be passed
+//! to [`Validator::is_valid`](crate::Validator::is_valid), e.g. values produced by alternative
+//! JSON parsers. The trait is object-safe, so nested values are exposed as `&dyn Value`.
+//!
+//! ```rust
+//! use jsonschema::{primitive_type::PrimitiveType, value::Value};
+//! use serde_json::json;
+//!
+//! /// A tiny JSON-like value that only knows about numbers and lists.
+//! enum Tiny {
+//!     Number(i64),
+//!     List(Vec<Tiny>),
+//! }
+//!
+//! impl Value for Tiny {
+//!     fn primitive_type(&self) -> PrimitiveType {
+//!         match self {
+//!             Tiny::Number(_) => PrimitiveType::Number,
+//!             Tiny::List(_) => PrimitiveType::Array,
+//!         }
+//!     }
+//!     fn as_i64(&self) -> Option<i64> {
+//!         match self {
+//!             Tiny::Number(n) => Some(*n),
+//!             Tiny::List(_) => None,
+//!         }
+//!     }
+//!     fn as_u64(&self) -> Option<u64> {
+//!         self.as_i64().and_then(|n| u64::try_from(n).ok())
+//!     }
+//!     #[allow(clippy::cast_precision_loss)]
+//!     fn as_f64(&self) -> Option<f64> {
+//!         self.as_i64().map(|n| n as f64)
+//!     }
+//!     fn array_len(&self) -> Option<usize> {
+//!         match self {
+//!             Tiny::List(items) => Some(items.len()),
+//!             Tiny::Number(_) => None,
+//!         }
+//!     }
+//!     fn array_get(&self, idx: usize) -> Option<&dyn Value> {
+//!         match self {
+//!             Tiny::List(items) => items.get(idx).map(|item| item as &dyn Value),
+//!             Tiny::Number(_) => None,
+//!         }
+//!     }
+//! }
+//!
+//! let validator = jsonschema::validator_for(&json!({
+//!     "type": "array",
+//!     "items": {"minimum": 0}
+//! }))
+//! .expect("Invalid schema");
+//!
+//! assert!(validator.is_valid(&Tiny::List(vec![Tiny::Number(1), Tiny::Number(2)])));
+//! assert!(!validator.is_valid(&Tiny::List(vec![Tiny::Number(-1)])));
+//! ```
+use crate::primitive_type::PrimitiveType;
+use num_cmp::NumCmp;
+use serde_json::Map;
+
+/// Read-only view of a JSON value used during validation.
+///
+/// Only [`Value::primitive_type`] is required, all other methods return `None` or an empty
+/// iterator by default, so implementations only need to provide the accessors relevant to the
+/// kinds of values they can represent.
+///
+/// Numbers follow the `serde_json` conventions: [`Value::as_u64`] and [`Value::as_i64`] return
+/// `Some` only if the number is an integer that fits in the respective type, while
+/// [`Value::as_f64`] should return `Some` for every number.
+pub trait Value {
+    /// JSON type of this value.
+    ///
+    /// Numbers should be reported as [`PrimitiveType::Number`], integer detection is done
+    /// via the numeric accessors.
+    fn primitive_type(&self) -> PrimitiveType;
+    /// Boolean value, if this value is a boolean.
+    fn as_bool(&self) -> Option<bool> {
+        None
+    }
+    /// String slice, if this value is a string.
+    fn as_str(&self) -> Option<&str> {
+        None
+    }
+    /// Number as `u64`, if it is a non-negative integer representable as `u64`.
+    fn as_u64(&self) -> Option<u64> {
+        None
+    }
+    /// Number as `i64`, if it is an integer representable as `i64`.
+    fn as_i64(&self) -> Option<i64> {
+        None
+    }
+    /// Number as `f64`, if this value is a number.
+    fn as_f64(&self) -> Option<f64> {
+        None
+    }
+    /// Number of items, if this value is an array.
+    fn array_len(&self) -> Option<usize> {
+        None
+    }
+    /// Array item at the given index.
+    fn array_get(&self, _idx: usize) -> Option<&dyn Value> {
+        None
+    }
+    /// Number of properties, if this value is an object.
+    fn object_len(&self) -> Option<usize> {
+        None
+    }
+    /// Object property with the given name.
+    fn object_get(&self, _key: &str) -> Option<&dyn Value> {
+        None
+    }
+    /// Iterator over object properties. Empty if this value is not an object.
+    fn object_iter(&self) -> Box<dyn Iterator<Item = (&str, &dyn Value)> + '_> {
+        Box::new(std::iter::empty())
+    }
+    /// Convert this value into [`serde_json::Value`].
+    ///
+    /// Used by keywords that are only implemented for `serde_json`, e.g. custom keywords.
+    fn to_json(&self) -> serde_json::Value {
+        match self.primitive_type() {
+            PrimitiveType::Null => serde_json::Value::Null,
+            PrimitiveType::Boolean => {
+                serde_json::Value::Bool(self.as_bool().expect("Boolean value"))
+            }
+            PrimitiveType::Number | PrimitiveType::Integer => {
+                if let Some(number) = self.as_u64() {
+                    number.into()
+                } else if let Some(number) = self.as_i64() {
+                    number.into()
+                } else {
+                    serde_json::Number::from_f64(self.as_f64().expect("Number value"))
+                        .map_or(serde_json::Value::Null, serde_json::Value::Number)
+                }
+            }
+            PrimitiveType::String => {
+                serde_json::Value::String(self.as_str().expect("String value").to_string())
+            }
+            PrimitiveType::Array => serde_json::Value::Array(
+                (0..self.array_len().unwrap_or(0))
+                    .filter_map(|idx| self.array_get(idx))
+                    .map(Value::to_json)
+                    .collect(),
+            ),
+            PrimitiveType::Object => serde_json::Value::Object(
+                self.object_iter()
+                    .map(|(key, value)| (key.to_string(), value.to_json()))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+    #[doc(hidden)]
+    /// Allows validators to use the `serde_json` fast path when possible.
+    fn as_serde_json(&self) -> Option<&serde_json::Value> {
+        None
+    }
+}
+
+impl<T: Value + ?Sized> Value for &T {
+    fn primitive_type(&self) -> PrimitiveType {
+        (**self).primitive_type()
+    }
+    fn as_bool(&self) -> Option<bool> {
+        (**self).as_bool()
+    }
+    fn as_str(&self) -> Option<&str> {
+        (**self).as_str()
+    }
+    fn as_u64(&self) -> Option<u64> {
+        (**self).as_u64()
+    }
+    fn as_i64(&self) -> Option<i64> {
+        (**self).as_i64()
+    }
+    fn as_f64(&self) -> Option<f64> {
+        (**self).as_f64()
+    }
+    fn array_len(&self) -> Option<usize> {
+        (**self).array_len()
+    }
+    fn array_get(&self, idx: usize) -> Option<&dyn Value> {
+        (**self).array_get(idx)
+    }
+    fn object_len(&self) -> Option<usize> {
+        (**self).object_len()
+    }
+    fn object_get(&self, key: &str) -> Option<&dyn Value> {
+        (**self).object_get(key)
+    }
+    fn object_iter(&self) -> Box<dyn Iterator<Item = (&str, &dyn Value)> + '_> {
+        (**self).object_iter()
+    }
+    fn to_json(&self) -> serde_json::Value {
+        (**self).to_json()
+    }
+    fn as_serde_json(&self) -> Option<&serde_json::Value> {
+        (**self).as_serde_json()
+    }
+}
+
+impl Value for serde_json::Value {
+    #[inline]
+    fn primitive_type(&self) -> PrimitiveType {
+        PrimitiveType::from(self)
+    }
+    #[inline]
+    fn as_bool(&self) -> Option<bool> {
+        serde_json::Value::as_bool(self)
+    }
+    #[inline]
+    fn as_str(&self) -> Option<&str> {
+        serde_json::Value::as_str(self)
+    }
+    #[inline]
+    fn as_u64(&self) -> Option<u64> {
+        serde_json::Value::as_u64(self)
+    }
+    #[inline]
+    fn as_i64(&self) -> Option<i64> {
+        serde_json::Value::as_i64(self)
+    }
+    #[inline]
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            serde_json::Value::Number(number) => Some(crate::keywords::helpers::to_f64(number)),
+            _ => None,
+        }
+    }
+    #[inline]
+    fn array_len(&self) -> Option<usize> {
+        self.as_array().map(Vec::len)
+    }
+    #[inline]
+    fn array_get(&self, idx: usize) -> Option<&dyn Value> {
+        self.as_array()
+            .and_then(|items| items.get(idx))
+            .map(|item| item as &dyn Value)
+    }
+    #[inline]
+    fn object_len(&self) -> Option<usize> {
+        self.as_object().map(Map::len)
+    }
+    #[inline]
+    fn object_get(&self, key: &str) -> Option<&dyn Value> {
+        self.as_object()
+            .and_then(|object| object.get(key))
+            .map(|value| value as &dyn Value)
+    }
+    fn object_iter(&self) -> Box<dyn Iterator<Item = (&str, &dyn Value)> + '_> {
+        if let serde_json::Value::Object(object) = self {
+            Box::new(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value as &dyn Value)),
+            )
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+    fn to_json(&self) -> serde_json::Value {
+        self.clone()
+    }
+    #[inline]
+    fn as_serde_json(&self) -> Option<&serde_json::Value> {
+        Some(self)
+    }
+}
+
+/// Iterate over array items. Empty if the value is not an array.
+pub(crate) fn array_iter(value: &dyn Value) -> impl Iterator<Item = &dyn Value> {
+    (0..value.array_len().unwrap_or(0)).filter_map(move |idx| value.array_get(idx))
+}
+
+/// Whether the value is an integer, i.e. a number without a fractional part.
+pub(crate) fn is_integer(value: &dyn Value) -> bool {
+    value.as_u64().is_some()
+        || value.as_i64().is_some()
+        || value.as_f64().is_some_and(|number| number.fract() == 0.)
+}
+
+macro_rules! num_cmp {
+    ($value:expr, $method:ident, $limit:expr) => {
+        if let Some(item) = $value.as_u64() {
+            Some(NumCmp::$method(item, $limit))
+        } else if let Some(item) = $value.as_i64() {
+            Some(NumCmp::$method(item, $limit))
+        } else {
+            $value.as_f64().map(|item| NumCmp::$method(item, $limit))
+        }
+    };
+}
+
+/// Whether a number is less than the limit. `None` if the value is not a number.
+pub(crate) fn num_lt<L>(value: &dyn Value, limit: L) -> Option<bool>
+where
+    u64: NumCmp<L>,
+    i64: NumCmp<L>,
+    f64: NumCmp<L>,
+    L: Copy,
+{
+    num_cmp!(value, num_lt, limit)
+}
+
+/// Whether a number is greater than the limit. `None` if the value is not a number.
+pub(crate) fn num_gt<L>(value: &dyn Value, limit: L) -> Option<bool>
+where
+    u64: NumCmp<L>,
+    i64: NumCmp<L>,
+    f64: NumCmp<L>,
+    L: Copy,
+{
+    num_cmp!(value, num_gt, limit)
+}
+
+/// JSON Schema equality between a foreign value and a `serde_json` one.
+pub(crate) fn equal(left: &dyn Value, right: &serde_json::Value) -> bool {
+    equal_values(left, right)
+}
+
+/// JSON Schema equality between two values. Numbers are compared by their mathematical value.
+pub(crate) fn equal_values(left: &dyn Value, right: &dyn Value) -> bool {
+    match (left.primitive_type(), right.primitive_type()) {
+        (PrimitiveType::Null, PrimitiveType::Null) => true,
+        (PrimitiveType::Boolean, PrimitiveType::Boolean) => left.as_bool() == right.as_bool(),
+        (PrimitiveType::String, PrimitiveType::String) => left.as_str() == right.as_str(),
+        (
+            PrimitiveType::Number | PrimitiveType::Integer,
+            PrimitiveType::Number | PrimitiveType::Integer,
+        ) => {
+            if let Some(a) = left.as_u64() {
+                num_eq(a, right)
+            } else if let Some(a) = left.as_i64() {
+                num_eq(a, right)
+            } else {
+                left.as_f64().is_some_and(|a| num_eq(a, right))
+            }
+        }
+        (PrimitiveType::Array, PrimitiveType::Array) => {
+            left.array_len() == right.array_len()
+                && array_iter(left)
+                    .zip(array_iter(right))
+                    .all(|(a, b)| equal_values(a, b))
+        }
+        (PrimitiveType::Object, PrimitiveType::Object) => {
+            left.object_len() == right.object_len()
+                && left
+                    .object_iter()
+                    .all(|(key, a)| right.object_get(key).is_some_and(|b| equal_values(a, b)))
+        }
+        (_, _) => false,
+    }
+}
+
+fn num_eq<L>(left: L, right: &dyn Value) -> bool
+where
+    L: NumCmp<u64> + NumCmp<i64> + NumCmp<f64> + Copy,
+{
+    if let Some(b) = right.as_u64() {
+        NumCmp::num_eq(left, b)
+    } else if let Some(b) = right.as_i64() {
+        NumCmp::num_eq(left, b)
+    } else {
+        right.as_f64().is_some_and(|b| NumCmp::num_eq(left, b))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{equal, Value};
+    use crate::primitive_type::PrimitiveType;
+    use serde_json::json;
+    use test_case::test_case;
+
+    /// Minimal JSON representation that does not share any code with `serde_json`.
+    pub(crate) enum Foreign {
+        Null,
+        Bool(bool),
+        Int(i64),
+        Float(f64),
+        Str(String),
+        List(Vec<Foreign>),
+        Map(Vec<(String, Foreign)>),
+    }
+
+    impl From<&serde_json::Value> for Foreign {
+        fn from(value: &serde_json::Value) -> Self {
+            match value {
+                serde_json::Value::Null => Foreign::Null,
+                serde_json::Value::Bool(b) => Foreign::Bool(*b),
+                serde_json::Value::Number(n) => {
+                    if let Some(n) = n.as_i64() {
+                        Foreign::Int(n)
+                    } else {
+                        Foreign::Float(n.as_f64().expect("Always valid"))
+                    }
+                }
+                serde_json::Value::String(s) => Foreign::Str(s.clone()),
+                serde_json::Value::Array(items) => {
+                    Foreign::List(items.iter().map(Foreign::from).collect())
+                }
+                serde_json::Value::Object(map) => Foreign::Map(
+                    map.iter()
+                        .map(|(k, v)| (k.clone(), Foreign::from(v)))
+                        .collect(),
+                ),
+            }
+        }
+    }
+
+    impl Value for Foreign {
+        fn primitive_type(&self) -> PrimitiveType {
+            match self {
+                Foreign::Null => PrimitiveType::Null,
+                Foreign::Bool(_) => PrimitiveType::Boolean,
+                Foreign::Int(_) | Foreign::Float(_) => PrimitiveType::Number,
+                Foreign::Str(_) => PrimitiveType::String,
+                Foreign::List(_) => PrimitiveType::Array,
+                Foreign::Map(_) => PrimitiveType::Object,
+            }
+        }
+        fn as_bool(&self) -> Option<bool> {
+            match self {
+                Foreign::Bool(b) => Some(*b),
+                _ => None,
+            }
+        }
+        fn as_str(&self) -> Option<&str> {
+            match self {
+                Foreign::Str(s) => Some(s),
+                _ => None,
+            }
+        }
+        fn as_u64(&self) -> Option<u64> {
+            match self {
+                Foreign::Int(n) => u64::try_from(*n).ok(),
+                _ => None,
+            }
+        }
+        fn as_i64(&self) -> Option<i64> {
+            match self {
+                Foreign::Int(n) => Some(*n),
+                _ => None,
+            }
+        }
+        #[allow(clippy::cast_precision_loss)]
+        fn as_f64(&self) -> Option<f64> {
+            match self {
+                Foreign::Int(n) => Some(*n as f64),
+                Foreign::Float(n) => Some(*n),
+                _ => None,
+            }
+        }
+        fn array_len(&self) -> Option<usize> {
+            match self {
+                Foreign::List(items) => Some(items.len()),
+                _ => None,
+            }
+        }
+        fn array_get(&self, idx: usize) -> Option<&dyn Value> {
+            match self {
+                Foreign::List(items) => items.get(idx).map(|item| item as &dyn Value),
+                _ => None,
+            }
+        }
+        fn object_len(&self) -> Option<usize> {
+            match self {
+                Foreign::Map(map) => Some(map.len()),
+                _ => None,
+            }
+        }
+        fn object_get(&self, key: &str) -> Option<&dyn Value> {
+            match self {
+                Foreign::Map(map) => map
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v as &dyn Value),
+                _ => None,
+            }
+        }
+        fn object_iter(&self) -> Box<dyn Iterator<Item = (&str, &dyn Value)> + '_> {
+            match self {
+                Foreign::Map(map) => {
+                    Box::new(map.iter().map(|(k, v)| (k.as_str(), v as &dyn Value)))
+                }
+                _ => Box::new(std::iter::empty()),
+            }
+        }
+    }
+
+    #[test_case(&json!(null))]
+    #[test_case(&json!(true))]
+    #[test_case(&json!(-5))]
+    #[test_case(&json!(1.5))]
+    #[test_case(&json!("foo"))]
+    #[test_case(&json!([1, [2, {"a": null}]]))]
+    #[test_case(&json!({"a": {"b": [1, "c"]}}))]
+    fn to_json_roundtrip(value: &serde_json::Value) {
+        assert_eq!(&Foreign::from(value).to_json(), value);
+    }
+
+    #[test_case(&json!(1), &json!(1.0))]
+    #[test_case(&json!([1, "a"]), &json!([1.0, "a"]))]
+    #[test_case(&json!({"a": 1, "b": 2}), &json!({"b": 2.0, "a": 1}))]
+    fn are_equal(left: &serde_json::Value, right: &serde_json::Value) {
+        assert!(equal(&Foreign::from(left), right));
+    }
+
+    #[test_case(&json!(1), &json!("1"))]
+    #[test_case(&json!([1]), &json!([1, 2]))]
+    #[test_case(&json!({"a": 1}), &json!({"b": 1}))]
+    fn are_not_equal(left: &serde_json::Value, right: &serde_json::Value) {
+        assert!(!equal(&Foreign::from(left), right));
+    }
+}
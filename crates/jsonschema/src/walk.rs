@@ -0,0 +1,386 @@
+//! Traversal over all subschemas of a schema.
+use ahash::AHashSet;
+use referencing::{Draft, Registry, Resolver, Uri};
+use serde_json::Value;
+
+use crate::{compiler::DEFAULT_ROOT_URL, paths::Location};
+
+/// Iterate over `schema` and all of its subschemas together with their locations, depth-first
+/// and in document order.
+///
+/// Only values in schema positions are yielded, so the contents of `enum`, `const` or
+/// `examples`, and the property names under `properties`, are skipped. What counts as a
+/// subschema is decided by the same per-draft rules that the registry used during compilation
+/// applies when it discovers embedded resources, and `$schema` in a subschema switches the draft
+/// for everything below it.
+///
+/// ```rust
+/// use serde_json::json;
+/// use jsonschema::Draft;
+///
+/// let schema = json!({
+///     "properties": {"tags": {"items": {"type": "string"}}},
+///     "enum": [{"type": "string"}]
+/// });
+/// let locations: Vec<String> = jsonschema::walk(&schema, Draft::Draft202012)
+///     .map(|(location, _)| location.to_string())
+///     .collect();
+/// assert_eq!(locations, ["", "/properties/tags", "/properties/tags/items"]);
+/// ```
+#[must_use]
+pub fn walk(schema: &Value, draft: Draft) -> Walk<'_> {
+    let draft = Draft::from_schema(schema).unwrap_or(draft);
+    Walk {
+        root: schema,
+        draft,
+        stack: vec![Frame {
+            location: Location::new(),
+            schema,
+            draft,
+            resolver: None,
+        }],
+        references: None,
+    }
+}
+
+/// Iterator over subschemas returned by [`walk`].
+#[derive(Debug)]
+pub struct Walk<'a> {
+    root: &'a Value,
+    draft: Draft,
+    stack: Vec<Frame<'a>>,
+    references: Option<References>,
+}
+
+#[derive(Debug)]
+struct Frame<'a> {
+    location: Location,
+    schema: &'a Value,
+    draft: Draft,
+    /// Present only when references are followed.
+    resolver: Option<Resolver<'a>>,
+}
+
+/// State for following `$ref` out of the walked document.
+#[derive(Debug)]
+struct References {
+    /// Documents embedded in the walked schema, which are walked anyway.
+    local: AHashSet<Uri<String>>,
+    /// Reference targets that were already walked, so cycles end.
+    entered: AHashSet<Uri<String>>,
+}
+
+impl<'a> Walk<'a> {
+    /// Also descend into the targets of `$ref` that point outside of the walked schema, looking
+    /// them up in `registry`.
+    ///
+    /// Subschemas of a target are located through the reference, e.g.
+    /// `/properties/name/$ref/minLength`, like keyword locations in validation errors. Each
+    /// target is walked once, and references that cannot be resolved are skipped. The walk
+    /// restarts from the root.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use jsonschema::{Draft, Registry, Resource};
+    ///
+    /// let registry = Registry::try_new(
+    ///     "https://example.com/name",
+    ///     Resource::from_contents(json!({"items": {"type": "string"}})).expect("Valid resource"),
+    /// )
+    /// .expect("Valid registry");
+    /// let schema = json!({"properties": {"name": {"$ref": "https://example.com/name"}}});
+    /// let locations: Vec<String> = jsonschema::walk(&schema, Draft::Draft202012)
+    ///     .follow_references(&registry)
+    ///     .map(|(location, _)| location.to_string())
+    ///     .collect();
+    /// assert_eq!(
+    ///     locations,
+    ///     ["", "/properties/name", "/properties/name/$ref", "/properties/name/$ref/items"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn follow_references(mut self, registry: &'a Registry) -> Self {
+        let resolver = registry
+            .try_resolver(DEFAULT_ROOT_URL)
+            .and_then(|resolver| resolver.in_subresource(self.draft.create_resource_ref(self.root)))
+            .ok();
+        let mut local = AHashSet::new();
+        if let Some(resolver) = &resolver {
+            collect_documents(self.root, self.draft, resolver, &mut local);
+        }
+        self.stack = vec![Frame {
+            location: Location::new(),
+            schema: self.root,
+            draft: self.draft,
+            resolver,
+        }];
+        self.references = Some(References {
+            local,
+            entered: AHashSet::new(),
+        });
+        self
+    }
+
+    fn reference_target(&mut self, frame: &Frame<'a>) -> Option<Frame<'a>> {
+        let resolver = frame.resolver.as_ref()?;
+        let references = self.references.as_mut()?;
+        let reference = frame.schema.get("$ref").and_then(Value::as_str)?;
+        let uri = resolver
+            .resolve_against(&resolver.base_uri().borrow(), reference)
+            .ok()?;
+        let document = document_of(&uri);
+        if references.local.contains(&document) {
+            return None;
+        }
+        // `#` and no fragment at all point to the same schema
+        let target = if uri
+            .fragment()
+            .is_some_and(|fragment| fragment.as_str().is_empty())
+        {
+            document
+        } else {
+            (*uri).clone()
+        };
+        if !references.entered.insert(target) {
+            return None;
+        }
+        let resolved = resolver.lookup(reference).ok()?;
+        let (schema, resolver, draft) = resolved.into_inner();
+        Some(Frame {
+            location: frame.location.join("$ref"),
+            schema,
+            draft: Draft::from_schema(schema).unwrap_or(draft),
+            resolver: Some(resolver),
+        })
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (Location, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.stack.pop()?;
+        let start = self.stack.len();
+        for (location, schema) in subschemas(&frame.location, frame.schema, frame.draft) {
+            let draft = Draft::from_schema(schema).unwrap_or(frame.draft);
+            let resolver = frame.resolver.as_ref().and_then(|resolver| {
+                resolver
+                    .in_subresource(draft.create_resource_ref(schema))
+                    .ok()
+            });
+            self.stack.push(Frame {
+                location,
+                schema,
+                draft,
+                resolver,
+            });
+        }
+        self.stack[start..].reverse();
+        if let Some(target) = self.reference_target(&frame) {
+            self.stack.push(target);
+        }
+        Some((frame.location, frame.schema))
+    }
+}
+
+/// Direct subschemas of `schema` with their locations, in document order.
+pub(crate) fn subschemas<'a>(
+    location: &Location,
+    schema: &'a Value,
+    draft: Draft,
+) -> Vec<(Location, &'a Value)> {
+    let Value::Object(object) = schema else {
+        return Vec::new();
+    };
+    let found: AHashSet<*const Value> = draft
+        .subresources_of(schema)
+        .map(|subschema| subschema as *const Value)
+        .collect();
+    let mut subschemas = Vec::with_capacity(found.len());
+    if found.is_empty() {
+        return subschemas;
+    }
+    let is_subschema = |value: &Value| found.contains(&(value as *const Value));
+    for (keyword, value) in object {
+        if is_subschema(value) {
+            subschemas.push((location.join(keyword), value));
+            continue;
+        }
+        match value {
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    if is_subschema(item) {
+                        subschemas.push((location.join(keyword).join(idx), item));
+                    }
+                }
+            }
+            Value::Object(map) => {
+                for (name, item) in map {
+                    if is_subschema(item) {
+                        subschemas.push((location.join(keyword).join(name), item));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    subschemas
+}
+
+/// Collect URIs of all documents that are `schema` itself or embedded into it.
+fn collect_documents(
+    schema: &Value,
+    draft: Draft,
+    resolver: &Resolver<'_>,
+    documents: &mut AHashSet<Uri<String>>,
+) {
+    documents.insert(document_of(&resolver.base_uri()));
+    for subschema in draft.subresources_of(schema) {
+        let draft = Draft::from_schema(subschema).unwrap_or(draft);
+        if let Ok(resolver) = resolver.in_subresource(draft.create_resource_ref(subschema)) {
+            collect_documents(subschema, draft, &resolver, documents);
+        }
+    }
+}
+
+pub(crate) fn document_of(uri: &Uri<String>) -> Uri<String> {
+    let mut document = uri.clone();
+    document.set_fragment(None);
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use referencing::{Registry, Resource};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::walk;
+    use crate::{paths::Location, Draft};
+
+    fn locations<'a>(walk: impl Iterator<Item = (Location, &'a Value)>) -> Vec<String> {
+        walk.map(|(location, _)| location.to_string()).collect()
+    }
+
+    #[test_case(&json!(true), Draft::Draft202012, &[""]; "boolean")]
+    #[test_case(
+        &json!({"enum": [{"type": "string"}], "const": {"properties": {"a": {}}}, "examples": [{}]}),
+        Draft::Draft202012,
+        &[""];
+        "non-schema values"
+    )]
+    #[test_case(
+        &json!({"properties": {"items": {"minimum": 1}}, "required": ["items"]}),
+        Draft::Draft202012,
+        &["", "/properties/items"];
+        "property names"
+    )]
+    #[test_case(
+        &json!({"allOf": [{}, {"not": {}}], "$defs": {"a": {"items": {}}}}),
+        Draft::Draft202012,
+        &["", "/$defs/a", "/$defs/a/items", "/allOf/0", "/allOf/1", "/allOf/1/not"];
+        "nested"
+    )]
+    #[test_case(
+        &json!({"prefixItems": [{}, {}], "items": {}}),
+        Draft::Draft202012,
+        &["", "/items", "/prefixItems/0", "/prefixItems/1"];
+        "prefix items"
+    )]
+    #[test_case(
+        &json!({"items": [{}, {}], "additionalItems": {}}),
+        Draft::Draft7,
+        &["", "/additionalItems", "/items/0", "/items/1"];
+        "items array"
+    )]
+    #[test_case(
+        &json!({"dependencies": {"a": ["b"], "c": {"minProperties": 1}}}),
+        Draft::Draft7,
+        &["", "/dependencies/c"];
+        "dependencies"
+    )]
+    #[test_case(
+        &json!({"additionalProperties": false, "properties": {"a": {"$schema": "http://json-schema.org/draft-04/schema#", "items": [{}]}}}),
+        Draft::Draft4,
+        &["", "/properties/a", "/properties/a/items/0"];
+        "boolean is not a draft 4 schema"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"$schema": "http://json-schema.org/draft-07/schema#", "items": [{}]}}}),
+        Draft::Draft202012,
+        &["", "/properties/a", "/properties/a/items/0"];
+        "draft switch"
+    )]
+    fn subschemas(schema: &Value, draft: Draft, expected: &[&str]) {
+        assert_eq!(locations(walk(schema, draft)), expected);
+    }
+
+    #[test]
+    fn values() {
+        let schema = json!({"properties": {"a": {"type": "string"}}});
+        let subschemas: Vec<_> = walk(&schema, Draft::Draft202012).collect();
+        assert_eq!(subschemas[1].1, &json!({"type": "string"}));
+        assert_eq!(subschemas[1].0.resolve(&schema), Some(subschemas[1].1));
+    }
+
+    #[test]
+    fn references_are_not_followed_by_default() {
+        let schema = json!({"$ref": "https://example.com/other"});
+        assert_eq!(locations(walk(&schema, Draft::Draft202012)), [""]);
+    }
+
+    #[test]
+    fn follow_references() {
+        let registry = Registry::try_from_resources(
+            [
+                (
+                    "https://example.com/a",
+                    Resource::from_contents(json!({
+                        "properties": {"b": {"$ref": "b"}, "self": {"$ref": "#"}},
+                        "$defs": {"x": {"type": "integer"}}
+                    }))
+                    .expect("Valid resource"),
+                ),
+                (
+                    "https://example.com/b",
+                    Resource::from_contents(json!({"items": {"$ref": "a#/$defs/x"}}))
+                        .expect("Valid resource"),
+                ),
+            ]
+            .into_iter(),
+        )
+        .expect("Valid registry");
+        let schema = json!({
+            "$id": "https://example.com/root",
+            "properties": {
+                "a": {"$ref": "a"},
+                "local": {"$ref": "#/$defs/local"},
+                "embedded": {"$ref": "embedded"},
+                "missing": {"$ref": "urn:example:missing"}
+            },
+            "$defs": {
+                "local": {"type": "string"},
+                "embedded": {"$id": "embedded", "type": "null"}
+            }
+        });
+        assert_eq!(
+            locations(walk(&schema, Draft::Draft202012).follow_references(&registry)),
+            [
+                "",
+                "/$defs/embedded",
+                "/$defs/local",
+                "/properties/a",
+                "/properties/a/$ref",
+                "/properties/a/$ref/$defs/x",
+                "/properties/a/$ref/properties/b",
+                "/properties/a/$ref/properties/b/$ref",
+                "/properties/a/$ref/properties/b/$ref/items",
+                "/properties/a/$ref/properties/b/$ref/items/$ref",
+                "/properties/a/$ref/properties/self",
+                "/properties/embedded",
+                "/properties/local",
+                "/properties/missing",
+            ]
+        );
+    }
+}
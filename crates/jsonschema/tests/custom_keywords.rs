@@ -0,0 +1,162 @@
+use jsonschema::{
+    output::PartialApplication,
+    paths::{LazyLocation, Location},
+    Keyword, ValidationError, Validator,
+};
+use serde_json::{json, Map, Value};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use test_case::test_case;
+
+/// Applies another schema to the instance, like `allOf` with a single subschema, and evaluates
+/// the properties and items that schema describes.
+struct Extends {
+    validator: Validator,
+    properties: Vec<String>,
+    prefix_items: usize,
+}
+
+impl Keyword for Extends {
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if self.is_valid(instance) {
+            Ok(())
+        } else {
+            Err(ValidationError::custom(
+                Location::new(),
+                location.into(),
+                instance,
+                "Not valid under the extended schema",
+            ))
+        }
+    }
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.validator.is_valid(instance)
+    }
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        let mut result = PartialApplication::valid_empty();
+        match self.validate(instance, location) {
+            Ok(()) => {
+                let names: Vec<_> = self.evaluated_properties(instance);
+                result.annotate(Value::from(names).into());
+            }
+            Err(error) => result.mark_errored(error.into()),
+        }
+        result
+    }
+    fn evaluated_properties<'i>(&self, instance: &'i Value) -> Vec<&'i str> {
+        if !self.is_valid(instance) {
+            return Vec::new();
+        }
+        instance
+            .as_object()
+            .into_iter()
+            .flat_map(Map::keys)
+            .filter(|key| self.properties.contains(key))
+            .map(String::as_str)
+            .collect()
+    }
+    fn evaluated_items(&self, instance: &Value) -> Vec<usize> {
+        match instance.as_array() {
+            Some(items) if self.is_valid(instance) => {
+                (0..items.len().min(self.prefix_items)).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn validator(schema: &Value) -> Validator {
+    jsonschema::options()
+        .with_keyword("x-extends", |_, value: &Value, _| {
+            let validator = jsonschema::validator_for(value)?;
+            let properties = value
+                .get("properties")
+                .and_then(Value::as_object)
+                .map(|properties| properties.keys().cloned().collect())
+                .unwrap_or_default();
+            let prefix_items = value
+                .get("prefixItems")
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len);
+            Ok(Box::new(Extends {
+                validator,
+                properties,
+                prefix_items,
+            }) as Box<dyn Keyword>)
+        })
+        .build(schema)
+        .expect("Valid schema")
+}
+
+#[test_case(&json!({"id": 1, "name": "a"}), true; "extended property")]
+#[test_case(&json!({"id": 1, "other": 2}), false; "unevaluated property")]
+#[test_case(&json!({"id": 1, "name": 2}), false; "invalid extended property")]
+fn unevaluated_properties(instance: &Value, expected: bool) {
+    let schema = json!({
+        "x-extends": {"properties": {"name": {"type": "string"}}},
+        "properties": {"id": {"type": "integer"}},
+        "unevaluatedProperties": false
+    });
+    assert_eq!(validator(&schema).is_valid(instance), expected);
+    let schema = json!({
+        "allOf": [{"x-extends": {"properties": {"name": {"type": "string"}}}}],
+        "properties": {"id": {"type": "integer"}},
+        "unevaluatedProperties": false
+    });
+    assert_eq!(validator(&schema).is_valid(instance), expected);
+}
+
+#[test_case(&json!(["a"]), true; "extended item")]
+#[test_case(&json!(["a", 1]), false; "unevaluated item")]
+#[test_case(&json!([1]), false; "invalid extended item")]
+fn unevaluated_items(instance: &Value, expected: bool) {
+    let schema = json!({
+        "x-extends": {"prefixItems": [{"type": "string"}]},
+        "unevaluatedItems": false
+    });
+    assert_eq!(validator(&schema).is_valid(instance), expected);
+}
+
+#[test]
+fn annotations() {
+    let schema = json!({
+        "x-extends": {"properties": {"name": {"type": "string"}}},
+        "unevaluatedProperties": false
+    });
+    let validator = validator(&schema);
+    let output =
+        serde_json::to_value(validator.apply(&json!({"name": "a"})).basic()).expect("Serializable");
+    assert_eq!(output["valid"], json!(true));
+    let unit = output["annotations"]
+        .as_array()
+        .expect("Annotations")
+        .iter()
+        .find(|unit| unit["keywordLocation"] == "/x-extends")
+        .expect("Custom keyword annotations");
+    assert_eq!(unit["annotations"], json!(["name"]));
+}
+
+#[test_case(&json!({"x-extends": {}, "unevaluatedProperties": false, "unevaluatedItems": false}); "sibling")]
+#[test_case(&json!({"allOf": [{"x-extends": {}}], "unevaluatedProperties": false}); "in subschema")]
+fn factory_runs_once(schema: &Value) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    jsonschema::options()
+        .with_keyword("x-extends", move |_, value: &Value, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(Extends {
+                validator: jsonschema::validator_for(value)?,
+                properties: Vec::new(),
+                prefix_items: 0,
+            }) as Box<dyn Keyword>)
+        })
+        .build(schema)
+        .expect("Valid schema");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
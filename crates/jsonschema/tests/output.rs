@@ -1,4 +1,5 @@
-use serde_json::json;
+use jsonschema::{output::Evaluated, paths::Location, BasicOutput};
+use serde_json::{json, Value};
 use test_case::test_case;
 
 #[test_case{
@@ -360,13 +361,7 @@ use test_case::test_case;
     &json!([]),
     &json!({
         "valid": true,
-        "annotations": [
-            {
-                "keywordLocation": "/items",
-                "instanceLocation": "",
-                "annotations": false
-            },
-        ]
+        "annotations": []
     }); "valid items empty array"
 }]
 #[test_case{
@@ -579,6 +574,31 @@ use test_case::test_case;
         ]
     }); "valid propertyNames"
 }]
+#[test_case{
+    &json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "dependencies": {
+            "a": ["b"],
+            "c": {"properties": {"c": {"type": "string"}}}
+        }
+    }),
+    &json!({"a": 1, "c": 1}),
+    &json!({
+        "valid": false,
+        "errors": [
+            {
+                "keywordLocation": "/dependencies/a",
+                "instanceLocation": "",
+                "error": "\"b\" is a required property when \"a\" is present"
+            },
+            {
+                "keywordLocation": "/dependencies/c/properties/c/type",
+                "instanceLocation": "/c",
+                "error": "1 is not of type \"string\""
+            }
+        ]
+    }); "invalid dependencies"
+}]
 fn test_basic_output(
     schema: &serde_json::Value,
     instance: &serde_json::Value,
@@ -675,7 +695,7 @@ fn test_basic_output(
             {
                 "keywordLocation": "/additionalProperties",
                 "instanceLocation": "",
-                "error": "False schema does not allow \"somename\""
+                "error": "Additional properties are not allowed ('name' was unexpected)"
             },
         ]
     }); "invalid AdditionalPropertiesFalseValidator"
@@ -687,7 +707,13 @@ fn test_basic_output(
     &json!({}),
     &json!({
         "valid": true,
-        "annotations": []
+        "annotations": [
+            {
+                "keywordLocation": "/additionalProperties",
+                "instanceLocation": "",
+                "annotations": []
+            }
+        ]
     }); "valid AdditionalPropertiesFalseValidator"
 }]
 #[test_case{
@@ -703,10 +729,20 @@ fn test_basic_output(
     &json!({
         "valid": true,
         "annotations": [
+            {
+                "keywordLocation": "/additionalProperties",
+                "instanceLocation": "",
+                "annotations": []
+            },
             {
                 "keywordLocation": "/properties/name",
                 "instanceLocation": "/name",
                 "annotations": {"prop": "annotation"}
+            },
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["name"]
             }
         ]
     }); "valid AdditionalPropertiesNotEmptyFalseValidator"
@@ -761,6 +797,11 @@ fn test_basic_output(
                 "keywordLocation": "/additionalProperties",
                 "instanceLocation": "/otherprop",
                 "annotations": {"other": "annotation"}
+            },
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["name"]
             }
         ]
     }); "valid AdditionalPropertiesNotEmptyValidator"
@@ -897,6 +938,16 @@ fn test_basic_output(
                 "keywordLocation": "/additionalProperties",
                 "instanceLocation": "",
                 "annotations": ["otherprop"]
+            },
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["name"]
+            },
+            {
+                "keywordLocation": "/patternProperties",
+                "instanceLocation": "",
+                "annotations": []
             }
         ]
     }); "valid AdditionalPropertiesWithPatternsNotEmptyValidator"
@@ -918,12 +969,27 @@ fn test_basic_output(
     &json!({
         "valid": true,
         "annotations": [
+            {
+                "keywordLocation": "/additionalProperties",
+                "instanceLocation": "",
+                "annotations": []
+            },
             {
                 "keywordLocation": "/properties/name",
                 "instanceLocation": "/name",
                 "annotations": {
                     "prop": "annotation"
                 }
+            },
+            {
+                "keywordLocation": "/properties",
+                "instanceLocation": "",
+                "annotations": ["name"]
+            },
+            {
+                "keywordLocation": "/patternProperties",
+                "instanceLocation": "",
+                "annotations": ["stringProp1"]
             }
         ]
     }); "valid AdditionalPropertiesWithPatternsNotEmptyFalseValidator"
@@ -967,6 +1033,11 @@ fn test_basic_output(
     &json!({
         "valid": true,
         "annotations": [
+            {
+                "keywordLocation": "/additionalProperties",
+                "instanceLocation": "",
+                "annotations": []
+            },
             {
                 "keywordLocation": "/patternProperties/stringProp(\\d+)",
                 "instanceLocation": "/stringProp1",
@@ -1017,3 +1088,315 @@ fn test_additional_properties_basic_output(
         panic!("\nExpected:\n{}\n\nGot:\n{}\n", expected_str, actual_str);
     }
 }
+
+fn evaluated(schema: &serde_json::Value, instance: &serde_json::Value) -> Vec<(String, Evaluated)> {
+    let validator = jsonschema::validator_for(schema).expect("Invalid schema");
+    let BasicOutput::Valid(units) = validator.apply(instance).basic() else {
+        panic!("Should be valid");
+    };
+    let mut evaluated: Vec<_> = units
+        .iter()
+        .filter_map(|unit| {
+            Some((
+                unit.keyword_location().to_string(),
+                unit.evaluated()?.clone(),
+            ))
+        })
+        .collect();
+    evaluated.sort_by(|(left, _), (right, _)| left.cmp(right));
+    evaluated
+}
+
+fn names(names: &[&str]) -> Evaluated {
+    Evaluated::Properties(names.iter().map(ToString::to_string).collect())
+}
+
+#[test_case(
+    &json!({
+        "properties": {"name": {}, "id": {}},
+        "patternProperties": {"^x-": {}, "-id$": {}},
+        "additionalProperties": {"type": "integer"}
+    }),
+    &json!({"name": "a", "x-id": "b", "x-tag": "c", "count": 1, "size": 2}),
+    &[
+        ("/additionalProperties", names(&["count", "size"])),
+        ("/patternProperties", names(&["x-id", "x-tag"])),
+        ("/properties", names(&["name"])),
+    ];
+    "mixed properties"
+)]
+#[test_case(
+    &json!({
+        "properties": {"name": {}},
+        "patternProperties": {"^x-": {}},
+        "additionalProperties": false
+    }),
+    &json!({"name": "a", "x-id": "b"}),
+    &[
+        ("/additionalProperties", names(&[])),
+        ("/patternProperties", names(&["x-id"])),
+        ("/properties", names(&["name"])),
+    ];
+    "no additional properties"
+)]
+#[test_case(
+    &json!({"properties": {"name": {}}, "patternProperties": {"^x-": {}, "-id$": {}}}),
+    &json!({"name": "a", "x-id": "b", "other": "c"}),
+    &[
+        ("/patternProperties", names(&["x-id"])),
+        ("/properties", names(&["name"])),
+    ];
+    "several patterns"
+)]
+#[test_case(
+    &json!({"prefixItems": [{}, {}], "items": {"type": "integer"}}),
+    &json!([1, 2, 3]),
+    &[("/items", Evaluated::AllItems), ("/prefixItems", Evaluated::LargestIndex(1))];
+    "prefix items"
+)]
+#[test_case(
+    &json!({"prefixItems": [{}, {}], "items": {"type": "integer"}}),
+    &json!([1]),
+    &[("/prefixItems", Evaluated::AllItems)];
+    "items not applied"
+)]
+#[test_case(
+    &json!({"$schema": "https://json-schema.org/draft/2019-09/schema", "items": [{}, {}]}),
+    &json!([1, 2, 3]),
+    &[("/items", Evaluated::LargestIndex(1))];
+    "items array"
+)]
+#[test_case(
+    &json!({"contains": {"type": "integer"}}),
+    &json!(["a", 1, 2]),
+    &[("/contains", Evaluated::Indices(vec![1, 2]))];
+    "contains"
+)]
+fn evaluated_annotations(
+    schema: &serde_json::Value,
+    instance: &serde_json::Value,
+    expected: &[(&str, Evaluated)],
+) {
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|(location, evaluated)| (location.to_string(), evaluated.clone()))
+        .collect();
+    assert_eq!(evaluated(schema, instance), expected);
+}
+
+#[test]
+fn evaluated_annotations_absolute_location() {
+    let schema = json!({
+        "$id": "https://example.com/schema.json",
+        "properties": {"name": {}},
+        "patternProperties": {"^x-": {}},
+        "additionalProperties": false
+    });
+    let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    let BasicOutput::Valid(units) = validator.apply(&json!({"name": 1})).basic() else {
+        panic!("Should be valid");
+    };
+    let mut locations: Vec<_> = units
+        .iter()
+        .map(|unit| {
+            unit.absolute_keyword_location()
+                .expect("Schema has an $id")
+                .to_string()
+        })
+        .collect();
+    locations.sort();
+    assert_eq!(
+        locations,
+        [
+            "https://example.com/schema.json#/additionalProperties",
+            "https://example.com/schema.json#/patternProperties",
+            "https://example.com/schema.json#/properties",
+        ]
+    );
+}
+
+#[test]
+fn absolute_keyword_location_through_ref() {
+    let schema = json!({
+        "$id": "https://example.com/schema.json",
+        "properties": {"a": {"$ref": "#/$defs/positive"}},
+        "$defs": {"positive": {"minimum": 0, "maximum": 10}}
+    });
+    let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    let output = validator.apply(&json!({"a": -1})).basic();
+    let serialized = serde_json::to_value(&output).expect("Should serialize");
+    assert_eq!(
+        serialized["errors"][0]["keywordLocation"],
+        "/properties/a/$ref/minimum"
+    );
+    assert_eq!(
+        serialized["errors"][0]["absoluteKeywordLocation"],
+        "https://example.com/schema.json#/$defs/positive/minimum"
+    );
+}
+
+#[test_case(&json!({"a": 1}), &json!({"valid": true, "annotations": [{"keywordLocation": "/properties", "instanceLocation": "", "absoluteKeywordLocation": "https://example.com/schema.json#/properties", "annotations": ["a"]}]}); "true")]
+#[test_case(&json!({"b": 1}), &json!({"valid": false, "errors": [{"keywordLocation": "/properties/b/anyOf/0/$ref", "instanceLocation": "/b", "absoluteKeywordLocation": "https://example.com/schema.json#/$defs/no", "error": "False schema does not allow 1"}, {"keywordLocation": "/unevaluatedProperties", "instanceLocation": "", "absoluteKeywordLocation": "https://example.com/schema.json#/unevaluatedProperties", "error": "Unevaluated properties are not allowed ('b' was unexpected)"}]}); "false")]
+fn boolean_reference_target(instance: &serde_json::Value, expected: &serde_json::Value) {
+    let schema = json!({
+        "$id": "https://example.com/schema.json",
+        "properties": {
+            "a": {"allOf": [{"$ref": "#/$defs/yes"}]},
+            "b": {"anyOf": [{"$ref": "#/$defs/no"}]}
+        },
+        "unevaluatedProperties": false,
+        "$defs": {"yes": true, "no": false}
+    });
+    for lazy in [false, true] {
+        let validator = jsonschema::options()
+            .with_lazy_references(lazy)
+            .build(&schema)
+            .expect("Invalid schema");
+        let output = validator.apply(instance).basic();
+        let serialized = serde_json::to_value(&output).expect("Should serialize");
+        assert_eq!(&serialized, expected);
+    }
+}
+
+#[test]
+fn errors_by_instance_location() {
+    let schema = json!({
+        "properties": {
+            "a": {"type": "string", "minLength": 2},
+            "b": {"items": {"minimum": 0}}
+        },
+        "required": ["c"]
+    });
+    let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    let instance = json!({"a": 1, "b": [1, -1, -2]});
+    let mut output = validator.apply(&instance).basic();
+    // Combining outputs of the same schema does not duplicate errors
+    output += validator.apply(&instance).basic();
+    let groups: Vec<_> = output
+        .by_instance_location()
+        .into_iter()
+        .map(|(location, units)| {
+            let keywords: Vec<_> = units
+                .iter()
+                .map(|unit| unit.keyword_location().to_string())
+                .collect();
+            (location.to_string(), keywords)
+        })
+        .collect();
+    assert_eq!(
+        groups,
+        [
+            (String::new(), vec!["/required".to_string()]),
+            ("/a".to_string(), vec!["/properties/a/type".to_string()]),
+            (
+                "/b/1".to_string(),
+                vec!["/properties/b/items/minimum".to_string()]
+            ),
+            (
+                "/b/2".to_string(),
+                vec!["/properties/b/items/minimum".to_string()]
+            ),
+        ]
+    );
+    assert_eq!(output.errors_for("/b/1").len(), 1);
+    assert!(output.errors_for("/b").is_empty());
+    assert!(output.errors_for("/b/0").is_empty());
+}
+
+#[test_case(&json!({}), None; "without base URI")]
+#[test_case(&json!({"$id": "https://example.com/schema.json"}), Some("https://example.com/schema.json#/$defs/name/type"); "with base URI")]
+fn errors_through_references(base: &Value, expected: Option<&str>) {
+    let mut schema = json!({
+        "allOf": [{"$ref": "#/$defs/name"}, {"$ref": "#/$defs/name"}],
+        "$defs": {"name": {"type": "string"}}
+    });
+    schema
+        .as_object_mut()
+        .expect("Is an object")
+        .extend(base.as_object().expect("Is an object").clone());
+    let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    let output = validator.apply(&json!(1)).basic();
+    // Both references lead to the same subschema, so its error is reported once
+    let errors = output.errors_for("");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0]
+            .absolute_keyword_location()
+            .map(|uri| uri.as_str()),
+        expected
+    );
+    assert_eq!(output.by_instance_location()[&Location::new()].len(), 1);
+    let serialized = serde_json::to_value(&output).expect("Should serialize");
+    assert_eq!(
+        serialized["errors"][0].get("absoluteKeywordLocation"),
+        expected.map(|uri| json!(uri)).as_ref()
+    );
+}
+
+#[test]
+fn errors_by_instance_location_valid() {
+    let validator = jsonschema::validator_for(&json!({"type": "string"})).expect("Invalid schema");
+    let output = validator.apply(&json!("a")).basic();
+    assert!(output.by_instance_location().is_empty());
+    assert!(output.errors_for("").is_empty());
+}
+
+#[test]
+fn errors_and_annotations() {
+    let schema = json!({"properties": {"a": {"type": "integer", "title": "A"}}});
+    let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    let valid = validator.apply(&json!({"a": 1})).basic();
+    assert_eq!(valid.errors().count(), 0);
+    assert!(valid
+        .annotations()
+        .any(|unit| unit.keyword_location().as_str() == "/properties/a"));
+    let invalid = validator.apply(&json!({"a": "1"})).basic();
+    assert_eq!(invalid.annotations().count(), 0);
+    let errors: Vec<_> = invalid
+        .errors()
+        .map(|unit| {
+            (
+                unit.keyword_location().as_str(),
+                unit.instance_location().as_str(),
+                unit.error_description().as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        [(
+            "/properties/a/type",
+            "/a",
+            r#""1" is not of type "integer""#
+        )]
+    );
+}
+
+#[test]
+fn merge_outputs() {
+    let validator = jsonschema::validator_for(&json!({"type": "integer"})).expect("Invalid schema");
+    let documents = [json!(1), json!("a"), json!(2), json!(null)];
+    let outputs: Vec<_> = documents
+        .iter()
+        .map(|document| validator.apply(document).basic())
+        .collect();
+    let valid = outputs[0].clone().merge(outputs[2].clone());
+    assert!(valid.is_valid());
+    let merged = outputs[0].clone().merge(outputs[1].clone());
+    assert!(!merged.is_valid());
+    assert_eq!(merged.errors().count(), 1);
+    let sum: BasicOutput = outputs.into_iter().sum();
+    assert_eq!(sum.errors().count(), 2);
+    let result: Result<_, _> = sum.into();
+    let errors = result.expect_err("Invalid documents");
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn output_into_result() {
+    let validator = jsonschema::validator_for(&json!({"title": "A"})).expect("Invalid schema");
+    let result: Result<_, _> = validator.apply(&json!(1)).basic().into();
+    let annotations = result.expect("Valid document");
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(annotations[0].value().as_ref(), &json!({"title": "A"}));
+}
@@ -137,6 +137,28 @@ mod tests {
                 pretty_json(&test.data),
             );
         }
+        let mut errors = Vec::new();
+        validator.validate_into(&test.data, &mut errors);
+        assert_eq!(
+            errors.iter().map(describe_error).collect::<Vec<_>>(),
+            validator
+                .iter_errors(&test.data)
+                .map(|error| describe_error(&error))
+                .collect::<Vec<_>>(),
+            "`validate_into` and `iter_errors` disagree:\nCase: {}\nTest: {}\nSchema: {}\nInstance: {}",
+            test.case,
+            test.description,
+            pretty_json(&test.schema),
+            pretty_json(&test.data),
+        );
+    }
+
+    fn describe_error(error: &jsonschema::ValidationError) -> (String, String, String) {
+        (
+            error.instance_path.as_str().to_string(),
+            error.schema_path.as_str().to_string(),
+            error.to_string(),
+        )
     }
 
     fn pretty_json(v: &serde_json::Value) -> String {
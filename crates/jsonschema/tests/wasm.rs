@@ -0,0 +1,40 @@
+//! Runs in a browser or Node.js via `wasm-bindgen-test-runner`.
+#![cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use jsonschema::error::ValidationErrorKind;
+use serde_json::json;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn validate() {
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "name": {"type": "string", "minLength": 1},
+            "email": {"format": "email"}
+        },
+        "required": ["name"]
+    });
+    let validator = jsonschema::options()
+        .should_validate_formats(true)
+        .build(&schema)
+        .expect("Invalid schema");
+    assert!(validator.is_valid(&json!({"name": "Jane", "email": "jane@example.com"})));
+    let instance = json!({"name": "", "email": "jane"});
+    let errors: Vec<_> = validator.iter_errors(&instance).collect();
+    assert_eq!(errors.len(), 2);
+}
+
+#[wasm_bindgen_test]
+fn invalid_schema() {
+    // Checked against the embedded meta-schema
+    let schema = json!({"$schema": "http://json-schema.org/draft-07/schema#", "type": 42});
+    assert!(jsonschema::validator_for(&schema).is_err());
+}
+
+#[wasm_bindgen_test]
+fn remote_reference() {
+    let schema = json!({"$ref": "https://example.com/schema.json"});
+    let error = jsonschema::validator_for(&schema).expect_err("Should fail");
+    assert!(matches!(error.kind, ValidationErrorKind::Referencing(_)));
+}